@@ -0,0 +1,13 @@
+//! Generates the gRPC service in `proto/bolo.proto` for the `grpc` feature.
+
+fn main() {
+    println!("cargo::rerun-if-changed=proto/bolo.proto");
+    #[cfg(feature = "grpc")]
+    {
+        let mut config = tonic_prost_build::Config::new();
+        config.protoc_executable(protoc_bin_vendored::protoc_bin_path().expect("vendored protoc"));
+        tonic_prost_build::configure()
+            .compile_with_config(config, &["proto/bolo.proto"], &["proto"])
+            .expect("compile proto/bolo.proto");
+    }
+}
@@ -0,0 +1,335 @@
+use crate::api::tree_sitter::{ASTNode, Span, Syntax};
+use crate::crates::CrateEdge;
+use crate::modules;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashSet;
+use std::fmt;
+
+/// The result envelope: per-file nodes plus non-fatal issues found on the way.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Analysis {
+    pub files: Vec<Vec<Syntax<'static>>>,
+    #[serde(default)]
+    pub warnings: Vec<Warning>,
+    /// Edges from Rust crates to their build scripts and proc-macro crates.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub crates: Vec<CrateEdge>,
+    /// Set when detail was dropped to fit `--max-output-bytes`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub truncated: Option<Truncated>,
+    /// Top-level keys from another bolo version that this one does not
+    /// know, written back out unchanged.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// What was left out of an [`Analysis`] to keep it under a size budget.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Truncated {
+    pub max_bytes: usize,
+    /// Each kind of detail dropped, in the order it went.
+    pub dropped: Vec<Dropped>,
+    /// Files cut from the end of `files` once nothing else was left to drop.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub files_omitted: usize,
+}
+
+/// Detail a size budget drops, least useful first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub enum Dropped {
+    /// String literal nodes (`--strings`)
+    Literals,
+    /// Call nodes
+    Calls,
+    /// `metadata.metrics` and `metadata.index`
+    Metrics,
+    /// Whole files, last first
+    Files,
+}
+
+impl fmt::Display for Dropped {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Dropped::Literals => "literals",
+            Dropped::Calls => "calls",
+            Dropped::Metrics => "metrics",
+            Dropped::Files => "files",
+        })
+    }
+}
+
+fn is_zero(n: &usize) -> bool {
+    *n == 0
+}
+
+/// A data-quality issue that did not stop the run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Warning {
+    pub kind: WarningKind,
+    pub file: String,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<Span>,
+    /// Fields a warning from another bolo version has that this one does
+    /// not know; written back out unchanged.
+    #[serde(flatten)]
+    pub unknown: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub enum WarningKind {
+    /// A `mod foo;` whose file is not among the analyzed files.
+    UnresolvedImport,
+    /// Invalid UTF-8 was replaced with U+FFFD before parsing.
+    LossyDecode,
+    /// The file's path is not valid UTF-8; U+FFFD stands in for the bad
+    /// bytes in its File path, which may then match another file's.
+    LossyPath,
+    /// The file was not analyzed (e.g. over the size limit).
+    SkippedFile,
+    /// The grammar recovered from a syntax error; nodes near it may be missing.
+    GrammarError,
+    /// A kind from another bolo version, kept as read.
+    #[serde(untagged)]
+    Unknown(Value),
+}
+
+impl Warning {
+    pub fn new(kind: WarningKind, file: &str, message: impl Into<String>) -> Self {
+        Warning {
+            kind,
+            file: file.to_string(),
+            message: message.into(),
+            span: None,
+            unknown: Map::new(),
+        }
+    }
+}
+
+/// Warn about Rust `mod foo;` declarations whose file was not analyzed
+/// (see [`modules::declared`] for where one is looked for).
+pub fn unresolved_modules(files: &[Vec<Syntax>]) -> Vec<Warning> {
+    let known: HashSet<String> = files
+        .iter()
+        .filter_map(|n| file_path(n).map(String::from))
+        .collect();
+    files
+        .iter()
+        .flat_map(|nodes| unresolved_in(nodes, &known))
+        .collect()
+}
+
+/// [`unresolved_modules`] for one file's nodes, with `known` the paths of
+/// every file in the tree.
+pub fn unresolved_in(nodes: &[Syntax], known: &HashSet<String>) -> Vec<Warning> {
+    let mut out = Vec::new();
+    let Some(path) = file_path(nodes) else {
+        return out;
+    };
+    modules(nodes, path, &mut |path, name, attr, span| {
+        let candidates = modules::declared(path, name, attr);
+        if !candidates.iter().any(|c| known.contains(c)) {
+            out.push(Warning {
+                span: Some(span),
+                ..Warning::new(
+                    WarningKind::UnresolvedImport,
+                    path,
+                    format!(
+                        "module `{name}` not found (looked for {})",
+                        candidates.join(", ")
+                    ),
+                )
+            });
+        }
+    });
+    out
+}
+
+fn file_path<'a>(nodes: &'a [Syntax]) -> Option<&'a str> {
+    match &nodes.first()?.node {
+        ASTNode::File(f) => Some(&f.path),
+        _ => None,
+    }
+}
+
+/// Each `mod foo;` under `nodes` with the path it is declared from (see
+/// [`modules::nested`] for those inside inline modules).
+fn modules(nodes: &[Syntax], path: &str, f: &mut impl FnMut(&str, &str, Option<&str>, Span)) {
+    for s in nodes {
+        match &s.node {
+            ASTNode::Module(m) if m.inline => {
+                modules(&s.contains, &modules::nested(path, &m.name), f);
+            }
+            ASTNode::Module(m) => f(path, &m.name, m.path.as_deref(), s.metadata.span),
+            _ => modules(&s.contains, path, f),
+        }
+    }
+}
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::tree_sitter::analyze;
+    use crate::api::tree_sitter::rs::Rust;
+
+    #[test]
+    fn modules_resolved_from_lib_and_nested() {
+        let files = vec![
+            analyze(&Rust, "src/lib.rs", "mod api;\nmod cli;\n"),
+            analyze(&Rust, "src/api/mod.rs", "mod fs;\n"),
+            analyze(&Rust, "src/api/fs.rs", ""),
+            analyze(&Rust, "src/cli.rs", "mod args;\n"),
+            analyze(&Rust, "src/cli/args.rs", ""),
+        ];
+        assert!(unresolved_modules(&files).is_empty());
+    }
+
+    #[test]
+    fn missing_module_warns_with_span() {
+        let files = vec![analyze(&Rust, "src/lib.rs", "\nmod gone;\n")];
+        let w = unresolved_modules(&files);
+        assert_eq!(w.len(), 1);
+        assert_eq!(w[0].kind, WarningKind::UnresolvedImport);
+        assert_eq!(w[0].file, "src/lib.rs");
+        assert!(w[0].message.contains("src/gone.rs"));
+        assert_eq!(w[0].span.unwrap().start_line, 2);
+    }
+
+    #[test]
+    fn path_attribute_relative_to_dir() {
+        let files = vec![
+            analyze(
+                &Rust,
+                "src/lib.rs",
+                "#[path = \"impl/unix.rs\"]\nmod sys;\n",
+            ),
+            analyze(&Rust, "src/impl/unix.rs", ""),
+        ];
+        assert!(unresolved_modules(&files).is_empty());
+    }
+
+    #[test]
+    fn envelope_roundtrip() {
+        let a = Analysis {
+            files: vec![analyze(&Rust, "a.rs", "fn f() {}\n")],
+            warnings: vec![Warning::new(
+                WarningKind::SkippedFile,
+                "big.rs",
+                "too large",
+            )],
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&a).unwrap();
+        assert!(json.contains("\"kind\":\"skipped-file\""));
+        let back: Analysis = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.files.len(), 1);
+        assert_eq!(back.warnings, a.warnings);
+    }
+
+    // ── Compatibility ──
+    //
+    // Analyses written by older releases must keep loading: fields added
+    // since are optional, and reading then writing an old analysis adds no
+    // keys it did not have.
+
+    /// The output of the first release: a bare array of files, before the
+    /// envelope and warnings, with no spans, `index`, `metrics`, signatures,
+    /// decorators, `public` or `module`.
+    const V0_1: &str = r#"[[
+        {"node":{"File":{"path":"a.py"}},"metadata":{"chars":29,"lines":5,"words":6,
+         "whitespaces":8,"newlines":4},"contains":[]},
+        {"node":{"Function":{"name":"f"}},"metadata":{"chars":14,"lines":2,"words":3,
+         "whitespaces":5,"newlines":1},"contains":[
+          {"node":{"Call":{"name":"g"}},"metadata":{"chars":3,"lines":1,"words":1,
+           "whitespaces":0,"newlines":0},"contains":[]}]},
+        {"node":{"Function":{"name":"g"}},"metadata":{"chars":14,"lines":2,"words":3,
+         "whitespaces":5,"newlines":1},"contains":[]}
+    ]]"#;
+
+    fn load(json: &str) -> Analysis {
+        crate::merge::input(serde_json::from_str(json).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn oldest_output_still_loads() {
+        let a = load(V0_1);
+        let nodes = &a.files[0];
+        let ASTNode::Function(f) = &nodes[1].node else {
+            panic!("expected fn");
+        };
+        assert!(!f.public && f.params.is_empty() && f.decorators.is_empty());
+        assert_eq!(nodes[1].metadata.span, Span::default());
+        assert_eq!(nodes[1].metadata.index, None);
+        assert!(nodes[1].metadata.metrics.is_empty());
+        assert!(nodes[1].metadata.unknown.is_none());
+        assert!(a.warnings.is_empty());
+
+        let graph = crate::graph::Graph::build(&a.files);
+        assert_eq!(graph.defs.len(), 2);
+        assert_eq!(graph.edges.len(), 1);
+    }
+
+    #[test]
+    fn old_output_rewritten_without_new_keys() {
+        let a = load(V0_1);
+        let json = serde_json::to_string(&a).unwrap();
+        for key in [
+            "index",
+            "metrics",
+            "params",
+            "decorators",
+            "public",
+            "module",
+            "crates",
+            "truncated",
+        ] {
+            assert!(!json.contains(&format!("\"{key}\"")), "{key} added: {json}");
+        }
+        let again: Analysis = serde_json::from_str(&json).unwrap();
+        assert_eq!(serde_json::to_string(&again).unwrap(), json);
+    }
+
+    #[test]
+    fn unknown_node_and_warning_kinds_round_trip() {
+        let meta = r#""metadata":{"chars":3,"lines":1,"words":1,"whitespaces":0,"newlines":0,
+            "span":{"start_byte":0,"end_byte":3,"start_line":1,"start_col":0,"end_line":1,"end_col":3}}"#;
+        let json = format!(
+            r#"{{"files":[[
+                {{"node":{{"File":{{"path":"a.rs"}}}},{meta},"contains":[]}},
+                {{"node":{{"Macro":{{"name":"m"}}}},{meta},"contains":[]}},
+                {{"node":"Blank",{meta},"contains":[]}}
+            ]],"warnings":[
+                {{"kind":"brand-new-kind","file":"a.rs","message":"m","severity":"low"}},
+                {{"kind":"skipped-file","file":"b.rs","message":"big","hint":"split it"}}
+            ]}}"#
+        );
+        let value: Value = serde_json::from_str(&json).unwrap();
+        let a = crate::merge::input(value.clone()).unwrap();
+        assert!(matches!(&a.files[0][1].node, ASTNode::Unknown(v) if v["Macro"]["name"] == "m"));
+        assert!(matches!(&a.files[0][2].node, ASTNode::Unknown(v) if v == "Blank"));
+        assert!(matches!(&a.warnings[0].kind, WarningKind::Unknown(k) if k == "brand-new-kind"));
+        assert_eq!(a.warnings[1].kind, WarningKind::SkippedFile);
+        assert_eq!(a.warnings[1].unknown["hint"], "split it");
+
+        let back = serde_json::to_value(&a).unwrap();
+        assert_eq!(back["files"], value["files"]);
+        assert_eq!(back["warnings"], value["warnings"]);
+    }
+
+    #[test]
+    fn unknown_fields_from_newer_writers_ignored() {
+        let json = V0_1
+            .replacen(r#""path":"a.py""#, r#""path":"a.py","owner":"x""#, 1)
+            .replacen(r#""chars":14,"#, r#""chars":14,"entropy":0.5,"#, 1);
+        let a: Analysis =
+            serde_json::from_str(&format!(r#"{{"files":{json},"schema":9}}"#)).unwrap();
+        assert_eq!(a.files[0].len(), 3);
+    }
+}
@@ -0,0 +1,332 @@
+use crate::analysis::Analysis;
+use crate::api::tree_sitter::{ASTNode, Syntax};
+use crate::crates::{BUILD_SCRIPT, EdgeKind, PROC_MACRO};
+use crate::hash::siphash;
+use crate::links::LINK;
+use serde_json::Value;
+use std::borrow::Cow;
+
+/// Environment variable holding the key names are hashed with.
+pub const KEY_VAR: &str = "BOLO_ANONYMIZE_KEY";
+
+/// Identifiers whose meaning comes from the language, not the codebase;
+/// kept so module layout (`mod.rs`, `__init__.py`) and receivers still read.
+const KEEP: &[&str] = &[
+    "self", "Self", "cls", "super", "crate", "mod", "lib", "main", "for", "dyn", "impl", "mut",
+    "const", "fn", "where", "as",
+];
+
+/// Hex digits kept from each hash: 48 bits, so collisions stay unlikely
+/// across millions of distinct names.
+const DIGITS: usize = 12;
+
+/// Replace every path, name and string literal in `analysis` with a keyed
+/// hash, leaving node kinds, nesting, spans and metrics as they were.
+///
+/// Identifiers are hashed one at a time wherever they appear (a call to
+/// `fs::walk`, a function `walk` and a file `fs.rs` share hashes), so
+/// calls still resolve to their definitions and paths keep their
+/// directory structure and extension. The same `key` gives the same hashes
+/// on every run, so anonymized analyses can still be diffed; without a
+/// secret key, common names can be recovered by hashing guesses. Links
+/// (which embed paths) are dropped and warning messages are cleared. Crate
+/// names, in crate edges and build-script or proc-macro metrics, are hashed
+/// as names.
+pub fn anonymize(analysis: &mut Analysis, key: &str) {
+    let h = Hasher::new(key);
+    for nodes in &mut analysis.files {
+        for s in nodes {
+            node(s, &h);
+        }
+    }
+    for w in &mut analysis.warnings {
+        w.file = h.path(&w.file);
+        w.message.clear();
+    }
+    for e in &mut analysis.crates {
+        e.from = h.names(&e.from);
+        e.to = match e.kind {
+            EdgeKind::BuildScript => h.path(&e.to),
+            EdgeKind::ProcMacro => h.names(&e.to),
+        };
+    }
+}
+
+/// Hash every string in `value`, a node of a kind this version does not
+/// know, since any of them may be a name.
+fn strings(value: &mut Value, h: &Hasher) {
+    match value {
+        Value::String(s) => *s = h.names(s),
+        Value::Array(items) => items.iter_mut().for_each(|v| strings(v, h)),
+        Value::Object(fields) => fields.values_mut().for_each(|v| strings(v, h)),
+        _ => {}
+    }
+}
+
+fn node(s: &mut Syntax, h: &Hasher) {
+    let names = |n: &mut Cow<str>| *n = Cow::Owned(h.names(n));
+    match &mut s.node {
+        ASTNode::File(f) => {
+            f.path = Cow::Owned(h.path(&f.path));
+            if let Some(m) = &mut f.module {
+                names(m);
+            }
+        }
+        ASTNode::Function(f) => {
+            names(&mut f.name);
+            for p in &mut f.params {
+                names(&mut p.name);
+                for text in [&mut p.annotation, &mut p.default].into_iter().flatten() {
+                    names(text);
+                }
+            }
+            if let Some(r) = &mut f.return_type {
+                names(r);
+            }
+            f.decorators.iter_mut().for_each(names);
+        }
+        ASTNode::Type(t) => {
+            names(&mut t.name);
+            t.decorators.iter_mut().for_each(names);
+        }
+        ASTNode::Field(f) => {
+            names(&mut f.name);
+            if let Some(a) = &mut f.annotation {
+                names(a);
+            }
+        }
+        ASTNode::Variant(v) => names(&mut v.name),
+        ASTNode::Call(c) => {
+            names(&mut c.name);
+            if let Some(via) = &mut c.spawned_by {
+                names(via);
+            }
+        }
+        ASTNode::UsesType(u) => names(&mut u.name),
+        ASTNode::Module(m) => {
+            names(&mut m.name);
+            for p in [&mut m.path, &mut m.file].into_iter().flatten() {
+                *p = Cow::Owned(h.path(p));
+            }
+        }
+        ASTNode::Import(i) => {
+            names(&mut i.module);
+            for item in i.items.iter_mut().chain(&mut i.alias) {
+                names(item);
+            }
+        }
+        ASTNode::DynamicImport(d) => {
+            if let Some(m) = &mut d.module {
+                names(m);
+            }
+        }
+        ASTNode::Endpoint(e) => {
+            names(&mut e.route);
+            names(&mut e.handler);
+        }
+        ASTNode::Fixture(f) => names(&mut f.name),
+        ASTNode::UsesFixture(u) => names(&mut u.name),
+        ASTNode::Exception(e) => names(&mut e.name),
+        ASTNode::Literal(l) => l.value = Cow::Owned(h.hash(&l.value)),
+        ASTNode::Attribute(a) => {
+            names(&mut a.name);
+            for arg in &mut a.args {
+                names(arg);
+            }
+        }
+        ASTNode::Unknown(v) => strings(v, h),
+        ASTNode::Unsafe(_) | ASTNode::Comment => {}
+    }
+    s.metadata.metrics.remove(LINK);
+    for key in [BUILD_SCRIPT, PROC_MACRO] {
+        if let Some(Value::String(name)) = s.metadata.metrics.get_mut(key) {
+            *name = h.names(name);
+        }
+    }
+    for child in &mut s.contains {
+        node(child, h);
+    }
+}
+
+/// SipHash-2-4 under a key derived from the user's, so hashes can't be
+/// reproduced without it.
+struct Hasher {
+    key: (u64, u64),
+}
+
+impl Hasher {
+    fn new(key: &str) -> Self {
+        let k0 = siphash((0, 0), key.as_bytes());
+        let k1 = siphash((k0, 1), key.as_bytes());
+        Hasher { key: (k0, k1) }
+    }
+
+    /// `s` as a fixed-width name starting with a letter, so it stays a
+    /// valid identifier.
+    fn hash(&self, s: &str) -> String {
+        let h = format!("{:016x}", siphash(self.key, s.as_bytes()));
+        format!("x{}", &h[..DIGITS])
+    }
+
+    /// Hash each identifier in `s`, keeping separators (`.`, `::`, `<>`,
+    /// spaces, `/`) and [`KEEP`]ed and dunder names as they are.
+    fn names(&self, s: &str) -> String {
+        let mut out = String::with_capacity(s.len());
+        let mut rest = s;
+        while let Some(start) = rest.find(is_ident) {
+            out.push_str(&rest[..start]);
+            rest = &rest[start..];
+            let end = rest.find(|c| !is_ident(c)).unwrap_or(rest.len());
+            let word = &rest[..end];
+            let dunder = word.len() > 4 && word.starts_with("__") && word.ends_with("__");
+            if KEEP.contains(&word) || dunder || word.starts_with(|c: char| c.is_ascii_digit()) {
+                out.push_str(word);
+            } else {
+                out.push_str(&self.hash(word));
+            }
+            rest = &rest[end..];
+        }
+        out.push_str(rest);
+        out
+    }
+
+    /// [`Hasher::names`] for a `/`-separated path, keeping the extension.
+    fn path(&self, path: &str) -> String {
+        match path.rsplit_once('.') {
+            Some((stem, ext)) if !ext.contains('/') => format!("{}.{ext}", self.names(stem)),
+            _ => self.names(path),
+        }
+    }
+}
+
+fn is_ident(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::{Warning, WarningKind};
+    use crate::api::tree_sitter::rs::Rust;
+    use crate::api::tree_sitter::{Span, analyze};
+    use crate::graph::Graph;
+
+    fn sample() -> Analysis {
+        Analysis {
+            files: vec![
+                analyze(
+                    &Rust,
+                    "src/billing/invoice.rs",
+                    "pub fn total(rate: tax::Rate) -> invoice::Total { tax::rate(); }\n",
+                ),
+                analyze(
+                    &Rust,
+                    "src/billing/tax.rs",
+                    "#[billing::audit]\npub fn rate() { invoice::total(); }\n",
+                ),
+            ],
+            warnings: vec![Warning {
+                kind: WarningKind::UnresolvedImport,
+                file: "src/billing/mod.rs".into(),
+                message: "`mod secret;` has no file".into(),
+                span: Some(Span::lines(1, 1)),
+                unknown: Default::default(),
+            }],
+            ..Default::default()
+        }
+    }
+
+    fn json(a: &Analysis) -> String {
+        serde_json::to_string(a).unwrap()
+    }
+
+    // ── anonymize ──
+
+    #[test]
+    fn no_identifier_survives() {
+        let mut a = sample();
+        anonymize(&mut a, "k");
+        let out = json(&a);
+        for word in ["billing", "invoice", "tax", "total", "rate", "secret"] {
+            assert!(!out.contains(word), "{word} leaked: {out}");
+        }
+        assert!(out.contains(".rs\""));
+        assert_eq!(a.warnings[0].span.unwrap().start_line, 1);
+        assert!(a.warnings[0].file.ends_with("/mod.rs"));
+    }
+
+    #[test]
+    fn graph_structure_preserved() {
+        let before = sample();
+        let mut after = sample();
+        anonymize(&mut after, "k");
+        let (g0, g1) = (Graph::build(&before.files), Graph::build(&after.files));
+        assert_eq!(g0.edges, g1.edges);
+        assert_eq!(g0.file_cycles().len(), 1);
+        assert_eq!(g1.file_cycles().len(), 1);
+        let lines = |a: &Analysis| a.files[0][1].metadata.lines;
+        assert_eq!(lines(&before), lines(&after));
+    }
+
+    #[test]
+    fn consistent_per_key() {
+        let (mut a, mut b, mut c) = (sample(), sample(), sample());
+        anonymize(&mut a, "k");
+        anonymize(&mut b, "k");
+        anonymize(&mut c, "other");
+        assert_eq!(json(&a), json(&b));
+        assert_ne!(json(&a), json(&c));
+    }
+
+    #[test]
+    fn names_keep_separators_and_language_words() {
+        let h = Hasher::new("");
+        let walk = h.hash("walk");
+        assert_eq!(walk.len(), DIGITS + 1);
+        assert_eq!(h.names("self.walk"), format!("self.{walk}"));
+        assert_eq!(h.names("fs::walk"), format!("{}::{walk}", h.hash("fs")));
+        assert_eq!(
+            h.names("Display for Foo<T>"),
+            format!(
+                "{} for {}<{}>",
+                h.hash("Display"),
+                h.hash("Foo"),
+                h.hash("T")
+            )
+        );
+        assert_eq!(h.names("__init__"), "__init__");
+        assert_eq!(
+            h.path("pkg/__init__.py"),
+            format!("{}/__init__.py", h.hash("pkg"))
+        );
+    }
+
+    #[test]
+    fn links_dropped() {
+        let mut a = sample();
+        a.files[0][0]
+            .metadata
+            .set_metric(LINK, "https://host/src/billing");
+        anonymize(&mut a, "k");
+        assert!(a.files[0][0].metadata.metric(LINK).is_none());
+    }
+
+    #[test]
+    fn crate_names_hashed() {
+        let mut a = sample();
+        a.files[0][0].metadata.set_metric(PROC_MACRO, "billing");
+        a.crates = vec![crate::crates::CrateEdge {
+            from: "billing".into(),
+            to: "src/build.rs".into(),
+            kind: EdgeKind::BuildScript,
+        }];
+        anonymize(&mut a, "k");
+        let h = Hasher::new("k");
+        let billing = Value::String(h.names("billing"));
+        assert_eq!(a.files[0][0].metadata.metric(PROC_MACRO), Some(&billing));
+        assert_eq!(a.crates[0].from, h.names("billing"));
+        assert_eq!(a.crates[0].to, h.path("src/build.rs"));
+    }
+}
@@ -1,8 +1,10 @@
 use crate::error::BoloError;
-use ignore::WalkBuilder;
+use ignore::{WalkBuilder, WalkState};
 use std::fs;
 use std::io::{self, Write};
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+use unicode_normalization::{UnicodeNormalization, is_nfc_quick};
 
 // ── Output Type ────────────────────────────────────────────────────
 
@@ -14,32 +16,60 @@ pub struct File {
     pub rel_path: PathBuf,
 }
 
-const MAX_FILE_SIZE: u64 = 10 * 1024 * 1024; // 10 MB
+pub const MAX_FILE_SIZE: u64 = 10 * 1024 * 1024; // 10 MB
 
 impl File {
+    /// Size in bytes; 0 if it can no longer be read.
+    pub fn size(&self) -> u64 {
+        fs::metadata(&self.path).map(|m| m.len()).unwrap_or(0)
+    }
+
+    /// Read the file, replacing invalid UTF-8 with U+FFFD, composed to NFC
+    /// so identifiers match whichever form an editor saved them in. Spans
+    /// count bytes of the composed text.
+    ///
+    /// Returns the text and whether any bytes had to be replaced.
+    pub fn read_lossy(&self) -> Result<(String, bool), BoloError> {
+        let bytes = fs::read(&self.path).map_err(|e| BoloError::Read {
+            path: simplify(&self.path),
+            reason: e.to_string(),
+        })?;
+        Ok(decode(bytes))
+    }
+
     /// Read the file contents into a string.
     pub fn read(&self) -> Result<String, BoloError> {
         let size = fs::metadata(&self.path)
             .map_err(|e| BoloError::Read {
-                path: self.path.clone(),
+                path: simplify(&self.path),
                 reason: e.to_string(),
             })?
             .len();
 
         if size > MAX_FILE_SIZE {
             return Err(BoloError::Read {
-                path: self.path.clone(),
+                path: simplify(&self.path),
                 reason: format!("file is {} MB, exceeds 10 MB limit", size / (1024 * 1024)),
             });
         }
 
         fs::read_to_string(&self.path).map_err(|e| BoloError::Read {
-            path: self.path.clone(),
+            path: simplify(&self.path),
             reason: e.to_string(),
         })
     }
 }
 
+/// `bytes` as NFC text, invalid UTF-8 replaced with U+FFFD, as
+/// [`File::read_lossy`] reads a file; also whether any bytes were replaced.
+pub fn decode(bytes: Vec<u8>) -> (String, bool) {
+    let (text, lossy) = match String::from_utf8(bytes) {
+        Ok(text) => (text, false),
+        Err(e) => (String::from_utf8_lossy(e.as_bytes()).into_owned(), true),
+    };
+    (nfc(text), lossy)
+}
+
 // ── Validation ─────────────────────────────────────────────────────
 
 pub fn validate_path(path: &Path) -> Result<(), BoloError> {
@@ -58,54 +88,184 @@ pub fn validate_path(path: &Path) -> Result<(), BoloError> {
 
 // ── Discovery ──────────────────────────────────────────────────────
 
+/// Directories skipped by default when walking for `ext` files: virtualenvs,
+/// tool caches and build output, which hold other people's code.
+pub fn default_excludes(ext: &str) -> &'static [&'static str] {
+    match ext {
+        "py" => &[
+            "venv",
+            ".venv",
+            ".tox",
+            ".nox",
+            "__pycache__",
+            "build",
+            "site-packages",
+        ],
+        "rs" => &["target"],
+        "js" | "ts" => &["node_modules", "dist", "coverage"],
+        "go" => &["vendor", "testdata"],
+        _ => &[],
+    }
+}
+
+/// Find `ext` files under `path`, skipping [`default_excludes`].
 pub fn walk_dir(path: &Path, ext: &str, no_ignore: bool) -> Result<Vec<File>, BoloError> {
+    walk_dir_threads(path, ext, no_ignore, default_excludes(ext), 1, None)
+}
+
+/// [`walk_dir`] on `threads` threads, skipping directories named in
+/// `excludes` below `path` and, with `max_depth`, anything deeper (1 keeps
+/// only the files directly in `path`). The result is sorted, so it does not
+/// depend on the thread count.
+pub fn walk_dir_threads(
+    path: &Path,
+    ext: &str,
+    no_ignore: bool,
+    excludes: &'static [&'static str],
+    threads: usize,
+    max_depth: Option<usize>,
+) -> Result<Vec<File>, BoloError> {
+    walk_dir_exts(path, &[ext], no_ignore, excludes, threads, max_depth)
+}
+
+/// [`walk_dir_threads`] for files with any of `exts`, in one pass.
+pub fn walk_dir_exts(
+    path: &Path,
+    exts: &[&str],
+    no_ignore: bool,
+    excludes: &[&'static str],
+    threads: usize,
+    max_depth: Option<usize>,
+) -> Result<Vec<File>, BoloError> {
+    let matches = |p: &Path| exts.iter().any(|ext| matches_ext(p, ext));
+    // Keep the verbatim form for I/O: on Windows it lifts the MAX_PATH limit.
     let root = path.canonicalize().map_err(|e| BoloError::Walk {
         path: path.to_path_buf(),
         reason: e.to_string(),
     })?;
 
     if root.is_file() {
-        return if matches_ext(&root, ext) {
-            Ok(vec![File {
-                rel_path: PathBuf::from(root.file_name().unwrap()),
-                path: root,
-            }])
-        } else {
-            Err(BoloError::Walk {
+        return match root.file_name().filter(|_| matches(&root)) {
+            Some(name) => Ok(vec![File {
+                rel_path: PathBuf::from(name),
                 path: root,
-                reason: format!("file does not have a .{ext} extension"),
-            })
+            }]),
+            None => Err(BoloError::Walk {
+                path: simplify(&root),
+                reason: format!("file does not have a .{} extension", exts.join(", .")),
+            }),
         };
     }
 
-    let mut files = Vec::new();
-
-    for entry in WalkBuilder::new(&root).git_ignore(!no_ignore).build() {
-        let entry = entry.map_err(|e| BoloError::Walk {
-            path: root.clone(),
-            reason: e.to_string(),
-        })?;
-
-        let Some(ft) = entry.file_type() else {
-            continue;
+    let found = Mutex::new(Vec::new());
+    let failed = Mutex::new(None);
+    let visit = |entry: Result<ignore::DirEntry, ignore::Error>| {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                failed.lock().unwrap().get_or_insert(e.to_string());
+                return WalkState::Quit;
+            }
         };
-        if !ft.is_file() {
-            continue;
+        if entry.file_type().is_some_and(|ft| ft.is_file()) && matches(entry.path()) {
+            let abs = entry.into_path();
+            let rel = abs.strip_prefix(&root).unwrap_or(&abs).to_path_buf();
+            found.lock().unwrap().push(File {
+                path: abs,
+                rel_path: rel,
+            });
         }
-        if !matches_ext(entry.path(), ext) {
-            continue;
+        WalkState::Continue
+    };
+
+    let excludes = excludes.to_vec();
+    let mut builder = WalkBuilder::new(&root);
+    builder
+        .git_ignore(!no_ignore)
+        .max_depth(max_depth)
+        .filter_entry(move |e| {
+            e.depth() == 0
+                || !e.file_type().is_some_and(|ft| ft.is_dir())
+                || !excludes.iter().any(|x| e.file_name() == *x)
+        });
+    if threads > 1 {
+        builder
+            .threads(threads)
+            .build_parallel()
+            .run(|| Box::new(&visit));
+    } else {
+        for entry in builder.build() {
+            if let WalkState::Quit = visit(entry) {
+                break;
+            }
         }
+    }
+
+    if let Some(reason) = failed.into_inner().unwrap() {
+        return Err(BoloError::Walk {
+            path: simplify(&root),
+            reason,
+        });
+    }
+    let mut files = found.into_inner().unwrap();
+    files.sort_by_cached_key(|f| portable(&f.rel_path, None));
+    Ok(files)
+}
 
-        let abs = entry.into_path();
-        let rel = abs.strip_prefix(&root).unwrap_or(&abs).to_path_buf();
+/// Split a file list on newlines, or on NULs when `null` (as from `find -print0`).
+/// Empty entries are skipped; with newlines, a trailing `\r` is dropped.
+pub fn read_list(input: &[u8], null: bool) -> Vec<PathBuf> {
+    let sep = if null { b'\0' } else { b'\n' };
+    input
+        .split(|b| *b == sep)
+        .map(|entry| {
+            let entry = if null {
+                entry
+            } else {
+                entry.strip_suffix(b"\r").unwrap_or(entry)
+            };
+            String::from_utf8_lossy(entry).into_owned()
+        })
+        .filter(|e| !e.is_empty())
+        .map(PathBuf::from)
+        .collect()
+}
 
+/// Resolve listed paths into [`File`]s with extension `ext`, relative to `root`.
+///
+/// Paths outside `root` keep the path as listed. Missing files are an error;
+/// other extensions are skipped.
+pub fn from_list(root: &Path, paths: Vec<PathBuf>, ext: &str) -> Result<Vec<File>, BoloError> {
+    let root = root.canonicalize().map_err(|e| BoloError::Walk {
+        path: root.to_path_buf(),
+        reason: e.to_string(),
+    })?;
+    let base = if root.is_file() {
+        root.parent().unwrap_or(&root).to_path_buf()
+    } else {
+        root
+    };
+
+    let mut files = Vec::new();
+    for listed in paths {
+        if !matches_ext(&listed, ext) {
+            continue;
+        }
+        let abs = listed.canonicalize().map_err(|e| BoloError::InvalidPath {
+            path: listed.clone(),
+            reason: e.to_string(),
+        })?;
+        let rel = abs
+            .strip_prefix(&base)
+            .map(Path::to_path_buf)
+            .unwrap_or(listed);
         files.push(File {
             path: abs,
             rel_path: rel,
         });
     }
-
-    files.sort_by(|a, b| a.rel_path.cmp(&b.rel_path));
+    files.sort_by_cached_key(|f| portable(&f.rel_path, None));
+    files.dedup_by(|a, b| a.path == b.path);
     Ok(files)
 }
 
@@ -114,6 +274,180 @@ fn matches_ext(path: &Path, ext: &str) -> bool {
         .is_some_and(|e| e.eq_ignore_ascii_case(ext))
 }
 
+// ── Sharding & Sampling ────────────────────────────────────────────
+
+/// Slice `index` (1-based) of `count`, written `i/n` on the command line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Shard {
+    pub index: usize,
+    pub count: usize,
+}
+
+impl std::str::FromStr for Shard {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (i, n) = s.split_once('/').ok_or("expected i/n, e.g. 1/4")?;
+        let index: usize = i
+            .trim()
+            .parse()
+            .map_err(|_| format!("invalid shard index `{i}`"))?;
+        let count: usize = n
+            .trim()
+            .parse()
+            .map_err(|_| format!("invalid shard count `{n}`"))?;
+        if count == 0 || index == 0 || index > count {
+            return Err(format!(
+                "shard must satisfy 1 <= i <= n, got {index}/{count}"
+            ));
+        }
+        Ok(Shard { index, count })
+    }
+}
+
+impl Shard {
+    /// Whether `file` belongs to this shard. Files are assigned by a stable
+    /// hash of their portable relative path, so every machine agrees and
+    /// adding a file never moves the others.
+    pub fn contains(&self, file: &File) -> bool {
+        (stable_hash(file, 0) % self.count as u64) as usize == self.index - 1
+    }
+}
+
+/// `--sample`: a percentage of the walked files.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sample {
+    pub percent: f64,
+}
+
+impl std::str::FromStr for Sample {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let n = s.trim();
+        let n = n.strip_suffix('%').unwrap_or(n);
+        let percent: f64 = n
+            .trim()
+            .parse()
+            .map_err(|_| format!("invalid sample `{s}`, expected e.g. 10%"))?;
+        if !(percent > 0.0 && percent <= 100.0) {
+            return Err(format!("sample must be in (0, 100]%, got {percent}%"));
+        }
+        Ok(Sample { percent })
+    }
+}
+
+impl Sample {
+    /// Files kept out of `total`; at least one when there are any.
+    pub fn count(&self, total: usize) -> usize {
+        let n = (total as f64 * self.percent / 100.0).ceil() as usize;
+        n.clamp(total.min(1), total)
+    }
+}
+
+/// Keep `keep` of `files`, chosen by a `seed`ed hash of each relative path.
+/// The same seed picks the same files on every machine, and adding a file
+/// displaces at most one of the others. Order is preserved.
+pub fn sample(files: Vec<File>, keep: usize, seed: u64) -> Vec<File> {
+    if keep >= files.len() {
+        return files;
+    }
+    let mut ranked: Vec<_> = files
+        .iter()
+        .enumerate()
+        .map(|(i, f)| (stable_hash(f, seed), i))
+        .collect();
+    ranked.sort_unstable();
+    let mut chosen = vec![false; files.len()];
+    for &(_, i) in &ranked[..keep] {
+        chosen[i] = true;
+    }
+    files
+        .into_iter()
+        .zip(chosen)
+        .filter_map(|(f, keep)| keep.then_some(f))
+        .collect()
+}
+
+/// FNV-1a of the portable relative path: stable across Rust versions and
+/// platforms, unlike `DefaultHasher`. Seed 0 is plain FNV-1a.
+fn stable_hash(file: &File, seed: u64) -> u64 {
+    let key = portable(&file.rel_path, None);
+    let basis = 0xcbf2_9ce4_8422_2325_u64 ^ seed.wrapping_mul(0x0100_0000_01b3);
+    key.bytes()
+        .fold(basis, |h, b| (h ^ b as u64).wrapping_mul(0x0100_0000_01b3))
+}
+
+// ── Identity ───────────────────────────────────────────────────────
+
+/// [`Path::canonicalize`] without Windows verbatim prefixes (see [`simplify`]).
+pub fn canonicalize(path: &Path) -> io::Result<PathBuf> {
+    path.canonicalize().map(|p| simplify(&p))
+}
+
+/// Drop the `\\?\` verbatim prefix Windows adds to canonical paths and
+/// uppercase the drive letter, so the same file always prints the same way.
+///
+/// `\\?\C:\x` → `C:\x`, `\\?\UNC\host\share` → `\\host\share`, `c:\x` → `C:\x`.
+pub fn simplify(path: &Path) -> PathBuf {
+    let Some(s) = path.to_str() else {
+        return path.to_path_buf();
+    };
+    let s = match s.strip_prefix(r"\\?\") {
+        Some(rest) => match rest.strip_prefix(r"UNC\") {
+            Some(unc) => format!(r"\\{unc}"),
+            None => rest.to_string(),
+        },
+        None => s.to_string(),
+    };
+    let mut chars = s.chars();
+    match (chars.next(), chars.next(), chars.next()) {
+        (Some(d), Some(':'), None | Some('\\' | '/')) if d.is_ascii_lowercase() => {
+            PathBuf::from(format!("{}{}", d.to_ascii_uppercase(), &s[1..]))
+        }
+        _ => PathBuf::from(s),
+    }
+}
+
+/// Forward-slash, NFC form of a relative path, optionally under a root
+/// `label`. Bytes that are not UTF-8 become U+FFFD.
+///
+/// Used as the File node path and the walk order so output is identical
+/// across machines.
+pub fn portable(path: &Path, label: Option<&str>) -> String {
+    // Backslashes are separators too, so Windows-style relative paths
+    // normalize the same on every host.
+    let parts = path
+        .components()
+        .filter_map(|c| match c {
+            Component::Normal(p) => Some(nfc(p.to_string_lossy().into_owned())),
+            _ => None,
+        })
+        .flat_map(|p| {
+            p.split('\\')
+                .filter(|s| !s.is_empty() && *s != ".")
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        });
+    label
+        .filter(|l| !l.is_empty())
+        .map(|l| l.trim_end_matches(['/', '\\']).replace('\\', "/"))
+        .into_iter()
+        .chain(parts)
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// `text` in Unicode Normalization Form C. macOS hands out decomposed
+/// file names and Linux keeps whatever was written, so paths and names are
+/// composed before they are compared or sorted.
+pub fn nfc(text: String) -> String {
+    match is_nfc_quick(text.chars()) {
+        unicode_normalization::IsNormalized::Yes => text,
+        _ => text.nfc().collect(),
+    }
+}
+
 // ── Output ─────────────────────────────────────────────────────────
 
 pub fn ensure_dir(path: &Path) -> io::Result<()> {
@@ -149,6 +483,20 @@ pub fn write_file(path: &Path, content: &str, mkdir: bool) -> Result<(), BoloErr
     Ok(())
 }
 
+/// Append `content` to `path`, creating it if needed (e.g. a CI job summary
+/// other steps also write to).
+pub fn append_file(path: &Path, content: &str) -> Result<(), BoloError> {
+    fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut f| f.write_all(content.as_bytes()))
+        .map_err(|e| BoloError::Write {
+            path: path.to_path_buf(),
+            reason: e.to_string(),
+        })
+}
+
 // ── Tests ──────────────────────────────────────────────────────────
 
 #[cfg(test)]
@@ -156,6 +504,17 @@ mod tests {
     use super::*;
     use tempfile::TempDir;
 
+    // ── append_file ──
+
+    #[test]
+    fn append_keeps_existing_content() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("summary.md");
+        append_file(&path, "one\n").unwrap();
+        append_file(&path, "two\n").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "one\ntwo\n");
+    }
+
     // ── validate_path ──
 
     #[test]
@@ -249,6 +608,44 @@ mod tests {
         assert!(matches!(err, BoloError::Walk { .. }));
     }
 
+    #[test]
+    fn walk_threads_matches_serial() {
+        let dir = TempDir::new().unwrap();
+        for d in ["a", "b/c", "d"] {
+            fs::create_dir_all(dir.path().join(d)).unwrap();
+            for name in ["x.py", "y.py", "z.rs"] {
+                fs::write(dir.path().join(d).join(name), "").unwrap();
+            }
+        }
+
+        let rel =
+            |files: Vec<File>| -> Vec<PathBuf> { files.into_iter().map(|f| f.rel_path).collect() };
+        let serial = rel(walk_dir(dir.path(), "py", false).unwrap());
+        let parallel = rel(walk_dir_threads(dir.path(), "py", false, &[], 4, None).unwrap());
+        assert_eq!(serial.len(), 6);
+        assert_eq!(serial, parallel);
+
+        fs::write(dir.path().join("top.py"), "").unwrap();
+        let shallow = rel(walk_dir_threads(dir.path(), "py", false, &[], 4, Some(1)).unwrap());
+        assert_eq!(shallow, [PathBuf::from("top.py")]);
+    }
+
+    #[test]
+    fn walk_several_extensions_at_once() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join("target")).unwrap();
+        for name in ["a.py", "b.rs", "c.go", "target/d.rs"] {
+            fs::write(dir.path().join(name), "").unwrap();
+        }
+
+        let files = walk_dir_exts(dir.path(), &["py", "rs"], false, &["target"], 2, None).unwrap();
+        let names: Vec<_> = files.iter().map(|f| f.rel_path.to_str().unwrap()).collect();
+        assert_eq!(names, ["a.py", "b.rs"]);
+
+        let err = walk_dir_exts(&dir.path().join("c.go"), &["py", "rs"], false, &[], 1, None);
+        assert!(err.unwrap_err().to_string().contains(".py, .rs"));
+    }
+
     #[test]
     fn walk_respects_gitignore() {
         let dir = TempDir::new().unwrap();
@@ -284,6 +681,87 @@ mod tests {
         assert_eq!(files.len(), 2);
     }
 
+    #[test]
+    fn walk_skips_default_excludes() {
+        let dir = TempDir::new().unwrap();
+        for sub in ["venv/lib", "pkg/__pycache__", "pkg/build", "target"] {
+            fs::create_dir_all(dir.path().join(sub)).unwrap();
+        }
+        for file in [
+            "app.py",
+            "venv/lib/six.py",
+            "pkg/__pycache__/mod.py",
+            "pkg/build/gen.py",
+            "pkg/build.py",
+            "target/x.py",
+        ] {
+            fs::write(dir.path().join(file), "").unwrap();
+        }
+        let rel =
+            |files: Vec<File>| -> Vec<PathBuf> { files.into_iter().map(|f| f.rel_path).collect() };
+        assert_eq!(
+            rel(walk_dir(dir.path(), "py", false).unwrap()),
+            ["app.py", "pkg/build.py", "target/x.py"].map(PathBuf::from)
+        );
+        let all = walk_dir_threads(dir.path(), "py", false, &[], 1, None).unwrap();
+        assert_eq!(all.len(), 6);
+        // The root itself is never skipped.
+        assert_eq!(
+            walk_dir(&dir.path().join("venv"), "py", false)
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    // ── read_list / from_list ──
+
+    #[test]
+    fn list_newline_delimited() {
+        let paths = read_list(b"a.py\r\nsub/b.py\n\n", false);
+        assert_eq!(
+            paths,
+            vec![PathBuf::from("a.py"), PathBuf::from("sub/b.py")]
+        );
+    }
+
+    #[test]
+    fn list_nul_delimited_keeps_newlines() {
+        let paths = read_list(b"odd\nname.py\0b.py\0", true);
+        assert_eq!(
+            paths,
+            vec![PathBuf::from("odd\nname.py"), PathBuf::from("b.py")]
+        );
+    }
+
+    #[test]
+    fn from_list_relative_to_root() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join("sub")).unwrap();
+        fs::write(dir.path().join("sub/b.py"), "").unwrap();
+        fs::write(dir.path().join("odd\nname.py"), "").unwrap();
+        fs::write(dir.path().join("c.rs"), "").unwrap();
+        let listed = vec![
+            dir.path().join("sub/b.py"),
+            dir.path().join("odd\nname.py"),
+            dir.path().join("c.rs"),
+            dir.path().join("sub/../sub/b.py"),
+        ];
+        let files = from_list(dir.path(), listed, "py").unwrap();
+        let rels: Vec<_> = files.iter().map(|f| f.rel_path.clone()).collect();
+        assert_eq!(
+            rels,
+            vec![PathBuf::from("odd\nname.py"), PathBuf::from("sub/b.py")]
+        );
+    }
+
+    #[test]
+    fn from_list_missing_file_errors() {
+        let dir = TempDir::new().unwrap();
+        let err = from_list(dir.path(), vec![dir.path().join("gone.py")], "py").unwrap_err();
+        assert!(matches!(err, BoloError::InvalidPath { .. }));
+    }
+
     // ── matches_ext ──
 
     #[test]
@@ -303,6 +781,223 @@ mod tests {
         assert!(!matches_ext(Path::new("file.rs"), "py"));
     }
 
+    // ── portable ──
+
+    #[test]
+    fn portable_joins_with_forward_slash() {
+        let p: PathBuf = ["src", "api", "fs.rs"].iter().collect();
+        assert_eq!(portable(&p, None), "src/api/fs.rs");
+    }
+
+    #[test]
+    fn portable_drops_dot_components() {
+        assert_eq!(portable(Path::new("./src/./lib.rs"), None), "src/lib.rs");
+    }
+
+    #[test]
+    fn portable_with_label() {
+        assert_eq!(portable(Path::new("a/b.py"), Some("svc/")), "svc/a/b.py");
+        assert_eq!(portable(Path::new("a/b.py"), Some("")), "a/b.py");
+    }
+
+    #[test]
+    fn portable_windows_separators() {
+        assert_eq!(portable(Path::new(r"src\api\fs.rs"), None), "src/api/fs.rs");
+        assert_eq!(portable(Path::new(r".\src\lib.rs"), None), "src/lib.rs");
+        assert_eq!(
+            portable(Path::new("a.py"), Some(r"org\svc\")),
+            "org/svc/a.py"
+        );
+    }
+
+    #[test]
+    fn portable_composes_nfc() {
+        let decomposed = portable(Path::new("caf\u{65}\u{301}/a.py"), None);
+        assert_eq!(decomposed, "caf\u{e9}/a.py");
+    }
+
+    #[test]
+    fn walk_keeps_unusual_file_names() {
+        let dir = TempDir::new().unwrap();
+        let names = ["-dash.py", "new\nline.py", "sp ace.py", "\u{1F600}.py"];
+        for name in names {
+            fs::write(dir.path().join(name), "").unwrap();
+        }
+        let files = walk_dir(dir.path(), "py", false).unwrap();
+        let found: Vec<_> = files.iter().map(|f| portable(&f.rel_path, None)).collect();
+        assert_eq!(found, names);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn walk_non_utf8_file_name() {
+        use std::os::unix::ffi::OsStrExt;
+        let dir = TempDir::new().unwrap();
+        let name = std::ffi::OsStr::from_bytes(b"bad\xff.py");
+        fs::write(dir.path().join(name), "").unwrap();
+
+        let files = walk_dir(dir.path(), "py", false).unwrap();
+        assert_eq!(files[0].rel_path, Path::new(name));
+        assert!(files[0].rel_path.to_str().is_none());
+        assert_eq!(portable(&files[0].rel_path, None), "bad\u{FFFD}.py");
+
+        let single = walk_dir(&dir.path().join(name), "py", false).unwrap();
+        assert_eq!(single[0].rel_path, Path::new(name));
+    }
+
+    #[test]
+    fn walk_sorts_by_composed_path() {
+        let dir = TempDir::new().unwrap();
+        // Decomposed, `é` would sort as `e` before `z`; composed, after it.
+        for name in ["cafe\u{301}.py", "cafz.py"] {
+            fs::write(dir.path().join(name), "").unwrap();
+        }
+        let files = walk_dir(dir.path(), "py", false).unwrap();
+        let names: Vec<_> = files.iter().map(|f| portable(&f.rel_path, None)).collect();
+        assert_eq!(names, ["cafz.py", "caf\u{e9}.py"]);
+    }
+
+    // ── simplify ──
+
+    #[test]
+    fn simplify_verbatim_drive() {
+        assert_eq!(
+            simplify(Path::new(r"\\?\C:\Users\me\proj")),
+            PathBuf::from(r"C:\Users\me\proj")
+        );
+    }
+
+    #[test]
+    fn simplify_verbatim_unc() {
+        assert_eq!(
+            simplify(Path::new(r"\\?\UNC\host\share\src")),
+            PathBuf::from(r"\\host\share\src")
+        );
+    }
+
+    #[test]
+    fn simplify_drive_letter_case() {
+        assert_eq!(simplify(Path::new(r"d:\code")), PathBuf::from(r"D:\code"));
+        assert_eq!(simplify(Path::new(r"\\?\e:\x")), PathBuf::from(r"E:\x"));
+    }
+
+    #[test]
+    fn simplify_leaves_unix_paths() {
+        assert_eq!(simplify(Path::new("/home/me")), PathBuf::from("/home/me"));
+        assert_eq!(simplify(Path::new("a:b")), PathBuf::from("a:b"));
+    }
+
+    #[test]
+    fn canonicalize_existing() {
+        let dir = TempDir::new().unwrap();
+        assert!(canonicalize(dir.path()).unwrap().is_absolute());
+    }
+
+    // ── Shard ──
+
+    #[test]
+    fn shard_parse() {
+        assert_eq!("2/4".parse::<Shard>(), Ok(Shard { index: 2, count: 4 }));
+        assert!("0/4".parse::<Shard>().is_err());
+        assert!("5/4".parse::<Shard>().is_err());
+        assert!("1/0".parse::<Shard>().is_err());
+        assert!("1".parse::<Shard>().is_err());
+        assert!("a/b".parse::<Shard>().is_err());
+    }
+
+    #[test]
+    fn shards_partition_files() {
+        let files: Vec<_> = (0..50)
+            .map(|i| File {
+                path: PathBuf::from(format!("/r/m{i}.py")),
+                rel_path: PathBuf::from(format!("m{i}.py")),
+            })
+            .collect();
+        let shards: Vec<_> = (1..=3).map(|i| Shard { index: i, count: 3 }).collect();
+        for f in &files {
+            assert_eq!(shards.iter().filter(|s| s.contains(f)).count(), 1);
+        }
+        // every shard gets some work
+        for s in &shards {
+            assert!(files.iter().any(|f| s.contains(f)));
+        }
+    }
+
+    #[test]
+    fn single_shard_takes_everything() {
+        let f = File {
+            path: PathBuf::from("/r/a.py"),
+            rel_path: PathBuf::from("a.py"),
+        };
+        assert!(Shard { index: 1, count: 1 }.contains(&f));
+    }
+
+    // ── Sample ──
+
+    fn numbered(n: usize) -> Vec<File> {
+        (0..n)
+            .map(|i| File {
+                path: PathBuf::from(format!("/r/m{i:03}.py")),
+                rel_path: PathBuf::from(format!("m{i:03}.py")),
+            })
+            .collect()
+    }
+
+    fn rel(files: &[File]) -> Vec<&Path> {
+        files.iter().map(|f| f.rel_path.as_path()).collect()
+    }
+
+    #[test]
+    fn sample_parse() {
+        assert_eq!("10%".parse::<Sample>(), Ok(Sample { percent: 10.0 }));
+        assert_eq!("2.5".parse::<Sample>(), Ok(Sample { percent: 2.5 }));
+        assert!("0%".parse::<Sample>().is_err());
+        assert!("101%".parse::<Sample>().is_err());
+        assert!("ten".parse::<Sample>().is_err());
+    }
+
+    #[test]
+    fn sample_count_rounds_up_and_keeps_one() {
+        let s = Sample { percent: 10.0 };
+        assert_eq!(s.count(100), 10);
+        assert_eq!(s.count(101), 11);
+        assert_eq!(s.count(3), 1);
+        assert_eq!(s.count(0), 0);
+        assert_eq!(Sample { percent: 100.0 }.count(7), 7);
+    }
+
+    #[test]
+    fn sample_is_deterministic_and_ordered() {
+        let a = sample(numbered(100), 10, 7);
+        let b = sample(numbered(100), 10, 7);
+        assert_eq!(a.len(), 10);
+        assert_eq!(rel(&a), rel(&b));
+        assert!(a.windows(2).all(|w| w[0].rel_path < w[1].rel_path));
+    }
+
+    #[test]
+    fn sample_seed_changes_selection() {
+        let a = sample(numbered(100), 10, 1);
+        let b = sample(numbered(100), 10, 2);
+        assert_ne!(rel(&a), rel(&b));
+    }
+
+    #[test]
+    fn sample_stable_when_a_file_is_added() {
+        let before = sample(numbered(100), 10, 0);
+        let after = sample(numbered(101), 10, 0);
+        let kept = rel(&before)
+            .iter()
+            .filter(|p| rel(&after).contains(p))
+            .count();
+        assert!(kept >= 9);
+    }
+
+    #[test]
+    fn sample_keep_all_is_identity() {
+        assert_eq!(sample(numbered(5), 10, 3).len(), 5);
+    }
+
     // ── File::read ──
 
     #[test]
@@ -318,6 +1013,35 @@ mod tests {
         assert_eq!(file.read().unwrap(), "import os\n");
     }
 
+    #[test]
+    fn file_read_lossy_flags_invalid_utf8() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("bad.py");
+        fs::write(&path, b"x = '\xff'\n").unwrap();
+        let file = File {
+            path,
+            rel_path: PathBuf::from("bad.py"),
+        };
+        let (text, lossy) = file.read_lossy().unwrap();
+        assert!(lossy);
+        assert_eq!(text, "x = '\u{FFFD}'\n");
+        assert!(file.read().is_err());
+    }
+
+    #[test]
+    fn file_read_lossy_composes_nfc() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("cafe.py");
+        fs::write(&path, "def cafe\u{301}():\n    pass\n").unwrap();
+        let file = File {
+            path,
+            rel_path: PathBuf::from("cafe.py"),
+        };
+        let (text, lossy) = file.read_lossy().unwrap();
+        assert!(!lossy);
+        assert_eq!(text, "def caf\u{e9}():\n    pass\n");
+    }
+
     // ── ensure_dir ──
 
     #[test]
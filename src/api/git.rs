@@ -0,0 +1,290 @@
+use crate::api::fs;
+use crate::error::BoloError;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tempfile::TempDir;
+
+/// True if `s` names a remote repository rather than a local path.
+pub fn is_remote(s: &str) -> bool {
+    ["https://", "http://", "ssh://", "git://", "file://"]
+        .iter()
+        .any(|p| s.starts_with(p))
+        || (s.starts_with("git@") && s.contains(':'))
+}
+
+/// Shallow-clone `url` at `rev` (branch, tag or commit; default HEAD) into a
+/// temporary directory, removed when the returned [`TempDir`] drops.
+pub fn clone(url: &str, rev: Option<&str>) -> Result<TempDir, BoloError> {
+    let err = |reason: String| BoloError::Git {
+        url: url.to_string(),
+        reason,
+    };
+    let dir = TempDir::new().map_err(|e| err(e.to_string()))?;
+    let path = dir.path();
+
+    match rev {
+        None => git(
+            None,
+            &[
+                "clone",
+                "--quiet",
+                "--depth",
+                "1",
+                url,
+                &path.to_string_lossy(),
+            ],
+        ),
+        // `clone --branch` can't take a commit; fetching the rev directly can.
+        Some(rev) => git(Some(path), &["init", "--quiet"])
+            .and_then(|_| git(Some(path), &["fetch", "--quiet", "--depth", "1", url, rev]))
+            .and_then(|_| git(Some(path), &["checkout", "--quiet", "FETCH_HEAD"])),
+    }
+    .map_err(err)?;
+
+    Ok(dir)
+}
+
+/// Check out `rev` of the local repository containing the directory `path`
+/// into a temporary directory, as [`clone`] does for remote URLs. Also
+/// returns where `path` is inside the checkout.
+pub fn checkout(path: &Path, rev: &str) -> Result<(TempDir, PathBuf), BoloError> {
+    let err = |reason| BoloError::Git {
+        url: path.display().to_string(),
+        reason,
+    };
+    let top = git(Some(path), &["rev-parse", "--show-toplevel"]).map_err(err)?;
+    let prefix = git(Some(path), &["rev-parse", "--show-prefix"]).map_err(err)?;
+    let dir = clone(&format!("file://{top}"), Some(rev))?;
+    let within = dir.path().join(prefix);
+    Ok((dir, within))
+}
+
+/// The commit checked out in the repository containing the directory `path`,
+/// and where `path` is inside it (`/`-terminated, or empty at the top).
+pub fn head(path: &Path) -> Result<(String, String), BoloError> {
+    let err = |reason| BoloError::Git {
+        url: path.display().to_string(),
+        reason,
+    };
+    let commit = git(Some(path), &["rev-parse", "HEAD"]).map_err(err)?;
+    let prefix = git(Some(path), &["rev-parse", "--show-prefix"]).map_err(err)?;
+    Ok((commit, prefix))
+}
+
+/// Files added, copied, modified or renamed in the index (`base` of `None`)
+/// or on HEAD since it forked from `base`, with their contents there, as
+/// `(path from the repository root, text)`, both in NFC.
+pub fn changed(path: &Path, base: Option<&str>) -> Result<Vec<(String, String)>, BoloError> {
+    let err = |reason| BoloError::Git {
+        url: path.display().to_string(),
+        reason,
+    };
+    let top = PathBuf::from(git(Some(path), &["rev-parse", "--show-toplevel"]).map_err(err)?);
+    let range = base.map(|b| format!("{b}...HEAD"));
+    let mut args = vec!["diff", "--name-only", "--diff-filter=ACMR", "-z"];
+    match &range {
+        Some(range) => args.push(range),
+        None => args.push("--cached"),
+    }
+    let names = git(Some(&top), &args).map_err(err)?;
+    let rev = if base.is_some() { "HEAD" } else { "" };
+    names
+        .split('\0')
+        .filter(|n| !n.is_empty())
+        .map(|name| {
+            let blob = git_bytes(Some(&top), &["show", &format!("{rev}:{name}")]).map_err(err)?;
+            Ok((
+                fs::nfc(name.to_string()),
+                fs::nfc(String::from_utf8_lossy(&blob).into_owned()),
+            ))
+        })
+        .collect()
+}
+
+/// The hooks directory of the repository containing `path`, honoring
+/// `core.hooksPath`.
+pub fn hooks_dir(path: &Path) -> Result<PathBuf, BoloError> {
+    let dir = git(Some(path), &["rev-parse", "--git-path", "hooks"]).map_err(|reason| {
+        BoloError::Git {
+            url: path.display().to_string(),
+            reason,
+        }
+    })?;
+    Ok(path.join(dir))
+}
+
+/// Run git, returning its trimmed stdout or, on failure, its stderr.
+fn git(cwd: Option<&Path>, args: &[&str]) -> Result<String, String> {
+    git_bytes(cwd, args).map(|out| String::from_utf8_lossy(&out).trim().to_string())
+}
+
+/// [`git`] with stdout untouched.
+fn git_bytes(cwd: Option<&Path>, args: &[&str]) -> Result<Vec<u8>, String> {
+    let mut cmd = Command::new("git");
+    if let Some(dir) = cwd {
+        cmd.current_dir(dir);
+    }
+    let out = cmd
+        .args(args)
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .output()
+        .map_err(|e| format!("cannot run git: {e}"))?;
+    if out.status.success() {
+        Ok(out.stdout)
+    } else {
+        Err(String::from_utf8_lossy(&out.stderr).trim().to_string())
+    }
+}
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    /// A local repo with two commits; returns its `file://` URL and first commit.
+    fn fixture() -> (TempDir, String, String) {
+        let dir = TempDir::new().unwrap();
+        let p = dir.path();
+        let run = |args: &[&str]| {
+            let out = Command::new("git")
+                .current_dir(p)
+                .args(args)
+                .output()
+                .unwrap();
+            assert!(
+                out.status.success(),
+                "{}",
+                String::from_utf8_lossy(&out.stderr)
+            );
+            String::from_utf8_lossy(&out.stdout).trim().to_string()
+        };
+        run(&["init", "--quiet"]);
+        run(&["config", "user.email", "t@example.com"]);
+        run(&["config", "user.name", "t"]);
+        fs::write(p.join("a.py"), "def a(): pass\n").unwrap();
+        run(&["add", "."]);
+        run(&["commit", "--quiet", "-m", "one"]);
+        let first = run(&["rev-parse", "HEAD"]);
+        fs::write(p.join("b.py"), "def b(): pass\n").unwrap();
+        run(&["add", "."]);
+        run(&["commit", "--quiet", "-m", "two"]);
+        let url = format!("file://{}", p.display());
+        (dir, url, first)
+    }
+
+    #[test]
+    fn remote_urls() {
+        assert!(is_remote("https://github.com/org/repo"));
+        assert!(is_remote("git@github.com:org/repo.git"));
+        assert!(is_remote("ssh://git@host/repo"));
+        assert!(is_remote("file:///tmp/repo"));
+        assert!(!is_remote("src/"));
+        assert!(!is_remote("."));
+        assert!(!is_remote("git@notes"));
+    }
+
+    #[test]
+    fn clone_head() {
+        let (_src, url, _) = fixture();
+        let dir = clone(&url, None).unwrap();
+        assert!(dir.path().join("a.py").exists());
+        assert!(dir.path().join("b.py").exists());
+    }
+
+    #[test]
+    fn clone_at_commit() {
+        let (_src, url, first) = fixture();
+        let dir = clone(&url, Some(&first)).unwrap();
+        assert!(dir.path().join("a.py").exists());
+        assert!(!dir.path().join("b.py").exists());
+    }
+
+    #[test]
+    fn clone_removed_on_drop() {
+        let (_src, url, _) = fixture();
+        let dir = clone(&url, None).unwrap();
+        let path = dir.path().to_path_buf();
+        drop(dir);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn checkout_local_revision() {
+        let (src, _, first) = fixture();
+        let (_dir, root) = checkout(src.path(), &first).unwrap();
+        assert!(root.join("a.py").exists());
+        assert!(!root.join("b.py").exists());
+        let (_head, root) = checkout(src.path(), "HEAD").unwrap();
+        assert!(root.join("b.py").exists());
+    }
+
+    #[test]
+    fn checkout_from_subdirectory() {
+        let (src, _, _) = fixture();
+        let sub = src.path().join("pkg");
+        fs::create_dir(&sub).unwrap();
+        let (dir, within) = checkout(&sub, "HEAD").unwrap();
+        assert_eq!(within, dir.path().join("pkg/"));
+    }
+
+    #[test]
+    fn head_commit_and_prefix() {
+        let (src, url, _) = fixture();
+        let dir = clone(&url, None).unwrap();
+        let (commit, prefix) = head(dir.path()).unwrap();
+        assert_eq!(commit.len(), 40);
+        assert_eq!(prefix, "");
+        let sub = src.path().join("pkg");
+        fs::create_dir(&sub).unwrap();
+        let (same, prefix) = head(&sub).unwrap();
+        assert_eq!(same, commit);
+        assert_eq!(prefix, "pkg/");
+    }
+
+    #[test]
+    fn changed_in_index_and_since_base() {
+        let (src, _, first) = fixture();
+        let p = src.path();
+        let git = |args: &[&str]| git(Some(p), args).unwrap();
+        fs::write(p.join("a.py"), "def a(): return 1\n").unwrap();
+        fs::write(p.join("c.py"), "def c(): pass\n").unwrap();
+        git(&["add", "a.py"]);
+        // Unstaged edits don't count; the staged text is what's returned.
+        fs::write(p.join("a.py"), "def a(): return 2\n").unwrap();
+        let staged = changed(p, None).unwrap();
+        assert_eq!(
+            staged,
+            vec![("a.py".to_string(), "def a(): return 1\n".to_string())]
+        );
+
+        let since = changed(p, Some(&first)).unwrap();
+        assert_eq!(
+            since,
+            vec![("b.py".to_string(), "def b(): pass\n".to_string())]
+        );
+    }
+
+    #[test]
+    fn hooks_dir_in_git_dir() {
+        let (src, _, _) = fixture();
+        let dir = hooks_dir(src.path()).unwrap();
+        assert!(dir.ends_with(".git/hooks"));
+    }
+
+    #[test]
+    fn checkout_outside_repo_errors() {
+        let dir = TempDir::new().unwrap();
+        assert!(matches!(
+            checkout(dir.path(), "HEAD"),
+            Err(BoloError::Git { .. })
+        ));
+    }
+
+    #[test]
+    fn clone_missing_repo_errors() {
+        let err = clone("file:///nonexistent/repo", None).unwrap_err();
+        assert!(matches!(err, BoloError::Git { .. }));
+    }
+}
@@ -1,2 +1,3 @@
 pub mod fs;
+pub mod git;
 pub mod tree_sitter;
@@ -0,0 +1,794 @@
+use super::{
+    ASTNode, Call, DynamicImport, Field, Function, Import, Metadata, Options, Span, Syntax, Type,
+    UsesType, join, metadata_from_node, syntax_errors,
+};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use tree_sitter::{Node, Parser, Tree};
+
+/// Go (`.go`). Methods are named after their receiver, `(*Server).Handle`
+/// or `Server.Name`, and package names resolve to import paths.
+pub struct Go;
+
+impl super::Lang for Go {
+    fn get_parser(&self) -> Parser {
+        let mut parser = Parser::new();
+        parser
+            .set_language(&tree_sitter_go::LANGUAGE.into())
+            .expect("failed to load go grammar");
+        parser
+    }
+
+    fn extract<'s>(
+        &self,
+        tree: &Tree,
+        source: &'s str,
+        opts: &Options,
+    ) -> (Vec<Syntax<'s>>, Vec<Span>) {
+        let src = source.as_bytes();
+        let root = tree.root_node();
+        let imports = collect_imports(root, src);
+        let mut out = walk(root, src, &imports);
+        if let Some(which) = opts.strings {
+            let mut literals = Vec::new();
+            collect_strings(root, src, &mut literals);
+            super::attach_literals(&mut out, literals, src, which);
+        }
+        (out, syntax_errors(root))
+    }
+}
+
+// ── Import Collection ───────────────────────────────────────────────
+
+/// Package names in scope mapped to their import paths: `http` (or an
+/// alias `h`) → `net/http`. Dot and blank imports bind no name.
+fn collect_imports(root: Node, src: &[u8]) -> HashMap<String, String> {
+    let mut imports = HashMap::new();
+    let mut cursor = root.walk();
+    for decl in root.named_children(&mut cursor) {
+        if decl.kind() != "import_declaration" {
+            continue;
+        }
+        for spec in import_specs(decl) {
+            let Some(path) = spec
+                .child_by_field_name("path")
+                .and_then(|p| string_value(p, src))
+            else {
+                continue;
+            };
+            let name = match spec.child_by_field_name("name") {
+                Some(n) if n.kind() == "package_identifier" => text(n, src),
+                Some(_) => continue,
+                None => path.rsplit('/').next().unwrap_or(path),
+            };
+            imports.insert(name.to_string(), path.to_string());
+        }
+    }
+    imports
+}
+
+/// The `import_spec`s of a declaration, parenthesized or not.
+fn import_specs(decl: Node) -> Vec<Node> {
+    let mut specs = Vec::new();
+    let mut c = decl.walk();
+    for child in decl.named_children(&mut c) {
+        match child.kind() {
+            "import_spec" => specs.push(child),
+            "import_spec_list" => {
+                let mut c = child.walk();
+                specs.extend(
+                    child
+                        .named_children(&mut c)
+                        .filter(|n| n.kind() == "import_spec"),
+                );
+            }
+            _ => {}
+        }
+    }
+    specs
+}
+
+/// One Import per spec; `alias` is the name written before the path,
+/// including `_` and `.`.
+fn import_nodes<'s>(decl: Node, src: &'s [u8]) -> Vec<Syntax<'s>> {
+    import_specs(decl)
+        .into_iter()
+        .filter_map(|spec| {
+            let path = spec
+                .child_by_field_name("path")
+                .and_then(|p| string_value(p, src))?;
+            Some(Syntax {
+                node: ASTNode::Import(Import {
+                    module: Cow::Borrowed(path),
+                    items: vec![],
+                    alias: spec
+                        .child_by_field_name("name")
+                        .map(|n| text(n, src).into()),
+                }),
+                metadata: meta(spec, src),
+                contains: vec![],
+            })
+        })
+        .collect()
+}
+
+// ── AST Walk ────────────────────────────────────────────────────────
+
+fn walk<'s>(node: Node, src: &'s [u8], imports: &HashMap<String, String>) -> Vec<Syntax<'s>> {
+    let mut out = Vec::new();
+    let mut cursor = node.walk();
+
+    for child in node.named_children(&mut cursor) {
+        match child.kind() {
+            "function_declaration" => {
+                let name = field_text(child, "name", src);
+                out.push(function(
+                    child,
+                    name.into(),
+                    is_exported(name),
+                    src,
+                    imports,
+                ));
+            }
+
+            // `func (s *Server) Handle(...)` → `(*Server).Handle`
+            "method_declaration" => {
+                let name = field_text(child, "name", src);
+                let label = match receiver(child, src) {
+                    Some(r) if r.starts_with('*') => format!("({r}).{name}"),
+                    Some(r) => format!("{r}.{name}"),
+                    None => name.to_string(),
+                };
+                out.push(function(
+                    child,
+                    label.into(),
+                    is_exported(name),
+                    src,
+                    imports,
+                ));
+            }
+
+            // `var handler = func(...) { ... }`
+            "var_spec" => match single_func_literal(child) {
+                Some(lit) => {
+                    let name = field_text(child, "name", src);
+                    let mut f = function(lit, name.into(), is_exported(name), src, imports);
+                    f.metadata = meta(child, src);
+                    out.push(f);
+                }
+                None => out.extend(walk(child, src, imports)),
+            },
+
+            "type_spec" | "type_alias" => {
+                let name = field_text(child, "name", src);
+                let ty = child.child_by_field_name("type");
+                let contains = match ty {
+                    Some(t) if t.kind() == "struct_type" => fields(t, src, imports),
+                    Some(t) if t.kind() == "interface_type" => methods(t, src, imports),
+                    Some(t) => uses_types(&[t], src, imports, &HashSet::new()),
+                    None => Vec::new(),
+                };
+                out.push(Syntax {
+                    node: ASTNode::Type(Type {
+                        name: name.into(),
+                        public: is_exported(name),
+                        is_abstract: ty.is_some_and(|t| t.kind() == "interface_type"),
+                        decorators: vec![],
+                    }),
+                    metadata: meta(child, src),
+                    contains,
+                });
+            }
+
+            "call_expression" => {
+                let callee = child.child_by_field_name("function");
+                match callee.filter(|f| f.kind() != "func_literal") {
+                    Some(f) => {
+                        let name = resolve_call(dotted_name(f, src), imports);
+                        out.push(Syntax {
+                            node: if DYNAMIC_IMPORTS.contains(&&*name) {
+                                ASTNode::DynamicImport(DynamicImport {
+                                    module: literal_arg(child, src).map(Cow::Borrowed),
+                                })
+                            } else {
+                                ASTNode::Call(Call {
+                                    name,
+                                    tags: vec![],
+                                    spawned_by: None,
+                                })
+                            },
+                            metadata: meta(child, src),
+                            contains: vec![],
+                        });
+                        if let Some(args) = child.child_by_field_name("arguments") {
+                            out.extend(walk(args, src, imports));
+                        }
+                        // `a().b()`: the inner call sits in the callee.
+                        out.extend(walk(f, src, imports));
+                    }
+                    // `go func() { ... }()`
+                    None => out.extend(walk(child, src, imports)),
+                }
+            }
+
+            "comment" => {
+                out.push(Syntax {
+                    node: ASTNode::Comment,
+                    metadata: meta(child, src),
+                    contains: vec![],
+                });
+            }
+
+            // Names are already resolved; record the statement itself.
+            "import_declaration" => out.extend(import_nodes(child, src)),
+            "package_clause" => {}
+
+            // declarations, control flow, expressions — recurse through
+            _ => out.extend(walk(child, src, imports)),
+        }
+    }
+
+    out
+}
+
+/// A Function named `name` with `func`'s signature types and body.
+fn function<'s>(
+    func: Node,
+    name: Cow<'s, str>,
+    public: bool,
+    src: &'s [u8],
+    imports: &HashMap<String, String>,
+) -> Syntax<'s> {
+    let mut contains = signature_types(func, src, imports);
+    if let Some(body) = func.child_by_field_name("body") {
+        contains.extend(walk(body, src, imports));
+    }
+    Syntax {
+        node: ASTNode::Function(Function {
+            name,
+            public,
+            params: vec![],
+            return_type: None,
+            decorators: vec![],
+        }),
+        metadata: meta(func, src),
+        contains,
+    }
+}
+
+/// The receiver's type as written in a method label: `*Server` or
+/// `Server`, without type arguments (`*List[T]` → `*List`).
+fn receiver<'s>(method: Node, src: &'s [u8]) -> Option<Cow<'s, str>> {
+    let param = method.child_by_field_name("receiver")?.named_child(0)?;
+    let mut ty = param.child_by_field_name("type")?;
+    let pointer = ty.kind() == "pointer_type";
+    if pointer {
+        ty = ty.named_child(0)?;
+    }
+    if ty.kind() == "generic_type" {
+        ty = ty.child_by_field_name("type")?;
+    }
+    let name = text(ty, src);
+    Some(if pointer {
+        Cow::Owned(format!("*{name}"))
+    } else {
+        Cow::Borrowed(name)
+    })
+}
+
+/// The `func` literal of a `var f = func() {}` spec binding one name.
+fn single_func_literal(spec: Node) -> Option<Node> {
+    let mut cursor = spec.walk();
+    if spec.children_by_field_name("name", &mut cursor).count() != 1 {
+        return None;
+    }
+    let values = spec.child_by_field_name("value")?;
+    (values.named_child_count() == 1)
+        .then(|| values.named_child(0))
+        .flatten()
+        .filter(|v| v.kind() == "func_literal")
+}
+
+// ── Fields & Methods ────────────────────────────────────────────────
+
+/// Struct fields; an embedded type is a Field named after it.
+fn fields<'s>(strukt: Node, src: &'s [u8], imports: &HashMap<String, String>) -> Vec<Syntax<'s>> {
+    let mut out = Vec::new();
+    let Some(list) = strukt.named_child(0) else {
+        return out;
+    };
+    let mut cursor = list.walk();
+    for decl in list.named_children(&mut cursor) {
+        if decl.kind() != "field_declaration" {
+            continue;
+        }
+        let Some(ty) = decl.child_by_field_name("type") else {
+            continue;
+        };
+        let annotation = resolve_type(ty, src, imports);
+        let mut c = decl.walk();
+        let names: Vec<&str> = decl
+            .children_by_field_name("name", &mut c)
+            .map(|n| text(n, src))
+            .collect();
+        let names = if names.is_empty() {
+            vec![text(ty, src).trim_start_matches('*')]
+        } else {
+            names
+        };
+        for name in names {
+            out.push(Syntax {
+                node: ASTNode::Field(Field {
+                    name: name.into(),
+                    annotation: Some(annotation.clone()),
+                }),
+                metadata: meta(decl, src),
+                contains: vec![],
+            });
+        }
+    }
+    out
+}
+
+/// Interface methods as bodiless Functions; embedded interfaces and type
+/// sets as UsesType.
+fn methods<'s>(iface: Node, src: &'s [u8], imports: &HashMap<String, String>) -> Vec<Syntax<'s>> {
+    let mut out = Vec::new();
+    let mut cursor = iface.walk();
+    for elem in iface.named_children(&mut cursor) {
+        match elem.kind() {
+            "method_elem" => {
+                let name = field_text(elem, "name", src);
+                out.push(function(elem, name.into(), is_exported(name), src, imports));
+            }
+            "type_elem" => out.extend(uses_types(&[elem], src, imports, &HashSet::new())),
+            "comment" => out.push(Syntax {
+                node: ASTNode::Comment,
+                metadata: meta(elem, src),
+                contains: vec![],
+            }),
+            _ => {}
+        }
+    }
+    out
+}
+
+// ── Types ───────────────────────────────────────────────────────────
+
+/// Types every Go file can name without importing them.
+const PREDECLARED: &[&str] = &[
+    "any",
+    "bool",
+    "byte",
+    "comparable",
+    "complex64",
+    "complex128",
+    "error",
+    "float32",
+    "float64",
+    "int",
+    "int8",
+    "int16",
+    "int32",
+    "int64",
+    "rune",
+    "string",
+    "uint",
+    "uint8",
+    "uint16",
+    "uint32",
+    "uint64",
+    "uintptr",
+];
+
+/// One UsesType per distinct type named in the parameters or results,
+/// skipping the function's own type parameters.
+fn signature_types<'s>(
+    func: Node,
+    src: &'s [u8],
+    imports: &HashMap<String, String>,
+) -> Vec<Syntax<'s>> {
+    let mut generics = HashSet::new();
+    if let Some(tp) = func.child_by_field_name("type_parameters") {
+        let mut cursor = tp.walk();
+        for decl in tp.named_children(&mut cursor) {
+            let mut c = decl.walk();
+            generics.extend(
+                decl.children_by_field_name("name", &mut c)
+                    .map(|n| text(n, src)),
+            );
+        }
+    }
+    let types: Vec<Node> = ["parameters", "result"]
+        .iter()
+        .filter_map(|f| func.child_by_field_name(f))
+        .collect();
+    uses_types(&types, src, imports, &generics)
+}
+
+fn uses_types<'s>(
+    nodes: &[Node],
+    src: &'s [u8],
+    imports: &HashMap<String, String>,
+    skip: &HashSet<&str>,
+) -> Vec<Syntax<'s>> {
+    let mut names = Vec::new();
+    for &n in nodes {
+        type_names(n, src, imports, &mut names);
+    }
+    let mut seen = HashSet::new();
+    let mut out = Vec::new();
+    for (node, name) in names {
+        if skip.contains(&*name) || !seen.insert(name.clone()) {
+            continue;
+        }
+        out.push(Syntax {
+            node: ASTNode::UsesType(UsesType { name }),
+            metadata: meta(node, src),
+            contains: vec![],
+        });
+    }
+    out
+}
+
+/// Render a type with imported package names replaced by their paths:
+/// `*http.Request` → `*net/http.Request`.
+fn resolve_type<'s>(node: Node, src: &'s [u8], imports: &HashMap<String, String>) -> Cow<'s, str> {
+    let mut names = Vec::new();
+    type_names(node, src, imports, &mut names);
+    if names.iter().all(|(_, n)| matches!(n, Cow::Borrowed(_))) {
+        return Cow::Borrowed(text(node, src));
+    }
+    let mut out = String::new();
+    let mut pos = node.start_byte();
+    for (name, resolved) in names {
+        out.push_str(std::str::from_utf8(&src[pos..name.start_byte()]).unwrap_or(""));
+        out.push_str(&resolved);
+        pos = name.end_byte();
+    }
+    out.push_str(std::str::from_utf8(&src[pos..node.end_byte()]).unwrap_or(""));
+    Cow::Owned(out)
+}
+
+/// `(node, resolved)` for each named, non-predeclared type in a type
+/// expression.
+fn type_names<'a, 's>(
+    node: Node<'a>,
+    src: &'s [u8],
+    imports: &HashMap<String, String>,
+    out: &mut Vec<(Node<'a>, Cow<'s, str>)>,
+) {
+    match node.kind() {
+        "type_identifier" => {
+            let name = text(node, src);
+            if !PREDECLARED.contains(&name) {
+                out.push((node, Cow::Borrowed(name)));
+            }
+        }
+        "qualified_type" => {
+            let package = field_text(node, "package", src);
+            let name = field_text(node, "name", src);
+            let joined = join(text(node, src), package, ".", name);
+            out.push((node, resolve_call(joined, imports)));
+        }
+        // Parameter names are identifiers, never types.
+        _ => {
+            let mut cursor = node.walk();
+            for child in node.named_children(&mut cursor) {
+                type_names(child, src, imports, out);
+            }
+        }
+    }
+}
+
+// ── Dynamic Imports ─────────────────────────────────────────────────
+
+/// Resolved call names that load code at runtime.
+const DYNAMIC_IMPORTS: &[&str] = &["plugin.Open"];
+
+/// The first argument of a call, if it is a string literal.
+fn literal_arg<'s>(call: Node, src: &'s [u8]) -> Option<&'s str> {
+    let args = call.child_by_field_name("arguments")?;
+    args.named_child(0).and_then(|n| string_value(n, src))
+}
+
+/// The contents of a string literal; `None` for escapes and non-strings.
+fn string_value<'s>(node: Node, src: &'s [u8]) -> Option<&'s str> {
+    if !matches!(
+        node.kind(),
+        "interpreted_string_literal" | "raw_string_literal"
+    ) {
+        return None;
+    }
+    let mut cursor = node.walk();
+    let mut content = None;
+    for part in node.named_children(&mut cursor) {
+        match part.kind() {
+            "interpreted_string_literal_content" | "raw_string_literal_content"
+                if content.is_none() =>
+            {
+                content = Some(text(part, src));
+            }
+            _ => return None,
+        }
+    }
+    Some(content.unwrap_or(""))
+}
+
+/// Every plain string literal outside imports and struct tags.
+fn collect_strings<'a, 's>(node: Node<'a>, src: &'s [u8], out: &mut Vec<(Node<'a>, Cow<'s, str>)>) {
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        match child.kind() {
+            "interpreted_string_literal" | "raw_string_literal" => {
+                out.extend(string_value(child, src).map(|v| (child, v.into())));
+            }
+            "import_declaration" => {}
+            "field_declaration" => {
+                if let Some(ty) = child.child_by_field_name("type") {
+                    collect_strings(ty, src, out);
+                }
+            }
+            _ => collect_strings(child, src, out),
+        }
+    }
+}
+
+// ── Helpers ─────────────────────────────────────────────────────────
+
+fn text<'s>(node: Node, src: &'s [u8]) -> &'s str {
+    node.utf8_text(src).unwrap_or("")
+}
+
+fn field_text<'s>(node: Node, field: &str, src: &'s [u8]) -> &'s str {
+    node.child_by_field_name(field)
+        .map(|n| text(n, src))
+        .unwrap_or("")
+}
+
+/// Resolve `a.b.c` from nested selector expressions. Borrowed unless the
+/// source spells it with whitespace or comments between the segments.
+fn dotted_name<'s>(node: Node, src: &'s [u8]) -> Cow<'s, str> {
+    let whole = text(node, src);
+    if node.kind() != "selector_expression" {
+        return Cow::Borrowed(whole);
+    }
+    let operand = node
+        .child_by_field_name("operand")
+        .map(|n| dotted_name(n, src))
+        .unwrap_or_default();
+    join(whole, &operand, ".", field_text(node, "field", src))
+}
+
+/// Replace a leading package name with its import path.
+fn resolve_call<'s>(name: Cow<'s, str>, imports: &HashMap<String, String>) -> Cow<'s, str> {
+    let Some((head, rest)) = name.split_once('.') else {
+        return name;
+    };
+    match imports.get(head) {
+        Some(path) if path != head => Cow::Owned(format!("{path}.{rest}")),
+        _ => name,
+    }
+}
+
+/// Exported: the name starts with an upper-case letter.
+fn is_exported(name: &str) -> bool {
+    name.chars().next().is_some_and(char::is_uppercase)
+}
+
+fn meta(node: Node, src: &[u8]) -> Metadata {
+    metadata_from_node(src, node)
+}
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::tree_sitter::{Lang, Strings};
+
+    fn parse(source: &str) -> Vec<Syntax<'_>> {
+        let lang = Go;
+        let mut parser = lang.get_parser();
+        let (nodes, errors) = lang
+            .parse_checked(&mut parser, source, &Options::default())
+            .unwrap();
+        assert!(errors.is_empty(), "syntax errors in {source}");
+        nodes
+    }
+
+    fn names(nodes: &[Syntax]) -> Vec<String> {
+        nodes
+            .iter()
+            .map(|s| match &s.node {
+                ASTNode::Function(f) if f.public => format!("pub fn:{}", f.name),
+                ASTNode::Function(f) => format!("fn:{}", f.name),
+                ASTNode::Type(t) if t.is_abstract => format!("interface:{}", t.name),
+                ASTNode::Type(t) => format!("ty:{}", t.name),
+                ASTNode::Field(f) => format!(
+                    "field:{}:{}",
+                    f.name,
+                    f.annotation.as_deref().unwrap_or("?")
+                ),
+                ASTNode::Call(c) => format!("call:{}", c.name),
+                ASTNode::UsesType(u) => format!("uses:{}", u.name),
+                ASTNode::Import(i) => {
+                    let mut name = format!("import:{}", i.module);
+                    if !i.items.is_empty() {
+                        name += &format!(":{}", i.items.join(","));
+                    }
+                    if let Some(alias) = &i.alias {
+                        name += &format!(" as {alias}");
+                    }
+                    name
+                }
+                ASTNode::DynamicImport(d) => {
+                    format!("dyn:{}", d.module.as_deref().unwrap_or("?"))
+                }
+                ASTNode::Literal(l) => format!("str:{}", l.value),
+                ASTNode::Comment => "comment".into(),
+                other => format!("{other:?}"),
+            })
+            .collect()
+    }
+
+    // ── Definitions ──
+
+    #[test]
+    fn empty_file() {
+        assert!(parse("package main\n").is_empty());
+    }
+
+    #[test]
+    fn functions_and_exports() {
+        let nodes = parse("package p\n\nfunc Run() {\n\tstep()\n}\n\nfunc step() {}\n");
+        assert_eq!(names(&nodes), ["pub fn:Run", "fn:step"]);
+        assert_eq!(names(&nodes[0].contains), ["call:step"]);
+    }
+
+    #[test]
+    fn methods_labeled_by_receiver() {
+        let src = "package p\n\nfunc (s *Server) Handle() {}\n\
+                   func (Server) name() string { return \"\" }\n\
+                   func (l *List[T]) Push(v T) {}\n";
+        assert_eq!(
+            names(&parse(src)),
+            [
+                "pub fn:(*Server).Handle",
+                "fn:Server.name",
+                "pub fn:(*List).Push"
+            ]
+        );
+    }
+
+    #[test]
+    fn structs_and_fields() {
+        let src = "package p\n\nimport h \"net/http\"\n\n\
+                   type Server struct {\n\tAddr, host string\n\tmux *h.ServeMux\n\th.Handler\n}\n";
+        let nodes = parse(src);
+        assert_eq!(names(&nodes), ["import:net/http as h", "ty:Server"]);
+        assert_eq!(
+            names(&nodes[1].contains),
+            [
+                "field:Addr:string",
+                "field:host:string",
+                "field:mux:*net/http.ServeMux",
+                "field:h.Handler:net/http.Handler",
+            ]
+        );
+    }
+
+    #[test]
+    fn interfaces_are_abstract() {
+        let src = "package p\n\nimport \"fmt\"\n\n\
+                   type Handler interface {\n\tHandle(r *Request) error\n\tfmt.Stringer\n}\n";
+        let nodes = parse(src);
+        assert_eq!(names(&nodes), ["import:fmt", "interface:Handler"]);
+        assert_eq!(
+            names(&nodes[1].contains),
+            ["pub fn:Handle", "uses:fmt.Stringer"]
+        );
+        assert_eq!(names(&nodes[1].contains[0].contains), ["uses:Request"]);
+    }
+
+    #[test]
+    fn aliases_and_defined_types() {
+        let nodes = parse("package p\n\ntype ID = int64\ntype Names []Name\n");
+        assert_eq!(names(&nodes), ["ty:ID", "ty:Names"]);
+        assert!(nodes[0].contains.is_empty());
+        assert_eq!(names(&nodes[1].contains), ["uses:Name"]);
+    }
+
+    #[test]
+    fn signature_types_skip_predeclared_and_type_parameters() {
+        let src = "package p\n\nfunc Map[T any, U any](xs []T, f func(T) U, n int) (*Result, error) { return nil, nil }\n";
+        assert_eq!(names(&parse(src)[0].contains), ["uses:Result"]);
+    }
+
+    #[test]
+    fn func_literals() {
+        let src = "package p\n\nvar handler = func() {\n\tserve()\n}\n\n\
+                   func Run() {\n\tgo func() { work() }()\n}\n";
+        let nodes = parse(src);
+        assert_eq!(names(&nodes), ["fn:handler", "pub fn:Run"]);
+        assert_eq!(names(&nodes[0].contains), ["call:serve"]);
+        assert_eq!(names(&nodes[1].contains), ["call:work"]);
+    }
+
+    #[test]
+    fn nested_calls() {
+        let src = "package p\n\nfunc f() {\n\tfmt.Println(strings.ToUpper(x()).Trim())\n}\n";
+        assert_eq!(
+            names(&parse(src)[0].contains),
+            [
+                "call:fmt.Println",
+                "call:strings.ToUpper(x()).Trim",
+                "call:strings.ToUpper",
+                "call:x"
+            ]
+        );
+    }
+
+    // ── Imports ──
+
+    #[test]
+    fn imports_resolve_calls() {
+        let src = "package p\n\nimport (\n\t\"fmt\"\n\th \"net/http\"\n\t. \"strings\"\n\t\
+                   _ \"embed\"\n\t\"github.com/acme/store\"\n)\n\n\
+                   func f(s *Server) {\n\tfmt.Println()\n\th.ListenAndServe()\n\tstore.Open()\n\t\
+                   ToUpper()\n\ts.store.Get()\n}\n";
+        let nodes = parse(src);
+        assert_eq!(
+            names(&nodes[..5]),
+            [
+                "import:fmt",
+                "import:net/http as h",
+                "import:strings as .",
+                "import:embed as _",
+                "import:github.com/acme/store"
+            ]
+        );
+        assert_eq!(nodes[1].metadata.span.start_line, 5);
+        assert_eq!(
+            names(&nodes[5].contains),
+            [
+                "uses:Server",
+                "call:fmt.Println",
+                "call:net/http.ListenAndServe",
+                "call:github.com/acme/store.Open",
+                "call:ToUpper",
+                "call:s.store.Get"
+            ]
+        );
+    }
+
+    #[test]
+    fn plugins_are_dynamic_imports() {
+        let src = "package p\n\nimport \"plugin\"\n\nfunc f(p string) {\n\t\
+                   plugin.Open(\"auth.so\")\n\tplugin.Open(p)\n}\n";
+        assert_eq!(names(&parse(src)[1].contains), ["dyn:auth.so", "dyn:?"]);
+    }
+
+    // ── Strings ──
+
+    #[test]
+    fn strings_skip_imports_and_tags() {
+        let src = "package p\n\nimport \"os\"\n\ntype T struct {\n\tA int `json:\"a\"`\n}\n\n\
+                   func f() {\n\tos.Open(\"/etc/hosts\")\n}\n";
+        let mut parser = Go.get_parser();
+        let opts = Options {
+            strings: Some(Strings::All),
+            ..Options::default()
+        };
+        let nodes = Go.parse(&mut parser, src, &opts).unwrap();
+        assert_eq!(
+            names(&nodes[2].contains),
+            ["call:os.Open", "str:/etc/hosts"]
+        );
+        assert!(
+            names(&nodes[1].contains)
+                .iter()
+                .all(|n| !n.starts_with("str:"))
+        );
+    }
+}
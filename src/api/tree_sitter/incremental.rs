@@ -0,0 +1,202 @@
+use super::{Lang, Options, ParseError, Span, Syntax};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tree_sitter::{InputEdit, Parser, Point, Tree};
+
+/// Keeps the last tree per file so repeated parses of a changing file only
+/// redo the edited region.
+///
+/// Each [`parse`](Incremental::parse) diffs the new source against the one
+/// seen last time, applies the changed byte range to the old tree as an
+/// [`InputEdit`], and hands that tree to tree-sitter as the starting point.
+pub struct Incremental<L: Lang> {
+    lang: L,
+    parser: Parser,
+    trees: HashMap<PathBuf, (String, Tree)>,
+}
+
+impl<L: Lang> Incremental<L> {
+    pub fn new(lang: L) -> Self {
+        let parser = lang.get_parser();
+        Self {
+            lang,
+            parser,
+            trees: HashMap::new(),
+        }
+    }
+
+    /// Parse `source` as the current contents of `path`, reusing the previous
+    /// tree for that path when there is one.
+    pub fn parse<'s>(
+        &mut self,
+        path: &Path,
+        source: &'s str,
+        opts: &Options,
+    ) -> Result<(Vec<Syntax<'s>>, Vec<Span>), ParseError> {
+        let old = match self.trees.get_mut(path) {
+            Some((text, tree)) => {
+                if let Some(edit) = edit(text, source) {
+                    tree.edit(&edit);
+                }
+                Some(&*tree)
+            }
+            None => None,
+        };
+        let tree = self
+            .parser
+            .parse(source, old)
+            .ok_or_else(|| ParseError("parse returned None".into()))?;
+        let out = self.lang.extract(&tree, source, opts);
+        self.trees
+            .insert(path.to_path_buf(), (source.to_string(), tree));
+        Ok(out)
+    }
+
+    /// Drop the cached tree for a deleted or closed file.
+    pub fn forget(&mut self, path: &Path) {
+        self.trees.remove(path);
+    }
+
+    pub fn len(&self) -> usize {
+        self.trees.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.trees.is_empty()
+    }
+}
+
+/// The single contiguous edit turning `old` into `new`, or `None` if they are
+/// equal. Found by trimming the common prefix and suffix.
+fn edit(old: &str, new: &str) -> Option<InputEdit> {
+    let (a, b) = (old.as_bytes(), new.as_bytes());
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    if prefix == a.len() && prefix == b.len() {
+        return None;
+    }
+    let max_suffix = a.len().min(b.len()) - prefix;
+    let suffix = a
+        .iter()
+        .rev()
+        .zip(b.iter().rev())
+        .take(max_suffix)
+        .take_while(|(x, y)| x == y)
+        .count();
+    let old_end = a.len() - suffix;
+    let new_end = b.len() - suffix;
+    Some(InputEdit {
+        start_byte: prefix,
+        old_end_byte: old_end,
+        new_end_byte: new_end,
+        start_position: point(a, prefix),
+        old_end_position: point(a, old_end),
+        new_end_position: point(b, new_end),
+    })
+}
+
+/// Row and byte column of `offset` in `text`.
+fn point(text: &[u8], offset: usize) -> Point {
+    let before = &text[..offset];
+    let row = before.iter().filter(|&&b| b == b'\n').count();
+    let column = match before.iter().rposition(|&b| b == b'\n') {
+        Some(nl) => offset - nl - 1,
+        None => offset,
+    };
+    Point { row, column }
+}
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::tree_sitter::py::Python;
+    use crate::api::tree_sitter::rs::Rust;
+
+    fn json(nodes: &[Syntax]) -> String {
+        serde_json::to_string(nodes).unwrap()
+    }
+
+    // ── edit ──
+
+    #[test]
+    fn edit_none_for_equal_text() {
+        assert!(edit("abc", "abc").is_none());
+    }
+
+    #[test]
+    fn edit_insertion() {
+        let e = edit("ab\ncd\n", "ab\ncXd\n").unwrap();
+        assert_eq!((e.start_byte, e.old_end_byte, e.new_end_byte), (4, 4, 5));
+        assert_eq!(e.start_position, Point { row: 1, column: 1 });
+        assert_eq!(e.new_end_position, Point { row: 1, column: 2 });
+    }
+
+    #[test]
+    fn edit_deletion() {
+        let e = edit("ab\ncd\nef\n", "ab\nef\n").unwrap();
+        assert_eq!((e.start_byte, e.old_end_byte, e.new_end_byte), (3, 6, 3));
+        assert_eq!(e.old_end_position, Point { row: 2, column: 0 });
+    }
+
+    #[test]
+    fn edit_repeated_text_does_not_overlap() {
+        // prefix and suffix both match "aa"; the suffix must stop at the prefix
+        let e = edit("aa", "aaa").unwrap();
+        assert_eq!((e.start_byte, e.old_end_byte, e.new_end_byte), (2, 2, 3));
+    }
+
+    // ── parse ──
+
+    #[test]
+    fn reparse_matches_fresh_parse() {
+        let path = Path::new("a.py");
+        let versions = [
+            "def f():\n    g()\n",
+            "def f():\n    g()\n    h()\n",
+            "import os\n\ndef f():\n    os.h()\n",
+            "def f(:\n",
+            "def f():\n    g()\n",
+            "",
+        ];
+        let mut inc = Incremental::new(Python);
+        let mut fresh = Python.get_parser();
+        for source in versions {
+            let (nodes, errors) = inc.parse(path, source, &Options::default()).unwrap();
+            let (want, want_errors) = Python
+                .parse_checked(&mut fresh, source, &Options::default())
+                .unwrap();
+            assert_eq!(json(&nodes), json(&want), "source: {source:?}");
+            assert_eq!(errors, want_errors);
+        }
+    }
+
+    #[test]
+    fn reparse_rust_matches_fresh_parse() {
+        let path = Path::new("lib.rs");
+        let versions = [
+            "fn a() { b(); }\n",
+            "fn a() { b(); c(); }\n",
+            "struct S { x: u8 }\nfn a() { b(); c(); }\n",
+        ];
+        let mut inc = Incremental::new(Rust);
+        let mut fresh = Rust.get_parser();
+        for source in versions {
+            let (nodes, _) = inc.parse(path, source, &Options::default()).unwrap();
+            let want = Rust.parse(&mut fresh, source, &Options::default()).unwrap();
+            assert_eq!(json(&nodes), json(&want), "source: {source:?}");
+        }
+    }
+
+    #[test]
+    fn trees_kept_per_path() {
+        let mut inc = Incremental::new(Python);
+        inc.parse(Path::new("a.py"), "x = 1\n", &Options::default())
+            .unwrap();
+        inc.parse(Path::new("b.py"), "y = 2\n", &Options::default())
+            .unwrap();
+        assert_eq!(inc.len(), 2);
+        inc.forget(Path::new("a.py"));
+        assert_eq!(inc.len(), 1);
+    }
+}
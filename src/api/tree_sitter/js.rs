@@ -0,0 +1,802 @@
+use super::ts;
+use super::{
+    ASTNode, Call, DynamicImport, Field, Function, Import, Metadata, Options, Span, Syntax, Type,
+    join, metadata_from_node, syntax_errors,
+};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use tree_sitter::{Node, Parser, Tree};
+
+pub struct JavaScript;
+
+impl super::Lang for JavaScript {
+    fn get_parser(&self) -> Parser {
+        let mut parser = Parser::new();
+        parser
+            .set_language(&tree_sitter_javascript::LANGUAGE.into())
+            .expect("failed to load javascript grammar");
+        parser
+    }
+
+    fn extract<'s>(
+        &self,
+        tree: &Tree,
+        source: &'s str,
+        opts: &Options,
+    ) -> (Vec<Syntax<'s>>, Vec<Span>) {
+        extract(tree, source, opts)
+    }
+}
+
+/// [`Lang::extract`](super::Lang::extract) for JavaScript and TypeScript,
+/// whose grammar extends JavaScript's; see [`super::ts`] for the
+/// TypeScript-only declarations.
+pub(super) fn extract<'s>(
+    tree: &Tree,
+    source: &'s str,
+    opts: &Options,
+) -> (Vec<Syntax<'s>>, Vec<Span>) {
+    let src = source.as_bytes();
+    let root = tree.root_node();
+    let imports = collect_imports(root, src);
+    let mut out = walk(root, src, &imports);
+    if let Some(which) = opts.strings {
+        let mut literals = Vec::new();
+        collect_strings(root, src, &mut literals);
+        super::attach_literals(&mut out, literals, src, which);
+    }
+    (out, syntax_errors(root))
+}
+
+// ── Import Collection ───────────────────────────────────────────────
+
+/// Local names bound by top-level `import` statements and `require` calls,
+/// mapped to `module.name` the way the Python backend maps `from m import x`.
+fn collect_imports(root: Node, src: &[u8]) -> HashMap<String, String> {
+    let mut imports = HashMap::new();
+    let mut cursor = root.walk();
+    for child in root.named_children(&mut cursor) {
+        match child.kind() {
+            "import_statement" => import_statement(child, src, &mut imports),
+            "lexical_declaration" | "variable_declaration" => {
+                let mut c = child.walk();
+                for decl in child.named_children(&mut c) {
+                    if decl.kind() == "variable_declarator" {
+                        require_declarator(decl, src, &mut imports);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    imports
+}
+
+/// `import x from "m"`, `import * as ns from "m"`, `import { a, b as c } from "m"`;
+/// in TypeScript also `import type { T } from "m"` and `import x = require("m")`.
+fn import_statement(node: Node, src: &[u8], imports: &mut HashMap<String, String>) {
+    let mut cursor = node.walk();
+    let Some(clause) = node
+        .named_children(&mut cursor)
+        .find(|n| matches!(n.kind(), "import_clause" | "import_require_clause"))
+    else {
+        return;
+    };
+    if clause.kind() == "import_require_clause" {
+        let alias = clause.named_child(0).map(|n| text(n, src));
+        let module = clause
+            .child_by_field_name("source")
+            .and_then(|s| string_value(s, src));
+        if let (Some(alias), Some(module)) = (alias, module) {
+            imports.insert(alias.to_string(), module.to_string());
+        }
+        return;
+    }
+    let Some(module) = node
+        .child_by_field_name("source")
+        .and_then(|s| string_value(s, src))
+    else {
+        return;
+    };
+    let mut cursor = clause.walk();
+    for part in clause.named_children(&mut cursor) {
+        match part.kind() {
+            "identifier" => {
+                let name = part.utf8_text(src).unwrap_or("");
+                imports.insert(name.to_string(), qualify(module, "default"));
+            }
+            "namespace_import" => {
+                if let Some(alias) = part.named_child(0).and_then(|n| n.utf8_text(src).ok()) {
+                    imports.insert(alias.to_string(), module.to_string());
+                }
+            }
+            "named_imports" => {
+                let mut c = part.walk();
+                for spec in part.named_children(&mut c) {
+                    if spec.kind() != "import_specifier" {
+                        continue;
+                    }
+                    let name = spec
+                        .child_by_field_name("name")
+                        .map(|n| string_value(n, src).unwrap_or_else(|| text(n, src)))
+                        .unwrap_or("");
+                    let alias = field_text(spec, "alias", src);
+                    let key = if alias.is_empty() { name } else { alias };
+                    imports.insert(key.to_string(), qualify(module, name));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The Imports of an `import` statement: one for the unrenamed named
+/// imports, and one per default, namespace or renamed binding.
+fn import_nodes<'s>(node: Node, src: &'s [u8]) -> Vec<Syntax<'s>> {
+    let import = |module: &'s str, items: Vec<&'s str>, alias: Option<&'s str>| Syntax {
+        node: ASTNode::Import(Import {
+            module: Cow::Borrowed(module),
+            items: items.into_iter().map(Cow::Borrowed).collect(),
+            alias: alias.map(Cow::Borrowed),
+        }),
+        metadata: meta(node, src),
+        contains: vec![],
+    };
+    let mut cursor = node.walk();
+    let clause = node
+        .named_children(&mut cursor)
+        .find(|n| matches!(n.kind(), "import_clause" | "import_require_clause"));
+    if let Some(clause) = clause.filter(|c| c.kind() == "import_require_clause") {
+        let module = clause
+            .child_by_field_name("source")
+            .and_then(|s| string_value(s, src));
+        let alias = clause.named_child(0).map(|n| text(n, src));
+        return module
+            .map(|m| import(m, vec![], alias))
+            .into_iter()
+            .collect();
+    }
+    let Some(module) = node
+        .child_by_field_name("source")
+        .and_then(|s| string_value(s, src))
+    else {
+        return vec![];
+    };
+    let Some(clause) = clause else {
+        // `import "m"` for its side effects
+        return vec![import(module, vec![], None)];
+    };
+    let mut out = Vec::new();
+    let mut cursor = clause.walk();
+    for part in clause.named_children(&mut cursor) {
+        match part.kind() {
+            "identifier" => out.push(import(module, vec!["default"], Some(text(part, src)))),
+            "namespace_import" => {
+                let alias = part.named_child(0).map(|n| text(n, src));
+                out.push(import(module, vec![], alias));
+            }
+            "named_imports" => {
+                let mut items = Vec::new();
+                let mut renamed = Vec::new();
+                let mut c = part.walk();
+                for spec in part.named_children(&mut c) {
+                    if spec.kind() != "import_specifier" {
+                        continue;
+                    }
+                    let name = spec
+                        .child_by_field_name("name")
+                        .map(|n| string_value(n, src).unwrap_or_else(|| text(n, src)))
+                        .unwrap_or("");
+                    match spec.child_by_field_name("alias") {
+                        Some(alias) => {
+                            renamed.push(import(module, vec![name], Some(text(alias, src))))
+                        }
+                        None => items.push(name),
+                    }
+                }
+                if !items.is_empty() {
+                    out.push(import(module, items, None));
+                }
+                out.extend(renamed);
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+/// `const x = require("m")`, `const { a, b: c } = require("m")` and
+/// `const y = require("m").y`.
+fn require_declarator(decl: Node, src: &[u8], imports: &mut HashMap<String, String>) {
+    let (Some(name), Some(value)) = (
+        decl.child_by_field_name("name"),
+        decl.child_by_field_name("value"),
+    ) else {
+        return;
+    };
+    let target = match value.kind() {
+        "member_expression" => value
+            .child_by_field_name("object")
+            .and_then(|o| require_module(o, src))
+            .map(|m| qualify(m, field_text(value, "property", src))),
+        _ => require_module(value, src).map(str::to_string),
+    };
+    let Some(target) = target else {
+        return;
+    };
+    match name.kind() {
+        "identifier" => {
+            imports.insert(text(name, src).to_string(), target);
+        }
+        "object_pattern" => {
+            let mut cursor = name.walk();
+            for prop in name.named_children(&mut cursor) {
+                match prop.kind() {
+                    "shorthand_property_identifier_pattern" => {
+                        let key = text(prop, src);
+                        imports.insert(key.to_string(), qualify(&target, key));
+                    }
+                    "pair_pattern" => {
+                        let key = field_text(prop, "key", src);
+                        let local = field_text(prop, "value", src);
+                        imports.insert(local.to_string(), qualify(&target, key));
+                    }
+                    _ => {}
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The module of a `require("m")` call.
+fn require_module<'s>(node: Node, src: &'s [u8]) -> Option<&'s str> {
+    if node.kind() != "call_expression" || field_text(node, "function", src) != "require" {
+        return None;
+    }
+    literal_arg(node, src)
+}
+
+fn qualify(module: &str, name: &str) -> String {
+    format!("{module}.{name}")
+}
+
+// ── AST Walk ────────────────────────────────────────────────────────
+
+pub(super) fn walk<'s>(
+    node: Node,
+    src: &'s [u8],
+    imports: &HashMap<String, String>,
+) -> Vec<Syntax<'s>> {
+    let mut out = Vec::new();
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        visit(child, src, imports, &mut out);
+    }
+    out
+}
+
+/// Nodes for `node` itself, so expression bodies (`() => g()`) and field
+/// initializers are seen whole rather than through their children.
+pub(super) fn visit<'s>(
+    child: Node,
+    src: &'s [u8],
+    imports: &HashMap<String, String>,
+    out: &mut Vec<Syntax<'s>>,
+) {
+    match child.kind() {
+        "function_declaration" | "generator_function_declaration" | "method_definition" => {
+            let name = field_text(child, "name", src);
+            out.push(function(child, child, name, src, imports));
+        }
+
+        "class_declaration" => {
+            let name = field_text(child, "name", src);
+            out.push(class(child, child, name, src, imports));
+        }
+
+        // TypeScript-only declarations.
+        "interface_declaration"
+        | "type_alias_declaration"
+        | "enum_declaration"
+        | "abstract_class_declaration"
+        | "method_signature"
+        | "abstract_method_signature"
+        | "property_signature"
+        | "public_field_definition" => out.push(ts::declaration(child, src, imports)),
+
+        // Overload and `declare` signatures; the implementation is the definition.
+        "function_signature" => {}
+
+        // `const f = () => ...`, `const C = class { ... }`
+        "variable_declarator" => {
+            let name = child.child_by_field_name("name");
+            let value = child.child_by_field_name("value");
+            match (name, value) {
+                (Some(n), Some(v)) if n.kind() == "identifier" => match v.kind() {
+                    "arrow_function" | "function_expression" | "generator_function" => {
+                        out.push(function(child, v, text(n, src), src, imports));
+                    }
+                    "class" => out.push(class(child, v, text(n, src), src, imports)),
+                    _ => out.extend(walk(child, src, imports)),
+                },
+                _ => out.extend(walk(child, src, imports)),
+            }
+        }
+
+        "field_definition" => {
+            let name = field_text(child, "property", src);
+            let mut contains = Vec::new();
+            if let Some(value) = child.child_by_field_name("value") {
+                visit(value, src, imports, &mut contains);
+            }
+            out.push(Syntax {
+                node: ASTNode::Field(Field {
+                    name: name.into(),
+                    annotation: None,
+                }),
+                metadata: meta(child, src),
+                contains,
+            });
+        }
+
+        "call_expression" => {
+            let Some(callee) = child.child_by_field_name("function") else {
+                return;
+            };
+            match callee.kind() {
+                "import" => out.push(dynamic_import(child, src, literal_arg(child, src))),
+                // A `require` of a literal is a static import, resolved in
+                // `collect_imports`; anything else loads at runtime.
+                "identifier" if text(callee, src) == "require" => {
+                    out.push(match literal_arg(child, src) {
+                        Some(module) => Syntax {
+                            node: ASTNode::Import(Import {
+                                module: Cow::Borrowed(module),
+                                items: vec![],
+                                alias: None,
+                            }),
+                            metadata: meta(child, src),
+                            contains: vec![],
+                        },
+                        None => dynamic_import(child, src, None),
+                    });
+                }
+                _ => out.push(call(child, callee, src, imports)),
+            }
+            out.extend(walk(child, src, imports));
+        }
+
+        "new_expression" => {
+            if let Some(ctor) = child.child_by_field_name("constructor") {
+                out.push(call(child, ctor, src, imports));
+            }
+            out.extend(walk(child, src, imports));
+        }
+
+        "comment" => {
+            out.push(Syntax {
+                node: ASTNode::Comment,
+                metadata: meta(child, src),
+                contains: vec![],
+            });
+        }
+
+        // Names are already resolved; record the statement itself.
+        "import_statement" => out.extend(import_nodes(child, src)),
+
+        // export statements, control flow, expressions — recurse through
+        _ => out.extend(walk(child, src, imports)),
+    }
+}
+
+/// A Function spanning `outer` (the declaration) with `inner`'s parameters
+/// and body, after the types its signature names (TypeScript).
+pub(super) fn function<'s>(
+    outer: Node,
+    inner: Node,
+    name: &'s str,
+    src: &'s [u8],
+    imports: &HashMap<String, String>,
+) -> Syntax<'s> {
+    let mut contains = ts::signature_types(inner, src, imports);
+    for field in ["parameters", "parameter", "body"] {
+        if let Some(n) = inner.child_by_field_name(field) {
+            visit(n, src, imports, &mut contains);
+        }
+    }
+    Syntax {
+        node: ASTNode::Function(Function {
+            name: name.into(),
+            public: is_public(outer, name),
+            params: vec![],
+            return_type: None,
+            decorators: vec![],
+        }),
+        metadata: meta(outer, src),
+        contains,
+    }
+}
+
+/// A Type spanning `outer` with `inner`'s members.
+pub(super) fn class<'s>(
+    outer: Node,
+    inner: Node,
+    name: &'s str,
+    src: &'s [u8],
+    imports: &HashMap<String, String>,
+) -> Syntax<'s> {
+    let contains = inner
+        .child_by_field_name("body")
+        .map(|b| walk(b, src, imports))
+        .unwrap_or_default();
+    Syntax {
+        node: ASTNode::Type(Type {
+            name: name.into(),
+            public: is_public(outer, name),
+            is_abstract: false,
+            decorators: vec![],
+        }),
+        metadata: meta(outer, src),
+        contains,
+    }
+}
+
+fn call<'s>(
+    node: Node,
+    callee: Node,
+    src: &'s [u8],
+    imports: &HashMap<String, String>,
+) -> Syntax<'s> {
+    let name = resolve_call(dotted_name(callee, src), imports);
+    Syntax {
+        node: ASTNode::Call(Call {
+            name,
+            tags: vec![],
+            spawned_by: None,
+        }),
+        metadata: meta(node, src),
+        contains: vec![],
+    }
+}
+
+fn dynamic_import<'s>(node: Node, src: &'s [u8], module: Option<&'s str>) -> Syntax<'s> {
+    Syntax {
+        node: ASTNode::DynamicImport(DynamicImport {
+            module: module.map(Cow::Borrowed),
+        }),
+        metadata: meta(node, src),
+        contains: vec![],
+    }
+}
+
+// ── Strings ─────────────────────────────────────────────────────────
+
+/// The first argument of a call, if it is a plain string literal.
+fn literal_arg<'s>(call: Node, src: &'s [u8]) -> Option<&'s str> {
+    let args = call.child_by_field_name("arguments")?;
+    args.named_child(0).and_then(|n| string_value(n, src))
+}
+
+/// The contents of a string literal; `None` for escapes, template strings
+/// and non-strings.
+fn string_value<'s>(node: Node, src: &'s [u8]) -> Option<&'s str> {
+    if node.kind() != "string" {
+        return None;
+    }
+    let mut cursor = node.walk();
+    let mut content = None;
+    for part in node.named_children(&mut cursor) {
+        match part.kind() {
+            "string_fragment" if content.is_none() => content = part.utf8_text(src).ok(),
+            _ => return None,
+        }
+    }
+    Some(content.unwrap_or(""))
+}
+
+/// Every plain string literal outside imports; template strings are skipped.
+fn collect_strings<'a, 's>(node: Node<'a>, src: &'s [u8], out: &mut Vec<(Node<'a>, Cow<'s, str>)>) {
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        match child.kind() {
+            "string" => out.extend(string_value(child, src).map(|v| (child, v.into()))),
+            "import_statement" => {}
+            "call_expression" if require_module(child, src).is_some() => {}
+            _ => collect_strings(child, src, out),
+        }
+    }
+}
+
+// ── Helpers ─────────────────────────────────────────────────────────
+
+pub(super) fn text<'s>(node: Node, src: &'s [u8]) -> &'s str {
+    node.utf8_text(src).unwrap_or("")
+}
+
+pub(super) fn field_text<'s>(node: Node, field: &str, src: &'s [u8]) -> &'s str {
+    node.child_by_field_name(field)
+        .map(|n| text(n, src))
+        .unwrap_or("")
+}
+
+/// Resolve `a.b.c` from nested member expressions. Borrowed unless the
+/// source spells it with whitespace, comments or `?.` between the segments.
+fn dotted_name<'s>(node: Node, src: &'s [u8]) -> Cow<'s, str> {
+    let whole = text(node, src);
+    if node.kind() != "member_expression" {
+        return Cow::Borrowed(whole);
+    }
+    let obj = node
+        .child_by_field_name("object")
+        .map(|n| dotted_name(n, src))
+        .unwrap_or_default();
+    join(whole, &obj, ".", field_text(node, "property", src))
+}
+
+/// Replace the first segment of a dotted call with its import mapping.
+pub(super) fn resolve_call<'s>(
+    name: Cow<'s, str>,
+    imports: &HashMap<String, String>,
+) -> Cow<'s, str> {
+    let (head, tail) = match name.split_once('.') {
+        Some((h, t)) => (h, Some(t)),
+        None => (&*name, None),
+    };
+    match imports.get(head) {
+        Some(module) => match tail {
+            Some(rest) => Cow::Owned(format!("{module}.{rest}")),
+            None => Cow::Owned(module.clone()),
+        },
+        None => name,
+    }
+}
+
+/// Exported from the module (`export function f`, `export const f = ...`),
+/// or a class or interface member not named `#private` or `_private` nor
+/// marked `private` or `protected`.
+pub(super) fn is_public(node: Node, name: &str) -> bool {
+    let parent = node.parent();
+    if parent.is_some_and(|p| matches!(p.kind(), "class_body" | "interface_body")) {
+        let mut cursor = node.walk();
+        let hidden = node
+            .children(&mut cursor)
+            .filter(|c| c.kind() == "accessibility_modifier")
+            .filter_map(|c| c.child(0))
+            .any(|k| matches!(k.kind(), "private" | "protected"));
+        return !hidden && !name.starts_with(['#', '_']);
+    }
+    // A declarator sits in a declaration, which sits in the export.
+    let holder = match parent {
+        Some(p) if node.kind() == "variable_declarator" => p.parent(),
+        p => p,
+    };
+    holder.is_some_and(|h| h.kind() == "export_statement")
+}
+
+pub(super) fn meta(node: Node, src: &[u8]) -> Metadata {
+    metadata_from_node(src, node)
+}
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::tree_sitter::{Lang, Strings};
+
+    fn parse(source: &str) -> Vec<Syntax<'_>> {
+        let lang = JavaScript;
+        let mut parser = lang.get_parser();
+        lang.parse(&mut parser, source, &Options::default())
+            .unwrap()
+    }
+
+    fn names(nodes: &[Syntax]) -> Vec<String> {
+        nodes
+            .iter()
+            .map(|s| match &s.node {
+                ASTNode::Function(f) => format!("fn:{}", f.name),
+                ASTNode::Type(t) => format!("ty:{}", t.name),
+                ASTNode::Field(f) => format!("field:{}", f.name),
+                ASTNode::Call(c) => format!("call:{}", c.name),
+                ASTNode::Import(i) => {
+                    let mut name = format!("import:{}", i.module);
+                    if !i.items.is_empty() {
+                        name += &format!(":{}", i.items.join(","));
+                    }
+                    if let Some(alias) = &i.alias {
+                        name += &format!(" as {alias}");
+                    }
+                    name
+                }
+                ASTNode::DynamicImport(d) => {
+                    format!("dyn:{}", d.module.as_deref().unwrap_or("?"))
+                }
+                ASTNode::Literal(l) => format!("str:{}", l.value),
+                ASTNode::Comment => "comment".into(),
+                other => format!("{other:?}"),
+            })
+            .collect()
+    }
+
+    fn calls(source: &str) -> Vec<String> {
+        let nodes = parse(source);
+        names(&nodes[nodes.len() - 1].contains)
+    }
+
+    // ── Definitions ──
+
+    #[test]
+    fn empty_source() {
+        assert!(parse("").is_empty());
+    }
+
+    #[test]
+    fn function_with_calls() {
+        let nodes = parse("function f(a) {\n  g(a);\n  a.b.c();\n}\n");
+        assert_eq!(names(&nodes), ["fn:f"]);
+        assert_eq!(names(&nodes[0].contains), ["call:g", "call:a.b.c"]);
+        assert_eq!(nodes[0].metadata.span.end_line, 4);
+    }
+
+    #[test]
+    fn arrow_and_function_expressions_named_by_binding() {
+        let nodes = parse("const f = () => g();\nlet h = function () {};\n");
+        assert_eq!(names(&nodes), ["fn:f", "fn:h"]);
+        assert_eq!(names(&nodes[0].contains), ["call:g"]);
+    }
+
+    #[test]
+    fn class_with_methods_and_fields() {
+        let src = "class A extends B {\n  x = make();\n  #y;\n  run() { this.x(); }\n}\n";
+        let nodes = parse(src);
+        assert_eq!(names(&nodes), ["ty:A"]);
+        assert_eq!(names(&nodes[0].contains), ["field:x", "field:#y", "fn:run"]);
+        assert_eq!(names(&nodes[0].contains[0].contains), ["call:make"]);
+        assert_eq!(names(&nodes[0].contains[2].contains), ["call:this.x"]);
+    }
+
+    #[test]
+    fn new_is_a_call() {
+        assert_eq!(
+            calls("function f() { return new Map(new m.Set()); }"),
+            ["call:Map", "call:m.Set"]
+        );
+    }
+
+    #[test]
+    fn nested_calls_and_callbacks() {
+        assert_eq!(
+            calls("function f() { app.get(\"/\", () => render(load())); }"),
+            ["call:app.get", "call:render", "call:load"]
+        );
+    }
+
+    #[test]
+    fn comments_kept() {
+        assert_eq!(
+            names(&parse("// a\n/** b */\nfunction f() {}\n")),
+            ["comment", "comment", "fn:f"]
+        );
+    }
+
+    // ── Visibility ──
+
+    fn public(nodes: &[Syntax]) -> Vec<bool> {
+        nodes
+            .iter()
+            .filter_map(|s| match &s.node {
+                ASTNode::Function(f) => Some(f.public),
+                ASTNode::Type(t) => Some(t.public),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn exported_declarations_public() {
+        let src = "export function a() {}\nfunction b() {}\nexport const c = () => 1;\n\
+                   const d = () => 2;\nexport default class E {}\n";
+        assert_eq!(public(&parse(src)), [true, false, true, false, true]);
+    }
+
+    #[test]
+    fn private_members_not_public() {
+        let nodes = parse("class A {\n  run() {}\n  _step() {}\n  #tick() {}\n}\n");
+        assert_eq!(public(&nodes[0].contains), [true, false, false]);
+    }
+
+    // ── Imports ──
+
+    #[test]
+    fn imports_resolve_calls() {
+        let src = "import def from \"./d\";\nimport * as ns from \"./n\";\n\
+                   import { a, b as c } from \"./m\";\n\
+                   function f() { def(); ns.run(); a(); c.x(); }\n";
+        assert_eq!(
+            calls(src),
+            [
+                "call:./d.default",
+                "call:./n.run",
+                "call:./m.a",
+                "call:./m.b.x"
+            ]
+        );
+    }
+
+    #[test]
+    fn require_resolves_calls() {
+        let src = "const fs = require(\"fs\");\nconst { join, resolve: r } = require(\"path\");\n\
+                   const read = require(\"./io\").read;\n\
+                   function f() { fs.readFileSync(); join(); r(); read(); }\n";
+        assert_eq!(
+            calls(src),
+            [
+                "call:fs.readFileSync",
+                "call:path.join",
+                "call:path.resolve",
+                "call:./io.read"
+            ]
+        );
+    }
+
+    #[test]
+    fn static_require_is_an_import() {
+        assert_eq!(
+            names(&parse("const fs = require(\"fs\");\n")),
+            ["import:fs"]
+        );
+    }
+
+    #[test]
+    fn import_statements_become_imports() {
+        let src = "import \"./polyfill\";\nimport React, { useState, useEffect as fx } from \"react\";\n\
+                   import * as path from \"path\";\n";
+        assert_eq!(
+            names(&parse(src)),
+            [
+                "import:./polyfill",
+                "import:react:default as React",
+                "import:react:useState",
+                "import:react:useEffect as fx",
+                "import:path as path"
+            ]
+        );
+    }
+
+    #[test]
+    fn runtime_loads_are_dynamic_imports() {
+        assert_eq!(
+            calls("function f(name) { import(\"./lazy\"); require(name); }"),
+            ["dyn:./lazy", "dyn:?"]
+        );
+    }
+
+    #[test]
+    fn nested_imports_ignored() {
+        // Like Python, only module-level imports resolve.
+        assert_eq!(
+            calls("function f() { const x = require(\"x\"); x.y(); }"),
+            ["import:x", "call:x.y"]
+        );
+    }
+
+    // ── Strings ──
+
+    #[test]
+    fn strings_skip_imports_and_templates() {
+        let src = "import a from \"./a\";\nconst b = require(\"./b\");\n\
+                   function f() {\n  get(\"/api/x\");\n  log(`t ${a}`);\n}\n";
+        let mut parser = JavaScript.get_parser();
+        let opts = Options {
+            strings: Some(Strings::All),
+            ..Options::default()
+        };
+        let nodes = JavaScript.parse(&mut parser, src, &opts).unwrap();
+        assert_eq!(
+            names(&nodes[2].contains),
+            ["call:get", "str:/api/x", "call:log"]
+        );
+    }
+}
@@ -1,9 +1,22 @@
+pub mod go;
+pub mod incremental;
+pub mod js;
 pub mod py;
 pub mod rs;
+pub mod ts;
 
-use serde::Serialize;
+use crate::api::fs::Shard;
+use crate::clean::CommentPolicy;
+use crate::pipeline::Pipeline;
+use serde::de::{DeserializeOwned, Error as _};
+use serde::ser::{Error as _, SerializeMap, SerializeStruct};
+use serde::{Deserialize, Serialize, Serializer};
+use serde_json::{Map, Value};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 use std::fmt;
-use tree_sitter::Parser;
+use std::path::PathBuf;
+use tree_sitter::{Node, Parser, Tree};
 
 // ── Error ────────────────────────────────────────────────────────────
 
@@ -20,56 +33,596 @@ impl std::error::Error for ParseError {}
 
 // ── Core Types ───────────────────────────────────────────────────────
 
-#[derive(Debug, Clone, Serialize)]
-pub struct Syntax {
-    pub node: ASTNode,
+/// A node and its children.
+///
+/// Names and other text borrow from the parsed source where they appear in it
+/// verbatim, so iterating a parse allocates little. Use [`Syntax::into_owned`]
+/// to keep nodes past the source buffer, e.g. to serialize them later;
+/// deserialized nodes are always owned.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Syntax<'src> {
+    pub node: ASTNode<'src>,
     pub metadata: Metadata,
-    pub contains: Vec<Syntax>,
+    pub contains: Vec<Syntax<'src>>,
 }
 
-#[derive(Debug, Clone, Serialize)]
-pub enum ASTNode {
-    File(File),
-    Function(Function),
-    Type(Type),
-    Call(Call),
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum ASTNode<'src> {
+    File(File<'src>),
+    Function(Function<'src>),
+    Type(Type<'src>),
+    Field(Field<'src>),
+    Variant(Variant<'src>),
+    Call(Call<'src>),
+    UsesType(UsesType<'src>),
+    Module(Module<'src>),
+    Import(Import<'src>),
+    DynamicImport(DynamicImport<'src>),
+    Endpoint(Endpoint<'src>),
+    Fixture(Fixture<'src>),
+    UsesFixture(UsesFixture<'src>),
+    Unsafe(Unsafe),
+    Attribute(Attribute<'src>),
+    Exception(Exception<'src>),
+    Literal(Literal<'src>),
     Comment,
+    /// A kind of node this version does not know, from an analysis written
+    /// by another; kept as read so it is written back out unchanged.
+    #[serde(untagged)]
+    Unknown(Value),
+}
+
+impl Syntax<'_> {
+    /// Detach from the source buffer, copying any borrowed text.
+    pub fn into_owned(self) -> Syntax<'static> {
+        Syntax {
+            node: self.node.into_owned(),
+            metadata: self.metadata,
+            contains: self.contains.into_iter().map(Syntax::into_owned).collect(),
+        }
+    }
+}
+
+impl Syntax<'static> {
+    /// Read a node written by any bolo version, keeping the fields this one
+    /// does not know in [`Metadata::unknown`] so they are written back out.
+    pub fn from_value(value: Value) -> serde_json::Result<Self> {
+        let Value::Object(mut rest) = value else {
+            return serde_json::from_value(value);
+        };
+        let mut take = |key: &'static str| {
+            rest.remove(key)
+                .ok_or_else(|| serde_json::Error::missing_field(key))
+        };
+        let (node, node_extra) = split(take("node")?, payload)?;
+        let (mut metadata, metadata_extra): (Metadata, _) = split(take("metadata")?, object)?;
+        let contains = match take("contains")? {
+            Value::Array(items) => items
+                .into_iter()
+                .map(Syntax::from_value)
+                .collect::<serde_json::Result<_>>()?,
+            other => serde_json::from_value(other)?,
+        };
+        if !(rest.is_empty() && node_extra.is_empty() && metadata_extra.is_empty()) {
+            metadata.unknown = Some(Box::new(Unknown {
+                syntax: rest,
+                node: node_extra,
+                metadata: metadata_extra,
+            }));
+        }
+        Ok(Syntax {
+            node,
+            metadata,
+            contains,
+        })
+    }
+}
+
+impl Serialize for Syntax<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let Some(unknown) = &self.metadata.unknown else {
+            let mut s = serializer.serialize_struct("Syntax", 3)?;
+            s.serialize_field("node", &self.node)?;
+            s.serialize_field("metadata", &self.metadata)?;
+            s.serialize_field("contains", &self.contains)?;
+            return s.end();
+        };
+        let with = |mut value: Value,
+                    at: fn(&mut Value) -> Option<&mut Map<String, Value>>,
+                    extra: &Map<String, Value>| {
+            if let Some(fields) = at(&mut value) {
+                for (k, v) in extra {
+                    fields.entry(k.clone()).or_insert_with(|| v.clone());
+                }
+            }
+            value
+        };
+        let node = serde_json::to_value(&self.node).map_err(S::Error::custom)?;
+        let metadata = serde_json::to_value(&self.metadata).map_err(S::Error::custom)?;
+        let mut s = serializer.serialize_map(None)?;
+        s.serialize_entry("node", &with(node, payload, &unknown.node))?;
+        s.serialize_entry("metadata", &with(metadata, object, &unknown.metadata))?;
+        s.serialize_entry("contains", &self.contains)?;
+        for (k, v) in &unknown.syntax {
+            if !matches!(k.as_str(), "node" | "metadata" | "contains") {
+                s.serialize_entry(k, v)?;
+            }
+        }
+        s.end()
+    }
+}
+
+/// Fields a node from another bolo version has that this one does not know.
+/// [`Syntax::from_value`] keeps them and serializing writes them back, so
+/// mixing versions in a pipeline does not lose data.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Unknown {
+    /// Keys beside `node`, `metadata` and `contains`.
+    pub syntax: Map<String, Value>,
+    /// Keys of the node's kind, inside `{"Function": {..}}`.
+    pub node: Map<String, Value>,
+    /// Keys of `metadata`.
+    pub metadata: Map<String, Value>,
+}
+
+/// `value` as a `T`, and the entries of the object `at` finds in it that `T`
+/// does not write back.
+fn split<T: Serialize + DeserializeOwned>(
+    mut value: Value,
+    at: fn(&mut Value) -> Option<&mut Map<String, Value>>,
+) -> serde_json::Result<(T, Map<String, Value>)> {
+    let typed = T::deserialize(&value)?;
+    let mut known = serde_json::to_value(&typed)?;
+    let extra = match (at(&mut value), at(&mut known)) {
+        (Some(original), Some(known)) => std::mem::take(original)
+            .into_iter()
+            .filter(|(k, _)| !known.contains_key(k))
+            .collect(),
+        _ => Map::new(),
+    };
+    Ok((typed, extra))
+}
+
+fn object(value: &mut Value) -> Option<&mut Map<String, Value>> {
+    value.as_object_mut()
+}
+
+/// The fields of a serialized [`ASTNode`], under its kind.
+fn payload(value: &mut Value) -> Option<&mut Map<String, Value>> {
+    value.as_object_mut()?.values_mut().next()?.as_object_mut()
+}
+
+impl ASTNode<'_> {
+    pub fn into_owned(self) -> ASTNode<'static> {
+        fn own(s: Cow<str>) -> Cow<'static, str> {
+            Cow::Owned(s.into_owned())
+        }
+        match self {
+            ASTNode::File(f) => ASTNode::File(File {
+                path: own(f.path),
+                license: f.license.map(own),
+                module: f.module.map(own),
+            }),
+            ASTNode::Function(f) => ASTNode::Function(Function {
+                name: own(f.name),
+                public: f.public,
+                params: f.params.into_iter().map(Param::into_owned).collect(),
+                return_type: f.return_type.map(own),
+                decorators: f.decorators.into_iter().map(own).collect(),
+            }),
+            ASTNode::Type(t) => ASTNode::Type(Type {
+                name: own(t.name),
+                public: t.public,
+                is_abstract: t.is_abstract,
+                decorators: t.decorators.into_iter().map(own).collect(),
+            }),
+            ASTNode::Field(f) => ASTNode::Field(Field {
+                name: own(f.name),
+                annotation: f.annotation.map(own),
+            }),
+            ASTNode::Variant(v) => ASTNode::Variant(Variant { name: own(v.name) }),
+            ASTNode::Call(c) => ASTNode::Call(Call {
+                name: own(c.name),
+                tags: c.tags,
+                spawned_by: c.spawned_by.map(own),
+            }),
+            ASTNode::UsesType(u) => ASTNode::UsesType(UsesType { name: own(u.name) }),
+            ASTNode::Module(m) => ASTNode::Module(Module {
+                name: own(m.name),
+                path: m.path.map(own),
+                file: m.file.map(own),
+                inline: m.inline,
+            }),
+            ASTNode::Import(i) => ASTNode::Import(Import {
+                module: own(i.module),
+                items: i.items.into_iter().map(own).collect(),
+                alias: i.alias.map(own),
+            }),
+            ASTNode::DynamicImport(d) => ASTNode::DynamicImport(DynamicImport {
+                module: d.module.map(own),
+            }),
+            ASTNode::Endpoint(e) => ASTNode::Endpoint(Endpoint {
+                route: own(e.route),
+                methods: e.methods,
+                handler: own(e.handler),
+            }),
+            ASTNode::Fixture(f) => ASTNode::Fixture(Fixture {
+                name: own(f.name),
+                scope: f.scope.map(own),
+            }),
+            ASTNode::UsesFixture(u) => ASTNode::UsesFixture(UsesFixture { name: own(u.name) }),
+            ASTNode::Unsafe(u) => ASTNode::Unsafe(u),
+            ASTNode::Attribute(a) => ASTNode::Attribute(Attribute {
+                name: own(a.name),
+                args: a.args.into_iter().map(own).collect(),
+            }),
+            ASTNode::Exception(e) => ASTNode::Exception(Exception {
+                name: own(e.name),
+                kind: e.kind,
+            }),
+            ASTNode::Literal(l) => ASTNode::Literal(Literal {
+                value: own(l.value),
+                kind: l.kind,
+            }),
+            ASTNode::Comment => ASTNode::Comment,
+            ASTNode::Unknown(v) => ASTNode::Unknown(v),
+        }
+    }
 }
 
 // ── Node Data ────────────────────────────────────────────────────────
 
-#[derive(Debug, Clone, Serialize)]
-pub struct File {
-    pub path: String,
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct File<'src> {
+    pub path: Cow<'src, str>,
+    /// SPDX id from the file's leading comment (`SPDX-License-Identifier` or a known header).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<Cow<'src, str>>,
+    /// Module path, `pkg.sub.m` in Python or `crate::a::b` in Rust (see
+    /// [`modules::assign`](crate::modules::assign)).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub module: Option<Cow<'src, str>>,
 }
 
-#[derive(Debug, Clone, Serialize)]
-pub struct Function {
-    pub name: String,
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Function<'src> {
+    pub name: Cow<'src, str>,
+    /// Part of the public API as written: `pub` in Rust (also trait items and
+    /// trait impl methods), no leading underscore in Python, exported in
+    /// JavaScript and TypeScript (class members unless `#private`, `_private`
+    /// or marked `private`/`protected`), capitalized in Go.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub public: bool,
+    /// Parameters in declaration order, receivers (`self`, `&mut self`)
+    /// included (Python, Rust).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub params: Vec<Param<'src>>,
+    /// The return type as written (Python) or resolved like field
+    /// annotations (Rust), when declared.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub return_type: Option<Cow<'src, str>>,
+    /// Decorators (Python) or outer attributes (Rust) as written, without
+    /// the `@` or `#[...]`: `app.route("/")`, `staticmethod`, `test`,
+    /// `tokio::main`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub decorators: Vec<Cow<'src, str>>,
 }
 
-#[derive(Debug, Clone, Serialize)]
-pub struct Type {
-    pub name: String,
+/// One parameter of a [`Function`]: `x`, `*args`, `&self`, or a `*` or `/`
+/// separator in Python.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Param<'src> {
+    pub name: Cow<'src, str>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub annotation: Option<Cow<'src, str>>,
+    /// The default value as written (Python).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<Cow<'src, str>>,
 }
 
-#[derive(Debug, Clone, Serialize)]
-pub struct Call {
-    pub name: String,
+impl Param<'_> {
+    pub fn into_owned(self) -> Param<'static> {
+        let own = |s: Cow<str>| Cow::Owned(s.into_owned());
+        Param {
+            name: own(self.name),
+            annotation: self.annotation.map(own),
+            default: self.default.map(own),
+        }
+    }
+}
+
+/// A struct, enum, trait, type alias or class. Rust `impl` blocks are Types
+/// too, named `Foo` or `Trait for Foo`, and never `public` themselves.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Type<'src> {
+    pub name: Cow<'src, str>,
+    /// See [`Function::public`].
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub public: bool,
+    /// An interface rather than a concrete type: a Rust trait, a Python
+    /// class built on `ABC`/`ABCMeta` or `Protocol` or with `@abstractmethod`s,
+    /// a TypeScript interface or abstract class, or a Go interface.
+    #[serde(rename = "abstract", default, skip_serializing_if = "is_false")]
+    pub is_abstract: bool,
+    /// See [`Function::decorators`]; `derive(Debug, Clone)` for a Rust type.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub decorators: Vec<Cow<'src, str>>,
+}
+
+fn is_false(b: &bool) -> bool {
+    !b
+}
+
+/// A data member of a type: class attribute, dataclass field, struct field.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Field<'src> {
+    pub name: Cow<'src, str>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub annotation: Option<Cow<'src, str>>,
+}
+
+/// An enum variant; tuple/struct payloads are nested as [`Field`]s.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Variant<'src> {
+    pub name: Cow<'src, str>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Call<'src> {
+    pub name: Cow<'src, str>,
+    /// Watchlists this call matches (see `bolo.toml`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// The call that starts this one as a task, thread or process
+    /// (`tokio::spawn`), under which it is nested; it runs apart from its
+    /// caller.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spawned_by: Option<Cow<'src, str>>,
+}
+
+/// A type named in a function signature (parameter or return annotation).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsesType<'src> {
+    pub name: Cow<'src, str>,
+}
+
+/// A file-backed `mod foo;` declaration, or an inline `mod foo { .. }`
+/// whose items are its children. `path` holds a `#[path = "..."]` override.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Module<'src> {
+    pub name: Cow<'src, str>,
+    pub path: Option<Cow<'src, str>>,
+    /// The analyzed file the declaration resolves to, when there is one
+    /// (see [`modules::declared`](crate::modules::declared)).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<Cow<'src, str>>,
+    /// Declared with a body rather than in a file of its own.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub inline: bool,
+}
+
+/// A static import, as written: `import a.b`, `from a import x, y`,
+/// `use a::{x, y}`, `import { x } from "a"`, Go `import "a/b"`.
+/// `items` is empty when the whole module is imported, `["*"]` for a glob.
+/// `alias` is the name a single module or item is bound to when renamed
+/// (`import numpy as np`); renamed items get an Import of their own.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Import<'src> {
+    pub module: Cow<'src, str>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub items: Vec<Cow<'src, str>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alias: Option<Cow<'src, str>>,
+}
+
+/// A runtime import (`importlib.import_module("pkg")`, `__import__("pkg")`).
+/// `module` is set when the target is a string literal.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DynamicImport<'src> {
+    pub module: Option<Cow<'src, str>>,
+}
+
+/// A web route bound to its handler (`@app.get("/x")`, Django `path("x/", view)`).
+/// `methods` is empty when the route accepts any method.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Endpoint<'src> {
+    pub route: Cow<'src, str>,
+    pub methods: Vec<String>,
+    pub handler: Cow<'src, str>,
+}
+
+/// A `@pytest.fixture` function; `name` honours `@fixture(name=...)`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Fixture<'src> {
+    pub name: Cow<'src, str>,
+    pub scope: Option<Cow<'src, str>>,
+}
+
+/// A fixture injected by parameter name into a test or another fixture.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsesFixture<'src> {
+    pub name: Cow<'src, str>,
+}
+
+/// An outer attribute of the item, field or variant it is under (Rust):
+/// `#[derive(Debug, Serialize)]` is `derive` with args `Debug` and, after
+/// `use serde::Serialize`, `serde::Serialize`. Other arguments are kept as
+/// written, split at top-level commas; `#[path = "a.rs"]` has the one arg
+/// `"a.rs"`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Attribute<'src> {
+    pub name: Cow<'src, str>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<Cow<'src, str>>,
+}
+
+/// An `unsafe` block, fn, impl or trait (Rust).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Unsafe {
+    pub kind: UnsafeKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum UnsafeKind {
+    Block,
+    Fn,
+    Impl,
+    Trait,
+}
+
+/// An exception type raised or caught in an `except` clause (Python), or
+/// the error type a function returns in its `Result` and propagates with
+/// `?` (Rust).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Exception<'src> {
+    pub name: Cow<'src, str>,
+    pub kind: ExceptionKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum ExceptionKind {
+    Raise,
+    Handle,
+    Returns,
+    Propagates,
+}
+
+/// A string literal, emitted when [`Options::strings`] is set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Literal<'src> {
+    pub value: Cow<'src, str>,
+    pub kind: LiteralKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum LiteralKind {
+    Url,
+    Path,
+    Sql,
+    Text,
+}
+
+impl LiteralKind {
+    /// Guess what a string holds from its shape.
+    pub fn classify(value: &str) -> Self {
+        let v = value.trim();
+        if v.contains("://") && !v.contains(char::is_whitespace) {
+            return LiteralKind::Url;
+        }
+        let upper = v.to_ascii_uppercase();
+        let verb = [
+            "SELECT ", "INSERT ", "UPDATE ", "DELETE ", "CREATE ", "DROP ", "ALTER ", "WITH ",
+        ]
+        .iter()
+        .any(|k| upper.starts_with(k));
+        let clause = [" FROM ", " INTO ", " SET ", " TABLE ", " VALUES", " WHERE "]
+            .iter()
+            .any(|k| upper.contains(k));
+        if verb && clause {
+            return LiteralKind::Sql;
+        }
+        let pathlike = ["/", "./", "../", "~/"].iter().any(|p| v.starts_with(p))
+            || v.get(1..3) == Some(":\\")
+            || (v.contains('/') && v.rsplit('/').next().is_some_and(|f| f.contains('.')));
+        if pathlike && v.len() > 1 && !v.contains(char::is_whitespace) {
+            return LiteralKind::Path;
+        }
+        LiteralKind::Text
+    }
 }
 
 // ── Metadata ─────────────────────────────────────────────────────────
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Metadata {
     pub chars: usize,
     pub lines: usize,
     pub words: usize,
     pub whitespaces: usize,
     pub newlines: usize,
+    /// Zero in analyses written before spans were recorded.
+    #[serde(default)]
+    pub span: Span,
+    /// Position among its siblings as parsed, before `clean` reorders or
+    /// removes any; `None` for synthetic nodes (File, merged Comment).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index: Option<usize>,
+    /// Values from optional passes (complexity, churn, profiling samples,
+    /// ...), keyed by metric name. Passes add keys here instead of new
+    /// fields, so the fixed fields above stay stable for consumers.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metrics: BTreeMap<String, Value>,
+    /// Fields of an analysis from another bolo version that this one does
+    /// not know (see [`Syntax::from_value`]); parsing never sets it.
+    #[serde(skip)]
+    pub unknown: Option<Box<Unknown>>,
+}
+
+impl Metadata {
+    /// Record `value` for the metric `key`, replacing any earlier value.
+    pub fn set_metric(&mut self, key: impl Into<String>, value: impl Into<Value>) {
+        self.metrics.insert(key.into(), value.into());
+    }
+
+    pub fn metric(&self, key: &str) -> Option<&Value> {
+        self.metrics.get(key)
+    }
+}
+
+/// Where a node sits in its file: a 1-based, inclusive line range, the
+/// 1-based byte columns of its first and one past its last character, and
+/// its 0-based, end-exclusive byte offsets.
+///
+/// Columns and offsets are 0 when only lines are known (older analysis
+/// JSON, whole-file or line-based spans).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Span {
+    pub start_line: usize,
+    pub end_line: usize,
+    #[serde(default)]
+    pub start_col: usize,
+    #[serde(default)]
+    pub end_col: usize,
+    #[serde(default)]
+    pub start_byte: usize,
+    #[serde(default)]
+    pub end_byte: usize,
+}
+
+impl Span {
+    /// The span of a parsed node.
+    pub fn of(node: Node) -> Self {
+        let (start, end) = (node.start_position(), node.end_position());
+        Span {
+            start_line: start.row + 1,
+            end_line: end.row + 1,
+            start_col: start.column + 1,
+            end_col: end.column + 1,
+            start_byte: node.start_byte(),
+            end_byte: node.end_byte(),
+        }
+    }
+
+    /// Only the lines `start..=end`.
+    pub fn lines(start: usize, end: usize) -> Self {
+        Span {
+            start_line: start,
+            end_line: end,
+            ..Span::default()
+        }
+    }
 }
 
 /// Build a [`Metadata`] from a byte‐range in the source.
+///
+/// The [`Span`] is left empty; see [`metadata_from_node`].
 pub fn metadata_from_span(src: &[u8], start: usize, end: usize) -> Metadata {
     let slice = src.get(start..end).unwrap_or(b"");
     let text = std::str::from_utf8(slice).unwrap_or("");
@@ -83,14 +636,261 @@ pub fn metadata_from_span(src: &[u8], start: usize, end: usize) -> Metadata {
             .filter(|c| c.is_whitespace() && *c != '\n')
             .count(),
         newlines,
+        span: Span::default(),
+        index: None,
+        metrics: BTreeMap::new(),
+        unknown: None,
+    }
+}
+
+/// Build a [`Metadata`] for a parsed node, including its [`Span`].
+pub fn metadata_from_node(src: &[u8], node: Node) -> Metadata {
+    let mut m = metadata_from_span(src, node.start_byte(), node.end_byte());
+    m.span = Span::of(node);
+    m
+}
+
+// ── Options ──────────────────────────────────────────────────────────
+
+/// Per-run analysis options; extraction passes are off by default.
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    /// Extract web framework route tables into [`Endpoint`] nodes (Python).
+    pub routes: bool,
+    /// Emit string [`Literal`]s under their enclosing function.
+    pub strings: Option<Strings>,
+    /// What the `strip-comments` pass does with Comment nodes.
+    pub comments: CommentPolicy,
+    /// Passes run over each file's nodes after parsing (`--passes`).
+    pub passes: Pipeline,
+    /// Prefix for File node paths, e.g. the project name.
+    pub root_label: Option<String>,
+    /// Only analyze the files in this slice of the walk.
+    pub shard: Option<Shard>,
+    /// The reported paths of the whole walk, when fewer files are analyzed
+    /// (`--shard`, `--sample`, `--stdin`); passes resolve imports and module
+    /// paths against it. `None` takes the files analyzed as the whole tree.
+    pub known: Option<HashSet<String>>,
+    /// Directory of the parse cache (`--cache`); see `cache::Cache`.
+    pub cache: Option<PathBuf>,
+}
+
+/// Which string literals [`Options::strings`] keeps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strings {
+    All,
+    /// URLs, paths and SQL only.
+    Interesting,
+}
+
+// ── Literals ─────────────────────────────────────────────────────────
+
+/// Build [`Literal`] nodes for `(node, value)` pairs and nest each under the
+/// innermost function or type whose span contains it.
+fn attach_literals<'s>(
+    out: &mut Vec<Syntax<'s>>,
+    literals: Vec<(Node, Cow<'s, str>)>,
+    src: &[u8],
+    which: Strings,
+) {
+    for (node, value) in literals {
+        let kind = LiteralKind::classify(&value);
+        if which == Strings::Interesting && kind == LiteralKind::Text {
+            continue;
+        }
+        let lit = Syntax {
+            node: ASTNode::Literal(Literal { value, kind }),
+            metadata: metadata_from_node(src, node),
+            contains: vec![],
+        };
+        nest(out, lit);
     }
 }
 
+fn nest<'s>(nodes: &mut Vec<Syntax<'s>>, item: Syntax<'s>) {
+    let span = item.metadata.span;
+    let parent = nodes.iter_mut().find(|s| {
+        matches!(
+            s.node,
+            ASTNode::Function(_) | ASTNode::Fixture(_) | ASTNode::Type(_) | ASTNode::Unsafe(_)
+        ) && s.metadata.span.start_line <= span.start_line
+            && span.end_line <= s.metadata.span.end_line
+    });
+    match parent {
+        Some(p) => nest(&mut p.contains, item),
+        None => {
+            let at = nodes
+                .iter()
+                .position(|s| s.metadata.span.start_line > span.start_line)
+                .unwrap_or(nodes.len());
+            nodes.insert(at, item);
+        }
+    }
+}
+
+// ── Global State ──────────────────────────────────────────────────────
+
+/// Module-level state functions can change: a Python global holding a
+/// mutable container or rebound with `global`, or a Rust `static mut`,
+/// `lazy_static!` static or static with interior mutability.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Global {
+    pub name: String,
+    pub kind: GlobalKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub enum GlobalKind {
+    /// A list, dict, set or other mutable collection (Python).
+    Container,
+    /// Assigned in a function that declares it `global` (Python).
+    Rebound,
+    StaticMut,
+    LazyStatic,
+    /// A `static` of a cell, lock, atomic or lazy type (Rust).
+    InteriorMut,
+}
+
+/// A file's [`Global`]s and the names each of its functions mentions,
+/// for matching references across files.
+#[derive(Debug, Clone, Default)]
+pub struct Globals {
+    pub defined: Vec<Global>,
+    /// `(function, names)` in source order; methods are qualified by
+    /// their class or impl type (`Cache.get`, `Cache::get`).
+    pub uses: Vec<(String, BTreeSet<String>)>,
+}
+
 // ── Trait ─────────────────────────────────────────────────────────────
 
 pub trait Lang {
     fn get_parser(&self) -> Parser;
-    fn parse(&self, parser: &mut Parser, source: &str) -> Result<Vec<Syntax>, ParseError>;
+
+    /// Nodes for an already-parsed `tree` of `source`, plus the spans of syntax
+    /// errors the grammar recovered from.
+    ///
+    /// Nodes borrow from `source`; see [`Syntax::into_owned`].
+    fn extract<'s>(
+        &self,
+        tree: &Tree,
+        source: &'s str,
+        opts: &Options,
+    ) -> (Vec<Syntax<'s>>, Vec<Span>);
+
+    /// Parse from scratch and [`extract`](Lang::extract).
+    fn parse_checked<'s>(
+        &self,
+        parser: &mut Parser,
+        source: &'s str,
+        opts: &Options,
+    ) -> Result<(Vec<Syntax<'s>>, Vec<Span>), ParseError> {
+        let tree = parser
+            .parse(source, None)
+            .ok_or_else(|| ParseError("parse returned None".into()))?;
+        Ok(self.extract(&tree, source, opts))
+    }
+
+    fn parse<'s>(
+        &self,
+        parser: &mut Parser,
+        source: &'s str,
+        opts: &Options,
+    ) -> Result<Vec<Syntax<'s>>, ParseError> {
+        self.parse_checked(parser, source, opts)
+            .map(|(nodes, _)| nodes)
+    }
+}
+
+/// Mark the calls in `work` as spawned by the call named `via`, leaving
+/// nested definitions and what a nested spawning call runs alone.
+fn spawned<'s>(mut work: Vec<Syntax<'s>>, via: Cow<'s, str>) -> Vec<Syntax<'s>> {
+    fn mark<'s>(nodes: &mut [Syntax<'s>], via: Cow<'s, str>) {
+        for s in nodes {
+            match &mut s.node {
+                ASTNode::Call(c) => c.spawned_by = Some(via.clone()),
+                ASTNode::Function(_) | ASTNode::Type(_) => {}
+                _ => mark(&mut s.contains, via.clone()),
+            }
+        }
+    }
+    mark(&mut work, via);
+    work
+}
+
+/// Metric key of a function's cyclomatic complexity.
+pub const COMPLEXITY: &str = "complexity";
+
+/// Cyclomatic complexity of a function body: one, plus what `decisions`
+/// counts for each node under `body`. Definitions of the `nested` kinds are
+/// skipped; they are measured on their own.
+fn complexity(body: Node, decisions: &dyn Fn(Node) -> usize, nested: &[&str]) -> usize {
+    let mut n = 1;
+    let mut stack = vec![body];
+    while let Some(node) = stack.pop() {
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            if !nested.contains(&child.kind()) {
+                n += decisions(child);
+                stack.push(child);
+            }
+        }
+    }
+    n
+}
+
+/// `{head}{sep}{tail}`, borrowed from `text` when the source spells it exactly
+/// that way (no whitespace or comments between the parts).
+fn join<'s>(text: &'s str, head: &str, sep: &str, tail: &str) -> Cow<'s, str> {
+    let verbatim = text.len() == head.len() + sep.len() + tail.len()
+        && text.starts_with(head)
+        && text[head.len()..].starts_with(sep)
+        && text.ends_with(tail);
+    if verbatim {
+        Cow::Borrowed(text)
+    } else {
+        Cow::Owned(format!("{head}{sep}{tail}"))
+    }
+}
+
+/// Spans of `ERROR` and missing nodes, outermost only.
+fn syntax_errors(root: Node) -> Vec<Span> {
+    let mut out = Vec::new();
+    let mut stack = vec![root];
+    while let Some(node) = stack.pop() {
+        if node.is_error() || node.is_missing() {
+            out.push(Span::of(node));
+        } else if node.has_error() {
+            let mut cursor = node.walk();
+            stack.extend(node.children(&mut cursor));
+        }
+    }
+    out.sort_by_key(|s| (s.start_line, s.end_line));
+    out
+}
+
+/// The fixture tests start from: `source` parsed as `lang` with `opts`
+/// and cleaned as the file `path`.
+#[cfg(test)]
+pub(crate) fn analyze_with(
+    lang: &dyn Lang,
+    path: &str,
+    source: &str,
+    opts: &Options,
+) -> Vec<Syntax<'static>> {
+    let ast = lang.parse(&mut lang.get_parser(), source, opts).unwrap();
+    crate::clean::clean(path, source, ast, Default::default())
+        .into_iter()
+        .map(Syntax::into_owned)
+        .collect()
+}
+
+/// [`analyze_with`] default options.
+#[cfg(test)]
+pub(crate) fn analyze(lang: &dyn Lang, path: &str, source: &str) -> Vec<Syntax<'static>> {
+    analyze_with(lang, path, source, &Options::default())
 }
 
 // ── Tests ──────────────────────────────────────────────────────────
@@ -181,6 +981,127 @@ mod tests {
         assert_eq!(m.whitespaces, 6);
     }
 
+    // ── metadata_from_node ──
+
+    #[test]
+    fn metadata_node_has_span() {
+        let source = "x = 1\n\ndef f():\n    pass\n";
+        let mut parser = Parser::new();
+        parser
+            .set_language(&tree_sitter_python::LANGUAGE.into())
+            .unwrap();
+        let tree = parser.parse(source, None).unwrap();
+        let func = tree.root_node().named_child(1).unwrap();
+        let m = metadata_from_node(source.as_bytes(), func);
+        assert_eq!(
+            m.span,
+            Span {
+                start_line: 3,
+                end_line: 4,
+                start_col: 1,
+                end_col: 9,
+                start_byte: 7,
+                end_byte: 24,
+            }
+        );
+    }
+
+    #[test]
+    fn metadata_from_span_has_empty_line_span() {
+        let m = metadata_from_span(b"abc", 0, 3);
+        assert_eq!(m.span, Span::default());
+    }
+
+    // ── LiteralKind ──
+
+    #[test]
+    fn classify_literals() {
+        use LiteralKind::*;
+        assert_eq!(LiteralKind::classify("https://api.example.com/v1"), Url);
+        assert_eq!(LiteralKind::classify("postgres://localhost/db"), Url);
+        assert_eq!(
+            LiteralKind::classify("SELECT id FROM users WHERE x = ?"),
+            Sql
+        );
+        assert_eq!(LiteralKind::classify("insert into t values (1)"), Sql);
+        assert_eq!(LiteralKind::classify("/etc/passwd"), Path);
+        assert_eq!(LiteralKind::classify("./config.toml"), Path);
+        assert_eq!(LiteralKind::classify("assets/logo.png"), Path);
+        assert_eq!(LiteralKind::classify("C:\\Windows"), Path);
+        assert_eq!(LiteralKind::classify("Hello, world"), Text);
+        assert_eq!(LiteralKind::classify("select a color"), Text);
+        assert_eq!(LiteralKind::classify("/"), Text);
+        assert_eq!(LiteralKind::classify("and/or"), Text);
+    }
+
+    // ── syntax_errors ──
+
+    fn errors(source: &str) -> Vec<Span> {
+        let mut parser = Parser::new();
+        parser
+            .set_language(&tree_sitter_python::LANGUAGE.into())
+            .unwrap();
+        syntax_errors(parser.parse(source, None).unwrap().root_node())
+    }
+
+    #[test]
+    fn syntax_errors_none_for_valid_source() {
+        assert!(errors("def f():\n    return 1\n").is_empty());
+    }
+
+    #[test]
+    fn syntax_errors_located() {
+        let spans = errors("x = 1\ndef f(:\n    pass\n");
+        assert!(!spans.is_empty());
+        assert_eq!(spans[0].start_line, 2);
+    }
+
+    // ── join ──
+
+    #[test]
+    fn join_borrows_verbatim_text() {
+        assert!(matches!(join("a.b", "a", ".", "b"), Cow::Borrowed("a.b")));
+    }
+
+    #[test]
+    fn join_owns_rebuilt_text() {
+        let joined = join("a . b", "a", ".", "b");
+        assert!(matches!(joined, Cow::Owned(_)));
+        assert_eq!(joined, "a.b");
+    }
+
+    // ── into_owned ──
+
+    #[test]
+    fn into_owned_detaches_from_source() {
+        let owned = {
+            let source = String::from("f");
+            let borrowed = Syntax {
+                node: ASTNode::Function(Function {
+                    name: Cow::Borrowed(source.as_str()),
+                    public: false,
+                    params: vec![],
+                    return_type: None,
+                    decorators: vec![],
+                }),
+                metadata: Metadata {
+                    chars: 1,
+                    lines: 1,
+                    words: 1,
+                    whitespaces: 0,
+                    newlines: 0,
+                    span: Span::default(),
+                    index: None,
+                    metrics: BTreeMap::new(),
+                    unknown: None,
+                },
+                contains: vec![],
+            };
+            borrowed.into_owned()
+        };
+        assert!(matches!(&owned.node, ASTNode::Function(f) if f.name == "f"));
+    }
+
     // ── Syntax serialization ──
 
     #[test]
@@ -188,6 +1109,10 @@ mod tests {
         let s = Syntax {
             node: ASTNode::Function(Function {
                 name: "main".into(),
+                public: false,
+                params: vec![],
+                return_type: None,
+                decorators: vec![],
             }),
             metadata: Metadata {
                 chars: 10,
@@ -195,12 +1120,94 @@ mod tests {
                 words: 2,
                 whitespaces: 1,
                 newlines: 0,
+                span: Span::default(),
+                index: None,
+                metrics: BTreeMap::new(),
+                unknown: None,
             },
             contains: vec![],
         };
         let json = serde_json::to_string(&s).unwrap();
         assert!(json.contains("\"Function\""));
         assert!(json.contains("\"main\""));
+        assert!(!json.contains("public"));
+    }
+
+    #[test]
+    fn metrics_serialized_only_when_set() {
+        let mut meta = metadata_from_span(b"f()", 0, 3);
+        let json = serde_json::to_string(&meta).unwrap();
+        assert!(!json.contains("metrics"));
+
+        meta.set_metric("complexity", 3);
+        meta.set_metric("hot", true);
+        let json = serde_json::to_string(&meta).unwrap();
+        assert!(json.contains("\"metrics\":{\"complexity\":3,\"hot\":true}"));
+
+        let back: Metadata = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.metric("complexity"), Some(&Value::from(3)));
+        assert_eq!(back.metric("churn"), None);
+    }
+
+    #[test]
+    fn public_flag_serialized_when_set() {
+        let f = ASTNode::Function(Function {
+            name: "main".into(),
+            public: true,
+            params: vec![],
+            return_type: None,
+            decorators: vec![],
+        });
+        let json = serde_json::to_string(&f).unwrap();
+        assert!(json.contains("\"public\":true"));
+        let back: ASTNode = serde_json::from_str("{\"Function\":{\"name\":\"f\"}}").unwrap();
+        assert!(matches!(back, ASTNode::Function(f) if !f.public));
+    }
+
+    #[test]
+    fn from_value_keeps_only_unknown_fields() {
+        let mut meta = metadata_from_span(b"f()", 0, 3);
+        meta.set_metric("complexity", 1);
+        let f = Syntax {
+            node: ASTNode::Function(Function {
+                name: "f".into(),
+                public: true,
+                params: vec![],
+                return_type: None,
+                decorators: vec!["cache".into()],
+            }),
+            metadata: meta,
+            contains: vec![],
+        };
+        let mut value = serde_json::to_value(&f).unwrap();
+        let back = Syntax::from_value(value.clone()).unwrap();
+        assert!(back.metadata.unknown.is_none());
+
+        value["node"]["Function"]["async"] = true.into();
+        value["contains"] = serde_json::json!([{
+            "node": "Comment", "metadata": value["metadata"].clone(), "contains": [], "doc": true
+        }]);
+        let back = Syntax::from_value(value.clone()).unwrap();
+        let unknown = back.metadata.unknown.as_deref().unwrap();
+        assert_eq!(unknown.node.keys().collect::<Vec<_>>(), ["async"]);
+        assert!(unknown.syntax.is_empty() && unknown.metadata.is_empty());
+        assert_eq!(serde_json::to_value(&back).unwrap(), value);
+        assert!(Syntax::from_value(serde_json::json!({"node": "Comment"})).is_err());
+    }
+
+    #[test]
+    fn from_value_reads_metadata_without_span() {
+        let value = serde_json::json!({
+            "node": {"File": {"path": "a.py"}},
+            "metadata": {"chars": 3, "lines": 1, "words": 1, "whitespaces": 0, "newlines": 0, "hash": "x"},
+            "contains": []
+        });
+        let back = Syntax::from_value(value).unwrap();
+        assert_eq!(back.metadata.span, Span::default());
+        let unknown = back.metadata.unknown.as_deref().unwrap();
+        assert_eq!(unknown.metadata.keys().collect::<Vec<_>>(), ["hash"]);
+        let json = serde_json::to_value(&back).unwrap();
+        assert_eq!(json["metadata"]["hash"], "x");
     }
 
     #[test]
@@ -213,6 +1220,10 @@ mod tests {
                 words: 1,
                 whitespaces: 0,
                 newlines: 0,
+                span: Span::default(),
+                index: None,
+                metrics: BTreeMap::new(),
+                unknown: None,
             },
             contains: vec![],
         };
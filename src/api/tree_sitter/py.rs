@@ -1,6 +1,13 @@
-use super::{ASTNode, Call, Function, Metadata, ParseError, Syntax, Type, metadata_from_span};
-use std::collections::HashMap;
-use tree_sitter::{Node, Parser};
+use super::{
+    ASTNode, COMPLEXITY, Call, DynamicImport, Endpoint, Exception, ExceptionKind, Field, Fixture,
+    Function, Global, GlobalKind, Globals, Import, Metadata, Options, Param, Span, Syntax, Type,
+    UsesFixture, UsesType, complexity, join, metadata_from_node, syntax_errors,
+};
+use serde::Serialize;
+use std::borrow::Cow;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt;
+use tree_sitter::{Node, Parser, Tree};
 
 pub struct Python;
 
@@ -13,14 +20,25 @@ impl super::Lang for Python {
         parser
     }
 
-    fn parse(&self, parser: &mut Parser, source: &str) -> Result<Vec<Syntax>, ParseError> {
-        let tree = parser
-            .parse(source, None)
-            .ok_or_else(|| ParseError("parse returned None".into()))?;
+    fn extract<'s>(
+        &self,
+        tree: &Tree,
+        source: &'s str,
+        opts: &Options,
+    ) -> (Vec<Syntax<'s>>, Vec<Span>) {
         let src = source.as_bytes();
         let root = tree.root_node();
         let imports = collect_imports(root, src);
-        Ok(walk(root, src, &imports))
+        let mut out = walk(root, src, &imports);
+        if opts.routes {
+            collect_routes(root, src, &imports, &mut out);
+        }
+        if let Some(which) = opts.strings {
+            let mut literals = Vec::new();
+            collect_strings(root, src, &mut literals);
+            super::attach_literals(&mut out, literals, src, which);
+        }
+        (out, syntax_errors(root))
     }
 }
 
@@ -45,7 +63,7 @@ fn collect_imports_inner(node: Node, src: &[u8], imports: &mut HashMap<String, S
                         let name = field_text(n, "name", src);
                         let alias = field_text(n, "alias", src);
                         if !alias.is_empty() {
-                            imports.insert(alias, name);
+                            imports.insert(alias.to_string(), name.to_string());
                         }
                     }
                 }
@@ -66,18 +84,14 @@ fn collect_imports_inner(node: Node, src: &[u8], imports: &mut HashMap<String, S
                     }
                     match n.kind() {
                         "dotted_name" => {
-                            let name = n.utf8_text(src).unwrap_or("").to_string();
-                            imports.insert(name.clone(), qualify(module, &name));
+                            let name = n.utf8_text(src).unwrap_or("");
+                            imports.insert(name.to_string(), qualify(module, name));
                         }
                         "aliased_import" => {
                             let name = field_text(n, "name", src);
                             let alias = field_text(n, "alias", src);
-                            let key = if alias.is_empty() {
-                                name.clone()
-                            } else {
-                                alias
-                            };
-                            imports.insert(key, qualify(module, &name));
+                            let key = if alias.is_empty() { name } else { alias };
+                            imports.insert(key.to_string(), qualify(module, name));
                         }
                         _ => {}
                     }
@@ -91,6 +105,53 @@ fn collect_imports_inner(node: Node, src: &[u8], imports: &mut HashMap<String, S
     }
 }
 
+/// One Import per module of `import a, b as c`; one for the unrenamed
+/// names of a `from` import plus one per renamed name.
+fn import_nodes<'s>(stmt: Node, src: &'s [u8]) -> Vec<Syntax<'s>> {
+    let import = |module: &'s str, items: Vec<&'s str>, alias: Option<&'s str>| Syntax {
+        node: ASTNode::Import(Import {
+            module: Cow::Borrowed(module),
+            items: items.into_iter().map(Cow::Borrowed).collect(),
+            alias: alias.map(Cow::Borrowed),
+        }),
+        metadata: meta(stmt, src),
+        contains: vec![],
+    };
+    let module = match stmt.kind() {
+        "future_import_statement" => Some("__future__"),
+        _ => stmt
+            .child_by_field_name("module_name")
+            .and_then(|n| n.utf8_text(src).ok()),
+    };
+    let module_id = stmt.child_by_field_name("module_name").map(|n| n.id());
+    let mut out = Vec::new();
+    let mut items = Vec::new();
+    let mut cursor = stmt.walk();
+    for n in stmt.named_children(&mut cursor) {
+        if Some(n.id()) == module_id {
+            continue;
+        }
+        let (name, alias) = match n.kind() {
+            "dotted_name" => (n.utf8_text(src).unwrap_or(""), None),
+            "aliased_import" => (
+                field_text(n, "name", src),
+                Some(field_text(n, "alias", src)),
+            ),
+            "wildcard_import" => ("*", None),
+            _ => continue,
+        };
+        match (module, alias) {
+            (Some(_), None) => items.push(name),
+            (Some(m), alias) => out.push(import(m, vec![name], alias)),
+            (None, alias) => out.push(import(name, vec![], alias)),
+        }
+    }
+    if let Some(m) = module.filter(|_| !items.is_empty()) {
+        out.insert(0, import(m, items, None));
+    }
+    out
+}
+
 fn qualify(module: &str, name: &str) -> String {
     if module.is_empty() {
         name.to_string()
@@ -103,7 +164,7 @@ fn qualify(module: &str, name: &str) -> String {
 
 // ── AST Walk ────────────────────────────────────────────────────────
 
-fn walk(node: Node, src: &[u8], imports: &HashMap<String, String>) -> Vec<Syntax> {
+fn walk<'s>(node: Node, src: &'s [u8], imports: &HashMap<String, String>) -> Vec<Syntax<'s>> {
     let mut out = Vec::new();
     let mut cursor = node.walk();
 
@@ -111,19 +172,61 @@ fn walk(node: Node, src: &[u8], imports: &HashMap<String, String>) -> Vec<Syntax
         match child.kind() {
             "function_definition" => {
                 let name = field_text(child, "name", src);
-                let contains = body_children(child, src, imports);
+                let mut contains = signature_types(child, src, imports);
+                if name.starts_with("test") {
+                    contains.extend(fixture_params(child, src));
+                }
+                contains.extend(body_children(child, src, imports));
                 out.push(Syntax {
-                    node: ASTNode::Function(Function { name }),
-                    metadata: meta(child, src),
+                    node: ASTNode::Function(Function {
+                        name: name.into(),
+                        public: is_public(name),
+                        params: params(child, src),
+                        return_type: child
+                            .child_by_field_name("return_type")
+                            .and_then(|t| t.utf8_text(src).ok())
+                            .map(Cow::Borrowed),
+                        decorators: vec![],
+                    }),
+                    metadata: measured(child, child, src),
                     contains,
                 });
             }
 
+            "decorated_definition" => match fixture(child, src, imports) {
+                Some((fixture, def)) => {
+                    let mut contains = fixture_params(def, src);
+                    contains.extend(body_children(def, src, imports));
+                    out.push(Syntax {
+                        node: ASTNode::Fixture(fixture),
+                        metadata: measured(child, def, src),
+                        contains,
+                    });
+                }
+                None => {
+                    // The definition comes last, after calls in its decorators.
+                    let mut inner = walk(child, src, imports);
+                    if let Some(def) = inner.last_mut() {
+                        match &mut def.node {
+                            ASTNode::Function(f) => f.decorators = decorators(child, src),
+                            ASTNode::Type(t) => t.decorators = decorators(child, src),
+                            _ => {}
+                        }
+                    }
+                    out.extend(inner);
+                }
+            },
+
             "class_definition" => {
                 let name = field_text(child, "name", src);
                 let contains = body_children(child, src, imports);
                 out.push(Syntax {
-                    node: ASTNode::Type(Type { name }),
+                    node: ASTNode::Type(Type {
+                        name: name.into(),
+                        public: is_public(name),
+                        is_abstract: is_abstract(child, src, imports),
+                        decorators: vec![],
+                    }),
                     metadata: meta(child, src),
                     contains,
                 });
@@ -134,17 +237,55 @@ fn walk(node: Node, src: &[u8], imports: &HashMap<String, String>) -> Vec<Syntax
                     .child_by_field_name("function")
                     .map(|f| dotted_name(f, src))
                     .unwrap_or_default();
-                let name = resolve_call(&raw, imports);
+                let name = resolve_call(raw, imports);
+                let contains = match SPAWNS.contains(&&*name) {
+                    true => super::spawned(spawned_work(child, src, imports), name.clone()),
+                    false => vec![],
+                };
+                let node = if DYNAMIC_IMPORTS.contains(&&*name) {
+                    ASTNode::DynamicImport(DynamicImport {
+                        module: literal_arg(child, src).map(Cow::Borrowed),
+                    })
+                } else {
+                    ASTNode::Call(Call {
+                        name,
+                        tags: vec![],
+                        spawned_by: None,
+                    })
+                };
                 out.push(Syntax {
-                    node: ASTNode::Call(Call { name }),
+                    node,
                     metadata: meta(child, src),
-                    contains: vec![],
+                    contains,
                 });
             }
 
             "expression_statement" => {
+                // Class-level `x = ...` / `x: T = ...` → Field
+                if let Some((assign, names)) = class_field(child) {
+                    let annotation = assign
+                        .child_by_field_name("type")
+                        .and_then(|n| n.utf8_text(src).ok())
+                        .map(Cow::Borrowed);
+                    // `x, y = ...` gives one Field per name; the value's
+                    // calls go under the last.
+                    let mut contains = walk(assign, src, imports);
+                    let last = names.len() - 1;
+                    for (i, name) in names.into_iter().enumerate() {
+                        out.push(Syntax {
+                            node: ASTNode::Field(Field {
+                                name: Cow::Borrowed(name.utf8_text(src).unwrap_or("")),
+                                annotation: annotation.clone(),
+                            }),
+                            metadata: meta(child, src),
+                            contains: match i == last {
+                                true => std::mem::take(&mut contains),
+                                false => vec![],
+                            },
+                        });
+                    }
                 // Bare string literal → docstring → treat as Comment
-                if child.named_child_count() == 1
+                } else if child.named_child_count() == 1
                     && child.named_child(0).is_some_and(|c| c.kind() == "string")
                 {
                     out.push(Syntax {
@@ -157,6 +298,16 @@ fn walk(node: Node, src: &[u8], imports: &HashMap<String, String>) -> Vec<Syntax
                 }
             }
 
+            "raise_statement" => {
+                out.extend(raised(child, src, imports));
+                out.extend(walk(child, src, imports));
+            }
+
+            "except_clause" => {
+                out.extend(handled(child, src, imports));
+                out.extend(walk(child, src, imports));
+            }
+
             "comment" => {
                 out.push(Syntax {
                     node: ASTNode::Comment,
@@ -165,8 +316,10 @@ fn walk(node: Node, src: &[u8], imports: &HashMap<String, String>) -> Vec<Syntax
                 });
             }
 
-            // Imports already collected — skip
-            "import_statement" | "import_from_statement" | "future_import_statement" => {}
+            // Names are already resolved; record the statement itself.
+            "import_statement" | "import_from_statement" | "future_import_statement" => {
+                out.extend(import_nodes(child, src));
+            }
 
             // decorated_definition, control flow, etc. — recurse through
             _ => out.extend(walk(child, src, imports)),
@@ -176,57 +329,963 @@ fn walk(node: Node, src: &[u8], imports: &HashMap<String, String>) -> Vec<Syntax
     out
 }
 
+// ── Spawned Work ────────────────────────────────────────────────────
+
+/// Resolved call names that run their argument as a task, thread or process.
+const SPAWNS: &[&str] = &[
+    "asyncio.create_task",
+    "asyncio.ensure_future",
+    "threading.Thread",
+    "multiprocessing.Process",
+];
+
+/// What a spawning call runs: the `target=` or positional function, the
+/// coroutine function called in its argument, or the calls in a lambda.
+fn spawned_work<'s>(
+    call: Node,
+    src: &'s [u8],
+    imports: &HashMap<String, String>,
+) -> Vec<Syntax<'s>> {
+    let Some(args) = call.child_by_field_name("arguments") else {
+        return vec![];
+    };
+    let mut out = Vec::new();
+    let mut cursor = args.walk();
+    for arg in args.named_children(&mut cursor) {
+        let value = match arg.kind() {
+            "keyword_argument" if field_text(arg, "name", src) == "target" => {
+                arg.child_by_field_name("value")
+            }
+            "keyword_argument" | "comment" => None,
+            _ => Some(arg),
+        };
+        let Some(value) = value else { continue };
+        let function = match value.kind() {
+            "identifier" | "attribute" => Some(value),
+            "call" => value.child_by_field_name("function"),
+            _ => None,
+        };
+        match function {
+            Some(f) => out.push(Syntax {
+                node: ASTNode::Call(Call {
+                    name: resolve_call(dotted_name(f, src), imports),
+                    tags: vec![],
+                    spawned_by: None,
+                }),
+                metadata: meta(value, src),
+                contains: vec![],
+            }),
+            None => out.extend(walk(value, src, imports)),
+        }
+    }
+    out
+}
+
+// ── Exceptions ──────────────────────────────────────────────────────
+
+/// The exception type of `raise X(...)` or `raise X`. Bare `raise` and
+/// re-raising the variable an enclosing `except ... as e` bound are skipped.
+fn raised<'s>(stmt: Node, src: &'s [u8], imports: &HashMap<String, String>) -> Option<Syntax<'s>> {
+    let value = stmt.named_child(0)?;
+    if stmt.child_by_field_name("cause") == Some(value) {
+        return None;
+    }
+    let ty = match value.kind() {
+        "call" => value.child_by_field_name("function")?,
+        "identifier" if bound_by_except(stmt, value, src) => return None,
+        "identifier" | "attribute" => value,
+        _ => return None,
+    };
+    Some(exception(ty, stmt, ExceptionKind::Raise, src, imports))
+}
+
+/// True if `name` is the `as` target of an `except` clause around `node`.
+fn bound_by_except(node: Node, name: Node, src: &[u8]) -> bool {
+    let name = name.utf8_text(src).unwrap_or("");
+    std::iter::successors(node.parent(), Node::parent)
+        .filter(|n| n.kind() == "except_clause")
+        .filter_map(|n| n.child_by_field_name("value"))
+        .any(|v| v.kind() == "as_pattern" && field_text(v, "alias", src) == name)
+}
+
+/// The exception types an `except` clause catches, one per member of a
+/// tuple. A bare `except:` catches `BaseException`.
+fn handled<'s>(clause: Node, src: &'s [u8], imports: &HashMap<String, String>) -> Vec<Syntax<'s>> {
+    let mut cursor = clause.walk();
+    let values: Vec<Node> = clause
+        .children_by_field_name("value", &mut cursor)
+        .collect();
+    if values.is_empty() {
+        return vec![Syntax {
+            node: ASTNode::Exception(Exception {
+                name: "BaseException".into(),
+                kind: ExceptionKind::Handle,
+            }),
+            metadata: meta(clause, src),
+            contains: vec![],
+        }];
+    }
+    let mut types = Vec::new();
+    for value in values {
+        let value = match value.kind() {
+            "as_pattern" => value.named_child(0).unwrap_or(value),
+            _ => value,
+        };
+        match value.kind() {
+            "tuple" | "parenthesized_expression" => {
+                let mut inner = value.walk();
+                types.extend(value.named_children(&mut inner));
+            }
+            _ => types.push(value),
+        }
+    }
+    types
+        .into_iter()
+        .filter(|t| matches!(t.kind(), "identifier" | "attribute"))
+        .map(|t| exception(t, t, ExceptionKind::Handle, src, imports))
+        .collect()
+}
+
+fn exception<'s>(
+    ty: Node,
+    at: Node,
+    kind: ExceptionKind,
+    src: &'s [u8],
+    imports: &HashMap<String, String>,
+) -> Syntax<'s> {
+    Syntax {
+        node: ASTNode::Exception(Exception {
+            name: resolve_call(dotted_name(ty, src), imports),
+            kind,
+        }),
+        metadata: meta(at, src),
+        contains: vec![],
+    }
+}
+
+// ── Global State ────────────────────────────────────────────────────
+
+/// Resolved calls that build a mutable collection.
+const MUTABLE_CALLS: &[&str] = &[
+    "list",
+    "dict",
+    "set",
+    "bytearray",
+    "collections.defaultdict",
+    "collections.OrderedDict",
+    "collections.Counter",
+    "collections.deque",
+];
+
+/// Module-level names bound to a mutable collection or rebound by a
+/// function through `global`, with the names every function mentions.
+/// Only top-level statements define globals; the first binding wins.
+pub fn globals(tree: &Tree, source: &str) -> Globals {
+    let src = source.as_bytes();
+    let root = tree.root_node();
+    let imports = collect_imports(root, src);
+    let mut out = Globals::default();
+    let mut rebound = HashSet::new();
+    collect_uses(root, src, "", None, &mut out.uses, &mut rebound);
+
+    let mut cursor = root.walk();
+    for stmt in root.named_children(&mut cursor) {
+        let Some(assign) = stmt
+            .named_child(0)
+            .filter(|n| stmt.kind() == "expression_statement" && n.kind() == "assignment")
+        else {
+            continue;
+        };
+        let Some(left) = assign
+            .child_by_field_name("left")
+            .filter(|l| l.kind() == "identifier")
+        else {
+            continue;
+        };
+        let name = left.utf8_text(src).unwrap_or("");
+        let kind = if rebound.contains(name) {
+            GlobalKind::Rebound
+        } else if assign
+            .child_by_field_name("right")
+            .is_some_and(|r| is_container(r, src, &imports))
+        {
+            GlobalKind::Container
+        } else {
+            continue;
+        };
+        if out.defined.iter().all(|g| g.name != name) {
+            out.defined.push(Global {
+                name: name.to_string(),
+                kind,
+                span: meta(stmt, src).span,
+            });
+        }
+    }
+    out
+}
+
+fn is_container(value: Node, src: &[u8], imports: &HashMap<String, String>) -> bool {
+    match value.kind() {
+        "list"
+        | "dictionary"
+        | "set"
+        | "list_comprehension"
+        | "dictionary_comprehension"
+        | "set_comprehension" => true,
+        "call" => value
+            .child_by_field_name("function")
+            .is_some_and(|f| MUTABLE_CALLS.contains(&&*resolve_call(dotted_name(f, src), imports))),
+        _ => false,
+    }
+}
+
+/// Record the identifiers under each function into `uses`, and the names
+/// `global` statements declare into `rebound`.
+fn collect_uses<'s>(
+    node: Node,
+    src: &'s [u8],
+    prefix: &str,
+    function: Option<usize>,
+    uses: &mut Vec<(String, BTreeSet<String>)>,
+    rebound: &mut HashSet<&'s str>,
+) {
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        match child.kind() {
+            "function_definition" => {
+                let name = format!("{prefix}{}", field_text(child, "name", src));
+                uses.push((name, BTreeSet::new()));
+                let index = uses.len() - 1;
+                collect_uses(child, src, prefix, Some(index), uses, rebound);
+            }
+            "class_definition" => {
+                let prefix = format!("{prefix}{}.", field_text(child, "name", src));
+                collect_uses(child, src, &prefix, function, uses, rebound);
+            }
+            "identifier" => {
+                let name = child.utf8_text(src).unwrap_or("");
+                if child
+                    .parent()
+                    .is_some_and(|p| p.kind() == "global_statement")
+                {
+                    rebound.insert(name);
+                }
+                if let Some(i) = function {
+                    uses[i].1.insert(name.to_string());
+                }
+            }
+            _ => collect_uses(child, src, prefix, function, uses, rebound),
+        }
+    }
+}
+
+// ── Dynamic Imports ─────────────────────────────────────────────────
+
+/// Resolved call names that load a module at runtime.
+const DYNAMIC_IMPORTS: &[&str] = &["importlib.import_module", "__import__"];
+
+/// The first positional argument of a call, if it is a plain string literal.
+fn literal_arg<'s>(call: Node, src: &'s [u8]) -> Option<&'s str> {
+    let args = call.child_by_field_name("arguments")?;
+    args.named_child(0).and_then(|n| string_value(n, src))
+}
+
+/// The contents of a string literal; `None` for f-strings and non-strings.
+fn string_value<'s>(node: Node, src: &'s [u8]) -> Option<&'s str> {
+    if node.kind() != "string" {
+        return None;
+    }
+    let mut cursor = node.walk();
+    let mut content = None;
+    for part in node.named_children(&mut cursor) {
+        match part.kind() {
+            "string_content" => content = part.utf8_text(src).ok(),
+            "interpolation" => return None,
+            _ => {}
+        }
+    }
+    Some(content.unwrap_or(""))
+}
+
+/// Every plain string literal except docstrings; f-strings are skipped.
+fn collect_strings<'a, 's>(node: Node<'a>, src: &'s [u8], out: &mut Vec<(Node<'a>, Cow<'s, str>)>) {
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        match child.kind() {
+            "string" => out.extend(string_value(child, src).map(|v| (child, v.into()))),
+            "expression_statement"
+                if child.named_child_count() == 1
+                    && child.named_child(0).is_some_and(|c| c.kind() == "string") => {}
+            _ => collect_strings(child, src, out),
+        }
+    }
+}
+
+// ── Routes ──────────────────────────────────────────────────────────
+
+/// Decorator methods that register a route (`@app.route`, `@router.get`).
+const ROUTE_DECORATORS: &[&str] = &[
+    "route",
+    "api_route",
+    "get",
+    "post",
+    "put",
+    "patch",
+    "delete",
+    "head",
+    "options",
+    "websocket",
+];
+
+/// Django URL helpers, matched on the last segment of the resolved name.
+const URL_HELPERS: &[&str] = &["path", "re_path", "url"];
+
+/// Collect Flask/FastAPI decorated handlers and Django `urlpatterns` entries.
+fn collect_routes<'s>(
+    node: Node,
+    src: &'s [u8],
+    imports: &HashMap<String, String>,
+    out: &mut Vec<Syntax<'s>>,
+) {
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        match child.kind() {
+            "decorated_definition" => {
+                let handler = child
+                    .child_by_field_name("definition")
+                    .map(|d| field_text(d, "name", src))
+                    .unwrap_or_default();
+                let mut c = child.walk();
+                for dec in child.named_children(&mut c) {
+                    if let Some((route, methods)) = route_decorator(dec, src) {
+                        out.push(Syntax {
+                            node: ASTNode::Endpoint(Endpoint {
+                                route: route.into(),
+                                methods,
+                                handler: handler.into(),
+                            }),
+                            metadata: meta(dec, src),
+                            contains: vec![],
+                        });
+                    }
+                }
+                collect_routes(child, src, imports, out);
+            }
+
+            "assignment" | "augmented_assignment"
+                if field_text(child, "left", src) == "urlpatterns" =>
+            {
+                if let Some(right) = child.child_by_field_name("right") {
+                    url_patterns(right, src, imports, out);
+                }
+            }
+
+            _ => collect_routes(child, src, imports, out),
+        }
+    }
+}
+
+/// `@x.route("/p", methods=[..])` / `@x.get("/p")` → `(route, methods)`.
+fn route_decorator<'s>(dec: Node, src: &'s [u8]) -> Option<(&'s str, Vec<String>)> {
+    let call = dec.named_child(0).filter(|n| n.kind() == "call")?;
+    let func = call.child_by_field_name("function")?;
+    let verb = func
+        .child_by_field_name("attribute")
+        .filter(|_| func.kind() == "attribute")
+        .and_then(|n| n.utf8_text(src).ok())
+        .filter(|v| ROUTE_DECORATORS.contains(v))?;
+    let route = literal_arg(call, src)?;
+
+    let methods = match verb {
+        "route" | "api_route" => keyword_strings(call, "methods", src),
+        other => vec![other.to_ascii_uppercase()],
+    };
+    Some((route, methods))
+}
+
+/// String items of a list-valued keyword argument (`methods=["GET", "POST"]`).
+fn keyword_strings(call: Node, keyword: &str, src: &[u8]) -> Vec<String> {
+    let Some(args) = call.child_by_field_name("arguments") else {
+        return vec![];
+    };
+    let mut cursor = args.walk();
+    let value = args
+        .named_children(&mut cursor)
+        .filter(|a| a.kind() == "keyword_argument")
+        .find(|a| field_text(*a, "name", src) == keyword)
+        .and_then(|a| a.child_by_field_name("value"));
+    let Some(value) = value else {
+        return vec![];
+    };
+    let mut c = value.walk();
+    value
+        .named_children(&mut c)
+        .filter_map(|n| string_value(n, src))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Endpoints from `path("r/", view)` calls anywhere in a `urlpatterns` value.
+fn url_patterns<'s>(
+    node: Node,
+    src: &'s [u8],
+    imports: &HashMap<String, String>,
+    out: &mut Vec<Syntax<'s>>,
+) {
+    if node.kind() == "call" {
+        let name = node
+            .child_by_field_name("function")
+            .map(|f| resolve_call(dotted_name(f, src), imports))
+            .unwrap_or_default();
+        let helper = name.rsplit('.').next().unwrap_or("");
+        if URL_HELPERS.contains(&helper) {
+            let args = node.child_by_field_name("arguments");
+            let route = args
+                .and_then(|a| a.named_child(0))
+                .and_then(|n| string_value(n, src));
+            let handler = args
+                .and_then(|a| a.named_child(1))
+                .and_then(|n| view_name(n, src, imports));
+            if let (Some(route), Some(handler)) = (route, handler) {
+                out.push(Syntax {
+                    node: ASTNode::Endpoint(Endpoint {
+                        route: route.into(),
+                        methods: vec![],
+                        handler,
+                    }),
+                    metadata: meta(node, src),
+                    contains: vec![],
+                });
+            }
+            return;
+        }
+    }
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        url_patterns(child, src, imports, out);
+    }
+}
+
+/// Handler of a Django route: `views.index` or `views.Index.as_view()`.
+/// `include(...)` and other calls are not handlers.
+fn view_name<'s>(
+    node: Node,
+    src: &'s [u8],
+    imports: &HashMap<String, String>,
+) -> Option<Cow<'s, str>> {
+    match node.kind() {
+        "identifier" | "attribute" => Some(resolve_call(dotted_name(node, src), imports)),
+        "call" => {
+            let func = node.child_by_field_name("function")?;
+            let view = match dotted_name(func, src) {
+                Cow::Borrowed(name) => Cow::Borrowed(name.strip_suffix(".as_view")?),
+                Cow::Owned(name) => Cow::Owned(name.strip_suffix(".as_view")?.to_string()),
+            };
+            Some(resolve_call(view, imports))
+        }
+        _ => None,
+    }
+}
+
+// ── Decorators ──────────────────────────────────────────────────────
+
+/// The decorators of `decorated`, in source order and as written after `@`.
+fn decorators<'s>(decorated: Node, src: &'s [u8]) -> Vec<Cow<'s, str>> {
+    let mut cursor = decorated.walk();
+    decorated
+        .named_children(&mut cursor)
+        .filter(|n| n.kind() == "decorator")
+        .filter_map(|d| d.named_child(0)?.utf8_text(src).ok())
+        .map(Cow::Borrowed)
+        .collect()
+}
+
+// ── Fixtures ────────────────────────────────────────────────────────
+
+/// Resolved decorator names that declare a pytest fixture.
+const FIXTURE_DECORATORS: &[&str] = &["pytest.fixture", "pytest_asyncio.fixture"];
+
+/// If `decorated` is a fixture, its [`Fixture`] data and the wrapped function.
+fn fixture<'a, 's>(
+    decorated: Node<'a>,
+    src: &'s [u8],
+    imports: &HashMap<String, String>,
+) -> Option<(Fixture<'s>, Node<'a>)> {
+    let def = decorated
+        .child_by_field_name("definition")
+        .filter(|d| d.kind() == "function_definition")?;
+    let mut cursor = decorated.walk();
+    let expr = decorated
+        .named_children(&mut cursor)
+        .filter(|n| n.kind() == "decorator")
+        .filter_map(|d| d.named_child(0))
+        .find(|e| {
+            let target = if e.kind() == "call" {
+                e.child_by_field_name("function")
+            } else {
+                Some(*e)
+            };
+            target.is_some_and(|t| {
+                FIXTURE_DECORATORS.contains(&&*resolve_call(dotted_name(t, src), imports))
+            })
+        })?;
+
+    let keyword = |key: &str| {
+        let args = expr
+            .child_by_field_name("arguments")
+            .filter(|_| expr.kind() == "call")?;
+        let mut c = args.walk();
+        args.named_children(&mut c)
+            .filter(|a| a.kind() == "keyword_argument")
+            .find(|a| field_text(*a, "name", src) == key)
+            .and_then(|a| a.child_by_field_name("value"))
+            .and_then(|v| string_value(v, src))
+    };
+    let name = keyword("name").unwrap_or_else(|| field_text(def, "name", src));
+    let scope = keyword("scope");
+    Some((
+        Fixture {
+            name: name.into(),
+            scope: scope.map(Cow::Borrowed),
+        },
+        def,
+    ))
+}
+
+/// Parameters injected by name. Defaulted parameters and `self`/`cls` are not fixtures.
+fn fixture_params<'s>(func: Node, src: &'s [u8]) -> Vec<Syntax<'s>> {
+    let Some(params) = func.child_by_field_name("parameters") else {
+        return vec![];
+    };
+    let mut cursor = params.walk();
+    params
+        .named_children(&mut cursor)
+        .filter_map(|p| match p.kind() {
+            "identifier" => Some(p),
+            "typed_parameter" => p.named_child(0).filter(|n| n.kind() == "identifier"),
+            _ => None,
+        })
+        .filter_map(|ident| {
+            let name = ident.utf8_text(src).ok()?;
+            (!matches!(name, "self" | "cls")).then(|| Syntax {
+                node: ASTNode::UsesFixture(UsesFixture { name: name.into() }),
+                metadata: meta(ident, src),
+                contains: vec![],
+            })
+        })
+        .collect()
+}
+
+// ── Parameters ──────────────────────────────────────────────────────
+
+/// A function's parameters as written, `*`/`/` separators and splats
+/// included.
+fn params<'s>(func: Node, src: &'s [u8]) -> Vec<Param<'s>> {
+    let Some(params) = func.child_by_field_name("parameters") else {
+        return vec![];
+    };
+    let text = |n: Node| Cow::Borrowed(n.utf8_text(src).unwrap_or(""));
+    let field = |n: Node, f| n.child_by_field_name(f).map(text);
+    let mut cursor = params.walk();
+    params
+        .named_children(&mut cursor)
+        .filter_map(|p| {
+            let name = match p.kind() {
+                "comment" => return None,
+                "typed_parameter" => p.named_child(0).map(text)?,
+                "default_parameter" | "typed_default_parameter" => field(p, "name")?,
+                _ => text(p),
+            };
+            Some(Param {
+                name,
+                annotation: field(p, "type"),
+                default: field(p, "value"),
+            })
+        })
+        .collect()
+}
+
+// ── Signature Types ─────────────────────────────────────────────────
+
+/// Builtins that never resolve to a definition in the scanned tree.
+const BUILTIN_TYPES: &[&str] = &[
+    "int",
+    "float",
+    "complex",
+    "str",
+    "bytes",
+    "bool",
+    "object",
+    "list",
+    "dict",
+    "set",
+    "frozenset",
+    "tuple",
+    "type",
+];
+
+/// One [`UsesType`] per distinct type named in parameter/return annotations.
+fn signature_types<'s>(
+    func: Node,
+    src: &'s [u8],
+    imports: &HashMap<String, String>,
+) -> Vec<Syntax<'s>> {
+    let mut annotations = Vec::new();
+    if let Some(params) = func.child_by_field_name("parameters") {
+        let mut cursor = params.walk();
+        for p in params.named_children(&mut cursor) {
+            annotations.extend(p.child_by_field_name("type"));
+        }
+    }
+    annotations.extend(func.child_by_field_name("return_type"));
+
+    let mut refs = Vec::new();
+    for ann in annotations {
+        type_refs(ann, src, &mut refs);
+    }
+
+    let mut seen = HashSet::new();
+    refs.into_iter()
+        .filter_map(|(node, raw)| {
+            let name = resolve_call(raw, imports);
+            seen.insert(name.clone()).then(|| Syntax {
+                node: ASTNode::UsesType(UsesType { name }),
+                metadata: meta(node, src),
+                contains: vec![],
+            })
+        })
+        .collect()
+}
+
+/// Collect dotted type names inside an annotation (`Optional[a.B]` → `Optional`, `a.B`).
+fn type_refs<'a, 's>(node: Node<'a>, src: &'s [u8], out: &mut Vec<(Node<'a>, Cow<'s, str>)>) {
+    match node.kind() {
+        "identifier" | "attribute" => {
+            let name = dotted_name(node, src);
+            if !BUILTIN_TYPES.contains(&&*name) {
+                out.push((node, name));
+            }
+        }
+        // Forward references (`"Foo"`) are not resolved
+        "string" => {}
+        _ => {
+            let mut cursor = node.walk();
+            for child in node.named_children(&mut cursor) {
+                type_refs(child, src, out);
+            }
+        }
+    }
+}
+
+// ── Stub Signatures ─────────────────────────────────────────────────
+
+/// A module-level function or method and the arguments it accepts, for
+/// comparing `.pyi` stubs with their implementations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signature {
+    /// `f`, or `Class.method` (`Outer.Inner.method` when nested).
+    pub name: String,
+    pub public: bool,
+    pub arity: Arity,
+    /// Declared `@overload`; the variants of a name together give its arity.
+    pub overload: bool,
+    pub span: Span,
+}
+
+/// How many arguments a function takes, `self`/`cls` included.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Arity {
+    /// Parameters without a default.
+    pub min: usize,
+    /// All named parameters; `None` with `*args` or `**kwargs`.
+    pub max: Option<usize>,
+}
+
+impl Arity {
+    /// The arguments either of two overloads accepts.
+    pub fn union(self, other: Arity) -> Arity {
+        Arity {
+            min: self.min.min(other.min),
+            max: self.max.zip(other.max).map(|(a, b)| a.max(b)),
+        }
+    }
+}
+
+impl fmt::Display for Arity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.max {
+            Some(max) if max == self.min => write!(f, "{max}"),
+            Some(max) => write!(f, "{}-{max}", self.min),
+            None => write!(f, "{}+", self.min),
+        }
+    }
+}
+
+/// Signatures of the functions at module level and in classes, including
+/// those under `if` (`if sys.version_info >= ...:` in stubs). Functions
+/// nested in function bodies are left out.
+pub fn signatures(tree: &Tree, source: &str) -> Vec<Signature> {
+    let mut out = Vec::new();
+    collect_signatures(tree.root_node(), source.as_bytes(), "", &mut out);
+    out
+}
+
+fn collect_signatures(node: Node, src: &[u8], prefix: &str, out: &mut Vec<Signature>) {
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        let (def, overload) = match child.kind() {
+            "decorated_definition" => {
+                let Some(def) = child.child_by_field_name("definition") else {
+                    continue;
+                };
+                let mut c = child.walk();
+                let overload = child
+                    .named_children(&mut c)
+                    .filter(|d| d.kind() == "decorator")
+                    .filter_map(|d| d.named_child(0))
+                    .any(|e| matches!(e.utf8_text(src), Ok("overload" | "typing.overload")));
+                (def, overload)
+            }
+            "if_statement" | "else_clause" | "elif_clause" | "block" => {
+                collect_signatures(child, src, prefix, out);
+                continue;
+            }
+            _ => (child, false),
+        };
+        let name = field_text(def, "name", src);
+        match def.kind() {
+            "function_definition" => out.push(Signature {
+                name: format!("{prefix}{name}"),
+                public: is_public(name),
+                arity: arity(def),
+                overload,
+                span: meta(child, src).span,
+            }),
+            "class_definition" => {
+                if let Some(body) = def.child_by_field_name("body") {
+                    collect_signatures(body, src, &format!("{prefix}{name}."), out);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn arity(func: Node) -> Arity {
+    let mut arity = Arity {
+        min: 0,
+        max: Some(0),
+    };
+    let Some(params) = func.child_by_field_name("parameters") else {
+        return arity;
+    };
+    let mut cursor = params.walk();
+    for p in params.named_children(&mut cursor) {
+        // `*args: int` is a typed_parameter around the splat.
+        let kind = match p.kind() {
+            "typed_parameter" => p.named_child(0).map_or("identifier", |n| n.kind()),
+            k => k,
+        };
+        match kind {
+            "identifier" => {
+                arity.min += 1;
+                arity.max = arity.max.map(|m| m + 1);
+            }
+            "default_parameter" | "typed_default_parameter" => {
+                arity.max = arity.max.map(|m| m + 1);
+            }
+            "list_splat_pattern" | "dictionary_splat_pattern" => arity.max = None,
+            // `/` and `*` separators take no argument.
+            _ => {}
+        }
+    }
+    arity
+}
+
 // ── Helpers ─────────────────────────────────────────────────────────
 
-fn field_text(node: Node, field: &str, src: &[u8]) -> String {
+fn field_text<'s>(node: Node, field: &str, src: &'s [u8]) -> &'s str {
     node.child_by_field_name(field)
         .and_then(|n| n.utf8_text(src).ok())
         .unwrap_or("")
-        .to_string()
 }
 
-fn body_children(node: Node, src: &[u8], imports: &HashMap<String, String>) -> Vec<Syntax> {
+fn body_children<'s>(
+    node: Node,
+    src: &'s [u8],
+    imports: &HashMap<String, String>,
+) -> Vec<Syntax<'s>> {
     node.child_by_field_name("body")
         .map(|b| walk(b, src, imports))
         .unwrap_or_default()
 }
 
-/// Resolve `a.b.c` from nested attribute nodes.
-fn dotted_name(node: Node, src: &[u8]) -> String {
-    match node.kind() {
-        "identifier" => node.utf8_text(src).unwrap_or("").to_string(),
-        "attribute" => {
-            let obj = node
-                .child_by_field_name("object")
-                .map(|n| dotted_name(n, src))
-                .unwrap_or_default();
-            let attr = node
-                .child_by_field_name("attribute")
-                .and_then(|n| n.utf8_text(src).ok())
-                .unwrap_or("");
-            format!("{obj}.{attr}")
-        }
-        _ => node.utf8_text(src).unwrap_or("").to_string(),
+/// True if `node` sits directly in a class body (not in a method).
+fn in_class_body(node: Node) -> bool {
+    node.parent()
+        .filter(|p| p.kind() == "block")
+        .and_then(|p| p.parent())
+        .is_some_and(|g| g.kind() == "class_definition")
+}
+
+/// The `assignment` of a class-level statement (`x = 1`, `x: int`,
+/// `x, y = 1, 2`) and the names it binds, when all of them are plain names.
+fn class_field(stmt: Node) -> Option<(Node, Vec<Node>)> {
+    if !in_class_body(stmt) || stmt.named_child_count() != 1 {
+        return None;
     }
+    let assign = stmt.named_child(0).filter(|n| n.kind() == "assignment")?;
+    let left = assign.child_by_field_name("left")?;
+    let names: Vec<_> = match left.kind() {
+        "identifier" => vec![left],
+        "pattern_list" | "tuple_pattern" => left.named_children(&mut left.walk()).collect(),
+        _ => return None,
+    };
+    names
+        .iter()
+        .all(|n| n.kind() == "identifier")
+        .then_some((assign, names))
+}
+
+/// Resolve `a.b.c` from nested attribute nodes. Borrowed unless the source
+/// spells it with whitespace or comments between the segments.
+fn dotted_name<'s>(node: Node, src: &'s [u8]) -> Cow<'s, str> {
+    let text = node.utf8_text(src).unwrap_or("");
+    if node.kind() != "attribute" {
+        return Cow::Borrowed(text);
+    }
+    let obj = node
+        .child_by_field_name("object")
+        .map(|n| dotted_name(n, src))
+        .unwrap_or_default();
+    let attr = node
+        .child_by_field_name("attribute")
+        .and_then(|n| n.utf8_text(src).ok())
+        .unwrap_or("");
+    join(text, &obj, ".", attr)
 }
 
 /// Replace the first segment of a dotted call with its import mapping.
-fn resolve_call(name: &str, imports: &HashMap<String, String>) -> String {
+fn resolve_call<'s>(name: Cow<'s, str>, imports: &HashMap<String, String>) -> Cow<'s, str> {
     let (head, tail) = match name.split_once('.') {
         Some((h, t)) => (h, Some(t)),
-        None => (name, None),
+        None => (&*name, None),
     };
     match imports.get(head) {
         Some(module) => match tail {
-            Some(rest) => format!("{module}.{rest}"),
-            None => module.clone(),
+            Some(rest) => Cow::Owned(format!("{module}.{rest}")),
+            None => Cow::Owned(module.clone()),
         },
-        None => name.to_string(),
+        None => name,
     }
 }
 
+/// Bases, metaclasses and decorators that make a class an interface.
+const ABSTRACT_BASES: &[&str] = &["abc.ABC", "typing.Protocol", "typing_extensions.Protocol"];
+const ABSTRACT_META: &str = "abc.ABCMeta";
+const ABSTRACT_METHOD: &str = "abc.abstractmethod";
+
+/// True if the class derives from an [`ABSTRACT_BASES`] entry, uses
+/// [`ABSTRACT_META`], or declares an `@abstractmethod`. Unimported names
+/// match on their last segment (`ABC`, `Protocol`).
+fn is_abstract(class: Node, src: &[u8], imports: &HashMap<String, String>) -> bool {
+    let is = |node: Node, names: &[&str]| {
+        let name = resolve_call(dotted_name(node, src), imports);
+        names
+            .iter()
+            .any(|n| *n == name || n.rsplit('.').next() == Some(&*name))
+    };
+    let mut cursor = class.walk();
+    let bases = class
+        .child_by_field_name("superclasses")
+        .map(|args| args.named_children(&mut cursor).collect::<Vec<_>>())
+        .unwrap_or_default();
+    let abstract_base = bases.iter().any(|&base| match base.kind() {
+        "keyword_argument" => {
+            base.child_by_field_name("name")
+                .and_then(|n| n.utf8_text(src).ok())
+                == Some("metaclass")
+                && base
+                    .child_by_field_name("value")
+                    .is_some_and(|v| is(v, &[ABSTRACT_META]))
+        }
+        // `Protocol[T]`
+        "subscript" => base
+            .child_by_field_name("value")
+            .is_some_and(|v| is(v, ABSTRACT_BASES)),
+        _ => is(base, ABSTRACT_BASES),
+    });
+    if abstract_base {
+        return true;
+    }
+
+    let mut cursor = class.walk();
+    let Some(body) = class.child_by_field_name("body") else {
+        return false;
+    };
+    let decorated: Vec<Node> = body
+        .named_children(&mut cursor)
+        .filter(|n| n.kind() == "decorated_definition")
+        .collect();
+    decorated.into_iter().any(|d| {
+        let mut cursor = d.walk();
+        d.named_children(&mut cursor)
+            .filter(|n| n.kind() == "decorator")
+            .filter_map(|n| n.named_child(0))
+            .any(|e| is(e, &[ABSTRACT_METHOD]))
+    })
+}
+
+/// Public by convention: no leading underscore, or a dunder like `__init__`.
+fn is_public(name: &str) -> bool {
+    !name.starts_with('_') || (name.starts_with("__") && name.ends_with("__"))
+}
+
 fn meta(node: Node, src: &[u8]) -> Metadata {
-    metadata_from_span(src, node.start_byte(), node.end_byte())
+    metadata_from_node(src, node)
+}
+
+/// [`meta`] of `node` with the cyclomatic complexity of the function `def`.
+fn measured(node: Node, def: Node, src: &[u8]) -> Metadata {
+    let mut metadata = meta(node, src);
+    if cfg!(feature = "complexity")
+        && let Some(body) = def.child_by_field_name("body")
+    {
+        let n = complexity(
+            body,
+            &decisions,
+            &["function_definition", "class_definition"],
+        );
+        metadata.set_metric(COMPLEXITY, n);
+    }
+    metadata
+}
+
+/// Decision points: `if`/`elif`, loops, `except` clauses, conditional
+/// expressions, `and`/`or`, comprehension `for`/`if` clauses, and each
+/// `case` of a `match` past the first.
+fn decisions(node: Node) -> usize {
+    match node.kind() {
+        "if_statement"
+        | "elif_clause"
+        | "for_statement"
+        | "while_statement"
+        | "except_clause"
+        | "conditional_expression"
+        | "boolean_operator"
+        | "for_in_clause"
+        | "if_clause" => 1,
+        "match_statement" => {
+            let mut cursor = node.walk();
+            let cases = node.child_by_field_name("body").map_or(0, |b| {
+                b.named_children(&mut cursor)
+                    .filter(|c| c.kind() == "case_clause")
+                    .count()
+            });
+            cases.saturating_sub(1)
+        }
+        _ => 0,
+    }
 }
 
 // ── Tests ──────────────────────────────────────────────────────────
@@ -234,12 +1293,40 @@ fn meta(node: Node, src: &[u8]) -> Metadata {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::api::tree_sitter::Lang;
+    use crate::api::tree_sitter::{Lang, LiteralKind, Strings};
+
+    fn parse(source: &str) -> Vec<Syntax<'_>> {
+        let lang = Python;
+        let mut parser = lang.get_parser();
+        lang.parse(&mut parser, source, &Options::default())
+            .unwrap()
+    }
+
+    fn parse_routes(source: &str) -> Vec<Endpoint<'_>> {
+        let lang = Python;
+        let mut parser = lang.get_parser();
+        let opts = Options {
+            routes: true,
+            ..Options::default()
+        };
+        lang.parse(&mut parser, source, &opts)
+            .unwrap()
+            .into_iter()
+            .filter_map(|s| match s.node {
+                ASTNode::Endpoint(e) => Some(e),
+                _ => None,
+            })
+            .collect()
+    }
 
-    fn parse(source: &str) -> Vec<Syntax> {
+    fn parse_strings(source: &str, which: Strings) -> Vec<Syntax<'_>> {
         let lang = Python;
         let mut parser = lang.get_parser();
-        lang.parse(&mut parser, source).unwrap()
+        let opts = Options {
+            strings: Some(which),
+            ..Options::default()
+        };
+        lang.parse(&mut parser, source, &opts).unwrap()
     }
 
     fn names(nodes: &[Syntax]) -> Vec<String> {
@@ -248,13 +1335,86 @@ mod tests {
             .map(|s| match &s.node {
                 ASTNode::Function(f) => format!("fn:{}", f.name),
                 ASTNode::Type(t) => format!("ty:{}", t.name),
+                ASTNode::Field(f) => format!("field:{}", f.name),
+                ASTNode::Variant(v) => format!("variant:{}", v.name),
                 ASTNode::Call(c) => format!("call:{}", c.name),
+                ASTNode::UsesType(u) => format!("uses:{}", u.name),
+                ASTNode::Module(m) => format!("mod:{}", m.name),
+                ASTNode::Import(i) => {
+                    let mut name = format!("import:{}", i.module);
+                    if !i.items.is_empty() {
+                        name += &format!(":{}", i.items.join(","));
+                    }
+                    if let Some(alias) = &i.alias {
+                        name += &format!(" as {alias}");
+                    }
+                    name
+                }
+                ASTNode::DynamicImport(d) => {
+                    format!("dyn:{}", d.module.as_deref().unwrap_or("?"))
+                }
+                ASTNode::Endpoint(e) => format!("endpoint:{}", e.route),
+                ASTNode::Fixture(f) => format!("fixture:{}", f.name),
+                ASTNode::UsesFixture(u) => format!("needs:{}", u.name),
+                ASTNode::Unsafe(u) => format!("unsafe:{:?}", u.kind),
+                ASTNode::Attribute(a) => format!("attr:{}({})", a.name, a.args.join(", ")),
+                ASTNode::Exception(e) => format!("{:?}:{}", e.kind, e.name),
+                ASTNode::Literal(l) => format!("str:{}", l.value),
                 ASTNode::Comment => "comment".into(),
+                ASTNode::Unknown(v) => format!("unknown:{v}"),
                 ASTNode::File(f) => format!("file:{}", f.path),
             })
             .collect()
     }
 
+    fn is_abstract_class(source: &str) -> bool {
+        parse(source)
+            .iter()
+            .find_map(|s| match &s.node {
+                ASTNode::Type(t) => Some(t.is_abstract),
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    // ── Abstract classes ──
+
+    #[test]
+    fn plain_class_concrete() {
+        assert!(!is_abstract_class(
+            "class A(Base):\n    def f(self): pass\n"
+        ));
+    }
+
+    #[test]
+    fn abc_and_protocol_bases_abstract() {
+        assert!(is_abstract_class(
+            "from abc import ABC\nclass A(ABC): pass\n"
+        ));
+        assert!(is_abstract_class("import abc\nclass A(abc.ABC): pass\n"));
+        assert!(is_abstract_class(
+            "from typing import Protocol\nclass P(Protocol): pass\n"
+        ));
+        assert!(is_abstract_class("class P(Protocol[T]): pass\n"));
+    }
+
+    #[test]
+    fn abcmeta_metaclass_abstract() {
+        assert!(is_abstract_class(
+            "import abc\nclass A(metaclass=abc.ABCMeta): pass\n"
+        ));
+        assert!(!is_abstract_class("class A(metaclass=Meta): pass\n"));
+    }
+
+    #[test]
+    fn abstractmethod_makes_class_abstract() {
+        let src = "from abc import abstractmethod\nclass A:\n    @abstractmethod\n    def f(self): pass\n";
+        assert!(is_abstract_class(src));
+        assert!(!is_abstract_class(
+            "class A:\n    @property\n    def f(self): pass\n"
+        ));
+    }
+
     // ── Empty ──
 
     #[test]
@@ -281,8 +1441,71 @@ mod tests {
         assert!(inner.contains(&"call:run".to_string()));
     }
 
+    // ── Signature Types ──
+
+    #[test]
+    fn signature_types_resolved() {
+        let src = "\
+from app.models import User
+def load(uid: int, into: List[User], cfg: conf.Settings = None) -> Optional[User]:
+    pass
+";
+        let nodes = parse(src);
+        assert_eq!(
+            names(&nodes[1].contains),
+            vec![
+                "uses:List",
+                "uses:app.models.User",
+                "uses:conf.Settings",
+                "uses:Optional"
+            ]
+        );
+    }
+
+    #[test]
+    fn signature_types_skip_builtins_and_strings() {
+        let src = "def f(a: int, b: \"Later\", *args: str) -> None:\n    pass\n";
+        let nodes = parse(src);
+        assert!(nodes[0].contains.is_empty());
+    }
+
+    #[test]
+    fn signature_types_before_body() {
+        let src = "def f(x: Foo):\n    run()\n";
+        let nodes = parse(src);
+        assert_eq!(names(&nodes[0].contains), vec!["uses:Foo", "call:run"]);
+    }
+
     // ── Classes ──
 
+    #[test]
+    fn function_params_and_return_type() {
+        let src =
+            "def f(self, a, b: int = 2, /, *args: str, c=None, **kw) -> list[str]:\n    pass\n";
+        let nodes = parse(src);
+        let ASTNode::Function(f) = &nodes[0].node else {
+            panic!("expected function");
+        };
+        let params: Vec<(&str, Option<&str>, Option<&str>)> = f
+            .params
+            .iter()
+            .map(|p| (&*p.name, p.annotation.as_deref(), p.default.as_deref()))
+            .collect();
+        assert_eq!(
+            params,
+            [
+                ("self", None, None),
+                ("a", None, None),
+                ("b", Some("int"), Some("2")),
+                ("/", None, None),
+                ("*args", Some("str"), None),
+                ("c", None, Some("None")),
+                ("**kw", None, None),
+            ]
+        );
+        assert_eq!(f.return_type.as_deref(), Some("list[str]"));
+    }
+
     #[test]
     fn simple_class() {
         let src = "class Foo:\n    pass\n";
@@ -300,6 +1523,59 @@ mod tests {
         assert_eq!(inner, vec!["fn:bark", "fn:wag"]);
     }
 
+    // ── Fields ──
+
+    #[test]
+    fn class_attributes() {
+        let src =
+            "class Cfg:\n    debug = False\n    name: str\n    def run(self):\n        x = 1\n";
+        let nodes = parse(src);
+        let inner = names(&nodes[0].contains);
+        assert_eq!(inner, vec!["field:debug", "field:name", "fn:run"]);
+        // Method locals are not fields
+        assert!(nodes[0].contains[2].contains.is_empty());
+    }
+
+    #[test]
+    fn dataclass_fields_with_annotations() {
+        let src = "\
+@dataclass
+class Point:
+    x: int
+    tags: List[str] = field(default_factory=list)
+";
+        let nodes = parse(src);
+        let fields: Vec<_> = nodes[0]
+            .contains
+            .iter()
+            .filter_map(|s| match &s.node {
+                ASTNode::Field(f) => Some((f.name.as_ref(), f.annotation.as_deref())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            fields,
+            vec![("x", Some("int")), ("tags", Some("List[str]"))]
+        );
+        // Default factory call is kept under the field
+        assert_eq!(names(&nodes[0].contains[1].contains), vec!["call:field"]);
+    }
+
+    #[test]
+    fn tuple_assignment_gives_a_field_per_name() {
+        let src = "class A:\n    z, w = 1, make()\n    (x, y) = 3, 4\n    p.q, r = 5, 6\n";
+        let nodes = parse(src);
+        let inner = names(&nodes[0].contains);
+        assert_eq!(inner, vec!["field:z", "field:w", "field:x", "field:y"]);
+        assert_eq!(names(&nodes[0].contains[1].contains), vec!["call:make"]);
+    }
+
+    #[test]
+    fn module_assignment_not_a_field() {
+        let nodes = parse("x: int = 1\n");
+        assert!(nodes.is_empty());
+    }
+
     // ── Calls ──
 
     #[test]
@@ -338,28 +1614,64 @@ mod tests {
     fn from_import_resolves() {
         let src = "from os.path import join\njoin('a', 'b')\n";
         let nodes = parse(src);
-        assert_eq!(names(&nodes), vec!["call:os.path.join"]);
+        assert_eq!(
+            names(&nodes),
+            vec!["import:os.path:join", "call:os.path.join"]
+        );
     }
 
     #[test]
     fn from_import_dotted_module() {
         let src = "from .models import Request\nRequest()\n";
         let nodes = parse(src);
-        assert_eq!(names(&nodes), vec!["call:.models.Request"]);
+        assert_eq!(
+            names(&nodes),
+            vec!["import:.models:Request", "call:.models.Request"]
+        );
     }
 
     #[test]
     fn aliased_import_resolves() {
         let src = "import numpy as np\nnp.array([1])\n";
         let nodes = parse(src);
-        assert_eq!(names(&nodes), vec!["call:numpy.array"]);
+        assert_eq!(
+            names(&nodes),
+            vec!["import:numpy as np", "call:numpy.array"]
+        );
     }
 
     #[test]
     fn from_import_with_alias() {
         let src = "from collections import OrderedDict as OD\nOD()\n";
         let nodes = parse(src);
-        assert_eq!(names(&nodes), vec!["call:collections.OrderedDict"]);
+        assert_eq!(
+            names(&nodes),
+            vec![
+                "import:collections:OrderedDict as OD",
+                "call:collections.OrderedDict"
+            ]
+        );
+    }
+
+    #[test]
+    fn import_statements_become_imports() {
+        let src = "import os, numpy as np\nfrom a.b import x, y as z, w\nfrom . import *\n\
+                   from __future__ import annotations\ndef f():\n    import json\n";
+        let nodes = parse(src);
+        assert_eq!(
+            names(&nodes),
+            vec![
+                "import:os",
+                "import:numpy as np",
+                "import:a.b:x,w",
+                "import:a.b:y as z",
+                "import:.:*",
+                "import:__future__:annotations",
+                "fn:f"
+            ]
+        );
+        assert_eq!(names(&nodes[6].contains), vec!["import:json"]);
+        assert_eq!(nodes[2].metadata.span.start_line, 2);
     }
 
     #[test]
@@ -369,26 +1681,289 @@ mod tests {
         assert_eq!(names(&nodes), vec!["call:unknown_func"]);
     }
 
+    // ── Spawned Work ──
+
+    #[test]
+    fn spawned_targets_and_coroutines() {
+        let src = "import asyncio\nfrom multiprocessing import Process\n\
+                   def main():\n    asyncio.create_task(fetch(url))\n    \
+                   p = Process(target=worker, args=(prepare(),))\n    \
+                   asyncio.ensure_future(lambda: tick())\n";
+        let nodes = parse(src);
+        let calls = &nodes[2].contains;
+        assert_eq!(
+            names(calls),
+            vec![
+                "call:asyncio.create_task",
+                "call:multiprocessing.Process",
+                "call:asyncio.ensure_future"
+            ]
+        );
+        let spawned: Vec<Vec<String>> = calls
+            .iter()
+            .map(|s| {
+                s.contains
+                    .iter()
+                    .filter_map(|w| match &w.node {
+                        ASTNode::Call(c) => {
+                            Some(format!("{} by {}", c.name, c.spawned_by.as_deref()?))
+                        }
+                        _ => None,
+                    })
+                    .collect()
+            })
+            .collect();
+        assert_eq!(
+            spawned,
+            [
+                ["fetch by asyncio.create_task"],
+                ["worker by multiprocessing.Process"],
+                ["tick by asyncio.ensure_future"],
+            ]
+        );
+    }
+
+    // ── Exceptions ──
+
+    #[test]
+    fn raise_resolves_exception_type() {
+        let src = "from app import errors\n\
+                   def load():\n    raise errors.NotFound(\"x\") from None\n";
+        let nodes = parse(src);
+        assert_eq!(
+            names(&nodes[1].contains),
+            vec!["Raise:app.errors.NotFound", "call:app.errors.NotFound"]
+        );
+    }
+
+    #[test]
+    fn bare_raise_and_reraise_skipped() {
+        let src = "def f():\n    try:\n        g()\n    except KeyError as e:\n        \
+                   log(e)\n        raise e\n    except OSError:\n        raise\n";
+        let nodes = parse(src);
+        assert_eq!(
+            names(&nodes[0].contains),
+            vec!["call:g", "Handle:KeyError", "call:log", "Handle:OSError"]
+        );
+    }
+
+    #[test]
+    fn except_tuple_and_bare() {
+        let src = "try:\n    f()\nexcept (ValueError, socket.timeout):\n    pass\n\
+                   except:\n    raise Fatal\n";
+        let nodes = parse(src);
+        assert_eq!(
+            names(&nodes),
+            vec![
+                "call:f",
+                "Handle:ValueError",
+                "Handle:socket.timeout",
+                "Handle:BaseException",
+                "Raise:Fatal"
+            ]
+        );
+    }
+
+    // ── Dynamic Imports ──
+
+    #[test]
+    fn import_module_literal() {
+        let src = "import importlib\nplugin = importlib.import_module(\"app.plugins.csv\")\n";
+        let nodes = parse(src);
+        assert_eq!(
+            names(&nodes),
+            vec!["import:importlib", "dyn:app.plugins.csv"]
+        );
+    }
+
+    #[test]
+    fn import_module_from_import() {
+        let src = "from importlib import import_module\nimport_module('pkg')\n";
+        let nodes = parse(src);
+        assert_eq!(
+            names(&nodes),
+            vec!["import:importlib:import_module", "dyn:pkg"]
+        );
+    }
+
+    #[test]
+    fn dunder_import_literal() {
+        let nodes = parse("__import__('json')\n");
+        assert_eq!(names(&nodes), vec!["dyn:json"]);
+    }
+
+    #[test]
+    fn dynamic_import_unknown_target() {
+        let src = "import importlib\nimportlib.import_module(name)\nimportlib.import_module(f\"x.{name}\")\n";
+        let nodes = parse(src);
+        assert_eq!(names(&nodes), vec!["import:importlib", "dyn:?", "dyn:?"]);
+    }
+
+    // ── Fixtures ──
+
+    #[test]
+    fn fixture_definition() {
+        let src = "import pytest\n@pytest.fixture\ndef db():\n    connect()\n";
+        let nodes = parse(src);
+        assert_eq!(names(&nodes), vec!["import:pytest", "fixture:db"]);
+        assert_eq!(names(&nodes[1].contains), vec!["call:connect"]);
+    }
+
+    #[test]
+    fn fixture_with_name_and_scope() {
+        let src = "\
+from pytest import fixture
+@fixture(scope='module', name='client')
+def make_client(db, tmp_path):
+    pass
+";
+        let nodes = parse(src);
+        let ASTNode::Fixture(f) = &nodes[1].node else {
+            panic!("expected fixture");
+        };
+        assert_eq!(f.name, "client");
+        assert_eq!(f.scope.as_deref(), Some("module"));
+        assert_eq!(
+            names(&nodes[1].contains),
+            vec!["needs:db", "needs:tmp_path"]
+        );
+    }
+
+    #[test]
+    fn test_function_uses_fixtures() {
+        let src = "def test_login(client, user: User, retries=3):\n    client.post()\n";
+        let nodes = parse(src);
+        assert_eq!(
+            names(&nodes[0].contains),
+            vec![
+                "uses:User",
+                "needs:client",
+                "needs:user",
+                "call:client.post"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_method_skips_self() {
+        let src = "class TestApi:\n    def test_get(self, client):\n        pass\n";
+        let nodes = parse(src);
+        assert_eq!(names(&nodes[0].contains[0].contains), vec!["needs:client"]);
+    }
+
+    #[test]
+    fn plain_function_params_not_fixtures() {
+        let nodes = parse("def helper(client):\n    pass\n");
+        assert!(nodes[0].contains.is_empty());
+    }
+
+    #[test]
+    fn other_decorators_still_walked() {
+        let src = "@app.route('/')\ndef index():\n    pass\n";
+        assert_eq!(names(&parse(src)), vec!["call:app.route", "fn:index"]);
+    }
+
+    #[test]
+    fn decorators_kept_as_written() {
+        let src = "@app.route('/')\n@login_required\ndef index():\n    pass\n\
+                   @dataclass(frozen=True)\nclass P:\n    @staticmethod\n    def make(): pass\n";
+        let nodes = parse(src);
+        assert_eq!(
+            names(&nodes),
+            vec!["call:app.route", "fn:index", "call:dataclass", "ty:P"]
+        );
+        let ASTNode::Function(f) = &nodes[1].node else {
+            panic!("expected fn");
+        };
+        assert_eq!(f.decorators, vec!["app.route('/')", "login_required"]);
+        let ASTNode::Type(t) = &nodes[3].node else {
+            panic!("expected class");
+        };
+        assert_eq!(t.decorators, vec!["dataclass(frozen=True)"]);
+        let ASTNode::Function(m) = &nodes[3].contains[0].node else {
+            panic!("expected method");
+        };
+        assert_eq!(m.decorators, vec!["staticmethod"]);
+    }
+
+    // ── Routes ──
+
+    #[test]
+    fn routes_off_by_default() {
+        let src = "@app.route('/')\ndef index():\n    pass\n";
+        let nodes = parse(src);
+        assert!(!nodes.iter().any(|s| matches!(s.node, ASTNode::Endpoint(_))));
+    }
+
+    #[test]
+    fn flask_route_with_methods() {
+        let src = "@app.route('/users/<id>', methods=['GET', 'POST'])\ndef user(id):\n    pass\n";
+        let eps = parse_routes(src);
+        assert_eq!(eps.len(), 1);
+        assert_eq!(eps[0].route, "/users/<id>");
+        assert_eq!(eps[0].methods, vec!["GET", "POST"]);
+        assert_eq!(eps[0].handler, "user");
+    }
+
+    #[test]
+    fn fastapi_verb_decorator() {
+        let src = "@router.get('/items')\nasync def items():\n    pass\n";
+        let eps = parse_routes(src);
+        assert_eq!(eps[0].methods, vec!["GET"]);
+        assert_eq!(eps[0].handler, "items");
+    }
+
+    #[test]
+    fn non_route_decorator_ignored() {
+        let src = "@functools.cache\n@pytest.mark.parametrize('x', [1])\ndef f(x):\n    pass\n";
+        assert!(parse_routes(src).is_empty());
+    }
+
+    #[test]
+    fn django_urlpatterns() {
+        let src = "\
+from django.urls import path, include
+from . import views
+urlpatterns = [
+    path('users/', views.user_list, name='users'),
+    path('about/', views.About.as_view()),
+    path('api/', include('api.urls')),
+]
+";
+        let eps = parse_routes(src);
+        let got: Vec<_> = eps
+            .iter()
+            .map(|e| (e.route.as_ref(), e.handler.as_ref()))
+            .collect();
+        assert_eq!(
+            got,
+            vec![("users/", ".views.user_list"), ("about/", ".views.About")]
+        );
+    }
+
     // ── resolve_call unit ──
 
     #[test]
     fn resolve_call_with_mapping() {
         let mut imports = HashMap::new();
         imports.insert("pd".to_string(), "pandas".to_string());
-        assert_eq!(resolve_call("pd.DataFrame", &imports), "pandas.DataFrame");
+        assert_eq!(
+            resolve_call("pd.DataFrame".into(), &imports),
+            "pandas.DataFrame"
+        );
     }
 
     #[test]
     fn resolve_call_no_mapping() {
         let imports = HashMap::new();
-        assert_eq!(resolve_call("foo.bar", &imports), "foo.bar");
+        assert_eq!(resolve_call("foo.bar".into(), &imports), "foo.bar");
     }
 
     #[test]
     fn resolve_call_bare_name() {
         let mut imports = HashMap::new();
         imports.insert("Request".to_string(), "http.Request".to_string());
-        assert_eq!(resolve_call("Request", &imports), "http.Request");
+        assert_eq!(resolve_call("Request".into(), &imports), "http.Request");
     }
 
     // ── qualify unit ──
@@ -418,6 +1993,57 @@ mod tests {
         assert!(nodes[0].metadata.lines >= 2);
     }
 
+    // ── Complexity ──
+
+    #[cfg(feature = "complexity")]
+    fn complexity_of(s: &Syntax) -> Option<u64> {
+        s.metadata.metric(COMPLEXITY).and_then(|v| v.as_u64())
+    }
+
+    #[cfg(feature = "complexity")]
+    #[test]
+    fn straight_line_function_has_complexity_one() {
+        let nodes = parse("def f():\n    x = 1\n    return x\n");
+        assert_eq!(complexity_of(&nodes[0]), Some(1));
+    }
+
+    #[cfg(feature = "complexity")]
+    #[test]
+    fn branches_loops_and_boolean_operators_count() {
+        let src = "def f(a, b):\n    if a and b or a:\n        pass\n    elif b:\n        pass\n    \
+                   else:\n        pass\n    for x in a:\n        while b:\n            pass\n    \
+                   try:\n        pass\n    except E:\n        pass\n    \
+                   y = [i for i in a if i]\n    return 1 if a else 2\n";
+        // if, and, or, elif, for, while, except, comprehension for and if,
+        // conditional expression.
+        assert_eq!(complexity_of(&parse(src)[0]), Some(11));
+    }
+
+    #[cfg(feature = "complexity")]
+    #[test]
+    fn match_counts_cases_past_the_first() {
+        let src = "def f(x):\n    match x:\n        case 1:\n            pass\n        \
+                   case 2:\n            pass\n        case _:\n            pass\n";
+        assert_eq!(complexity_of(&parse(src)[0]), Some(3));
+    }
+
+    #[cfg(feature = "complexity")]
+    #[test]
+    fn nested_definitions_measured_separately() {
+        let src = "class C:\n    def m(self, a):\n        def inner():\n            \
+                   if a:\n                pass\n        if a:\n            pass\n";
+        let nodes = parse(src);
+        assert_eq!(complexity_of(&nodes[0]), None);
+        let m = &nodes[0].contains[0];
+        assert_eq!(complexity_of(m), Some(2));
+        let inner = m
+            .contains
+            .iter()
+            .find(|s| matches!(s.node, ASTNode::Function(_)))
+            .unwrap();
+        assert_eq!(complexity_of(inner), Some(2));
+    }
+
     // ── Mixed ──
 
     #[test]
@@ -439,4 +2065,150 @@ hello()
         assert!(n.contains(&"fn:run".to_string()));
         assert!(n.contains(&"call:hello".to_string()));
     }
+
+    // ── String Literals ──
+
+    #[test]
+    fn strings_off_by_default() {
+        let nodes = parse("def f():\n    return 'x'\n");
+        assert_eq!(names(&nodes[0].contains), Vec::<String>::new());
+    }
+
+    #[test]
+    fn strings_nested_in_function() {
+        let src =
+            "URL = 'https://x.io'\ndef f():\n    '''Doc.'''\n    g('hi')\n    return f'{x}'\n";
+        let nodes = parse_strings(src, Strings::All);
+        assert_eq!(names(&nodes), vec!["str:https://x.io", "fn:f"]);
+        // docstring stays a comment, f-strings are skipped
+        assert_eq!(
+            names(&nodes[1].contains),
+            vec!["comment", "call:g", "str:hi"]
+        );
+    }
+
+    #[test]
+    fn strings_interesting_only() {
+        let src =
+            "def q(db):\n    db.run('SELECT * FROM t')\n    log('done')\n    open('/tmp/x.log')\n";
+        let nodes = parse_strings(src, Strings::Interesting);
+        let kinds: Vec<_> = nodes[0]
+            .contains
+            .iter()
+            .filter_map(|s| match &s.node {
+                ASTNode::Literal(l) => Some(l.kind),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(kinds, vec![LiteralKind::Sql, LiteralKind::Path]);
+    }
+
+    // ── Global State ──
+
+    fn globals_in(source: &str) -> Globals {
+        let tree = Python.get_parser().parse(source, None).unwrap();
+        globals(&tree, source)
+    }
+
+    #[test]
+    fn global_containers_and_rebinds() {
+        let src = "import collections\n\
+                   LIMIT = 10\n\
+                   CACHE = {}\n\
+                   SEEN = collections.defaultdict(list)\n\
+                   NAMES = [n for n in x]\n\
+                   count = 0\n\
+                   def bump():\n    global count\n    count += 1\n";
+        let g = globals_in(src);
+        let found: Vec<(&str, GlobalKind)> = g
+            .defined
+            .iter()
+            .map(|g| (g.name.as_str(), g.kind))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("CACHE", GlobalKind::Container),
+                ("SEEN", GlobalKind::Container),
+                ("NAMES", GlobalKind::Container),
+                ("count", GlobalKind::Rebound),
+            ]
+        );
+        assert_eq!(g.defined[0].span.start_line, 3);
+    }
+
+    #[test]
+    fn global_uses_qualify_methods() {
+        let src = "class Store:\n    def get(self, k):\n        return CACHE[k]\n\
+                   def f():\n    def inner():\n        pass\n";
+        let g = globals_in(src);
+        let names: Vec<&str> = g.uses.iter().map(|(f, _)| f.as_str()).collect();
+        assert_eq!(names, ["Store.get", "f", "inner"]);
+        assert!(g.uses[0].1.contains("CACHE"));
+    }
+
+    // ── Stub Signatures ──
+
+    fn sigs(source: &str) -> Vec<(String, String, bool)> {
+        let tree = Python.get_parser().parse(source, None).unwrap();
+        signatures(&tree, source)
+            .into_iter()
+            .map(|s| (s.name, s.arity.to_string(), s.overload))
+            .collect()
+    }
+
+    fn sig(name: &str, arity: &str, overload: bool) -> (String, String, bool) {
+        (name.into(), arity.into(), overload)
+    }
+
+    #[test]
+    fn arity_counts_defaults_and_splats() {
+        let src = "def f(a, b: int, c=1, *, d: int = 2): ...\n\
+                   def g(a, /, *args: int, **kw): ...\n\
+                   def h(): ...\n";
+        assert_eq!(
+            sigs(src),
+            [
+                sig("f", "2-4", false),
+                sig("g", "1+", false),
+                sig("h", "0", false)
+            ]
+        );
+    }
+
+    #[test]
+    fn signatures_of_methods_overloads_and_conditionals() {
+        let src = "class C:\n    def m(self, x): ...\n    class D:\n        def n(self): ...\n\
+                   @overload\ndef f(a: int) -> int: ...\n\
+                   @typing.overload\ndef f(a: str, b: str) -> str: ...\n\
+                   if sys.version_info >= (3, 9):\n    def g(): ...\nelse:\n    def g(x): ...\n\
+                   def outer():\n    def inner(): ...\n";
+        assert_eq!(
+            sigs(src),
+            [
+                sig("C.m", "2", false),
+                sig("C.D.n", "1", false),
+                sig("f", "1", true),
+                sig("f", "2", true),
+                sig("g", "0", false),
+                sig("g", "1", false),
+                sig("outer", "0", false),
+            ]
+        );
+    }
+
+    #[test]
+    fn arity_union_widens() {
+        let one = Arity {
+            min: 1,
+            max: Some(1),
+        };
+        let two_three = Arity {
+            min: 2,
+            max: Some(3),
+        };
+        let any = Arity { min: 0, max: None };
+        assert_eq!(one.union(two_three).to_string(), "1-3");
+        assert_eq!(one.union(any).to_string(), "0+");
+    }
 }
@@ -1,6 +1,13 @@
-use super::{ASTNode, Call, Function, Metadata, ParseError, Syntax, Type, metadata_from_span};
-use std::collections::HashMap;
-use tree_sitter::{Node, Parser};
+use super::{
+    ASTNode, Attribute, COMPLEXITY, Call, Exception, ExceptionKind, Field, Function, Global,
+    GlobalKind, Globals, Import, Metadata, Module, Options, Param, Span, Syntax, Type, Unsafe,
+    UnsafeKind, UsesType, Variant, complexity, join, metadata_from_node, syntax_errors,
+};
+use serde::Serialize;
+use std::borrow::Cow;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt;
+use tree_sitter::{Node, Parser, Tree};
 
 pub struct Rust;
 
@@ -13,14 +20,22 @@ impl super::Lang for Rust {
         parser
     }
 
-    fn parse(&self, parser: &mut Parser, source: &str) -> Result<Vec<Syntax>, ParseError> {
-        let tree = parser
-            .parse(source, None)
-            .ok_or_else(|| ParseError("parse returned None".into()))?;
+    fn extract<'s>(
+        &self,
+        tree: &Tree,
+        source: &'s str,
+        opts: &Options,
+    ) -> (Vec<Syntax<'s>>, Vec<Span>) {
         let src = source.as_bytes();
         let root = tree.root_node();
         let imports = collect_imports(root, src);
-        Ok(walk(root, src, &imports))
+        let mut out = walk(root, src, &imports);
+        if let Some(which) = opts.strings {
+            let mut literals = Vec::new();
+            collect_strings(root, src, &mut literals);
+            super::attach_literals(&mut out, literals, src, which);
+        }
+        (out, syntax_errors(root))
     }
 }
 
@@ -30,11 +45,22 @@ fn collect_imports(root: Node, src: &[u8]) -> HashMap<String, String> {
     let mut imports = HashMap::new();
     let mut cursor = root.walk();
     for child in root.named_children(&mut cursor) {
-        if child.kind() == "use_declaration" {
-            let mut c = child.walk();
-            for n in child.named_children(&mut c) {
-                collect_use_tree(n, src, "", &mut imports);
+        match child.kind() {
+            "use_declaration" => {
+                let mut c = child.walk();
+                for n in child.named_children(&mut c) {
+                    collect_use_tree(n, src, "", &mut imports);
+                }
+            }
+            // `extern crate foo as bar;` — only the alias needs resolution
+            "extern_crate_declaration" => {
+                let name = field_text(child, "name", src);
+                let alias = field_text(child, "alias", src);
+                if !alias.is_empty() {
+                    imports.insert(alias.to_string(), name.to_string());
+                }
             }
+            _ => {}
         }
     }
     imports
@@ -42,16 +68,13 @@ fn collect_imports(root: Node, src: &[u8]) -> HashMap<String, String> {
 
 fn collect_use_tree(node: Node, src: &[u8], prefix: &str, imports: &mut HashMap<String, String>) {
     match node.kind() {
-        "self" => {
-            if !prefix.is_empty() {
-                let local = prefix.rsplit("::").next().unwrap_or(prefix).to_string();
-                imports.insert(local, prefix.to_string());
-            }
+        "self" if !prefix.is_empty() => {
+            let local = prefix.rsplit("::").next().unwrap_or(prefix).to_string();
+            imports.insert(local, prefix.to_string());
         }
         "identifier" | "type_identifier" => {
-            let name = node.utf8_text(src).unwrap_or("").to_string();
-            let full = qualify(prefix, &name);
-            imports.insert(name, full);
+            let name = node.utf8_text(src).unwrap_or("");
+            imports.insert(name.to_string(), qualify(prefix, name));
         }
         "scoped_identifier" | "scoped_type_identifier" => {
             let full = scoped_path(node, src);
@@ -100,6 +123,105 @@ fn collect_use_tree(node: Node, src: &[u8], prefix: &str, imports: &mut HashMap<
     }
 }
 
+/// The Imports of a `use` or `extern crate`. Items of a `use` tree are
+/// grouped by the module they come from; a renamed item, or a bare module
+/// (`use serde;`, `use a::{self}`), gets an Import of its own.
+fn import_nodes<'s>(item: Node, src: &'s [u8]) -> Vec<Syntax<'s>> {
+    let mut leaves = Vec::new();
+    if item.kind() == "extern_crate_declaration" {
+        let alias = Some(field_text(item, "alias", src)).filter(|a| !a.is_empty());
+        leaves.push((field_text(item, "name", src).to_string(), None, alias));
+    } else if let Some(tree) = item.child_by_field_name("argument") {
+        use_leaves(tree, src, "", &mut leaves);
+    }
+    let mut imports: Vec<Import<'s>> = Vec::new();
+    for (module, name, alias) in leaves {
+        let group = imports
+            .iter_mut()
+            .find(|i| i.module == module && i.alias.is_none() && !i.items.is_empty());
+        match (name, alias, group) {
+            (Some(name), None, Some(group)) => group.items.push(Cow::Owned(name)),
+            (name, alias, _) => imports.push(Import {
+                module: Cow::Owned(module),
+                items: name.map(Cow::Owned).into_iter().collect(),
+                alias: alias.map(Cow::Borrowed),
+            }),
+        }
+    }
+    imports
+        .into_iter()
+        .map(|i| Syntax {
+            node: ASTNode::Import(i),
+            metadata: meta(item, src),
+            contains: vec![],
+        })
+        .collect()
+}
+
+/// `(module, item, alias)` for every path a use tree imports.
+fn use_leaves<'s>(
+    node: Node,
+    src: &'s [u8],
+    prefix: &str,
+    out: &mut Vec<(String, Option<String>, Option<&'s str>)>,
+) {
+    let split = |path: Node| match path.kind() {
+        "scoped_identifier" | "scoped_type_identifier" => {
+            let module = path
+                .child_by_field_name("path")
+                .map(|n| scoped_path(n, src))
+                .unwrap_or_default();
+            (
+                qualify(prefix, &module),
+                Some(field_text(path, "name", src)),
+            )
+        }
+        _ if prefix.is_empty() => (path.utf8_text(src).unwrap_or("").to_string(), None),
+        _ => (prefix.to_string(), Some(path.utf8_text(src).unwrap_or(""))),
+    };
+    match node.kind() {
+        "self" => out.push((prefix.to_string(), None, None)),
+        "use_as_clause" => {
+            let alias = node
+                .child_by_field_name("alias")
+                .and_then(|n| n.utf8_text(src).ok());
+            if let Some(path) = node.child_by_field_name("path") {
+                let (module, name) = match path.kind() {
+                    "self" => (prefix.to_string(), None),
+                    _ => split(path),
+                };
+                out.push((module, name.map(str::to_string), alias));
+            }
+        }
+        "use_wildcard" => {
+            let module = node
+                .named_child(0)
+                .map(|n| qualify(prefix, &scoped_path(n, src)))
+                .unwrap_or_else(|| prefix.to_string());
+            out.push((module, Some("*".into()), None));
+        }
+        "scoped_use_list" => {
+            let path = node
+                .child_by_field_name("path")
+                .map(|n| scoped_path(n, src))
+                .unwrap_or_default();
+            if let Some(list) = node.child_by_field_name("list") {
+                use_leaves(list, src, &qualify(prefix, &path), out);
+            }
+        }
+        "use_list" => {
+            let mut c = node.walk();
+            for child in node.named_children(&mut c) {
+                use_leaves(child, src, prefix, out);
+            }
+        }
+        _ => {
+            let (module, name) = split(node);
+            out.push((module, name.map(str::to_string), None));
+        }
+    }
+}
+
 fn qualify(prefix: &str, name: &str) -> String {
     if prefix.is_empty() {
         name.to_string()
@@ -109,55 +231,141 @@ fn qualify(prefix: &str, name: &str) -> String {
 }
 
 /// Recursively build `a::b::c` from scoped_identifier chains.
-fn scoped_path(node: Node, src: &[u8]) -> String {
-    match node.kind() {
-        "identifier" | "type_identifier" | "self" | "crate" | "super" | "metavariable" => {
-            node.utf8_text(src).unwrap_or("").to_string()
-        }
-        "scoped_identifier" | "scoped_type_identifier" => {
-            let prefix = node
-                .child_by_field_name("path")
-                .map(|n| scoped_path(n, src))
-                .unwrap_or_default();
-            let name = node
-                .child_by_field_name("name")
-                .and_then(|n| n.utf8_text(src).ok())
-                .unwrap_or("");
-            if prefix.is_empty() {
-                name.to_string()
-            } else {
-                format!("{prefix}::{name}")
+fn scoped_path<'s>(node: Node, src: &'s [u8]) -> Cow<'s, str> {
+    let text = node.utf8_text(src).unwrap_or("");
+    if !matches!(node.kind(), "scoped_identifier" | "scoped_type_identifier") {
+        return Cow::Borrowed(text);
+    }
+    let prefix = node
+        .child_by_field_name("path")
+        .map(|n| scoped_path(n, src))
+        .unwrap_or_default();
+    let name = node
+        .child_by_field_name("name")
+        .and_then(|n| n.utf8_text(src).ok())
+        .unwrap_or("");
+    if prefix.is_empty() {
+        // `::name` — the leading `::` is dropped
+        Cow::Borrowed(name)
+    } else {
+        join(text, &prefix, "::", name)
+    }
+}
+
+// ── String Literals ─────────────────────────────────────────────────
+
+/// Every string literal outside attributes, including those in macro arguments.
+/// Escapes are kept as written.
+fn collect_strings<'a, 's>(node: Node<'a>, src: &'s [u8], out: &mut Vec<(Node<'a>, Cow<'s, str>)>) {
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        match child.kind() {
+            "string_literal" | "raw_string_literal" => {
+                out.push((child, string_contents(child, src).into()));
             }
+            "attribute_item" | "inner_attribute_item" => {}
+            _ => collect_strings(child, src, out),
         }
-        _ => node.utf8_text(src).unwrap_or("").to_string(),
     }
 }
 
+/// Source text between the quotes: from the first to the last content child.
+fn string_contents<'s>(lit: Node, src: &'s [u8]) -> &'s str {
+    let count = lit.named_child_count() as u32;
+    let (Some(first), Some(last)) = (lit.named_child(0), lit.named_child(count.saturating_sub(1)))
+    else {
+        return "";
+    };
+    src.get(first.start_byte()..last.end_byte())
+        .and_then(|b| std::str::from_utf8(b).ok())
+        .unwrap_or("")
+}
+
 // ── AST Walk ────────────────────────────────────────────────────────
 
-fn walk(node: Node, src: &[u8], imports: &HashMap<String, String>) -> Vec<Syntax> {
+fn walk<'s>(node: Node, src: &'s [u8], imports: &HashMap<String, String>) -> Vec<Syntax<'s>> {
     let mut out = Vec::new();
     let mut cursor = node.walk();
 
     for child in node.named_children(&mut cursor) {
+        let before = out.len();
         match child.kind() {
             "function_item" => {
                 let name = field_text(child, "name", src);
-                let body = child
+                let mut contains = unsafe_marker(child, UnsafeKind::Fn, src);
+                contains.extend(signature_types(child, src, imports));
+                let error = child
+                    .child_by_field_name("return_type")
+                    .and_then(|r| error_type(r, src, imports).map(|e| (r, e)));
+                if let Some((ret, name)) = &error {
+                    contains.push(error_node(*ret, name.clone(), ExceptionKind::Returns, src));
+                }
+                if let Some(body) = child.child_by_field_name("body") {
+                    contains.extend(walk(body, src, imports));
+                    if let Some((_, name)) = &error {
+                        propagations(body, name.clone(), src, &mut contains);
+                    }
+                }
+                out.push(Syntax {
+                    node: ASTNode::Function(Function {
+                        name: name.into(),
+                        public: is_pub(child),
+                        params: params(child, src, imports),
+                        return_type: child
+                            .child_by_field_name("return_type")
+                            .map(|t| resolve_type(t, src, imports)),
+                        decorators: vec![],
+                    }),
+                    metadata: measured(child, src),
+                    contains,
+                });
+            }
+
+            "struct_item" => {
+                let name = field_text(child, "name", src);
+                let fields = child
                     .child_by_field_name("body")
-                    .map(|b| walk(b, src, imports))
+                    .map(|b| fields(b, src, imports))
                     .unwrap_or_default();
                 out.push(Syntax {
-                    node: ASTNode::Function(Function { name }),
+                    node: ASTNode::Type(Type {
+                        name: name.into(),
+                        public: is_pub(child),
+                        is_abstract: false,
+                        decorators: vec![],
+                    }),
                     metadata: meta(child, src),
-                    contains: body,
+                    contains: fields,
                 });
             }
 
-            "struct_item" | "enum_item" | "type_item" => {
+            "enum_item" => {
                 let name = field_text(child, "name", src);
+                let variants = child
+                    .child_by_field_name("body")
+                    .map(|b| variants(b, src, imports))
+                    .unwrap_or_default();
                 out.push(Syntax {
-                    node: ASTNode::Type(Type { name }),
+                    node: ASTNode::Type(Type {
+                        name: name.into(),
+                        public: is_pub(child),
+                        is_abstract: false,
+                        decorators: vec![],
+                    }),
+                    metadata: meta(child, src),
+                    contains: variants,
+                });
+            }
+
+            "type_item" => {
+                let name = field_text(child, "name", src);
+                out.push(Syntax {
+                    node: ASTNode::Type(Type {
+                        name: name.into(),
+                        public: is_pub(child),
+                        is_abstract: false,
+                        decorators: vec![],
+                    }),
                     metadata: meta(child, src),
                     contains: vec![],
                 });
@@ -165,38 +373,66 @@ fn walk(node: Node, src: &[u8], imports: &HashMap<String, String>) -> Vec<Syntax
 
             "trait_item" => {
                 let name = field_text(child, "name", src);
-                let body = child
-                    .child_by_field_name("body")
-                    .map(|b| walk(b, src, imports))
-                    .unwrap_or_default();
+                let mut contains = unsafe_marker(child, UnsafeKind::Trait, src);
+                contains.extend(
+                    child
+                        .child_by_field_name("body")
+                        .map(|b| public_items(walk(b, src, imports)))
+                        .unwrap_or_default(),
+                );
                 out.push(Syntax {
-                    node: ASTNode::Type(Type { name }),
+                    node: ASTNode::Type(Type {
+                        name: name.into(),
+                        public: is_pub(child),
+                        is_abstract: true,
+                        decorators: vec![],
+                    }),
                     metadata: meta(child, src),
-                    contains: body,
+                    contains,
                 });
             }
 
             "impl_item" => {
                 let type_name = child
                     .child_by_field_name("type")
-                    .map(|n| n.utf8_text(src).unwrap_or("").to_string())
-                    .unwrap_or_default();
+                    .and_then(|n| n.utf8_text(src).ok())
+                    .unwrap_or("");
                 let trait_name = child
                     .child_by_field_name("trait")
-                    .and_then(|n| n.utf8_text(src).ok())
-                    .map(|s| s.to_string());
+                    .and_then(|n| n.utf8_text(src).ok());
                 let label = match trait_name {
-                    Some(t) => format!("{t} for {type_name}"),
-                    None => type_name,
+                    Some(t) => Cow::Owned(format!("{t} for {type_name}")),
+                    None => Cow::Borrowed(type_name),
                 };
-                let body = child
+                // A trait impl's methods are as public as the trait and type.
+                let items = child
                     .child_by_field_name("body")
                     .map(|b| walk(b, src, imports))
                     .unwrap_or_default();
+                let mut contains = unsafe_marker(child, UnsafeKind::Impl, src);
+                contains.extend(match trait_name {
+                    Some(_) => public_items(items),
+                    None => items,
+                });
+                out.push(Syntax {
+                    node: ASTNode::Type(Type {
+                        name: label,
+                        public: false,
+                        is_abstract: false,
+                        decorators: vec![],
+                    }),
+                    metadata: meta(child, src),
+                    contains,
+                });
+            }
+
+            "unsafe_block" => {
                 out.push(Syntax {
-                    node: ASTNode::Type(Type { name: label }),
+                    node: ASTNode::Unsafe(Unsafe {
+                        kind: UnsafeKind::Block,
+                    }),
                     metadata: meta(child, src),
-                    contains: body,
+                    contains: walk(child, src, imports),
                 });
             }
 
@@ -205,11 +441,19 @@ fn walk(node: Node, src: &[u8], imports: &HashMap<String, String>) -> Vec<Syntax
                     .child_by_field_name("function")
                     .map(|f| call_name(f, src))
                     .unwrap_or_default();
-                let name = resolve_call(&raw, imports);
+                let name = resolve_call(raw, imports);
+                let contains = match SPAWNS.contains(&&*name) {
+                    true => super::spawned(spawned_work(child, src, imports), name.clone()),
+                    false => vec![],
+                };
                 out.push(Syntax {
-                    node: ASTNode::Call(Call { name }),
+                    node: ASTNode::Call(Call {
+                        name,
+                        tags: vec![],
+                        spawned_by: None,
+                    }),
                     metadata: meta(child, src),
-                    contains: vec![],
+                    contains,
                 });
             }
 
@@ -218,10 +462,12 @@ fn walk(node: Node, src: &[u8], imports: &HashMap<String, String>) -> Vec<Syntax
                     .child_by_field_name("macro")
                     .map(|m| scoped_path(m, src))
                     .unwrap_or_default();
-                let name = resolve_call(&raw, imports);
+                let name = resolve_call(raw, imports);
                 out.push(Syntax {
                     node: ASTNode::Call(Call {
-                        name: format!("{name}!"),
+                        name: Cow::Owned(format!("{name}!")),
+                        tags: vec![],
+                        spawned_by: None,
                     }),
                     metadata: meta(child, src),
                     contains: vec![],
@@ -236,29 +482,436 @@ fn walk(node: Node, src: &[u8], imports: &HashMap<String, String>) -> Vec<Syntax
                 });
             }
 
-            "use_declaration" => {}
-            "attribute_item" | "inner_attribute_item" | "mod_item" => {}
+            // `mod foo;` → Module; `mod foo { .. }` → Module holding its items
+            "mod_item" => {
+                let name = field_text(child, "name", src);
+                let body = child.child_by_field_name("body");
+                out.push(Syntax {
+                    node: ASTNode::Module(Module {
+                        name: name.into(),
+                        path: path_attribute(child, src).map(Cow::Borrowed),
+                        file: None,
+                        inline: body.is_some(),
+                    }),
+                    metadata: meta(child, src),
+                    contains: body.map(|b| walk(b, src, imports)).unwrap_or_default(),
+                });
+            }
+
+            "use_declaration" | "extern_crate_declaration" => {
+                out.extend(import_nodes(child, src));
+            }
+            "attribute_item" | "inner_attribute_item" => {}
 
             _ => out.extend(walk(child, src, imports)),
         }
+        // An item's attributes go first among the children of its node.
+        if ITEMS.contains(&child.kind()) && out.len() == before + 1 {
+            out[before]
+                .contains
+                .splice(0..0, attributes(child, src, imports));
+            let written = || {
+                outer_attributes(child)
+                    .into_iter()
+                    .filter_map(|item| item.named_child(0)?.utf8_text(src).ok())
+                    .map(Cow::Borrowed)
+                    .collect()
+            };
+            match &mut out[before].node {
+                ASTNode::Function(f) => f.decorators = written(),
+                ASTNode::Type(t) => t.decorators = written(),
+                _ => {}
+            }
+        }
     }
 
     out
 }
 
+// ── Spawned Work ────────────────────────────────────────────────────
+
+/// Resolved call names that run their argument as a task or thread.
+const SPAWNS: &[&str] = &[
+    "tokio::spawn",
+    "tokio::task::spawn",
+    "tokio::task::spawn_blocking",
+    "tokio::task::spawn_local",
+    "std::thread::spawn",
+];
+
+/// What a spawning call runs: a function passed by name, or the calls in
+/// the closure, async block or future it is given.
+fn spawned_work<'s>(
+    call: Node,
+    src: &'s [u8],
+    imports: &HashMap<String, String>,
+) -> Vec<Syntax<'s>> {
+    let Some(args) = call.child_by_field_name("arguments") else {
+        return vec![];
+    };
+    let mut cursor = args.walk();
+    let mut out: Vec<Syntax> = args
+        .named_children(&mut cursor)
+        .filter(|a| matches!(a.kind(), "identifier" | "scoped_identifier"))
+        .map(|a| Syntax {
+            node: ASTNode::Call(Call {
+                name: resolve_call(call_name(a, src), imports),
+                tags: vec![],
+                spawned_by: None,
+            }),
+            metadata: meta(a, src),
+            contains: vec![],
+        })
+        .collect();
+    out.extend(walk(args, src, imports));
+    out
+}
+
+// ── Fields & Variants ───────────────────────────────────────────────
+
+/// Named (`{ x: T }`) or positional (`(T, U)`) fields of a struct or variant.
+fn fields<'s>(body: Node, src: &'s [u8], imports: &HashMap<String, String>) -> Vec<Syntax<'s>> {
+    let mut out = Vec::new();
+    let mut cursor = body.walk();
+    match body.kind() {
+        "field_declaration_list" => {
+            for decl in body.named_children(&mut cursor) {
+                if decl.kind() != "field_declaration" {
+                    continue;
+                }
+                let name = field_text(decl, "name", src);
+                let annotation = decl
+                    .child_by_field_name("type")
+                    .map(|t| resolve_type(t, src, imports));
+                out.push(Syntax {
+                    node: ASTNode::Field(Field {
+                        name: name.into(),
+                        annotation,
+                    }),
+                    metadata: meta(decl, src),
+                    contains: attributes(decl, src, imports),
+                });
+            }
+        }
+        "ordered_field_declaration_list" => {
+            for (i, ty) in body.children_by_field_name("type", &mut cursor).enumerate() {
+                out.push(Syntax {
+                    node: ASTNode::Field(Field {
+                        name: Cow::Owned(i.to_string()),
+                        annotation: Some(resolve_type(ty, src, imports)),
+                    }),
+                    metadata: meta(ty, src),
+                    contains: vec![],
+                });
+            }
+        }
+        _ => {}
+    }
+    out
+}
+
+/// Whether an item is declared plain `pub` (not `pub(crate)` and the like).
+fn is_pub(item: Node) -> bool {
+    let mut cursor = item.walk();
+    item.children(&mut cursor)
+        .find(|c| c.kind() == "visibility_modifier")
+        .is_some_and(|v| v.named_child_count() == 0 && v.end_byte() - v.start_byte() == 3)
+}
+
+/// Mark the functions directly in a trait or trait impl body public.
+fn public_items(mut items: Vec<Syntax>) -> Vec<Syntax> {
+    for s in &mut items {
+        if let ASTNode::Function(f) = &mut s.node {
+            f.public = true;
+        }
+    }
+    items
+}
+
+fn variants<'s>(body: Node, src: &'s [u8], imports: &HashMap<String, String>) -> Vec<Syntax<'s>> {
+    let mut out = Vec::new();
+    let mut cursor = body.walk();
+    for v in body.named_children(&mut cursor) {
+        if v.kind() != "enum_variant" {
+            continue;
+        }
+        let name = field_text(v, "name", src);
+        let mut payload = attributes(v, src, imports);
+        payload.extend(
+            v.child_by_field_name("body")
+                .map(|b| fields(b, src, imports))
+                .unwrap_or_default(),
+        );
+        out.push(Syntax {
+            node: ASTNode::Variant(Variant { name: name.into() }),
+            metadata: meta(v, src),
+            contains: payload,
+        });
+    }
+    out
+}
+
+/// One [`UsesType`] per distinct type named in the parameters or return type.
+///
+/// The function's own generic parameters and `Self` are skipped.
+fn signature_types<'s>(
+    func: Node,
+    src: &'s [u8],
+    imports: &HashMap<String, String>,
+) -> Vec<Syntax<'s>> {
+    let mut generics = HashSet::new();
+    if let Some(tp) = func.child_by_field_name("type_parameters") {
+        let mut cursor = tp.walk();
+        for p in tp.named_children(&mut cursor) {
+            if let Some(n) = p.child_by_field_name("name") {
+                generics.insert(n.utf8_text(src).unwrap_or(""));
+            }
+        }
+    }
+
+    let mut types = Vec::new();
+    if let Some(params) = func.child_by_field_name("parameters") {
+        let mut cursor = params.walk();
+        for p in params.named_children(&mut cursor) {
+            types.extend(p.child_by_field_name("type"));
+        }
+    }
+    types.extend(func.child_by_field_name("return_type"));
+
+    let mut names = Vec::new();
+    for ty in types {
+        type_names(ty, src, imports, &mut names);
+    }
+
+    let mut seen = HashSet::new();
+    let mut out = Vec::new();
+    for (node, name) in names {
+        if name == "Self" || generics.contains(&*name) || !seen.insert(name.clone()) {
+            continue;
+        }
+        out.push(Syntax {
+            node: ASTNode::UsesType(UsesType { name }),
+            metadata: meta(node, src),
+            contains: vec![],
+        });
+    }
+    out
+}
+
+/// A function's parameters: the receiver as written, then each pattern
+/// with its type resolved like field annotations.
+fn params<'s>(func: Node, src: &'s [u8], imports: &HashMap<String, String>) -> Vec<Param<'s>> {
+    let Some(params) = func.child_by_field_name("parameters") else {
+        return vec![];
+    };
+    let mut cursor = params.walk();
+    params
+        .named_children(&mut cursor)
+        .filter_map(|p| {
+            let (name, ty) = match p.kind() {
+                "parameter" => (
+                    p.child_by_field_name("pattern")?,
+                    p.child_by_field_name("type"),
+                ),
+                "self_parameter" => return Some(self_param(p, src)),
+                "variadic_parameter" => (p, None),
+                _ => return None,
+            };
+            Some(Param {
+                name: Cow::Borrowed(name.utf8_text(src).unwrap_or("")),
+                annotation: ty.map(|t| resolve_type(t, src, imports)),
+                default: None,
+            })
+        })
+        .collect()
+}
+
+/// `self`, typed by its receiver: `&'a mut self` is `&'a mut Self`, and
+/// `self` and `mut self` are `Self`.
+fn self_param<'s>(p: Node, src: &'s [u8]) -> Param<'s> {
+    let text = p.utf8_text(src).unwrap_or("");
+    let receiver = text.strip_suffix("self").unwrap_or("");
+    Param {
+        name: Cow::Borrowed("self"),
+        annotation: Some(match receiver.starts_with('&') {
+            true => Cow::Owned(format!("{receiver}Self")),
+            false => Cow::Borrowed("Self"),
+        }),
+        default: None,
+    }
+}
+
+/// Render a type with every imported name replaced by its full path.
+///
+/// `Option<Foo>` with `use crate::a::Foo` → `Option<crate::a::Foo>`.
+fn resolve_type<'s>(node: Node, src: &'s [u8], imports: &HashMap<String, String>) -> Cow<'s, str> {
+    let mut names = Vec::new();
+    type_names(node, src, imports, &mut names);
+    if names
+        .iter()
+        .all(|(_, resolved)| matches!(resolved, Cow::Borrowed(_)))
+    {
+        return Cow::Borrowed(node.utf8_text(src).unwrap_or(""));
+    }
+
+    let mut out = String::new();
+    let mut pos = node.start_byte();
+    for (name, resolved) in names {
+        out.push_str(std::str::from_utf8(&src[pos..name.start_byte()]).unwrap_or(""));
+        out.push_str(&resolved);
+        pos = name.end_byte();
+    }
+    out.push_str(std::str::from_utf8(&src[pos..node.end_byte()]).unwrap_or(""));
+    Cow::Owned(out)
+}
+
+/// Collect `(node, resolved)` for each type name inside a type expression.
+fn type_names<'a, 's>(
+    node: Node<'a>,
+    src: &'s [u8],
+    imports: &HashMap<String, String>,
+    out: &mut Vec<(Node<'a>, Cow<'s, str>)>,
+) {
+    match node.kind() {
+        "type_identifier" | "scoped_type_identifier" => {
+            let resolved = resolve_call(scoped_path(node, src), imports);
+            out.push((node, resolved));
+        }
+        _ => {
+            let mut cursor = node.walk();
+            for child in node.named_children(&mut cursor) {
+                type_names(child, src, imports, out);
+            }
+        }
+    }
+}
+
 // ── Helpers ─────────────────────────────────────────────────────────
 
-fn field_text(node: Node, field: &str, src: &[u8]) -> String {
+fn field_text<'s>(node: Node, field: &str, src: &'s [u8]) -> &'s str {
     node.child_by_field_name(field)
         .and_then(|n| n.utf8_text(src).ok())
         .unwrap_or("")
-        .to_string()
+}
+
+/// An [`Unsafe`] node spanning `item` if it is declared `unsafe`, else nothing.
+fn unsafe_marker<'s>(item: Node, kind: UnsafeKind, src: &[u8]) -> Vec<Syntax<'s>> {
+    let mut cursor = item.walk();
+    let is_unsafe = item.children(&mut cursor).any(|c| match c.kind() {
+        "unsafe" => true,
+        "function_modifiers" => {
+            let mut m = c.walk();
+            c.children(&mut m).any(|k| k.kind() == "unsafe")
+        }
+        _ => false,
+    });
+    if !is_unsafe {
+        return vec![];
+    }
+    vec![Syntax {
+        node: ASTNode::Unsafe(Unsafe { kind }),
+        metadata: meta(item, src),
+        contains: vec![],
+    }]
+}
+
+/// The `#[path = "..."]` value among the attributes directly above `node`.
+fn path_attribute<'s>(node: Node, src: &'s [u8]) -> Option<&'s str> {
+    outer_attributes(node).into_iter().rev().find_map(|item| {
+        let attr = item.named_child(0)?;
+        let is_path = attr
+            .named_child(0)
+            .and_then(|n| n.utf8_text(src).ok())
+            .is_some_and(|t| t == "path");
+        if !is_path {
+            return None;
+        }
+        attr.child_by_field_name("value")
+            .and_then(|v| v.named_child(0))
+            .and_then(|n| n.utf8_text(src).ok())
+    })
+}
+
+// ── Attributes ──────────────────────────────────────────────────────
+
+/// Items whose node carries the attributes written above them.
+const ITEMS: &[&str] = &[
+    "function_item",
+    "struct_item",
+    "enum_item",
+    "type_item",
+    "trait_item",
+    "impl_item",
+    "mod_item",
+];
+
+/// The `attribute_item`s directly above `node`, in source order; comments
+/// between them are skipped.
+fn outer_attributes(node: Node) -> Vec<Node> {
+    let mut out = Vec::new();
+    let mut prev = node.prev_named_sibling();
+    while let Some(n) = prev {
+        match n.kind() {
+            "attribute_item" => out.push(n),
+            "line_comment" | "block_comment" => {}
+            _ => break,
+        }
+        prev = n.prev_named_sibling();
+    }
+    out.reverse();
+    out
+}
+
+/// One [`Attribute`] per outer attribute of `node`.
+fn attributes<'s>(node: Node, src: &'s [u8], imports: &HashMap<String, String>) -> Vec<Syntax<'s>> {
+    outer_attributes(node)
+        .into_iter()
+        .filter_map(|item| {
+            let attr = item.named_child(0)?;
+            let name = attr.named_child(0)?.utf8_text(src).ok()?;
+            let mut args = match (
+                attr.child_by_field_name("arguments"),
+                attr.child_by_field_name("value"),
+            ) {
+                (Some(tokens), _) => token_args(tokens, src),
+                (None, Some(value)) => vec![Cow::Borrowed(value.utf8_text(src).unwrap_or(""))],
+                (None, None) => vec![],
+            };
+            if name == "derive" {
+                args = args.into_iter().map(|a| resolve_call(a, imports)).collect();
+            }
+            Some(Syntax {
+                node: ASTNode::Attribute(Attribute {
+                    name: Cow::Borrowed(name),
+                    args,
+                }),
+                metadata: meta(item, src),
+                contains: vec![],
+            })
+        })
+        .collect()
+}
+
+/// The text of each top-level comma-separated argument in `(a, b = "c", d(e, f))`.
+fn token_args<'s>(tokens: Node, src: &'s [u8]) -> Vec<Cow<'s, str>> {
+    let mut cursor = tokens.walk();
+    let children: Vec<Node> = tokens.children(&mut cursor).collect();
+    let inner = children
+        .get(1..children.len().saturating_sub(1))
+        .unwrap_or(&[]);
+    inner
+        .split(|n| n.kind() == ",")
+        .filter_map(|arg| {
+            let (first, last) = (arg.first()?, arg.last()?);
+            let text = std::str::from_utf8(&src[first.start_byte()..last.end_byte()]).ok()?;
+            Some(Cow::Borrowed(text))
+        })
+        .collect()
 }
 
 /// Extract a call's name from its function expression.
-fn call_name(node: Node, src: &[u8]) -> String {
+fn call_name<'s>(node: Node, src: &'s [u8]) -> Cow<'s, str> {
     match node.kind() {
-        "identifier" | "type_identifier" => node.utf8_text(src).unwrap_or("").to_string(),
         "scoped_identifier" | "scoped_type_identifier" => scoped_path(node, src),
         "field_expression" => {
             let obj = node
@@ -269,37 +922,523 @@ fn call_name(node: Node, src: &[u8]) -> String {
                 .child_by_field_name("field")
                 .and_then(|n| n.utf8_text(src).ok())
                 .unwrap_or("");
-            format!("{obj}.{field}")
+            join(node.utf8_text(src).unwrap_or(""), &obj, ".", field)
         }
-        _ => node.utf8_text(src).unwrap_or("").to_string(),
+        _ => Cow::Borrowed(node.utf8_text(src).unwrap_or("")),
     }
 }
 
 /// Replace the first segment of a call with its import mapping.
-fn resolve_call(name: &str, imports: &HashMap<String, String>) -> String {
+fn resolve_call<'s>(name: Cow<'s, str>, imports: &HashMap<String, String>) -> Cow<'s, str> {
     let (head, sep, tail) = if let Some((h, t)) = name.split_once("::") {
         (h, "::", Some(t))
     } else if let Some((h, t)) = name.split_once('.') {
         (h, ".", Some(t))
     } else {
-        (name, "", None)
+        (&*name, "", None)
     };
 
     if matches!(head, "self" | "super" | "crate") {
-        return name.to_string();
+        return name;
     }
 
     match imports.get(head) {
         Some(resolved) => match tail {
-            Some(rest) => format!("{resolved}{sep}{rest}"),
-            None => resolved.clone(),
+            Some(rest) => Cow::Owned(format!("{resolved}{sep}{rest}")),
+            None => Cow::Owned(resolved.clone()),
         },
-        None => name.to_string(),
+        None => name,
+    }
+}
+
+fn meta(node: Node, src: &[u8]) -> Metadata {
+    metadata_from_node(src, node)
+}
+
+/// [`meta`] of a `function_item` with its cyclomatic complexity.
+fn measured(item: Node, src: &[u8]) -> Metadata {
+    let mut metadata = meta(item, src);
+    if cfg!(feature = "complexity")
+        && let Some(body) = item.child_by_field_name("body")
+    {
+        let nested = &["function_item", "impl_item", "trait_item", "mod_item"];
+        metadata.set_metric(COMPLEXITY, complexity(body, &decisions, nested));
+    }
+    metadata
+}
+
+/// Decision points: `if` (and `if let`, `else if`), `while`, `for`, `?`,
+/// `&&`/`||`, and each arm of a `match` past the first.
+fn decisions(node: Node) -> usize {
+    match node.kind() {
+        "if_expression" | "while_expression" | "for_expression" | "try_expression" => 1,
+        "binary_expression" => node
+            .child_by_field_name("operator")
+            .is_some_and(|op| matches!(op.kind(), "&&" | "||"))
+            .into(),
+        "match_expression" => {
+            let mut cursor = node.walk();
+            let arms = node.child_by_field_name("body").map_or(0, |b| {
+                b.named_children(&mut cursor)
+                    .filter(|c| c.kind() == "match_arm")
+                    .count()
+            });
+            arms.saturating_sub(1)
+        }
+        _ => 0,
+    }
+}
+
+// ── Error Flow ──────────────────────────────────────────────────────
+
+/// The error type of a `Result<T, E>` return type. A `Result` without an
+/// error argument is taken for the alias its module defines, so
+/// `io::Result<T>` and `fmt::Result` give `std::io::Error` and
+/// `std::fmt::Error`; an unqualified, unimported `Result<T>` is unknown.
+fn error_type<'s>(
+    ret: Node,
+    src: &'s [u8],
+    imports: &HashMap<String, String>,
+) -> Option<Cow<'s, str>> {
+    let (base, args) = match ret.kind() {
+        "generic_type" => (
+            ret.child_by_field_name("type")?,
+            ret.child_by_field_name("type_arguments"),
+        ),
+        "type_identifier" | "scoped_type_identifier" => (ret, None),
+        _ => return None,
+    };
+    let path = resolve_call(scoped_path(base, src), imports);
+    let module = path.strip_suffix("Result")?;
+    if !(module.is_empty() || module.ends_with("::")) {
+        return None;
+    }
+    let mut types = Vec::new();
+    if let Some(args) = args {
+        let mut cursor = args.walk();
+        types.extend(
+            args.named_children(&mut cursor)
+                .filter(|a| !matches!(a.kind(), "lifetime" | "comment")),
+        );
+    }
+    match types.as_slice() {
+        [_, error] => Some(resolve_type(*error, src, imports)),
+        [] | [_] if !module.is_empty() => Some(Cow::Owned(format!("{module}Error"))),
+        _ => None,
+    }
+}
+
+fn error_node<'s>(at: Node, name: Cow<'s, str>, kind: ExceptionKind, src: &[u8]) -> Syntax<'s> {
+    Syntax {
+        node: ASTNode::Exception(Exception { name, kind }),
+        metadata: meta(at, src),
+        contains: vec![],
+    }
+}
+
+/// One node per `?` in a function body returning the error `name`.
+/// Closures, async blocks and nested functions return on their own and
+/// are skipped.
+fn propagations<'s>(node: Node, name: Cow<'s, str>, src: &[u8], out: &mut Vec<Syntax<'s>>) {
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        match child.kind() {
+            "closure_expression" | "async_block" | "function_item" => continue,
+            "try_expression" => {
+                out.push(error_node(
+                    child,
+                    name.clone(),
+                    ExceptionKind::Propagates,
+                    src,
+                ));
+            }
+            _ => {}
+        }
+        propagations(child, name.clone(), src, out);
+    }
+}
+
+// ── Global State ────────────────────────────────────────────────────
+
+/// Type names, by last segment, that make an immutable static mutable
+/// through a shared reference; `Atomic*` types also count.
+const INTERIOR_MUT: &[&str] = &[
+    "OnceCell", "OnceLock", "Lazy", "LazyLock", "LazyCell", "Mutex", "RwLock", "RefCell", "Cell",
+];
+
+/// `static mut` items, statics of an interior-mutable type and
+/// `lazy_static!` statics, with the names every function mentions.
+/// Functions in `impl` and `trait` blocks are named `Type::f`.
+pub fn globals(tree: &Tree, source: &str) -> Globals {
+    let src = source.as_bytes();
+    let mut out = Globals::default();
+    collect_globals(tree.root_node(), src, "", None, &mut out);
+    out
+}
+
+fn collect_globals(
+    node: Node,
+    src: &[u8],
+    prefix: &str,
+    function: Option<usize>,
+    out: &mut Globals,
+) {
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        match child.kind() {
+            "function_item" => {
+                let name = format!("{prefix}{}", field_text(child, "name", src));
+                out.uses.push((name, BTreeSet::new()));
+                let index = out.uses.len() - 1;
+                collect_globals(child, src, prefix, Some(index), out);
+            }
+            "impl_item" | "trait_item" => {
+                let field = if child.kind() == "trait_item" {
+                    "name"
+                } else {
+                    "type"
+                };
+                let prefix = format!("{prefix}{}::", field_text(child, field, src));
+                collect_globals(child, src, &prefix, function, out);
+            }
+            "static_item" => {
+                let mutable = {
+                    let mut c = child.walk();
+                    child
+                        .children(&mut c)
+                        .any(|n| n.kind() == "mutable_specifier")
+                };
+                let interior = child.child_by_field_name("type").is_some_and(|t| {
+                    let mut names = Vec::new();
+                    last_segments(t, src, &mut names);
+                    names
+                        .iter()
+                        .any(|n| INTERIOR_MUT.contains(n) || n.starts_with("Atomic"))
+                });
+                let kind = match (mutable, interior) {
+                    (true, _) => Some(GlobalKind::StaticMut),
+                    (false, true) => Some(GlobalKind::InteriorMut),
+                    _ => None,
+                };
+                if let Some(kind) = kind.filter(|_| function.is_none()) {
+                    out.defined.push(Global {
+                        name: field_text(child, "name", src).to_string(),
+                        kind,
+                        span: meta(child, src).span,
+                    });
+                }
+                collect_globals(child, src, prefix, function, out);
+            }
+            "macro_invocation" if field_text(child, "macro", src) == "lazy_static" => {
+                let mut c = child.walk();
+                for tokens in child.named_children(&mut c) {
+                    if tokens.kind() == "token_tree" {
+                        lazy_statics(tokens, src, &mut out.defined);
+                    }
+                }
+            }
+            "identifier" => {
+                if let Some(i) = function {
+                    let name = child.utf8_text(src).unwrap_or("");
+                    out.uses[i].1.insert(name.to_string());
+                }
+            }
+            _ => collect_globals(child, src, prefix, function, out),
+        }
+    }
+}
+
+/// The names declared `static [ref] NAME` in a `lazy_static!` body.
+fn lazy_statics(tokens: Node, src: &[u8], out: &mut Vec<Global>) {
+    let mut cursor = tokens.walk();
+    let children: Vec<Node> = tokens.children(&mut cursor).collect();
+    for (i, token) in children.iter().enumerate() {
+        if token.utf8_text(src) != Ok("static") {
+            continue;
+        }
+        let name = children[i + 1..]
+            .iter()
+            .find(|n| !matches!(n.utf8_text(src), Ok("ref" | "mut")))
+            .filter(|n| n.kind() == "identifier");
+        if let Some(name) = name {
+            out.push(Global {
+                name: name.utf8_text(src).unwrap_or("").to_string(),
+                kind: GlobalKind::LazyStatic,
+                span: meta(*token, src).span,
+            });
+        }
+    }
+}
+
+/// The last segment of every type name in a type expression.
+fn last_segments<'s>(node: Node, src: &'s [u8], out: &mut Vec<&'s str>) {
+    match node.kind() {
+        "type_identifier" => out.push(node.utf8_text(src).unwrap_or("")),
+        "scoped_type_identifier" => out.push(field_text(node, "name", src)),
+        _ => {}
+    }
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        last_segments(child, src, out);
+    }
+}
+
+// ── Conditional Compilation ─────────────────────────────────────────
+
+/// A `#[cfg(...)]` predicate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Cfg {
+    /// `feature = "name"`
+    Feature(String),
+    All(Vec<Cfg>),
+    Any(Vec<Cfg>),
+    Not(Box<Cfg>),
+    /// Any other predicate (`unix`, `test`, `target_os = "linux"`), as written.
+    Other(String),
+}
+
+impl Cfg {
+    /// Whether the predicate holds with `enabled` features; `None` when it
+    /// depends on more than features.
+    pub fn eval(&self, enabled: &BTreeSet<String>) -> Option<bool> {
+        match self {
+            Cfg::Feature(f) => Some(enabled.contains(f)),
+            Cfg::All(all) => {
+                let values: Vec<Option<bool>> = all.iter().map(|c| c.eval(enabled)).collect();
+                if values.contains(&Some(false)) {
+                    Some(false)
+                } else if values.contains(&None) {
+                    None
+                } else {
+                    Some(true)
+                }
+            }
+            Cfg::Any(any) => {
+                let values: Vec<Option<bool>> = any.iter().map(|c| c.eval(enabled)).collect();
+                if values.contains(&Some(true)) {
+                    Some(true)
+                } else if values.contains(&None) {
+                    None
+                } else {
+                    Some(false)
+                }
+            }
+            Cfg::Not(c) => c.eval(enabled).map(|v| !v),
+            Cfg::Other(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for Cfg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let list = |f: &mut fmt::Formatter<'_>, name, items: &[Cfg]| {
+            let items: Vec<String> = items.iter().map(Cfg::to_string).collect();
+            write!(f, "{name}({})", items.join(", "))
+        };
+        match self {
+            Cfg::Feature(name) => write!(f, "feature = \"{name}\""),
+            Cfg::All(all) => list(f, "all", all),
+            Cfg::Any(any) => list(f, "any", any),
+            Cfg::Not(c) => write!(f, "not({c})"),
+            Cfg::Other(text) => f.write_str(text),
+        }
+    }
+}
+
+/// A definition or call compiled only under `#[cfg]` conditions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Gated {
+    pub kind: GatedKind,
+    /// `f` or `Type::f` for definitions, the resolved callee for calls.
+    pub name: String,
+    /// The function a call is made from, as named for definitions.
+    pub caller: Option<String>,
+    /// Every condition on the node and the items around it; all must hold.
+    pub cfg: Vec<Cfg>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub enum GatedKind {
+    Function,
+    Type,
+    Call,
+}
+
+/// Functions, types and calls under a `#[cfg]`, on themselves or on an
+/// enclosing item, statement or module (`#![cfg]` at the top gates the
+/// file). Inline `mod` bodies are walked; `cfg_attr` is not a condition.
+pub fn gated(tree: &Tree, source: &str) -> Vec<Gated> {
+    let src = source.as_bytes();
+    let root = tree.root_node();
+    let imports = collect_imports(root, src);
+    let mut cursor = root.walk();
+    let file: Vec<Cfg> = root
+        .named_children(&mut cursor)
+        .filter(|n| n.kind() == "inner_attribute_item")
+        .filter_map(|n| cfg_attribute(n, src))
+        .collect();
+    let mut out = Vec::new();
+    let scope = Scope {
+        prefix: String::new(),
+        caller: None,
+    };
+    collect_gated(root, src, &imports, &file, &scope, &mut out);
+    out
+}
+
+/// Where [`collect_gated`] is: the path definitions are named under and the
+/// function calls are made from.
+struct Scope {
+    prefix: String,
+    caller: Option<String>,
+}
+
+fn collect_gated(
+    node: Node,
+    src: &[u8],
+    imports: &HashMap<String, String>,
+    cfg: &[Cfg],
+    scope: &Scope,
+    out: &mut Vec<Gated>,
+) {
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        if matches!(child.kind(), "attribute_item" | "inner_attribute_item") {
+            continue;
+        }
+        let mut conds = cfg.to_vec();
+        conds.extend(cfg_attributes(child, src));
+        let mut push = |kind, name: String, caller: Option<String>| {
+            if !conds.is_empty() {
+                out.push(Gated {
+                    kind,
+                    name,
+                    caller,
+                    cfg: conds.clone(),
+                    span: meta(child, src).span,
+                });
+            }
+        };
+        let inner = match child.kind() {
+            "function_item" => {
+                let name = format!("{}{}", scope.prefix, field_text(child, "name", src));
+                push(GatedKind::Function, name.clone(), None);
+                Some(Scope {
+                    prefix: scope.prefix.clone(),
+                    caller: Some(name),
+                })
+            }
+            "struct_item" | "enum_item" | "type_item" | "union_item" => {
+                let name = format!("{}{}", scope.prefix, field_text(child, "name", src));
+                push(GatedKind::Type, name, None);
+                None
+            }
+            "trait_item" | "impl_item" => {
+                let field = if child.kind() == "trait_item" {
+                    "name"
+                } else {
+                    "type"
+                };
+                let name = format!("{}{}", scope.prefix, field_text(child, field, src));
+                if child.kind() == "trait_item" {
+                    push(GatedKind::Type, name.clone(), None);
+                }
+                Some(Scope {
+                    prefix: format!("{name}::"),
+                    caller: scope.caller.clone(),
+                })
+            }
+            "mod_item" => Some(Scope {
+                prefix: format!("{}{}::", scope.prefix, field_text(child, "name", src)),
+                caller: scope.caller.clone(),
+            }),
+            "call_expression" | "macro_invocation" => {
+                let (field, bang) = match child.kind() {
+                    "call_expression" => ("function", ""),
+                    _ => ("macro", "!"),
+                };
+                let raw = child
+                    .child_by_field_name(field)
+                    .map(|f| call_name(f, src))
+                    .unwrap_or_default();
+                let name = format!("{}{bang}", resolve_call(raw, imports));
+                push(GatedKind::Call, name, scope.caller.clone());
+                None
+            }
+            _ => None,
+        };
+        collect_gated(
+            child,
+            src,
+            imports,
+            &conds,
+            inner.as_ref().unwrap_or(scope),
+            out,
+        );
+    }
+}
+
+/// The `#[cfg(...)]` predicates among the attributes directly above `node`.
+fn cfg_attributes(node: Node, src: &[u8]) -> Vec<Cfg> {
+    outer_attributes(node)
+        .into_iter()
+        .filter_map(|item| cfg_attribute(item, src))
+        .collect()
+}
+
+/// The predicate of a `#[cfg(...)]` or `#![cfg(...)]` attribute item.
+fn cfg_attribute(item: Node, src: &[u8]) -> Option<Cfg> {
+    let attr = item.named_child(0)?;
+    let name = attr.named_child(0)?.utf8_text(src).ok()?;
+    if name != "cfg" {
+        return None;
     }
+    let mut predicates = cfg_list(attr.child_by_field_name("arguments")?, src);
+    (predicates.len() == 1).then(|| predicates.remove(0))
 }
 
-fn meta(node: Node, src: &[u8]) -> Metadata {
-    metadata_from_span(src, node.start_byte(), node.end_byte())
+/// The comma-separated predicates inside a token tree, `(a, b = "c", d(..))`.
+fn cfg_list(tokens: Node, src: &[u8]) -> Vec<Cfg> {
+    let mut out = Vec::new();
+    let mut cursor = tokens.walk();
+    let children: Vec<Node> = tokens.children(&mut cursor).collect();
+    let mut i = 0;
+    while i < children.len() {
+        let node = children[i];
+        i += 1;
+        if node.kind() != "identifier" {
+            continue;
+        }
+        let name = node.utf8_text(src).unwrap_or("");
+        let next = children.get(i).map(|n| n.kind());
+        let predicate = match next {
+            Some("=") => {
+                let value = children.get(i + 1).map_or("", |v| string_contents(*v, src));
+                i += 2;
+                match name {
+                    "feature" => Cfg::Feature(value.to_string()),
+                    _ => Cfg::Other(format!("{name} = \"{value}\"")),
+                }
+            }
+            Some("token_tree") => {
+                let tree = children[i];
+                let args = cfg_list(tree, src);
+                i += 1;
+                match name {
+                    "all" => Cfg::All(args),
+                    "any" => Cfg::Any(args),
+                    "not" if args.len() == 1 => Cfg::Not(Box::new(args[0].clone())),
+                    _ => Cfg::Other(format!("{name}{}", tree.utf8_text(src).unwrap_or(""))),
+                }
+            }
+            _ => Cfg::Other(name.to_string()),
+        };
+        out.push(predicate);
+    }
+    out
 }
 
 // ── Tests ──────────────────────────────────────────────────────────
@@ -307,12 +1446,23 @@ fn meta(node: Node, src: &[u8]) -> Metadata {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::api::tree_sitter::Lang;
+    use crate::api::tree_sitter::{Lang, Strings};
+
+    fn parse(source: &str) -> Vec<Syntax<'_>> {
+        let lang = Rust;
+        let mut parser = lang.get_parser();
+        lang.parse(&mut parser, source, &Options::default())
+            .unwrap()
+    }
 
-    fn parse(source: &str) -> Vec<Syntax> {
+    fn parse_strings(source: &str) -> Vec<Syntax<'_>> {
         let lang = Rust;
         let mut parser = lang.get_parser();
-        lang.parse(&mut parser, source).unwrap()
+        let opts = Options {
+            strings: Some(Strings::All),
+            ..Options::default()
+        };
+        lang.parse(&mut parser, source, &opts).unwrap()
     }
 
     fn names(nodes: &[Syntax]) -> Vec<String> {
@@ -321,8 +1471,33 @@ mod tests {
             .map(|s| match &s.node {
                 ASTNode::Function(f) => format!("fn:{}", f.name),
                 ASTNode::Type(t) => format!("ty:{}", t.name),
+                ASTNode::Field(f) => format!("field:{}", f.name),
+                ASTNode::Variant(v) => format!("variant:{}", v.name),
                 ASTNode::Call(c) => format!("call:{}", c.name),
+                ASTNode::UsesType(u) => format!("uses:{}", u.name),
+                ASTNode::Module(m) => format!("mod:{}", m.name),
+                ASTNode::Import(i) => {
+                    let mut name = format!("import:{}", i.module);
+                    if !i.items.is_empty() {
+                        name += &format!(":{}", i.items.join(","));
+                    }
+                    if let Some(alias) = &i.alias {
+                        name += &format!(" as {alias}");
+                    }
+                    name
+                }
+                ASTNode::DynamicImport(d) => {
+                    format!("dyn:{}", d.module.as_deref().unwrap_or("?"))
+                }
+                ASTNode::Endpoint(e) => format!("endpoint:{}", e.route),
+                ASTNode::Fixture(f) => format!("fixture:{}", f.name),
+                ASTNode::UsesFixture(u) => format!("needs:{}", u.name),
+                ASTNode::Unsafe(u) => format!("unsafe:{:?}", u.kind),
+                ASTNode::Attribute(a) => format!("attr:{}({})", a.name, a.args.join(", ")),
+                ASTNode::Exception(e) => format!("{:?}:{}", e.kind, e.name),
+                ASTNode::Literal(l) => format!("str:{}", l.value),
                 ASTNode::Comment => "comment".into(),
+                ASTNode::Unknown(v) => format!("unknown:{v}"),
                 ASTNode::File(f) => format!("file:{}", f.path),
             })
             .collect()
@@ -354,19 +1529,220 @@ mod tests {
         assert!(inner.contains(&"call:bar".to_string()));
     }
 
+    // ── Complexity ──
+
+    #[cfg(feature = "complexity")]
+    fn complexity_of(s: &Syntax) -> Option<u64> {
+        s.metadata.metric(COMPLEXITY).and_then(|v| v.as_u64())
+    }
+
+    #[cfg(feature = "complexity")]
+    #[test]
+    fn straight_line_function_has_complexity_one() {
+        assert_eq!(complexity_of(&parse("fn f() { g(); }")[0]), Some(1));
+    }
+
+    #[cfg(feature = "complexity")]
+    #[test]
+    fn branches_loops_operators_and_question_marks_count() {
+        let src = "fn f(a: bool, b: bool) -> Result<(), E> {\n\
+                   if a && b || a { g()?; } else if b { h(); }\n\
+                   for x in y { while a {} }\n\
+                   if let Some(v) = o { loop { break; } }\n\
+                   let z = a & b;\n\
+                   Ok(())\n}";
+        // if, &&, ||, ?, else if, for, while, if let.
+        assert_eq!(complexity_of(&parse(src)[0]), Some(9));
+    }
+
+    #[cfg(feature = "complexity")]
+    #[test]
+    fn match_counts_arms_past_the_first() {
+        let src = "fn f(x: u8) { match x { 0 => {} 1 | 2 => {} _ => {} } }";
+        assert_eq!(complexity_of(&parse(src)[0]), Some(3));
+    }
+
+    #[cfg(feature = "complexity")]
+    #[test]
+    fn methods_and_closures() {
+        let src = "impl S {\n    fn m(&self) {\n        let c = |x| if x { 1 } else { 2 };\n        \
+                   fn inner(a: bool) { if a {} }\n    }\n}";
+        let nodes = parse(src);
+        let m = nodes
+            .iter()
+            .flat_map(|s| std::iter::once(s).chain(&s.contains))
+            .find(|s| matches!(&s.node, ASTNode::Function(f) if f.name == "m"))
+            .unwrap();
+        // The closure's `if` counts toward `m`; `inner` is measured alone.
+        assert_eq!(complexity_of(m), Some(2));
+    }
+
+    // ── Signature Types ──
+
+    #[test]
+    fn signature_types_resolved() {
+        let src = "\
+use crate::error::BoloError;
+use std::io;
+fn f<T: Clone>(&self, a: &Foo, b: Vec<T>, c: io::Result<u8>) -> Result<Self, BoloError> {}
+";
+        let nodes = parse(src);
+        assert_eq!(
+            names(&nodes[2].contains),
+            vec![
+                "uses:Foo",
+                "uses:Vec",
+                "uses:std::io::Result",
+                "uses:Result",
+                "uses:crate::error::BoloError",
+                "Returns:crate::error::BoloError"
+            ]
+        );
+    }
+
+    #[test]
+    fn function_params_and_return_type() {
+        let src = "use std::path::Path;\nfn f(&mut self, p: &Path, (a, b): (u8, u8)) -> Option<Path> {}\nfn g() {}\n";
+        let nodes = parse(src);
+        let ASTNode::Function(f) = &nodes[1].node else {
+            panic!("expected function");
+        };
+        let params: Vec<(&str, Option<&str>)> = f
+            .params
+            .iter()
+            .map(|p| (&*p.name, p.annotation.as_deref()))
+            .collect();
+        assert_eq!(
+            params,
+            [
+                ("self", Some("&mut Self")),
+                ("p", Some("&std::path::Path")),
+                ("(a, b)", Some("(u8, u8)")),
+            ]
+        );
+        assert_eq!(f.return_type.as_deref(), Some("Option<std::path::Path>"));
+        let ASTNode::Function(g) = &nodes[2].node else {
+            panic!("expected function");
+        };
+        assert!(g.params.is_empty() && g.return_type.is_none());
+    }
+
+    #[test]
+    fn self_receivers_typed() {
+        let src = "impl S {\n    fn a(self) {}\n    fn b(mut self) {}\n    fn c(&'a self) {}\n    \
+                   fn d(self: Box<Self>) {}\n}\n";
+        let nodes = parse(src);
+        let receivers: Vec<(&str, Option<&str>)> = nodes[0]
+            .contains
+            .iter()
+            .filter_map(|s| match &s.node {
+                ASTNode::Function(f) => {
+                    Some((&*f.params[0].name, f.params[0].annotation.as_deref()))
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            receivers,
+            [
+                ("self", Some("Self")),
+                ("self", Some("Self")),
+                ("self", Some("&'a Self")),
+                ("self", Some("Box<Self>")),
+            ]
+        );
+    }
+    #[test]
+    fn signature_types_primitives_skipped() {
+        let nodes = parse("fn f(a: u8, b: &str) -> bool { g() }");
+        assert_eq!(names(&nodes[0].contains), vec!["call:g"]);
+    }
+
     // ── Structs / Enums / Type Aliases ──
 
     #[test]
     fn struct_item() {
         let nodes = parse("struct Config { x: i32 }");
         assert_eq!(names(&nodes), vec!["ty:Config"]);
-        assert!(nodes[0].contains.is_empty());
+        assert_eq!(names(&nodes[0].contains), vec!["field:x"]);
     }
 
     #[test]
     fn enum_item() {
         let nodes = parse("enum Color { Red, Blue }");
         assert_eq!(names(&nodes), vec!["ty:Color"]);
+        assert_eq!(
+            names(&nodes[0].contains),
+            vec!["variant:Red", "variant:Blue"]
+        );
+    }
+
+    // ── Fields & Variants ──
+
+    fn annotations(nodes: &[Syntax]) -> Vec<(String, Option<String>)> {
+        nodes
+            .iter()
+            .filter_map(|s| match &s.node {
+                ASTNode::Field(f) => Some((
+                    f.name.to_string(),
+                    f.annotation.as_deref().map(str::to_string),
+                )),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn struct_field_types_resolved() {
+        let src = "use crate::api::File;\nstruct Walk { root: File, files: Vec<File>, n: usize }";
+        let nodes = parse(src);
+        assert_eq!(
+            annotations(&nodes[1].contains),
+            vec![
+                ("root".into(), Some("crate::api::File".into())),
+                ("files".into(), Some("Vec<crate::api::File>".into())),
+                ("n".into(), Some("usize".into())),
+            ]
+        );
+    }
+
+    #[test]
+    fn scoped_field_type_resolved() {
+        let src = "use std::io;\nstruct E { err: &'static io::Error }";
+        let nodes = parse(src);
+        assert_eq!(
+            annotations(&nodes[1].contains),
+            vec![("err".into(), Some("&'static std::io::Error".into()))]
+        );
+    }
+
+    #[test]
+    fn tuple_struct_fields_positional() {
+        let nodes = parse("struct Pair(pub Foo, u8);");
+        assert_eq!(
+            annotations(&nodes[0].contains),
+            vec![
+                ("0".into(), Some("Foo".into())),
+                ("1".into(), Some("u8".into())),
+            ]
+        );
+    }
+
+    #[test]
+    fn variant_payloads_nested() {
+        let src = "enum Node { Leaf, Pair(Foo, Bar), Named { inner: Baz } }";
+        let nodes = parse(src);
+        let variants = &nodes[0].contains;
+        assert_eq!(
+            names(variants),
+            vec!["variant:Leaf", "variant:Pair", "variant:Named"]
+        );
+        assert!(variants[0].contains.is_empty());
+        assert_eq!(names(&variants[1].contains), vec!["field:0", "field:1"]);
+        assert_eq!(
+            annotations(&variants[2].contains),
+            vec![("inner".into(), Some("Baz".into()))]
+        );
     }
 
     #[test]
@@ -445,6 +1821,110 @@ mod tests {
         assert_eq!(inner, vec!["call:parser.parse"]);
     }
 
+    // ── Spawned Work ──
+
+    fn spawned_by<'a>(s: &'a Syntax) -> Option<&'a str> {
+        match &s.node {
+            ASTNode::Call(c) => c.spawned_by.as_deref(),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn spawned_closure_and_function() {
+        let src = "use std::thread;\nfn f() {\n    tokio::spawn(async move { serve(conn).await });\n    thread::spawn(worker);\n    other();\n}";
+        let nodes = parse(src);
+        let calls = &nodes[1].contains;
+        assert_eq!(
+            names(calls),
+            vec!["call:tokio::spawn", "call:std::thread::spawn", "call:other"]
+        );
+        assert_eq!(names(&calls[0].contains), vec!["call:serve"]);
+        assert_eq!(spawned_by(&calls[0].contains[0]), Some("tokio::spawn"));
+        assert_eq!(names(&calls[1].contains), vec!["call:worker"]);
+        assert_eq!(
+            spawned_by(&calls[1].contains[0]),
+            Some("std::thread::spawn")
+        );
+        assert_eq!(spawned_by(&calls[2]), None);
+    }
+
+    #[test]
+    fn nested_spawn_keeps_its_own_work() {
+        let src = "fn f() { std::thread::spawn(|| { tokio::spawn(job()); log() }); }";
+        let nodes = parse(src);
+        let outer = &nodes[0].contains[0];
+        assert_eq!(
+            names(&outer.contains),
+            vec!["call:tokio::spawn", "call:log"]
+        );
+        assert_eq!(spawned_by(&outer.contains[0]), Some("std::thread::spawn"));
+        assert_eq!(
+            spawned_by(&outer.contains[0].contains[0]),
+            Some("tokio::spawn")
+        );
+    }
+
+    // ── Error Flow ──
+
+    fn errors(nodes: &[Syntax]) -> Vec<String> {
+        names(nodes)
+            .into_iter()
+            .filter(|n| n.starts_with("Returns:") || n.starts_with("Propagates:"))
+            .collect()
+    }
+
+    #[test]
+    fn result_error_types() {
+        let src = "\
+use std::{fmt, io};
+use crate::error::Result;
+fn a() -> Result<(), Error> {}
+fn b() -> io::Result<u8> {}
+fn c() -> fmt::Result {}
+fn d() -> Result<u8> {}
+fn e() -> std::result::Result<u8, Box<dyn std::error::Error>> {}
+fn f() -> Option<u8> {}
+";
+        let nodes = parse(src);
+        let found: Vec<Vec<String>> = nodes[2..].iter().map(|n| errors(&n.contains)).collect();
+        assert_eq!(
+            found,
+            [
+                vec!["Returns:Error"],
+                vec!["Returns:std::io::Error"],
+                vec!["Returns:std::fmt::Error"],
+                vec!["Returns:crate::error::Error"],
+                vec!["Returns:Box<dyn std::error::Error>"],
+                vec![],
+            ]
+        );
+    }
+
+    #[test]
+    fn question_marks_propagate_the_return_error() {
+        let src = "\
+fn load(p: &Path) -> Result<Config, BoloError> {
+    let text = read(p)?;
+    let f = |s: &str| -> Result<u8, E> { s.parse()? };
+    parse(&text)?.validate()?;
+    Ok(c)
+}
+fn find() -> Option<u8> { x()? }
+";
+        let nodes = parse(src);
+        assert_eq!(
+            errors(&nodes[0].contains),
+            vec![
+                "Returns:BoloError",
+                "Propagates:BoloError",
+                "Propagates:BoloError",
+                "Propagates:BoloError"
+            ]
+        );
+        assert!(errors(&nodes[1].contains).is_empty());
+    }
+
     // ── Macros ──
 
     #[test]
@@ -483,7 +1963,7 @@ mod tests {
     fn use_resolves_call() {
         let src = "use std::collections::HashMap;\nfn f() { HashMap::new() }";
         let nodes = parse(src);
-        let inner = names(&nodes[0].contains);
+        let inner = names(&nodes[1].contains);
         assert_eq!(inner, vec!["call:std::collections::HashMap::new"]);
     }
 
@@ -491,7 +1971,7 @@ mod tests {
     fn use_braces_resolves() {
         let src = "use std::io::{Read, Write};\nfn f() { Read::read() }";
         let nodes = parse(src);
-        let inner = names(&nodes[0].contains);
+        let inner = names(&nodes[1].contains);
         assert_eq!(inner, vec!["call:std::io::Read::read"]);
     }
 
@@ -499,21 +1979,32 @@ mod tests {
     fn use_alias_resolves() {
         let src = "use std::collections::HashMap as Map;\nfn f() { Map::new() }";
         let nodes = parse(src);
-        let inner = names(&nodes[0].contains);
+        let inner = names(&nodes[1].contains);
         assert_eq!(inner, vec!["call:std::collections::HashMap::new"]);
     }
 
+    #[test]
+    fn extern_crate_alias_resolves() {
+        let src = "extern crate serde_json as json;\nfn f() { json::to_string() }";
+        let nodes = parse(src);
+        assert_eq!(names(&nodes), vec!["import:serde_json as json", "fn:f"]);
+        assert_eq!(
+            names(&nodes[1].contains),
+            vec!["call:serde_json::to_string"]
+        );
+    }
+
     #[test]
     fn self_prefix_not_resolved() {
         let imports = HashMap::new();
-        assert_eq!(resolve_call("self.foo", &imports), "self.foo");
+        assert_eq!(resolve_call("self.foo".into(), &imports), "self.foo");
     }
 
     #[test]
     fn crate_prefix_not_resolved() {
         let imports = HashMap::new();
         assert_eq!(
-            resolve_call("crate::util::run", &imports),
+            resolve_call("crate::util::run".into(), &imports),
             "crate::util::run"
         );
     }
@@ -528,7 +2019,7 @@ mod tests {
             "std::collections::HashMap".to_string(),
         );
         assert_eq!(
-            resolve_call("HashMap::new", &imports),
+            resolve_call("HashMap::new".into(), &imports),
             "std::collections::HashMap::new"
         );
     }
@@ -536,7 +2027,7 @@ mod tests {
     #[test]
     fn resolve_call_no_mapping() {
         let imports = HashMap::new();
-        assert_eq!(resolve_call("foo::bar", &imports), "foo::bar");
+        assert_eq!(resolve_call("foo::bar".into(), &imports), "foo::bar");
     }
 
     #[test]
@@ -544,7 +2035,7 @@ mod tests {
         let mut imports = HashMap::new();
         imports.insert("parser".to_string(), "tree_sitter::Parser".to_string());
         assert_eq!(
-            resolve_call("parser.parse", &imports),
+            resolve_call("parser.parse".into(), &imports),
             "tree_sitter::Parser.parse"
         );
     }
@@ -567,10 +2058,24 @@ mod tests {
     // ── Skipped nodes ──
 
     #[test]
-    fn use_declarations_not_in_output() {
-        let src = "use std::io;\nfn main() {}";
+    fn use_declarations_become_imports() {
+        let src = "use std::io;\nuse serde;\nuse crate::{self as root, api::{File, Lang}, error::*};\n\
+                   use std::io::{Read, Write as W, BufRead};\nfn main() {}";
         let nodes = parse(src);
-        assert_eq!(names(&nodes), vec!["fn:main"]);
+        assert_eq!(
+            names(&nodes),
+            vec![
+                "import:std:io",
+                "import:serde",
+                "import:crate as root",
+                "import:crate::api:File,Lang",
+                "import:crate::error:*",
+                "import:std::io:Read,BufRead",
+                "import:std::io:Write as W",
+                "fn:main"
+            ]
+        );
+        assert_eq!(nodes[2].metadata.span.start_line, 3);
     }
 
     #[test]
@@ -580,6 +2085,160 @@ mod tests {
         assert_eq!(names(&nodes), vec!["ty:Foo"]);
     }
 
+    // ── Attributes ──
+
+    #[test]
+    fn attributes_on_items() {
+        let src = "use serde::{Deserialize, Serialize};\n\
+                   #[derive(Debug, Clone, Serialize, Deserialize)]\n\
+                   /// A config.\n\
+                   #[serde(rename_all = \"kebab-case\", default)]\n\
+                   pub struct Config;\n\
+                   #[tokio::main]\nasync fn main() {}\n\
+                   #[test]\nfn t() { f() }\n";
+        let nodes = parse(src);
+        assert_eq!(
+            names(&nodes[2].contains),
+            vec![
+                "attr:derive(Debug, Clone, serde::Serialize, serde::Deserialize)",
+                "attr:serde(rename_all = \"kebab-case\", default)"
+            ]
+        );
+        assert_eq!(nodes[2].contains[1].metadata.span.start_line, 4);
+        assert_eq!(names(&nodes[3].contains), vec!["attr:tokio::main()"]);
+        assert_eq!(names(&nodes[4].contains), vec!["attr:test()", "call:f"]);
+    }
+
+    #[test]
+    fn decorators_on_functions_and_types() {
+        let src = "#[derive(Debug)]\n#[cfg(test)]\nstruct S;\n\
+                   #[tokio::main]\nasync fn main() {}\nfn plain() {}\n";
+        let nodes = parse(src);
+        let ASTNode::Type(t) = &nodes[0].node else {
+            panic!("expected type");
+        };
+        assert_eq!(t.decorators, vec!["derive(Debug)", "cfg(test)"]);
+        let ASTNode::Function(f) = &nodes[1].node else {
+            panic!("expected fn");
+        };
+        assert_eq!(f.decorators, vec!["tokio::main"]);
+        let ASTNode::Function(f) = &nodes[2].node else {
+            panic!("expected fn");
+        };
+        assert!(f.decorators.is_empty());
+    }
+
+    #[test]
+    fn attributes_on_fields_variants_and_modules() {
+        let src = "struct S {\n    #[serde(skip)]\n    cache: u8,\n    n: u8,\n}\n\
+                   enum E {\n    #[default]\n    A,\n    B(#[allow(unused)] u8),\n}\n\
+                   #[path = \"sys/unix.rs\"]\nmod sys;\n";
+        let nodes = parse(src);
+        assert_eq!(
+            names(&nodes[0].contains[0].contains),
+            vec!["attr:serde(skip)"]
+        );
+        assert!(nodes[0].contains[1].contains.is_empty());
+        assert_eq!(
+            names(&nodes[1].contains[0].contains),
+            vec!["attr:default()"]
+        );
+        assert_eq!(
+            names(&nodes[2].contains),
+            vec!["attr:path(\"sys/unix.rs\")"]
+        );
+    }
+
+    // ── Unsafe ──
+
+    #[test]
+    fn unsafe_fn_marked() {
+        let nodes = parse("unsafe fn raw() {}\nfn safe() {}");
+        assert_eq!(names(&nodes[0].contains), vec!["unsafe:Fn"]);
+        assert!(nodes[1].contains.is_empty());
+    }
+
+    #[test]
+    fn unsafe_extern_fn_marked() {
+        let nodes = parse("pub unsafe extern \"C\" fn c() {}");
+        assert_eq!(names(&nodes[0].contains), vec!["unsafe:Fn"]);
+    }
+
+    #[test]
+    fn unsafe_block_nests_calls() {
+        let nodes = parse("fn f() { a(); unsafe { g(); } }");
+        assert_eq!(names(&nodes[0].contains), vec!["call:a", "unsafe:Block"]);
+        assert_eq!(names(&nodes[0].contains[1].contains), vec!["call:g"]);
+    }
+
+    #[test]
+    fn unsafe_impl_and_trait_marked() {
+        let nodes = parse("unsafe impl Send for X {}\nunsafe trait T {}");
+        assert_eq!(names(&nodes[0].contains), vec!["unsafe:Impl"]);
+        assert_eq!(names(&nodes[1].contains), vec!["unsafe:Trait"]);
+    }
+
+    #[test]
+    fn unsafe_span_lines() {
+        let nodes = parse("fn f() {\n    unsafe {\n        g();\n    }\n}");
+        let span = nodes[0].contains[0].metadata.span;
+        assert_eq!((span.start_line, span.end_line), (2, 4));
+    }
+
+    // ── Modules ──
+
+    fn module<'a>(s: &'a Syntax) -> &'a Module<'a> {
+        match &s.node {
+            ASTNode::Module(m) => m,
+            other => panic!("expected module, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn mod_declaration() {
+        let nodes = parse("mod api;\npub mod cli;");
+        assert_eq!(names(&nodes), vec!["mod:api", "mod:cli"]);
+        assert!(module(&nodes[0]).path.is_none());
+    }
+
+    #[test]
+    fn mod_path_attribute() {
+        let src = "#[cfg(unix)]\n#[path = \"sys/unix.rs\"]\nmod sys;";
+        let nodes = parse(src);
+        assert_eq!(module(&nodes[0]).path.as_deref(), Some("sys/unix.rs"));
+    }
+
+    #[test]
+    fn path_attribute_not_borrowed_from_previous_item() {
+        let src = "#[path = \"a.rs\"]\nmod a;\nmod b;";
+        let nodes = parse(src);
+        assert!(module(&nodes[1]).path.is_none());
+    }
+
+    #[test]
+    fn inline_mod_holds_its_items() {
+        let nodes = parse("mod tests { fn t() {} }\nmod api;");
+        assert_eq!(names(&nodes), vec!["mod:tests", "mod:api"]);
+        assert!(module(&nodes[0]).inline);
+        assert!(!module(&nodes[1]).inline);
+        assert_eq!(names(&nodes[0].contains), vec!["fn:t"]);
+    }
+
+    // ── Abstract ──
+
+    #[test]
+    fn only_traits_abstract() {
+        let nodes = parse("trait T {}\nstruct S;\nenum E {}\nimpl T for S {}\n");
+        let flags: Vec<bool> = nodes
+            .iter()
+            .filter_map(|s| match &s.node {
+                ASTNode::Type(t) => Some(t.is_abstract),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(flags, vec![true, false, false, false]);
+    }
+
     // ── Metadata ──
 
     #[test]
@@ -605,4 +2264,156 @@ fn run() { Cfg {} }
         assert!(n.contains(&"ty:Cfg".to_string()));
         assert!(n.contains(&"fn:run".to_string()));
     }
+
+    // ── String Literals ──
+
+    #[test]
+    fn strings_in_functions_and_macros() {
+        let src = "#[doc = \"skip\"]\nfn f() {\n    let a = \"a\\nb\";\n    let r = r#\"raw\"#;\n    println!(\"{}\", \"\");\n}\n";
+        let nodes = parse_strings(src);
+        assert_eq!(
+            names(&nodes[0].contains),
+            vec![
+                "attr:doc(\"skip\")",
+                "str:a\\nb",
+                "str:raw",
+                "call:println!",
+                "str:{}",
+                "str:"
+            ]
+        );
+    }
+
+    #[test]
+    fn strings_nested_in_impl_methods() {
+        let src = "struct S;\nimpl S {\n    fn url() -> &'static str {\n        \"https://x.io\"\n    }\n}\n";
+        let nodes = parse_strings(src);
+        // impl S → Type(S) → fn url
+        let url = &nodes[1].contains[0];
+        assert_eq!(names(std::slice::from_ref(url)), vec!["fn:url"]);
+        assert_eq!(names(&url.contains), vec!["str:https://x.io"]);
+    }
+
+    // ── Conditional Compilation ──
+
+    fn gated_in(source: &str) -> Vec<Gated> {
+        let tree = Rust.get_parser().parse(source, None).unwrap();
+        gated(&tree, source)
+    }
+
+    #[test]
+    fn cfg_predicates_parsed() {
+        let src = "#[cfg(all(feature = \"a\", not(any(unix, feature = \"b\"))))]\nfn f() {}\n\
+                   #[cfg(target_os = \"linux\")]\nfn g() {}\n";
+        let g = gated_in(src);
+        assert_eq!(
+            g[0].cfg,
+            [Cfg::All(vec![
+                Cfg::Feature("a".into()),
+                Cfg::Not(Box::new(Cfg::Any(vec![
+                    Cfg::Other("unix".into()),
+                    Cfg::Feature("b".into()),
+                ]))),
+            ])]
+        );
+        assert_eq!(
+            g[0].cfg[0].to_string(),
+            "all(feature = \"a\", not(any(unix, feature = \"b\")))"
+        );
+        assert_eq!(g[1].cfg, [Cfg::Other("target_os = \"linux\"".into())]);
+    }
+
+    #[test]
+    fn cfg_eval_is_unknown_beyond_features() {
+        let on = BTreeSet::from(["a".to_string()]);
+        let a = Cfg::Feature("a".into());
+        let unix = Cfg::Other("unix".into());
+        assert_eq!(a.eval(&on), Some(true));
+        assert_eq!(Cfg::Not(Box::new(a.clone())).eval(&on), Some(false));
+        assert_eq!(Cfg::All(vec![a.clone(), unix.clone()]).eval(&on), None);
+        assert_eq!(
+            Cfg::Any(vec![a.clone(), unix.clone()]).eval(&on),
+            Some(true)
+        );
+        assert_eq!(Cfg::Any(vec![a, unix]).eval(&BTreeSet::new()), None);
+    }
+
+    #[test]
+    fn gated_items_calls_and_statements() {
+        let src = "use crate::net::fetch;\n\
+                   fn always() {\n    #[cfg(feature = \"net\")]\n    fetch();\n    local();\n}\n\
+                   #[cfg(feature = \"net\")]\nimpl Client {\n    fn send(&self) { println!(); }\n}\n\
+                   #[derive(Debug)]\n#[cfg(test)]\nstruct Fixture;\n";
+        let g = gated_in(src);
+        let found: Vec<(GatedKind, &str, Option<&str>, usize)> = g
+            .iter()
+            .map(|g| (g.kind, g.name.as_str(), g.caller.as_deref(), g.cfg.len()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (GatedKind::Call, "crate::net::fetch", Some("always"), 1),
+                (GatedKind::Function, "Client::send", None, 1),
+                (GatedKind::Call, "println!", Some("Client::send"), 1),
+                (GatedKind::Type, "Fixture", None, 1),
+            ]
+        );
+        assert_eq!(g[0].span.start_line, 4);
+    }
+
+    #[test]
+    fn inner_cfg_gates_the_file() {
+        let src = "#![cfg(feature = \"x\")]\n#[cfg(unix)]\nfn f() {}\n";
+        let g = gated_in(src);
+        assert_eq!(
+            g[0].cfg,
+            [Cfg::Feature("x".into()), Cfg::Other("unix".into())]
+        );
+    }
+
+    // ── Global State ──
+
+    fn globals_in(source: &str) -> Globals {
+        let tree = Rust.get_parser().parse(source, None).unwrap();
+        globals(&tree, source)
+    }
+
+    #[test]
+    fn static_kinds() {
+        let src = "static mut COUNT: u32 = 0;\n\
+                   static NAME: &str = \"x\";\n\
+                   static CONFIG: OnceLock<Config> = OnceLock::new();\n\
+                   static HITS: std::sync::atomic::AtomicUsize = AtomicUsize::new(0);\n\
+                   lazy_static! {\n    static ref TABLE: Mutex<Vec<u8>> = Mutex::new(vec![]);\n}\n";
+        let g = globals_in(src);
+        let found: Vec<(&str, GlobalKind)> = g
+            .defined
+            .iter()
+            .map(|g| (g.name.as_str(), g.kind))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("COUNT", GlobalKind::StaticMut),
+                ("CONFIG", GlobalKind::InteriorMut),
+                ("HITS", GlobalKind::InteriorMut),
+                ("TABLE", GlobalKind::LazyStatic),
+            ]
+        );
+        assert_eq!(g.defined[3].span.start_line, 6);
+    }
+
+    #[test]
+    fn global_uses_per_function() {
+        let src = "static mut COUNT: u32 = 0;\n\
+                   fn bump() { unsafe { COUNT += 1 } }\n\
+                   impl Counter {\n    fn get(&self) -> u32 { unsafe { crate::COUNT } }\n}\n";
+        let g = globals_in(src);
+        let uses: Vec<(&str, bool)> = g
+            .uses
+            .iter()
+            .map(|(f, names)| (f.as_str(), names.contains("COUNT")))
+            .collect();
+        assert_eq!(uses, [("bump", true), ("Counter::get", true)]);
+    }
 }
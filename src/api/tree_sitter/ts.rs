@@ -0,0 +1,353 @@
+use super::js::{self, class, field_text, function, is_public, meta, resolve_call, text, walk};
+use super::{ASTNode, Field, Options, Span, Syntax, Type, UsesType, Variant};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use tree_sitter::{Node, Parser, Tree};
+
+/// TypeScript (`.ts`). The grammar extends JavaScript's, so extraction is
+/// [`js::extract`]; this module adds the declarations JavaScript lacks.
+pub struct TypeScript;
+
+impl super::Lang for TypeScript {
+    fn get_parser(&self) -> Parser {
+        let mut parser = Parser::new();
+        parser
+            .set_language(&tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into())
+            .expect("failed to load typescript grammar");
+        parser
+    }
+
+    fn extract<'s>(
+        &self,
+        tree: &Tree,
+        source: &'s str,
+        opts: &Options,
+    ) -> (Vec<Syntax<'s>>, Vec<Span>) {
+        js::extract(tree, source, opts)
+    }
+}
+
+// ── Declarations ────────────────────────────────────────────────────
+
+/// Interfaces and abstract classes are abstract Types, type aliases Types
+/// naming what they alias, enums Types of Variants; interface members and
+/// abstract methods are bodiless Functions and Fields.
+pub(super) fn declaration<'s>(
+    node: Node,
+    src: &'s [u8],
+    imports: &HashMap<String, String>,
+) -> Syntax<'s> {
+    let name = field_text(node, "name", src);
+    match node.kind() {
+        "abstract_class_declaration" => {
+            let mut syntax = class(node, node, name, src, imports);
+            if let ASTNode::Type(t) = &mut syntax.node {
+                t.is_abstract = true;
+            }
+            syntax
+        }
+        "method_signature" | "abstract_method_signature" => {
+            function(node, node, name, src, imports)
+        }
+        "property_signature" | "public_field_definition" => {
+            let annotation = node
+                .child_by_field_name("type")
+                .and_then(|t| t.named_child(0))
+                .map(|t| Cow::Borrowed(text(t, src)));
+            let contains = node
+                .child_by_field_name("value")
+                .map(|v| {
+                    let mut out = Vec::new();
+                    js::visit(v, src, imports, &mut out);
+                    out
+                })
+                .unwrap_or_default();
+            Syntax {
+                node: ASTNode::Field(Field {
+                    name: name.into(),
+                    annotation,
+                }),
+                metadata: meta(node, src),
+                contains,
+            }
+        }
+        kind => {
+            let contains = match kind {
+                "enum_declaration" => variants(node, src),
+                "interface_declaration" => {
+                    let mut contains = heritage(node, src, imports);
+                    if let Some(body) = node.child_by_field_name("body") {
+                        contains.extend(walk(body, src, imports));
+                    }
+                    contains
+                }
+                // type_alias_declaration
+                _ => node
+                    .child_by_field_name("value")
+                    .map(|v| uses_types(v, src, imports))
+                    .unwrap_or_default(),
+            };
+            Syntax {
+                node: ASTNode::Type(Type {
+                    name: name.into(),
+                    public: is_public(node, name),
+                    is_abstract: kind == "interface_declaration",
+                    decorators: vec![],
+                }),
+                metadata: meta(node, src),
+                contains,
+            }
+        }
+    }
+}
+
+fn variants<'s>(node: Node, src: &'s [u8]) -> Vec<Syntax<'s>> {
+    let Some(body) = node.child_by_field_name("body") else {
+        return Vec::new();
+    };
+    let mut cursor = body.walk();
+    body.named_children(&mut cursor)
+        .filter_map(|m| {
+            let name = match m.kind() {
+                "property_identifier" | "string" => text(m, src),
+                "enum_assignment" => field_text(m, "name", src),
+                _ => return None,
+            };
+            Some(Syntax {
+                node: ASTNode::Variant(Variant { name: name.into() }),
+                metadata: meta(m, src),
+                contains: vec![],
+            })
+        })
+        .collect()
+}
+
+/// The interfaces an interface `extends`.
+fn heritage<'s>(node: Node, src: &'s [u8], imports: &HashMap<String, String>) -> Vec<Syntax<'s>> {
+    let mut cursor = node.walk();
+    node.named_children(&mut cursor)
+        .filter(|c| c.kind() == "extends_type_clause")
+        .flat_map(|c| uses_types(c, src, imports))
+        .collect()
+}
+
+// ── Type References ─────────────────────────────────────────────────
+
+/// UsesType nodes for the types a function's parameters and return type
+/// name, so `import type` bindings resolve like calls do. Empty for
+/// JavaScript, which has no annotations.
+pub(super) fn signature_types<'s>(
+    func: Node,
+    src: &'s [u8],
+    imports: &HashMap<String, String>,
+) -> Vec<Syntax<'s>> {
+    let mut refs = Vec::new();
+    for field in ["parameters", "return_type"] {
+        if let Some(n) = func.child_by_field_name(field) {
+            type_refs(n, src, &mut refs);
+        }
+    }
+    resolve_types(refs, src, imports)
+}
+
+/// Named types in `node`, first mention of each; built-ins such as
+/// `string` are `predefined_type` nodes and never seen.
+fn uses_types<'s>(node: Node, src: &'s [u8], imports: &HashMap<String, String>) -> Vec<Syntax<'s>> {
+    let mut refs = Vec::new();
+    type_refs(node, src, &mut refs);
+    resolve_types(refs, src, imports)
+}
+
+fn resolve_types<'s>(
+    refs: Vec<(Node, Cow<'s, str>)>,
+    src: &'s [u8],
+    imports: &HashMap<String, String>,
+) -> Vec<Syntax<'s>> {
+    let mut seen = Vec::new();
+    let mut out = Vec::new();
+    for (n, raw) in refs {
+        let name = resolve_call(raw, imports);
+        if seen.contains(&name) {
+            continue;
+        }
+        seen.push(name.clone());
+        out.push(Syntax {
+            node: ASTNode::UsesType(UsesType { name }),
+            metadata: meta(n, src),
+            contains: vec![],
+        });
+    }
+    out
+}
+
+fn type_refs<'a, 's>(node: Node<'a>, src: &'s [u8], out: &mut Vec<(Node<'a>, Cow<'s, str>)>) {
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        match child.kind() {
+            "type_identifier" | "nested_type_identifier" => {
+                out.push((child, Cow::Borrowed(text(child, src))));
+            }
+            // Default values are expressions, not types.
+            "required_parameter" | "optional_parameter" => {
+                if let Some(t) = child.child_by_field_name("type") {
+                    type_refs(t, src, out);
+                }
+            }
+            // `<T>` declares a type parameter rather than naming a type.
+            "type_parameters" => {}
+            _ => type_refs(child, src, out),
+        }
+    }
+}
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::tree_sitter::Lang;
+
+    fn parse(source: &str) -> Vec<Syntax<'_>> {
+        let lang = TypeScript;
+        let mut parser = lang.get_parser();
+        let (nodes, errors) = lang
+            .parse_checked(&mut parser, source, &Options::default())
+            .unwrap();
+        assert!(errors.is_empty(), "syntax errors in {source}");
+        nodes
+    }
+
+    fn names(nodes: &[Syntax]) -> Vec<String> {
+        nodes
+            .iter()
+            .map(|s| match &s.node {
+                ASTNode::Function(f) => format!("fn:{}", f.name),
+                ASTNode::Type(t) if t.is_abstract => format!("abstract:{}", t.name),
+                ASTNode::Type(t) => format!("ty:{}", t.name),
+                ASTNode::Field(f) => format!(
+                    "field:{}:{}",
+                    f.name,
+                    f.annotation.as_deref().unwrap_or("?")
+                ),
+                ASTNode::Variant(v) => format!("variant:{}", v.name),
+                ASTNode::Call(c) => format!("call:{}", c.name),
+                ASTNode::UsesType(u) => format!("uses:{}", u.name),
+                ASTNode::Import(i) => match &i.alias {
+                    Some(alias) => format!("import:{}:{} as {alias}", i.module, i.items.join(",")),
+                    None => format!("import:{}:{}", i.module, i.items.join(",")),
+                },
+                other => format!("{other:?}"),
+            })
+            .collect()
+    }
+
+    // ── Declarations ──
+
+    #[test]
+    fn interfaces_are_abstract() {
+        let nodes =
+            parse("interface Repo extends Base {\n  get(id: Id): Item;\n  name: string;\n}\n");
+        assert_eq!(names(&nodes), ["abstract:Repo"]);
+        assert_eq!(
+            names(&nodes[0].contains),
+            ["uses:Base", "fn:get", "field:name:string"]
+        );
+        assert_eq!(
+            names(&nodes[0].contains[1].contains),
+            ["uses:Id", "uses:Item"]
+        );
+    }
+
+    #[test]
+    fn type_alias_uses_its_types() {
+        let nodes = parse("type Index = Map<string, User[]>;\n");
+        assert_eq!(names(&nodes), ["ty:Index"]);
+        assert_eq!(names(&nodes[0].contains), ["uses:Map", "uses:User"]);
+    }
+
+    #[test]
+    fn enums_have_variants() {
+        let nodes = parse("export enum Color { Red, Green = 2 }\n");
+        assert_eq!(names(&nodes), ["ty:Color"]);
+        assert_eq!(names(&nodes[0].contains), ["variant:Red", "variant:Green"]);
+    }
+
+    #[test]
+    fn classes_fields_and_abstract_members() {
+        let src = "abstract class Svc {\n  private x: number = make();\n  abstract run(): void;\n  \
+                   go() { this.run(); }\n}\nclass Impl extends Svc {}\n";
+        let nodes = parse(src);
+        assert_eq!(names(&nodes), ["abstract:Svc", "ty:Impl"]);
+        assert_eq!(
+            names(&nodes[0].contains),
+            ["field:x:number", "fn:run", "fn:go"]
+        );
+        assert_eq!(names(&nodes[0].contains[0].contains), ["call:make"]);
+        assert_eq!(names(&nodes[0].contains[2].contains), ["call:this.run"]);
+    }
+
+    #[test]
+    fn functions_and_calls() {
+        let src = "export function f(u: User, n?: number): Promise<User> {\n  return load(u);\n}\n\
+                   const g = <T,>(x: T): void => h(x);\n";
+        let nodes = parse(src);
+        assert_eq!(names(&nodes), ["fn:f", "fn:g"]);
+        assert_eq!(
+            names(&nodes[0].contains),
+            ["uses:User", "uses:Promise", "call:load"]
+        );
+        assert_eq!(names(&nodes[1].contains), ["uses:T", "call:h"]);
+    }
+
+    #[test]
+    fn overload_signatures_skipped() {
+        let src = "function f(a: string): void;\nfunction f(a: number): void;\n\
+                   function f(a: any) {}\n";
+        assert_eq!(names(&parse(src)), ["fn:f"]);
+    }
+
+    // ── Visibility ──
+
+    #[test]
+    fn private_and_protected_members_not_public() {
+        let nodes = parse(
+            "export class A {\n  run() {}\n  private a() {}\n  protected b() {}\n  public c() {}\n}\n",
+        );
+        let public: Vec<bool> = nodes[0]
+            .contains
+            .iter()
+            .map(|s| matches!(&s.node, ASTNode::Function(f) if f.public))
+            .collect();
+        assert_eq!(public, [true, false, false, true]);
+        assert!(matches!(&nodes[0].node, ASTNode::Type(t) if t.public));
+    }
+
+    // ── Imports ──
+
+    #[test]
+    fn imports_resolve_calls_and_types() {
+        let src = "import type { User } from \"./models\";\n\
+                   import { type Id, load as fetch } from \"./db\";\n\
+                   import fs = require(\"fs\");\n\
+                   function f(id: Id): User {\n  fs.read();\n  return fetch(id);\n}\n";
+        let nodes = parse(src);
+        assert_eq!(
+            names(&nodes[..4]),
+            [
+                "import:./models:User",
+                "import:./db:Id",
+                "import:./db:load as fetch",
+                "import:fs: as fs"
+            ]
+        );
+        assert_eq!(
+            names(&nodes[4].contains),
+            [
+                "uses:./db.Id",
+                "uses:./models.User",
+                "call:fs.read",
+                "call:./db.load"
+            ]
+        );
+    }
+}
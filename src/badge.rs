@@ -0,0 +1,263 @@
+use crate::api::tree_sitter::{ASTNode, Syntax};
+use crate::deps;
+use crate::graph::{DefKind, Graph};
+use serde::Serialize;
+
+/// What `bolo badge` reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Metric {
+    /// Files that depend on each other in a cycle
+    Cycles,
+    /// Mean calls made per function
+    Complexity,
+    /// Share of non-test functions reachable from tests in the call graph
+    Coverage,
+}
+
+/// A shields.io endpoint badge
+/// (<https://shields.io/badges/endpoint-badge>).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Badge {
+    pub schema_version: u8,
+    pub label: &'static str,
+    pub message: String,
+    pub color: &'static str,
+}
+
+/// Path components holding only tests.
+const TEST_DIRS: &[&str] = &["tests", "test"];
+
+/// `metric` over an analysis's first-party files (see [`deps::first_party`]), as a badge.
+pub fn badge(files: &[Vec<Syntax>], metric: Metric) -> Badge {
+    let own = deps::first_party(files);
+    let graph = Graph::build(&own);
+    let (label, message, color) = match metric {
+        Metric::Cycles => {
+            let n = graph.file_cycles().len();
+            let color = match n {
+                0 => "brightgreen",
+                1..=2 => "yellow",
+                _ => "red",
+            };
+            ("cycles", n.to_string(), color)
+        }
+        Metric::Complexity => match complexity(&graph) {
+            Some(mean) => {
+                let color = match mean {
+                    m if m <= 4.0 => "brightgreen",
+                    m if m <= 8.0 => "green",
+                    m if m <= 12.0 => "yellow",
+                    m if m <= 20.0 => "orange",
+                    _ => "red",
+                };
+                ("complexity", format!("{mean:.1} calls/fn"), color)
+            }
+            None => ("complexity", "n/a".to_string(), "lightgrey"),
+        },
+        Metric::Coverage => match coverage(&graph) {
+            Some(pct) => {
+                let color = match pct {
+                    90.. => "brightgreen",
+                    75.. => "green",
+                    60.. => "yellowgreen",
+                    40.. => "yellow",
+                    20.. => "orange",
+                    _ => "red",
+                };
+                ("coverage", format!("{pct}%"), color)
+            }
+            None => ("coverage", "n/a".to_string(), "lightgrey"),
+        },
+    };
+    Badge {
+        schema_version: 1,
+        label,
+        message,
+        color,
+    }
+}
+
+/// Mean number of calls made directly in each function, or `None` without
+/// functions.
+pub(crate) fn complexity(graph: &Graph) -> Option<f64> {
+    let counts: Vec<usize> = graph
+        .defs
+        .iter()
+        .filter(|d| d.kind == DefKind::Function)
+        .map(|d| calls(&d.syntax.contains))
+        .collect();
+    (!counts.is_empty()).then(|| counts.iter().sum::<usize>() as f64 / counts.len() as f64)
+}
+
+/// Calls in `nodes`, not counting those inside nested definitions.
+fn calls(nodes: &[Syntax]) -> usize {
+    nodes
+        .iter()
+        .map(|s| match &s.node {
+            ASTNode::Call(_) => 1 + calls(&s.contains),
+            ASTNode::Function(_) | ASTNode::Type(_) => 0,
+            _ => calls(&s.contains),
+        })
+        .sum()
+}
+
+/// Percentage, rounded down, of non-test functions some test reaches
+/// through call edges; `None` without non-test functions. A static
+/// estimate: calls the graph can't resolve are not followed.
+pub(crate) fn coverage(graph: &Graph) -> Option<usize> {
+    let tests: Vec<bool> = graph
+        .defs
+        .iter()
+        .map(|d| is_test_file(d.file) || d.name.starts_with("test"))
+        .collect();
+    let mut callees = vec![Vec::new(); graph.defs.len()];
+    for &(a, b) in &graph.edges {
+        callees[a].push(b);
+    }
+    let mut reached = tests.clone();
+    let mut stack: Vec<usize> = (0..tests.len()).filter(|&i| tests[i]).collect();
+    while let Some(i) = stack.pop() {
+        for &j in &callees[i] {
+            if !reached[j] {
+                reached[j] = true;
+                stack.push(j);
+            }
+        }
+    }
+
+    let (mut covered, mut total) = (0, 0);
+    for (i, d) in graph.defs.iter().enumerate() {
+        if d.kind == DefKind::Function && !tests[i] {
+            total += 1;
+            covered += usize::from(reached[i]);
+        }
+    }
+    (total > 0).then(|| covered * 100 / total)
+}
+
+/// Files under a test directory, and `test_*`, `*_test` and `conftest` modules.
+fn is_test_file(path: &str) -> bool {
+    let parts: Vec<&str> = path.split('/').collect();
+    let (name, dirs) = parts.split_last().unwrap_or((&"", &[]));
+    if dirs.iter().any(|d| TEST_DIRS.contains(d)) {
+        return true;
+    }
+    let stem = name.rsplit_once('.').map_or(*name, |(s, _)| s);
+    stem.starts_with("test_") || stem.ends_with("_test") || stem == "conftest"
+}
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::tree_sitter::analyze;
+    use crate::api::tree_sitter::py::Python;
+
+    // ── cycles ──
+
+    #[test]
+    fn no_cycles_is_green() {
+        let files = [analyze(
+            &Python,
+            "a.py",
+            "def f():\n    g()\n\ndef g(): pass\n",
+        )];
+        let b = badge(&files, Metric::Cycles);
+        assert_eq!(
+            (b.label, b.message.as_str(), b.color),
+            ("cycles", "0", "brightgreen")
+        );
+    }
+
+    #[test]
+    fn cycle_counted() {
+        let files = [
+            analyze(&Python, "a.py", "def f():\n    g()\n"),
+            analyze(&Python, "b.py", "def g():\n    f()\n"),
+        ];
+        let b = badge(&files, Metric::Cycles);
+        assert_eq!((b.message.as_str(), b.color), ("1", "yellow"));
+    }
+
+    // ── complexity ──
+
+    #[test]
+    fn mean_calls_per_function() {
+        let files = [analyze(
+            &Python,
+            "a.py",
+            "def f():\n    a()\n    b()\n    c()\n\ndef g():\n    def inner():\n        d()\n",
+        )];
+        // f: 3 calls, g: 0 (inner's call is its own), inner: 1.
+        let b = badge(&files, Metric::Complexity);
+        assert_eq!(
+            (b.message.as_str(), b.color),
+            ("1.3 calls/fn", "brightgreen")
+        );
+    }
+
+    #[test]
+    fn no_functions_is_grey() {
+        let files = [analyze(&Python, "a.py", "x = 1\n")];
+        let b = badge(&files, Metric::Complexity);
+        assert_eq!((b.message.as_str(), b.color), ("n/a", "lightgrey"));
+    }
+
+    // ── coverage ──
+
+    #[test]
+    fn reachable_from_tests() {
+        let files = [
+            analyze(
+                &Python,
+                "pkg/core.py",
+                "def used():\n    helper()\n\ndef helper(): pass\n\ndef unused(): pass\n",
+            ),
+            analyze(&Python, "tests/test_core.py", "def check():\n    used()\n"),
+        ];
+        // used and helper reached, unused not: 2 of 3.
+        let b = badge(&files, Metric::Coverage);
+        assert_eq!(
+            (b.label, b.message.as_str(), b.color),
+            ("coverage", "66%", "yellowgreen")
+        );
+    }
+
+    #[test]
+    fn test_named_functions_count_as_tests() {
+        let files = [analyze(
+            &Python,
+            "a.py",
+            "def f(): pass\n\ndef test_f():\n    f()\n",
+        )];
+        assert_eq!(badge(&files, Metric::Coverage).message, "100%");
+    }
+
+    #[test]
+    fn test_files() {
+        assert!(is_test_file("tests/it.rs"));
+        assert!(is_test_file("pkg/test_io.py"));
+        assert!(is_test_file("conftest.py"));
+        assert!(is_test_file("src/parse_test.go"));
+        assert!(!is_test_file("src/testing.py"));
+    }
+
+    // ── JSON ──
+
+    #[test]
+    fn shields_endpoint_shape() {
+        let b = badge(&[], Metric::Cycles);
+        let json = serde_json::to_value(&b).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "schemaVersion": 1,
+                "label": "cycles",
+                "message": "0",
+                "color": "brightgreen"
+            })
+        );
+    }
+}
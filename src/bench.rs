@@ -0,0 +1,379 @@
+use crate::analysis::Analysis;
+use crate::api::fs;
+use crate::api::tree_sitter::{Lang, Options, Syntax};
+use crate::clean;
+use crate::error::BoloError;
+use crate::survey::human;
+use rayon::prelude::*;
+use serde::Serialize;
+use std::fmt::Write;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Files per generated package directory.
+const PER_DIR: usize = 50;
+
+/// Shape of a synthetic tree for `bolo bench`.
+#[derive(Debug, Clone, Copy)]
+pub struct Corpus {
+    pub files: usize,
+    /// Top-level functions per file; each file also gets one type with a method.
+    pub functions: usize,
+    pub seed: u64,
+}
+
+/// Wall time of one pipeline phase.
+#[derive(Debug, Clone, Serialize)]
+pub struct Phase {
+    pub name: &'static str,
+    pub seconds: f64,
+}
+
+/// Timings for one benchmark run.
+#[derive(Debug, Clone, Serialize)]
+pub struct Report {
+    pub files: usize,
+    pub bytes: u64,
+    pub threads: usize,
+    /// `generate`, then the pipeline in order: walk, read, parse, clean (including
+    /// detaching nodes from the source), serialize.
+    pub phases: Vec<Phase>,
+}
+
+impl Report {
+    /// Pipeline time, excluding corpus generation.
+    pub fn total(&self) -> Duration {
+        let secs = self.phases.iter().filter(|p| p.name != "generate");
+        Duration::from_secs_f64(secs.map(|p| p.seconds).sum())
+    }
+
+    /// Human-readable table for `ext` files.
+    pub fn render(&self, ext: &str) -> String {
+        let mut out = format!(
+            "{} .{ext} files ({}) on {} thread{}\n\n",
+            self.files,
+            human(self.bytes),
+            self.threads,
+            if self.threads == 1 { "" } else { "s" }
+        );
+        let rate = |secs: f64| match secs {
+            0.0 => String::new(),
+            s => format!("{:>9}/s", human((self.bytes as f64 / s) as u64)),
+        };
+        for p in &self.phases {
+            let _ = writeln!(
+                out,
+                "  {:<10} {:>8.3}s  {}",
+                p.name,
+                p.seconds,
+                rate(p.seconds)
+            );
+        }
+        let total = self.total().as_secs_f64();
+        let _ = write!(out, "  {:<10} {total:>8.3}s  {}", "total", rate(total));
+        out
+    }
+}
+
+/// Write a synthetic tree of `corpus` into `dir`. Returns the bytes written.
+///
+/// Output depends only on `corpus`, so the same arguments give the same tree.
+pub fn generate(dir: &Path, ext: &str, corpus: &Corpus) -> Result<u64, BoloError> {
+    let mut rng = Rng(corpus.seed);
+    let mut bytes = 0;
+    for i in 0..corpus.files {
+        let path = dir.join(format!("pkg_{}/m_{i}.{ext}", i / PER_DIR));
+        let source = match ext {
+            "rs" => rust_file(i, corpus, &mut rng),
+            // Plain JavaScript is valid TypeScript.
+            "js" | "ts" => js_file(i, corpus, &mut rng),
+            "go" => go_file(i, corpus, &mut rng),
+            _ => python_file(i, corpus, &mut rng),
+        };
+        fs::write_file(&path, &source, true)?;
+        bytes += source.len() as u64;
+    }
+    Ok(bytes)
+}
+
+/// Run walk → read → parse → clean → serialize over `root`, timing each phase
+/// on the current rayon pool.
+pub fn run(root: &Path, ext: &str, lang: &(dyn Lang + Sync)) -> Result<Report, BoloError> {
+    let opts = Options::default();
+    let mut phases = Vec::new();
+    let mut timed = |name, start: Instant| {
+        phases.push(Phase {
+            name,
+            seconds: start.elapsed().as_secs_f64(),
+        })
+    };
+
+    let start = Instant::now();
+    let files = fs::walk_dir(root, ext, true)?;
+    timed("walk", start);
+
+    let start = Instant::now();
+    let sources = files
+        .par_iter()
+        .map(|f| f.read_lossy().map(|(text, _)| text))
+        .collect::<Result<Vec<_>, _>>()?;
+    timed("read", start);
+
+    let start = Instant::now();
+    let asts = files
+        .par_iter()
+        .zip(&sources)
+        .map(|(file, source)| {
+            let mut parser = lang.get_parser();
+            lang.parse(&mut parser, source, &opts)
+                .map_err(|e| BoloError::Parse {
+                    file: fs::portable(&file.rel_path, None),
+                    reason: e.to_string(),
+                })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    timed("parse", start);
+
+    let start = Instant::now();
+    let cleaned = files
+        .par_iter()
+        .zip(&sources)
+        .zip(asts)
+        .map(|((file, source), ast)| {
+            let path = fs::portable(&file.rel_path, None);
+            let nodes = clean::clean(&path, source, ast, opts.comments);
+            nodes.into_iter().map(Syntax::into_owned).collect()
+        })
+        .collect();
+    timed("clean", start);
+
+    let start = Instant::now();
+    let analysis = Analysis {
+        files: cleaned,
+        ..Default::default()
+    };
+    serde_json::to_string(&analysis)?;
+    timed("serialize", start);
+
+    Ok(Report {
+        files: files.len(),
+        bytes: sources.iter().map(|s| s.len() as u64).sum(),
+        threads: rayon::current_num_threads(),
+        phases,
+    })
+}
+
+// ── Generators ──────────────────────────────────────────────────────
+
+/// splitmix64; enough to vary names and call targets reproducibly.
+struct Rng(u64);
+
+impl Rng {
+    fn below(&mut self, n: usize) -> usize {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        ((z ^ (z >> 31)) % n.max(1) as u64) as usize
+    }
+}
+
+fn python_file(i: usize, c: &Corpus, rng: &mut Rng) -> String {
+    let mut out = format!("\"\"\"Synthetic module {i}.\"\"\"\nimport os\n\n\n");
+    let _ = write!(
+        out,
+        "class Item{i}:\n    def __init__(self, v):\n        self.v = v\n\n    \
+         def get(self):\n        return f_{i}_0(self.v, 1)\n\n\n"
+    );
+    for j in 0..c.functions {
+        let (t, u) = (rng.below(c.files), rng.below(c.functions));
+        let _ = write!(
+            out,
+            "def f_{i}_{j}(a, b):\n    \"\"\"Synthetic function {j}.\"\"\"\n    \
+             # pick a neighbour\n    x = f_{t}_{u}(a, b)\n    if a > b:\n        \
+             return os.path.join(str(x), \"data\")\n    \
+             return [x + k for k in range(b)]\n\n\n"
+        );
+    }
+    out
+}
+
+fn rust_file(i: usize, c: &Corpus, rng: &mut Rng) -> String {
+    let mut out = format!("//! Synthetic module {i}.\n\n");
+    let _ = write!(
+        out,
+        "pub struct Item{i} {{\n    v: u32,\n}}\n\nimpl Item{i} {{\n    \
+         pub fn get(&self) -> u32 {{\n        f_{i}_0(self.v, 1)\n    }}\n}}\n\n"
+    );
+    for j in 0..c.functions {
+        let (t, u) = (rng.below(c.files), rng.below(c.functions));
+        let _ = write!(
+            out,
+            "/// Synthetic function {j}.\npub fn f_{i}_{j}(a: u32, b: u32) -> u32 {{\n    \
+             // pick a neighbour\n    let x = crate::m_{t}::f_{t}_{u}(a, b);\n    \
+             if a > b {{\n        return x.wrapping_add(1);\n    }}\n    \
+             (0..b).map(|k| k + x).sum()\n}}\n\n"
+        );
+    }
+    out
+}
+
+fn js_file(i: usize, c: &Corpus, rng: &mut Rng) -> String {
+    let mut out = format!("// Synthetic module {i}.\nconst path = require(\"path\");\n\n");
+    let _ = write!(
+        out,
+        "export class Item{i} {{\n  constructor(v) {{\n    this.v = v;\n  }}\n\n  \
+         get() {{\n    return f_{i}_0(this.v, 1);\n  }}\n}}\n\n"
+    );
+    for j in 0..c.functions {
+        let (t, u) = (rng.below(c.files), rng.below(c.functions));
+        let _ = write!(
+            out,
+            "/** Synthetic function {j}. */\nexport function f_{i}_{j}(a, b) {{\n  \
+             // pick a neighbour\n  const x = f_{t}_{u}(a, b);\n  if (a > b) {{\n    \
+             return path.join(String(x), \"data\");\n  }}\n  \
+             return Array.from({{ length: b }}, (_, k) => x + k);\n}}\n\n"
+        );
+    }
+    out
+}
+
+fn go_file(i: usize, c: &Corpus, rng: &mut Rng) -> String {
+    let mut out = format!(
+        "// Synthetic module {i}.\npackage pkg_{}\n\nimport \"path\"\n\n",
+        i / PER_DIR
+    );
+    let _ = write!(
+        out,
+        "type Item{i} struct {{\n\tV int\n}}\n\n\
+         func (it *Item{i}) Get() int {{\n\treturn f_{i}_0(it.V, 1)\n}}\n\n"
+    );
+    for j in 0..c.functions {
+        let (t, u) = (rng.below(c.files), rng.below(c.functions));
+        let _ = write!(
+            out,
+            "// f_{i}_{j} is synthetic function {j}.\nfunc f_{i}_{j}(a, b int) int {{\n\t\
+             // pick a neighbour\n\tx := f_{t}_{u}(a, b)\n\tif a > b {{\n\t\t\
+             _ = path.Join(\"data\", \"x\")\n\t\treturn x\n\t}}\n\t\
+             for k := 0; k < b; k++ {{\n\t\tx += k\n\t}}\n\treturn x\n}}\n\n"
+        );
+    }
+    out
+}
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::tree_sitter::go::Go;
+    use crate::api::tree_sitter::js::JavaScript;
+    use crate::api::tree_sitter::py::Python;
+    use crate::api::tree_sitter::rs::Rust;
+    use crate::api::tree_sitter::ts::TypeScript;
+    use tempfile::TempDir;
+
+    const SMALL: Corpus = Corpus {
+        files: 120,
+        functions: 3,
+        seed: 7,
+    };
+
+    #[test]
+    fn generate_spreads_files_over_packages() {
+        let dir = TempDir::new().unwrap();
+        let bytes = generate(dir.path(), "py", &SMALL).unwrap();
+        assert!(bytes > 0);
+        assert!(dir.path().join("pkg_0/m_0.py").is_file());
+        assert!(dir.path().join("pkg_2/m_119.py").is_file());
+        assert_eq!(fs::walk_dir(dir.path(), "py", true).unwrap().len(), 120);
+    }
+
+    #[test]
+    fn generate_is_deterministic() {
+        let (a, b) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+        generate(a.path(), "rs", &SMALL).unwrap();
+        generate(b.path(), "rs", &SMALL).unwrap();
+        let read = |d: &TempDir| std::fs::read_to_string(d.path().join("pkg_1/m_77.rs")).unwrap();
+        assert_eq!(read(&a), read(&b));
+    }
+
+    #[test]
+    fn generated_python_parses_cleanly() {
+        let mut parser = Python.get_parser();
+        let src = python_file(0, &SMALL, &mut Rng(0));
+        let (_, errors) = Python
+            .parse_checked(&mut parser, &src, &Options::default())
+            .unwrap();
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn generated_rust_parses_cleanly() {
+        let mut parser = Rust.get_parser();
+        let src = rust_file(0, &SMALL, &mut Rng(0));
+        let (_, errors) = Rust
+            .parse_checked(&mut parser, &src, &Options::default())
+            .unwrap();
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn generated_javascript_parses_cleanly() {
+        let mut parser = JavaScript.get_parser();
+        let src = js_file(0, &SMALL, &mut Rng(0));
+        let (_, errors) = JavaScript
+            .parse_checked(&mut parser, &src, &Options::default())
+            .unwrap();
+        assert!(errors.is_empty());
+        let mut parser = TypeScript.get_parser();
+        let (_, errors) = TypeScript
+            .parse_checked(&mut parser, &src, &Options::default())
+            .unwrap();
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn generated_go_parses_cleanly() {
+        let mut parser = Go.get_parser();
+        let src = go_file(0, &SMALL, &mut Rng(0));
+        let (_, errors) = Go
+            .parse_checked(&mut parser, &src, &Options::default())
+            .unwrap();
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn run_times_every_phase() {
+        let dir = TempDir::new().unwrap();
+        let bytes = generate(dir.path(), "py", &SMALL).unwrap();
+        let report = run(dir.path(), "py", &Python).unwrap();
+        assert_eq!(report.files, 120);
+        assert_eq!(report.bytes, bytes);
+        let names: Vec<_> = report.phases.iter().map(|p| p.name).collect();
+        assert_eq!(names, vec!["walk", "read", "parse", "clean", "serialize"]);
+    }
+
+    #[test]
+    fn render_lists_phases_and_total() {
+        let report = Report {
+            files: 2,
+            bytes: 2048,
+            threads: 1,
+            phases: vec![
+                Phase {
+                    name: "generate",
+                    seconds: 1.0,
+                },
+                Phase {
+                    name: "parse",
+                    seconds: 0.5,
+                },
+            ],
+        };
+        let text = report.render("py");
+        assert!(text.starts_with("2 .py files (2.0 KB) on 1 thread\n"));
+        assert!(text.contains("parse         0.500s"));
+        assert!(text.contains("total         0.500s"));
+    }
+}
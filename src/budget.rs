@@ -0,0 +1,185 @@
+use crate::analysis::{Analysis, Dropped, Truncated};
+use crate::api::tree_sitter::{ASTNode, Syntax};
+use crate::error::BoloError;
+
+/// Render `analysis` with `render`, dropping detail until the result is at
+/// most `max_bytes`: string literals, then calls, then metrics and sibling
+/// indices, and finally whole files from the end. What went is recorded in
+/// the rendered envelope's `truncated` and returned with it.
+///
+/// If even an analysis with no files doesn't fit, that is what's returned.
+pub fn fit(
+    analysis: &Analysis,
+    max_bytes: usize,
+    render: impl Fn(&Analysis) -> Result<String, BoloError>,
+) -> Result<(String, Option<Truncated>), BoloError> {
+    let out = render(analysis)?;
+    if out.len() <= max_bytes {
+        return Ok((out, None));
+    }
+
+    let mut a = analysis.clone();
+    let mut note = Truncated {
+        max_bytes,
+        dropped: Vec::new(),
+        files_omitted: 0,
+    };
+    for step in [Dropped::Literals, Dropped::Calls, Dropped::Metrics] {
+        for nodes in &mut a.files {
+            drop_detail(nodes, step);
+        }
+        note.dropped.push(step);
+        a.truncated = Some(note.clone());
+        let out = render(&a)?;
+        if out.len() <= max_bytes {
+            return Ok((out, Some(note)));
+        }
+    }
+
+    // The most leading files that fit, by bisection.
+    note.dropped.push(Dropped::Files);
+    let files = std::mem::take(&mut a.files);
+    let attempt = |keep: usize, a: &mut Analysis| {
+        a.files = files[..keep].to_vec();
+        a.truncated = Some(Truncated {
+            files_omitted: files.len() - keep,
+            ..note.clone()
+        });
+        render(a)
+    };
+    let (mut lo, mut hi) = (0, files.len());
+    while lo < hi {
+        let mid = (lo + hi).div_ceil(2);
+        if attempt(mid, &mut a)?.len() <= max_bytes {
+            lo = mid;
+        } else {
+            hi = mid - 1;
+        }
+    }
+    let out = attempt(lo, &mut a)?;
+    Ok((out, a.truncated))
+}
+
+fn drop_detail(nodes: &mut Vec<Syntax>, step: Dropped) {
+    nodes.retain(|s| match step {
+        Dropped::Literals => !matches!(s.node, ASTNode::Literal(_)),
+        Dropped::Calls => !matches!(s.node, ASTNode::Call(_)),
+        Dropped::Metrics | Dropped::Files => true,
+    });
+    for s in nodes {
+        if step == Dropped::Metrics {
+            s.metadata.metrics.clear();
+            s.metadata.index = None;
+        }
+        drop_detail(&mut s.contains, step);
+    }
+}
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::tree_sitter::py::Python;
+    use crate::api::tree_sitter::{Options, Strings, analyze_with};
+
+    fn analyze(path: &str, source: &str) -> Vec<Syntax<'static>> {
+        let opts = Options {
+            strings: Some(Strings::All),
+            ..Default::default()
+        };
+        analyze_with(&Python, path, source, &opts)
+    }
+
+    fn sample() -> Analysis {
+        let source = "def f():\n    g('a long literal value')\n    h()\n";
+        Analysis {
+            files: (0..4)
+                .map(|i| analyze(&format!("m{i}.py"), source))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn json(a: &Analysis) -> Result<String, BoloError> {
+        Ok(serde_json::to_string(a)?)
+    }
+
+    fn len(a: &Analysis) -> usize {
+        json(a).unwrap().len()
+    }
+
+    #[test]
+    fn under_budget_untouched() {
+        let a = sample();
+        let (out, note) = fit(&a, len(&a), json).unwrap();
+        assert_eq!(out, json(&a).unwrap());
+        assert!(note.is_none());
+    }
+
+    #[test]
+    fn literals_go_first() {
+        let a = sample();
+        let (out, note) = fit(&a, len(&a) - 1, json).unwrap();
+        let note = note.unwrap();
+        assert_eq!(note.dropped, vec![Dropped::Literals]);
+        assert!(out.len() < len(&a));
+        assert!(!out.contains("a long literal value"));
+        assert!(out.contains("\"Call\""));
+        assert!(out.contains("\"truncated\""));
+    }
+
+    #[test]
+    fn then_calls_then_metrics() {
+        let a = sample();
+        let mut no_calls = a.clone();
+        for nodes in &mut no_calls.files {
+            drop_detail(nodes, Dropped::Literals);
+            drop_detail(nodes, Dropped::Calls);
+        }
+        let (out, note) = fit(&a, len(&no_calls) + 120, json).unwrap();
+        assert_eq!(
+            note.unwrap().dropped,
+            vec![Dropped::Literals, Dropped::Calls]
+        );
+        assert!(!out.contains("\"Call\""));
+        assert!(out.contains("\"index\""));
+
+        let mut bare = no_calls.clone();
+        for nodes in &mut bare.files {
+            drop_detail(nodes, Dropped::Metrics);
+        }
+        bare.truncated = Some(Truncated {
+            max_bytes: len(&no_calls) - 1,
+            dropped: vec![Dropped::Literals, Dropped::Calls, Dropped::Metrics],
+            files_omitted: 0,
+        });
+        let (out, note) = fit(&a, len(&bare), json).unwrap();
+        assert_eq!(note.unwrap().dropped.last(), Some(&Dropped::Metrics));
+        assert!(!out.contains("\"index\""));
+    }
+
+    #[test]
+    fn files_cut_from_the_end() {
+        let a = sample();
+        let mut one = a.clone();
+        one.files.truncate(1);
+        let (out, note) = fit(&a, len(&one) + 120, json).unwrap();
+        let note = note.unwrap();
+        assert_eq!(note.dropped.last(), Some(&Dropped::Files));
+        assert!(out.len() <= len(&one) + 120);
+        assert!(out.contains("m0.py"));
+        assert!(!out.contains("m3.py"));
+        assert!(note.files_omitted >= 2);
+        let back: Analysis = serde_json::from_str(&out).unwrap();
+        assert_eq!(back.files.len() + note.files_omitted, 4);
+        assert_eq!(back.truncated, Some(note));
+    }
+
+    #[test]
+    fn impossible_budget_keeps_no_files() {
+        let (out, note) = fit(&sample(), 1, json).unwrap();
+        assert_eq!(note.unwrap().files_omitted, 4);
+        assert!(out.contains("\"files\":[]"));
+    }
+}
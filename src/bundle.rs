@@ -0,0 +1,215 @@
+use crate::analysis::Analysis;
+use crate::error::BoloError;
+use crate::export::{self, Format, Layout, html, viewer};
+use crate::labels::Labels;
+use crate::merge;
+use crate::serve::Response;
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::path::Path;
+
+/// Bumped when the bundle layout changes; older bundles are refused.
+pub const SCHEMA: u32 = 1;
+
+/// An analysis packed with its viewer into one gzipped JSON file, to hand to
+/// someone who only needs `bolo open`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bundle {
+    pub schema: u32,
+    /// The bolo version that wrote the bundle.
+    pub version: String,
+    pub analysis: Analysis,
+    /// The `.html` rendering of `analysis`.
+    pub viewer: String,
+}
+
+impl Bundle {
+    pub fn new(analysis: &Analysis, labels: &Labels) -> Self {
+        Bundle {
+            schema: SCHEMA,
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            viewer: html::render(&analysis.files, labels),
+            analysis: analysis.clone(),
+        }
+    }
+
+    /// Write the bundle to `path`, gzip-compressed.
+    pub fn write(&self, path: &Path) -> Result<(), BoloError> {
+        let err = |e: std::io::Error| BoloError::Write {
+            path: path.to_path_buf(),
+            reason: e.to_string(),
+        };
+        let file = std::fs::File::create(path).map_err(err)?;
+        let mut gz = GzEncoder::new(file, Compression::default());
+        serde_json::to_writer(&mut gz, self)?;
+        gz.finish().map_err(err)?.flush().map_err(err)
+    }
+
+    /// Read a bundle written by [`Bundle::write`], refusing other schemas.
+    pub fn read(path: &Path) -> Result<Self, BoloError> {
+        let file = std::fs::File::open(path).map_err(|e| BoloError::Read {
+            path: path.to_path_buf(),
+            reason: e.to_string(),
+        })?;
+        let mut text = String::new();
+        GzDecoder::new(file)
+            .read_to_string(&mut text)
+            .map_err(|e| BoloError::Parse {
+                file: path.display().to_string(),
+                reason: format!("not a bolo bundle: {e}"),
+            })?;
+        let parse_err = |e: serde_json::Error| BoloError::Parse {
+            file: path.display().to_string(),
+            reason: e.to_string(),
+        };
+        // The schema is checked first: a newer analysis may not parse at all.
+        #[derive(Deserialize)]
+        struct Header {
+            schema: u32,
+            version: String,
+        }
+        let header: Header = serde_json::from_str(&text).map_err(parse_err)?;
+        if header.schema != SCHEMA {
+            return Err(BoloError::Parse {
+                file: path.display().to_string(),
+                reason: format!(
+                    "bundle schema {} from bolo {}; this bolo reads schema {SCHEMA}",
+                    header.schema, header.version
+                ),
+            });
+        }
+        serde_json::from_str(&text).map_err(parse_err)
+    }
+
+    /// Read `path` as a bundle if it is gzipped, else as analysis JSON
+    /// (see [`merge::load`]), packing that with a fresh viewer.
+    pub fn open(path: &Path) -> Result<Self, BoloError> {
+        let mut magic = [0; 2];
+        let gzipped = std::fs::File::open(path)
+            .and_then(|mut f| f.read_exact(&mut magic))
+            .is_ok_and(|()| magic == [0x1f, 0x8b]);
+        if gzipped {
+            return Bundle::read(path);
+        }
+        Ok(Bundle::new(&merge::load(path)?, &Labels::default()))
+    }
+
+    /// Answer `bolo open` requests: the interactive graph at `/`, the file
+    /// listing at `/files.html`, and the graph and analysis as JSON at
+    /// `/graph.json` and `/analysis.json`.
+    pub fn handle(&self, method: &str, target: &str) -> Response {
+        let path = target.split_once('?').map_or(target, |(p, _)| p);
+        let graph = || {
+            export::render(
+                Format::Graph,
+                &self.analysis,
+                Layout::Compact,
+                &Labels::default(),
+            )
+        };
+        match (method, path) {
+            ("GET", "/" | "/index.html") => match graph() {
+                Ok(json) => Response::html(viewer::render(&json)),
+                Err(e) => Response::error(500, &e.to_string()),
+            },
+            ("GET", "/files.html") => Response::html(self.viewer.clone()),
+            ("GET", "/graph.json") => match graph() {
+                Ok(json) => Response::ok(json),
+                Err(e) => Response::error(500, &e.to_string()),
+            },
+            ("GET", "/analysis.json") => match serde_json::to_string(&self.analysis) {
+                Ok(json) => Response::ok(json),
+                Err(e) => Response::error(500, &e.to_string()),
+            },
+            (_, "/" | "/index.html" | "/files.html" | "/graph.json" | "/analysis.json") => {
+                Response::error(405, "method not allowed")
+            }
+            _ => Response::error(404, &format!("no route `{path}`")),
+        }
+    }
+}
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::tree_sitter::analyze;
+    use crate::api::tree_sitter::py::Python;
+    use tempfile::TempDir;
+
+    fn analysis() -> Analysis {
+        let source = "def f():\n    g()\n";
+        let nodes = analyze(&Python, "a.py", source);
+        Analysis {
+            files: vec![nodes],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn write_then_read_round_trips() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("out.bolo");
+        Bundle::new(&analysis(), &Labels::default())
+            .write(&path)
+            .unwrap();
+        let bundle = Bundle::read(&path).unwrap();
+        assert_eq!(bundle.schema, SCHEMA);
+        assert_eq!(bundle.analysis.files.len(), 1);
+        assert!(bundle.viewer.contains("a.py"));
+    }
+
+    #[test]
+    fn other_schemas_and_plain_files_refused() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("out.bolo");
+        let mut bundle = Bundle::new(&analysis(), &Labels::default());
+        bundle.schema = SCHEMA + 1;
+        bundle.write(&path).unwrap();
+        let err = Bundle::read(&path).unwrap_err().to_string();
+        assert!(err.contains(&format!("schema {}", SCHEMA + 1)));
+
+        std::fs::write(&path, "{}").unwrap();
+        let err = Bundle::read(&path).unwrap_err().to_string();
+        assert!(err.contains("not a bolo bundle"));
+    }
+
+    #[test]
+    fn open_reads_bundles_and_analysis_json() {
+        let dir = TempDir::new().unwrap();
+        let bundled = dir.path().join("out.bolo");
+        Bundle::new(&analysis(), &Labels::default())
+            .write(&bundled)
+            .unwrap();
+        assert_eq!(Bundle::open(&bundled).unwrap().analysis.files.len(), 1);
+
+        let plain = dir.path().join("out.json");
+        std::fs::write(&plain, serde_json::to_string(&analysis()).unwrap()).unwrap();
+        let bundle = Bundle::open(&plain).unwrap();
+        assert_eq!(bundle.analysis.files.len(), 1);
+        assert!(bundle.viewer.contains("a.py"));
+
+        std::fs::write(&plain, "nope").unwrap();
+        assert!(Bundle::open(&plain).is_err());
+    }
+
+    #[test]
+    fn serves_viewer_and_analysis() {
+        let bundle = Bundle::new(&analysis(), &Labels::default());
+        let page = bundle.handle("GET", "/");
+        assert_eq!(page.content_type, "text/html; charset=utf-8");
+        assert!(page.body.contains("bolo graph"));
+        assert!(page.body.contains(r#""id":"a.py::f""#));
+        assert_eq!(bundle.handle("GET", "/files.html").body, bundle.viewer);
+        let graph = bundle.handle("GET", "/graph.json");
+        assert!(graph.body.contains(r#""edges":"#));
+        let json = bundle.handle("GET", "/analysis.json");
+        assert!(json.body.starts_with("{\"files\":"));
+        assert_eq!(bundle.handle("GET", "/nope").status, 404);
+        assert_eq!(bundle.handle("POST", "/graph.json").status, 405);
+    }
+}
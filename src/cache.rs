@@ -0,0 +1,198 @@
+use crate::analysis::Warning;
+use crate::api::tree_sitter::{Options, Syntax};
+use crate::error::BoloError;
+use crate::hash::siphash;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Part of every key, so older entries miss. Bump it whenever what an
+/// entry holds changes (the serialized nodes or warnings, or how they are
+/// extracted) or what [`Cache::key`] covers changes; a release that
+/// forgets would read entries in the old shape back as current.
+const FORMAT: u32 = 11;
+
+/// Entries neither read nor written for this long are removed when a cache
+/// is opened.
+const MAX_AGE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// Parsed per-file results stored on disk, keyed by content hash, before
+/// any pass of the pipeline has run.
+///
+/// A key covers the bolo version, the options that change extraction
+/// (`--routes`, `--strings`), the file's reported path and
+/// its contents, so any change to these misses and the file is parsed
+/// again. Reads and writes that fail are treated as misses: a broken cache
+/// slows a run down but never fails it.
+#[derive(Debug, Clone)]
+pub struct Cache {
+    dir: PathBuf,
+}
+
+impl Cache {
+    /// Open (creating) the cache at `dir` and remove stale entries.
+    pub fn open(dir: &Path) -> Result<Self, BoloError> {
+        std::fs::create_dir_all(dir).map_err(|e| BoloError::Write {
+            path: dir.to_path_buf(),
+            reason: e.to_string(),
+        })?;
+        let cache = Cache {
+            dir: dir.to_path_buf(),
+        };
+        cache.prune(SystemTime::now());
+        Ok(cache)
+    }
+
+    /// `$XDG_CACHE_HOME/bolo`, else `~/.cache/bolo`.
+    pub fn default_dir() -> Option<PathBuf> {
+        let var = |k| std::env::var_os(k).filter(|v| !v.is_empty());
+        var("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| var("HOME").map(|h| Path::new(&h).join(".cache")))
+            .map(|d| d.join("bolo"))
+    }
+
+    /// The key `source`, reported as `path`, is stored under with `opts`.
+    pub fn key(path: &str, source: &str, opts: &Options) -> String {
+        let header = format!(
+            "{}\0{FORMAT}\0{:?}\0{:?}\0{path}\0",
+            env!("CARGO_PKG_VERSION"),
+            opts.routes,
+            opts.strings,
+        );
+        let mut data = header.into_bytes();
+        data.extend_from_slice(source.as_bytes());
+        format!(
+            "{:016x}{:016x}",
+            siphash((0, 0), &data),
+            siphash((0, 1), &data)
+        )
+    }
+
+    /// The nodes and warnings stored under `key`, marking the entry used.
+    pub fn get(&self, key: &str) -> Option<(Vec<Syntax<'static>>, Vec<Warning>)> {
+        let path = self.entry(key);
+        let text = std::fs::read_to_string(&path).ok()?;
+        let entry = serde_json::from_str(&text).ok()?;
+        if let Ok(file) = std::fs::File::options().append(true).open(&path) {
+            file.set_modified(SystemTime::now()).ok();
+        }
+        Some(entry)
+    }
+
+    /// Store `nodes` and `warnings` under `key`. Entries are written to a
+    /// temporary file and renamed into place, so concurrent runs never read
+    /// half an entry.
+    pub fn put(&self, key: &str, nodes: &[Syntax], warnings: &[Warning]) {
+        let path = self.entry(key);
+        let Some(parent) = path.parent() else { return };
+        let write = || -> std::io::Result<()> {
+            std::fs::create_dir_all(parent)?;
+            let mut tmp = tempfile::NamedTempFile::new_in(parent)?;
+            serde_json::to_writer(&mut tmp, &(nodes, warnings))?;
+            tmp.flush()?;
+            tmp.persist(&path)?;
+            Ok(())
+        };
+        write().ok();
+    }
+
+    /// Entries fan out over 256 directories by their first two digits.
+    fn entry(&self, key: &str) -> PathBuf {
+        let (fan, rest) = key.split_at(2.min(key.len()));
+        self.dir.join(fan).join(format!("{rest}.json"))
+    }
+
+    fn prune(&self, now: SystemTime) {
+        let Ok(fans) = std::fs::read_dir(&self.dir) else {
+            return;
+        };
+        for fan in fans.flatten().map(|f| f.path()).filter(|p| p.is_dir()) {
+            let Ok(entries) = std::fs::read_dir(&fan) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let stale = entry
+                    .metadata()
+                    .and_then(|m| m.modified())
+                    .is_ok_and(|t| now.duration_since(t).unwrap_or_default() > MAX_AGE);
+                if stale {
+                    std::fs::remove_file(entry.path()).ok();
+                }
+            }
+        }
+    }
+}
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::WarningKind;
+    use crate::api::tree_sitter::py::Python;
+    use crate::api::tree_sitter::{ASTNode, analyze};
+    use tempfile::TempDir;
+
+    fn nodes(source: &str) -> Vec<Syntax<'static>> {
+        let lang = Python;
+        analyze(&lang, "a.py", source)
+    }
+
+    #[test]
+    fn key_changes_with_content_path_and_options() {
+        let opts = Options::default();
+        let key = Cache::key("a.py", "x = 1\n", &opts);
+        assert_eq!(key.len(), 32);
+        assert_eq!(key, Cache::key("a.py", "x = 1\n", &opts));
+        assert_ne!(key, Cache::key("a.py", "x = 2\n", &opts));
+        assert_ne!(key, Cache::key("b.py", "x = 1\n", &opts));
+        let routes = Options {
+            routes: true,
+            ..Options::default()
+        };
+        assert_ne!(key, Cache::key("a.py", "x = 1\n", &routes));
+    }
+
+    #[test]
+    fn put_then_get_round_trips() {
+        let dir = TempDir::new().unwrap();
+        let cache = Cache::open(dir.path()).unwrap();
+        let key = Cache::key("a.py", "def f():\n    g()\n", &Options::default());
+        assert!(cache.get(&key).is_none());
+
+        let warning = Warning::new(WarningKind::LossyDecode, "a.py", "replaced");
+        cache.put(
+            &key,
+            &nodes("def f():\n    g()\n"),
+            std::slice::from_ref(&warning),
+        );
+        let (cached, warnings) = cache.get(&key).unwrap();
+        assert!(matches!(&cached[0].node, ASTNode::File(f) if f.path == "a.py"));
+        assert!(matches!(&cached[1].node, ASTNode::Function(f) if f.name == "f"));
+        assert_eq!(warnings, [warning]);
+    }
+
+    #[test]
+    fn corrupt_entry_is_a_miss() {
+        let dir = TempDir::new().unwrap();
+        let cache = Cache::open(dir.path()).unwrap();
+        let key = Cache::key("a.py", "", &Options::default());
+        let path = cache.entry(&key);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, "{").unwrap();
+        assert!(cache.get(&key).is_none());
+    }
+
+    #[test]
+    fn stale_entries_pruned() {
+        let dir = TempDir::new().unwrap();
+        let cache = Cache::open(dir.path()).unwrap();
+        let key = Cache::key("a.py", "", &Options::default());
+        cache.put(&key, &nodes(""), &[]);
+        cache.prune(SystemTime::now() + MAX_AGE / 2);
+        assert!(cache.get(&key).is_some());
+        cache.prune(SystemTime::now() + MAX_AGE * 2);
+        assert!(cache.get(&key).is_none());
+    }
+}
@@ -0,0 +1,95 @@
+use crate::semver::{Delta, Report};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// Section order and headings; the ones a reader must act on come first.
+const SECTIONS: &[(Delta, &str)] = &[
+    (Delta::Removed, "Removed"),
+    (Delta::Changed, "Changed"),
+    (Delta::Added, "Added"),
+];
+
+/// Markdown changelog skeleton for the public API changes between `from`
+/// and `to`: one section per kind of change, symbols grouped by module.
+/// Each entry ends in a `TODO` for the maintainer to describe.
+pub fn render(from: &str, to: &str, report: &Report) -> String {
+    let mut out = format!(
+        "## {to} (since {from})\n\nSuggested bump: **{}**\n",
+        report.bump
+    );
+    if report.changes.is_empty() {
+        out.push_str("\nNo public API changes.\n");
+        return out;
+    }
+    for &(delta, heading) in SECTIONS {
+        let mut modules: BTreeMap<&str, Vec<(&str, &str)>> = BTreeMap::new();
+        for c in report.changes.iter().filter(|c| c.delta == delta) {
+            let (module, symbol) = c.id.split_once("::").unwrap_or(("", &c.id));
+            modules.entry(module).or_default().push((c.kind, symbol));
+        }
+        if modules.is_empty() {
+            continue;
+        }
+        let _ = write!(out, "\n### {heading}\n");
+        for (module, symbols) in modules {
+            let _ = write!(out, "\n#### `{module}`\n\n");
+            for (kind, symbol) in symbols {
+                let _ = writeln!(out, "- {kind} `{symbol}`: TODO");
+            }
+        }
+    }
+    out
+}
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::semver::{ApiChange, Bump};
+
+    fn change(id: &str, kind: &'static str, delta: Delta) -> ApiChange {
+        ApiChange {
+            id: id.to_string(),
+            kind,
+            delta,
+            bump: match delta {
+                Delta::Added => Bump::Minor,
+                _ => Bump::Major,
+            },
+        }
+    }
+
+    #[test]
+    fn no_changes() {
+        let report = Report {
+            bump: Bump::Patch,
+            changes: vec![],
+        };
+        assert_eq!(
+            render("v1.2", "HEAD", &report),
+            "## HEAD (since v1.2)\n\nSuggested bump: **patch**\n\nNo public API changes.\n"
+        );
+    }
+
+    #[test]
+    fn grouped_by_section_then_module() {
+        let report = Report {
+            bump: Bump::Major,
+            changes: vec![
+                change("src/lib.rs::old", "fn", Delta::Removed),
+                change("src/b.rs::S.new", "fn", Delta::Added),
+                change("src/a.rs::E", "type", Delta::Added),
+                change("src/a.rs::f", "fn", Delta::Added),
+            ],
+        };
+        let md = render("v1", "v2", &report);
+        assert_eq!(
+            md,
+            "## v2 (since v1)\n\nSuggested bump: **major**\n\
+             \n### Removed\n\n#### `src/lib.rs`\n\n- fn `old`: TODO\n\
+             \n### Added\n\n#### `src/a.rs`\n\n- type `E`: TODO\n- fn `f`: TODO\n\
+             \n#### `src/b.rs`\n\n- fn `S.new`: TODO\n"
+        );
+    }
+}
@@ -1,42 +1,269 @@
-use crate::api::tree_sitter::{ASTNode, File, Metadata, Syntax, metadata_from_span};
-use std::path::Path;
+use crate::api::tree_sitter::{ASTNode, File, Metadata, Span, Syntax, metadata_from_span};
+use crate::hash::siphash;
+use crate::license;
+use serde::Deserialize;
 
-/// Strip all comments (nested or otherwise) and hoist a merged Comment to the top.
+/// File metric: lines holding comment or docstring text.
+pub const COMMENT_LINES: &str = "comment_lines";
+/// File metric: lines holding code, trailing comments or not.
+pub const CODE_LINES: &str = "code_lines";
+/// File metric: public functions and types.
+pub const DEFINITIONS: &str = "definitions";
+/// File metric: public functions and types with a comment right above them
+/// (attributes and decorators between are fine) or a leading docstring.
+pub const DOCUMENTED: &str = "documented";
+/// Function and Type metric: a hash of the definition's source tokens with
+/// its own name taken out, so a renamed definition matches its old self
+/// however few calls it makes.
+pub const BODY_HASH: &str = "body_hash";
+/// Function and Type metric: how many tokens [`BODY_HASH`] covers.
+pub const BODY_TOKENS: &str = "body_tokens";
+
+/// What [`clean`] and the `strip-comments` pass do with Comment nodes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum CommentPolicy {
+    /// Strip all comments and hoist one merged Comment to the top.
+    #[default]
+    StripMerge,
+    /// Strip all comments.
+    StripDrop,
+    /// Leave comments where the parser put them.
+    KeepInPlace,
+    /// Move each comment into the `contains` of the node that follows it.
+    AttachToNext,
+}
+
+/// Prepend the File node and apply the comment `policy`: [`prepare`], then
+/// the `metrics` and `strip-comments` passes of the default
+/// [`Pipeline`](crate::pipeline::Pipeline).
 ///
-/// Returns: `[File(path), Comment(merged), ...stripped_nodes]`
-pub fn clean(path: &Path, source: &str, nodes: Vec<Syntax>) -> Vec<Syntax> {
-    let mut comment_meta = Metadata {
-        chars: 0,
-        lines: 0,
-        words: 0,
-        whitespaces: 0,
-        newlines: 0,
+/// Returns (strip-merge): `[File(path), Comment(merged), ...stripped_nodes]`
+pub fn clean<'s>(
+    path: &str,
+    source: &str,
+    nodes: Vec<Syntax<'s>>,
+    policy: CommentPolicy,
+) -> Vec<Syntax<'s>> {
+    let mut nodes = prepare(path, source, nodes);
+    measure(source, &mut nodes);
+    strip(&mut nodes, policy);
+    nodes
+}
+
+/// Number the parsed `nodes` and prepend their File node, which carries the
+/// license detected in the leading comment block. Comments stay where the
+/// parser put them.
+pub fn prepare<'s>(path: &str, source: &str, mut nodes: Vec<Syntax<'s>>) -> Vec<Syntax<'s>> {
+    let license = license::detect(&leading_comment(source, &nodes));
+    number(&mut nodes);
+    let mut file_meta = metadata_from_span(source.as_bytes(), 0, source.len());
+    let last_line = source.rsplit('\n').next().unwrap_or_default();
+    file_meta.span = Span {
+        start_line: 1,
+        end_line: file_meta.lines,
+        start_col: 1,
+        end_col: last_line.len() + 1,
+        start_byte: 0,
+        end_byte: source.len(),
     };
-    let stripped = strip_comments(nodes, &mut comment_meta);
-    let file_meta = metadata_from_span(source.as_bytes(), 0, source.len());
 
-    let mut out = Vec::with_capacity(stripped.len() + 2);
+    let mut out = Vec::with_capacity(nodes.len() + 2);
     out.push(Syntax {
         node: ASTNode::File(File {
-            path: path.display().to_string(),
+            path: path.to_string().into(),
+            license: license.map(Into::into),
+            module: None,
         }),
         metadata: file_meta,
         contains: vec![],
     });
+    out.extend(nodes);
+    out
+}
+
+/// Set the comment and documentation counts on the File node of
+/// [`prepare`]'s output, and [`BODY_HASH`] on its definitions; run it
+/// before comments are moved or dropped.
+pub fn measure(source: &str, nodes: &mut [Syntax]) {
+    let Some((file, rest)) = nodes.split_first_mut() else {
+        return;
+    };
+    for (key, count) in counts(source, rest) {
+        file.metadata.set_metric(key, count);
+    }
+    fingerprint(source, rest);
+}
 
-    if comment_meta.chars > 0 {
-        out.push(Syntax {
+/// Set [`BODY_HASH`] and [`BODY_TOKENS`] on every Function and Type,
+/// nested ones included.
+fn fingerprint(source: &str, nodes: &mut [Syntax]) {
+    for s in nodes {
+        let name = match &s.node {
+            ASTNode::Function(f) => Some(f.name.to_string()),
+            ASTNode::Type(t) => Some(t.name.to_string()),
+            _ => None,
+        };
+        let span = s.metadata.span;
+        if let (Some(name), Some(text)) = (name, source.get(span.start_byte..span.end_byte)) {
+            // `Display for Foo` names an impl by every word in it.
+            let own: Vec<&str> = tokens(&name).collect();
+            let kept: Vec<&str> = tokens(text).filter(|t| !own.contains(t)).collect();
+            let hash = siphash((0, 0), kept.join(" ").as_bytes());
+            s.metadata.set_metric(BODY_HASH, format!("{hash:016x}"));
+            s.metadata.set_metric(BODY_TOKENS, kept.len());
+        }
+        fingerprint(source, &mut s.contains);
+    }
+}
+
+/// Words (identifiers, keywords, numbers) and single punctuation
+/// characters of `text`, whitespace skipped.
+fn tokens(text: &str) -> impl Iterator<Item = &str> {
+    let word = |c: char| c.is_alphanumeric() || c == '_';
+    let mut rest = text;
+    std::iter::from_fn(move || {
+        rest = rest.trim_start();
+        let first = rest.chars().next()?;
+        let end = if word(first) {
+            rest.find(|c| !word(c)).unwrap_or(rest.len())
+        } else {
+            first.len_utf8()
+        };
+        let (token, tail) = rest.split_at(end);
+        rest = tail;
+        Some(token)
+    })
+}
+
+/// Apply the comment `policy` to the nodes after the File node of
+/// [`prepare`]'s output.
+pub fn strip(nodes: &mut Vec<Syntax>, policy: CommentPolicy) {
+    if nodes.is_empty() {
+        return;
+    }
+    let rest = nodes.split_off(1);
+    let mut comment_meta = Metadata {
+        chars: 0,
+        lines: 0,
+        words: 0,
+        whitespaces: 0,
+        newlines: 0,
+        span: Span::default(),
+        index: None,
+        metrics: Default::default(),
+        unknown: None,
+    };
+    let stripped = match policy {
+        CommentPolicy::StripMerge | CommentPolicy::StripDrop => {
+            strip_comments(rest, &mut comment_meta)
+        }
+        CommentPolicy::KeepInPlace => rest,
+        CommentPolicy::AttachToNext => attach_to_next(rest),
+    };
+    if policy == CommentPolicy::StripMerge && comment_meta.chars > 0 {
+        nodes.push(Syntax {
             node: ASTNode::Comment,
             metadata: comment_meta,
             contains: vec![],
         });
     }
+    nodes.extend(stripped);
+}
 
-    out.extend(stripped);
-    out
+/// Comment and documentation counts for the File node, taken before the
+/// comment policy moves or drops anything.
+fn counts(source: &str, nodes: &[Syntax]) -> [(&'static str, usize); 4] {
+    let mut in_comment = vec![false; source.len()];
+    mark_comments(nodes, &mut in_comment);
+    let (mut comment_lines, mut code_lines) = (0, 0);
+    let mut start = 0;
+    for line in source.split_inclusive('\n') {
+        let text = line.bytes().zip(&in_comment[start..]);
+        let (mut comment, mut code) = (false, false);
+        for (_, &c) in text.filter(|(b, _)| !b.is_ascii_whitespace()) {
+            match c {
+                true => comment = true,
+                false => code = true,
+            }
+        }
+        comment_lines += usize::from(comment);
+        code_lines += usize::from(code);
+        start += line.len();
+    }
+    let (mut definitions, mut documented) = (0, 0);
+    count_documented(source, nodes, &mut definitions, &mut documented);
+    [
+        (COMMENT_LINES, comment_lines),
+        (CODE_LINES, code_lines),
+        (DEFINITIONS, definitions),
+        (DOCUMENTED, documented),
+    ]
 }
 
-fn strip_comments(nodes: Vec<Syntax>, acc: &mut Metadata) -> Vec<Syntax> {
+fn mark_comments(nodes: &[Syntax], in_comment: &mut [bool]) {
+    for s in nodes {
+        if matches!(s.node, ASTNode::Comment) {
+            let span = s.metadata.span;
+            let end = span.end_byte.min(in_comment.len());
+            in_comment[span.start_byte.min(end)..end].fill(true);
+        }
+        mark_comments(&s.contains, in_comment);
+    }
+}
+
+fn count_documented(
+    source: &str,
+    nodes: &[Syntax],
+    definitions: &mut usize,
+    documented: &mut usize,
+) {
+    for (i, s) in nodes.iter().enumerate() {
+        let public = match &s.node {
+            ASTNode::Function(f) => f.public,
+            ASTNode::Type(t) => t.public,
+            _ => false,
+        };
+        if public {
+            *definitions += 1;
+            let above = i
+                .checked_sub(1)
+                .map(|j| &nodes[j])
+                .filter(|c| matches!(c.node, ASTNode::Comment))
+                .is_some_and(|c| directly_above(source, c.metadata.span, s.metadata.span));
+            let docstring = s
+                .contains
+                .iter()
+                .find(|c| !matches!(c.node, ASTNode::Attribute(_)))
+                .is_some_and(|c| matches!(c.node, ASTNode::Comment));
+            *documented += usize::from(above || docstring);
+        }
+        count_documented(source, &s.contains, definitions, documented);
+    }
+}
+
+/// Whether only attribute or decorator lines separate `comment` from `def`.
+fn directly_above(source: &str, comment: Span, def: Span) -> bool {
+    let Some(between) = source.get(comment.end_byte..def.start_byte) else {
+        return false;
+    };
+    // Line comments may end with their newline.
+    let between = match source[..comment.end_byte].ends_with('\n') {
+        true => &source[comment.end_byte - 1..def.start_byte],
+        false => between,
+    };
+    let mut lines: Vec<&str> = between.split('\n').collect();
+    if lines.len() < 2 || !lines[0].trim().is_empty() {
+        return false;
+    }
+    lines.pop();
+    lines[1..].iter().all(|l| {
+        let l = l.trim_start();
+        l.starts_with('@') || l.starts_with("#[")
+    })
+}
+
+fn strip_comments<'s>(nodes: Vec<Syntax<'s>>, acc: &mut Metadata) -> Vec<Syntax<'s>> {
     nodes
         .into_iter()
         .filter_map(|mut s| match &s.node {
@@ -46,6 +273,7 @@ fn strip_comments(nodes: Vec<Syntax>, acc: &mut Metadata) -> Vec<Syntax> {
                 acc.words += s.metadata.words;
                 acc.whitespaces += s.metadata.whitespaces;
                 acc.newlines += s.metadata.newlines;
+                acc.span = cover(acc.span, s.metadata.span);
                 None
             }
             _ => {
@@ -56,13 +284,82 @@ fn strip_comments(nodes: Vec<Syntax>, acc: &mut Metadata) -> Vec<Syntax> {
         .collect()
 }
 
+/// Record each node's position among its siblings.
+fn number(nodes: &mut [Syntax]) {
+    for (i, s) in nodes.iter_mut().enumerate() {
+        s.metadata.index = Some(i);
+        number(&mut s.contains);
+    }
+}
+
+/// Comments before a sibling become that sibling's first children; trailing
+/// comments with no following sibling stay where they are.
+fn attach_to_next<'s>(nodes: Vec<Syntax<'s>>) -> Vec<Syntax<'s>> {
+    let mut out = Vec::with_capacity(nodes.len());
+    let mut pending = Vec::new();
+    for mut s in nodes {
+        if matches!(s.node, ASTNode::Comment) {
+            pending.push(s);
+            continue;
+        }
+        let children = attach_to_next(std::mem::take(&mut s.contains));
+        s.contains = std::mem::take(&mut pending);
+        s.contains.extend(children);
+        out.push(s);
+    }
+    out.extend(pending);
+    out
+}
+
+/// Source lines of the top-level comments that precede the first other node.
+fn leading_comment(source: &str, nodes: &[Syntax]) -> String {
+    let span = nodes
+        .iter()
+        .take_while(|s| matches!(s.node, ASTNode::Comment))
+        .fold(Span::default(), |acc, s| cover(acc, s.metadata.span));
+    if span == Span::default() {
+        return String::new();
+    }
+    source
+        .lines()
+        .skip(span.start_line - 1)
+        .take(span.end_line + 1 - span.start_line)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Smallest span covering both; an empty span is ignored.
+fn cover(a: Span, b: Span) -> Span {
+    if a == Span::default() {
+        return b;
+    }
+    if b == Span::default() {
+        return a;
+    }
+    let first = match (a.start_line, a.start_col) <= (b.start_line, b.start_col) {
+        true => a,
+        false => b,
+    };
+    let end = match (a.end_line, a.end_col) >= (b.end_line, b.end_col) {
+        true => a,
+        false => b,
+    };
+    Span {
+        start_line: first.start_line,
+        end_line: end.end_line,
+        start_col: first.start_col,
+        end_col: end.end_col,
+        start_byte: first.start_byte,
+        end_byte: end.end_byte,
+    }
+}
+
 // ── Tests ──────────────────────────────────────────────────────────
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::api::tree_sitter::{Call, Function, Type};
-    use std::path::Path;
 
     fn meta(chars: usize, words: usize) -> Metadata {
         Metadata {
@@ -71,6 +368,17 @@ mod tests {
             words,
             whitespaces: 0,
             newlines: 0,
+            span: Span::default(),
+            index: None,
+            metrics: Default::default(),
+            unknown: None,
+        }
+    }
+
+    fn meta_at(start_line: usize, end_line: usize) -> Metadata {
+        Metadata {
+            span: Span::lines(start_line, end_line),
+            ..meta(5, 1)
         }
     }
 
@@ -80,8 +388,33 @@ mod tests {
             .map(|s| match &s.node {
                 ASTNode::Function(f) => format!("fn:{}", f.name),
                 ASTNode::Type(t) => format!("ty:{}", t.name),
+                ASTNode::Field(f) => format!("field:{}", f.name),
+                ASTNode::Variant(v) => format!("variant:{}", v.name),
                 ASTNode::Call(c) => format!("call:{}", c.name),
+                ASTNode::UsesType(u) => format!("uses:{}", u.name),
+                ASTNode::Module(m) => format!("mod:{}", m.name),
+                ASTNode::Import(i) => {
+                    let mut name = format!("import:{}", i.module);
+                    if !i.items.is_empty() {
+                        name += &format!(":{}", i.items.join(","));
+                    }
+                    if let Some(alias) = &i.alias {
+                        name += &format!(" as {alias}");
+                    }
+                    name
+                }
+                ASTNode::DynamicImport(d) => {
+                    format!("dyn:{}", d.module.as_deref().unwrap_or("?"))
+                }
+                ASTNode::Endpoint(e) => format!("endpoint:{}", e.route),
+                ASTNode::Fixture(f) => format!("fixture:{}", f.name),
+                ASTNode::UsesFixture(u) => format!("needs:{}", u.name),
+                ASTNode::Unsafe(u) => format!("unsafe:{:?}", u.kind),
+                ASTNode::Attribute(a) => format!("attr:{}({})", a.name, a.args.join(", ")),
+                ASTNode::Exception(e) => format!("{:?}:{}", e.kind, e.name),
+                ASTNode::Literal(l) => format!("str:{}", l.value),
                 ASTNode::Comment => "comment".into(),
+                ASTNode::Unknown(v) => format!("unknown:{v}"),
                 ASTNode::File(f) => format!("file:{}", f.path),
             })
             .collect()
@@ -92,7 +425,7 @@ mod tests {
     #[test]
     fn clean_empty_nodes() {
         let source = "";
-        let result = clean(Path::new("test.py"), source, vec![]);
+        let result = clean("test.py", source, vec![], CommentPolicy::StripMerge);
         assert_eq!(names(&result), vec!["file:test.py"]);
     }
 
@@ -100,11 +433,17 @@ mod tests {
     fn clean_no_comments() {
         let source = "def foo(): pass";
         let nodes = vec![Syntax {
-            node: ASTNode::Function(Function { name: "foo".into() }),
+            node: ASTNode::Function(Function {
+                name: "foo".into(),
+                public: false,
+                params: vec![],
+                return_type: None,
+                decorators: vec![],
+            }),
             metadata: meta(15, 3),
             contains: vec![],
         }];
-        let result = clean(Path::new("test.py"), source, nodes);
+        let result = clean("test.py", source, nodes, CommentPolicy::StripMerge);
         // No comment node inserted when there are no comments
         assert_eq!(names(&result), vec!["file:test.py", "fn:foo"]);
     }
@@ -119,12 +458,18 @@ mod tests {
                 contains: vec![],
             },
             Syntax {
-                node: ASTNode::Function(Function { name: "foo".into() }),
+                node: ASTNode::Function(Function {
+                    name: "foo".into(),
+                    public: false,
+                    params: vec![],
+                    return_type: None,
+                    decorators: vec![],
+                }),
                 metadata: meta(15, 3),
                 contains: vec![],
             },
         ];
-        let result = clean(Path::new("test.py"), source, nodes);
+        let result = clean("test.py", source, nodes, CommentPolicy::StripMerge);
         assert_eq!(names(&result), vec!["file:test.py", "comment", "fn:foo"]);
     }
 
@@ -143,7 +488,7 @@ mod tests {
                 contains: vec![],
             },
         ];
-        let result = clean(Path::new("x.py"), source, nodes);
+        let result = clean("x.py", source, nodes, CommentPolicy::StripMerge);
         assert_eq!(names(&result), vec!["file:x.py", "comment"]);
         // Merged metadata
         let comment = &result[1];
@@ -151,11 +496,51 @@ mod tests {
         assert_eq!(comment.metadata.words, 4);
     }
 
+    #[test]
+    fn clean_merged_comment_span_covers_all() {
+        let nodes = vec![
+            Syntax {
+                node: ASTNode::Comment,
+                metadata: meta_at(3, 3),
+                contains: vec![],
+            },
+            Syntax {
+                node: ASTNode::Comment,
+                metadata: meta_at(1, 2),
+                contains: vec![],
+            },
+        ];
+        let result = clean("x.py", "", nodes, CommentPolicy::StripMerge);
+        assert_eq!(result[1].metadata.span, Span::lines(1, 3));
+    }
+
+    #[test]
+    fn clean_file_node_spans_whole_file() {
+        let result = clean("x.py", "a\nb\nc", vec![], CommentPolicy::StripMerge);
+        assert_eq!(
+            result[0].metadata.span,
+            Span {
+                start_line: 1,
+                end_line: 3,
+                start_col: 1,
+                end_col: 2,
+                start_byte: 0,
+                end_byte: 5,
+            }
+        );
+    }
+
     #[test]
     fn clean_strips_nested_comments() {
         let source = "def foo():\n    # inner\n    pass";
         let nodes = vec![Syntax {
-            node: ASTNode::Function(Function { name: "foo".into() }),
+            node: ASTNode::Function(Function {
+                name: "foo".into(),
+                public: false,
+                params: vec![],
+                return_type: None,
+                decorators: vec![],
+            }),
             metadata: meta(30, 5),
             contains: vec![Syntax {
                 node: ASTNode::Comment,
@@ -163,7 +548,7 @@ mod tests {
                 contains: vec![],
             }],
         }];
-        let result = clean(Path::new("test.py"), source, nodes);
+        let result = clean("test.py", source, nodes, CommentPolicy::StripMerge);
         assert_eq!(names(&result), vec!["file:test.py", "comment", "fn:foo"]);
         // Nested comment stripped from contains
         assert!(result[2].contains.is_empty());
@@ -177,14 +562,14 @@ mod tests {
             metadata: meta(15, 3),
             contains: vec![],
         }];
-        let result = clean(Path::new("c.py"), source, nodes);
+        let result = clean("c.py", source, nodes, CommentPolicy::StripMerge);
         assert_eq!(names(&result), vec!["file:c.py", "comment"]);
     }
 
     #[test]
     fn clean_file_node_has_full_metadata() {
         let source = "hello world\nsecond line\n";
-        let result = clean(Path::new("test.py"), source, vec![]);
+        let result = clean("test.py", source, vec![], CommentPolicy::StripMerge);
         let file_meta = &result[0].metadata;
         assert_eq!(file_meta.chars, source.len());
         assert_eq!(file_meta.newlines, 2);
@@ -194,21 +579,247 @@ mod tests {
     fn clean_preserves_nesting() {
         let source = "class Foo:\n    def bar(self):\n        baz()";
         let nodes = vec![Syntax {
-            node: ASTNode::Type(Type { name: "Foo".into() }),
+            node: ASTNode::Type(Type {
+                name: "Foo".into(),
+                public: false,
+                is_abstract: false,
+                decorators: vec![],
+            }),
             metadata: meta(44, 6),
             contains: vec![Syntax {
-                node: ASTNode::Function(Function { name: "bar".into() }),
+                node: ASTNode::Function(Function {
+                    name: "bar".into(),
+                    public: false,
+                    params: vec![],
+                    return_type: None,
+                    decorators: vec![],
+                }),
                 metadata: meta(30, 4),
                 contains: vec![Syntax {
-                    node: ASTNode::Call(Call { name: "baz".into() }),
+                    node: ASTNode::Call(Call {
+                        name: "baz".into(),
+                        tags: vec![],
+                        spawned_by: None,
+                    }),
                     metadata: meta(5, 1),
                     contains: vec![],
                 }],
             }],
         }];
-        let result = clean(Path::new("test.py"), source, nodes);
+        let result = clean("test.py", source, nodes, CommentPolicy::StripMerge);
         // Type → Function → Call nesting preserved
         assert_eq!(names(&result[1].contains), vec!["fn:bar"]);
         assert_eq!(names(&result[1].contains[0].contains), vec!["call:baz"]);
     }
+
+    // ── License ──
+
+    fn license_of<'a>(result: &'a [Syntax]) -> Option<&'a str> {
+        match &result[0].node {
+            ASTNode::File(f) => f.license.as_deref(),
+            _ => None,
+        }
+    }
+
+    fn comment_at(line: usize) -> Syntax<'static> {
+        Syntax {
+            node: ASTNode::Comment,
+            metadata: meta_at(line, line),
+            contains: vec![],
+        }
+    }
+
+    #[test]
+    fn clean_license_from_leading_comment() {
+        let source = "#!/usr/bin/env python\n# SPDX-License-Identifier: MIT\nx = 1\n";
+        let nodes = vec![comment_at(1), comment_at(2)];
+        let result = clean("a.py", source, nodes, CommentPolicy::StripMerge);
+        assert_eq!(license_of(&result), Some("MIT"));
+    }
+
+    #[test]
+    fn clean_license_ignores_comments_after_code() {
+        let source = "def f(): pass\n# SPDX-License-Identifier: MIT\n";
+        let nodes = vec![
+            Syntax {
+                node: ASTNode::Function(Function {
+                    name: "f".into(),
+                    public: false,
+                    params: vec![],
+                    return_type: None,
+                    decorators: vec![],
+                }),
+                metadata: meta_at(1, 1),
+                contains: vec![],
+            },
+            comment_at(2),
+        ];
+        let result = clean("a.py", source, nodes, CommentPolicy::StripMerge);
+        assert_eq!(license_of(&result), None);
+    }
+
+    #[test]
+    fn clean_no_license_without_comments() {
+        let result = clean("a.py", "x = 1", vec![], CommentPolicy::StripMerge);
+        assert_eq!(license_of(&result), None);
+    }
+
+    // ── Comment policy ──
+
+    fn commented() -> Vec<Syntax<'static>> {
+        vec![
+            comment_at(1),
+            Syntax {
+                node: ASTNode::Function(Function {
+                    name: "f".into(),
+                    public: false,
+                    params: vec![],
+                    return_type: None,
+                    decorators: vec![],
+                }),
+                metadata: meta_at(2, 4),
+                contains: vec![comment_at(3), comment_at(4)],
+            },
+            comment_at(5),
+        ]
+    }
+
+    #[test]
+    fn policy_strip_drop() {
+        let result = clean("a.py", "", commented(), CommentPolicy::StripDrop);
+        assert_eq!(names(&result), vec!["file:a.py", "fn:f"]);
+        assert!(result[1].contains.is_empty());
+    }
+
+    #[test]
+    fn policy_keep_in_place() {
+        let result = clean("a.py", "", commented(), CommentPolicy::KeepInPlace);
+        assert_eq!(
+            names(&result),
+            vec!["file:a.py", "comment", "fn:f", "comment"]
+        );
+        assert_eq!(names(&result[2].contains), vec!["comment", "comment"]);
+    }
+
+    #[test]
+    fn policy_attach_to_next() {
+        let result = clean("a.py", "", commented(), CommentPolicy::AttachToNext);
+        // Leading comment moves into f; the trailing one has no next sibling
+        assert_eq!(names(&result), vec!["file:a.py", "fn:f", "comment"]);
+        assert_eq!(
+            names(&result[1].contains),
+            vec!["comment", "comment", "comment"]
+        );
+        assert_eq!(result[1].contains[0].metadata.span.start_line, 1);
+    }
+
+    #[test]
+    fn policy_attach_to_next_nested() {
+        let nodes = vec![Syntax {
+            node: ASTNode::Type(Type {
+                name: "T".into(),
+                public: false,
+                is_abstract: false,
+                decorators: vec![],
+            }),
+            metadata: meta_at(1, 4),
+            contains: vec![
+                comment_at(2),
+                Syntax {
+                    node: ASTNode::Function(Function {
+                        name: "m".into(),
+                        public: false,
+                        params: vec![],
+                        return_type: None,
+                        decorators: vec![],
+                    }),
+                    metadata: meta_at(3, 4),
+                    contains: vec![],
+                },
+            ],
+        }];
+        let result = clean("a.py", "", nodes, CommentPolicy::AttachToNext);
+        assert_eq!(names(&result[1].contains), vec!["fn:m"]);
+        assert_eq!(names(&result[1].contains[0].contains), vec!["comment"]);
+    }
+
+    // ── Sibling index ──
+
+    #[test]
+    fn index_survives_hoisting() {
+        let result = clean("a.py", "", commented(), CommentPolicy::StripMerge);
+        assert_eq!(names(&result), vec!["file:a.py", "comment", "fn:f"]);
+        // Synthetic File and merged Comment have no index; f was second
+        assert_eq!(result[0].metadata.index, None);
+        assert_eq!(result[1].metadata.index, None);
+        assert_eq!(result[2].metadata.index, Some(1));
+    }
+
+    #[test]
+    fn index_follows_parse_order_when_attached() {
+        let result = clean("a.py", "", commented(), CommentPolicy::AttachToNext);
+        let f = &result[1];
+        let idx: Vec<_> = f.contains.iter().map(|s| s.metadata.index).collect();
+        // Attached leading comment keeps its top-level index
+        assert_eq!(idx, vec![Some(0), Some(0), Some(1)]);
+    }
+
+    // ── Documentation ──
+
+    fn metrics(source: &str) -> Vec<(String, serde_json::Value)> {
+        use crate::api::tree_sitter::rs::Rust;
+        use crate::api::tree_sitter::{Lang, Options};
+        let mut parser = Rust.get_parser();
+        let ast = Rust
+            .parse(&mut parser, source, &Options::default())
+            .unwrap();
+        let result = clean("a.rs", source, ast, CommentPolicy::StripDrop);
+        result[0].metadata.metrics.clone().into_iter().collect()
+    }
+
+    #[test]
+    fn comment_and_code_lines() {
+        let m = metrics("// a\n\nfn f() {\n    g(); // call\n}\n");
+        let get = |k: &str| m.iter().find(|(key, _)| key == k).unwrap().1.clone();
+        assert_eq!(get(COMMENT_LINES), 2);
+        assert_eq!(get(CODE_LINES), 3);
+    }
+
+    #[test]
+    fn documented_through_attributes_not_blank_lines() {
+        let src = "/// A.\n#[derive(Debug)]\npub struct A;\n\n\
+                   /// B.\n\npub fn b() {}\n\
+                   fn private() {}\n";
+        let m = metrics(src);
+        let get = |k: &str| m.iter().find(|(key, _)| key == k).unwrap().1.clone();
+        assert_eq!(get(DEFINITIONS), 2);
+        assert_eq!(get(DOCUMENTED), 1);
+    }
+
+    // ── Body hash ──
+
+    fn body(source: &str) -> (serde_json::Value, serde_json::Value) {
+        use crate::api::tree_sitter::rs::Rust;
+        use crate::api::tree_sitter::{Lang, Options};
+        let ast = Rust
+            .parse(&mut Rust.get_parser(), source, &Options::default())
+            .unwrap();
+        let result = clean("a.rs", source, ast, CommentPolicy::StripDrop);
+        let m = &result[1].metadata;
+        (
+            m.metric(BODY_HASH).unwrap().clone(),
+            m.metric(BODY_TOKENS).unwrap().clone(),
+        )
+    }
+
+    #[test]
+    fn body_hash_ignores_name_and_layout() {
+        let (hash, n) = body("fn total(a: u8, b: u8) -> u8 { a + b }\n");
+        assert_eq!(n, 18);
+        assert_eq!(
+            body("fn sum(a: u8,\n       b: u8) -> u8 {\n    a + b\n}\n").0,
+            hash
+        );
+        assert_ne!(body("fn total(a: u8, b: u8) -> u8 { a - b }\n").0, hash);
+    }
 }
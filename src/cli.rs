@@ -1,4 +1,12 @@
-use clap::{Parser, Subcommand};
+use crate::api::fs::{Sample, Shard};
+use crate::badge::Metric;
+use crate::clean::CommentPolicy;
+use crate::hook::Hook;
+use crate::jobs::Jobs;
+use crate::links::LinkTemplate;
+use crate::pretty::Color;
+use clap::{Parser, Subcommand, ValueEnum};
+use std::num::NonZeroUsize;
 use std::path::PathBuf;
 
 #[derive(Parser)]
@@ -6,6 +14,10 @@ use std::path::PathBuf;
 pub struct Bolo {
     #[command(subcommand)]
     pub lang: LangCmd,
+
+    /// Color messages on stderr
+    #[arg(long, global = true, value_enum, default_value_t)]
+    pub color: Color,
 }
 
 #[derive(Subcommand)]
@@ -14,11 +26,248 @@ pub enum LangCmd {
     Py(Args),
     /// Analyze Rust source files
     Rs(Args),
+    /// Analyze JavaScript source files
+    Js(Args),
+    /// Analyze TypeScript source files
+    Ts(Args),
+    /// Analyze Go source files
+    Go(Args),
+    /// Analyze every supported language under PATH in one run, keyed by language
+    All(AllArgs),
+    /// Compare Python .pyi stubs with the .py files beside them
+    Stubs(StubsArgs),
+    /// Inventory unsafe blocks, fns, impls and traits in Rust source files
+    Unsafe(Args),
+    /// Inventory raised and handled exceptions in Python source files
+    Exceptions(Args),
+    /// Map the error types Rust functions return and propagate with `?`, by module
+    Errors(Args),
+    /// Inventory panic, unwrap and process-exit calls, grouped by file
+    Panics(PanicsArgs),
+    /// Inventory mutable globals and statics with the functions referencing them
+    Globals(ReportArgs),
+    /// Report comment-to-code ratios and documentation coverage per file and directory
+    Stats(ReportArgs),
+    /// Count the words in function, type and call names, and synonyms used side by side
+    Vocab(ReportArgs),
+    /// Report calls matching each watchlist in bolo.toml
+    Watch(ReportArgs),
+    /// Cluster files by their calls and flag files placed in the wrong directory
+    Clusters(ReportArgs),
+    /// Coupling, instability and abstractness per directory (Martin metrics)
+    Coupling(ReportArgs),
+    /// Merge analysis JSON files into one, deduplicating files
+    Merge(MergeArgs),
+    /// Compare the call graphs of two analysis JSON files
+    Diff(DiffArgs),
+    /// Suggest a major/minor/patch bump from two analyses' public API
+    Semver(SemverArgs),
+    /// List the call sites renaming a definition would touch
+    PreviewRename(PreviewRenameArgs),
+    /// Extract part of an analysis JSON file's call graph
+    Query(QueryArgs),
+    /// Write a shields.io endpoint badge for a metric of an analysis JSON file
+    Badge(BadgeArgs),
+    /// Append an analysis JSON file's aggregate metrics to a trend database
+    Record(RecordArgs),
+    /// Print how recorded metrics evolved across commits
+    Trend(TrendArgs),
+    /// Draft a markdown changelog from public API changes between git revisions
+    Changelog(ChangelogArgs),
+    /// Time the pipeline over a generated synthetic tree
+    Bench(BenchArgs),
+    /// Serve symbols and call hierarchy to editors (language server over stdio)
+    Lsp(LspArgs),
+    /// Serve an analysis as JSON over HTTP, re-analyzing on request
+    Serve(ServeArgs),
+    /// Serve a `--bundle` file's viewer and analysis locally
+    Open(OpenArgs),
+    /// Check staged changes against bolo.toml rules from a git hook
+    Hook(HookArgs),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Language {
+    Py,
+    Rs,
+    Js,
+    Ts,
+    Go,
+}
+
+/// Which string literals `--strings` emits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum StringsArg {
+    /// Every string literal
+    All,
+    /// URLs, paths and SQL only
+    Interesting,
+}
+
+/// Conditions that `--fail-on` turns into a non-zero exit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum FailOn {
+    /// A file had a syntax error the parser recovered from
+    ParseError,
+    /// Any entry in the output's warnings
+    Warning,
+    /// Files that depend on each other in a cycle
+    Cycle,
+    /// A call or layer dependency forbidden by `[rules]` in bolo.toml
+    RuleViolation,
 }
 
 #[derive(Parser)]
-pub struct Args {
-    /// File or directory to analyze
+pub struct MergeArgs {
+    /// Analysis JSON files written by `bolo py` / `bolo rs`
+    #[arg(required = true)]
+    pub inputs: Vec<PathBuf>,
+
+    /// Output file, repeatable; format from extension: .json, .dot, .html (omit for stdout)
+    #[arg(short, long)]
+    pub output: Vec<PathBuf>,
+
+    /// Overwrite existing output
+    #[arg(short, long)]
+    pub force: bool,
+}
+
+/// Output format for `bolo diff`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum FormatArg {
+    /// Added and removed definitions and edges
+    Json,
+    /// Graphviz union graph, additions green and removals red
+    Dot,
+    /// HTML page per file, additions green and removals red
+    Html,
+    /// Markdown counts and highlights, to $GITHUB_STEP_SUMMARY if set and no --output
+    GhSummary,
+}
+
+/// Other renderings of an analysis, chosen with `--format`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum AnalysisFormat {
+    /// Markdown counts, cycles and rule violations, appended to $GITHUB_STEP_SUMMARY (stdout when unset)
+    GhSummary,
+    /// Cytoscape.js elements instead of analysis JSON, on stdout and in -o files
+    Cytoscape,
+    /// Call graph as `nodes` and `edges` arrays instead of analysis JSON, on stdout and in -o files
+    Graph,
+    /// Graphviz DOT call graph instead of analysis JSON, on stdout and in -o files
+    Dot,
+    /// One analysis JSON object per file, written as each file is parsed
+    Ndjson,
+}
+
+#[derive(Parser)]
+pub struct DiffArgs {
+    /// Analysis JSON before the change
+    pub old: PathBuf,
+
+    /// Analysis JSON after the change
+    pub new: PathBuf,
+
+    /// Output format (default: from --output's extension, else json)
+    #[arg(long, value_enum)]
+    pub format: Option<FormatArg>,
+
+    /// Output file (omit for stdout)
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// Overwrite existing output
+    #[arg(short, long)]
+    pub force: bool,
+}
+
+#[derive(Parser)]
+pub struct SemverArgs {
+    /// Analysis JSON of the last release
+    pub old: PathBuf,
+
+    /// Analysis JSON of the candidate release
+    pub new: PathBuf,
+
+    /// Output file (omit for stdout)
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// Overwrite existing output
+    #[arg(short, long)]
+    pub force: bool,
+}
+
+#[derive(Parser)]
+pub struct PreviewRenameArgs {
+    /// Definition to rename: a graph id (`src/net.py::Client.send`), qualified or plain name
+    pub old: String,
+
+    /// New name; only its last `.`/`::` segment is used
+    pub new: String,
+
+    /// Analysis JSON written by `bolo py` / `bolo rs`
+    #[arg(short, long)]
+    pub input: PathBuf,
+
+    /// Output file (omit for stdout)
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// Overwrite existing output
+    #[arg(short, long)]
+    pub force: bool,
+}
+
+#[derive(Parser)]
+pub struct QueryArgs {
+    #[command(subcommand)]
+    pub cmd: QueryCmd,
+}
+
+#[derive(Subcommand)]
+pub enum QueryCmd {
+    /// Definitions within a few calls of one, either way, and the edges between them
+    Neighborhood(NeighborhoodArgs),
+}
+
+/// Output format for `bolo query`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum QueryFormat {
+    /// `nodes` and `edges` arrays, as `--format graph`
+    Graph,
+    /// Graphviz DOT, as `--format dot`
+    Dot,
+}
+
+#[derive(Parser)]
+pub struct NeighborhoodArgs {
+    /// Center definition: a graph id (`src/net.py::Client.send`), symbol, qualified or plain name
+    pub symbol: String,
+
+    /// Analysis JSON written by `bolo py` / `bolo rs`
+    #[arg(short, long)]
+    pub input: PathBuf,
+
+    /// Most call edges between the center and a definition kept
+    #[arg(long, default_value = "1")]
+    pub radius: usize,
+
+    #[arg(long, value_enum, default_value = "graph")]
+    pub format: QueryFormat,
+
+    /// Output file (omit for stdout)
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// Overwrite existing output
+    #[arg(short, long)]
+    pub force: bool,
+}
+
+#[derive(Parser)]
+pub struct StubsArgs {
+    /// File or directory holding .pyi stubs
     #[arg(default_value = ".")]
     pub path: PathBuf,
 
@@ -29,20 +278,452 @@ pub struct Args {
     /// Overwrite existing output
     #[arg(short, long)]
     pub force: bool,
+}
+
+#[derive(Parser)]
+pub struct BadgeArgs {
+    /// Analysis JSON written by `bolo py` / `bolo rs`
+    pub input: PathBuf,
+
+    /// Metric the badge shows
+    #[arg(long, value_enum)]
+    pub metric: Metric,
+
+    /// Output file (omit for stdout)
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// Overwrite existing output
+    #[arg(short, long)]
+    pub force: bool,
+}
+
+#[derive(Parser)]
+pub struct RecordArgs {
+    /// Analysis JSON written by `bolo py` / `bolo rs`
+    pub input: PathBuf,
+
+    /// Trend database (SQLite for .sqlite/.sqlite3/.db, else JSON Lines), created if missing
+    #[arg(long)]
+    pub db: PathBuf,
+
+    /// Commit the analysis was taken at (default: HEAD of the current directory)
+    #[arg(long)]
+    pub commit: Option<String>,
+}
+
+#[derive(Parser)]
+pub struct TrendArgs {
+    /// Trend database written by `bolo record`
+    #[arg(long)]
+    pub db: PathBuf,
+
+    /// Metric to show, repeatable (default: all)
+    #[arg(long, value_parser = clap::builder::PossibleValuesParser::new(crate::trend::METRICS))]
+    pub metric: Vec<String>,
+}
+
+#[derive(Parser)]
+pub struct ChangelogArgs {
+    /// Language to analyze
+    #[arg(value_enum)]
+    pub lang: Language,
+
+    /// Directory inside a git repository
+    #[arg(default_value = ".")]
+    pub path: PathBuf,
+
+    /// Revision of the last release (tag, branch or commit)
+    #[arg(long)]
+    pub from: String,
+
+    /// Revision to describe
+    #[arg(long, default_value = "HEAD")]
+    pub to: String,
+
+    /// Output file (omit for stdout)
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// Overwrite existing output
+    #[arg(short, long)]
+    pub force: bool,
+}
+
+#[derive(Parser)]
+pub struct LspArgs {
+    /// Language to serve
+    #[arg(value_enum)]
+    pub lang: Language,
+}
+
+#[derive(Parser)]
+pub struct ServeArgs {
+    /// Language to analyze
+    #[arg(value_enum)]
+    pub lang: Language,
+
+    /// Directory to analyze
+    #[arg(default_value = ".")]
+    pub path: PathBuf,
+
+    /// Port to listen on
+    #[arg(long, default_value = "7878")]
+    pub port: u16,
+
+    /// Address to bind; beyond loopback, requests must carry the bearer
+    /// token in $BOLO_SERVE_TOKEN
+    #[arg(long, default_value = "127.0.0.1")]
+    pub host: String,
+
+    /// Serve HTTPS with this PEM certificate chain
+    #[arg(long, requires = "tls_key")]
+    pub tls_cert: Option<PathBuf>,
+
+    /// Private key for --tls-cert, in PEM
+    #[arg(long, requires = "tls_cert")]
+    pub tls_key: Option<PathBuf>,
+
+    /// Serve the gRPC API in proto/bolo.proto instead of HTTP
+    #[arg(long)]
+    pub grpc: bool,
 
     /// Include files ignored by .gitignore
     #[arg(long)]
     pub no_ignore: bool,
 
+    /// Comment handling (default: strip-merge, or `comments` in bolo.toml)
+    #[arg(long, value_enum)]
+    pub comments: Option<CommentPolicy>,
+
+    /// Post-processing passes to run, in order, e.g. metrics,strip-comments
+    /// (default: all, or `passes` in bolo.toml)
+    #[arg(long, value_delimiter = ',')]
+    pub passes: Option<Vec<String>>,
+
+    /// Prefix File node paths with this label (e.g. the project name)
+    #[arg(long)]
+    pub root_label: Option<String>,
+
+    /// Config file (default: nearest bolo.toml above PATH)
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+}
+
+#[derive(Parser)]
+pub struct OpenArgs {
+    /// Bundle written by `--bundle`, or analysis JSON
+    pub input: PathBuf,
+
+    /// Port to listen on
+    #[arg(long, default_value = "7878")]
+    pub port: u16,
+
+    /// Address to bind; beyond loopback, requests must carry the bearer
+    /// token in $BOLO_SERVE_TOKEN
+    #[arg(long, default_value = "127.0.0.1")]
+    pub host: String,
+
+    /// Print the address without opening a browser
+    #[arg(long)]
+    pub no_browser: bool,
+}
+
+#[derive(Parser)]
+pub struct HookArgs {
+    #[command(subcommand)]
+    pub cmd: HookCmd,
+}
+
+#[derive(Subcommand)]
+pub enum HookCmd {
+    /// Install a git hook that runs `bolo hook run`
+    Install(HookInstallArgs),
+    /// Check changed files against the rules; exits non-zero on violations
+    Run(HookRunArgs),
+}
+
+#[derive(Parser)]
+pub struct HookInstallArgs {
+    /// Which hook to install
+    #[arg(long, value_enum, default_value = "pre-commit")]
+    pub hook: Hook,
+
+    /// Replace an existing hook not installed by bolo
+    #[arg(short, long)]
+    pub force: bool,
+}
+
+#[derive(Parser)]
+pub struct HookRunArgs {
+    /// Check files changed on HEAD since it forked from REV instead of staged files
+    #[arg(long, value_name = "REV")]
+    pub against: Option<String>,
+
+    /// Config file (default: nearest bolo.toml above the working directory)
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+}
+
+#[derive(Parser)]
+pub struct BenchArgs {
+    /// Language of the generated tree
+    #[arg(value_enum)]
+    pub lang: Language,
+
+    /// Number of files to generate
+    #[arg(long, default_value = "1000")]
+    pub files: usize,
+
+    /// Top-level functions per file
+    #[arg(long, default_value = "20")]
+    pub functions: usize,
+
+    /// Seed for names and call targets; same seed, same tree
+    #[arg(long, default_value = "0")]
+    pub seed: u64,
+
+    /// Print timings as JSON
+    #[arg(long)]
+    pub json: bool,
+
+    /// Number of parallel threads (0 = all cores, `auto` = sized to the workload)
+    #[arg(short = 'j', long, default_value = "1")]
+    pub jobs: Jobs,
+}
+
+#[derive(Parser)]
+pub struct ReportArgs {
+    /// Language to analyze
+    #[arg(value_enum)]
+    pub lang: Language,
+
+    #[command(flatten)]
+    pub args: Args,
+}
+
+#[derive(Parser)]
+pub struct AllArgs {
+    /// Directory or file to analyze
+    #[arg(default_value = ".")]
+    pub path: PathBuf,
+
+    /// Languages to analyze, comma-separated (default: all)
+    #[arg(long, value_enum, value_delimiter = ',')]
+    pub lang: Vec<Language>,
+
+    /// Output file, repeatable; JSON only (omit for stdout)
+    #[arg(short, long)]
+    pub output: Vec<PathBuf>,
+
+    /// Overwrite existing output
+    #[arg(short, long)]
+    pub force: bool,
+
+    /// Include files ignored by .gitignore
+    #[arg(long)]
+    pub no_ignore: bool,
+
+    /// Walk into venv/, __pycache__/, build/, target/ and the like
+    #[arg(long)]
+    pub no_default_excludes: bool,
+
     /// Only scan immediate directory (not recursive)
     #[arg(long)]
     pub shallow: bool,
 
-    /// Show file count and exit
+    /// Comment handling (default: strip-merge, or `comments` in bolo.toml)
+    #[arg(long, value_enum)]
+    pub comments: Option<CommentPolicy>,
+
+    /// Post-processing passes to run, in order, e.g. metrics,strip-comments
+    /// (default: all, or `passes` in bolo.toml)
+    #[arg(long, value_delimiter = ',')]
+    pub passes: Option<Vec<String>>,
+
+    /// Prefix File node paths with this label (e.g. the project name)
+    #[arg(long)]
+    pub root_label: Option<String>,
+
+    /// Config file (default: nearest bolo.toml above PATH)
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+
+    /// Minified JSON with sorted keys (default for -o files and pipes)
+    #[arg(long, conflicts_with = "pretty")]
+    pub compact: bool,
+
+    /// Indented JSON (default on a terminal)
+    #[arg(long)]
+    pub pretty: bool,
+
+    /// Number of parallel threads (0 = all cores, `auto` = sized to the workload)
+    #[arg(short = 'j', long, default_value = "1")]
+    pub jobs: Jobs,
+}
+
+#[derive(Parser)]
+pub struct PanicsArgs {
+    /// Language to analyze
+    #[arg(value_enum)]
+    pub lang: Language,
+
+    #[command(flatten)]
+    pub args: Args,
+
+    /// Call names to report, comma-separated (default: panics and exits)
+    #[arg(long, value_delimiter = ',')]
+    pub calls: Vec<String>,
+}
+
+#[derive(Parser)]
+pub struct Args {
+    /// File or directory to analyze, or a git URL to shallow-clone
+    #[arg(default_value = ".")]
+    pub path: PathBuf,
+
+    /// Branch, tag or commit to check out when PATH is a git URL
+    #[arg(long)]
+    pub rev: Option<String>,
+
+    /// Output file, repeatable; format from extension: .json, .dot, .html (omit for stdout)
+    #[arg(short, long)]
+    pub output: Vec<PathBuf>,
+
+    /// Overwrite existing output
+    #[arg(short, long)]
+    pub force: bool,
+
+    /// Analyze the files listed in FILE (`-` for stdin) instead of walking PATH
+    #[arg(long, value_name = "FILE")]
+    pub files_from: Option<PathBuf>,
+
+    /// File list entries are NUL-delimited (as from `find -print0`)
+    #[arg(short = '0', long, requires = "files_from")]
+    pub null: bool,
+
+    /// Analyze source read from stdin, e.g. an unsaved editor buffer, instead of walking PATH
+    #[arg(long, requires = "filename", conflicts_with_all = [
+        "files_from", "include_deps", "dry_run", "list_files", "cfg_matrix", "shard", "sample",
+        "max_files",
+    ])]
+    pub stdin: bool,
+
+    /// File name `--stdin` source is reported as, relative to PATH
+    #[arg(long, value_name = "NAME", requires = "stdin")]
+    pub filename: Option<PathBuf>,
+
+    /// Include files ignored by .gitignore
+    #[arg(long)]
+    pub no_ignore: bool,
+
+    /// Walk into venv/, __pycache__/, build/, target/ and the like
+    #[arg(long)]
+    pub no_default_excludes: bool,
+
+    /// Also analyze installed third-party packages, tagged external
+    #[arg(long, conflicts_with = "files_from")]
+    pub include_deps: bool,
+
+    /// Only scan immediate directory (not recursive)
+    #[arg(long)]
+    pub shallow: bool,
+
+    /// Show file counts, sizes and an estimated run time, then exit
     #[arg(long)]
     pub dry_run: bool,
 
-    /// Number of parallel threads (0 = all cores)
+    /// Print the resolved file set (path, size, language) as JSON and exit
+    #[arg(long, conflicts_with = "dry_run")]
+    pub list_files: bool,
+
+    /// Report definitions and calls only some Cargo feature combinations compile (`bolo rs`)
+    #[arg(long, conflicts_with_all = ["dry_run", "list_files"])]
+    pub cfg_matrix: bool,
+
+    /// Extract web routes (Flask, FastAPI, Django) as Endpoint nodes (py only)
+    #[arg(long)]
+    pub routes: bool,
+
+    /// Emit string literals under their enclosing function
+    #[arg(long, value_enum, num_args = 0..=1, default_missing_value = "all")]
+    pub strings: Option<StringsArg>,
+
+    /// Comment handling (default: strip-merge, or `comments` in bolo.toml)
+    #[arg(long, value_enum)]
+    pub comments: Option<CommentPolicy>,
+
+    /// Post-processing passes to run, in order, e.g. metrics,strip-comments
+    /// (default: all, or `passes` in bolo.toml)
+    #[arg(long, value_delimiter = ',')]
+    pub passes: Option<Vec<String>>,
+
+    /// Prefix File node paths with this label (e.g. the project name)
+    #[arg(long)]
+    pub root_label: Option<String>,
+
+    /// Analyze only slice i of n (1-based), e.g. 2/4; combine with `bolo merge`
+    #[arg(long, value_name = "I/N")]
+    pub shard: Option<Shard>,
+
+    /// Analyze a deterministic sample of the files, e.g. 10%
+    #[arg(long, value_name = "PCT")]
+    pub sample: Option<Sample>,
+
+    /// Analyze at most N files, chosen as for --sample
+    #[arg(long, value_name = "N")]
+    pub max_files: Option<NonZeroUsize>,
+
+    /// Seed for --sample and --max-files; same seed, same files
+    #[arg(long, default_value = "0")]
+    pub seed: u64,
+
+    /// Reuse parses of unchanged files from DIR (default: ~/.cache/bolo)
+    #[arg(long, value_name = "DIR", num_args = 0..=1, require_equals = true)]
+    pub cache: Option<Option<PathBuf>>,
+
+    /// Exit non-zero when any of these occur, comma-separated (output is still written)
+    #[arg(long, value_enum, value_delimiter = ',')]
+    pub fail_on: Vec<FailOn>,
+
+    /// Config file (default: nearest bolo.toml above PATH)
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+
+    /// Also render a job summary (`gh-summary`), or write Cytoscape.js elements (`cytoscape`),
+    /// the call graph (`graph`, `dot`) or one JSON line per file (`ndjson`) instead
+    #[arg(long, value_enum)]
+    pub format: Option<AnalysisFormat>,
+
+    /// Attach sample counts from a profile (folded stacks or speedscope JSON) to functions
+    #[arg(long, value_name = "FILE")]
+    pub profile: Option<PathBuf>,
+
+    /// Link files and definitions to source, e.g. `https://host/blob/{commit}/{path}#L{start}-L{end}`
+    #[arg(long, value_name = "TEMPLATE")]
+    pub link_template: Option<LinkTemplate>,
+
+    /// Replace paths, names and string literals with hashes keyed by $BOLO_ANONYMIZE_KEY
+    #[arg(long)]
+    pub anonymize: bool,
+
+    /// Drop detail from analysis JSON larger than this: literals, then calls, then metrics, then files
+    #[arg(long, value_name = "N")]
+    pub max_output_bytes: Option<usize>,
+
+    /// Also pack the analysis and its HTML viewer into one file for `bolo open`
+    #[arg(long, value_name = "FILE")]
+    pub bundle: Option<PathBuf>,
+
+    /// Minified JSON with sorted keys (default for -o files and pipes)
+    #[arg(long, conflicts_with = "pretty")]
+    pub compact: bool,
+
+    /// Indented JSON (default on a terminal)
+    #[arg(long)]
+    pub pretty: bool,
+
+    /// Number of parallel threads (0 = all cores, `auto` = sized to the workload)
     #[arg(short = 'j', long, default_value = "1")]
-    pub jobs: usize,
+    pub jobs: Jobs,
 }
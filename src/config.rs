@@ -0,0 +1,256 @@
+use crate::clean::CommentPolicy;
+use crate::error::BoloError;
+use crate::labels::Labels;
+use crate::links::LinkTemplate;
+use crate::redact::Redact;
+use crate::rules::Rules;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const FILE_NAME: &str = "bolo.toml";
+
+/// Project configuration read from `bolo.toml`. Unknown keys are ignored.
+#[derive(Debug, Default, Deserialize)]
+pub struct Config {
+    /// Named call patterns, e.g. `network = ["requests.*", "reqwest::*"]`.
+    #[serde(default)]
+    pub watchlists: BTreeMap<String, Vec<String>>,
+    /// Comment handling; `--comments` overrides it.
+    pub comments: Option<CommentPolicy>,
+    /// Post-processing passes, in order; `--passes` overrides it.
+    pub passes: Option<Vec<String>>,
+    /// Prefix for File node paths; `--root-label` overrides it.
+    pub root_label: Option<String>,
+    /// Source permalink pattern; `--link-template` overrides it.
+    pub link_template: Option<LinkTemplate>,
+    /// Checks reported as rule violations (`--fail-on rule-violation`).
+    #[serde(default)]
+    pub rules: Rules,
+    /// Files and symbols left out of written output.
+    #[serde(default)]
+    pub redact: Redact,
+    /// Definition labels and tooltips in `.dot` and `.html` output.
+    #[serde(default)]
+    pub labels: Labels,
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Self, BoloError> {
+        let text = fs::read_to_string(path).map_err(|e| BoloError::Config {
+            path: path.to_path_buf(),
+            reason: e.to_string(),
+        })?;
+        let config: Config = toml::from_str(&text).map_err(|e| BoloError::Config {
+            path: path.to_path_buf(),
+            reason: e.message().to_string(),
+        })?;
+        config
+            .rules
+            .validate()
+            .map_err(|reason| BoloError::Config {
+                path: path.to_path_buf(),
+                reason,
+            })?;
+        Ok(config)
+    }
+}
+
+/// Find `bolo.toml` in `start` or its nearest ancestor.
+pub fn discover(start: &Path) -> Option<PathBuf> {
+    let start = crate::api::fs::canonicalize(start).ok()?;
+    let dir = if start.is_file() {
+        start.parent()?
+    } else {
+        &start
+    };
+    dir.ancestors()
+        .map(|d| d.join(FILE_NAME))
+        .find(|p| p.is_file())
+}
+
+/// Load `explicit` if given, else the config discovered from `start`, else defaults.
+pub fn resolve(explicit: Option<&Path>, start: &Path) -> Result<Config, BoloError> {
+    match explicit.map(Path::to_path_buf).or_else(|| discover(start)) {
+        Some(path) => Config::load(&path),
+        None => Ok(Config::default()),
+    }
+}
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn load_watchlists() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(FILE_NAME);
+        fs::write(
+            &path,
+            "[watchlists]\nnetwork = [\"requests.*\", \"reqwest::*\"]\n",
+        )
+        .unwrap();
+        let cfg = Config::load(&path).unwrap();
+        assert_eq!(cfg.watchlists["network"], vec!["requests.*", "reqwest::*"]);
+    }
+
+    #[test]
+    fn empty_file_is_default() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(FILE_NAME);
+        fs::write(&path, "").unwrap();
+        assert!(Config::load(&path).unwrap().watchlists.is_empty());
+    }
+
+    #[test]
+    fn load_comment_policy() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(FILE_NAME);
+        fs::write(&path, "comments = \"attach-to-next\"\n").unwrap();
+        let cfg = Config::load(&path).unwrap();
+        assert_eq!(cfg.comments, Some(CommentPolicy::AttachToNext));
+    }
+
+    #[test]
+    fn unknown_comment_policy_errors() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(FILE_NAME);
+        fs::write(&path, "comments = \"hoist\"\n").unwrap();
+        assert!(Config::load(&path).is_err());
+    }
+
+    #[test]
+    fn unknown_keys_ignored() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(FILE_NAME);
+        fs::write(&path, "future = 1\n").unwrap();
+        assert!(Config::load(&path).is_ok());
+    }
+
+    #[test]
+    fn invalid_toml_errors() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(FILE_NAME);
+        fs::write(&path, "[watchlists\n").unwrap();
+        let err = Config::load(&path).unwrap_err();
+        assert!(matches!(err, BoloError::Config { .. }));
+    }
+
+    #[test]
+    fn discover_from_ancestor() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join("src/api")).unwrap();
+        fs::write(dir.path().join(FILE_NAME), "").unwrap();
+        let found = discover(&dir.path().join("src/api")).unwrap();
+        assert_eq!(found, dir.path().canonicalize().unwrap().join(FILE_NAME));
+    }
+
+    #[test]
+    fn discover_from_file() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join(FILE_NAME), "").unwrap();
+        fs::write(dir.path().join("main.rs"), "").unwrap();
+        assert!(discover(&dir.path().join("main.rs")).is_some());
+    }
+
+    #[test]
+    fn resolve_explicit_missing_errors() {
+        let dir = TempDir::new().unwrap();
+        let missing = dir.path().join("nope.toml");
+        assert!(resolve(Some(&missing), dir.path()).is_err());
+    }
+
+    #[test]
+    fn load_rules() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(FILE_NAME);
+        fs::write(&path, "[rules]\nforbid = [\"eval\", \"os.system\"]\n").unwrap();
+        let config = Config::load(&path).unwrap();
+        assert_eq!(config.rules.forbid, vec!["eval", "os.system"]);
+    }
+
+    #[test]
+    fn load_layers() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(FILE_NAME);
+        fs::write(
+            &path,
+            "[[rules.layers]]\nname = \"domain\"\npaths = [\"domain/**\"]\n\
+             [[rules.layers]]\nname = \"ui\"\npaths = [\"ui/**\"]\nmay_use = [\"domain\"]\n",
+        )
+        .unwrap();
+        let config = Config::load(&path).unwrap();
+        assert_eq!(config.rules.layers.len(), 2);
+        assert_eq!(config.rules.layers[1].may_use, vec!["domain"]);
+    }
+
+    #[test]
+    fn unknown_layer_errors() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(FILE_NAME);
+        fs::write(
+            &path,
+            "[[rules.layers]]\nname = \"ui\"\npaths = [\"ui/**\"]\nmay_use = [\"domian\"]\n",
+        )
+        .unwrap();
+        let err = Config::load(&path).unwrap_err();
+        assert!(err.to_string().contains("unknown layer `domian`"));
+    }
+
+    #[test]
+    fn load_coverage_thresholds() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(FILE_NAME);
+        fs::write(&path, "[rules]\nmin_doc_coverage = 0.8\n").unwrap();
+        let config = Config::load(&path).unwrap();
+        assert_eq!(config.rules.min_doc_coverage, Some(0.8));
+        fs::write(&path, "[rules]\nmin_comment_ratio = 80\n").unwrap();
+        assert!(Config::load(&path).is_err());
+    }
+
+    #[test]
+    fn load_link_template() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(FILE_NAME);
+        fs::write(
+            &path,
+            "link_template = \"https://x/{commit}/{path}#L{start}\"\n",
+        )
+        .unwrap();
+        assert!(Config::load(&path).unwrap().link_template.is_some());
+        fs::write(&path, "link_template = \"https://x/{file}\"\n").unwrap();
+        let err = Config::load(&path).unwrap_err();
+        assert!(err.to_string().contains("unknown placeholder `{file}`"));
+    }
+
+    #[test]
+    fn load_labels() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(FILE_NAME);
+        fs::write(
+            &path,
+            "[labels]\nnode = \"{kind}:{qualified_name} ({lines}L)\"\ntooltip = \"{file}:{line}\"\n",
+        )
+        .unwrap();
+        let labels = Config::load(&path).unwrap().labels;
+        assert!(labels.node.is_some() && labels.tooltip.is_some());
+        fs::write(&path, "[labels]\nnode = \"{commit}\"\n").unwrap();
+        let err = Config::load(&path).unwrap_err();
+        assert!(err.to_string().contains("unknown placeholder `{commit}`"));
+    }
+
+    #[test]
+    fn load_redact() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(FILE_NAME);
+        fs::write(&path, "[redact]\npaths = [\"src/phi/\"]\n").unwrap();
+        let config = Config::load(&path).unwrap();
+        assert!(config.redact.hides_path("src/phi/records.py"));
+        assert!(config.redact.symbols.is_empty());
+        assert!(Config::default().redact.is_empty());
+    }
+}
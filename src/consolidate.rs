@@ -1,9 +1,15 @@
+use crate::analysis::{Analysis, Warning, WarningKind};
 use crate::api::fs;
-use crate::api::tree_sitter::{Lang, Syntax};
+use crate::api::tree_sitter::{Lang, Options, Syntax};
+use crate::cache::Cache;
 use crate::clean;
 use crate::error::BoloError;
+use crate::pipeline::Context;
 use rayon::prelude::*;
+use std::borrow::Cow;
+use std::collections::HashSet;
 use std::path::Path;
+use std::sync::mpsc;
 
 /// Parse and clean files in the immediate directory (non-recursive).
 pub fn folder(
@@ -11,26 +17,14 @@ pub fn folder(
     ext: &str,
     no_ignore: bool,
     lang: &(dyn Lang + Sync),
-) -> Result<Vec<Vec<Syntax>>, BoloError> {
-    let files: Vec<_> = fs::walk_dir(root, ext, no_ignore)?
+    opts: &Options,
+) -> Result<Analysis, BoloError> {
+    let files = fs::walk_dir(root, ext, no_ignore)?
         .into_iter()
         .filter(|f| f.rel_path.components().count() == 1)
         .collect();
 
-    files
-        .par_iter()
-        .map(|file| -> Result<_, BoloError> {
-            let source = file.read()?;
-            let mut parser = lang.get_parser();
-            let ast = lang
-                .parse(&mut parser, &source)
-                .map_err(|e| BoloError::Parse {
-                    file: file.rel_path.display().to_string(),
-                    reason: e.to_string(),
-                })?;
-            Ok(clean::clean(&file.rel_path, &source, ast))
-        })
-        .collect::<Result<Vec<_>, _>>()
+    sharded(files, lang, opts)
 }
 
 /// Parse and clean all files under a directory tree (recursive).
@@ -39,23 +33,226 @@ pub fn recursive(
     ext: &str,
     no_ignore: bool,
     lang: &(dyn Lang + Sync),
-) -> Result<Vec<Vec<Syntax>>, BoloError> {
-    let files = fs::walk_dir(root, ext, no_ignore)?;
+    opts: &Options,
+) -> Result<Analysis, BoloError> {
+    sharded(fs::walk_dir(root, ext, no_ignore)?, lang, opts)
+}
 
-    files
+/// [`list`] of the walked `files` in [`Options::shard`], whose passes still
+/// see every walked file.
+fn sharded(
+    files: Vec<fs::File>,
+    lang: &(dyn Lang + Sync),
+    opts: &Options,
+) -> Result<Analysis, BoloError> {
+    let Some(shard) = opts.shard else {
+        return list(&files, lang, opts);
+    };
+    let opts = Options {
+        known: Some(paths(&files, opts)),
+        ..opts.clone()
+    };
+    let files: Vec<_> = files.into_iter().filter(|f| shard.contains(f)).collect();
+    list(&files, lang, &opts)
+}
+
+/// Parse and clean an explicit file list (e.g. from `--files-from`).
+///
+/// Oversized files, invalid UTF-8 and grammar errors become warnings rather
+/// than failing the run. With [`Options::cache`], files whose contents are
+/// cached are not parsed again, and parsed files are stored. Each file's
+/// nodes then go through [`Options::passes`], which see [`Options::known`]
+/// as the whole tree, or else the paths in `files`.
+pub fn list(
+    files: &[fs::File],
+    lang: &(dyn Lang + Sync),
+    opts: &Options,
+) -> Result<Analysis, BoloError> {
+    let cache = opts.cache.as_deref().map(Cache::open).transpose()?;
+    let known = known(files, opts);
+    let results = files
         .par_iter()
-        .map(|file| -> Result<_, BoloError> {
-            let source = file.read()?;
-            let mut parser = lang.get_parser();
-            let ast = lang
-                .parse(&mut parser, &source)
-                .map_err(|e| BoloError::Parse {
-                    file: file.rel_path.display().to_string(),
-                    reason: e.to_string(),
-                })?;
-            Ok(clean::clean(&file.rel_path, &source, ast))
-        })
-        .collect::<Result<Vec<_>, _>>()
+        .map(|file| parse(file, lang, opts, cache.as_ref(), Some(&known)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut analysis = Analysis::default();
+    for (nodes, warnings) in results {
+        analysis.files.extend(nodes);
+        analysis.warnings.extend(warnings);
+    }
+    Ok(analysis)
+}
+
+/// Parse and clean `bytes` as the one file `name` (relative to the root, as
+/// with [`list`]), e.g. an unsaved editor buffer read from stdin. Passes
+/// see it among [`Options::known`], so its imports and module path resolve
+/// as they would in a run over the whole tree.
+pub fn buffer(
+    name: &Path,
+    bytes: Vec<u8>,
+    lang: &(dyn Lang + Sync),
+    opts: &Options,
+) -> Result<Analysis, BoloError> {
+    let path = fs::portable(name, opts.root_label.as_deref());
+    let cache = opts.cache.as_deref().map(Cache::open).transpose()?;
+    let (source, lossy) = fs::decode(bytes);
+    let mut known = opts.known.clone().unwrap_or_default();
+    known.insert(path.clone());
+    let (nodes, warnings) = analyze(
+        &path,
+        &source,
+        lossy,
+        lang,
+        opts,
+        cache.as_ref(),
+        Some(&known),
+    )?;
+    Ok(Analysis {
+        files: vec![nodes],
+        warnings,
+        ..Analysis::default()
+    })
+}
+
+/// Files parsed but not yet written that [`stream`] holds at most.
+const STREAM_BOUND: usize = 64;
+
+/// Like [`list`], but hands each file's nodes and warnings to `write` as
+/// soon as the file is parsed, in the order files finish, so the tree is
+/// never held in memory whole. `write` runs on its own thread behind a
+/// bounded channel: parsers wait while it falls behind.
+pub fn stream(
+    files: &[fs::File],
+    lang: &(dyn Lang + Sync),
+    opts: &Options,
+    mut write: impl FnMut(Option<Vec<Syntax<'static>>>, Vec<Warning>) -> Result<(), BoloError> + Send,
+) -> Result<(), BoloError> {
+    let cache = opts.cache.as_deref().map(Cache::open).transpose()?;
+    let known = known(files, opts);
+    let (tx, rx) = mpsc::sync_channel(STREAM_BOUND);
+    std::thread::scope(|scope| {
+        let writer = scope.spawn(move || {
+            for (nodes, warnings) in rx {
+                write(nodes, warnings)?;
+            }
+            Ok(())
+        });
+        let parsed = files.par_iter().try_for_each_with(tx, |tx, file| {
+            let (nodes, warnings) = parse(file, lang, opts, cache.as_ref(), Some(&known))?;
+            // The writer only hangs up on an error, which is returned below.
+            tx.send((nodes, warnings)).map_err(|e| BoloError::Write {
+                path: "-".into(),
+                reason: e.to_string(),
+            })
+        });
+        let written = writer
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+        written.and(parsed)
+    })
+}
+
+/// [`Options::known`], or the reported paths of `files` without it.
+fn known<'a>(files: &[fs::File], opts: &'a Options) -> Cow<'a, HashSet<String>> {
+    match &opts.known {
+        Some(known) => Cow::Borrowed(known),
+        None => Cow::Owned(paths(files, opts)),
+    }
+}
+
+/// The reported paths of `files`.
+fn paths(files: &[fs::File], opts: &Options) -> HashSet<String> {
+    files
+        .iter()
+        .map(|f| fs::portable(&f.rel_path, opts.root_label.as_deref()))
+        .collect()
+}
+
+/// Read, parse and clean one file, or only warn about it: see [`list`].
+fn parse(
+    file: &fs::File,
+    lang: &(dyn Lang + Sync),
+    opts: &Options,
+    cache: Option<&Cache>,
+    known: Option<&HashSet<String>>,
+) -> Result<(Option<Vec<Syntax<'static>>>, Vec<Warning>), BoloError> {
+    let path = fs::portable(&file.rel_path, opts.root_label.as_deref());
+    let mut warnings = Vec::new();
+    if file.rel_path.to_str().is_none() {
+        warnings.push(Warning::new(
+            WarningKind::LossyPath,
+            &path,
+            "path is not valid UTF-8; replaced with U+FFFD",
+        ));
+    }
+    let size = file.size();
+    if size > fs::MAX_FILE_SIZE {
+        let message = format!(
+            "file is {} MB, exceeds {} MB limit",
+            size / (1024 * 1024),
+            fs::MAX_FILE_SIZE / (1024 * 1024)
+        );
+        warnings.push(Warning::new(WarningKind::SkippedFile, &path, message));
+        return Ok((None, warnings));
+    }
+
+    let (source, lossy) = file.read_lossy()?;
+    let (nodes, more) = analyze(&path, &source, lossy, lang, opts, cache, known)?;
+    warnings.extend(more);
+    Ok((Some(nodes), warnings))
+}
+
+/// Parse and clean `source`, read from `path`, and run the passes over it;
+/// `lossy` if decoding it replaced bytes.
+fn analyze(
+    path: &str,
+    source: &str,
+    lossy: bool,
+    lang: &(dyn Lang + Sync),
+    opts: &Options,
+    cache: Option<&Cache>,
+    known: Option<&HashSet<String>>,
+) -> Result<(Vec<Syntax<'static>>, Vec<Warning>), BoloError> {
+    let cx = Context {
+        source,
+        known,
+        options: opts,
+    };
+    let cached = cache.map(|c| (c, Cache::key(path, source, opts)));
+    if let Some((c, key)) = &cached
+        && let Some((mut nodes, mut warnings)) = c.get(key)
+    {
+        warnings.extend(opts.passes.run(&mut nodes, &cx));
+        return Ok((nodes, warnings));
+    }
+    let mut warnings = Vec::new();
+    if lossy {
+        warnings.push(Warning::new(
+            WarningKind::LossyDecode,
+            path,
+            "invalid UTF-8 replaced with U+FFFD",
+        ));
+    }
+
+    let mut parser = lang.get_parser();
+    let (ast, errors) =
+        lang.parse_checked(&mut parser, source, opts)
+            .map_err(|e| BoloError::Parse {
+                file: path.to_string(),
+                reason: e.to_string(),
+            })?;
+    warnings.extend(errors.into_iter().map(|span| Warning {
+        span: Some(span),
+        ..Warning::new(WarningKind::GrammarError, path, "syntax error")
+    }));
+
+    let nodes = clean::prepare(path, source, ast);
+    let mut nodes: Vec<_> = nodes.into_iter().map(Syntax::into_owned).collect();
+    if let Some((c, key)) = &cached {
+        c.put(key, &nodes, &warnings);
+    }
+    warnings.extend(opts.passes.run(&mut nodes, &cx));
+    Ok((nodes, warnings))
 }
 
 // ── Tests ──────────────────────────────────────────────────────────
@@ -63,9 +260,9 @@ pub fn recursive(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::api::tree_sitter::ASTNode;
     use crate::api::tree_sitter::py::Python;
     use crate::api::tree_sitter::rs::Rust;
+    use crate::api::tree_sitter::{ASTNode, Syntax};
     use tempfile::TempDir;
 
     fn file_paths(result: &[Vec<Syntax>]) -> Vec<String> {
@@ -73,7 +270,7 @@ mod tests {
             .iter()
             .filter_map(|file_nodes| {
                 file_nodes.first().and_then(|s| match &s.node {
-                    ASTNode::File(f) => Some(f.path.clone()),
+                    ASTNode::File(f) => Some(f.path.to_string()),
                     _ => None,
                 })
             })
@@ -89,13 +286,91 @@ mod tests {
         std::fs::write(dir.path().join("top.py"), "def foo(): pass\n").unwrap();
         std::fs::write(dir.path().join("sub/deep.py"), "def bar(): pass\n").unwrap();
 
-        let result = recursive(dir.path(), "py", false, &Python).unwrap();
+        let result = recursive(dir.path(), "py", false, &Python, &Options::default())
+            .unwrap()
+            .files;
         assert_eq!(result.len(), 2);
         let paths = file_paths(&result);
         assert!(paths.iter().any(|p| p.contains("top.py")));
         assert!(paths.iter().any(|p| p.contains("deep.py")));
     }
 
+    #[test]
+    fn recursive_paths_are_portable_and_labelled() {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("sub")).unwrap();
+        std::fs::write(dir.path().join("sub/deep.py"), "def bar(): pass\n").unwrap();
+
+        let result = recursive(dir.path(), "py", false, &Python, &Options::default())
+            .unwrap()
+            .files;
+        assert_eq!(file_paths(&result), vec!["sub/deep.py"]);
+
+        let opts = Options {
+            root_label: Some("svc".into()),
+            ..Options::default()
+        };
+        let result = recursive(dir.path(), "py", false, &Python, &opts)
+            .unwrap()
+            .files;
+        assert_eq!(file_paths(&result), vec!["svc/sub/deep.py"]);
+    }
+
+    #[test]
+    fn recursive_shards_cover_all_files_once() {
+        let dir = TempDir::new().unwrap();
+        for i in 0..12 {
+            std::fs::write(dir.path().join(format!("m{i}.py")), "x = 1\n").unwrap();
+        }
+        let mut seen = Vec::new();
+        for index in 1..=3 {
+            let opts = Options {
+                shard: Some(fs::Shard { index, count: 3 }),
+                ..Options::default()
+            };
+            seen.extend(file_paths(
+                &recursive(dir.path(), "py", false, &Python, &opts)
+                    .unwrap()
+                    .files,
+            ));
+        }
+        seen.sort();
+        assert_eq!(seen.len(), 12);
+        seen.dedup();
+        assert_eq!(seen.len(), 12);
+    }
+
+    #[test]
+    fn merged_shards_match_a_single_run() {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("app")).unwrap();
+        std::fs::write(dir.path().join("app/__init__.py"), "").unwrap();
+        std::fs::write(dir.path().join("app/models.py"), "class Req: pass\n").unwrap();
+        for i in 0..8 {
+            let source = "from .models import Req\ndef f():\n    Req()\n";
+            std::fs::write(dir.path().join(format!("app/v{i}.py")), source).unwrap();
+        }
+        let whole = recursive(dir.path(), "py", false, &Python, &Options::default()).unwrap();
+
+        let mut merged = Vec::new();
+        for index in 1..=3 {
+            let opts = Options {
+                shard: Some(fs::Shard { index, count: 3 }),
+                ..Options::default()
+            };
+            let shard = recursive(dir.path(), "py", false, &Python, &opts).unwrap();
+            assert!(shard.warnings.is_empty());
+            merged.extend(shard.files);
+        }
+        merged.sort_by_key(|nodes| file_paths(std::slice::from_ref(nodes)));
+        assert_eq!(
+            serde_json::to_value(&merged).unwrap(),
+            serde_json::to_value(&whole.files).unwrap()
+        );
+        let json = serde_json::to_string(&merged).unwrap();
+        assert!(json.contains("app.models.Req"));
+    }
+
     #[test]
     fn recursive_finds_all_rs_files() {
         let dir = TempDir::new().unwrap();
@@ -103,14 +378,18 @@ mod tests {
         std::fs::write(dir.path().join("main.rs"), "fn main() {}\n").unwrap();
         std::fs::write(dir.path().join("sub/lib.rs"), "fn lib() {}\n").unwrap();
 
-        let result = recursive(dir.path(), "rs", false, &Rust).unwrap();
+        let result = recursive(dir.path(), "rs", false, &Rust, &Options::default())
+            .unwrap()
+            .files;
         assert_eq!(result.len(), 2);
     }
 
     #[test]
     fn recursive_empty_dir() {
         let dir = TempDir::new().unwrap();
-        let result = recursive(dir.path(), "py", false, &Python).unwrap();
+        let result = recursive(dir.path(), "py", false, &Python, &Options::default())
+            .unwrap()
+            .files;
         assert!(result.is_empty());
     }
 
@@ -120,7 +399,9 @@ mod tests {
         std::fs::write(dir.path().join("a.py"), "x = 1\n").unwrap();
         std::fs::write(dir.path().join("b.py"), "y = 2\n").unwrap();
 
-        let result = recursive(dir.path(), "py", false, &Python).unwrap();
+        let result = recursive(dir.path(), "py", false, &Python, &Options::default())
+            .unwrap()
+            .files;
         for file_nodes in &result {
             assert!(matches!(&file_nodes[0].node, ASTNode::File(_)));
         }
@@ -135,7 +416,9 @@ mod tests {
         std::fs::write(dir.path().join("top.py"), "def foo(): pass\n").unwrap();
         std::fs::write(dir.path().join("sub/deep.py"), "def bar(): pass\n").unwrap();
 
-        let result = folder(dir.path(), "py", false, &Python).unwrap();
+        let result = folder(dir.path(), "py", false, &Python, &Options::default())
+            .unwrap()
+            .files;
         assert_eq!(result.len(), 1);
         let paths = file_paths(&result);
         assert!(paths[0].contains("top.py"));
@@ -144,7 +427,9 @@ mod tests {
     #[test]
     fn folder_empty_dir() {
         let dir = TempDir::new().unwrap();
-        let result = folder(dir.path(), "py", false, &Python).unwrap();
+        let result = folder(dir.path(), "py", false, &Python, &Options::default())
+            .unwrap()
+            .files;
         assert!(result.is_empty());
     }
 
@@ -153,7 +438,9 @@ mod tests {
         let dir = TempDir::new().unwrap();
         std::fs::write(dir.path().join("main.rs"), "fn main() {}").unwrap();
 
-        let result = folder(dir.path(), "py", false, &Python).unwrap();
+        let result = folder(dir.path(), "py", false, &Python, &Options::default())
+            .unwrap()
+            .files;
         assert!(result.is_empty());
     }
 
@@ -164,7 +451,9 @@ mod tests {
         let dir = TempDir::new().unwrap();
         std::fs::write(dir.path().join("app.py"), "def greet():\n    print('hi')\n").unwrap();
 
-        let result = recursive(dir.path(), "py", false, &Python).unwrap();
+        let result = recursive(dir.path(), "py", false, &Python, &Options::default())
+            .unwrap()
+            .files;
         let file_nodes = &result[0];
         // File, then maybe Comment, then Function
         let has_greet = file_nodes.iter().any(|s| match &s.node {
@@ -179,10 +468,231 @@ mod tests {
         let dir = TempDir::new().unwrap();
         std::fs::write(dir.path().join("c.py"), "# hello\ndef f(): pass\n").unwrap();
 
-        let result = recursive(dir.path(), "py", false, &Python).unwrap();
+        let result = recursive(dir.path(), "py", false, &Python, &Options::default())
+            .unwrap()
+            .files;
         let file_nodes = &result[0];
         // [File, Comment, Function] — comment is second
         assert!(matches!(&file_nodes[0].node, ASTNode::File(_)));
         assert!(matches!(&file_nodes[1].node, ASTNode::Comment));
     }
+
+    // ── warnings ──
+
+    #[test]
+    fn clean_tree_has_no_warnings() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("a.py"), "def f(): pass\n").unwrap();
+        let result = recursive(dir.path(), "py", false, &Python, &Options::default()).unwrap();
+        assert!(result.warnings.is_empty());
+    }
+
+    #[test]
+    fn lossy_decode_warns_and_still_parses() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("a.py"), b"x = '\xff'\ndef f(): pass\n").unwrap();
+        let result = recursive(dir.path(), "py", false, &Python, &Options::default()).unwrap();
+        assert_eq!(result.files.len(), 1);
+        assert_eq!(result.warnings.len(), 1);
+        assert_eq!(result.warnings[0].kind, WarningKind::LossyDecode);
+        assert_eq!(result.warnings[0].file, "a.py");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn non_utf8_path_warns_and_still_parses() {
+        use std::os::unix::ffi::OsStrExt;
+        let dir = TempDir::new().unwrap();
+        let name = std::ffi::OsStr::from_bytes(b"\xfe.py");
+        std::fs::write(dir.path().join(name), "def f(): pass\n").unwrap();
+        let result = recursive(dir.path(), "py", false, &Python, &Options::default()).unwrap();
+        assert_eq!(file_paths(&result.files), ["\u{FFFD}.py"]);
+        assert_eq!(result.warnings.len(), 1);
+        assert_eq!(result.warnings[0].kind, WarningKind::LossyPath);
+        assert_eq!(result.warnings[0].file, "\u{FFFD}.py");
+    }
+
+    #[test]
+    fn grammar_error_warns_with_span() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("a.py"), "def f(): pass\ndef g(:\n").unwrap();
+        let result = recursive(dir.path(), "py", false, &Python, &Options::default()).unwrap();
+        assert_eq!(result.files.len(), 1);
+        let w = &result.warnings[0];
+        assert_eq!(w.kind, WarningKind::GrammarError);
+        assert_eq!(w.span.unwrap().start_line, 2);
+    }
+
+    #[test]
+    fn oversized_file_skipped_with_warning() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("a.py"), "x = 1\n").unwrap();
+        let big = std::fs::File::create(dir.path().join("big.py")).unwrap();
+        big.set_len(fs::MAX_FILE_SIZE + 1).unwrap();
+        let result = recursive(dir.path(), "py", false, &Python, &Options::default()).unwrap();
+        assert_eq!(file_paths(&result.files), vec!["a.py"]);
+        assert_eq!(result.warnings.len(), 1);
+        assert_eq!(result.warnings[0].kind, WarningKind::SkippedFile);
+        assert_eq!(result.warnings[0].file, "big.py");
+    }
+
+    #[test]
+    fn unresolved_rust_module_warns() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("lib.rs"), "mod here;\nmod gone;\n").unwrap();
+        std::fs::write(dir.path().join("here.rs"), "").unwrap();
+        let result = recursive(dir.path(), "rs", false, &Rust, &Options::default()).unwrap();
+        assert_eq!(result.warnings.len(), 1);
+        assert_eq!(result.warnings[0].kind, WarningKind::UnresolvedImport);
+        assert!(result.warnings[0].message.contains("`gone`"));
+    }
+
+    #[test]
+    fn cached_run_matches_and_sees_changes() {
+        let dir = TempDir::new().unwrap();
+        let cache = TempDir::new().unwrap();
+        let src = dir.path().join("src");
+        std::fs::create_dir_all(&src).unwrap();
+        std::fs::write(src.join("a.py"), "def f(): pass\n").unwrap();
+        std::fs::write(src.join("b.py"), "def g(): pass\n").unwrap();
+        let opts = Options {
+            cache: Some(cache.path().to_path_buf()),
+            ..Options::default()
+        };
+        let names = |files: &[Vec<Syntax>]| -> Vec<String> {
+            files
+                .iter()
+                .flat_map(|nodes| nodes.iter())
+                .filter_map(|s| match &s.node {
+                    ASTNode::Function(f) => Some(f.name.to_string()),
+                    _ => None,
+                })
+                .collect()
+        };
+
+        let first = recursive(&src, "py", false, &Python, &opts).unwrap();
+        let second = recursive(&src, "py", false, &Python, &opts).unwrap();
+        assert_eq!(names(&second.files), ["f", "g"]);
+        assert_eq!(file_paths(&second.files), file_paths(&first.files));
+
+        std::fs::write(src.join("b.py"), "def h(): pass\n").unwrap();
+        let third = recursive(&src, "py", false, &Python, &opts).unwrap();
+        assert_eq!(names(&third.files), ["f", "h"]);
+    }
+
+    #[test]
+    fn files_carry_module_paths() {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("pkg")).unwrap();
+        std::fs::write(dir.path().join("pkg/__init__.py"), "").unwrap();
+        std::fs::write(dir.path().join("pkg/net.py"), "def send(): pass\n").unwrap();
+        let result = recursive(dir.path(), "py", false, &Python, &Options::default()).unwrap();
+        let modules: Vec<Option<&str>> = result
+            .files
+            .iter()
+            .map(|nodes| match &nodes[0].node {
+                ASTNode::File(f) => f.module.as_deref(),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(modules, [Some("pkg"), Some("pkg.net")]);
+    }
+
+    // ── buffer ──
+
+    #[test]
+    fn buffer_is_one_file_under_its_name() {
+        let opts = Options {
+            root_label: Some("svc".into()),
+            ..Options::default()
+        };
+        let source = b"# hi\ndef f():\n    g(\n".to_vec();
+        let result = buffer(Path::new("pkg/foo.py"), source, &Python, &opts).unwrap();
+        assert_eq!(file_paths(&result.files), ["svc/pkg/foo.py"]);
+        assert!(matches!(&result.files[0][1].node, ASTNode::Comment));
+        assert_eq!(result.warnings.len(), 1);
+        assert_eq!(result.warnings[0].kind, WarningKind::GrammarError);
+        assert_eq!(result.warnings[0].file, "svc/pkg/foo.py");
+    }
+
+    #[test]
+    fn buffer_resolves_against_the_walked_tree() {
+        let opts = Options {
+            known: Some(HashSet::from(["here.rs".to_string()])),
+            ..Options::default()
+        };
+        let source = b"mod here;\nmod gone;\n".to_vec();
+        let result = buffer(Path::new("lib.rs"), source, &Rust, &opts).unwrap();
+        assert_eq!(result.warnings.len(), 1);
+        assert!(result.warnings[0].message.contains("`gone`"));
+        let ASTNode::File(f) = &result.files[0][0].node else {
+            unreachable!()
+        };
+        assert_eq!(f.module.as_deref(), Some("crate"));
+    }
+
+    // ── stream ──
+
+    #[test]
+    fn stream_matches_list() {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("pkg")).unwrap();
+        std::fs::write(dir.path().join("pkg/__init__.py"), "").unwrap();
+        for i in 0..100 {
+            let source = format!("def f{i}():\n    f{}()\n", i + 1);
+            std::fs::write(dir.path().join(format!("pkg/m{i}.py")), source).unwrap();
+        }
+        let files = fs::walk_dir(dir.path(), "py", false).unwrap();
+        let listed = list(&files, &Python, &Options::default()).unwrap();
+
+        let mut streamed = Vec::new();
+        stream(&files, &Python, &Options::default(), |nodes, warnings| {
+            assert!(warnings.is_empty());
+            streamed.extend(nodes);
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(streamed.len(), 101);
+        streamed.sort_by_key(|nodes| file_paths(std::slice::from_ref(nodes)));
+        assert_eq!(
+            serde_json::to_value(&streamed).unwrap(),
+            serde_json::to_value(&listed.files).unwrap()
+        );
+    }
+
+    #[test]
+    fn stream_stops_on_writer_error() {
+        let dir = TempDir::new().unwrap();
+        for i in 0..200 {
+            std::fs::write(dir.path().join(format!("m{i}.py")), "x = 1\n").unwrap();
+        }
+        let files = fs::walk_dir(dir.path(), "py", false).unwrap();
+        let mut calls = 0;
+        let err = stream(&files, &Python, &Options::default(), |_, _| {
+            calls += 1;
+            Err(BoloError::Write {
+                path: "out.ndjson".into(),
+                reason: "disk full".into(),
+            })
+        })
+        .unwrap_err();
+        assert!(err.to_string().contains("disk full"));
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn stream_warns_about_unresolved_modules() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("lib.rs"), "mod here;\nmod gone;\n").unwrap();
+        std::fs::write(dir.path().join("here.rs"), "").unwrap();
+        let files = fs::walk_dir(dir.path(), "rs", false).unwrap();
+        let mut all = Vec::new();
+        stream(&files, &Rust, &Options::default(), |_, warnings| {
+            all.extend(warnings);
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(all.len(), 1);
+        assert!(all[0].message.contains("`gone`"));
+    }
 }
@@ -0,0 +1,329 @@
+use crate::api::fs;
+use crate::api::tree_sitter::{ASTNode, Syntax};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+
+/// Metric set on the files and definitions of a build script, naming its
+/// crate.
+pub const BUILD_SCRIPT: &str = "build_script";
+
+/// Metric set on the files and definitions of a proc-macro crate, naming it.
+pub const PROC_MACRO: &str = "proc_macro";
+
+/// A package found by its `Cargo.toml`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Crate {
+    pub name: String,
+    /// Directory of the manifest as a File path prefix, `""` at the top.
+    pub dir: String,
+    /// File path of the build script: `build = "..."`, else `build.rs`
+    /// beside the manifest if it exists.
+    pub build: Option<String>,
+    /// `[lib] proc-macro = true`.
+    pub proc_macro: bool,
+    /// Names of its normal, dev and build dependencies.
+    pub dependencies: Vec<String>,
+    /// `[features]`: each feature and what it enables.
+    pub features: BTreeMap<String, Vec<String>>,
+}
+
+/// A crate's dependency on code the compiler runs for it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CrateEdge {
+    /// Name of the depending crate.
+    pub from: String,
+    /// The build script's file path, or the proc-macro crate's name.
+    pub to: String,
+    pub kind: EdgeKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub enum EdgeKind {
+    BuildScript,
+    ProcMacro,
+}
+
+#[derive(Deserialize)]
+struct Manifest {
+    package: Option<Package>,
+    #[serde(default)]
+    lib: Lib,
+    #[serde(default)]
+    dependencies: BTreeMap<String, toml::Value>,
+    #[serde(default, rename = "dev-dependencies")]
+    dev_dependencies: BTreeMap<String, toml::Value>,
+    #[serde(default, rename = "build-dependencies")]
+    build_dependencies: BTreeMap<String, toml::Value>,
+    #[serde(default)]
+    features: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Deserialize)]
+struct Package {
+    name: String,
+    /// A path, or `false` to turn off the `build.rs` default.
+    build: Option<toml::Value>,
+}
+
+#[derive(Default, Deserialize)]
+struct Lib {
+    #[serde(default, rename = "proc-macro")]
+    proc_macro: bool,
+}
+
+/// Packages under `dir`, from each `Cargo.toml` the walk finds. Manifests
+/// that do not parse or have no `[package]` (virtual workspaces) are
+/// skipped. `known` holds the analyzed File paths, which `label` prefixes.
+pub fn discover(dir: &Path, label: Option<&str>, known: &HashSet<&str>) -> Vec<Crate> {
+    let Ok(tomls) = fs::walk_dir(dir, "toml", false) else {
+        return Vec::new();
+    };
+    let mut out: Vec<Crate> = tomls
+        .iter()
+        .filter(|f| f.path.file_name().is_some_and(|n| n == "Cargo.toml"))
+        .filter_map(|f| {
+            let text = std::fs::read_to_string(&f.path).ok()?;
+            let rel = fs::portable(f.rel_path.parent()?, label);
+            parse(&text, &rel, known)
+        })
+        .collect();
+    out.sort_by(|a, b| a.dir.cmp(&b.dir));
+    out
+}
+
+/// The package a manifest at `dir` describes.
+fn parse(text: &str, dir: &str, known: &HashSet<&str>) -> Option<Crate> {
+    let m: Manifest = toml::from_str(text).ok()?;
+    let package = m.package?;
+    let under = |file: &str| match dir {
+        "" => file.to_string(),
+        d => format!("{d}/{file}"),
+    };
+    let build = match package.build {
+        Some(toml::Value::String(path)) => Some(under(path.trim_start_matches("./"))),
+        Some(toml::Value::Boolean(false)) => None,
+        _ => Some(under("build.rs")).filter(|b| known.contains(b.as_str())),
+    };
+    let dependencies = [m.dependencies, m.dev_dependencies, m.build_dependencies]
+        .into_iter()
+        .flatten()
+        .map(
+            |(key, spec)| match spec.get("package").and_then(|p| p.as_str()) {
+                Some(renamed) => renamed.to_string(),
+                None => key,
+            },
+        )
+        .collect();
+    Some(Crate {
+        name: package.name,
+        dir: dir.to_string(),
+        build,
+        proc_macro: m.lib.proc_macro,
+        dependencies,
+        features: m.features,
+    })
+}
+
+/// Tag build scripts with [`BUILD_SCRIPT`] and the files of proc-macro
+/// crates with [`PROC_MACRO`], and list each crate's edges to its build
+/// script and to the proc-macro crates among `crates` it depends on.
+pub fn link(files: &mut [Vec<Syntax>], crates: &[Crate]) -> Vec<CrateEdge> {
+    for nodes in files.iter_mut() {
+        let Some(ASTNode::File(f)) = nodes.first().map(|s| &s.node) else {
+            continue;
+        };
+        let path = f.path.to_string();
+        if let Some(c) = crates.iter().find(|c| c.build.as_deref() == Some(&path)) {
+            mark(nodes, BUILD_SCRIPT, &c.name);
+        } else if let Some(c) = owner(crates, &path).filter(|c| c.proc_macro) {
+            mark(nodes, PROC_MACRO, &c.name);
+        }
+    }
+
+    let mut edges = Vec::new();
+    for c in crates {
+        if let Some(build) = &c.build {
+            edges.push(CrateEdge {
+                from: c.name.clone(),
+                to: build.clone(),
+                kind: EdgeKind::BuildScript,
+            });
+        }
+        for dep in &c.dependencies {
+            if crates.iter().any(|p| p.proc_macro && &p.name == dep) {
+                edges.push(CrateEdge {
+                    from: c.name.clone(),
+                    to: dep.clone(),
+                    kind: EdgeKind::ProcMacro,
+                });
+            }
+        }
+    }
+    edges
+}
+
+/// The crate whose directory holds `path` most closely.
+pub fn owner<'a>(crates: &'a [Crate], path: &str) -> Option<&'a Crate> {
+    crates
+        .iter()
+        .filter(|c| {
+            c.dir.is_empty()
+                || path
+                    .strip_prefix(&c.dir)
+                    .is_some_and(|r| r.starts_with('/'))
+        })
+        .max_by_key(|c| c.dir.len())
+}
+
+fn mark(nodes: &mut [Syntax], key: &str, name: &str) {
+    for s in nodes {
+        if matches!(
+            s.node,
+            ASTNode::File(_) | ASTNode::Function(_) | ASTNode::Type(_)
+        ) {
+            s.metadata.set_metric(key, name);
+        }
+        mark(&mut s.contains, key, name);
+    }
+}
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::tree_sitter::analyze;
+    use crate::api::tree_sitter::rs::Rust;
+    use tempfile::TempDir;
+
+    fn write(dir: &TempDir, path: &str, text: &str) {
+        let path = dir.path().join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, text).unwrap();
+    }
+
+    // ── Manifests ──
+
+    #[test]
+    fn parse_reads_build_proc_macro_and_dependencies() {
+        let known = HashSet::from(["app/build.rs"]);
+        let c = parse(
+            "[package]\nname = \"app\"\n[dependencies]\nserde = \"1\"\n\
+             derive = { path = \"../derive\", package = \"app-derive\" }\n\
+             [build-dependencies]\ncc = \"1\"\n",
+            "app",
+            &known,
+        )
+        .unwrap();
+        assert_eq!(c.build.as_deref(), Some("app/build.rs"));
+        assert!(!c.proc_macro);
+        assert_eq!(c.dependencies, ["app-derive", "serde", "cc"]);
+
+        let known = HashSet::new();
+        let c = parse(
+            "[package]\nname = \"d\"\nbuild = \"gen/build.rs\"\n[lib]\nproc-macro = true\n",
+            "",
+            &known,
+        )
+        .unwrap();
+        assert_eq!(c.build.as_deref(), Some("gen/build.rs"));
+        assert!(c.proc_macro);
+
+        let c = parse(
+            "[package]\nname = \"f\"\n[features]\ndefault = [\"std\"]\nstd = []\n",
+            "",
+            &known,
+        )
+        .unwrap();
+        assert_eq!(c.features["default"], ["std"]);
+    }
+
+    #[test]
+    fn parse_skips_missing_and_disabled_build_scripts() {
+        let known = HashSet::from(["build.rs"]);
+        let off = parse("[package]\nname = \"a\"\nbuild = false\n", "", &known).unwrap();
+        assert_eq!(off.build, None);
+        let absent = parse("[package]\nname = \"a\"\n", "", &HashSet::new()).unwrap();
+        assert_eq!(absent.build, None);
+        assert!(parse("[workspace]\nmembers = [\"a\"]\n", "", &known).is_none());
+    }
+
+    #[test]
+    fn discover_finds_workspace_members() {
+        let dir = TempDir::new().unwrap();
+        write(
+            &dir,
+            "Cargo.toml",
+            "[workspace]\nmembers = [\"app\", \"derive\"]\n",
+        );
+        write(&dir, "app/Cargo.toml", "[package]\nname = \"app\"\n");
+        write(&dir, "app/build.rs", "fn main() {}\n");
+        write(
+            &dir,
+            "derive/Cargo.toml",
+            "[package]\nname = \"derive\"\n[lib]\nproc-macro = true\n",
+        );
+        let known = HashSet::from(["app/build.rs"]);
+        let crates = discover(dir.path(), None, &known);
+        let names: Vec<(&str, &str)> = crates
+            .iter()
+            .map(|c| (c.name.as_str(), c.dir.as_str()))
+            .collect();
+        assert_eq!(names, [("app", "app"), ("derive", "derive")]);
+        assert_eq!(crates[0].build.as_deref(), Some("app/build.rs"));
+    }
+
+    // ── Tagging & Edges ──
+
+    #[test]
+    fn link_tags_files_and_records_edges() {
+        let crates = [
+            Crate {
+                name: "app".into(),
+                dir: "app".into(),
+                build: Some("app/build.rs".into()),
+                proc_macro: false,
+                dependencies: vec!["derive".into(), "serde".into()],
+                features: BTreeMap::new(),
+            },
+            Crate {
+                name: "derive".into(),
+                dir: "derive".into(),
+                build: None,
+                proc_macro: true,
+                dependencies: vec![],
+                features: BTreeMap::new(),
+            },
+        ];
+        let mut files = [
+            analyze(&Rust, "app/build.rs", "fn main() {}\n"),
+            analyze(&Rust, "app/src/main.rs", "fn main() {}\n"),
+            analyze(&Rust, "derive/src/lib.rs", "pub fn expand() {}\n"),
+        ];
+        let edges = link(&mut files, &crates);
+        assert_eq!(
+            edges,
+            [
+                CrateEdge {
+                    from: "app".into(),
+                    to: "app/build.rs".into(),
+                    kind: EdgeKind::BuildScript,
+                },
+                CrateEdge {
+                    from: "app".into(),
+                    to: "derive".into(),
+                    kind: EdgeKind::ProcMacro,
+                },
+            ]
+        );
+        let metric = |f: usize, n: usize, key| files[f][n].metadata.metric(key).cloned();
+        assert_eq!(metric(0, 0, BUILD_SCRIPT), Some("app".into()));
+        assert_eq!(metric(0, 1, BUILD_SCRIPT), Some("app".into()));
+        assert_eq!(metric(1, 0, BUILD_SCRIPT), None);
+        assert_eq!(metric(1, 0, PROC_MACRO), None);
+        assert_eq!(metric(2, 1, PROC_MACRO), Some("derive".into()));
+    }
+}
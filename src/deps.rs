@@ -0,0 +1,413 @@
+use crate::api::fs::{self, File};
+use crate::api::tree_sitter::{ASTNode, Syntax};
+use crate::error::BoloError;
+use serde::Deserialize;
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Metric set on files and definitions from installed third-party packages.
+pub const EXTERNAL: &str = "external";
+
+/// Metric set on files and definitions in vendored directories and git
+/// submodules.
+pub const VENDORED: &str = "vendored";
+
+/// Directory names that hold copies of other projects.
+const VENDOR_DIRS: &[&str] = &["vendor", "vendored", "third_party", "third-party"];
+
+/// A directory of installed third-party sources, and the directory its
+/// files are reported under.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Root {
+    pub label: String,
+    pub dir: PathBuf,
+}
+
+/// Where third-party sources for `ext` files analyzed at `path` are
+/// installed, looking up environment variables with `var`.
+///
+/// Python: `site-packages` of a `venv/` or `.venv/` in `path`, and of
+/// `$VIRTUAL_ENV`, reported under `site-packages/`. Rust: each registry
+/// package in the nearest `Cargo.lock`, unpacked under
+/// `$CARGO_HOME/registry/src` (default `~/.cargo`), reported under
+/// `registry/<name>-<version>/`. Packages not downloaded yet are counted
+/// in the second value.
+pub fn roots(
+    path: &Path,
+    ext: &str,
+    var: impl Fn(&str) -> Option<String>,
+) -> Result<(Vec<Root>, usize), BoloError> {
+    let dir = if path.is_file() {
+        path.parent().unwrap_or(Path::new("."))
+    } else {
+        path
+    };
+    Ok(match ext {
+        "py" => {
+            let envs = [dir.join("venv"), dir.join(".venv")]
+                .into_iter()
+                .chain(var("VIRTUAL_ENV").map(PathBuf::from));
+            let mut seen = HashSet::new();
+            let roots = envs
+                .flat_map(|env| site_packages(&env))
+                .filter(|d| seen.insert(d.canonicalize().unwrap_or(d.clone())))
+                .map(|dir| Root {
+                    label: "site-packages".into(),
+                    dir,
+                })
+                .collect();
+            (roots, 0)
+        }
+        "rs" => {
+            let Some(lock) = dir
+                .ancestors()
+                .map(|d| d.join("Cargo.lock"))
+                .find(|l| l.is_file())
+            else {
+                return Ok((Vec::new(), 0));
+            };
+            let home = var("CARGO_HOME")
+                .map(PathBuf::from)
+                .or_else(|| var("HOME").map(|h| Path::new(&h).join(".cargo")));
+            let indexes = home
+                .map(|h| sorted_dirs(&h.join("registry/src")))
+                .unwrap_or_default();
+            let mut missing = 0;
+            let mut roots = Vec::new();
+            for package in registry_packages(&lock)? {
+                let label = format!("{}-{}", package.name, package.version);
+                match indexes.iter().map(|i| i.join(&label)).find(|d| d.is_dir()) {
+                    Some(dir) => roots.push(Root {
+                        label: format!("registry/{label}"),
+                        dir,
+                    }),
+                    None => missing += 1,
+                }
+            }
+            (roots, missing)
+        }
+        _ => (Vec::new(), 0),
+    })
+}
+
+/// Walk every root for `ext` files, with paths under its label.
+pub fn walk(roots: &[Root], ext: &str, threads: usize) -> Result<Vec<File>, BoloError> {
+    let mut files = Vec::new();
+    for root in roots {
+        // Installed packages have no .gitignore worth honoring.
+        for f in fs::walk_dir_threads(
+            &root.dir,
+            ext,
+            true,
+            fs::default_excludes(ext),
+            threads,
+            None,
+        )? {
+            files.push(File {
+                rel_path: Path::new(&root.label).join(&f.rel_path),
+                path: f.path,
+            });
+        }
+    }
+    Ok(files)
+}
+
+/// Set [`EXTERNAL`] on File nodes whose path is in `paths`, and on the
+/// functions, types and fixtures they define.
+pub fn tag(files: &mut [Vec<Syntax>], paths: &HashSet<String>) {
+    for nodes in files {
+        if path(nodes).is_some_and(|p| paths.contains(p)) {
+            mark(nodes, EXTERNAL);
+        }
+    }
+}
+
+/// Paths of the git submodules declared in `dir`'s `.gitmodules`.
+pub fn submodules(dir: &Path) -> Vec<String> {
+    let text = std::fs::read_to_string(dir.join(".gitmodules")).unwrap_or_default();
+    text.lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once('=')?;
+            (key.trim() == "path").then(|| value.trim().trim_end_matches('/').to_string())
+        })
+        .collect()
+}
+
+/// Set [`VENDORED`] like [`tag`] on files inside a `vendor/`,
+/// `third_party/` (or similar) directory or under one of `submodules`
+/// (File path prefixes). Returns how many files were tagged.
+pub fn tag_vendored(files: &mut [Vec<Syntax>], submodules: &[String]) -> usize {
+    let mut n = 0;
+    for nodes in files {
+        let vendored = path(nodes).is_some_and(|p| {
+            let dirs = p.rsplit_once('/').map_or("", |(d, _)| d);
+            dirs.split('/').any(|d| VENDOR_DIRS.contains(&d))
+                || submodules.iter().any(|m| {
+                    p.strip_prefix(m.as_str())
+                        .is_some_and(|r| r.starts_with('/'))
+                })
+        });
+        if vendored {
+            mark(nodes, VENDORED);
+            n += 1;
+        }
+    }
+    n
+}
+
+/// Whether `s` was tagged [`EXTERNAL`] or [`VENDORED`].
+pub fn is_third_party(s: &Syntax) -> bool {
+    s.metadata.metric(EXTERNAL).is_some() || s.metadata.metric(VENDORED).is_some()
+}
+
+/// `files` without third-party ones, for metrics about the project's own
+/// architecture. Borrowed when there are none.
+pub fn first_party<'a, 's>(files: &'a [Vec<Syntax<'s>>]) -> Cow<'a, [Vec<Syntax<'s>>]> {
+    let own = |nodes: &Vec<Syntax>| !nodes.first().is_some_and(is_third_party);
+    if files.iter().all(own) {
+        return Cow::Borrowed(files);
+    }
+    Cow::Owned(files.iter().filter(|n| own(n)).cloned().collect())
+}
+
+fn path<'a>(nodes: &'a [Syntax]) -> Option<&'a str> {
+    match &nodes.first()?.node {
+        ASTNode::File(f) => Some(&f.path),
+        _ => None,
+    }
+}
+
+fn mark(nodes: &mut [Syntax], key: &str) {
+    for s in nodes {
+        if matches!(
+            s.node,
+            ASTNode::File(_) | ASTNode::Function(_) | ASTNode::Type(_) | ASTNode::Fixture(_)
+        ) {
+            s.metadata.set_metric(key, true);
+        }
+        mark(&mut s.contains, key);
+    }
+}
+
+/// `lib/python3.X/site-packages` (or `Lib/site-packages` on Windows) in a
+/// virtualenv.
+fn site_packages(env: &Path) -> Vec<PathBuf> {
+    let windows = env.join("Lib/site-packages");
+    if windows.is_dir() {
+        return vec![windows];
+    }
+    sorted_dirs(&env.join("lib"))
+        .into_iter()
+        .filter(|d| {
+            d.file_name()
+                .is_some_and(|n| n.to_string_lossy().starts_with("python"))
+        })
+        .map(|d| d.join("site-packages"))
+        .filter(|d| d.is_dir())
+        .collect()
+}
+
+fn sorted_dirs(dir: &Path) -> Vec<PathBuf> {
+    let mut dirs: Vec<_> = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.is_dir())
+        .collect();
+    dirs.sort();
+    dirs
+}
+
+#[derive(Deserialize)]
+struct Lockfile {
+    #[serde(default)]
+    package: Vec<Package>,
+}
+
+#[derive(Deserialize)]
+struct Package {
+    name: String,
+    version: String,
+    source: Option<String>,
+}
+
+/// Packages in a `Cargo.lock` that come from a registry (not the workspace,
+/// a path or git).
+fn registry_packages(lock: &Path) -> Result<Vec<Package>, BoloError> {
+    let text = std::fs::read_to_string(lock).map_err(|e| BoloError::Read {
+        path: lock.to_path_buf(),
+        reason: e.to_string(),
+    })?;
+    let parsed: Lockfile = toml::from_str(&text).map_err(|e| BoloError::Parse {
+        file: lock.display().to_string(),
+        reason: e.message().to_string(),
+    })?;
+    Ok(parsed
+        .package
+        .into_iter()
+        .filter(|p| {
+            p.source
+                .as_deref()
+                .is_some_and(|s| s.starts_with("registry+") || s.starts_with("sparse+"))
+        })
+        .collect())
+}
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::tree_sitter::analyze;
+    use crate::api::tree_sitter::py::Python;
+    use crate::graph::Graph;
+    use tempfile::TempDir;
+
+    fn write(root: &Path, rel: &str, text: &str) {
+        let path = root.join(rel);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, text).unwrap();
+    }
+
+    fn no_env(_: &str) -> Option<String> {
+        None
+    }
+
+    // ── roots ──
+
+    #[test]
+    fn python_site_packages_in_venvs() {
+        let dir = TempDir::new().unwrap();
+        write(dir.path(), ".venv/lib/python3.12/site-packages/six.py", "");
+        write(dir.path(), "env/Lib/site-packages/requests/api.py", "");
+        let env = dir.path().join("env").display().to_string();
+
+        let (roots, missing) = roots(dir.path(), "py", |k| {
+            (k == "VIRTUAL_ENV").then(|| env.clone())
+        })
+        .unwrap();
+        assert_eq!(missing, 0);
+        assert_eq!(roots.len(), 2);
+        assert!(roots.iter().all(|r| r.label == "site-packages"));
+
+        let files = walk(&roots, "py", 1).unwrap();
+        let rel: Vec<_> = files
+            .iter()
+            .map(|f| fs::portable(&f.rel_path, None))
+            .collect();
+        assert_eq!(
+            rel,
+            ["site-packages/six.py", "site-packages/requests/api.py"]
+        );
+    }
+
+    #[test]
+    fn rust_registry_packages_from_lockfile() {
+        let dir = TempDir::new().unwrap();
+        write(
+            dir.path(),
+            "app/Cargo.lock",
+            r#"
+[[package]]
+name = "app"
+version = "0.1.0"
+
+[[package]]
+name = "serde"
+version = "1.0.0"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "gone"
+version = "2.0.0"
+source = "sparse+https://index.crates.io/"
+"#,
+        );
+        write(
+            dir.path(),
+            "cargo/registry/src/index.crates.io-1/serde-1.0.0/src/lib.rs",
+            "pub fn to_string() {}\n",
+        );
+        let home = dir.path().join("cargo").display().to_string();
+
+        let (roots, missing) = roots(&dir.path().join("app/src"), "rs", |k| {
+            (k == "CARGO_HOME").then(|| home.clone())
+        })
+        .unwrap();
+        assert_eq!(missing, 1);
+        assert_eq!(roots.len(), 1);
+        assert_eq!(roots[0].label, "registry/serde-1.0.0");
+        let files = walk(&roots, "rs", 1).unwrap();
+        assert_eq!(
+            fs::portable(&files[0].rel_path, None),
+            "registry/serde-1.0.0/src/lib.rs"
+        );
+    }
+
+    #[test]
+    fn nothing_installed() {
+        let dir = TempDir::new().unwrap();
+        assert_eq!(roots(dir.path(), "py", no_env).unwrap(), (vec![], 0));
+        assert_eq!(roots(dir.path(), "rs", no_env).unwrap(), (vec![], 0));
+    }
+
+    // ── tag ──
+
+    #[test]
+    fn external_definitions_tagged_and_linked() {
+        let mut files = vec![
+            analyze(&Python, "app.py", "def main():\n    get()\n"),
+            analyze(
+                &Python,
+                "site-packages/requests/api.py",
+                "def get():\n    pass\n",
+            ),
+        ];
+        let paths = HashSet::from(["site-packages/requests/api.py".to_string()]);
+        tag(&mut files, &paths);
+
+        let external = |s: &Syntax| s.metadata.metric(EXTERNAL).is_some();
+        assert!(!external(&files[0][0]) && !external(&files[0][1]));
+        assert!(external(&files[1][0]) && external(&files[1][1]));
+
+        let g = Graph::build(&files);
+        let get = g.defs.iter().position(|d| d.name == "get").unwrap();
+        assert!(g.edges.iter().any(|&(_, to)| to == get));
+    }
+
+    // ── vendored ──
+
+    #[test]
+    fn submodules_from_gitmodules() {
+        let dir = TempDir::new().unwrap();
+        write(
+            dir.path(),
+            ".gitmodules",
+            "[submodule \"libs/zlib\"]\n\tpath = libs/zlib\n\turl = https://host/zlib.git\n",
+        );
+        assert_eq!(submodules(dir.path()), ["libs/zlib"]);
+        assert!(submodules(&dir.path().join("missing")).is_empty());
+    }
+
+    #[test]
+    fn vendored_by_directory_or_submodule() {
+        let mut files = vec![
+            analyze(&Python, "app/main.py", "def main():\n    inflate()\n"),
+            analyze(&Python, "app/vendor/six.py", "def six():\n    pass\n"),
+            analyze(&Python, "libs/zlib/z.py", "def inflate():\n    pass\n"),
+            analyze(&Python, "libs/zlibx.py", ""),
+            analyze(&Python, "vendor.py", ""),
+        ];
+        let n = tag_vendored(&mut files, &["libs/zlib".to_string()]);
+        assert_eq!(n, 2);
+        let tagged: Vec<bool> = files.iter().map(|f| is_third_party(&f[0])).collect();
+        assert_eq!(tagged, [false, true, true, false, false]);
+        assert!(is_third_party(&files[2][1]));
+
+        let own = first_party(&files);
+        assert_eq!(own.len(), 3);
+        assert!(matches!(first_party(&own), Cow::Borrowed(_)));
+    }
+}
@@ -0,0 +1,480 @@
+use crate::api::tree_sitter::Syntax;
+use crate::clean::{BODY_HASH, BODY_TOKENS};
+use crate::graph::{Def, Graph};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Token overlap (Jaccard) above which two bodies are the same code.
+const SIMILARITY: f64 = 0.8;
+/// Bodies with fewer tokens than this only match exactly.
+/// Similar bodies must also be within a quarter of each other's line count.
+const MIN_TOKENS: usize = 3;
+/// Definitions with fewer source tokens than this, name excluded, are
+/// stubs: `def f(): pass` and `fn new() -> Self { Self }` look alike
+/// everywhere.
+const STUB_TOKENS: usize = 10;
+
+/// How a definition or edge differs between the old and new analysis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Change {
+    Added,
+    Removed,
+    /// A definition that moved or changed name, keeping its body.
+    Renamed,
+    Unchanged,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DefChange {
+    pub id: String,
+    pub file: String,
+    pub name: String,
+    pub kind: &'static str,
+    pub change: Change,
+    /// The old id, for [`Change::Renamed`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EdgeChange {
+    pub from: String,
+    pub to: String,
+    pub change: Change,
+}
+
+/// The union of two call graphs, each definition and edge marked with how
+/// it changed. Definitions match by id (`file::Qualified.name`); a removed
+/// and an added definition with the same body are reported as one rename,
+/// and edges follow renamed definitions to their new ids.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Diff {
+    pub defs: Vec<DefChange>,
+    pub edges: Vec<EdgeChange>,
+}
+
+impl Diff {
+    pub fn new<'a>(old: &'a [Vec<Syntax<'a>>], new: &'a [Vec<Syntax<'a>>]) -> Self {
+        let (old, new) = (Graph::build(old), Graph::build(new));
+        let old_ids: BTreeSet<&str> = old.defs.iter().map(|d| d.id.as_str()).collect();
+        let new_ids: BTreeSet<&str> = new.defs.iter().map(|d| d.id.as_str()).collect();
+
+        // `old id → new id` for every rename.
+        let renamed: HashMap<&str, &str> =
+            renames(&unmatched(&old, &new_ids), &unmatched(&new, &old_ids))
+                .into_iter()
+                .collect();
+        let renamed_to: HashMap<&str, &str> = renamed.iter().map(|(o, n)| (*n, *o)).collect();
+
+        let mut defs: BTreeMap<&str, DefChange> = BTreeMap::new();
+        for (graph, change) in [(&old, Change::Removed), (&new, Change::Added)] {
+            for d in &graph.defs {
+                if change == Change::Removed && renamed.contains_key(d.id.as_str()) {
+                    continue;
+                }
+                let from = renamed_to.get(d.id.as_str()).map(|o| o.to_string());
+                defs.entry(&d.id)
+                    .and_modify(|c| c.change = Change::Unchanged)
+                    .or_insert_with(|| DefChange {
+                        id: d.id.clone(),
+                        file: d.file.to_string(),
+                        name: d.name.to_string(),
+                        kind: d.kind.as_str(),
+                        change: if from.is_some() {
+                            Change::Renamed
+                        } else {
+                            change
+                        },
+                        from,
+                    });
+            }
+        }
+
+        let mut edges: BTreeMap<(&str, &str), Change> = BTreeMap::new();
+        for (graph, change) in [(&old, Change::Removed), (&new, Change::Added)] {
+            let id = |i: usize| {
+                let id = graph.defs[i].id.as_str();
+                renamed.get(id).copied().unwrap_or(id)
+            };
+            for &(a, b) in &graph.edges {
+                edges
+                    .entry((id(a), id(b)))
+                    .and_modify(|c| *c = Change::Unchanged)
+                    .or_insert(change);
+            }
+        }
+
+        Diff {
+            defs: defs.into_values().collect(),
+            edges: edges
+                .into_iter()
+                .map(|((from, to), change)| EdgeChange {
+                    from: from.to_string(),
+                    to: to.to_string(),
+                    change,
+                })
+                .collect(),
+        }
+    }
+
+    /// Only what was added, removed or renamed.
+    pub fn changes(&self) -> Diff {
+        Diff {
+            defs: self
+                .defs
+                .iter()
+                .filter(|d| d.change != Change::Unchanged)
+                .cloned()
+                .collect(),
+            edges: self
+                .edges
+                .iter()
+                .filter(|e| e.change != Change::Unchanged)
+                .cloned()
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.defs
+            .iter()
+            .map(|d| d.change)
+            .chain(self.edges.iter().map(|e| e.change))
+            .all(|c| c == Change::Unchanged)
+    }
+}
+
+// ── Renames ────────────────────────────────────────────────────────
+
+/// Definitions of `graph` whose id isn't in `other`, with their bodies.
+fn unmatched<'g>(graph: &'g Graph, other: &BTreeSet<&str>) -> Vec<(&'g str, &'static str, Body)> {
+    graph
+        .defs
+        .iter()
+        .filter(|d| !other.contains(d.id.as_str()))
+        .map(|d| (d.id.as_str(), d.kind.as_str(), Body::of(d)))
+        .collect()
+}
+
+/// A definition with its name taken out: its size, the nodes inside it,
+/// and its source text when the analysis fingerprinted it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Body {
+    /// Lines, words, and characters not counting the name.
+    size: (usize, usize, usize),
+    /// Every nested node (calls, types used, nested definitions), in order.
+    tokens: Vec<String>,
+    /// [`BODY_HASH`] and [`BODY_TOKENS`]; `None` in analyses written
+    /// before they were recorded or with metrics dropped.
+    source: Option<(String, usize)>,
+}
+
+impl Body {
+    fn of(def: &Def) -> Self {
+        Body::new(def.syntax, def.name)
+    }
+
+    pub(crate) fn new(s: &Syntax, name: &str) -> Self {
+        let m = &s.metadata;
+        let mut tokens = Vec::new();
+        descendants(&s.contains, &mut tokens);
+        let source = m.metric(BODY_HASH).and_then(|h| h.as_str()).zip(
+            m.metric(BODY_TOKENS)
+                .and_then(|n| n.as_u64())
+                .map(|n| n as usize),
+        );
+        Body {
+            size: (
+                m.lines,
+                m.words,
+                m.chars.saturating_sub(name.chars().count()),
+            ),
+            tokens,
+            source: source.map(|(h, n)| (h.to_string(), n)),
+        }
+    }
+
+    /// The same code under another name: the same source text when both
+    /// sides have it, else the same size and nodes.
+    fn same(&self, other: &Body) -> bool {
+        match (&self.source, &other.source) {
+            (Some(a), Some(b)) => a == b,
+            _ => self.size == other.size && self.tokens == other.tokens,
+        }
+    }
+
+    /// Too little code to tell apart from other definitions.
+    fn is_stub(&self) -> bool {
+        match &self.source {
+            Some((_, n)) => *n < STUB_TOKENS,
+            None => self.tokens.is_empty(),
+        }
+    }
+
+    /// Jaccard similarity of the token sets.
+    fn similarity(&self, other: &Body) -> f64 {
+        let a: BTreeSet<&String> = self.tokens.iter().collect();
+        let b: BTreeSet<&String> = other.tokens.iter().collect();
+        let union = a.union(&b).count();
+        if union == 0 {
+            return 0.0;
+        }
+        a.intersection(&b).count() as f64 / union as f64
+    }
+}
+
+fn descendants(nodes: &[Syntax], out: &mut Vec<String>) {
+    for s in nodes {
+        out.push(serde_json::to_string(&s.node).unwrap_or_default());
+        descendants(&s.contains, out);
+    }
+}
+
+/// Pair `removed` with `added` definitions of the same kind that are
+/// probably the same code: identical bodies first (when the match is
+/// unambiguous), then the most similar bodies above [`SIMILARITY`].
+/// Stubs never match, since every stub looks alike.
+pub(crate) fn renames<'k>(
+    removed: &[(&'k str, &'static str, Body)],
+    added: &[(&'k str, &'static str, Body)],
+) -> Vec<(&'k str, &'k str)> {
+    let mut pairs = Vec::new();
+    let (mut used_old, mut used_new) = (BTreeSet::new(), BTreeSet::new());
+
+    for (i, (old, kind, body)) in removed.iter().enumerate() {
+        if body.is_stub() {
+            continue;
+        }
+        let same = |(_, k, b): &&(&str, &str, Body)| k == kind && b.same(body);
+        let matches: Vec<usize> = added
+            .iter()
+            .enumerate()
+            .filter(|(_, a)| same(a))
+            .map(|(j, _)| j)
+            .collect();
+        let twins = removed.iter().filter(|r| same(r)).count();
+        if let ([j], 1) = (matches.as_slice(), twins) {
+            pairs.push((*old, added[*j].0));
+            used_old.insert(i);
+            used_new.insert(*j);
+        }
+    }
+
+    let mut scored = Vec::new();
+    for (i, (_, kind, a)) in removed.iter().enumerate() {
+        for (j, (_, k, b)) in added.iter().enumerate() {
+            if used_old.contains(&i) || used_new.contains(&j) || k != kind {
+                continue;
+            }
+            // Identical bodies left over were ambiguous in the exact pass.
+            if a.same(b) || a.tokens.len() < MIN_TOKENS || b.tokens.len() < MIN_TOKENS {
+                continue;
+            }
+            if a.size.0.abs_diff(b.size.0) * 4 > a.size.0.max(b.size.0) {
+                continue;
+            }
+            let score = a.similarity(b);
+            if score >= SIMILARITY {
+                scored.push((score, i, j));
+            }
+        }
+    }
+    scored.sort_by(|x, y| y.0.total_cmp(&x.0).then((x.1, x.2).cmp(&(y.1, y.2))));
+    for (_, i, j) in scored {
+        if !used_old.contains(&i) && !used_new.contains(&j) {
+            used_old.insert(i);
+            used_new.insert(j);
+            pairs.push((removed[i].0, added[j].0));
+        }
+    }
+    pairs
+}
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::tree_sitter::analyze;
+    use crate::api::tree_sitter::py::Python;
+
+    fn owned(path: &str, source: String) -> Vec<Syntax<'static>> {
+        analyze(&Python, path, &source)
+            .into_iter()
+            .map(Syntax::into_owned)
+            .collect()
+    }
+
+    fn edge(diff: &Diff, from: &str, to: &str) -> Option<Change> {
+        diff.edges
+            .iter()
+            .find(|e| e.from == from && e.to == to)
+            .map(|e| e.change)
+    }
+
+    fn def(diff: &Diff, id: &str) -> Option<Change> {
+        diff.defs.iter().find(|d| d.id == id).map(|d| d.change)
+    }
+
+    #[test]
+    fn identical_graphs_unchanged() {
+        let files = [analyze(
+            &Python,
+            "a.py",
+            "def f():\n    g()\ndef g(): pass\n",
+        )];
+        let diff = Diff::new(&files, &files);
+        assert!(diff.is_empty());
+        assert_eq!(edge(&diff, "a.py::f", "a.py::g"), Some(Change::Unchanged));
+        assert!(diff.changes().defs.is_empty());
+    }
+
+    #[test]
+    fn added_and_removed_edges() {
+        let old = [analyze(
+            &Python,
+            "a.py",
+            "def f():\n    g()\ndef g(): pass\ndef h(): pass\n",
+        )];
+        let new = [analyze(
+            &Python,
+            "a.py",
+            "def f():\n    h()\ndef g(): pass\ndef h(): pass\n",
+        )];
+        let diff = Diff::new(&old, &new);
+        assert_eq!(edge(&diff, "a.py::f", "a.py::g"), Some(Change::Removed));
+        assert_eq!(edge(&diff, "a.py::f", "a.py::h"), Some(Change::Added));
+        assert_eq!(diff.changes().edges.len(), 2);
+    }
+
+    #[test]
+    fn added_and_removed_defs() {
+        let old = [analyze(&Python, "a.py", "def f(): pass\n")];
+        let new = [analyze(&Python, "a.py", "def g(): pass\n")];
+        let diff = Diff::new(&old, &new);
+        assert_eq!(def(&diff, "a.py::f"), Some(Change::Removed));
+        assert_eq!(def(&diff, "a.py::g"), Some(Change::Added));
+        assert!(!diff.is_empty());
+    }
+
+    fn from(diff: &Diff, id: &str) -> Option<String> {
+        diff.defs.iter().find(|d| d.id == id)?.from.clone()
+    }
+
+    // ── Renames ──
+
+    const BODY: &str = "():\n    x = load()\n    check(x)\n    save(x)\n";
+
+    #[test]
+    fn same_body_new_name_is_rename() {
+        let old = [owned("a.py", format!("def fetch{BODY}"))];
+        let new = [owned("a.py", format!("def fetch_all{BODY}"))];
+        let diff = Diff::new(&old, &new);
+        assert_eq!(def(&diff, "a.py::fetch_all"), Some(Change::Renamed));
+        assert_eq!(
+            from(&diff, "a.py::fetch_all").as_deref(),
+            Some("a.py::fetch")
+        );
+        assert_eq!(def(&diff, "a.py::fetch"), None);
+        assert_eq!(diff.changes().defs.len(), 1);
+    }
+
+    #[test]
+    fn moved_to_another_file_is_rename() {
+        let old = [owned("a.py", format!("def f{BODY}"))];
+        let new = [owned("b.py", format!("def f{BODY}"))];
+        let diff = Diff::new(&old, &new);
+        assert_eq!(def(&diff, "b.py::f"), Some(Change::Renamed));
+        assert_eq!(from(&diff, "b.py::f").as_deref(), Some("a.py::f"));
+    }
+
+    #[test]
+    fn similar_body_is_rename() {
+        let old = [owned("a.py", format!("def f{BODY}"))];
+        let new = [analyze(
+            &Python,
+            "a.py",
+            "def g():\n    x = load()\n    check(x)\n    save(x)\n    save(x)\n",
+        )];
+        let diff = Diff::new(&old, &new);
+        assert_eq!(def(&diff, "a.py::g"), Some(Change::Renamed));
+    }
+
+    #[test]
+    fn different_body_is_add_and_remove() {
+        let old = [owned("a.py", format!("def f{BODY}"))];
+        let new = [analyze(
+            &Python,
+            "a.py",
+            "def g():\n    parse()\n    emit()\n",
+        )];
+        let diff = Diff::new(&old, &new);
+        assert_eq!(def(&diff, "a.py::f"), Some(Change::Removed));
+        assert_eq!(def(&diff, "a.py::g"), Some(Change::Added));
+    }
+
+    #[test]
+    fn empty_bodies_never_match() {
+        let old = [analyze(&Python, "a.py", "def f(): pass\n")];
+        let new = [analyze(&Python, "a.py", "def g(): pass\n")];
+        let diff = Diff::new(&old, &new);
+        assert_eq!(def(&diff, "a.py::g"), Some(Change::Added));
+    }
+
+    #[test]
+    fn same_source_without_calls_is_rename() {
+        let body = "(price, qty, tax):\n    return price * qty + price * qty * tax\n";
+        let old = [owned("a.py", format!("def compute_total{body}"))];
+        let new = [owned("a.py", format!("def calc_total{body}"))];
+        let diff = Diff::new(&old, &new);
+        assert_eq!(def(&diff, "a.py::calc_total"), Some(Change::Renamed));
+        assert_eq!(
+            from(&diff, "a.py::calc_total").as_deref(),
+            Some("a.py::compute_total")
+        );
+    }
+
+    #[test]
+    fn same_calls_different_source_is_not_exact() {
+        let old = [owned("a.py", format!("def f{BODY}"))];
+        let new = [owned(
+            "a.py",
+            "def g():\n    x = load()\n    check(x + 1)\n    save(x)\n".to_string(),
+        )];
+        let (old, new) = (Graph::build(&old), Graph::build(&new));
+        let (a, b) = (Body::of(&old.defs[0]), Body::of(&new.defs[0]));
+        assert_eq!(a.tokens, b.tokens);
+        assert!(!a.same(&b));
+        assert!(a.similarity(&b) >= SIMILARITY);
+    }
+
+    #[test]
+    fn ambiguous_exact_match_left_alone() {
+        let old = [owned("a.py", format!("def f{BODY}"))];
+        let new = [owned("a.py", format!("def g{BODY}def h{BODY}"))];
+        let diff = Diff::new(&old, &new);
+        assert_eq!(def(&diff, "a.py::f"), Some(Change::Removed));
+        assert_eq!(def(&diff, "a.py::g"), Some(Change::Added));
+        assert_eq!(def(&diff, "a.py::h"), Some(Change::Added));
+    }
+
+    #[test]
+    fn edges_follow_renames() {
+        let old = [owned("a.py", format!("def main():\n    f()\ndef f{BODY}"))];
+        let new = [owned("a.py", format!("def main():\n    g()\ndef g{BODY}"))];
+        let diff = Diff::new(&old, &new);
+        assert_eq!(
+            edge(&diff, "a.py::main", "a.py::g"),
+            Some(Change::Unchanged)
+        );
+        assert_eq!(edge(&diff, "a.py::main", "a.py::f"), None);
+    }
+
+    #[test]
+    fn serializes_change_lowercase() {
+        let diff = Diff::new(&[], &[analyze(&Python, "a.py", "def f(): pass\n")]);
+        let json = serde_json::to_string(&diff.defs[0]).unwrap();
+        assert!(json.contains("\"change\":\"added\""));
+    }
+}
@@ -1,6 +1,7 @@
 use std::path::PathBuf;
 
 #[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
 pub enum BoloError {
     #[error("cannot access `{}`: {reason}", path.display())]
     InvalidPath { path: PathBuf, reason: String },
@@ -22,4 +23,34 @@ pub enum BoloError {
 
     #[error("cannot write `{}`: {reason}", path.display())]
     Write { path: PathBuf, reason: String },
+
+    #[error("cannot write `{}`: {reason}", path.display())]
+    Format { path: PathBuf, reason: String },
+
+    #[error("cannot fetch `{url}`: {reason}")]
+    Git { url: String, reason: String },
+
+    #[error("invalid config `{}`: {reason}", path.display())]
+    Config { path: PathBuf, reason: String },
+
+    #[error("`--{flag}` is not supported {context}")]
+    Unsupported {
+        flag: &'static str,
+        context: &'static str,
+    },
+
+    #[error("invalid profile `{}`: {reason}", path.display())]
+    Profile { path: PathBuf, reason: String },
+
+    #[error("cannot rename `{old}`: {reason}")]
+    Rename { old: String, reason: String },
+
+    #[error("cannot query `{symbol}`: {reason}")]
+    Query { symbol: String, reason: String },
+
+    #[error("cannot listen on `{addr}`: {reason}")]
+    Listen { addr: String, reason: String },
+
+    #[error("unknown pass `{name}` (known: {known})")]
+    Pass { name: String, known: String },
 }
@@ -0,0 +1,100 @@
+use crate::api::tree_sitter::Syntax;
+use crate::graph::Graph;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// Callgrind profile data, so KCachegrind or QCacheGrind can browse the call
+/// graph: one function per definition, costed by its line count, calling
+/// each definition it resolves to (see [`Graph::build`]) once per call site.
+/// Costs are static sizes, not measurements; a call's inclusive cost is the
+/// callee's own line count.
+pub fn render(files: &[Vec<Syntax>]) -> String {
+    let graph = Graph::build(files);
+    let mut out = String::from(
+        "# callgrind format\nversion: 1\ncreator: bolo\npositions: line\nevents: Lines\n",
+    );
+    let total: usize = graph.defs.iter().map(|d| d.syntax.metadata.lines).sum();
+    let _ = writeln!(out, "summary: {total}");
+
+    let mut callees: Vec<Vec<(usize, usize)>> = vec![Vec::new(); graph.defs.len()];
+    for (&(a, b), &n) in graph.edges.iter().zip(&graph.sites) {
+        callees[a].push((b, n));
+    }
+    let (mut files, mut fns) = (Names::default(), Names::default());
+    for (d, calls) in graph.defs.iter().zip(&callees) {
+        let meta = &d.syntax.metadata;
+        let _ = writeln!(out, "\nfl={}", files.get(d.file));
+        let _ = writeln!(out, "fn={}", fns.get(&d.id));
+        let _ = writeln!(out, "{} {}", meta.span.start_line, meta.lines);
+        for &(b, n) in calls {
+            let callee = &graph.defs[b];
+            if callee.file != d.file {
+                let _ = writeln!(out, "cfi={}", files.get(callee.file));
+            }
+            let _ = writeln!(out, "cfn={}", fns.get(&callee.id));
+            let target = &callee.syntax.metadata;
+            let _ = writeln!(out, "calls={n} {}", target.span.start_line);
+            let _ = writeln!(out, "{} {}", meta.span.start_line, target.lines);
+        }
+    }
+    out
+}
+
+/// Callgrind name compression: a name is written as `(n) name` the first
+/// time and `(n)` after that.
+#[derive(Default)]
+struct Names<'a> {
+    ids: HashMap<&'a str, usize>,
+}
+
+impl<'a> Names<'a> {
+    fn get(&mut self, name: &'a str) -> String {
+        let next = self.ids.len() + 1;
+        match self.ids.get(name) {
+            Some(id) => format!("({id})"),
+            None => {
+                self.ids.insert(name, next);
+                format!("({next}) {name}")
+            }
+        }
+    }
+}
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::tree_sitter::analyze;
+    use crate::api::tree_sitter::py::Python;
+
+    #[test]
+    fn header_and_functions() {
+        let out = render(&[
+            analyze(
+                &Python,
+                "a.py",
+                "def f():\n    g()\n    g()\n    h()\n\ndef h(): pass\n",
+            ),
+            analyze(&Python, "b.py", "def g():\n    pass\n"),
+        ]);
+        assert!(out.starts_with("# callgrind format\nversion: 1\n"));
+        assert!(out.contains("events: Lines\nsummary: 7\n"));
+        assert!(out.contains(
+            "fl=(1) a.py\nfn=(1) a.py::f\n1 4\n\
+             cfi=(2) b.py\ncfn=(2) b.py::g\ncalls=2 1\n1 2\n\
+             cfn=(3) a.py::h\ncalls=1 6\n1 1\n"
+        ));
+        // Names are compressed after their first use.
+        assert!(out.contains("\nfl=(1)\nfn=(3)\n6 1\n"));
+        assert!(out.contains("\nfl=(2)\nfn=(2)\n1 2\n"));
+    }
+
+    #[test]
+    fn empty_analysis_has_header_only() {
+        assert_eq!(
+            render(&[]),
+            "# callgrind format\nversion: 1\ncreator: bolo\npositions: line\nevents: Lines\nsummary: 0\n"
+        );
+    }
+}
@@ -0,0 +1,160 @@
+use crate::api::tree_sitter::Syntax;
+use crate::deps;
+use crate::graph::Graph;
+use serde::Serialize;
+
+/// Cytoscape.js elements: a compound node per file, a child node per
+/// definition and an edge per resolved call (see [`Graph::build`]).
+/// Passes straight to `cytoscape({ elements })` and loads in Cytoscape
+/// desktop as `.cyjs`.
+#[derive(Debug, Serialize)]
+pub struct Elements<'a> {
+    pub elements: Parts<'a>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Parts<'a> {
+    pub nodes: Vec<Element<NodeData<'a>>>,
+    pub edges: Vec<Element<EdgeData<'a>>>,
+}
+
+/// Cytoscape.js keeps an element's fields under `data`.
+#[derive(Debug, Serialize)]
+pub struct Element<T> {
+    pub data: T,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NodeData<'a> {
+    pub id: &'a str,
+    pub label: &'a str,
+    /// `file`, `fn`, `type` or `fixture`.
+    pub kind: &'static str,
+    /// The file a definition is in.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lines: Option<usize>,
+    /// Vendored or from an installed package (see [`deps::is_third_party`]).
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub third_party: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EdgeData<'a> {
+    pub id: String,
+    pub source: &'a str,
+    pub target: &'a str,
+    /// Call sites behind the edge.
+    pub weight: usize,
+}
+
+/// `graph` as Cytoscape.js elements.
+pub fn elements<'a>(graph: &'a Graph<'a>) -> Elements<'a> {
+    let mut nodes = Vec::new();
+    let mut file = None;
+    for d in &graph.defs {
+        let third_party = deps::is_third_party(d.syntax);
+        // Definitions come in file order.
+        if file.replace(d.file) != Some(d.file) {
+            nodes.push(Element {
+                data: NodeData {
+                    id: d.file,
+                    label: d.file,
+                    kind: "file",
+                    parent: None,
+                    line: None,
+                    lines: None,
+                    third_party,
+                },
+            });
+        }
+        let meta = &d.syntax.metadata;
+        nodes.push(Element {
+            data: NodeData {
+                id: &d.id,
+                label: d.name,
+                kind: d.kind.as_str(),
+                parent: Some(d.file),
+                line: Some(meta.span.start_line),
+                lines: Some(meta.lines),
+                third_party,
+            },
+        });
+    }
+    let edges = graph
+        .edges
+        .iter()
+        .zip(&graph.sites)
+        .map(|(&(a, b), &weight)| {
+            let (source, target) = (graph.defs[a].id.as_str(), graph.defs[b].id.as_str());
+            Element {
+                data: EdgeData {
+                    id: format!("{source}->{target}"),
+                    source,
+                    target,
+                    weight,
+                },
+            }
+        })
+        .collect();
+    Elements {
+        elements: Parts { nodes, edges },
+    }
+}
+
+/// [`elements`] of `files`, serialized.
+pub fn render(files: &[Vec<Syntax>]) -> serde_json::Result<serde_json::Value> {
+    serde_json::to_value(elements(&Graph::build(files)))
+}
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::tree_sitter::analyze;
+    use crate::api::tree_sitter::py::Python;
+    use serde_json::json;
+
+    #[test]
+    fn files_are_compound_parents() {
+        let value = render(&[
+            analyze(&Python, "a.py", "def f():\n    g()\n    g()\n"),
+            analyze(&Python, "vendor/b.py", "def g(): pass\n"),
+        ])
+        .unwrap();
+        let nodes = &value["elements"]["nodes"];
+        assert_eq!(
+            nodes[0],
+            json!({"data": {"id": "a.py", "label": "a.py", "kind": "file"}})
+        );
+        assert_eq!(
+            nodes[1],
+            json!({"data": {
+                "id": "a.py::f", "label": "f", "kind": "fn",
+                "parent": "a.py", "line": 1, "lines": 3,
+            }})
+        );
+        assert_eq!(nodes.as_array().unwrap().len(), 4);
+        assert_eq!(
+            value["elements"]["edges"],
+            json!([{"data": {
+                "id": "a.py::f->vendor/b.py::g",
+                "source": "a.py::f", "target": "vendor/b.py::g", "weight": 2,
+            }}])
+        );
+    }
+
+    #[test]
+    fn third_party_flagged() {
+        let mut files = [analyze(&Python, "vendor/b.py", "def g(): pass\n")];
+        deps::tag_vendored(&mut files, &[]);
+        let value = render(&files).unwrap();
+        let nodes = &value["elements"]["nodes"];
+        assert_eq!(nodes[0]["data"]["third_party"], true);
+        assert_eq!(nodes[1]["data"]["third_party"], true);
+    }
+}
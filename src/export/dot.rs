@@ -0,0 +1,448 @@
+use crate::api::tree_sitter::Syntax;
+use crate::deps;
+use crate::diff::{Change, DefChange, Diff};
+use crate::graph::{Def, DefKind, Graph};
+use crate::labels::{Labels, Node};
+use crate::profile;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// Graphviz digraph: one cluster per file, nested in a cluster per
+/// directory, one node per definition, and an edge for each call that
+/// resolves to a definition (see [`Graph::build`]). Entry points (called by
+/// nothing, calling something) share the first rank so calls flow left to
+/// right. Profiled functions show their sample count; hot ones are filled
+/// red. Vendored and external files sit in a separate dashed `third-party`
+/// cluster, and calls across its boundary are dashed. Nodes are labeled
+/// and given tooltips per `labels`.
+pub fn render(files: &[Vec<Syntax>], labels: &Labels) -> String {
+    render_graph(&Graph::build(files), &|_| true, labels)
+}
+
+/// [`render`] for the definitions of `graph` that `keep` picks by index
+/// and the edges between them.
+pub fn render_graph(graph: &Graph, keep: &dyn Fn(usize) -> bool, labels: &Labels) -> String {
+    let mut out = String::from("digraph bolo {\n    rankdir=LR;\n    newrank=true;\n");
+    out.push_str("    node [shape=box];\n");
+    let mut file_defs: Vec<(&str, Vec<&Def>)> = Vec::new();
+    for (_, d) in graph.defs.iter().enumerate().filter(|&(i, _)| keep(i)) {
+        match file_defs.last_mut() {
+            Some((f, v)) if *f == d.file => v.push(d),
+            _ => file_defs.push((d.file, vec![d])),
+        }
+    }
+    let third_party = |d: &Def| deps::is_third_party(d.syntax);
+    let (own, other): (Vec<_>, Vec<_>) = file_defs
+        .iter()
+        .enumerate()
+        .partition(|(_, (_, ds))| !third_party(ds[0]));
+    let mut dirs = 0;
+    dir_clusters(&mut out, &own, "", labels, "    ", &mut dirs);
+    if !other.is_empty() {
+        out.push_str("    subgraph cluster_third_party {\n");
+        out.push_str("        label=\"third-party\";\n        style=dashed;\n");
+        dir_clusters(&mut out, &other, "", labels, "        ", &mut dirs);
+        out.push_str("    }\n");
+    }
+
+    let edges: Vec<_> = graph
+        .edges
+        .iter()
+        .filter(|&&(a, b)| keep(a) && keep(b))
+        .collect();
+    for &&(a, b) in &edges {
+        let (a, b) = (&graph.defs[a], &graph.defs[b]);
+        let boundary = if third_party(a) != third_party(b) {
+            " [style=dashed]"
+        } else {
+            ""
+        };
+        let _ = writeln!(out, "    {} -> {}{boundary};", quote(&a.id), quote(&b.id));
+    }
+    for spawn in graph.spawns.iter().filter(|s| keep(s.from) && keep(s.to)) {
+        let (a, b) = (&graph.defs[spawn.from], &graph.defs[spawn.to]);
+        let _ = writeln!(
+            out,
+            "    {} -> {} [style=dotted, label={}];",
+            quote(&a.id),
+            quote(&b.id),
+            quote(spawn.via)
+        );
+    }
+
+    let mut called = vec![false; graph.defs.len()];
+    let mut calls = vec![false; graph.defs.len()];
+    for &&(a, b) in &edges {
+        if a != b {
+            calls[a] = true;
+            called[b] = true;
+        }
+    }
+    let entries: Vec<String> = graph
+        .defs
+        .iter()
+        .enumerate()
+        .filter(|&(i, d)| keep(i) && calls[i] && !called[i] && !third_party(d))
+        .map(|(_, d)| quote(&d.id))
+        .collect();
+    if !entries.is_empty() {
+        let _ = writeln!(out, "    {{rank=min; {};}}", entries.join("; "));
+    }
+    out.push_str("}\n");
+    out
+}
+
+type FileDefs<'a, 'd> = (usize, &'a (&'d str, Vec<&'d Def<'d>>));
+
+/// Clusters for `files`, all under `dir` (`""` or ending in `/`): one per
+/// subdirectory holding two or more of them, labeled with its path and
+/// collapsed through directories that hold nothing else, around
+/// [`file_clusters`]. `dirs` numbers the directory clusters.
+fn dir_clusters(
+    out: &mut String,
+    files: &[FileDefs],
+    dir: &str,
+    labels: &Labels,
+    indent: &str,
+    dirs: &mut usize,
+) {
+    // Subdirectory (or file, as `None`) groups in walk order.
+    let mut groups: Vec<(Option<&str>, Vec<FileDefs>)> = Vec::new();
+    for &f in files {
+        let sub = f.1.0[dir.len()..].split_once('/').map(|(s, _)| s);
+        match groups.iter_mut().find(|(g, _)| sub.is_some() && *g == sub) {
+            Some((_, v)) => v.push(f),
+            None => groups.push((sub, vec![f])),
+        }
+    }
+    for (sub, group) in groups {
+        let Some(sub) = sub.filter(|_| group.len() > 1) else {
+            file_clusters(out, &group, labels, indent);
+            continue;
+        };
+        let mut path = format!("{dir}{sub}/");
+        while let Some(next) = common_subdir(&group, &path) {
+            path = format!("{path}{next}/");
+        }
+        let _ = writeln!(out, "{indent}subgraph cluster_dir_{dirs} {{");
+        let _ = writeln!(out, "{indent}    label={};", quote(&path[..path.len() - 1]));
+        *dirs += 1;
+        dir_clusters(out, &group, &path, labels, &format!("{indent}    "), dirs);
+        let _ = writeln!(out, "{indent}}}");
+    }
+}
+
+/// The subdirectory of `dir` every file in `files` sits in, if any.
+fn common_subdir<'d>(files: &[FileDefs<'_, 'd>], dir: &str) -> Option<&'d str> {
+    let mut common = None;
+    for f in files {
+        let (sub, _) = f.1.0[dir.len()..].split_once('/')?;
+        if *common.get_or_insert(sub) != sub {
+            return None;
+        }
+    }
+    common
+}
+
+/// A cluster per file, numbered by position among all files.
+fn file_clusters(out: &mut String, files: &[FileDefs], labels: &Labels, indent: &str) {
+    for (i, (file, ds)) in files {
+        let _ = writeln!(out, "{indent}subgraph cluster_{i} {{");
+        let _ = writeln!(out, "{indent}    label={};", quote(file));
+        for d in ds {
+            let shape = if d.kind == DefKind::Type {
+                "component"
+            } else {
+                "box"
+            };
+            let meta = &d.syntax.metadata;
+            let node = Node::of(d);
+            let mut label = labels.label(&node);
+            if let Some(n) = meta.metric(profile::SAMPLES) {
+                let _ = write!(label, "\n{n} samples");
+            }
+            let tooltip = match labels.title(&node) {
+                Some(t) => format!(", tooltip={}", quote(&t)),
+                None => String::new(),
+            };
+            let hot = if meta.metric(profile::HOT).is_some() {
+                ", style=filled, fillcolor=\"#f4a6a6\""
+            } else {
+                ""
+            };
+            let _ = writeln!(
+                out,
+                "{indent}    {} [label={}, shape={shape}{tooltip}{hot}];",
+                quote(&d.id),
+                quote(&label)
+            );
+        }
+        let _ = writeln!(out, "{indent}}}");
+    }
+}
+
+/// [`render`] for a [`Diff`]: the union graph, with added definitions and
+/// edges in green, removed ones in red (dashed), and renamed definitions
+/// in blue, labeled with their old id.
+pub fn render_diff(diff: &Diff) -> String {
+    let mut out = String::from("digraph bolo {\n    rankdir=LR;\n    node [shape=box];\n");
+    let mut by_file: BTreeMap<&str, Vec<&DefChange>> = BTreeMap::new();
+    for d in &diff.defs {
+        by_file.entry(&d.file).or_default().push(d);
+    }
+    for (i, (file, ds)) in by_file.iter().enumerate() {
+        let _ = writeln!(out, "    subgraph cluster_{i} {{");
+        let _ = writeln!(out, "        label={};", quote(file));
+        for d in ds {
+            let shape = if d.kind == "type" { "component" } else { "box" };
+            let _ = writeln!(
+                out,
+                "        {} [label={}, shape={shape}{}];",
+                quote(&d.id),
+                quote(&match &d.from {
+                    Some(from) => format!("{} (was {from})", d.name),
+                    None => d.name.clone(),
+                }),
+                style(d.change)
+            );
+        }
+        out.push_str("    }\n");
+    }
+
+    for e in &diff.edges {
+        let _ = writeln!(
+            out,
+            "    {} -> {}{};",
+            quote(&e.from),
+            quote(&e.to),
+            match style(e.change) {
+                "" => String::new(),
+                s => format!(" [{}]", &s[2..]),
+            }
+        );
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// Extra attributes (with a leading `, `) for a changed node or edge.
+fn style(change: Change) -> &'static str {
+    match change {
+        Change::Added => ", color=green, fontcolor=green",
+        Change::Removed => ", color=red, fontcolor=red, style=dashed",
+        Change::Renamed => ", color=blue, fontcolor=blue",
+        Change::Unchanged => "",
+    }
+}
+
+fn quote(s: &str) -> String {
+    let escaped = s
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n");
+    format!("\"{escaped}\"")
+}
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::tree_sitter::analyze;
+    use crate::api::tree_sitter::py::Python;
+    use crate::labels::LabelTemplate;
+
+    fn plain(files: &[Vec<Syntax>]) -> String {
+        render(files, &Labels::default())
+    }
+
+    #[test]
+    fn clusters_per_file() {
+        let dot = plain(&[
+            analyze(&Python, "a.py", "def f(): pass\n"),
+            analyze(&Python, "b.py", "class C:\n    def m(self): pass\n"),
+        ]);
+        assert!(dot.starts_with("digraph bolo {"));
+        assert!(dot.contains("subgraph cluster_0 {\n        label=\"a.py\";"));
+        assert!(dot.contains("\"a.py::f\" [label=\"f\", shape=box];"));
+        assert!(dot.contains("\"b.py::C\" [label=\"C\", shape=component];"));
+        assert!(dot.contains("\"b.py::C.m\" [label=\"m\", shape=box];"));
+    }
+
+    #[test]
+    fn directories_nest_file_clusters() {
+        let dot = plain(&[
+            analyze(&Python, "main.py", "def f(): pass\n"),
+            analyze(&Python, "src/app/a.py", "def a(): pass\n"),
+            analyze(&Python, "src/app/b.py", "def b(): pass\n"),
+            analyze(&Python, "src/app/io/c.py", "def c(): pass\n"),
+            analyze(&Python, "tools/d.py", "def d(): pass\n"),
+        ]);
+        assert!(dot.contains("    subgraph cluster_0 {\n        label=\"main.py\";"));
+        // `src/` holds nothing but `app/`, so the two collapse.
+        assert!(dot.contains(
+            "    subgraph cluster_dir_0 {\n        label=\"src/app\";\n        \
+             subgraph cluster_1 {\n            label=\"src/app/a.py\";"
+        ));
+        // A directory of one file adds no cluster.
+        assert!(!dot.contains("label=\"src/app/io\""));
+        assert!(
+            dot.contains("        subgraph cluster_3 {\n            label=\"src/app/io/c.py\";")
+        );
+        assert!(dot.contains("    subgraph cluster_4 {\n        label=\"tools/d.py\";"));
+    }
+
+    #[test]
+    fn entry_points_ranked_first() {
+        let dot = plain(&[
+            analyze(&Python, "a.py", "def main():\n    g()\ndef h():\n    h()\n"),
+            analyze(&Python, "b.py", "def g():\n    k()\ndef k(): pass\n"),
+        ]);
+        assert!(dot.contains("    newrank=true;\n"));
+        assert!(dot.contains("    {rank=min; \"a.py::main\";}\n}\n"));
+        assert!(!plain(&[analyze(&Python, "a.py", "def f(): pass\n")]).contains("rank=min"));
+    }
+
+    #[test]
+    fn call_edges_resolve_by_name() {
+        let dot = plain(&[
+            analyze(
+                &Python,
+                "a.py",
+                "from b import g\ndef f():\n    g()\n    g()\n",
+            ),
+            analyze(&Python, "b.py", "def g(): pass\n"),
+        ]);
+        assert_eq!(dot.matches("\"a.py::f\" -> \"b.py::g\";").count(), 1);
+    }
+
+    #[test]
+    fn subgraph_keeps_picked_definitions() {
+        let files = [analyze(
+            &Python,
+            "a.py",
+            "def a():\n    b()\ndef b():\n    c()\ndef c(): pass\n",
+        )];
+        let graph = Graph::build(&files);
+        let dot = render_graph(&graph, &|i| i > 0, &Labels::default());
+        assert!(!dot.contains("\"a.py::a\""));
+        assert!(dot.contains("\"a.py::b\" -> \"a.py::c\";"));
+        assert!(dot.contains("{rank=min; \"a.py::b\";}"));
+    }
+
+    #[test]
+    fn local_definition_preferred() {
+        let dot = plain(&[
+            analyze(&Python, "a.py", "def h(): pass\ndef f():\n    h()\n"),
+            analyze(&Python, "b.py", "def h(): pass\n"),
+        ]);
+        assert!(dot.contains("\"a.py::f\" -> \"a.py::h\";"));
+        assert!(!dot.contains("-> \"b.py::h\""));
+    }
+
+    #[test]
+    fn profiled_functions_labeled_and_hot_filled() {
+        let mut files = [analyze(
+            &Python,
+            "a.py",
+            "def f():\n    g()\ndef g(): pass\n",
+        )];
+        let p = profile::Profile::parse("f;g 20\n").unwrap();
+        profile::attach(&mut files, &p);
+        let dot = plain(&files);
+        assert!(dot.contains(
+            "\"a.py::g\" [label=\"g\\n20 samples\", shape=box, style=filled, fillcolor=\"#f4a6a6\"];"
+        ));
+    }
+
+    #[test]
+    fn third_party_in_own_cluster_with_boundary_edges() {
+        let mut files = [
+            analyze(&Python, "a.py", "def f():\n    g()\n"),
+            analyze(&Python, "vendor/b.py", "def g():\n    h()\ndef h(): pass\n"),
+        ];
+        deps::tag_vendored(&mut files, &[]);
+        let dot = plain(&files);
+        let boundary = dot.find("subgraph cluster_third_party {").unwrap();
+        assert!(dot.find("label=\"a.py\"").unwrap() < boundary);
+        assert!(
+            dot[boundary..]
+                .contains("        subgraph cluster_1 {\n            label=\"vendor/b.py\";")
+        );
+        assert!(dot.contains("\"a.py::f\" -> \"vendor/b.py::g\" [style=dashed];"));
+        assert!(dot.contains("\"vendor/b.py::g\" -> \"vendor/b.py::h\";"));
+    }
+
+    #[test]
+    fn label_templates() {
+        let labels = Labels {
+            node: Some(LabelTemplate::new("{kind}:{qualified_name} ({lines}L)").unwrap()),
+            tooltip: Some(LabelTemplate::new("{file}:{line}").unwrap()),
+        };
+        let dot = render(
+            &[analyze(
+                &Python,
+                "b.py",
+                "class C:\n    def m(self): pass\n",
+            )],
+            &labels,
+        );
+        assert!(
+            dot.contains("\"b.py::C.m\" [label=\"fn:C.m (1L)\", shape=box, tooltip=\"b.py:2\"];")
+        );
+    }
+
+    #[test]
+    fn ambiguous_calls_dropped() {
+        let dot = plain(&[
+            analyze(&Python, "a.py", "def f():\n    h()\n"),
+            analyze(&Python, "b.py", "def h(): pass\n"),
+            analyze(&Python, "c.py", "def h(): pass\n"),
+        ]);
+        assert!(!dot.contains("->"));
+    }
+
+    #[test]
+    fn diff_colors_added_and_removed_edges() {
+        let old = [analyze(
+            &Python,
+            "a.py",
+            "def f():\n    g()\ndef g(): pass\ndef h(): pass\n",
+        )];
+        let new = [analyze(
+            &Python,
+            "a.py",
+            "def f():\n    h()\ndef g(): pass\ndef h(): pass\ndef k(): pass\n",
+        )];
+        let dot = render_diff(&Diff::new(&old, &new));
+        assert!(dot.contains("\"a.py::f\" -> \"a.py::h\" [color=green, fontcolor=green];"));
+        assert!(
+            dot.contains("\"a.py::f\" -> \"a.py::g\" [color=red, fontcolor=red, style=dashed];")
+        );
+        assert!(
+            dot.contains("\"a.py::k\" [label=\"k\", shape=box, color=green, fontcolor=green];")
+        );
+        assert!(dot.contains("\"a.py::f\" [label=\"f\", shape=box];"));
+    }
+
+    #[test]
+    fn diff_labels_renames() {
+        let body = "():\n    x = load()\n    check(x)\n    save(x)\n";
+        let (old, new) = (format!("def f{body}"), format!("def g{body}"));
+        let (old, new) = (
+            [analyze(&Python, "a.py", &old)],
+            [analyze(&Python, "a.py", &new)],
+        );
+        let dot = render_diff(&Diff::new(&old, &new));
+        assert!(dot.contains(
+            "\"a.py::g\" [label=\"g (was a.py::f)\", shape=box, color=blue, fontcolor=blue];"
+        ));
+        assert!(!dot.contains("\"a.py::f\" ["));
+    }
+
+    #[test]
+    fn quotes_escaped() {
+        assert_eq!(quote("a\"b"), "\"a\\\"b\"");
+        assert_eq!(quote("f\n3 samples"), "\"f\\n3 samples\"");
+    }
+}
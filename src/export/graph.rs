@@ -0,0 +1,245 @@
+use crate::api::tree_sitter::{Span, Syntax};
+use crate::deps;
+use crate::graph::{DefKind, Graph};
+use serde::Serialize;
+
+/// The call graph as plain `nodes` and `edges` arrays: a node per
+/// definition and an edge per resolved call (see [`Graph::build`]), then
+/// one per spawned definition, type named and fixture taken, for tools
+/// that want the graph without walking per-file syntax trees.
+#[derive(Debug, Serialize)]
+pub struct NodeLink<'a> {
+    pub nodes: Vec<Node<'a>>,
+    pub edges: Vec<Edge<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Node<'a> {
+    /// `file::Qual.name`, unique across the tree.
+    pub id: &'a str,
+    pub file: &'a str,
+    pub name: &'a str,
+    /// `fn`, `type` or `fixture`.
+    pub kind: DefKind,
+    /// The definition under its module path (see [`crate::modules`]).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<&'a str>,
+    pub span: Span,
+    /// Vendored or from an installed package (see [`deps::is_third_party`]).
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub third_party: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Edge<'a> {
+    /// Id of the calling definition.
+    pub source: &'a str,
+    /// Id of the definition called.
+    pub target: &'a str,
+    /// Call sites behind a call or spawn edge.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub calls: Option<usize>,
+    /// Signature types or fields behind a `uses_type` edge.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sites: Option<usize>,
+    /// Set when `target` is spawned, not called: the spawning call.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spawned_by: Option<&'a str>,
+    /// Set when `target` is a type `source` names in its signature or
+    /// fields, not calls.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub uses_type: bool,
+    /// Set when `target` is a fixture the test `source` takes.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub uses_fixture: bool,
+}
+
+/// `graph` as nodes and edges, keeping the definitions `keep` picks by
+/// index and the edges between them.
+pub fn node_link<'a>(graph: &'a Graph<'a>, keep: &dyn Fn(usize) -> bool) -> NodeLink<'a> {
+    let nodes = graph
+        .defs
+        .iter()
+        .enumerate()
+        .filter(|&(i, _)| keep(i))
+        .map(|(_, d)| Node {
+            id: &d.id,
+            file: d.file,
+            name: d.name,
+            kind: d.kind,
+            symbol: d.symbol.as_deref(),
+            span: d.syntax.metadata.span,
+            third_party: deps::is_third_party(d.syntax),
+        })
+        .collect();
+    let edges = graph
+        .edges
+        .iter()
+        .zip(&graph.sites)
+        .filter(|&(&(a, b), _)| keep(a) && keep(b))
+        .map(|(&(a, b), &calls)| Edge {
+            source: &graph.defs[a].id,
+            target: &graph.defs[b].id,
+            calls: Some(calls),
+            sites: None,
+            spawned_by: None,
+            uses_type: false,
+            uses_fixture: false,
+        })
+        .chain(
+            graph
+                .spawns
+                .iter()
+                .filter(|s| keep(s.from) && keep(s.to))
+                .map(|s| Edge {
+                    source: &graph.defs[s.from].id,
+                    target: &graph.defs[s.to].id,
+                    calls: Some(s.sites),
+                    sites: None,
+                    spawned_by: Some(s.via),
+                    uses_type: false,
+                    uses_fixture: false,
+                }),
+        )
+        .chain(
+            graph
+                .type_uses
+                .iter()
+                .filter(|u| keep(u.from) && keep(u.to))
+                .map(|u| Edge {
+                    source: &graph.defs[u.from].id,
+                    target: &graph.defs[u.to].id,
+                    calls: None,
+                    sites: Some(u.sites),
+                    spawned_by: None,
+                    uses_type: true,
+                    uses_fixture: false,
+                }),
+        )
+        .chain(
+            graph
+                .fixture_uses
+                .iter()
+                .filter(|&&(a, b)| keep(a) && keep(b))
+                .map(|&(a, b)| Edge {
+                    source: &graph.defs[a].id,
+                    target: &graph.defs[b].id,
+                    calls: None,
+                    sites: None,
+                    spawned_by: None,
+                    uses_type: false,
+                    uses_fixture: true,
+                }),
+        )
+        .collect();
+    NodeLink { nodes, edges }
+}
+
+/// [`node_link`] of `files`, serialized.
+pub fn render(files: &[Vec<Syntax>]) -> serde_json::Result<serde_json::Value> {
+    serde_json::to_value(node_link(&Graph::build(files), &|_| true))
+}
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::tree_sitter::py::Python;
+    use crate::api::tree_sitter::rs::Rust;
+    use crate::api::tree_sitter::{ASTNode, analyze};
+    use serde_json::json;
+
+    #[test]
+    fn calls_across_files_become_edges() {
+        let value = render(&[
+            analyze(&Python, "a.py", "def f():\n    g()\n    g()\n"),
+            analyze(&Python, "b.py", "class C:\n    def g(self): pass\n"),
+        ])
+        .unwrap();
+        assert_eq!(
+            value["nodes"][0],
+            json!({
+                "id": "a.py::f", "file": "a.py", "name": "f", "kind": "fn",
+                "span": {
+                    "start_line": 1, "end_line": 3, "start_col": 1, "end_col": 8,
+                    "start_byte": 0, "end_byte": 24,
+                },
+            })
+        );
+        let ids: Vec<&str> = value["nodes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|n| n["id"].as_str().unwrap())
+            .collect();
+        assert_eq!(ids, ["a.py::f", "b.py::C", "b.py::C.g"]);
+        assert_eq!(
+            value["edges"],
+            json!([{"source": "a.py::f", "target": "b.py::C.g", "calls": 2}])
+        );
+    }
+
+    #[test]
+    fn symbols_under_module_paths() {
+        let mut files = [analyze(
+            &Python,
+            "pkg/net.py",
+            "class C:\n    def send(self): pass\n",
+        )];
+        if let ASTNode::File(f) = &mut files[0][0].node {
+            f.module = Some("pkg.net".into());
+        }
+        let value = render(&files).unwrap();
+        assert_eq!(value["nodes"][1]["symbol"], "pkg.net.C.send");
+    }
+
+    #[test]
+    fn spawns_follow_call_edges() {
+        let src = "import asyncio\nasync def poll(): pass\ndef main():\n    asyncio.create_task(poll())\n";
+        let value = render(&[analyze(&Python, "a.py", src)]).unwrap();
+        assert_eq!(
+            value["edges"],
+            json!([{
+                "source": "a.py::main", "target": "a.py::poll", "calls": 1,
+                "spawned_by": "asyncio.create_task",
+            }])
+        );
+    }
+
+    #[test]
+    fn type_uses_follow_spawns() {
+        let src = "class C: pass\ndef make() -> C:\n    return C()\n";
+        let value = render(&[analyze(&Python, "a.py", src)]).unwrap();
+        assert_eq!(
+            value["edges"],
+            json!([
+                {"source": "a.py::make", "target": "a.py::C", "calls": 1},
+                {"source": "a.py::make", "target": "a.py::C", "sites": 1, "uses_type": true},
+            ])
+        );
+    }
+
+    #[test]
+    fn unresolved_calls_have_no_edge() {
+        let value = render(&[analyze(&Python, "a.py", "def f():\n    print()\n")]).unwrap();
+        assert_eq!(value["nodes"].as_array().unwrap().len(), 1);
+        assert_eq!(value["edges"], json!([]));
+    }
+
+    #[test]
+    fn types_are_nodes() {
+        let src =
+            "struct S;\nimpl S {\n    fn new() -> S { S }\n}\nfn main() {\n    S::new();\n}\n";
+        let files = [analyze(&Rust, "m.rs", src)];
+        let value = render(&files).unwrap();
+        let kinds: Vec<&str> = value["nodes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|n| n["kind"].as_str().unwrap())
+            .collect();
+        assert!(kinds.contains(&"type"));
+        assert_eq!(value["edges"][0]["target"], "m.rs::S.new");
+    }
+}
@@ -0,0 +1,325 @@
+use super::definition;
+use crate::api::tree_sitter::{ASTNode, Syntax};
+use crate::diff::{Change, DefChange, Diff, EdgeChange};
+use crate::labels::{Labels, Node};
+use crate::links;
+use crate::profile;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+
+/// Self-contained HTML page: one collapsible section per file listing its
+/// definitions (with line spans) and the calls made in each. Profiled
+/// functions show their sample counts, hot ones highlighted; linked nodes
+/// (`--link-template`) link to their source. Definitions are labeled and
+/// given titles per `labels`.
+pub fn render(files: &[Vec<Syntax>], labels: &Labels) -> String {
+    let mut out = String::from(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>bolo report</title>\n\
+         <style>body{font-family:sans-serif}code{color:#555}li{margin:2px 0}\
+         .hot>b{background:#f4a6a6}</style>\n\
+         </head>\n<body>\n<h1>bolo report</h1>\n",
+    );
+    let _ = writeln!(out, "<p>{} files</p>", files.len());
+    for nodes in files {
+        let Some(ASTNode::File(file)) = nodes.first().map(|s| &s.node) else {
+            continue;
+        };
+        let _ = writeln!(
+            out,
+            "<details open>\n<summary>{} <code>{} lines</code></summary>",
+            linked(&nodes[0], escape(&file.path)),
+            nodes[0].metadata.lines
+        );
+        list(&nodes[1..], &file.path, "", labels, &mut out);
+        out.push_str("</details>\n");
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+/// Definitions in `nodes`, nested in `prefix` (dotted, as in graph ids).
+fn list(nodes: &[Syntax], file: &str, prefix: &str, labels: &Labels, out: &mut String) {
+    let defs: Vec<_> = nodes
+        .iter()
+        .filter_map(|s| definition(&s.node).map(|d| (d, s)))
+        .collect();
+    if defs.is_empty() {
+        return;
+    }
+    out.push_str("<ul>\n");
+    for ((kind, name), s) in defs {
+        let meta = &s.metadata;
+        let hot = meta.metric(profile::HOT).is_some();
+        let qualified = match prefix {
+            "" => name.to_string(),
+            _ => format!("{prefix}.{name}"),
+        };
+        let node = Node {
+            kind: kind.as_str(),
+            name,
+            qualified_name: &qualified,
+            file,
+            line: meta.span.start_line,
+            end_line: meta.span.end_line,
+            lines: meta.lines,
+        };
+        let title = match labels.title(&node) {
+            Some(t) => format!(" title=\"{}\"", escape(&t)),
+            None => String::new(),
+        };
+        let _ = write!(
+            out,
+            "<li{}>{kind} <b{title}>{}</b> <code>L{}–{}</code>",
+            if hot { " class=\"hot\"" } else { "" },
+            linked(s, escape(&labels.label(&node))),
+            meta.span.start_line,
+            meta.span.end_line
+        );
+        if let (Some(total), Some(own)) = (
+            meta.metric(profile::SAMPLES),
+            meta.metric(profile::SELF_SAMPLES),
+        ) {
+            let _ = write!(out, " <code>{total} samples ({own} self)</code>");
+        }
+        let calls: Vec<_> = s
+            .contains
+            .iter()
+            .filter_map(|c| match &c.node {
+                ASTNode::Call(call) => Some(escape(&call.name)),
+                _ => None,
+            })
+            .collect();
+        if !calls.is_empty() {
+            let _ = write!(out, " → <code>{}</code>", calls.join(", "));
+        }
+        out.push('\n');
+        list(&s.contains, file, &qualified, labels, out);
+        out.push_str("</li>\n");
+    }
+    out.push_str("</ul>\n");
+}
+
+/// `html` wrapped in an anchor to the node's source link, if it has one.
+fn linked(s: &Syntax, html: String) -> String {
+    match s.metadata.metric(links::LINK).and_then(|v| v.as_str()) {
+        Some(url) => format!("<a href=\"{}\">{html}</a>", escape(url)),
+        None => html,
+    }
+}
+
+/// [`render`] for a [`Diff`]: per file, the definitions and outgoing call
+/// edges of the union graph, added in green, removed in red and renamed
+/// in blue.
+pub fn render_diff(diff: &Diff) -> String {
+    let mut out = String::from(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>bolo diff</title>\n\
+         <style>body{font-family:sans-serif}code{color:#555}li{margin:2px 0}\
+         .added{color:green}.removed{color:red;text-decoration:line-through}.renamed{color:blue}</style>\n\
+         </head>\n<body>\n<h1>bolo diff</h1>\n",
+    );
+    let count = |change| {
+        let defs = diff.defs.iter().filter(|d| d.change == change).count();
+        let edges = diff.edges.iter().filter(|e| e.change == change).count();
+        (defs, edges)
+    };
+    let (added, removed) = (count(Change::Added), count(Change::Removed));
+    let _ = writeln!(
+        out,
+        "<p><span class=\"added\">+{} definitions, +{} edges</span> \
+         <span class=\"removed\">−{} definitions, −{} edges</span> \
+         <span class=\"renamed\">{} renamed</span></p>",
+        added.0,
+        added.1,
+        removed.0,
+        removed.1,
+        count(Change::Renamed).0
+    );
+
+    let mut files: BTreeMap<&str, (Vec<&DefChange>, Vec<&EdgeChange>)> = BTreeMap::new();
+    let file_of: HashMap<&str, &str> = diff
+        .defs
+        .iter()
+        .map(|d| (d.id.as_str(), d.file.as_str()))
+        .collect();
+    for d in &diff.defs {
+        files.entry(&d.file).or_default().0.push(d);
+    }
+    for e in &diff.edges {
+        if let Some(file) = file_of.get(e.from.as_str()) {
+            files.entry(file).or_default().1.push(e);
+        }
+    }
+
+    for (file, (defs, edges)) in files {
+        let changed = defs.iter().any(|d| d.change != Change::Unchanged)
+            || edges.iter().any(|e| e.change != Change::Unchanged);
+        let _ = writeln!(
+            out,
+            "<details{}>\n<summary>{}</summary>\n<ul>",
+            if changed { " open" } else { "" },
+            escape(file)
+        );
+        for d in defs {
+            let from = match &d.from {
+                Some(from) => format!("<code>{}</code> → ", escape(from)),
+                None => String::new(),
+            };
+            let _ = writeln!(
+                out,
+                "<li class=\"{}\">{} {from}<b>{}</b></li>",
+                class(d.change),
+                d.kind,
+                escape(&d.name)
+            );
+        }
+        for e in edges {
+            let _ = writeln!(
+                out,
+                "<li class=\"{}\"><code>{}</code> → <code>{}</code></li>",
+                class(e.change),
+                escape(&e.from),
+                escape(&e.to)
+            );
+        }
+        out.push_str("</ul>\n</details>\n");
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+fn class(change: Change) -> &'static str {
+    match change {
+        Change::Added => "added",
+        Change::Removed => "removed",
+        Change::Renamed => "renamed",
+        Change::Unchanged => "unchanged",
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::tree_sitter::analyze;
+    use crate::api::tree_sitter::rs::Rust;
+    use crate::labels::LabelTemplate;
+
+    fn plain(files: &[Vec<Syntax>]) -> String {
+        render(files, &Labels::default())
+    }
+
+    #[test]
+    fn lists_files_and_definitions() {
+        let html = plain(&[analyze(&Rust, "lib.rs", "fn run() {\n    go();\n}\n")]);
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<p>1 files</p>"));
+        assert!(html.contains("<summary>lib.rs <code>4 lines</code></summary>"));
+        assert!(html.contains("<li>fn <b>run</b> <code>L1–3</code> → <code>go</code>"));
+        assert!(html.ends_with("</html>\n"));
+    }
+
+    #[test]
+    fn nested_definitions() {
+        let html = plain(&[analyze(
+            &Rust,
+            "a.rs",
+            "struct S;\nimpl S {\n    fn m() {}\n}\n",
+        )]);
+        assert!(html.contains("<b>m</b>"));
+        assert!(html.matches("<ul>").count() >= 2);
+    }
+
+    #[test]
+    fn profiled_functions_show_samples() {
+        let mut files = [analyze(
+            &Rust,
+            "lib.rs",
+            "fn run() { go(); }\nfn go() {}\nfn idle() {}\n",
+        )];
+        let p = profile::Profile::parse("run;go 9\nidle 1\n").unwrap();
+        profile::attach(&mut files, &p);
+        let html = plain(&files);
+        assert!(html.contains(
+            "<li class=\"hot\">fn <b>go</b> <code>L2–2</code> <code>9 samples (9 self)</code>"
+        ));
+        assert!(html.contains("<li class=\"hot\">fn <b>idle</b>"));
+        let html = plain(&[analyze(&Rust, "lib.rs", "fn run() {}\n")]);
+        assert!(!html.contains("samples"));
+    }
+
+    #[test]
+    fn linked_nodes_become_anchors() {
+        let mut files = [analyze(&Rust, "lib.rs", "fn run() {}\n")];
+        let t = links::LinkTemplate::new("https://x/{path}?a=1&l={start}").unwrap();
+        links::attach(&mut files, &t, &Default::default());
+        let html = plain(&files);
+        assert!(html.contains("<summary><a href=\"https://x/lib.rs?a=1&amp;l=1\">lib.rs</a>"));
+        assert!(html.contains("<b><a href=\"https://x/lib.rs?a=1&amp;l=1\">run</a></b>"));
+    }
+
+    #[test]
+    fn diff_marks_changes() {
+        let old = [analyze(&Rust, "a.rs", "fn f() { g(); }\nfn g() {}\n")];
+        let new = [analyze(
+            &Rust,
+            "a.rs",
+            "fn f() { h(); }\nfn g() {}\nfn h() {}\n",
+        )];
+        let html = render_diff(&Diff::new(&old, &new));
+        assert!(html.contains("+1 definitions, +1 edges"));
+        assert!(html.contains("−0 definitions, −1 edges"));
+        assert!(html.contains("<details open>\n<summary>a.rs</summary>"));
+        assert!(html.contains("<li class=\"added\">fn <b>h</b></li>"));
+        assert!(
+            html.contains("<li class=\"removed\"><code>a.rs::f</code> → <code>a.rs::g</code></li>")
+        );
+    }
+
+    #[test]
+    fn diff_shows_renames() {
+        let old = [analyze(
+            &Rust,
+            "a.rs",
+            "fn f() { load(); check(); save(); }\n",
+        )];
+        let new = [analyze(
+            &Rust,
+            "b.rs",
+            "fn f() { load(); check(); save(); }\n",
+        )];
+        let html = render_diff(&Diff::new(&old, &new));
+        assert!(html.contains("<span class=\"renamed\">1 renamed</span>"));
+        assert!(html.contains("<li class=\"renamed\">fn <code>a.rs::f</code> → <b>f</b></li>"));
+        assert!(!html.contains("<summary>a.rs</summary>"));
+    }
+
+    #[test]
+    fn label_templates() {
+        let labels = Labels {
+            node: Some(LabelTemplate::new("{qualified_name} ({lines}L)").unwrap()),
+            tooltip: Some(LabelTemplate::new("<{file}>").unwrap()),
+        };
+        let html = render(
+            &[analyze(
+                &Rust,
+                "a.rs",
+                "struct S;\nimpl S {\n    fn m() {}\n}\n",
+            )],
+            &labels,
+        );
+        assert!(html.contains("fn <b title=\"&lt;a.rs&gt;\">S.m (1L)</b>"));
+    }
+
+    #[test]
+    fn escapes_markup() {
+        assert_eq!(escape("Vec<&T>"), "Vec&lt;&amp;T&gt;");
+    }
+}
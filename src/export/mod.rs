@@ -0,0 +1,204 @@
+pub mod callgrind;
+pub mod cytoscape;
+pub mod dot;
+pub mod graph;
+pub mod html;
+pub mod summary;
+pub mod viewer;
+
+use crate::analysis::Analysis;
+use crate::api::tree_sitter::ASTNode;
+use crate::diff::Diff;
+use crate::error::BoloError;
+use crate::graph::DefKind;
+use crate::labels::Labels;
+use serde::Serialize;
+use std::io::IsTerminal;
+use std::path::Path;
+
+/// Output format, picked from the output file's extension.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Format {
+    #[default]
+    Json,
+    Dot,
+    Html,
+    Callgrind,
+    Cytoscape,
+    /// Only by `--format graph`; no extension picks it.
+    Graph,
+}
+
+impl Format {
+    /// `.dot`/`.gv` → Dot, `.html`/`.htm` → Html, `.callgrind` or a
+    /// `callgrind.out*` name → Callgrind, `.cyjs` → Cytoscape, anything
+    /// else → Json.
+    pub fn from_path(path: &Path) -> Self {
+        if path
+            .file_name()
+            .is_some_and(|n| n.to_string_lossy().starts_with("callgrind.out"))
+        {
+            return Format::Callgrind;
+        }
+        let ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_ascii_lowercase);
+        match ext.as_deref() {
+            Some("dot" | "gv") => Format::Dot,
+            Some("html" | "htm") => Format::Html,
+            Some("callgrind") => Format::Callgrind,
+            Some("cyjs") => Format::Cytoscape,
+            _ => Format::Json,
+        }
+    }
+}
+
+/// How JSON is laid out: indented for people, or minified with sorted keys
+/// for tools.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layout {
+    Pretty,
+    Compact,
+}
+
+impl Layout {
+    /// `forced` if given, else pretty when stdout is a terminal and compact
+    /// in files and pipes.
+    pub fn pick(forced: Option<Layout>, to_file: bool) -> Self {
+        forced.unwrap_or_else(|| Layout::unforced(!to_file && std::io::stdout().is_terminal()))
+    }
+
+    fn unforced(terminal: bool) -> Self {
+        match terminal {
+            true => Layout::Pretty,
+            false => Layout::Compact,
+        }
+    }
+}
+
+/// `value` as JSON in `layout`.
+pub fn json(value: &impl Serialize, layout: Layout) -> Result<String, BoloError> {
+    Ok(match layout {
+        Layout::Pretty => serde_json::to_string_pretty(value)?,
+        // Through `Value`, whose objects are ordered by key.
+        Layout::Compact => serde_json::to_string(&serde_json::to_value(value)?)?,
+    })
+}
+
+/// Render an analysis in `format`. Only JSON carries the warnings; `labels`
+/// apply to `.dot` and `.html`.
+pub fn render(
+    format: Format,
+    analysis: &Analysis,
+    layout: Layout,
+    labels: &Labels,
+) -> Result<String, BoloError> {
+    Ok(match format {
+        Format::Json => json(analysis, layout)?,
+        Format::Dot => dot::render(&analysis.files, labels),
+        Format::Html => html::render(&analysis.files, labels),
+        Format::Callgrind => callgrind::render(&analysis.files),
+        Format::Cytoscape => json(&cytoscape::render(&analysis.files)?, layout)?,
+        Format::Graph => json(&graph::render(&analysis.files)?, layout)?,
+    })
+}
+
+/// Render a [`Diff`] in `format`. JSON lists only what changed; the graph
+/// formats draw the union with changes highlighted. Callgrind, Cytoscape
+/// and node-link graphs have no way to show a change.
+pub fn render_diff(format: Format, diff: &Diff, layout: Layout) -> Result<String, BoloError> {
+    Ok(match format {
+        Format::Json => json(&diff.changes(), layout)?,
+        Format::Dot => dot::render_diff(diff),
+        Format::Html => html::render_diff(diff),
+        Format::Callgrind | Format::Cytoscape | Format::Graph => {
+            return Err(BoloError::Unsupported {
+                flag: "output",
+                context: "with a Callgrind, Cytoscape or graph file by diff",
+            });
+        }
+    })
+}
+
+/// Kind and name of nodes that define something (functions, types, fixtures).
+pub(crate) fn definition<'a>(node: &'a ASTNode) -> Option<(DefKind, &'a str)> {
+    match node {
+        ASTNode::Function(f) => Some((DefKind::Function, &f.name)),
+        ASTNode::Type(t) => Some((DefKind::Type, &t.name)),
+        ASTNode::Fixture(f) => Some((DefKind::Fixture, &f.name)),
+        _ => None,
+    }
+}
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_from_extension() {
+        assert_eq!(Format::from_path(Path::new("dag.json")), Format::Json);
+        assert_eq!(Format::from_path(Path::new("dag.dot")), Format::Dot);
+        assert_eq!(Format::from_path(Path::new("dag.GV")), Format::Dot);
+        assert_eq!(Format::from_path(Path::new("report.html")), Format::Html);
+        assert_eq!(Format::from_path(Path::new("report.htm")), Format::Html);
+        assert_eq!(
+            Format::from_path(Path::new("g.callgrind")),
+            Format::Callgrind
+        );
+        assert_eq!(
+            Format::from_path(Path::new("out/callgrind.out.bolo")),
+            Format::Callgrind
+        );
+    }
+
+    #[test]
+    fn unknown_extension_is_json() {
+        assert_eq!(Format::from_path(Path::new("out.txt")), Format::Json);
+        assert_eq!(Format::from_path(Path::new("out")), Format::Json);
+    }
+
+    #[test]
+    fn render_diff_json_omits_unchanged() {
+        let json = render_diff(Format::Json, &Diff::default(), Layout::Pretty).unwrap();
+        assert_eq!(json, "{\n  \"defs\": [],\n  \"edges\": []\n}");
+    }
+
+    #[test]
+    fn render_json_is_envelope() {
+        let json = render(
+            Format::Json,
+            &Analysis::default(),
+            Layout::Pretty,
+            &Labels::default(),
+        )
+        .unwrap();
+        assert_eq!(json, "{\n  \"files\": [],\n  \"warnings\": []\n}");
+    }
+
+    #[test]
+    fn compact_json_sorts_keys() {
+        #[derive(Serialize)]
+        struct S {
+            b: u8,
+            a: [u8; 2],
+        }
+        let s = S { b: 1, a: [2, 3] };
+        assert_eq!(json(&s, Layout::Compact).unwrap(), r#"{"a":[2,3],"b":1}"#);
+        assert_eq!(
+            json(&s, Layout::Pretty).unwrap(),
+            "{\n  \"b\": 1,\n  \"a\": [\n    2,\n    3\n  ]\n}"
+        );
+    }
+
+    #[test]
+    fn layout_pretty_only_for_terminals() {
+        assert_eq!(Layout::unforced(true), Layout::Pretty);
+        assert_eq!(Layout::unforced(false), Layout::Compact);
+        assert_eq!(Layout::pick(None, true), Layout::Compact);
+        assert_eq!(Layout::pick(Some(Layout::Pretty), true), Layout::Pretty);
+        assert_eq!(Layout::pick(Some(Layout::Compact), false), Layout::Compact);
+    }
+}
@@ -0,0 +1,194 @@
+use crate::analysis::Analysis;
+use crate::api::tree_sitter::{ASTNode, Syntax};
+use crate::diff::{Change, Diff};
+use crate::rules::Violation;
+use std::fmt::Write;
+
+/// Rows listed per section before the rest are summed up.
+const TOP: usize = 10;
+
+/// Markdown job summary for GitHub Actions: counts, file cycles and the
+/// first rule violations.
+pub fn render(analysis: &Analysis, cycles: &[Vec<&str>], violations: &[Violation]) -> String {
+    let (mut functions, mut types, mut calls) = (0, 0, 0);
+    for nodes in &analysis.files {
+        count(nodes, &mut functions, &mut types, &mut calls);
+    }
+    let mut out = String::from("## bolo\n\n");
+    out.push_str("| Files | Functions | Types | Calls | Warnings | Cycles | Violations |\n");
+    out.push_str("| ----: | --------: | ----: | ----: | -------: | -----: | ---------: |\n");
+    let _ = writeln!(
+        out,
+        "| {} | {functions} | {types} | {calls} | {} | {} | {} |",
+        analysis.files.len(),
+        analysis.warnings.len(),
+        cycles.len(),
+        violations.len()
+    );
+
+    if cycles.is_empty() && violations.is_empty() {
+        out.push_str("\nNo cycles or rule violations.\n");
+        return out;
+    }
+    if !cycles.is_empty() {
+        let _ = writeln!(out, "\n### Cycles\n");
+        for c in cycles.iter().take(TOP) {
+            let files: Vec<String> = c.iter().map(|f| format!("`{f}`")).collect();
+            let _ = writeln!(out, "- {}", files.join(" \u{2192} "));
+        }
+        more(&mut out, cycles.len());
+    }
+    if !violations.is_empty() {
+        let _ = writeln!(out, "\n### Rule violations\n");
+        out.push_str("| Location | Rule | Message |\n| -------- | ---- | ------- |\n");
+        for v in violations.iter().take(TOP) {
+            let _ = writeln!(
+                out,
+                "| `{}:{}` | {} | {} |",
+                v.file,
+                v.span.start_line,
+                v.rule,
+                cell(&v.message)
+            );
+        }
+        more(&mut out, violations.len());
+    }
+    out
+}
+
+/// [`render`] for a [`Diff`]: counts per change and the first changed
+/// definitions.
+pub fn render_diff(diff: &Diff) -> String {
+    let defs = |c| diff.defs.iter().filter(|d| d.change == c).count();
+    let edges = |c| diff.edges.iter().filter(|e| e.change == c).count();
+    let mut out = String::from("## bolo diff\n\n");
+    out.push_str("| | Added | Removed | Renamed |\n| - | ----: | ------: | ------: |\n");
+    let _ = writeln!(
+        out,
+        "| Definitions | {} | {} | {} |",
+        defs(Change::Added),
+        defs(Change::Removed),
+        defs(Change::Renamed)
+    );
+    let _ = writeln!(
+        out,
+        "| Call edges | {} | {} | |",
+        edges(Change::Added),
+        edges(Change::Removed)
+    );
+
+    let changed: Vec<_> = diff
+        .defs
+        .iter()
+        .filter(|d| d.change != Change::Unchanged)
+        .collect();
+    if changed.is_empty() {
+        out.push_str("\nNo definitions changed.\n");
+        return out;
+    }
+    out.push_str("\n### Highlights\n\n");
+    for d in changed.iter().take(TOP) {
+        let _ = match (d.change, &d.from) {
+            (Change::Renamed, Some(from)) => {
+                writeln!(out, "- renamed {} `{from}` \u{2192} `{}`", d.kind, d.id)
+            }
+            (Change::Added, _) => writeln!(out, "- added {} `{}`", d.kind, d.id),
+            _ => writeln!(out, "- removed {} `{}`", d.kind, d.id),
+        };
+    }
+    more(&mut out, changed.len());
+    out
+}
+
+pub(crate) fn count(nodes: &[Syntax], functions: &mut usize, types: &mut usize, calls: &mut usize) {
+    for s in nodes {
+        match &s.node {
+            ASTNode::Function(_) => *functions += 1,
+            ASTNode::Type(_) => *types += 1,
+            ASTNode::Call(_) => *calls += 1,
+            _ => {}
+        }
+        count(&s.contains, functions, types, calls);
+    }
+}
+
+fn more(out: &mut String, total: usize) {
+    if total > TOP {
+        let _ = writeln!(out, "\n\u{2026} and {} more", total - TOP);
+    }
+}
+
+/// `s` safe inside a markdown table cell.
+fn cell(s: &str) -> String {
+    s.replace('|', "\\|").replace('\n', " ")
+}
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::tree_sitter::rs::Rust;
+    use crate::api::tree_sitter::{Span, analyze};
+
+    fn violation(line: usize) -> Violation {
+        Violation {
+            rule: "forbid",
+            file: "a.rs".into(),
+            function: None,
+            span: Span::lines(line, line),
+            message: "call to `a|b` matches forbidden `a*`".into(),
+            target: None,
+        }
+    }
+
+    #[test]
+    fn clean_run() {
+        let analysis = Analysis {
+            files: vec![analyze(
+                &Rust,
+                "a.rs",
+                "struct S;\nfn f() { g(); }\nfn g() {}\n",
+            )],
+            ..Default::default()
+        };
+        let md = render(&analysis, &[], &[]);
+        assert!(md.starts_with("## bolo\n"));
+        assert!(md.contains("| 1 | 2 | 1 | 1 | 0 | 0 | 0 |"));
+        assert!(md.contains("No cycles or rule violations."));
+    }
+
+    #[test]
+    fn cycles_and_top_violations() {
+        let cycles = vec![vec!["a.rs", "b.rs"]];
+        let violations: Vec<_> = (1..=12).map(violation).collect();
+        let md = render(&Analysis::default(), &cycles, &violations);
+        assert!(md.contains("- `a.rs` \u{2192} `b.rs`"));
+        assert!(md.contains("| `a.rs:1` | forbid | call to `a\\|b` matches forbidden `a*` |"));
+        assert!(!md.contains("`a.rs:11`"));
+        assert!(md.contains("\u{2026} and 2 more"));
+    }
+
+    #[test]
+    fn diff_highlights() {
+        let old = [analyze(
+            &Rust,
+            "a.rs",
+            "fn f() { g(); }\nfn g() {}\nfn r() { a(); b(); c(); }\n",
+        )];
+        let new = [analyze(
+            &Rust,
+            "a.rs",
+            "fn f() { h(); }\nfn h() { x(); y(); }\nfn s() { a(); b(); c(); }\n",
+        )];
+        let md = render_diff(&Diff::new(&old, &new));
+        assert!(md.contains("| Definitions | 1 | 1 | 1 |"));
+        assert!(md.contains("| Call edges | 1 | 1 | |"));
+        assert!(md.contains("- added fn `a.rs::h`"));
+        assert!(md.contains("- removed fn `a.rs::g`"));
+        assert!(md.contains("- renamed fn `a.rs::r` \u{2192} `a.rs::s`"));
+
+        let same = render_diff(&Diff::new(&old, &old));
+        assert!(same.contains("No definitions changed."));
+    }
+}
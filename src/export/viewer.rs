@@ -0,0 +1,154 @@
+/// Self-contained interactive page for a call graph in `--format graph`
+/// form: definitions laid out by a small force simulation, panned by
+/// dragging and zoomed with the wheel. Clicking a definition highlights it
+/// with its callers and callees and lists them, each clickable; the search
+/// box highlights definitions whose id contains the text. No scripts are
+/// fetched, so the page works offline.
+pub fn render(graph: &str) -> String {
+    // `<` escaped so the data cannot close its script element early.
+    let data = graph.replace('<', "\\u003c");
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>bolo graph</title>\n\
+         <style>{STYLE}</style>\n</head>\n<body>\n\
+         <header><b>bolo graph</b> <input id=\"q\" placeholder=\"search\" autofocus> \
+         <span id=\"count\"></span> <a href=\"/files.html\">files</a></header>\n\
+         <svg id=\"g\"><g id=\"view\"><g id=\"edges\"></g><g id=\"nodes\"></g></g></svg>\n\
+         <aside id=\"info\">Click a definition.</aside>\n\
+         <script type=\"application/json\" id=\"data\">{data}</script>\n\
+         <script>{SCRIPT}</script>\n</body>\n</html>\n"
+    )
+}
+
+const STYLE: &str = "body{margin:0;font-family:sans-serif;display:grid;\
+grid-template:auto 1fr/1fr 22em;height:100vh}\
+header{grid-column:1/3;padding:6px;border-bottom:1px solid #ccc}\
+svg{width:100%;height:100%;cursor:grab}aside{overflow:auto;padding:6px;\
+border-left:1px solid #ccc;font-size:14px}aside a{cursor:pointer;color:#06c}\
+line{stroke:#bbb}circle{fill:#69c;stroke:#fff}circle.type{fill:#c96}\
+circle.fixture{fill:#9c6}.third{opacity:.4}.dim{opacity:.1}\
+.hit{fill:#e33}text{font-size:10px;pointer-events:none}";
+
+const SCRIPT: &str = r#"
+const data = JSON.parse(document.getElementById('data').textContent);
+const NS = 'http://www.w3.org/2000/svg';
+const nodes = data.nodes, byId = new Map(nodes.map((n, i) => [n.id, i]));
+const edges = data.edges
+  .map(e => ({ s: byId.get(e.source), t: byId.get(e.target), e }))
+  .filter(e => e.s !== undefined && e.t !== undefined);
+const out = nodes.map(() => []), inn = nodes.map(() => []);
+for (const e of edges) { out[e.s].push(e); inn[e.t].push(e); }
+
+// Spring layout: edges pull, every pair pushes; a few hundred steps.
+const n = nodes.length, size = Math.max(400, Math.sqrt(n) * 60);
+const x = nodes.map((_, i) => size / 2 + Math.cos(i) * size * Math.sqrt(i / n) / 2);
+const y = nodes.map((_, i) => size / 2 + Math.sin(i) * size * Math.sqrt(i / n) / 2);
+const steps = n > 1500 ? 0 : 300;
+for (let step = 0; step < steps; step++) {
+  const dx = new Float64Array(n), dy = new Float64Array(n), heat = 1 - step / steps;
+  for (let i = 0; i < n; i++) for (let j = i + 1; j < n; j++) {
+    const ax = x[i] - x[j], ay = y[i] - y[j], d2 = ax * ax + ay * ay + 0.01;
+    const f = 900 / d2;
+    dx[i] += ax * f; dy[i] += ay * f; dx[j] -= ax * f; dy[j] -= ay * f;
+  }
+  for (const { s, t } of edges) {
+    const ax = x[t] - x[s], ay = y[t] - y[s];
+    dx[s] += ax * 0.02; dy[s] += ay * 0.02; dx[t] -= ax * 0.02; dy[t] -= ay * 0.02;
+  }
+  for (let i = 0; i < n; i++) {
+    dx[i] += (size / 2 - x[i]) * 0.005; dy[i] += (size / 2 - y[i]) * 0.005;
+    const m = Math.hypot(dx[i], dy[i]), cap = 10 * heat + 0.5;
+    if (m > cap) { dx[i] *= cap / m; dy[i] *= cap / m; }
+    x[i] += dx[i]; y[i] += dy[i];
+  }
+}
+
+const el = (tag, attrs, parent) => {
+  const e = document.createElementNS(NS, tag);
+  for (const k in attrs) e.setAttribute(k, attrs[k]);
+  return parent.appendChild(e);
+};
+const lines = edges.map(({ s, t }) =>
+  el('line', { x1: x[s], y1: y[s], x2: x[t], y2: y[t] }, document.getElementById('edges')));
+const circles = nodes.map((d, i) => {
+  const g = el('g', {}, document.getElementById('nodes'));
+  const c = el('circle', { cx: x[i], cy: y[i], r: 5, class: d.kind + (d.third_party ? ' third' : '') }, g);
+  el('title', {}, c).textContent = d.id;
+  el('text', { x: x[i] + 7, y: y[i] + 3 }, g).textContent = d.name;
+  c.addEventListener('click', ev => { ev.stopPropagation(); select(i); });
+  return g;
+});
+document.getElementById('count').textContent = `${n} definitions, ${edges.length} edges`;
+
+const info = document.getElementById('info');
+function link(i) {
+  const a = document.createElement('a');
+  a.textContent = nodes[i].id;
+  a.onclick = () => select(i);
+  return a;
+}
+function list(title, items) {
+  const h = document.createElement('h4');
+  h.textContent = `${title} (${items.length})`;
+  const ul = document.createElement('ul');
+  for (const { i, calls } of items) {
+    const li = ul.appendChild(document.createElement('li'));
+    li.append(link(i), ` ×${calls}`);
+  }
+  info.append(h, ul);
+}
+function select(i) {
+  const near = new Set([i, ...out[i].map(e => e.t), ...inn[i].map(e => e.s)]);
+  circles.forEach((g, j) => g.classList.toggle('dim', !near.has(j)));
+  lines.forEach((l, k) => l.classList.toggle('dim', edges[k].s !== i && edges[k].t !== i));
+  const d = nodes[i];
+  info.replaceChildren();
+  const h = info.appendChild(document.createElement('h3'));
+  h.textContent = d.name;
+  info.append(`${d.kind} in ${d.file}, lines ${d.span.start_line}–${d.span.end_line}`);
+  if (d.symbol) info.append(document.createElement('br'), d.symbol);
+  const calls = e => e.e.calls !== undefined;
+  list('calls', out[i].filter(calls).map(e => ({ i: e.t, calls: e.e.calls })));
+  list('called by', inn[i].filter(calls).map(e => ({ i: e.s, calls: e.e.calls })));
+}
+document.getElementById('g').addEventListener('click', () => {
+  circles.forEach(g => g.classList.remove('dim'));
+  lines.forEach(l => l.classList.remove('dim'));
+});
+document.getElementById('q').addEventListener('input', ev => {
+  const q = ev.target.value.toLowerCase();
+  circles.forEach((g, i) =>
+    g.firstChild.classList.toggle('hit', q !== '' && nodes[i].id.toLowerCase().includes(q)));
+});
+
+// Pan by dragging, zoom with the wheel around the pointer.
+const svg = document.getElementById('g'), view = document.getElementById('view');
+let scale = Math.min(1, 800 / size), tx = 0, ty = 0, drag = null;
+const apply = () => view.setAttribute('transform', `translate(${tx},${ty}) scale(${scale})`);
+svg.addEventListener('mousedown', ev => { drag = [ev.clientX - tx, ev.clientY - ty]; });
+addEventListener('mouseup', () => { drag = null; });
+addEventListener('mousemove', ev => {
+  if (drag) { tx = ev.clientX - drag[0]; ty = ev.clientY - drag[1]; apply(); }
+});
+svg.addEventListener('wheel', ev => {
+  ev.preventDefault();
+  const k = Math.exp(-ev.deltaY / 500), r = svg.getBoundingClientRect();
+  const px = ev.clientX - r.left, py = ev.clientY - r.top;
+  tx = px - (px - tx) * k; ty = py - (py - ty) * k; scale *= k; apply();
+}, { passive: false });
+apply();
+"#;
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn embeds_graph_without_closing_script() {
+        let page = render(r#"{"nodes":[{"id":"a.py::</script>"}],"edges":[]}"#);
+        assert!(page.starts_with("<!DOCTYPE html>"));
+        assert!(page.contains(r#"{"nodes":[{"id":"a.py::\u003c/script>"}],"edges":[]}"#));
+        assert_eq!(page.matches("</script>").count(), 2);
+    }
+}
@@ -0,0 +1,1269 @@
+use crate::analysis::Analysis;
+use crate::api::tree_sitter::{ASTNode, Call, Syntax};
+use crate::export::definition;
+use crate::modules;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::fmt;
+
+/// What a [`Def`] defines. Serialized and displayed as `fn`, `type` or
+/// `fixture`, the names graph outputs use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+#[non_exhaustive]
+pub enum DefKind {
+    #[serde(rename = "fn")]
+    Function,
+    #[serde(rename = "type")]
+    Type,
+    #[serde(rename = "fixture")]
+    Fixture,
+}
+
+impl DefKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DefKind::Function => "fn",
+            DefKind::Type => "type",
+            DefKind::Fixture => "fixture",
+        }
+    }
+}
+
+impl fmt::Display for DefKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A definition: its graph id, owning file and the calls made directly in it.
+pub struct Def<'a> {
+    /// `file::Qual.name`, unique across the tree: a later definition of the
+    /// same name in the file (a Rust `impl` after its struct, a redefined
+    /// Python function) adds `#2`, `#3`, ... in source order.
+    pub id: String,
+    pub file: &'a str,
+    pub name: &'a str,
+    pub kind: DefKind,
+    /// The qualified name under the file's module, `pkg.net.Client.send` or
+    /// `crate::net::Client::send` (see [`modules::symbol`]).
+    pub symbol: Option<String>,
+    /// The defining node, with its body.
+    pub syntax: &'a Syntax<'a>,
+    calls: Vec<&'a Call<'a>>,
+    /// Types named directly in it: signature types of a function, field
+    /// annotations of a type.
+    types: Vec<&'a str>,
+    /// Fixtures a test function takes by parameter name.
+    fixtures: Vec<&'a str>,
+}
+
+/// Iterator of [`Graph::walk`]: `(index into defs, distance)` pairs in
+/// order of distance.
+pub struct Walk {
+    next: Vec<Vec<usize>>,
+    seen: Vec<bool>,
+    queue: VecDeque<(usize, usize)>,
+}
+
+impl Iterator for Walk {
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (i, depth) = self.queue.pop_front()?;
+        for &j in &self.next[i] {
+            if !self.seen[j] {
+                self.seen[j] = true;
+                self.queue.push_back((j, depth + 1));
+            }
+        }
+        Some((i, depth))
+    }
+}
+
+/// Largest cycle, in file edges, whose cuts are searched exhaustively.
+const EXACT_EDGES: usize = 16;
+
+/// A file dependency to remove to help break cycles, with the number of
+/// call edges behind it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cut<'a> {
+    pub from: &'a str,
+    pub to: &'a str,
+    pub weight: usize,
+}
+
+/// A definition started as a task, thread or process by another.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Spawn<'a> {
+    /// Indices into [`Graph::defs`] of the spawning and spawned definitions.
+    pub from: usize,
+    pub to: usize,
+    /// The spawning call, `tokio::spawn` or `threading.Thread`.
+    pub via: &'a str,
+    /// How many spawned calls stand behind the edge.
+    pub sites: usize,
+}
+
+/// A type named by a definition: in a function's signature, or in the
+/// annotation of one of a type's fields.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeUse {
+    /// Indices into [`Graph::defs`] of the naming definition and the type.
+    pub from: usize,
+    pub to: usize,
+    /// How many signature types or fields stand behind the edge.
+    pub sites: usize,
+}
+
+/// How one definition reaches another.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum EdgeKind<'a> {
+    /// Called directly.
+    Call,
+    /// Started as a task, thread or process by the call named `via`.
+    Spawn { via: &'a str },
+    /// Names the type in its signature or a field.
+    UsesType,
+    /// Takes the fixture as a parameter.
+    UsesFixture,
+}
+
+/// One edge of a [`Graph`], as its traversal methods yield them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Edge<'a> {
+    /// Indices into [`Graph::defs`] of the calling and called definitions.
+    pub from: usize,
+    pub to: usize,
+    pub kind: EdgeKind<'a>,
+    /// Call sites behind the edge.
+    pub sites: usize,
+}
+
+/// Which edges a [`Graph::walk`] follows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// From caller to callee.
+    Callees,
+    /// From callee to caller.
+    Callers,
+    Both,
+}
+
+/// Definitions across all files and the call edges between them.
+///
+/// Calls, spawns, type uses and fixture uses become edges. Raised and
+/// handled exceptions and route endpoints do not: a raise is already a call
+/// to the exception type, a handler names a type without depending on its
+/// code, and an endpoint links a route, not a definition, to its handler.
+/// Read those from the syntax trees (`bolo exceptions`, `--routes`).
+///
+/// Build one from an [`Analysis`] with [`Graph::from_analysis`], look
+/// definitions up with [`Graph::find`], and traverse with
+/// [`Graph::all_edges`], [`Graph::callees`], [`Graph::callers`] and
+/// [`Graph::walk`]. Definitions are addressed by their index in `defs`.
+pub struct Graph<'a> {
+    /// In file order, parents before their nested definitions.
+    pub defs: Vec<Def<'a>>,
+    /// `(caller, callee)` indices into `defs`, one per distinct pair.
+    pub edges: Vec<(usize, usize)>,
+    /// How many call sites stand behind each of `edges`.
+    pub sites: Vec<usize>,
+    /// Definitions run by spawned calls, kept apart from `edges`: one per
+    /// distinct spawner, spawned definition and spawning call.
+    pub spawns: Vec<Spawn<'a>>,
+    /// Types named by definitions, kept apart from `edges`: one per
+    /// distinct pair.
+    pub type_uses: Vec<TypeUse>,
+    /// `(test, fixture)` indices into `defs`, one per distinct pair.
+    pub fixture_uses: Vec<(usize, usize)>,
+}
+
+impl<'a> Graph<'a> {
+    /// Resolve calls to definitions.
+    ///
+    /// A call resolves by its last `.`/`::` segment to the definitions whose
+    /// name ends the same way, preferring one in the same file; ambiguous
+    /// names across files are left out. A `crate::` path whose symbol a
+    /// definition has resolves to that one first. Type names in signatures
+    /// and field annotations resolve the same way among type definitions.
+    pub fn build(files: &'a [Vec<Syntax<'a>>]) -> Self {
+        let mut defs = Vec::new();
+        for nodes in files {
+            let Some(ASTNode::File(file)) = nodes.first().map(|s| &s.node) else {
+                continue;
+            };
+            let start = defs.len();
+            collect(nodes, &file.path, "", &mut defs);
+            if let Some(module) = &file.module {
+                for d in &mut defs[start..] {
+                    let qualified = &d.id[d.file.len() + 2..];
+                    d.symbol = Some(modules::symbol(module, qualified));
+                }
+            }
+            let mut seen: HashMap<String, usize> = HashMap::new();
+            for d in &mut defs[start..] {
+                let n = seen.entry(d.id.clone()).or_default();
+                *n += 1;
+                if *n > 1 {
+                    d.id = format!("{}#{n}", d.id);
+                }
+            }
+        }
+
+        let mut by_name: HashMap<&str, Vec<usize>> = HashMap::new();
+        let mut types_by_name: HashMap<&str, Vec<usize>> = HashMap::new();
+        let mut fixtures_by_name: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (i, d) in defs.iter().enumerate() {
+            by_name.entry(last_segment(d.name)).or_default().push(i);
+            let kind_by_name = match d.kind {
+                DefKind::Type => &mut types_by_name,
+                DefKind::Fixture => &mut fixtures_by_name,
+                _ => continue,
+            };
+            kind_by_name
+                .entry(last_segment(d.name))
+                .or_default()
+                .push(i);
+        }
+
+        let (mut edges, mut sites) = (Vec::new(), Vec::new());
+        let mut spawns: Vec<Spawn> = Vec::new();
+        for (i, d) in defs.iter().enumerate() {
+            let (start, spawn_start) = (edges.len(), spawns.len());
+            for call in &d.calls {
+                let Some(target) = resolve(&call.name, d.file, &defs, &by_name) else {
+                    continue;
+                };
+                if let Some(via) = call.spawned_by.as_deref() {
+                    match spawns[spawn_start..]
+                        .iter_mut()
+                        .find(|s| s.to == target && s.via == via)
+                    {
+                        Some(s) => s.sites += 1,
+                        None => spawns.push(Spawn {
+                            from: i,
+                            to: target,
+                            via,
+                            sites: 1,
+                        }),
+                    }
+                    continue;
+                }
+                match edges[start..].iter().position(|&(_, t)| t == target) {
+                    Some(k) => sites[start + k] += 1,
+                    None => {
+                        edges.push((i, target));
+                        sites.push(1);
+                    }
+                }
+            }
+        }
+
+        let mut type_uses: Vec<TypeUse> = Vec::new();
+        for (i, d) in defs.iter().enumerate() {
+            let start = type_uses.len();
+            for name in &d.types {
+                let Some(target) = resolve(name, d.file, &defs, &types_by_name) else {
+                    continue;
+                };
+                match type_uses[start..].iter_mut().find(|u| u.to == target) {
+                    Some(u) => u.sites += 1,
+                    None => type_uses.push(TypeUse {
+                        from: i,
+                        to: target,
+                        sites: 1,
+                    }),
+                }
+            }
+        }
+        let mut fixture_uses = Vec::new();
+        for (i, d) in defs.iter().enumerate() {
+            for name in &d.fixtures {
+                if let Some(target) = resolve(name, d.file, &defs, &fixtures_by_name)
+                    && !fixture_uses.contains(&(i, target))
+                {
+                    fixture_uses.push((i, target));
+                }
+            }
+        }
+        Graph {
+            defs,
+            edges,
+            sites,
+            spawns,
+            type_uses,
+            fixture_uses,
+        }
+    }
+
+    /// [`Graph::build`] over an analysis's files.
+    pub fn from_analysis(analysis: &'a Analysis) -> Self {
+        Graph::build(&analysis.files)
+    }
+
+    /// Call edges, then spawn edges, then type and fixture uses.
+    pub fn all_edges(&self) -> impl Iterator<Item = Edge<'a>> + '_ {
+        let calls = self
+            .edges
+            .iter()
+            .zip(&self.sites)
+            .map(|(&(from, to), &sites)| Edge {
+                from,
+                to,
+                kind: EdgeKind::Call,
+                sites,
+            });
+        let spawns = self.spawns.iter().map(|s| Edge {
+            from: s.from,
+            to: s.to,
+            kind: EdgeKind::Spawn { via: s.via },
+            sites: s.sites,
+        });
+        let types = self.type_uses.iter().map(|u| Edge {
+            from: u.from,
+            to: u.to,
+            kind: EdgeKind::UsesType,
+            sites: u.sites,
+        });
+        let fixtures = self.fixture_uses.iter().map(|&(from, to)| Edge {
+            from,
+            to,
+            kind: EdgeKind::UsesFixture,
+            sites: 1,
+        });
+        calls.chain(spawns).chain(types).chain(fixtures)
+    }
+
+    /// Edges out of `def`: what it calls, spawns or uses as a type or fixture.
+    pub fn callees(&self, def: usize) -> impl Iterator<Item = Edge<'a>> + '_ {
+        self.all_edges().filter(move |e| e.from == def)
+    }
+
+    /// Edges into `def`: what calls, spawns or uses it as a type or fixture.
+    pub fn callers(&self, def: usize) -> impl Iterator<Item = Edge<'a>> + '_ {
+        self.all_edges().filter(move |e| e.to == def)
+    }
+
+    /// Breadth-first from `start` along `direction`'s edges: each reachable
+    /// definition once with its distance in edges, `start` first at 0.
+    pub fn walk(&self, start: usize, direction: Direction) -> Walk {
+        let mut next = vec![Vec::new(); self.defs.len()];
+        for e in self.all_edges() {
+            if direction != Direction::Callers {
+                next[e.from].push(e.to);
+            }
+            if direction != Direction::Callees {
+                next[e.to].push(e.from);
+            }
+        }
+        let mut seen = vec![false; self.defs.len()];
+        seen[start] = true;
+        Walk {
+            next,
+            seen,
+            queue: VecDeque::from([(start, 0)]),
+        }
+    }
+
+    /// Call nodes made directly in `caller` that resolve to `callee`, in
+    /// source order.
+    pub fn call_sites(&self, caller: usize, callee: usize) -> Vec<&'a Syntax<'a>> {
+        let mut out = Vec::new();
+        named_calls(
+            &self.defs[caller].syntax.contains,
+            last_segment(self.defs[callee].name),
+            false,
+            &mut out,
+        );
+        out
+    }
+
+    /// Like [`Graph::call_sites`], the spawned calls of `spawn`.
+    pub fn spawn_sites(&self, spawn: &Spawn) -> Vec<&'a Syntax<'a>> {
+        let mut out = Vec::new();
+        named_calls(
+            &self.defs[spawn.from].syntax.contains,
+            last_segment(self.defs[spawn.to].name),
+            true,
+            &mut out,
+        );
+        out.retain(
+            |s| matches!(&s.node, ASTNode::Call(c) if c.spawned_by.as_deref() == Some(spawn.via)),
+        );
+        out
+    }
+
+    /// The one definition `name` names: by id or symbol, else by qualified
+    /// name, else by plain name.
+    pub fn find(&self, name: &str) -> Result<usize, String> {
+        let qualified = |id: &str| id.split_once("::").map_or("", |(_, q)| q).to_string();
+        let matches = |pick: &dyn Fn(usize) -> bool| -> Vec<usize> {
+            (0..self.defs.len()).filter(|&i| pick(i)).collect()
+        };
+        let found = [
+            matches(&|i| self.defs[i].id == name || self.defs[i].symbol.as_deref() == Some(name)),
+            matches(&|i| qualified(&self.defs[i].id) == name),
+            matches(&|i| self.defs[i].name == name),
+        ]
+        .into_iter()
+        .find(|m| !m.is_empty())
+        .unwrap_or_default();
+        match found.as_slice() {
+            [i] => Ok(*i),
+            [] => Err("no such definition".into()),
+            many => {
+                let ids: Vec<&str> = many.iter().map(|&i| self.defs[i].id.as_str()).collect();
+                Err(format!(
+                    "{} definitions match, use one of: {}",
+                    ids.len(),
+                    ids.join(", ")
+                ))
+            }
+        }
+    }
+
+    /// Which definitions lie within `radius` call or spawn edges of
+    /// `center`, followed either way, indexed like [`Graph::defs`].
+    pub fn neighborhood(&self, center: usize, radius: usize) -> Vec<bool> {
+        let mut within = vec![false; self.defs.len()];
+        for (i, _) in self
+            .walk(center, Direction::Both)
+            .take_while(|&(_, d)| d <= radius)
+        {
+            within[i] = true;
+        }
+        within
+    }
+
+    /// File-to-file dependencies implied by the call edges, without self-loops.
+    pub fn file_edges(&self) -> BTreeSet<(&'a str, &'a str)> {
+        self.edges
+            .iter()
+            .map(|&(a, b)| (self.defs[a].file, self.defs[b].file))
+            .filter(|(a, b)| a != b)
+            .collect()
+    }
+
+    /// How many call edges stand behind each of [`Graph::file_edges`].
+    pub fn file_weights(&self) -> BTreeMap<(&'a str, &'a str), usize> {
+        let mut out = BTreeMap::new();
+        for &(a, b) in &self.edges {
+            let (a, b) = (self.defs[a].file, self.defs[b].file);
+            if a != b {
+                *out.entry((a, b)).or_default() += 1;
+            }
+        }
+        out
+    }
+
+    /// The lightest set of file dependencies whose removal leaves the file
+    /// graph acyclic, lightest first: the cheapest refactors to try. Exact
+    /// for cycles of up to [`EXACT_EDGES`] edges; larger ones use the
+    /// Eades–Lin–Smyth ordering heuristic, which may remove a few too many.
+    pub fn cycle_cuts(&self) -> Vec<Cut<'a>> {
+        let weights = self.file_weights();
+        let mut cuts = Vec::new();
+        for cycle in self.file_cycles() {
+            let inside: Vec<Cut> = weights
+                .iter()
+                .filter(|((a, b), _)| cycle.contains(a) && cycle.contains(b))
+                .map(|(&(from, to), &weight)| Cut { from, to, weight })
+                .collect();
+            let index = |f: &str| cycle.iter().position(|c| *c == f).unwrap_or(0);
+            let edges: Vec<(usize, usize, usize)> = inside
+                .iter()
+                .map(|c| (index(c.from), index(c.to), c.weight))
+                .collect();
+            let removed = if edges.len() <= EXACT_EDGES {
+                exact_cut(cycle.len(), &edges)
+            } else {
+                greedy_cut(cycle.len(), &edges)
+            };
+            cuts.extend(removed.into_iter().map(|i| inside[i].clone()));
+        }
+        cuts.sort_by(|a, b| (a.weight, a.from, a.to).cmp(&(b.weight, b.from, b.to)));
+        cuts
+    }
+
+    /// Groups of files that depend on each other in a cycle, each sorted by
+    /// path. Computed with Tarjan's algorithm over [`Graph::file_edges`].
+    pub fn file_cycles(&self) -> Vec<Vec<&'a str>> {
+        let mut adj: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for (a, b) in self.file_edges() {
+            adj.entry(a).or_default().push(b);
+            adj.entry(b).or_default();
+        }
+        let nodes: Vec<&str> = adj.keys().copied().collect();
+        let index_of: HashMap<&str, usize> =
+            nodes.iter().enumerate().map(|(i, n)| (*n, i)).collect();
+        let succ: Vec<Vec<usize>> = nodes
+            .iter()
+            .map(|n| adj[n].iter().map(|m| index_of[m]).collect())
+            .collect();
+
+        let mut cycles: Vec<Vec<&str>> = tarjan(&succ)
+            .into_iter()
+            .filter(|c| c.len() > 1)
+            .map(|c| {
+                let mut files: Vec<&str> = c.into_iter().map(|i| nodes[i]).collect();
+                files.sort_unstable();
+                files
+            })
+            .collect();
+        cycles.sort();
+        cycles
+    }
+}
+
+/// Indices of the lightest subset of weighted `(from, to, weight)` edges
+/// over `n` nodes whose removal leaves no cycle; ties go to fewer edges.
+fn exact_cut(n: usize, edges: &[(usize, usize, usize)]) -> Vec<usize> {
+    let mut best: Option<(usize, u32, u32)> = None;
+    for mask in 0..1u32 << edges.len() {
+        let weight = (0..edges.len())
+            .filter(|i| mask & 1 << i != 0)
+            .map(|i| edges[i].2)
+            .sum();
+        let key = (weight, mask.count_ones(), mask);
+        if best.is_some_and(|b| key >= b) {
+            continue;
+        }
+        let kept: Vec<(usize, usize)> = (0..edges.len())
+            .filter(|i| mask & 1 << i == 0)
+            .map(|i| (edges[i].0, edges[i].1))
+            .collect();
+        if is_acyclic(n, &kept) {
+            best = Some(key);
+        }
+    }
+    let mask = best.map_or(0, |b| b.2);
+    (0..edges.len()).filter(|i| mask & 1 << i != 0).collect()
+}
+
+/// Indices of the edges pointing backwards in an Eades–Lin–Smyth vertex
+/// ordering: sinks go last, sources first, and otherwise the node with the
+/// most outgoing weight over incoming goes next.
+fn greedy_cut(n: usize, edges: &[(usize, usize, usize)]) -> Vec<usize> {
+    let (mut out, mut inc) = (vec![0; n], vec![0; n]);
+    let mut touching: Vec<Vec<usize>> = vec![Vec::new(); n];
+    for (i, &(a, b, w)) in edges.iter().enumerate() {
+        out[a] += w;
+        inc[b] += w;
+        touching[a].push(i);
+        touching[b].push(i);
+    }
+    let mut alive = vec![true; n];
+    let (mut head, mut tail) = (Vec::new(), Vec::new());
+    for _ in 0..n {
+        let live = (0..n).filter(|&v| alive[v]);
+        let v = if let Some(v) = live.clone().find(|&v| out[v] == 0) {
+            tail.push(v);
+            v
+        } else if let Some(v) = live.clone().find(|&v| inc[v] == 0) {
+            head.push(v);
+            v
+        } else {
+            let v = live
+                .max_by_key(|&v| (out[v] as isize - inc[v] as isize, usize::MAX - v))
+                .unwrap_or(0);
+            head.push(v);
+            v
+        };
+        alive[v] = false;
+        for &i in &touching[v] {
+            let (a, b, w) = edges[i];
+            if alive[a] || alive[b] {
+                out[a] -= w;
+                inc[b] -= w;
+            }
+        }
+    }
+    head.extend(tail.into_iter().rev());
+    let mut position = vec![0; n];
+    for (i, &v) in head.iter().enumerate() {
+        position[v] = i;
+    }
+    (0..edges.len())
+        .filter(|&i| position[edges[i].0] > position[edges[i].1])
+        .collect()
+}
+
+/// Kahn's algorithm: true if every node can be peeled off in topological order.
+fn is_acyclic(n: usize, edges: &[(usize, usize)]) -> bool {
+    let mut indegree = vec![0; n];
+    for &(_, b) in edges {
+        indegree[b] += 1;
+    }
+    let mut ready: Vec<usize> = (0..n).filter(|&v| indegree[v] == 0).collect();
+    let mut seen = 0;
+    while let Some(v) = ready.pop() {
+        seen += 1;
+        for &(a, b) in edges {
+            if a == v {
+                indegree[b] -= 1;
+                if indegree[b] == 0 {
+                    ready.push(b);
+                }
+            }
+        }
+    }
+    seen == n
+}
+
+/// Strongly connected components, iteratively so deep graphs can't overflow.
+fn tarjan(succ: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    const UNSEEN: usize = usize::MAX;
+    let n = succ.len();
+    let mut index = vec![UNSEEN; n];
+    let mut low = vec![0; n];
+    let mut on_stack = vec![false; n];
+    let mut stack = Vec::new();
+    let mut next = 0;
+    let mut out = Vec::new();
+
+    for root in 0..n {
+        if index[root] != UNSEEN {
+            continue;
+        }
+        // (node, position of the next successor to visit)
+        let mut work = vec![(root, 0)];
+        while let Some(&mut (v, ref mut pos)) = work.last_mut() {
+            if *pos == 0 {
+                index[v] = next;
+                low[v] = next;
+                next += 1;
+                stack.push(v);
+                on_stack[v] = true;
+            }
+            if let Some(&w) = succ[v].get(*pos) {
+                *pos += 1;
+                if index[w] == UNSEEN {
+                    work.push((w, 0));
+                } else if on_stack[w] {
+                    low[v] = low[v].min(index[w]);
+                }
+                continue;
+            }
+            work.pop();
+            if let Some(&(parent, _)) = work.last() {
+                low[parent] = low[parent].min(low[v]);
+            }
+            if low[v] == index[v] {
+                let mut component = Vec::new();
+                while let Some(w) = stack.pop() {
+                    on_stack[w] = false;
+                    component.push(w);
+                    if w == v {
+                        break;
+                    }
+                }
+                out.push(component);
+            }
+        }
+    }
+    out
+}
+
+fn collect<'a>(nodes: &'a [Syntax<'a>], file: &'a str, prefix: &str, out: &mut Vec<Def<'a>>) {
+    for s in nodes {
+        if let ASTNode::Module(m) = &s.node
+            && m.inline
+        {
+            let path = if prefix.is_empty() {
+                m.name.to_string()
+            } else {
+                format!("{prefix}.{}", m.name)
+            };
+            collect(&s.contains, file, &path, out);
+            continue;
+        }
+        let Some((kind, name)) = definition(&s.node) else {
+            continue;
+        };
+        let path = if prefix.is_empty() {
+            name.to_string()
+        } else {
+            format!("{prefix}.{name}")
+        };
+        let mut calls = Vec::new();
+        direct_calls(&s.contains, &mut calls);
+        let mut types = Vec::new();
+        direct_types(&s.contains, &mut types);
+        let fixtures = s
+            .contains
+            .iter()
+            .filter_map(|c| match &c.node {
+                ASTNode::UsesFixture(u) => Some(&*u.name),
+                _ => None,
+            })
+            .collect();
+        out.push(Def {
+            id: format!("{file}::{path}"),
+            file,
+            name,
+            kind,
+            symbol: None,
+            syntax: s,
+            calls,
+            types,
+            fixtures,
+        });
+        collect(&s.contains, file, &path, out);
+    }
+}
+
+/// Calls in `nodes`, spawned ones included, not descending into nested
+/// definitions.
+fn direct_calls<'a>(nodes: &'a [Syntax<'a>], out: &mut Vec<&'a Call<'a>>) {
+    for s in nodes {
+        match &s.node {
+            ASTNode::Call(c) => out.push(c),
+            n if definition(n).is_some() => continue,
+            _ => {}
+        }
+        direct_calls(&s.contains, out);
+    }
+}
+
+/// Type names in `nodes`' UsesType nodes and field annotations, not
+/// descending into nested definitions. An annotation such as
+/// `Vec<Option<Item>>` or `dict[str, Item]` names each type in it.
+fn direct_types<'a>(nodes: &'a [Syntax<'a>], out: &mut Vec<&'a str>) {
+    for s in nodes {
+        match &s.node {
+            ASTNode::UsesType(u) => out.push(&u.name),
+            ASTNode::Field(f) => {
+                let names = f
+                    .annotation
+                    .as_deref()
+                    .unwrap_or_default()
+                    .split(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '.' | ':')));
+                out.extend(
+                    names
+                        .map(|n| n.trim_matches(['.', ':']))
+                        .filter(|n| !n.is_empty()),
+                );
+            }
+            n if definition(n).is_some() => continue,
+            _ => {}
+        }
+        direct_types(&s.contains, out);
+    }
+}
+
+/// Calls in `nodes` whose last segment is `name`, as [`resolve`] matches
+/// them, either `spawned` or not, not descending into nested definitions.
+fn named_calls<'a>(
+    nodes: &'a [Syntax<'a>],
+    name: &str,
+    spawned: bool,
+    out: &mut Vec<&'a Syntax<'a>>,
+) {
+    for s in nodes {
+        match &s.node {
+            ASTNode::Call(c)
+                if c.spawned_by.is_some() == spawned
+                    && last_segment(c.name.trim_end_matches('!')) == name =>
+            {
+                out.push(s);
+            }
+            n if definition(n).is_some() => continue,
+            _ => {}
+        }
+        named_calls(&s.contains, name, spawned, out);
+    }
+}
+
+/// What a call or definition name resolves by: `send` for `client.send`,
+/// `Path::new` or a Go method named `(*Server).send`.
+fn last_segment(name: &str) -> &str {
+    name.rsplit(['.', ':']).next().unwrap_or(name)
+}
+
+fn resolve(
+    call: &str,
+    file: &str,
+    defs: &[Def],
+    by_name: &HashMap<&str, Vec<usize>>,
+) -> Option<usize> {
+    let call = call.trim_end_matches('!');
+    let candidates = by_name.get(last_segment(call))?;
+    // A path from the crate or package root names its definition's symbol
+    // outright.
+    let exact: Vec<usize> = candidates
+        .iter()
+        .copied()
+        .filter(|&i| defs[i].symbol.as_deref() == Some(call))
+        .collect();
+    let candidates = if exact.is_empty() { candidates } else { &exact };
+    let local: Vec<usize> = candidates
+        .iter()
+        .copied()
+        .filter(|&i| defs[i].file == file)
+        .collect();
+    match (local.as_slice(), candidates.as_slice()) {
+        ([i], _) | ([], [i]) => Some(*i),
+        _ => None,
+    }
+}
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::tree_sitter::analyze;
+    use crate::api::tree_sitter::go::Go;
+    use crate::api::tree_sitter::py::Python;
+
+    fn edge_ids(g: &Graph) -> Vec<(String, String)> {
+        g.edges
+            .iter()
+            .map(|&(a, b)| (g.defs[a].id.clone(), g.defs[b].id.clone()))
+            .collect()
+    }
+
+    // ── build ──
+
+    #[test]
+    fn edges_deduplicated_per_caller() {
+        let files = [
+            analyze(&Python, "a.py", "def f():\n    g()\n    g()\n"),
+            analyze(&Python, "b.py", "def g(): pass\n"),
+        ];
+        let g = Graph::build(&files);
+        assert_eq!(edge_ids(&g), vec![("a.py::f".into(), "b.py::g".into())]);
+        assert_eq!(g.sites, vec![2]);
+    }
+
+    #[test]
+    fn nested_definitions_have_dotted_ids() {
+        let files = [analyze(
+            &Python,
+            "a.py",
+            "class C:\n    def m(self): pass\n",
+        )];
+        let ids: Vec<_> = Graph::build(&files)
+            .defs
+            .into_iter()
+            .map(|d| d.id)
+            .collect();
+        assert_eq!(ids, vec!["a.py::C", "a.py::C.m"]);
+    }
+
+    #[test]
+    fn same_named_definitions_get_distinct_ids() {
+        use crate::api::tree_sitter::rs::Rust;
+        let src = "struct Holder;\nimpl Holder {\n    fn new() {}\n}\nimpl Holder {}\n";
+        let files = [
+            analyze(&Rust, "src/lib.rs", src),
+            analyze(&Python, "a.py", "def f(): pass\ndef f(): pass\n"),
+        ];
+        let ids: Vec<_> = Graph::build(&files)
+            .defs
+            .into_iter()
+            .map(|d| d.id)
+            .collect();
+        assert_eq!(
+            ids,
+            [
+                "src/lib.rs::Holder",
+                "src/lib.rs::Holder#2",
+                "src/lib.rs::Holder.new",
+                "src/lib.rs::Holder#3",
+                "a.py::f",
+                "a.py::f#2",
+            ]
+        );
+    }
+
+    #[test]
+    fn receiver_named_methods_resolve_by_method_name() {
+        let src =
+            "package p\n\nfunc (s *Server) Handle() {}\n\nfunc run(s *Server) {\n\ts.Handle()\n}\n";
+        let files = [analyze(&Go, "a.go", src)];
+        let g = Graph::build(&files);
+        assert_eq!(
+            edge_ids(&g),
+            vec![("a.go::run".into(), "a.go::(*Server).Handle".into())]
+        );
+    }
+
+    #[test]
+    fn crate_paths_resolve_by_symbol() {
+        use crate::api::tree_sitter::rs::Rust;
+        let rust = |path: &str, src: &'static str| analyze(&Rust, path, src);
+        let mut files = [
+            rust("src/lib.rs", "mod a;\nmod b;\nfn run() {}\n"),
+            rust("src/a.rs", "pub fn run() {}\n"),
+            rust(
+                "src/b.rs",
+                "fn run() {}\nfn f() { super::a::run(); run(); }\n",
+            ),
+        ];
+        crate::modules::assign(&mut files);
+        let g = Graph::build(&files);
+        assert_eq!(
+            edge_ids(&g),
+            vec![
+                ("src/b.rs::f".into(), "src/a.rs::run".into()),
+                ("src/b.rs::f".into(), "src/b.rs::run".into())
+            ]
+        );
+    }
+
+    #[test]
+    fn inline_modules_qualify_definitions() {
+        use crate::api::tree_sitter::rs::Rust;
+        let src = "mod inner {\n    pub fn run() {}\n}\nfn main() { crate::inner::run(); }\n";
+        let mut files = [analyze(&Rust, "src/main.rs", src)];
+        crate::modules::assign(&mut files);
+        let g = Graph::build(&files);
+        assert_eq!(
+            edge_ids(&g),
+            vec![("src/main.rs::main".into(), "src/main.rs::inner.run".into())]
+        );
+    }
+
+    #[test]
+    fn relative_imports_resolve_by_symbol() {
+        let mut files = [
+            analyze(&Python, "pkg/__init__.py", ""),
+            analyze(&Python, "pkg/models.py", "def run(): pass\n"),
+            analyze(&Python, "pkg/other.py", "def run(): pass\n"),
+            analyze(
+                &Python,
+                "pkg/api.py",
+                "from .models import run\ndef f():\n    run()\n",
+            ),
+        ];
+        crate::modules::assign(&mut files);
+        let g = Graph::build(&files);
+        assert_eq!(
+            edge_ids(&g),
+            vec![("pkg/api.py::f".into(), "pkg/models.py::run".into())]
+        );
+    }
+
+    #[test]
+    fn spawned_calls_are_spawn_edges() {
+        let src = "import threading\ndef work(): pass\ndef main():\n    \
+                   threading.Thread(target=work)\n    threading.Thread(target=work)\n    work()\n";
+        let files = [analyze(&Python, "a.py", src)];
+        let g = Graph::build(&files);
+        assert_eq!(
+            edge_ids(&g),
+            vec![("a.py::main".into(), "a.py::work".into())]
+        );
+        assert_eq!(g.sites, vec![1]);
+        assert_eq!(
+            g.spawns,
+            vec![Spawn {
+                from: 1,
+                to: 0,
+                via: "threading.Thread",
+                sites: 2,
+            }]
+        );
+        assert_eq!(g.spawn_sites(&g.spawns[0]).len(), 2);
+        assert_eq!(g.call_sites(1, 0).len(), 1);
+    }
+
+    #[test]
+    fn file_edges_skip_self_loops() {
+        let files = [
+            analyze(
+                &Python,
+                "a.py",
+                "def h(): pass\ndef f():\n    h()\n    g()\n",
+            ),
+            analyze(&Python, "b.py", "def g(): pass\n"),
+        ];
+        let g = Graph::build(&files);
+        assert_eq!(
+            g.file_edges().into_iter().collect::<Vec<_>>(),
+            vec![("a.py", "b.py")]
+        );
+    }
+
+    // ── traversal ──
+
+    #[test]
+    fn typed_edges_and_kinds() {
+        let files = [analyze(
+            &Python,
+            "a.py",
+            "import threading\nclass C: pass\ndef f():\n    g()\n    g()\n    \
+             threading.Thread(target=h)\ndef g(): pass\ndef h(): pass\n",
+        )];
+        let analysis = Analysis {
+            files: files
+                .iter()
+                .map(|n| n.iter().cloned().map(Syntax::into_owned).collect())
+                .collect(),
+            ..Default::default()
+        };
+        let g = Graph::from_analysis(&analysis);
+        let kinds: Vec<DefKind> = g.defs.iter().map(|d| d.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                DefKind::Type,
+                DefKind::Function,
+                DefKind::Function,
+                DefKind::Function
+            ]
+        );
+        assert_eq!(DefKind::Type.to_string(), "type");
+        assert_eq!(serde_json::to_string(&DefKind::Function).unwrap(), "\"fn\"");
+
+        let f = g.find("f").unwrap();
+        let out: Vec<(&str, EdgeKind, usize)> = g
+            .callees(f)
+            .map(|e| (g.defs[e.to].name, e.kind, e.sites))
+            .collect();
+        assert_eq!(
+            out,
+            [
+                ("g", EdgeKind::Call, 2),
+                (
+                    "h",
+                    EdgeKind::Spawn {
+                        via: "threading.Thread"
+                    },
+                    1
+                )
+            ]
+        );
+        let h = g.find("h").unwrap();
+        assert_eq!(g.callers(h).map(|e| e.from).collect::<Vec<_>>(), [f]);
+        assert_eq!(g.all_edges().count(), 2);
+    }
+
+    #[test]
+    fn signature_and_field_types_are_type_edges() {
+        let files = [analyze(
+            &Python,
+            "a.py",
+            "class Item: pass\nclass Order:\n    items: list[Item]\n    first: Item\n\
+             def total(o: Order) -> int:\n    return 0\n",
+        )];
+        let g = Graph::build(&files);
+        let uses: Vec<(&str, &str, usize)> = g
+            .all_edges()
+            .filter(|e| e.kind == EdgeKind::UsesType)
+            .map(|e| (g.defs[e.from].name, g.defs[e.to].name, e.sites))
+            .collect();
+        assert_eq!(uses, [("Order", "Item", 2), ("total", "Order", 1)]);
+        assert!(g.edges.is_empty());
+        let total = g.find("total").unwrap();
+        assert_eq!(
+            g.callees(total).map(|e| e.kind).collect::<Vec<_>>(),
+            [EdgeKind::UsesType]
+        );
+    }
+
+    #[test]
+    fn fixture_parameters_are_fixture_edges() {
+        let src = "import pytest\n@pytest.fixture\ndef db(): pass\ndef db_url(): pass\n\
+                   def test_a(db, tmp_path): pass\n";
+        let files = [analyze(&Python, "test_a.py", src)];
+        let g = Graph::build(&files);
+        let test = g.find("test_a").unwrap();
+        let out: Vec<(&str, EdgeKind)> = g
+            .callees(test)
+            .map(|e| (g.defs[e.to].name, e.kind))
+            .collect();
+        assert_eq!(out, [("db", EdgeKind::UsesFixture)]);
+        assert_eq!(g.defs[g.fixture_uses[0].1].kind, DefKind::Fixture);
+    }
+
+    #[test]
+    fn rust_types_resolve_among_types_only() {
+        use crate::api::tree_sitter::rs::Rust;
+        let src = "struct Id;\nfn Id() {}\nenum Shape { Circle { at: Point } }\nstruct Point(Id);\n\
+                   fn draw(s: &Shape) -> Vec<Point> { vec![] }\n";
+        let files = [analyze(&Rust, "a.rs", src)];
+        let g = Graph::build(&files);
+        let uses: Vec<(&str, &str)> = g
+            .type_uses
+            .iter()
+            .map(|u| (g.defs[u.from].name, g.defs[u.to].name))
+            .collect();
+        assert_eq!(
+            uses,
+            [
+                ("Shape", "Point"),
+                ("Point", "Id"),
+                ("draw", "Shape"),
+                ("draw", "Point")
+            ]
+        );
+    }
+
+    #[test]
+    fn walk_is_breadth_first_by_direction() {
+        let files = [analyze(
+            &Python,
+            "a.py",
+            "def a():\n    b()\ndef b():\n    c()\n    d()\ndef c():\n    d()\ndef d(): pass\n",
+        )];
+        let g = Graph::build(&files);
+        let names = |walk: Walk| -> Vec<(&str, usize)> {
+            walk.map(|(i, depth)| (g.defs[i].name, depth)).collect()
+        };
+        let b = g.find("b").unwrap();
+        assert_eq!(
+            names(g.walk(b, Direction::Callees)),
+            [("b", 0), ("c", 1), ("d", 1)]
+        );
+        assert_eq!(names(g.walk(b, Direction::Callers)), [("b", 0), ("a", 1)]);
+        let d = g.find("d").unwrap();
+        assert_eq!(
+            names(g.walk(d, Direction::Both)),
+            [("d", 0), ("b", 1), ("c", 1), ("a", 2)]
+        );
+    }
+
+    // ── neighborhood ──
+
+    #[test]
+    fn neighborhood_follows_edges_both_ways() {
+        let files = [analyze(
+            &Python,
+            "a.py",
+            "def a():\n    b()\ndef b():\n    c()\ndef c():\n    d()\ndef d(): pass\ndef e(): pass\n",
+        )];
+        let g = Graph::build(&files);
+        let center = g.find("c").unwrap();
+        let names = |within: Vec<bool>| -> Vec<&str> {
+            (0..g.defs.len())
+                .filter(|&i| within[i])
+                .map(|i| g.defs[i].name)
+                .collect()
+        };
+        assert_eq!(names(g.neighborhood(center, 0)), ["c"]);
+        assert_eq!(names(g.neighborhood(center, 1)), ["b", "c", "d"]);
+        assert_eq!(names(g.neighborhood(center, 2)), ["a", "b", "c", "d"]);
+        assert!(g.find("nope").is_err());
+    }
+
+    // ── file_cycles ──
+
+    #[test]
+    fn acyclic_has_no_cycles() {
+        let files = [
+            analyze(&Python, "a.py", "def f():\n    g()\n"),
+            analyze(&Python, "b.py", "def g(): pass\n"),
+        ];
+        assert!(Graph::build(&files).file_cycles().is_empty());
+    }
+
+    #[test]
+    fn mutual_calls_form_cycle() {
+        let files = [
+            analyze(&Python, "a.py", "def f():\n    g()\n"),
+            analyze(&Python, "b.py", "def g():\n    h()\n"),
+            analyze(&Python, "c.py", "def h():\n    f()\n"),
+            analyze(&Python, "d.py", "def k():\n    f()\n"),
+        ];
+        let cycles = Graph::build(&files).file_cycles();
+        assert_eq!(cycles, vec![vec!["a.py", "b.py", "c.py"]]);
+    }
+
+    #[test]
+    fn recursion_within_file_is_not_a_cycle() {
+        let files = [analyze(&Python, "a.py", "def f():\n    f()\n")];
+        assert!(Graph::build(&files).file_cycles().is_empty());
+    }
+
+    // ── cycle_cuts ──
+
+    #[test]
+    fn file_weights_count_calls() {
+        let files = [
+            analyze(
+                &Python,
+                "a.py",
+                "def f():\n    g()\n    h()\ndef k():\n    g()\n",
+            ),
+            analyze(&Python, "b.py", "def g(): pass\ndef h(): pass\n"),
+        ];
+        let g = Graph::build(&files);
+        assert_eq!(g.file_weights()[&("a.py", "b.py")], 3);
+    }
+
+    #[test]
+    fn cuts_lightest_edge() {
+        let files = [
+            analyze(&Python, "a.py", "def f():\n    g()\n    h()\n"),
+            analyze(&Python, "b.py", "def g(): pass\ndef h():\n    k()\n"),
+            analyze(&Python, "c.py", "def k():\n    f()\n"),
+        ];
+        let g = Graph::build(&files);
+        assert_eq!(
+            g.cycle_cuts(),
+            vec![Cut {
+                from: "b.py",
+                to: "c.py",
+                weight: 1
+            }]
+        );
+    }
+
+    #[test]
+    fn cuts_break_every_cycle() {
+        // a ⇄ b and b ⇄ c: two cuts needed.
+        let files = [
+            analyze(&Python, "a.py", "def f():\n    g()\n"),
+            analyze(&Python, "b.py", "def g():\n    f()\n    k()\n"),
+            analyze(&Python, "c.py", "def k():\n    g()\n"),
+        ];
+        let cuts = Graph::build(&files).cycle_cuts();
+        assert_eq!(cuts.len(), 2);
+    }
+
+    #[test]
+    fn acyclic_has_no_cuts() {
+        let files = [
+            analyze(&Python, "a.py", "def f():\n    g()\n"),
+            analyze(&Python, "b.py", "def g(): pass\n"),
+        ];
+        assert!(Graph::build(&files).cycle_cuts().is_empty());
+    }
+
+    #[test]
+    fn exact_cut_prefers_weight_over_count() {
+        // 0 → 1 (5), 1 → 0 via 1 → 2 → 0 (1 each): one cut of weight 1 beats 5.
+        let edges = [(0, 1, 5), (1, 2, 1), (2, 0, 1)];
+        let cut = exact_cut(3, &edges);
+        assert_eq!(cut.len(), 1);
+        assert_eq!(edges[cut[0]].2, 1);
+    }
+
+    #[test]
+    fn greedy_cut_leaves_dag() {
+        // A ring of 40 nodes with chords back: too many edges for exact search.
+        let n = 40;
+        let mut edges: Vec<(usize, usize, usize)> = (0..n).map(|i| (i, (i + 1) % n, 1)).collect();
+        edges.extend((0..n).step_by(4).map(|i| (i, (i + n - 2) % n, 2)));
+        let cut = greedy_cut(n, &edges);
+        let kept: Vec<(usize, usize)> = (0..edges.len())
+            .filter(|i| !cut.contains(i))
+            .map(|i| (edges[i].0, edges[i].1))
+            .collect();
+        assert!(!cut.is_empty());
+        assert!(is_acyclic(n, &kept));
+    }
+
+    #[test]
+    fn tarjan_long_chain_does_not_overflow() {
+        let n = 100_000;
+        let mut succ: Vec<Vec<usize>> = (0..n).map(|i| vec![i + 1]).collect();
+        succ[n - 1] = vec![0];
+        let sccs = tarjan(&succ);
+        assert_eq!(sccs.len(), 1);
+        assert_eq!(sccs[0].len(), n);
+    }
+}
@@ -0,0 +1,344 @@
+//! The analysis API over gRPC, as `proto/bolo.proto` defines it: what
+//! `bolo serve --grpc` answers instead of HTTP.
+
+use crate::error::BoloError;
+use crate::export::{self, Layout};
+use crate::graph::{EdgeKind, Graph};
+use crate::serve::{self, Server, Tls};
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio::sync::watch;
+use tokio_stream::wrappers::{TcpListenerStream, WatchStream};
+use tokio_stream::{Stream, StreamExt};
+use tonic::service::interceptor::InterceptedService;
+use tonic::transport::{Identity, ServerTlsConfig};
+use tonic::{Request, Response, Status};
+
+/// Messages, server and client generated from `proto/bolo.proto`.
+pub mod pb {
+    tonic::include_proto!("bolo.v1");
+}
+
+use pb::analysis_server::AnalysisServer;
+
+/// Most bytes of headers, metadata included, in one request.
+const MAX_HEADERS: u32 = 16 * 1024;
+
+/// A [`Server`]'s analysis, answered over gRPC.
+pub struct Service {
+    server: Arc<Server<'static>>,
+    updates: watch::Sender<pb::Summary>,
+}
+
+impl Service {
+    pub fn new(server: Server<'static>) -> Self {
+        let updates = watch::Sender::new(summary(&server));
+        Service {
+            server: Arc::new(server),
+            updates,
+        }
+    }
+
+    /// The one definition `name` names, with the graph holding it.
+    fn find<T>(
+        &self,
+        name: &str,
+        answer: impl FnOnce(&Graph, usize) -> Result<T, Status>,
+    ) -> Result<Response<T>, Status> {
+        let analysis = self.server.analysis();
+        let graph = Graph::from_analysis(&analysis);
+        let def = graph.find(name).map_err(Status::not_found)?;
+        answer(&graph, def).map(Response::new)
+    }
+}
+
+fn summary(server: &Server) -> pb::Summary {
+    let analysis = server.analysis();
+    pb::Summary {
+        files: analysis.files.len() as u64,
+        warnings: analysis.warnings.len() as u64,
+    }
+}
+
+fn internal(e: impl ToString) -> Status {
+    Status::internal(e.to_string())
+}
+
+#[tonic::async_trait]
+impl pb::analysis_server::Analysis for Service {
+    async fn analyze(
+        &self,
+        _: Request<pb::AnalyzeRequest>,
+    ) -> Result<Response<pb::Summary>, Status> {
+        let server = self.server.clone();
+        let (files, warnings) = tokio::task::spawn_blocking(move || server.analyze())
+            .await
+            .map_err(internal)?
+            .map_err(internal)?;
+        let summary = pb::Summary {
+            files: files as u64,
+            warnings: warnings as u64,
+        };
+        self.updates.send_replace(summary);
+        Ok(Response::new(summary))
+    }
+
+    async fn get_symbol(
+        &self,
+        request: Request<pb::SymbolRequest>,
+    ) -> Result<Response<pb::Symbol>, Status> {
+        self.find(&request.get_ref().name, |graph, def| {
+            let d = &graph.defs[def];
+            let span = &d.syntax.metadata.span;
+            Ok(pb::Symbol {
+                id: d.id.clone(),
+                kind: d.kind.as_str().to_string(),
+                file: d.file.to_string(),
+                symbol: d.symbol.clone().unwrap_or_default(),
+                start_line: span.start_line as u64,
+                end_line: span.end_line as u64,
+                fan_in: graph.edges.iter().filter(|&&(_, b)| b == def).count() as u64,
+                fan_out: graph.edges.iter().filter(|&&(a, _)| a == def).count() as u64,
+                json: export::json(d.syntax, Layout::Compact).map_err(internal)?,
+            })
+        })
+    }
+
+    async fn get_callers(
+        &self,
+        request: Request<pb::SymbolRequest>,
+    ) -> Result<Response<pb::Callers>, Status> {
+        self.find(&request.get_ref().name, |graph, def| {
+            let callers = graph.callers(def).map(|e| {
+                let (edge, via) = match e.kind {
+                    EdgeKind::Call => ("call", ""),
+                    EdgeKind::Spawn { via } => ("spawn", via),
+                    EdgeKind::UsesType => ("type", ""),
+                    EdgeKind::UsesFixture => ("fixture", ""),
+                };
+                pb::Caller {
+                    id: graph.defs[e.from].id.clone(),
+                    file: graph.defs[e.from].file.to_string(),
+                    edge: edge.to_string(),
+                    via: via.to_string(),
+                    sites: e.sites as u64,
+                }
+            });
+            Ok(pb::Callers {
+                callers: callers.collect(),
+            })
+        })
+    }
+
+    type StreamUpdatesStream = Pin<Box<dyn Stream<Item = Result<pb::Summary, Status>> + Send>>;
+
+    async fn stream_updates(
+        &self,
+        _: Request<pb::UpdatesRequest>,
+    ) -> Result<Response<Self::StreamUpdatesStream>, Status> {
+        let updates = WatchStream::new(self.updates.subscribe()).map(Ok);
+        Ok(Response::new(Box::pin(updates)))
+    }
+}
+
+/// A bound socket, answering gRPC with [`Listener::serve`].
+pub struct Listener {
+    tcp: std::net::TcpListener,
+    token: Option<String>,
+    tls: Option<Identity>,
+}
+
+impl Listener {
+    /// Bind `addr`, serving over TLS with `tls`. Without a `token`, `addr`
+    /// must be loopback, as for [`serve::Listener::bind`].
+    pub fn bind(addr: &str, token: Option<String>, tls: Option<&Tls>) -> Result<Self, BoloError> {
+        serve::guard(addr, token.is_some())?;
+        let read = |path: &std::path::PathBuf| {
+            std::fs::read(path).map_err(|e| BoloError::Read {
+                path: path.clone(),
+                reason: e.to_string(),
+            })
+        };
+        let tls = match tls {
+            Some(tls) => Some(Identity::from_pem(read(&tls.cert)?, read(&tls.key)?)),
+            None => None,
+        };
+        let listen = |e: std::io::Error| BoloError::Listen {
+            addr: addr.to_string(),
+            reason: e.to_string(),
+        };
+        let tcp = std::net::TcpListener::bind(addr).map_err(listen)?;
+        tcp.set_nonblocking(true).map_err(listen)?;
+        Ok(Listener { tcp, token, tls })
+    }
+
+    /// Where the listener answers, e.g. `https://127.0.0.1:7878`.
+    pub fn url(&self) -> String {
+        let scheme = if self.tls.is_some() { "https" } else { "http" };
+        match self.tcp.local_addr() {
+            Ok(addr) => format!("{scheme}://{addr}"),
+            Err(_) => format!("{scheme}://"),
+        }
+    }
+
+    /// Answer `server`'s analysis until the listener fails. With a token,
+    /// calls without `authorization: Bearer <token>` metadata fail with
+    /// `UNAUTHENTICATED`.
+    pub fn serve(self, server: Server<'static>) -> Result<(), BoloError> {
+        let fail = |reason: String| BoloError::Listen {
+            addr: self.url(),
+            reason,
+        };
+        let token = self.token.clone();
+        let service = InterceptedService::new(
+            AnalysisServer::new(Service::new(server))
+                .max_decoding_message_size(serve::MAX_BODY as usize),
+            move |request| authorize(request, token.as_deref()),
+        );
+        let mut builder =
+            tonic::transport::Server::builder().http2_max_header_list_size(MAX_HEADERS);
+        if let Some(identity) = self.tls.clone() {
+            builder = builder
+                .tls_config(ServerTlsConfig::new().identity(identity))
+                .map_err(|e| fail(e.to_string()))?;
+        }
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| fail(e.to_string()))?;
+        runtime.block_on(async {
+            let tcp = self.tcp.try_clone().map_err(|e| fail(e.to_string()))?;
+            let tcp = tokio::net::TcpListener::from_std(tcp).map_err(|e| fail(e.to_string()))?;
+            builder
+                .add_service(service)
+                .serve_with_incoming(TcpListenerStream::new(tcp))
+                .await
+                .map_err(|e| fail(e.to_string()))
+        })
+    }
+}
+
+fn authorize(request: Request<()>, token: Option<&str>) -> Result<Request<()>, Status> {
+    let given = request.metadata().get("authorization");
+    match token {
+        Some(token) if !serve::bearer(given.and_then(|v| v.to_str().ok()), token) => {
+            Err(Status::unauthenticated("missing or wrong bearer token"))
+        }
+        _ => Ok(request),
+    }
+}
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::tree_sitter::Options;
+    use crate::api::tree_sitter::py::Python;
+    use pb::analysis_client::AnalysisClient;
+    use tempfile::TempDir;
+    use tonic::transport::Channel;
+
+    /// Serve `dir` over gRPC on a free loopback port, returning its URL.
+    fn start(dir: &TempDir, token: Option<&str>) -> String {
+        std::fs::write(dir.path().join("a.py"), "def f():\n    g()\n").unwrap();
+        std::fs::write(dir.path().join("main.py"), "def g():\n    pass\n").unwrap();
+        let root = dir.path().to_path_buf();
+        let server = Server::new(&Python, "py", root, false, Options::default()).unwrap();
+        let listener = Listener::bind("127.0.0.1:0", token.map(String::from), None).unwrap();
+        let url = listener.url();
+        std::thread::spawn(move || listener.serve(server));
+        url
+    }
+
+    fn runtime() -> tokio::runtime::Runtime {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+    }
+
+    async fn client(url: String) -> AnalysisClient<Channel> {
+        AnalysisClient::connect(url).await.unwrap()
+    }
+
+    fn symbol(name: &str) -> pb::SymbolRequest {
+        pb::SymbolRequest { name: name.into() }
+    }
+
+    #[test]
+    fn symbol_and_callers() {
+        let dir = TempDir::new().unwrap();
+        let url = start(&dir, None);
+        runtime().block_on(async {
+            let mut c = client(url).await;
+            let g = c.get_symbol(symbol("g")).await.unwrap().into_inner();
+            assert_eq!((g.id.as_str(), g.kind.as_str()), ("main.py::g", "fn"));
+            assert_eq!((g.start_line, g.end_line), (1, 2));
+            assert_eq!((g.fan_in, g.fan_out), (1, 0));
+            assert!(g.json.contains(r#""name":"g""#));
+
+            let callers = c.get_callers(symbol("main.py::g")).await.unwrap();
+            let callers = callers.into_inner().callers;
+            assert_eq!(callers.len(), 1);
+            assert_eq!(
+                (callers[0].id.as_str(), callers[0].edge.as_str()),
+                ("a.py::f", "call")
+            );
+            assert!(
+                c.get_callers(symbol("f"))
+                    .await
+                    .unwrap()
+                    .into_inner()
+                    .callers
+                    .is_empty()
+            );
+
+            let missing = c.get_symbol(symbol("nope")).await.unwrap_err();
+            assert_eq!(missing.code(), tonic::Code::NotFound);
+        });
+    }
+
+    #[test]
+    fn analyze_streams_updates() {
+        let dir = TempDir::new().unwrap();
+        let url = start(&dir, None);
+        runtime().block_on(async {
+            let mut c = client(url).await;
+            let mut updates = c
+                .stream_updates(pb::UpdatesRequest {})
+                .await
+                .unwrap()
+                .into_inner();
+            let first = updates.message().await.unwrap().unwrap();
+            assert_eq!((first.files, first.warnings), (2, 0));
+
+            std::fs::write(dir.path().join("new.py"), "x = 1\n").unwrap();
+            let summary = c.analyze(pb::AnalyzeRequest {}).await.unwrap().into_inner();
+            assert_eq!(summary.files, 3);
+            assert_eq!(updates.message().await.unwrap(), Some(summary));
+        });
+    }
+
+    #[test]
+    fn token_required_when_set() {
+        let dir = TempDir::new().unwrap();
+        let url = start(&dir, Some("s3cret"));
+        runtime().block_on(async {
+            let mut c = client(url).await;
+            let refused = c.get_symbol(symbol("g")).await.unwrap_err();
+            assert_eq!(refused.code(), tonic::Code::Unauthenticated);
+
+            let mut request = Request::new(symbol("g"));
+            let bearer = "Bearer s3cret".parse().unwrap();
+            request.metadata_mut().insert("authorization", bearer);
+            assert!(c.get_symbol(request).await.is_ok());
+        });
+    }
+
+    #[test]
+    fn token_required_beyond_loopback() {
+        let e = Listener::bind("0.0.0.0:0", None, None).err().unwrap();
+        assert!(e.to_string().contains(serve::TOKEN_VAR), "{e}");
+    }
+}
@@ -0,0 +1,180 @@
+use crate::api::git;
+use crate::api::tree_sitter::go::Go;
+use crate::api::tree_sitter::js::JavaScript;
+use crate::api::tree_sitter::py::Python;
+use crate::api::tree_sitter::rs::Rust;
+use crate::api::tree_sitter::ts::TypeScript;
+use crate::api::tree_sitter::{Lang, Options, Syntax};
+use crate::clean;
+use crate::error::BoloError;
+use std::path::{Path, PathBuf};
+
+/// First line after the shebang of every script [`install`] writes, so a
+/// reinstall can tell its own hooks from ones it must not overwrite.
+const MARKER: &str = "# Installed by `bolo hook install`.";
+
+/// Git hooks `bolo hook install` can set up.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum Hook {
+    /// Check staged files before each commit
+    #[default]
+    PreCommit,
+    /// Check files changed since the upstream branch before each push
+    PrePush,
+}
+
+impl Hook {
+    fn name(self) -> &'static str {
+        match self {
+            Hook::PreCommit => "pre-commit",
+            Hook::PrePush => "pre-push",
+        }
+    }
+
+    fn script(self) -> String {
+        let run = match self {
+            Hook::PreCommit => "bolo hook run",
+            Hook::PrePush => "bolo hook run --against '@{upstream}'",
+        };
+        format!("#!/bin/sh\n{MARKER}\nexec {run}\n")
+    }
+}
+
+/// Write `hook` into the hooks directory of the repository containing
+/// `repo`. An existing hook not written by bolo is kept unless `force`.
+pub fn install(repo: &Path, hook: Hook, force: bool) -> Result<PathBuf, BoloError> {
+    let path = git::hooks_dir(repo)?.join(hook.name());
+    if !force
+        && let Ok(existing) = std::fs::read_to_string(&path)
+        && !existing.contains(MARKER)
+    {
+        return Err(BoloError::Exists { path });
+    }
+    crate::api::fs::write_file(&path, &hook.script(), true)?;
+    make_executable(&path)?;
+    Ok(path)
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> Result<(), BoloError> {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755)).map_err(|e| {
+        BoloError::Write {
+            path: path.to_path_buf(),
+            reason: e.to_string(),
+        }
+    })
+}
+
+#[cfg(not(unix))]
+fn make_executable(_: &Path) -> Result<(), BoloError> {
+    Ok(())
+}
+
+/// Parse `files` (`(path, text)`, as [`git::changed`] returns them) with
+/// the grammar their extension names, skipping other files and ones that
+/// fail to parse. Only these files are parsed, so layer rules see calls
+/// between changed files alone.
+pub fn analyze(files: &[(String, String)]) -> Vec<Vec<Syntax<'static>>> {
+    let mut out = Vec::new();
+    for (path, text) in files {
+        let lang: &dyn Lang = match Path::new(path).extension().and_then(|e| e.to_str()) {
+            Some("py") => &Python,
+            Some("rs") => &Rust,
+            Some("js") => &JavaScript,
+            Some("ts") => &TypeScript,
+            Some("go") => &Go,
+            _ => continue,
+        };
+        let mut parser = lang.get_parser();
+        let Ok(ast) = lang.parse(&mut parser, text, &Options::default()) else {
+            continue;
+        };
+        let nodes = clean::clean(path, text, ast, Default::default());
+        out.push(nodes.into_iter().map(Syntax::into_owned).collect());
+    }
+    out
+}
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::{self, Rules};
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    fn repo() -> TempDir {
+        let dir = TempDir::new().unwrap();
+        let out = Command::new("git")
+            .current_dir(dir.path())
+            .args(["init", "--quiet"])
+            .output()
+            .unwrap();
+        assert!(out.status.success());
+        dir
+    }
+
+    // ── install ──
+
+    #[test]
+    fn installs_executable_pre_commit() {
+        let dir = repo();
+        let path = install(dir.path(), Hook::PreCommit, false).unwrap();
+        assert!(path.ends_with(".git/hooks/pre-commit"));
+        let script = std::fs::read_to_string(&path).unwrap();
+        assert!(script.starts_with("#!/bin/sh\n"));
+        assert!(script.ends_with("exec bolo hook run\n"));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o111, 0o111);
+        }
+    }
+
+    #[test]
+    fn pre_push_checks_against_upstream() {
+        let dir = repo();
+        let path = install(dir.path(), Hook::PrePush, false).unwrap();
+        let script = std::fs::read_to_string(path).unwrap();
+        assert!(script.contains("--against '@{upstream}'"));
+    }
+
+    #[test]
+    fn foreign_hook_kept_unless_forced() {
+        let dir = repo();
+        let path = dir.path().join(".git/hooks/pre-commit");
+        std::fs::write(&path, "#!/bin/sh\nmake lint\n").unwrap();
+        let err = install(dir.path(), Hook::PreCommit, false).unwrap_err();
+        assert!(matches!(err, BoloError::Exists { .. }));
+        install(dir.path(), Hook::PreCommit, true).unwrap();
+        // Its own hook is replaced without --force.
+        install(dir.path(), Hook::PreCommit, false).unwrap();
+    }
+
+    // ── check ──
+
+    #[test]
+    fn analyzes_by_extension() {
+        let files = [
+            ("a.py".to_string(), "def f():\n    eval(x)\n".to_string()),
+            ("b.rs".to_string(), "fn g() { h(); }\n".to_string()),
+            ("README.md".to_string(), "# hi\n".to_string()),
+        ];
+        assert_eq!(analyze(&files).len(), 2);
+    }
+
+    #[test]
+    fn forbidden_call_in_changed_file() {
+        let files = analyze(&[("a.py".to_string(), "def f():\n    eval(x)\n".to_string())]);
+        let rules = Rules {
+            forbid: vec!["eval".to_string()],
+            ..Default::default()
+        };
+        let v = rules::check(&files, &rules);
+        assert_eq!(v.len(), 1);
+        assert_eq!((v[0].file.as_str(), v[0].span.start_line), ("a.py", 2));
+    }
+}
@@ -0,0 +1,188 @@
+use crate::api::fs::File;
+use std::str::FromStr;
+
+/// A parse thread is only worth starting once it has this many files…
+const FILES_PER_THREAD: usize = 32;
+/// …or this many bytes to chew through.
+const BYTES_PER_THREAD: u64 = 1024 * 1024;
+/// Peak memory per parse thread, as a multiple of the file it holds: the
+/// source, its tree-sitter tree and the nodes built from it.
+const TREE_OVERHEAD: u64 = 16;
+/// Fixed memory per thread (stack, parser, allocator arenas).
+const THREAD_BASE: u64 = 8 * 1024 * 1024;
+/// Walking is I/O-bound, so it may run more threads than there are cores.
+const MAX_WALK_THREADS: usize = 8;
+
+/// `--jobs`: a fixed thread count (0 = all cores) or `auto`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Jobs {
+    Auto,
+    Fixed(usize),
+}
+
+impl FromStr for Jobs {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "auto" => Ok(Jobs::Auto),
+            n => n
+                .parse()
+                .map(Jobs::Fixed)
+                .map_err(|_| format!("expected a thread count or `auto`, got `{n}`")),
+        }
+    }
+}
+
+impl Jobs {
+    /// Threads for the directory walk, which runs before the file set is known.
+    pub fn walk_threads(self) -> usize {
+        match self {
+            Jobs::Auto => (cores() * 2).min(MAX_WALK_THREADS),
+            Jobs::Fixed(0) => cores(),
+            Jobs::Fixed(n) => n,
+        }
+    }
+
+    /// Threads for parsing `files`.
+    pub fn parse_threads(self, files: &[File]) -> usize {
+        match self {
+            Jobs::Auto => {
+                let bytes = files.iter().map(File::size).sum();
+                auto(files.len(), bytes, cores(), available_memory())
+            }
+            Jobs::Fixed(0) => cores(),
+            Jobs::Fixed(n) => n,
+        }
+    }
+}
+
+/// Parse pool size for `files` files totalling `bytes` on `cores` cores.
+///
+/// Small runs get few threads, since spawning costs more than it saves and
+/// each thread warms its own caches. When `available` memory is known, the
+/// pool is also capped so every thread can hold an average file's tree.
+pub fn auto(files: usize, bytes: u64, cores: usize, available: Option<u64>) -> usize {
+    let by_work = (files / FILES_PER_THREAD).max((bytes / BYTES_PER_THREAD) as usize);
+    let by_memory = match available {
+        Some(mem) => {
+            let avg = bytes / files.max(1) as u64;
+            (mem / (avg * TREE_OVERHEAD + THREAD_BASE)) as usize
+        }
+        None => usize::MAX,
+    };
+    by_work.min(by_memory).min(cores).max(1)
+}
+
+fn cores() -> usize {
+    std::thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+/// `MemAvailable` from `/proc/meminfo`; `None` where that doesn't exist.
+fn available_memory() -> Option<u64> {
+    let info = std::fs::read_to_string("/proc/meminfo").ok()?;
+    meminfo_available(&info)
+}
+
+fn meminfo_available(info: &str) -> Option<u64> {
+    let line = info.lines().find(|l| l.starts_with("MemAvailable:"))?;
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb * 1024)
+}
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GIB: u64 = 1024 * 1024 * 1024;
+
+    // ── FromStr ──
+
+    #[test]
+    fn parse_auto() {
+        assert_eq!("auto".parse::<Jobs>().unwrap(), Jobs::Auto);
+    }
+
+    #[test]
+    fn parse_count() {
+        assert_eq!("4".parse::<Jobs>().unwrap(), Jobs::Fixed(4));
+        assert_eq!("0".parse::<Jobs>().unwrap(), Jobs::Fixed(0));
+    }
+
+    #[test]
+    fn parse_rejects_other_words() {
+        assert!("fast".parse::<Jobs>().is_err());
+        assert!("-1".parse::<Jobs>().is_err());
+    }
+
+    // ── Fixed ──
+
+    #[test]
+    fn fixed_is_used_for_both_pools() {
+        assert_eq!(Jobs::Fixed(3).walk_threads(), 3);
+        assert_eq!(Jobs::Fixed(3).parse_threads(&[]), 3);
+    }
+
+    #[test]
+    fn fixed_zero_is_all_cores() {
+        assert_eq!(Jobs::Fixed(0).parse_threads(&[]), cores());
+    }
+
+    // ── auto ──
+
+    #[test]
+    fn auto_small_run_single_thread() {
+        assert_eq!(auto(10, 40_000, 16, Some(64 * GIB)), 1);
+    }
+
+    #[test]
+    fn auto_scales_with_file_count() {
+        assert_eq!(auto(128, 128 * 4096, 16, Some(64 * GIB)), 4);
+    }
+
+    #[test]
+    fn auto_scales_with_bytes() {
+        // few files, but large ones
+        assert_eq!(auto(8, 8 * 1024 * 1024, 16, Some(64 * GIB)), 8);
+    }
+
+    #[test]
+    fn auto_capped_by_cores() {
+        assert_eq!(auto(100_000, 400 * 1024 * 1024, 8, Some(64 * GIB)), 8);
+    }
+
+    #[test]
+    fn auto_capped_by_memory() {
+        // 4 MiB average file → 72 MiB per thread; 300 MiB fits 4
+        let files = 1000;
+        let bytes = files as u64 * 4 * 1024 * 1024;
+        assert_eq!(auto(files, bytes, 64, Some(300 * 1024 * 1024)), 4);
+    }
+
+    #[test]
+    fn auto_never_zero() {
+        assert_eq!(auto(0, 0, 8, Some(0)), 1);
+    }
+
+    #[test]
+    fn auto_walk_oversubscribes_but_is_bounded() {
+        let n = Jobs::Auto.walk_threads();
+        assert!((2..=MAX_WALK_THREADS).contains(&n));
+    }
+
+    // ── meminfo ──
+
+    #[test]
+    fn meminfo_reads_available() {
+        let info =
+            "MemTotal:       16000000 kB\nMemFree:         1000 kB\nMemAvailable:    2048 kB\n";
+        assert_eq!(meminfo_available(info), Some(2048 * 1024));
+    }
+
+    #[test]
+    fn meminfo_missing_field() {
+        assert_eq!(meminfo_available("MemTotal: 1 kB\n"), None);
+    }
+}
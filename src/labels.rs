@@ -0,0 +1,146 @@
+use crate::graph::Def;
+use crate::links::check_placeholders;
+use serde::Deserialize;
+
+/// Placeholders a label template may use.
+const PLACEHOLDERS: &[&str] = &[
+    "{kind}",
+    "{name}",
+    "{qualified_name}",
+    "{file}",
+    "{line}",
+    "{end_line}",
+    "{lines}",
+];
+
+/// The `[labels]` table in `bolo.toml`: how `.dot` and `.html` outputs
+/// show definitions.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Labels {
+    /// Shown in place of the definition's name.
+    pub node: Option<LabelTemplate>,
+    /// Hover text: a Graphviz `tooltip`, an HTML `title`.
+    pub tooltip: Option<LabelTemplate>,
+}
+
+/// What a label template can show about a definition.
+#[derive(Debug, Clone, Copy)]
+pub struct Node<'a> {
+    /// `fn`, `type` or `fixture`.
+    pub kind: &'a str,
+    pub name: &'a str,
+    /// Dotted through enclosing definitions, e.g. `Client.send`.
+    pub qualified_name: &'a str,
+    pub file: &'a str,
+    pub line: usize,
+    pub end_line: usize,
+    pub lines: usize,
+}
+
+impl<'a> Node<'a> {
+    pub fn of(def: &'a Def<'a>) -> Self {
+        let meta = &def.syntax.metadata;
+        Node {
+            kind: def.kind.as_str(),
+            name: def.name,
+            qualified_name: def
+                .id
+                .strip_prefix(def.file)
+                .and_then(|q| q.strip_prefix("::"))
+                .unwrap_or(&def.id),
+            file: def.file,
+            line: meta.span.start_line,
+            end_line: meta.span.end_line,
+            lines: meta.lines,
+        }
+    }
+}
+
+impl Labels {
+    /// `node`'s label: its name unless a template is set.
+    pub fn label(&self, node: &Node) -> String {
+        match &self.node {
+            Some(t) => t.render(node),
+            None => node.name.to_string(),
+        }
+    }
+
+    /// `node`'s hover text, if a template is set.
+    pub fn title(&self, node: &Node) -> Option<String> {
+        self.tooltip.as_ref().map(|t| t.render(node))
+    }
+}
+
+/// A label pattern such as `{kind}:{qualified_name} ({lines}L)`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct LabelTemplate(String);
+
+impl LabelTemplate {
+    /// Reject templates with an unknown `{placeholder}`.
+    pub fn new(template: &str) -> Result<Self, String> {
+        check_placeholders(template, PLACEHOLDERS, "label")?;
+        Ok(LabelTemplate(template.to_string()))
+    }
+
+    pub fn render(&self, node: &Node) -> String {
+        self.0
+            .replace("{kind}", node.kind)
+            .replace("{name}", node.name)
+            .replace("{qualified_name}", node.qualified_name)
+            .replace("{file}", node.file)
+            .replace("{line}", &node.line.to_string())
+            .replace("{end_line}", &node.end_line.to_string())
+            .replace("{lines}", &node.lines.to_string())
+    }
+}
+
+impl TryFrom<String> for LabelTemplate {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        LabelTemplate::new(&s)
+    }
+}
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEND: Node = Node {
+        kind: "fn",
+        name: "send",
+        qualified_name: "Client.send",
+        file: "net.py",
+        line: 4,
+        end_line: 9,
+        lines: 6,
+    };
+
+    #[test]
+    fn renders_every_placeholder() {
+        let t = LabelTemplate::new("{kind}:{qualified_name} ({lines}L) {file}:{line}-{end_line}")
+            .unwrap();
+        assert_eq!(t.render(&SEND), "fn:Client.send (6L) net.py:4-9");
+    }
+
+    #[test]
+    fn defaults_to_name_without_tooltip() {
+        let labels = Labels::default();
+        assert_eq!(labels.label(&SEND), "send");
+        assert_eq!(labels.title(&SEND), None);
+    }
+
+    #[test]
+    fn unknown_placeholder_rejected() {
+        let err = LabelTemplate::new("{path}").unwrap_err();
+        assert!(err.contains("unknown placeholder `{path}` in label template"));
+        assert!(
+            LabelTemplate::new("{name")
+                .unwrap_err()
+                .contains("unclosed")
+        );
+    }
+}
@@ -1,6 +1,42 @@
+pub mod analysis;
+pub mod anonymize;
 pub mod api;
+pub mod badge;
+pub mod bench;
+pub mod budget;
+pub mod bundle;
+pub mod cache;
+pub mod changelog;
 pub mod clean;
 pub mod cli;
+pub mod config;
 pub mod consolidate;
+pub mod crates;
+pub mod deps;
+pub mod diff;
 pub mod error;
+pub mod export;
+pub mod graph;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+pub mod hash;
+pub mod hook;
+pub mod jobs;
+pub mod labels;
+pub mod license;
+pub mod links;
+pub mod lsp;
+pub mod merge;
+pub mod modules;
+pub mod pipeline;
 pub mod pretty;
+pub mod profile;
+pub mod query;
+pub mod redact;
+pub mod rename;
+pub mod report;
+pub mod rules;
+pub mod semver;
+pub mod serve;
+pub mod survey;
+pub mod trend;
@@ -0,0 +1,128 @@
+/// Known license header phrases and the SPDX id they imply, checked in order.
+const HEADERS: &[(&str, &str)] = &[
+    ("apache license, version 2.0", "Apache-2.0"),
+    ("apache license version 2.0", "Apache-2.0"),
+    ("mozilla public license, v. 2.0", "MPL-2.0"),
+    ("gnu affero general public license", "AGPL-3.0"),
+    ("gnu lesser general public license", "LGPL"),
+    ("gnu general public license", "GPL"),
+    ("permission is hereby granted, free of charge", "MIT"),
+    ("redistribution and use in source and binary forms", "BSD"),
+    ("this is free and unencumbered software", "Unlicense"),
+];
+
+/// Detect a license in a file's leading comment text.
+///
+/// An `SPDX-License-Identifier:` line wins; otherwise well-known header
+/// phrases map to an SPDX id. GPL-family ids get a version suffix when the
+/// header names one (`GPL-3.0`).
+pub fn detect(header: &str) -> Option<String> {
+    if let Some(id) = spdx(header) {
+        return Some(id);
+    }
+    let text = normalize(header);
+    let (_, id) = HEADERS.iter().find(|(p, _)| text.contains(p))?;
+    if id.ends_with("GPL") {
+        let version = ["3", "2.1", "2"]
+            .iter()
+            .find(|v| text.contains(&format!("version {v}")));
+        if let Some(v) = version {
+            let v = if v.contains('.') {
+                v.to_string()
+            } else {
+                format!("{v}.0")
+            };
+            return Some(format!("{id}-{v}"));
+        }
+    }
+    Some(id.to_string())
+}
+
+fn spdx(header: &str) -> Option<String> {
+    const TAG: &str = "SPDX-License-Identifier:";
+    let start = header.find(TAG)? + TAG.len();
+    let line = header[start..].lines().next().unwrap_or("");
+    let id = line
+        .trim()
+        .trim_end_matches("*/")
+        .trim_end_matches("\"\"\"")
+        .trim();
+    (!id.is_empty()).then(|| id.to_string())
+}
+
+/// Lowercase and collapse comment markers and whitespace to single spaces.
+fn normalize(text: &str) -> String {
+    text.split_whitespace()
+        .filter(|w| !matches!(*w, "#" | "//" | "//!" | "///" | "/*" | "*/" | "*"))
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ── SPDX ──
+
+    #[test]
+    fn spdx_line_comment() {
+        assert_eq!(
+            detect("// SPDX-License-Identifier: MIT OR Apache-2.0\n").as_deref(),
+            Some("MIT OR Apache-2.0")
+        );
+    }
+
+    #[test]
+    fn spdx_block_comment() {
+        assert_eq!(
+            detect("/* SPDX-License-Identifier: GPL-2.0-only */").as_deref(),
+            Some("GPL-2.0-only")
+        );
+    }
+
+    #[test]
+    fn spdx_wins_over_header_text() {
+        let header = "# SPDX-License-Identifier: BSD-3-Clause\n# Permission is hereby granted, free of charge";
+        assert_eq!(detect(header).as_deref(), Some("BSD-3-Clause"));
+    }
+
+    #[test]
+    fn spdx_empty_value_ignored() {
+        assert_eq!(detect("# SPDX-License-Identifier:\n"), None);
+    }
+
+    // ── Headers ──
+
+    #[test]
+    fn apache_header() {
+        let header = "# Licensed under the Apache License, Version 2.0 (the \"License\");";
+        assert_eq!(detect(header).as_deref(), Some("Apache-2.0"));
+    }
+
+    #[test]
+    fn mit_header_across_lines() {
+        let header = "// Permission is hereby granted,\n// free of charge, to any person";
+        assert_eq!(detect(header).as_deref(), Some("MIT"));
+    }
+
+    #[test]
+    fn gpl_header_with_version() {
+        let header = "# under the terms of the GNU General Public License as published by\n# the Free Software Foundation, either version 3 of the License";
+        assert_eq!(detect(header).as_deref(), Some("GPL-3.0"));
+    }
+
+    #[test]
+    fn lgpl_header_with_minor_version() {
+        let header = "/* GNU Lesser General Public License\n * version 2.1 */";
+        assert_eq!(detect(header).as_deref(), Some("LGPL-2.1"));
+    }
+
+    #[test]
+    fn no_license() {
+        assert_eq!(detect("# helper utilities for parsing"), None);
+        assert_eq!(detect(""), None);
+    }
+}
@@ -0,0 +1,215 @@
+use crate::api::tree_sitter::{ASTNode, Syntax};
+use crate::export::definition;
+use serde::Deserialize;
+use std::str::FromStr;
+
+/// Metric key holding a node's source permalink.
+pub const LINK: &str = "link";
+
+/// Placeholders a link template may use.
+const PLACEHOLDERS: &[&str] = &["{commit}", "{path}", "{start}", "{end}"];
+
+/// A permalink pattern such as
+/// `https://github.com/org/repo/blob/{commit}/{path}#L{start}-L{end}`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct LinkTemplate(String);
+
+impl LinkTemplate {
+    /// Reject templates with an unknown `{placeholder}`, which would
+    /// otherwise end up verbatim in every link.
+    pub fn new(template: &str) -> Result<Self, String> {
+        check_placeholders(template, PLACEHOLDERS, "link")?;
+        Ok(LinkTemplate(template.to_string()))
+    }
+
+    /// True if links need the commit being analyzed.
+    pub fn needs_commit(&self) -> bool {
+        self.0.contains("{commit}")
+    }
+
+    pub fn render(&self, commit: &str, path: &str, start: usize, end: usize) -> String {
+        self.0
+            .replace("{commit}", commit)
+            .replace("{path}", path)
+            .replace("{start}", &start.to_string())
+            .replace("{end}", &end.to_string())
+    }
+}
+
+/// Check that every `{placeholder}` in a `what` template is one of `known`.
+pub(crate) fn check_placeholders(template: &str, known: &[&str], what: &str) -> Result<(), String> {
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        let close = rest[open..]
+            .find('}')
+            .ok_or_else(|| format!("unclosed `{{` in {what} template `{template}`"))?;
+        let placeholder = &rest[open..open + close + 1];
+        if !known.contains(&placeholder) {
+            return Err(format!(
+                "unknown placeholder `{placeholder}` in {what} template (use {})",
+                known.join(", ")
+            ));
+        }
+        rest = &rest[open + close + 1..];
+    }
+    Ok(())
+}
+
+impl FromStr for LinkTemplate {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        LinkTemplate::new(s)
+    }
+}
+
+impl TryFrom<String> for LinkTemplate {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        LinkTemplate::new(&s)
+    }
+}
+
+/// Where the analyzed files sit in their repository.
+#[derive(Debug, Clone, Default)]
+pub struct Source<'a> {
+    pub commit: &'a str,
+    /// Directory of the analyzed `PATH` within the repository, `/`-terminated
+    /// or empty.
+    pub prefix: &'a str,
+    /// `--root-label`, stripped from File paths before linking.
+    pub root_label: Option<&'a str>,
+}
+
+/// Set a [`LINK`] on every File node (its whole line range) and definition
+/// (its span). Other nodes are left alone to keep the output small.
+pub fn attach(files: &mut [Vec<Syntax>], template: &LinkTemplate, source: &Source) {
+    for nodes in files {
+        let Some(ASTNode::File(file)) = nodes.first().map(|s| &s.node) else {
+            continue;
+        };
+        // Labels are joined the way `fs::portable` joins them.
+        let label = source
+            .root_label
+            .filter(|l| !l.is_empty())
+            .map(|l| l.trim_end_matches(['/', '\\']).replace('\\', "/"));
+        let rel = match &label {
+            Some(label) => file
+                .path
+                .strip_prefix(label.as_str())
+                .and_then(|p| p.strip_prefix('/'))
+                .unwrap_or(&file.path),
+            None => &file.path,
+        };
+        let path = format!("{}{rel}", source.prefix);
+        let lines = nodes[0].metadata.lines;
+        let link = template.render(source.commit, &path, 1, lines);
+        nodes[0].metadata.set_metric(LINK, link);
+        definitions(&mut nodes[1..], &|start, end| {
+            template.render(source.commit, &path, start, end)
+        });
+    }
+}
+
+fn definitions(nodes: &mut [Syntax], link: &dyn Fn(usize, usize) -> String) {
+    for s in nodes {
+        if definition(&s.node).is_some() {
+            let span = s.metadata.span;
+            s.metadata
+                .set_metric(LINK, link(span.start_line, span.end_line));
+        }
+        definitions(&mut s.contains, link);
+    }
+}
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::tree_sitter::analyze;
+    use crate::api::tree_sitter::py::Python;
+    use serde_json::Value;
+
+    const GITHUB: &str = "https://github.com/o/r/blob/{commit}/{path}#L{start}-L{end}";
+
+    fn link<'a>(s: &'a Syntax) -> Option<&'a str> {
+        s.metadata.metric(LINK).and_then(Value::as_str)
+    }
+
+    #[test]
+    fn template_placeholders_validated() {
+        assert!(LinkTemplate::new(GITHUB).is_ok());
+        assert!(
+            LinkTemplate::new("https://x/{file}")
+                .unwrap_err()
+                .contains("{file}")
+        );
+        assert!(
+            LinkTemplate::new("https://x/{path")
+                .unwrap_err()
+                .contains("unclosed")
+        );
+        assert!(
+            !LinkTemplate::new("https://x/{path}")
+                .unwrap()
+                .needs_commit()
+        );
+    }
+
+    #[test]
+    fn render_fills_placeholders() {
+        let t = LinkTemplate::new(GITHUB).unwrap();
+        assert_eq!(
+            t.render("abc123", "src/a.py", 3, 7),
+            "https://github.com/o/r/blob/abc123/src/a.py#L3-L7"
+        );
+    }
+
+    #[test]
+    fn files_and_definitions_linked() {
+        let mut files = [analyze(
+            &Python,
+            "a.py",
+            "class C:\n    def m(self):\n        f()\n\ndef g(): pass\n",
+        )];
+        let t = LinkTemplate::new(GITHUB).unwrap();
+        let source = Source {
+            commit: "abc",
+            prefix: "pkg/",
+            root_label: None,
+        };
+        attach(&mut files, &t, &source);
+        let nodes = &files[0];
+        assert_eq!(
+            link(&nodes[0]),
+            Some("https://github.com/o/r/blob/abc/pkg/a.py#L1-L6")
+        );
+        let class = nodes.iter().find(|s| matches!(s.node, ASTNode::Type(_)));
+        let class = class.unwrap();
+        assert_eq!(
+            link(class),
+            Some("https://github.com/o/r/blob/abc/pkg/a.py#L1-L3")
+        );
+        let method = &class.contains[0];
+        assert_eq!(
+            link(method),
+            Some("https://github.com/o/r/blob/abc/pkg/a.py#L2-L3")
+        );
+        assert!(link(&method.contains[0]).is_none());
+    }
+
+    #[test]
+    fn root_label_stripped() {
+        let mut files = [analyze(&Python, "proj/a.py", "def f(): pass\n")];
+        let t = LinkTemplate::new("{path}").unwrap();
+        let source = Source {
+            root_label: Some("proj"),
+            ..Default::default()
+        };
+        attach(&mut files, &t, &source);
+        assert_eq!(link(&files[0][0]), Some("a.py"));
+    }
+}
@@ -0,0 +1,767 @@
+use crate::api::fs;
+use crate::api::tree_sitter::incremental::Incremental;
+use crate::api::tree_sitter::{ASTNode, Lang, Options, Span, Syntax};
+use crate::clean;
+use crate::error::BoloError;
+use crate::export::definition;
+use crate::graph::Graph;
+use crate::query;
+use serde_json::{Value, json};
+use std::cell::OnceCell;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+
+/// JSON-RPC error codes used in responses.
+const PARSE_ERROR: i64 = -32700;
+const INVALID_PARAMS: i64 = -32602;
+const METHOD_NOT_FOUND: i64 = -32601;
+
+// LSP `SymbolKind` values.
+const CLASS: u8 = 5;
+const METHOD: u8 = 6;
+const INTERFACE: u8 = 11;
+const FUNCTION: u8 = 12;
+
+/// A minimal language server over bolo's graph: workspace and document
+/// symbols and call hierarchy. Open documents are reparsed on every change
+/// with an [`Incremental`] engine, so edits show up before they are saved.
+pub struct Server<L: Lang> {
+    engine: Incremental<L>,
+    ext: &'static str,
+    root: PathBuf,
+    /// Parsed files, sorted by path relative to `root` (their File node).
+    files: Vec<Vec<Syntax<'static>>>,
+    /// The graph over `files`, built on first use after they change.
+    index: OnceCell<Index>,
+    /// Columns are sent in UTF-16 code units, the LSP default, unless the
+    /// client offers UTF-8 (bolo's byte columns) at `initialize`.
+    utf16: bool,
+    exit: bool,
+}
+
+/// What requests need of a [`Graph`], owned so it can outlive one build.
+struct Index {
+    defs: Vec<Symbol>,
+    /// `(caller, callee)` indices into `defs`.
+    edges: Vec<(usize, usize)>,
+    /// The call sites behind each of `edges`.
+    sites: Vec<Vec<Span>>,
+}
+
+struct Symbol {
+    id: String,
+    name: String,
+    file: String,
+    kind: u8,
+    span: Span,
+}
+
+impl Index {
+    fn build(files: &[Vec<Syntax>]) -> Self {
+        let graph = Graph::build(files);
+        let defs = graph
+            .defs
+            .iter()
+            .map(|d| Symbol {
+                id: d.id.clone(),
+                name: d.name.to_string(),
+                file: d.file.to_string(),
+                kind: kind(d.syntax, false),
+                span: d.syntax.metadata.span,
+            })
+            .collect();
+        let sites = graph
+            .edges
+            .iter()
+            .map(|&(a, b)| {
+                let calls = graph.call_sites(a, b);
+                calls.iter().map(|s| s.metadata.span).collect()
+            })
+            .collect();
+        Index {
+            defs,
+            edges: graph.edges,
+            sites,
+        }
+    }
+}
+
+impl<L: Lang> Server<L> {
+    /// A server for files with extension `ext`, rooted at the working
+    /// directory until `initialize` names the workspace.
+    pub fn new(lang: L, ext: &'static str) -> Self {
+        Server {
+            engine: Incremental::new(lang),
+            ext,
+            root: PathBuf::from("."),
+            files: Vec::new(),
+            index: OnceCell::new(),
+            utf16: true,
+            exit: false,
+        }
+    }
+
+    /// True once the client sent `exit`.
+    pub fn exited(&self) -> bool {
+        self.exit
+    }
+
+    /// Handle one JSON-RPC message, returning the response to a request or
+    /// `None` for a notification.
+    pub fn handle(&mut self, msg: &Value) -> Option<Value> {
+        let method = msg.get("method")?.as_str()?;
+        let params = &msg["params"];
+        let uri = params["textDocument"]["uri"].as_str();
+        match method {
+            "initialized" => return None,
+            "exit" => {
+                self.exit = true;
+                return None;
+            }
+            "textDocument/didOpen" => {
+                if let (Some(uri), Some(text)) = (uri, params["textDocument"]["text"].as_str()) {
+                    self.update(uri, text);
+                }
+                return None;
+            }
+            "textDocument/didChange" => {
+                // Full sync: the last change holds the whole document.
+                let changes = params["contentChanges"].as_array();
+                let text = changes.and_then(|c| c.last()?["text"].as_str());
+                if let (Some(uri), Some(text)) = (uri, text) {
+                    self.update(uri, text);
+                }
+                return None;
+            }
+            "textDocument/didSave" | "textDocument/didClose" => {
+                if let Some(uri) = uri {
+                    self.reload(uri);
+                }
+                return None;
+            }
+            _ => {}
+        }
+        let id = msg.get("id")?.clone();
+
+        let result = match method {
+            "initialize" => Ok(self.initialize(params)),
+            "shutdown" => Ok(Value::Null),
+            "workspace/symbol" => {
+                Ok(self.workspace_symbols(params["query"].as_str().unwrap_or("")))
+            }
+            "textDocument/documentSymbol" => match uri {
+                Some(uri) => Ok(self.document_symbols(uri)),
+                None => Err((INVALID_PARAMS, "missing textDocument.uri".to_string())),
+            },
+            "textDocument/prepareCallHierarchy" => match (uri, params["position"]["line"].as_u64())
+            {
+                (Some(uri), Some(line)) => Ok(self.prepare_call_hierarchy(uri, line as usize + 1)),
+                _ => Err((
+                    INVALID_PARAMS,
+                    "missing textDocument or position".to_string(),
+                )),
+            },
+            "callHierarchy/incomingCalls" | "callHierarchy/outgoingCalls" => {
+                match params["item"]["data"]["id"].as_str() {
+                    Some(def) => Ok(self.calls(def, method.ends_with("incomingCalls"))),
+                    None => Err((INVALID_PARAMS, "missing item.data.id".to_string())),
+                }
+            }
+            _ => Err((METHOD_NOT_FOUND, format!("unsupported method `{method}`"))),
+        };
+        Some(match result {
+            Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+            Err((code, message)) => error(id, code, &message),
+        })
+    }
+
+    fn initialize(&mut self, params: &Value) -> Value {
+        let root = params["rootUri"]
+            .as_str()
+            .and_then(uri_path)
+            .or_else(|| params["rootPath"].as_str().map(PathBuf::from));
+        if let Some(root) = root {
+            self.root = fs::canonicalize(&root).unwrap_or(root);
+        }
+        let offered = params["capabilities"]["general"]["positionEncodings"].as_array();
+        self.utf16 = !offered.is_some_and(|e| e.iter().any(|e| e == "utf-8"));
+        self.load();
+        json!({
+            "capabilities": {
+                "positionEncoding": if self.utf16 { "utf-16" } else { "utf-8" },
+                "textDocumentSync": 1,
+                "workspaceSymbolProvider": true,
+                "documentSymbolProvider": true,
+                "callHierarchyProvider": true,
+            },
+            "serverInfo": {"name": "bolo", "version": env!("CARGO_PKG_VERSION")},
+        })
+    }
+
+    /// Parse every file under the root; unreadable ones are skipped.
+    fn load(&mut self) {
+        self.files.clear();
+        self.index.take();
+        for file in fs::walk_dir(&self.root, self.ext, false).unwrap_or_default() {
+            if file.size() > fs::MAX_FILE_SIZE {
+                continue;
+            }
+            if let Ok((text, _)) = file.read_lossy() {
+                let rel = fs::portable(&file.rel_path, None);
+                self.parse(rel, &text);
+            }
+        }
+    }
+
+    fn update(&mut self, uri: &str, text: &str) {
+        if let Some(rel) = self.relative(uri) {
+            self.parse(rel, text);
+        }
+    }
+
+    /// Replace a file with its contents on disk, or drop it if it is gone.
+    fn reload(&mut self, uri: &str) {
+        let Some(rel) = self.relative(uri) else {
+            return;
+        };
+        let path = self.root.join(&rel);
+        match std::fs::read(&path) {
+            Ok(bytes) => self.parse(rel, &String::from_utf8_lossy(&bytes)),
+            Err(_) => {
+                self.engine.forget(&path);
+                if let Ok(i) = self.find(&rel) {
+                    self.files.remove(i);
+                    self.index.take();
+                }
+            }
+        }
+    }
+
+    fn parse(&mut self, rel: String, text: &str) {
+        let path = self.root.join(&rel);
+        let Ok((ast, _)) = self.engine.parse(&path, text, &Options::default()) else {
+            return;
+        };
+        let nodes = clean::clean(&rel, text, ast, Default::default());
+        let mut nodes: Vec<_> = nodes.into_iter().map(Syntax::into_owned).collect();
+        if self.utf16 {
+            utf16_columns(text, &mut nodes);
+        }
+        match self.find(&rel) {
+            Ok(i) => self.files[i] = nodes,
+            Err(i) => self.files.insert(i, nodes),
+        }
+        self.index.take();
+    }
+
+    /// Index of the file at `rel`, or where it would go.
+    fn find(&self, rel: &str) -> Result<usize, usize> {
+        self.files
+            .binary_search_by(|nodes| match nodes.first().map(|s| &s.node) {
+                Some(ASTNode::File(f)) => f.path.as_ref().cmp(rel),
+                _ => std::cmp::Ordering::Less,
+            })
+    }
+
+    /// The root-relative path of a `file://` URI with this server's extension.
+    fn relative(&self, uri: &str) -> Option<String> {
+        let path = uri_path(uri)?;
+        if path.extension().is_none_or(|e| e != self.ext) {
+            return None;
+        }
+        let path = fs::canonicalize(&path).unwrap_or(path);
+        let rel = path.strip_prefix(&self.root).ok()?;
+        Some(fs::portable(rel, None))
+    }
+
+    fn uri(&self, rel: &str) -> String {
+        path_uri(&self.root.join(rel))
+    }
+
+    fn index(&self) -> &Index {
+        self.index.get_or_init(|| Index::build(&self.files))
+    }
+
+    /// Definitions whose name contains `query`, ignoring case.
+    fn workspace_symbols(&self, query: &str) -> Value {
+        let query = query.to_lowercase();
+        let symbols: Vec<Value> = self
+            .index()
+            .defs
+            .iter()
+            .filter(|d| d.name.to_lowercase().contains(&query))
+            .map(|d| {
+                json!({
+                    "name": d.name,
+                    "kind": d.kind,
+                    "location": {"uri": self.uri(&d.file), "range": range(d.span)},
+                    "containerName": d.file,
+                })
+            })
+            .collect();
+        Value::Array(symbols)
+    }
+
+    fn document_symbols(&self, uri: &str) -> Value {
+        let nodes = self
+            .relative(uri)
+            .and_then(|rel| self.find(&rel).ok())
+            .map(|i| &self.files[i]);
+        match nodes {
+            Some(nodes) => Value::Array(document_symbols(&nodes[1..], false)),
+            None => Value::Array(Vec::new()),
+        }
+    }
+
+    /// The innermost definition spanning `line` (1-based).
+    fn prepare_call_hierarchy(&self, uri: &str, line: usize) -> Value {
+        let Some(rel) = self.relative(uri) else {
+            return Value::Null;
+        };
+        self.index()
+            .defs
+            .iter()
+            .rposition(|d| d.file == rel && d.span.start_line <= line && line <= d.span.end_line)
+            .map_or(Value::Null, |i| json!([self.item(i)]))
+    }
+
+    /// Callers of (`incoming`) or callees of the definition with id `def`,
+    /// each with the ranges of the calls between them.
+    fn calls(&self, def: &str, incoming: bool) -> Value {
+        let index = self.index();
+        let Some(target) = index.defs.iter().position(|d| d.id == def) else {
+            return Value::Null;
+        };
+        let calls: Vec<Value> = index
+            .edges
+            .iter()
+            .zip(&index.sites)
+            .filter_map(|(&(a, b), sites)| {
+                let (this, other) = if incoming { (b, a) } else { (a, b) };
+                (this == target).then_some((other, sites))
+            })
+            .map(|(other, sites)| {
+                let sites: Vec<Value> = sites.iter().map(|&s| range(s)).collect();
+                let key = if incoming { "from" } else { "to" };
+                let mut call = json!({"fromRanges": sites});
+                call[key] = self.item(other);
+                call
+            })
+            .collect();
+        Value::Array(calls)
+    }
+
+    fn item(&self, i: usize) -> Value {
+        let d = &self.index().defs[i];
+        let r = range(d.span);
+        json!({
+            "name": d.name,
+            "kind": d.kind,
+            "detail": d.file,
+            "uri": self.uri(&d.file),
+            "range": r,
+            "selectionRange": r,
+            "data": {"id": d.id},
+        })
+    }
+}
+
+/// Serve LSP over `input`/`output` until the client exits or hangs up.
+pub fn serve<L: Lang>(
+    server: &mut Server<L>,
+    mut input: impl BufRead,
+    mut output: impl Write,
+) -> Result<(), BoloError> {
+    let read_err = |e: io::Error| BoloError::Read {
+        path: PathBuf::from("<stdin>"),
+        reason: e.to_string(),
+    };
+    let write_err = |e: io::Error| BoloError::Write {
+        path: PathBuf::from("<stdout>"),
+        reason: e.to_string(),
+    };
+    while let Some(body) = read_message(&mut input).map_err(read_err)? {
+        let response = match serde_json::from_slice::<Value>(&body) {
+            Ok(msg) => server.handle(&msg),
+            Err(e) => Some(error(Value::Null, PARSE_ERROR, &e.to_string())),
+        };
+        if let Some(response) = response {
+            write_message(&mut output, &response).map_err(write_err)?;
+        }
+        if server.exited() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Read one `Content-Length`-framed message body; `None` at end of input.
+pub fn read_message(input: &mut impl BufRead) -> io::Result<Option<Vec<u8>>> {
+    let mut length = None;
+    loop {
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':')
+            && name.eq_ignore_ascii_case("content-length")
+        {
+            length = value.trim().parse::<usize>().ok();
+        }
+    }
+    let length = length.ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidData, "missing Content-Length header")
+    })?;
+    let mut body = vec![0; length];
+    input.read_exact(&mut body)?;
+    Ok(Some(body))
+}
+
+pub fn write_message(output: &mut impl Write, msg: &Value) -> io::Result<()> {
+    let body = msg.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{body}", body.len())?;
+    output.flush()
+}
+
+fn error(id: Value, code: i64, message: &str) -> Value {
+    json!({"jsonrpc": "2.0", "id": id, "error": {"code": code, "message": message}})
+}
+
+/// `span` as an LSP range: 0-based lines and columns (in the negotiated
+/// encoding, see [`utf16_columns`]), or whole lines when the span has no
+/// columns (output of older versions).
+fn range(span: Span) -> Value {
+    if span.start_col == 0 {
+        return json!({
+            "start": {"line": span.start_line.saturating_sub(1), "character": 0},
+            "end": {"line": span.end_line, "character": 0},
+        });
+    }
+    json!({
+        "start": {"line": span.start_line - 1, "character": span.start_col - 1},
+        "end": {"line": span.end_line.saturating_sub(1), "character": span.end_col.saturating_sub(1)},
+    })
+}
+
+/// Rewrite the byte columns of every span in `nodes` as UTF-16 code units.
+fn utf16_columns(text: &str, nodes: &mut [Syntax]) {
+    for s in nodes {
+        let span = &mut s.metadata.span;
+        if span.start_col != 0 {
+            span.start_col = utf16_column(text, span.start_byte, span.start_col);
+            span.end_col = utf16_column(text, span.end_byte, span.end_col);
+        }
+        utf16_columns(text, &mut s.contains);
+    }
+}
+
+/// The 1-based UTF-16 column of offset `byte`, whose 1-based byte column
+/// is `col`.
+fn utf16_column(text: &str, byte: usize, col: usize) -> usize {
+    byte.checked_sub(col - 1)
+        .and_then(|line| text.get(line..byte))
+        .map_or(col, |before| before.encode_utf16().count() + 1)
+}
+
+fn kind(s: &Syntax, in_type: bool) -> u8 {
+    match &s.node {
+        ASTNode::Type(t) if t.is_abstract => INTERFACE,
+        ASTNode::Type(_) => CLASS,
+        ASTNode::Function(_) if in_type => METHOD,
+        _ => FUNCTION,
+    }
+}
+
+/// Nested `DocumentSymbol`s for the definitions in `nodes`.
+fn document_symbols(nodes: &[Syntax], in_type: bool) -> Vec<Value> {
+    nodes
+        .iter()
+        .filter_map(|s| {
+            let (_, name) = definition(&s.node)?;
+            let r = range(s.metadata.span);
+            let is_type = matches!(s.node, ASTNode::Type(_));
+            Some(json!({
+                "name": name,
+                "kind": kind(s, in_type),
+                "range": r,
+                "selectionRange": r,
+                "children": document_symbols(&s.contains, is_type),
+            }))
+        })
+        .collect()
+}
+
+/// The local path of a `file://` URI, percent-decoded.
+fn uri_path(uri: &str) -> Option<PathBuf> {
+    query::decode(uri.strip_prefix("file://")?).map(PathBuf::from)
+}
+
+/// A `file://` URI for `path`, escaping what URIs reserve.
+fn path_uri(path: &Path) -> String {
+    let mut out = String::from("file://");
+    for b in path.to_string_lossy().bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' => {
+                out.push(b as char)
+            }
+            _ => out.push_str(&format!("%{b:02X}")),
+        }
+    }
+    out
+}
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::tree_sitter::py::Python;
+    use std::io::Cursor;
+    use tempfile::TempDir;
+
+    /// A server initialized on a temp workspace holding `files`.
+    fn server(files: &[(&str, &str)]) -> (TempDir, Server<Python>) {
+        let (dir, s, _) = server_with(files, json!({}));
+        (dir, s)
+    }
+
+    /// [`server`] for a client with `capabilities`, and the `initialize`
+    /// result.
+    fn server_with(
+        files: &[(&str, &str)],
+        capabilities: Value,
+    ) -> (TempDir, Server<Python>, Value) {
+        let dir = TempDir::new().unwrap();
+        for (path, text) in files {
+            let path = dir.path().join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, text).unwrap();
+        }
+        let mut s = Server::new(Python, "py");
+        let root = path_uri(&dir.path().canonicalize().unwrap());
+        let params = json!({"rootUri": root, "capabilities": capabilities});
+        let init = s.handle(&request(1, "initialize", params)).unwrap()["result"].clone();
+        assert_eq!(init["capabilities"]["callHierarchyProvider"], true);
+        (dir, s, init)
+    }
+
+    fn request(id: u64, method: &str, params: Value) -> Value {
+        json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params})
+    }
+
+    fn uri(dir: &TempDir, rel: &str) -> String {
+        path_uri(&dir.path().canonicalize().unwrap().join(rel))
+    }
+
+    fn result(s: &mut Server<Python>, method: &str, params: Value) -> Value {
+        s.handle(&request(7, method, params)).unwrap()["result"].clone()
+    }
+
+    fn names(v: &Value) -> Vec<&str> {
+        v.as_array()
+            .unwrap()
+            .iter()
+            .map(|s| s["name"].as_str().unwrap())
+            .collect()
+    }
+
+    // ── framing ──
+
+    #[test]
+    fn message_round_trip() {
+        let mut out = Vec::new();
+        write_message(&mut out, &json!({"a": 1})).unwrap();
+        assert_eq!(out, b"Content-Length: 7\r\n\r\n{\"a\":1}");
+        let mut input = Cursor::new(out);
+        assert_eq!(read_message(&mut input).unwrap().unwrap(), b"{\"a\":1}");
+        assert!(read_message(&mut input).unwrap().is_none());
+    }
+
+    #[test]
+    fn missing_length_errors() {
+        let mut input = Cursor::new(b"Content-Type: x\r\n\r\n{}".to_vec());
+        assert!(read_message(&mut input).is_err());
+    }
+
+    #[test]
+    fn serve_answers_until_exit() {
+        let mut input = Vec::new();
+        write_message(&mut input, &request(1, "shutdown", Value::Null)).unwrap();
+        write_message(&mut input, &json!({"jsonrpc": "2.0", "method": "exit"})).unwrap();
+        write_message(&mut input, &request(2, "shutdown", Value::Null)).unwrap();
+        let mut out = Vec::new();
+        let mut s = Server::new(Python, "py");
+        serve(&mut s, Cursor::new(input), &mut out).unwrap();
+        let mut out = Cursor::new(out);
+        let reply: Value =
+            serde_json::from_slice(&read_message(&mut out).unwrap().unwrap()).unwrap();
+        assert_eq!(reply["id"], 1);
+        assert!(read_message(&mut out).unwrap().is_none());
+    }
+
+    #[test]
+    fn unknown_request_errors() {
+        let mut s = Server::new(Python, "py");
+        let reply = s
+            .handle(&request(3, "textDocument/hover", json!({})))
+            .unwrap();
+        assert_eq!(reply["error"]["code"], METHOD_NOT_FOUND);
+        assert!(s.handle(&json!({"method": "$/cancelRequest"})).is_none());
+    }
+
+    // ── symbols ──
+
+    #[test]
+    fn workspace_symbols_filter_by_name() {
+        let (dir, mut s) = server(&[
+            ("a.py", "def load_config(): pass\n"),
+            ("pkg/b.py", "class Loader:\n    def run(self): pass\n"),
+        ]);
+        let found = result(&mut s, "workspace/symbol", json!({"query": "LOAD"}));
+        assert_eq!(names(&found), vec!["load_config", "Loader"]);
+        assert_eq!(found[1]["kind"], CLASS);
+        assert_eq!(found[1]["location"]["uri"], uri(&dir, "pkg/b.py"));
+        assert_eq!(found[1]["location"]["range"]["start"]["line"], 0);
+    }
+
+    #[test]
+    fn document_symbols_nest() {
+        let (dir, mut s) = server(&[("b.py", "class Loader:\n    def run(self): pass\n")]);
+        let doc = json!({"textDocument": {"uri": uri(&dir, "b.py")}});
+        let symbols = result(&mut s, "textDocument/documentSymbol", doc);
+        assert_eq!(names(&symbols), vec!["Loader"]);
+        let children = &symbols[0]["children"];
+        assert_eq!(names(children), vec!["run"]);
+        assert_eq!(children[0]["kind"], METHOD);
+    }
+
+    #[test]
+    fn edits_reparsed_before_save() {
+        let (dir, mut s) = server(&[("a.py", "def old(): pass\n")]);
+        let file = uri(&dir, "a.py");
+        s.handle(&json!({
+            "method": "textDocument/didChange",
+            "params": {
+                "textDocument": {"uri": file, "version": 2},
+                "contentChanges": [{"text": "def new(): pass\n"}],
+            },
+        }));
+        assert!(s.index.get().is_none());
+        let found = result(&mut s, "workspace/symbol", json!({"query": ""}));
+        assert_eq!(names(&found), vec!["new"]);
+        assert!(s.index.get().is_some());
+        result(&mut s, "workspace/symbol", json!({"query": "new"}));
+        assert!(s.index.get().is_some());
+
+        // Closing without saving goes back to the file on disk.
+        s.handle(
+            &json!({"method": "textDocument/didClose", "params": {"textDocument": {"uri": file}}}),
+        );
+        let found = result(&mut s, "workspace/symbol", json!({"query": ""}));
+        assert_eq!(names(&found), vec!["old"]);
+    }
+
+    // ── call hierarchy ──
+
+    #[test]
+    fn call_hierarchy_both_ways() {
+        let (dir, mut s) = server(&[
+            ("a.py", "def main():\n    helper()\n    helper()\n"),
+            ("b.py", "def helper():\n    pass\n"),
+        ]);
+        let position = json!({
+            "textDocument": {"uri": uri(&dir, "b.py")},
+            "position": {"line": 1, "character": 4},
+        });
+        let items = result(&mut s, "textDocument/prepareCallHierarchy", position);
+        assert_eq!(names(&items), vec!["helper"]);
+
+        let incoming = result(
+            &mut s,
+            "callHierarchy/incomingCalls",
+            json!({"item": items[0]}),
+        );
+        let incoming = incoming.as_array().unwrap();
+        assert_eq!(incoming.len(), 1);
+        assert_eq!(incoming[0]["from"]["name"], "main");
+        assert_eq!(incoming[0]["fromRanges"].as_array().unwrap().len(), 2);
+        let site = &incoming[0]["fromRanges"][0];
+        assert_eq!(site["start"], json!({"line": 1, "character": 4}));
+        assert_eq!(site["end"], json!({"line": 1, "character": 12}));
+
+        let main = incoming[0]["from"].clone();
+        let outgoing = result(&mut s, "callHierarchy/outgoingCalls", json!({"item": main}));
+        assert_eq!(outgoing[0]["to"]["name"], "helper");
+    }
+
+    #[test]
+    fn prepare_outside_definitions_is_null() {
+        let (dir, mut s) = server(&[("a.py", "import os\n\ndef f(): pass\n")]);
+        let position = json!({
+            "textDocument": {"uri": uri(&dir, "a.py")},
+            "position": {"line": 0, "character": 0},
+        });
+        let items = result(&mut s, "textDocument/prepareCallHierarchy", position);
+        assert!(items.is_null());
+    }
+
+    // ── uris ──
+
+    #[test]
+    fn uri_round_trip() {
+        let path = Path::new("/tmp/my project/a#1.py");
+        let uri = path_uri(path);
+        assert_eq!(uri, "file:///tmp/my%20project/a%231.py");
+        assert_eq!(uri_path(&uri).unwrap(), path);
+        assert!(uri_path("untitled:Untitled-1").is_none());
+    }
+
+    // ── position encoding ──
+
+    /// The encoding a client offering `capabilities` gets, and where the
+    /// call to `helper` after non-ASCII text starts.
+    fn call_site(capabilities: Value) -> (Value, Value) {
+        let (dir, mut s, init) = server_with(
+            &[(
+                "a.py",
+                "def main():\n    x = \"é😀\"; helper()\ndef helper():\n    pass\n",
+            )],
+            capabilities,
+        );
+        let position = json!({
+            "textDocument": {"uri": uri(&dir, "a.py")},
+            "position": {"line": 3, "character": 4},
+        });
+        let items = result(&mut s, "textDocument/prepareCallHierarchy", position);
+        let incoming = result(
+            &mut s,
+            "callHierarchy/incomingCalls",
+            json!({"item": items[0]}),
+        );
+        (
+            init["capabilities"]["positionEncoding"].clone(),
+            incoming[0]["fromRanges"][0]["start"].clone(),
+        )
+    }
+
+    #[test]
+    fn columns_in_utf16_by_default() {
+        let (encoding, start) = call_site(json!({}));
+        assert_eq!(encoding, "utf-16");
+        // `é` is one UTF-16 unit and `😀` two.
+        assert_eq!(start, json!({"line": 1, "character": 15}));
+    }
+
+    #[test]
+    fn columns_in_bytes_when_client_offers_utf8() {
+        let offer = json!({"general": {"positionEncodings": ["utf-8", "utf-16"]}});
+        let (encoding, start) = call_site(offer);
+        assert_eq!(encoding, "utf-8");
+        // `é` is two bytes and `😀` four.
+        assert_eq!(start, json!({"line": 1, "character": 18}));
+    }
+}
@@ -1,20 +1,69 @@
-use bolomoty::api::fs;
-use bolomoty::api::tree_sitter::Lang;
-use bolomoty::api::tree_sitter::py::Python;
-use bolomoty::api::tree_sitter::rs::Rust;
-use bolomoty::cli::{Args, Bolo, LangCmd};
+use bolomoty::analysis::{Analysis, Warning, WarningKind};
+use bolomoty::anonymize;
+use bolomoty::api::tree_sitter::go::Go;
+use bolomoty::api::tree_sitter::js::JavaScript;
+use bolomoty::api::tree_sitter::py::{self, Python, Signature};
+use bolomoty::api::tree_sitter::rs::{self, Rust};
+use bolomoty::api::tree_sitter::ts::TypeScript;
+use bolomoty::api::tree_sitter::{ASTNode, Globals, Lang, Options, Strings, Syntax};
+use bolomoty::api::{fs, git};
+use bolomoty::badge;
+use bolomoty::bench;
+use bolomoty::budget;
+use bolomoty::bundle::Bundle;
+use bolomoty::cache::Cache;
+use bolomoty::changelog;
+use bolomoty::cli::{
+    AllArgs, AnalysisFormat, Args, BadgeArgs, BenchArgs, Bolo, ChangelogArgs, DiffArgs, FailOn,
+    FormatArg, HookArgs, HookCmd, LangCmd, Language, LspArgs, MergeArgs, NeighborhoodArgs,
+    OpenArgs, PreviewRenameArgs, QueryArgs, QueryCmd, QueryFormat, RecordArgs, SemverArgs,
+    ServeArgs, StringsArg, StubsArgs, TrendArgs,
+};
+use bolomoty::config;
 use bolomoty::consolidate;
+use bolomoty::crates;
+use bolomoty::deps;
+use bolomoty::diff::Diff;
 use bolomoty::error::BoloError;
+use bolomoty::export::{self, Format, Layout, dot, summary};
+use bolomoty::graph::Graph;
+use bolomoty::hook;
+use bolomoty::labels::Labels;
+use bolomoty::links;
+use bolomoty::lsp;
+use bolomoty::merge;
+use bolomoty::pipeline::{Pipeline, Registry};
 use bolomoty::pretty;
+use bolomoty::profile::{self, Profile};
+use bolomoty::redact::{REDACTED, Redacted};
+use bolomoty::rename;
+use bolomoty::report::{
+    calls, clusters, coupling, errors, exceptions, features, globals, stats, stubs, unsafety, vocab,
+};
+use bolomoty::rules;
+use bolomoty::semver::{self, Delta};
+use bolomoty::serve;
+use bolomoty::survey::{self, Survey};
+use bolomoty::trend;
 
-use clap::Parser;
+use clap::{Parser, ValueEnum};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::io::{Read, Write};
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
 use std::process::ExitCode;
+use std::time::Instant;
 
 // ── Entry Point ─────────────────────────────────────────────────────
 
 fn main() -> ExitCode {
     match run() {
-        Ok(()) => ExitCode::SUCCESS,
+        Ok(None) => ExitCode::SUCCESS,
+        Ok(Some(class)) => {
+            let name = class.to_possible_value().map(|v| v.get_name().to_string());
+            pretty::error(&format!("--fail-on {}", name.unwrap_or_default()));
+            ExitCode::from(exit_code(class))
+        }
         Err(e) => {
             pretty::error(&e.to_string());
             ExitCode::FAILURE
@@ -22,52 +71,1407 @@ fn main() -> ExitCode {
     }
 }
 
+/// 0 success, 1 error, 2 usage error (from clap), then one code per
+/// `--fail-on` class so CI can tell them apart.
+fn exit_code(class: FailOn) -> u8 {
+    match class {
+        FailOn::ParseError => 3,
+        FailOn::Warning => 4,
+        FailOn::Cycle => 5,
+        FailOn::RuleViolation => 6,
+    }
+}
+
 // ── Orchestrator ────────────────────────────────────────────────────
 
-fn run() -> Result<(), BoloError> {
+/// Returns the `--fail-on` class that tripped, if any, after writing output.
+fn run() -> Result<Option<FailOn>, BoloError> {
     let cli = Bolo::parse();
+    pretty::set_color(cli.color);
+
+    let (language, args): (Language, &Args) = match &cli.lang {
+        LangCmd::Py(a) | LangCmd::Exceptions(a) => (Language::Py, a),
+        LangCmd::Rs(a) | LangCmd::Unsafe(a) | LangCmd::Errors(a) => (Language::Rs, a),
+        LangCmd::Js(a) => (Language::Js, a),
+        LangCmd::Ts(a) => (Language::Ts, a),
+        LangCmd::Go(a) => (Language::Go, a),
+        LangCmd::Panics(p) => (p.lang, &p.args),
+        LangCmd::Watch(w)
+        | LangCmd::Clusters(w)
+        | LangCmd::Coupling(w)
+        | LangCmd::Globals(w)
+        | LangCmd::Stats(w)
+        | LangCmd::Vocab(w) => (w.lang, &w.args),
+        LangCmd::All(a) => return run_all(a).map(|()| None),
+        LangCmd::Merge(m) => return run_merge(m).map(|()| None),
+        LangCmd::Diff(d) => return run_diff(d).map(|()| None),
+        LangCmd::Semver(v) => return run_semver(v).map(|()| None),
+        LangCmd::PreviewRename(r) => return run_preview_rename(r).map(|()| None),
+        LangCmd::Query(q) => return run_query(q).map(|()| None),
+        LangCmd::Stubs(s) => return run_stubs(s).map(|()| None),
+        LangCmd::Badge(b) => return run_badge(b).map(|()| None),
+        LangCmd::Record(r) => return run_record(r).map(|()| None),
+        LangCmd::Trend(t) => return run_trend(t).map(|()| None),
+        LangCmd::Changelog(c) => return run_changelog(c).map(|()| None),
+        LangCmd::Bench(b) => return run_bench(b).map(|()| None),
+        LangCmd::Lsp(l) => return run_lsp(l).map(|()| None),
+        LangCmd::Serve(s) => return run_serve(s).map(|()| None),
+        LangCmd::Open(o) => return run_open(o).map(|()| None),
+        LangCmd::Hook(h) => return run_hook(h),
+    };
+    let (lang, ext) = parser(language);
+
+    // A git URL is cloned to a temp dir that lives until the run ends.
+    let checkout = match args.path.to_str().filter(|p| git::is_remote(p)) {
+        Some(url) => Some(git::clone(url, args.rev.as_deref())?),
+        None => None,
+    };
+    let path = checkout.as_ref().map_or(args.path.as_path(), |d| d.path());
 
-    let (lang, ext, args): (Box<dyn Lang + Sync>, &str, &Args) = match &cli.lang {
-        LangCmd::Py(a) => (Box::new(Python), "py", a),
-        LangCmd::Rs(a) => (Box::new(Rust), "rs", a),
+    fs::validate_path(path)?;
+    let dir = if path.is_file() {
+        path.parent().unwrap_or(Path::new("."))
+    } else {
+        path
     };
 
-    fs::validate_path(&args.path)?;
+    if args.cfg_matrix && !matches!(cli.lang, LangCmd::Rs(_)) {
+        return Err(BoloError::Unsupported {
+            flag: "cfg-matrix",
+            context: "outside `bolo rs`",
+        });
+    }
+    if args.routes && language != Language::Py {
+        return Err(BoloError::Unsupported {
+            flag: "routes",
+            context: "for languages other than py",
+        });
+    }
+    let is_report = args.list_files
+        || args.cfg_matrix
+        || !matches!(
+            cli.lang,
+            LangCmd::Py(_) | LangCmd::Rs(_) | LangCmd::Js(_) | LangCmd::Ts(_) | LangCmd::Go(_)
+        );
+    let ndjson = args.format == Some(AnalysisFormat::Ndjson);
+    check_outputs(&args.output, args.force, is_report || ndjson)?;
+    if args.anonymize && is_report {
+        return Err(BoloError::Unsupported {
+            flag: "anonymize",
+            context: "by reports or --list-files",
+        });
+    }
+    if args.bundle.is_some() && is_report {
+        return Err(BoloError::Unsupported {
+            flag: "bundle",
+            context: "by reports or --list-files",
+        });
+    }
+    if let Some(bundle) = &args.bundle {
+        check_outputs(std::slice::from_ref(bundle), args.force, false)?;
+    }
+    let replaces_json = match args.format {
+        Some(AnalysisFormat::Cytoscape) => Some("format cytoscape"),
+        Some(AnalysisFormat::Graph) => Some("format graph"),
+        Some(AnalysisFormat::Dot) => Some("format dot"),
+        Some(AnalysisFormat::Ndjson) => Some("format ndjson"),
+        _ => None,
+    };
+    if let Some(flag) = replaces_json.filter(|_| is_report) {
+        return Err(BoloError::Unsupported {
+            flag,
+            context: "by reports or --list-files",
+        });
+    }
+    // These need every file at once, which streaming never holds.
+    let whole_tree = [
+        (args.bundle.is_some(), "bundle"),
+        (args.max_output_bytes.is_some(), "max-output-bytes"),
+        (args.profile.is_some(), "profile"),
+        (args.fail_on.contains(&FailOn::Cycle), "fail-on cycle"),
+        (
+            args.fail_on.contains(&FailOn::RuleViolation),
+            "fail-on rule-violation",
+        ),
+    ];
+    if let Some((_, flag)) = whole_tree.into_iter().find(|&(set, _)| set && ndjson) {
+        return Err(BoloError::Unsupported {
+            flag,
+            context: "with --format ndjson",
+        });
+    }
+    // Both read their files from disk again, which a buffer never was.
+    if args.stdin && (ndjson || matches!(cli.lang, LangCmd::Globals(_))) {
+        return Err(BoloError::Unsupported {
+            flag: "stdin",
+            context: "with --format ndjson or by `bolo globals`",
+        });
+    }
+
+    let config = config::resolve(args.config.as_deref(), path)?;
+    let root_label = args.root_label.clone().or(config.root_label.clone());
+
+    let deps = match args.include_deps {
+        true => {
+            let (roots, missing) = deps::roots(path, ext, |k| std::env::var(k).ok())?;
+            if roots.is_empty() {
+                pretty::warn("--include-deps: no installed packages found");
+            }
+            if missing > 0 {
+                pretty::warn(&format!(
+                    "--include-deps: {missing} Cargo.lock package(s) not downloaded (run `cargo fetch`)"
+                ));
+            }
+            deps::walk(&roots, ext, args.jobs.walk_threads())?
+        }
+        false => Vec::new(),
+    };
+    let external: HashSet<String> = deps
+        .iter()
+        .map(|f| fs::portable(&f.rel_path, root_label.as_deref()))
+        .collect();
+
+    // An explicit list replaces the directory walk; stdin replaces both,
+    // but passes still resolve the buffer against the walked tree.
+    let walked: Vec<_> = match &args.files_from {
+        Some(list) => fs::from_list(path, read_list(list, args.null)?, ext)?,
+        None => fs::walk_dir_threads(
+            path,
+            ext,
+            args.no_ignore,
+            match args.no_default_excludes {
+                true => &[],
+                false => fs::default_excludes(ext),
+            },
+            args.jobs.walk_threads(),
+            args.shallow.then_some(1),
+        )?
+        .into_iter()
+        .chain(deps)
+        .collect(),
+    };
+    let known: HashSet<String> = walked
+        .iter()
+        .map(|f| fs::portable(&f.rel_path, root_label.as_deref()))
+        .collect();
+    let files: Vec<_> = match args.stdin {
+        true => Vec::new(),
+        false => walked
+            .into_iter()
+            .filter(|f| args.shard.is_none_or(|s| s.contains(f)))
+            .collect(),
+    };
+
+    let total = files.len();
+    let keep = [
+        args.sample.map(|s| s.count(total)),
+        args.max_files.map(NonZeroUsize::get),
+    ]
+    .into_iter()
+    .flatten()
+    .min()
+    .filter(|&keep| keep < total);
+    let files = match keep {
+        Some(keep) => {
+            pretty::neutral(&format!(
+                "sampled {keep} of {total} files (seed {})",
+                args.seed
+            ));
+            fs::sample(files, keep, args.seed)
+        }
+        None => files,
+    };
 
     if args.dry_run {
-        let files = fs::walk_dir(&args.path, ext, args.no_ignore)?;
-        pretty::neutral(&format!("{} .{ext} files found", files.len()));
-        return Ok(());
+        let threads = args.jobs.parse_threads(&files);
+        pretty::neutral(&Survey::new(&files).render(ext, threads));
+        return Ok(None);
+    }
+
+    if args.list_files {
+        let entries = survey::entries(&files, language_name(language), root_label.as_deref());
+        emit(
+            &args.output,
+            files.len(),
+            &Analysis::default(),
+            Some(serde_json::to_value(&entries)?),
+            &Style {
+                layout: layout(args),
+                ..Default::default()
+            },
+        )?;
+        return Ok(None);
+    }
+
+    if args.cfg_matrix {
+        let gated = files
+            .iter()
+            .map(|f| {
+                let (source, _) = f.read_lossy()?;
+                let tree =
+                    lang.get_parser()
+                        .parse(&source, None)
+                        .ok_or_else(|| BoloError::Parse {
+                            file: fs::portable(&f.rel_path, None),
+                            reason: "parser returned no tree".into(),
+                        })?;
+                let path = fs::portable(&f.rel_path, root_label.as_deref());
+                Ok((path, rs::gated(&tree, &source)))
+            })
+            .collect::<Result<Vec<_>, BoloError>>()?;
+        let known: HashSet<&str> = gated.iter().map(|(p, _)| p.as_str()).collect();
+        let found = crates::discover(dir, root_label.as_deref(), &known);
+        let matrix = features::collect(&gated, &found);
+        pretty::neutral(&format!(
+            "{} combination(s) across {} crate(s), {} definition(s) and call(s) behind features",
+            matrix.combinations.len(),
+            found.len(),
+            matrix.gated.len()
+        ));
+        emit(
+            &args.output,
+            files.len(),
+            &Analysis::default(),
+            Some(serde_json::to_value(&matrix)?),
+            &Style {
+                layout: layout(args),
+                ..Default::default()
+            },
+        )?;
+        return Ok(None);
     }
 
     rayon::ThreadPoolBuilder::new()
-        .num_threads(args.jobs)
+        .num_threads(args.jobs.parse_threads(&files))
         .build_global()
         .ok();
 
-    let result = if args.shallow {
-        consolidate::folder(&args.path, ext, args.no_ignore, &*lang)?
+    let opts = Options {
+        routes: args.routes,
+        strings: args.strings.map(|s| match s {
+            StringsArg::All => Strings::All,
+            StringsArg::Interesting => Strings::Interesting,
+        }),
+        comments: args.comments.or(config.comments).unwrap_or_default(),
+        passes: passes(args.passes.as_ref().or(config.passes.as_ref()))?,
+        root_label,
+        shard: args.shard,
+        known: Some(known),
+        cache: match &args.cache {
+            Some(Some(dir)) => Some(dir.clone()),
+            Some(None) => Some(Cache::default_dir().ok_or(BoloError::Unsupported {
+                flag: "cache",
+                context: "without $HOME or $XDG_CACHE_HOME; pass --cache=DIR",
+            })?),
+            None => None,
+        },
+    };
+
+    if ndjson {
+        return run_ndjson(args, &files, &*lang, &opts, dir, &external, &config);
+    }
+
+    let mut analysis = match &args.filename {
+        Some(name) if args.stdin => {
+            let mut source = Vec::new();
+            std::io::stdin()
+                .read_to_end(&mut source)
+                .map_err(|e| BoloError::Read {
+                    path: "-".into(),
+                    reason: e.to_string(),
+                })?;
+            consolidate::buffer(name, source, &*lang, &opts)?
+        }
+        _ => consolidate::list(&files, &*lang, &opts)?,
+    };
+    for w in &analysis.warnings {
+        warn(w);
+    }
+
+    deps::tag(&mut analysis.files, &external);
+    let submodules: Vec<String> = deps::submodules(dir)
+        .iter()
+        .map(|m| fs::portable(Path::new(m), opts.root_label.as_deref()))
+        .collect();
+    let vendored = deps::tag_vendored(&mut analysis.files, &submodules);
+    if vendored > 0 {
+        pretty::neutral(&format!(
+            "{vendored} vendored file{} kept out of architecture metrics",
+            if vendored == 1 { "" } else { "s" }
+        ));
+    }
+    if language == Language::Rs {
+        let known: HashSet<&str> = analysis
+            .files
+            .iter()
+            .filter_map(|n| match n.first().map(|s| &s.node) {
+                Some(ASTNode::File(f)) => Some(f.path.as_ref()),
+                _ => None,
+            })
+            .collect();
+        let found = crates::discover(dir, opts.root_label.as_deref(), &known);
+        analysis.crates = crates::link(&mut analysis.files, &found);
+    }
+    calls::tag(&mut analysis.files, &config.watchlists);
+    if let Some(path) = &args.profile {
+        let coverage = profile::attach(&mut analysis.files, &Profile::load(path)?);
+        pretty::neutral(&format!(
+            "profile: matched {} of {} samples to functions",
+            coverage.matched, coverage.total
+        ));
+    }
+    if let Some(template) = args
+        .link_template
+        .as_ref()
+        .or(config.link_template.as_ref())
+    {
+        // Outside a repository `{path}` still works, relative to PATH.
+        let (commit, prefix) = match git::head(dir) {
+            Ok(head) => head,
+            Err(e) if template.needs_commit() => return Err(e),
+            Err(_) => Default::default(),
+        };
+        let source = links::Source {
+            commit: &commit,
+            prefix: &prefix,
+            root_label: opts.root_label.as_deref(),
+        };
+        links::attach(&mut analysis.files, template, &source);
+    }
+    // Written output is redacted and anonymized on a copy; reports, rules,
+    // cycles and --fail-on below still see everything, and reports have
+    // the redacted paths and names masked instead.
+    let mut exported = None;
+    if !config.redact.is_empty() {
+        let copy = exported.get_or_insert_with(|| analysis.clone());
+        let redacted = config.redact.apply(copy);
+        pretty::neutral(&format!(
+            "redacted {} file{} and {} node{} from the output",
+            redacted.files,
+            if redacted.files == 1 { "" } else { "s" },
+            redacted.nodes,
+            if redacted.nodes == 1 { "" } else { "s" }
+        ));
+    }
+    if args.anonymize {
+        let key = std::env::var(anonymize::KEY_VAR).unwrap_or_default();
+        anonymize::anonymize(exported.get_or_insert_with(|| analysis.clone()), &key);
+    }
+    let exported = exported.as_ref().unwrap_or(&analysis);
+    // Reports never run with --anonymize (see above).
+    let result = &analysis.files;
+
+    let report = match &cli.lang {
+        LangCmd::Unsafe(_) => Some(serde_json::to_value(unsafety::collect(result))?),
+        LangCmd::Exceptions(_) => Some(serde_json::to_value(exceptions::collect(result))?),
+        LangCmd::Errors(_) => Some(serde_json::to_value(errors::collect(result))?),
+        LangCmd::Panics(p) => {
+            let calls = if p.calls.is_empty() {
+                calls::PANIC_CALLS.iter().map(|c| c.to_string()).collect()
+            } else {
+                p.calls.clone()
+            };
+            Some(serde_json::to_value(calls::collect(result, &calls))?)
+        }
+        LangCmd::Watch(_) => Some(serde_json::to_value(calls::watchlists(
+            result,
+            &config.watchlists,
+        ))?),
+        LangCmd::Clusters(_) => Some(serde_json::to_value(clusters::collect(
+            &deps::first_party(result),
+        ))?),
+        LangCmd::Coupling(_) => Some(serde_json::to_value(coupling::collect(
+            &deps::first_party(result),
+        ))?),
+        LangCmd::Globals(_) => {
+            let parsed = global_state(&files, language, &*lang, &opts, result)?;
+            Some(serde_json::to_value(globals::collect(&parsed))?)
+        }
+        LangCmd::Stats(_) => Some(serde_json::to_value(stats::collect(result))?),
+        LangCmd::Vocab(_) => Some(serde_json::to_value(vocab::collect(result))?),
+        LangCmd::Py(_)
+        | LangCmd::Rs(_)
+        | LangCmd::Js(_)
+        | LangCmd::Ts(_)
+        | LangCmd::Go(_)
+        | LangCmd::All(_)
+        | LangCmd::Merge(_)
+        | LangCmd::Diff(_)
+        | LangCmd::Semver(_)
+        | LangCmd::PreviewRename(_)
+        | LangCmd::Query(_)
+        | LangCmd::Stubs(_)
+        | LangCmd::Badge(_)
+        | LangCmd::Record(_)
+        | LangCmd::Trend(_)
+        | LangCmd::Changelog(_)
+        | LangCmd::Bench(_)
+        | LangCmd::Lsp(_)
+        | LangCmd::Serve(_)
+        | LangCmd::Open(_)
+        | LangCmd::Hook(_) => None,
+    };
+    let report = report.map(|mut r| {
+        config.redact.hidden(&analysis).mask(&mut r);
+        r
+    });
+    // A summary printed to stdout takes the place of the JSON there.
+    let gh_summary = args.format == Some(AnalysisFormat::GhSummary);
+    let summary_to_stdout = gh_summary && step_summary().is_none();
+    if !(summary_to_stdout && args.output.is_empty()) {
+        emit(
+            &args.output,
+            exported.files.len(),
+            exported,
+            report,
+            &Style {
+                plain: match args.format {
+                    Some(AnalysisFormat::Cytoscape) => Format::Cytoscape,
+                    Some(AnalysisFormat::Graph) => Format::Graph,
+                    Some(AnalysisFormat::Dot) => Format::Dot,
+                    _ => Format::Json,
+                },
+                max_bytes: args.max_output_bytes,
+                layout: layout(args),
+                labels: config.labels.clone(),
+            },
+        )?;
+    }
+
+    if let Some(path) = &args.bundle {
+        Bundle::new(exported, &config.labels).write(path)?;
+        pretty::success(&format!(
+            "bundled {} files \u{2192} {}",
+            exported.files.len(),
+            path.display()
+        ));
+    }
+
+    let violations = rules::check(&analysis.files, &config.rules);
+    for v in &violations {
+        pretty::warn(&format!("{}:{}: {}", v.file, v.span.start_line, v.message));
+    }
+    let fail_on_cycle = args.fail_on.contains(&FailOn::Cycle);
+    let own = deps::first_party(&analysis.files);
+    let (cycles, cuts) = if fail_on_cycle || gh_summary {
+        let graph = Graph::build(&own);
+        (graph.file_cycles(), graph.cycle_cuts())
     } else {
-        consolidate::recursive(&args.path, ext, args.no_ignore, &*lang)?
+        (Vec::new(), Vec::new())
     };
+    if fail_on_cycle {
+        for c in &cycles {
+            pretty::warn(&format!("cycle: {}", c.join(" \u{2192} ")));
+        }
+        for cut in &cuts {
+            pretty::warn(&format!(
+                "to break cycles, remove {} \u{2192} {} ({} call{})",
+                cut.from,
+                cut.to,
+                cut.weight,
+                if cut.weight == 1 { "" } else { "s" }
+            ));
+        }
+    }
+    if gh_summary {
+        let redact = &config.redact;
+        let cycles: Vec<Vec<&str>> = cycles
+            .iter()
+            .map(|c| {
+                c.iter()
+                    .map(|f| if redact.hides_path(f) { REDACTED } else { f })
+                    .collect()
+            })
+            .collect();
+        let violations: Vec<_> = violations
+            .iter()
+            .map(|v| redact.violation(v.clone()))
+            .collect();
+        publish(&summary::render(&analysis, &cycles, &violations), None)?;
+    }
+
+    Ok(args
+        .fail_on
+        .iter()
+        .copied()
+        .filter(|class| match class {
+            FailOn::ParseError => analysis
+                .warnings
+                .iter()
+                .any(|w| w.kind == WarningKind::GrammarError),
+            FailOn::Warning => !analysis.warnings.is_empty(),
+            FailOn::Cycle => !cycles.is_empty(),
+            FailOn::RuleViolation => !violations.is_empty(),
+        })
+        .min())
+}
+
+fn warn(w: &Warning) {
+    match w.span {
+        Some(span) => pretty::warn(&format!("{}:{}: {}", w.file, span.start_line, w.message)),
+        None => pretty::warn(&format!("{}: {}", w.file, w.message)),
+    }
+}
+
+/// `--format ndjson`: write each file as a one-file analysis on its own
+/// line as soon as it is parsed, tagged, linked, redacted and anonymized
+/// like the whole analysis would be. Crate edges, rules and cycles need
+/// every file at once and are left out.
+fn run_ndjson(
+    args: &Args,
+    files: &[fs::File],
+    lang: &(dyn Lang + Sync),
+    opts: &Options,
+    dir: &Path,
+    external: &HashSet<String>,
+    config: &config::Config,
+) -> Result<Option<FailOn>, BoloError> {
+    let submodules: Vec<String> = deps::submodules(dir)
+        .iter()
+        .map(|m| fs::portable(Path::new(m), opts.root_label.as_deref()))
+        .collect();
+    let template = args
+        .link_template
+        .as_ref()
+        .or(config.link_template.as_ref());
+    let head = match template {
+        Some(template) => match git::head(dir) {
+            Ok(head) => head,
+            Err(e) if template.needs_commit() => return Err(e),
+            Err(_) => Default::default(),
+        },
+        None => Default::default(),
+    };
+    let source = links::Source {
+        commit: &head.0,
+        prefix: &head.1,
+        root_label: opts.root_label.as_deref(),
+    };
+    let key = std::env::var(anonymize::KEY_VAR).unwrap_or_default();
 
-    let json = serde_json::to_string_pretty(&result)?;
+    fn write_err(path: &Path) -> impl Fn(std::io::Error) -> BoloError + '_ {
+        move |e| BoloError::Write {
+            path: path.to_path_buf(),
+            reason: e.to_string(),
+        }
+    }
+    let mut sinks = Vec::new();
+    for out in &args.output {
+        let parent = out.parent().unwrap_or(Path::new("."));
+        fs::ensure_dir(parent).map_err(write_err(out))?;
+        let tmp = tempfile::NamedTempFile::new_in(parent).map_err(write_err(out))?;
+        sinks.push((out.as_path(), std::io::BufWriter::new(tmp), 0));
+    }
+    let mut stdout = std::io::BufWriter::new(std::io::stdout());
 
-    match &args.output {
+    let (mut written, mut vendored, mut redacted) = (0, 0, Redacted::default());
+    let (mut warnings, mut grammar_errors) = (0, false);
+    consolidate::stream(files, lang, opts, |nodes, file_warnings| {
+        for w in &file_warnings {
+            warn(w);
+        }
+        warnings += file_warnings.len();
+        grammar_errors |= file_warnings
+            .iter()
+            .any(|w| w.kind == WarningKind::GrammarError);
+        let mut one = Analysis {
+            files: nodes.into_iter().collect(),
+            warnings: file_warnings,
+            ..Default::default()
+        };
+        written += one.files.len();
+        deps::tag(&mut one.files, external);
+        vendored += deps::tag_vendored(&mut one.files, &submodules);
+        calls::tag(&mut one.files, &config.watchlists);
+        if let Some(template) = template {
+            links::attach(&mut one.files, template, &source);
+        }
+        if !config.redact.is_empty() {
+            let r = config.redact.apply(&mut one);
+            redacted.files += r.files;
+            redacted.nodes += r.nodes;
+        }
+        if args.anonymize {
+            anonymize::anonymize(&mut one, &key);
+        }
+        let line = export::json(&one, Layout::Compact)? + "\n";
+        if sinks.is_empty() {
+            stdout
+                .write_all(line.as_bytes())
+                .map_err(write_err(Path::new("-")))?;
+        }
+        for (out, sink, bytes) in &mut sinks {
+            sink.write_all(line.as_bytes()).map_err(write_err(out))?;
+            *bytes += line.len();
+        }
+        Ok(())
+    })?;
+    stdout.flush().map_err(write_err(Path::new("-")))?;
+
+    if vendored > 0 {
+        pretty::neutral(&format!(
+            "{vendored} vendored file{} kept out of architecture metrics",
+            if vendored == 1 { "" } else { "s" }
+        ));
+    }
+    if !config.redact.is_empty() {
+        pretty::neutral(&format!(
+            "redacted {} file{} and {} node{} from the output",
+            redacted.files,
+            if redacted.files == 1 { "" } else { "s" },
+            redacted.nodes,
+            if redacted.nodes == 1 { "" } else { "s" }
+        ));
+    }
+    for (out, sink, bytes) in sinks {
+        let tmp = sink
+            .into_inner()
+            .map_err(|e| write_err(out)(e.into_error()))?;
+        tmp.persist(out).map_err(|e| write_err(out)(e.error))?;
+        pretty::success(&format!(
+            "{written} files \u{2192} {} ({bytes} bytes)",
+            out.display()
+        ));
+    }
+
+    Ok(args
+        .fail_on
+        .iter()
+        .copied()
+        .filter(|class| match class {
+            FailOn::ParseError => grammar_errors,
+            FailOn::Warning => warnings > 0,
+            FailOn::Cycle | FailOn::RuleViolation => false,
+        })
+        .min())
+}
+
+/// Walk PATH once for every language in `--lang`, analyze each language's
+/// files with its parser and emit one JSON object keyed by language.
+fn run_all(a: &AllArgs) -> Result<(), BoloError> {
+    check_outputs(&a.output, a.force, true)?;
+    fs::validate_path(&a.path)?;
+    let dir = if a.path.is_file() {
+        a.path.parent().unwrap_or(Path::new("."))
+    } else {
+        a.path.as_path()
+    };
+    let mut languages = match a.lang.is_empty() {
+        true => Language::value_variants().to_vec(),
+        false => a.lang.clone(),
+    };
+    languages.dedup();
+    let parsers: Vec<_> = languages.into_iter().map(parser).collect();
+    let exts: Vec<&str> = parsers.iter().map(|&(_, ext)| ext).collect();
+    let excludes: Vec<&'static str> = match a.no_default_excludes {
+        true => vec![],
+        false => exts
+            .iter()
+            .flat_map(|ext| fs::default_excludes(ext))
+            .copied()
+            .collect(),
+    };
+    let files = fs::walk_dir_exts(
+        &a.path,
+        &exts,
+        a.no_ignore,
+        &excludes,
+        a.jobs.walk_threads(),
+        a.shallow.then_some(1),
+    )?;
+
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(a.jobs.parse_threads(&files))
+        .build_global()
+        .ok();
+
+    let config = config::resolve(a.config.as_deref(), &a.path)?;
+    let opts = Options {
+        comments: a.comments.or(config.comments).unwrap_or_default(),
+        passes: passes(a.passes.as_ref().or(config.passes.as_ref()))?,
+        root_label: a.root_label.clone().or(config.root_label.clone()),
+        ..Default::default()
+    };
+
+    let mut out = BTreeMap::new();
+    for (lang, ext) in &parsers {
+        let own: Vec<_> = files
+            .iter()
+            .filter(|f| {
+                f.rel_path
+                    .extension()
+                    .is_some_and(|e| e.eq_ignore_ascii_case(ext))
+            })
+            .cloned()
+            .collect();
+        if own.is_empty() {
+            continue;
+        }
+        let mut analysis = consolidate::list(&own, &**lang, &opts)?;
+        for w in &analysis.warnings {
+            pretty::warn(&format!("{}: {}", w.file, w.message));
+        }
+        if *ext == "rs" {
+            let known: HashSet<&str> = analysis
+                .files
+                .iter()
+                .filter_map(|n| match n.first().map(|s| &s.node) {
+                    Some(ASTNode::File(f)) => Some(f.path.as_ref()),
+                    _ => None,
+                })
+                .collect();
+            let found = crates::discover(dir, opts.root_label.as_deref(), &known);
+            analysis.crates = crates::link(&mut analysis.files, &found);
+        }
+        calls::tag(&mut analysis.files, &config.watchlists);
+        for v in rules::check(&analysis.files, &config.rules) {
+            pretty::warn(&format!("{}:{}: {}", v.file, v.span.start_line, v.message));
+        }
+        config.redact.apply(&mut analysis);
+        out.insert(*ext, analysis);
+    }
+
+    let total = out.values().map(|a| a.files.len()).sum();
+    emit(
+        &a.output,
+        total,
+        &Analysis::default(),
+        Some(serde_json::to_value(&out)?),
+        &Style {
+            layout: match (a.compact, a.pretty) {
+                (true, _) => Some(Layout::Compact),
+                (_, true) => Some(Layout::Pretty),
+                _ => None,
+            },
+            ..Default::default()
+        },
+    )
+}
+
+fn run_merge(m: &MergeArgs) -> Result<(), BoloError> {
+    check_outputs(&m.output, m.force, false)?;
+    let inputs = m
+        .inputs
+        .iter()
+        .map(|p| merge::load(p))
+        .collect::<Result<Vec<_>, _>>()?;
+    let (analysis, conflicts) = merge::merge(inputs);
+    for c in &conflicts {
+        pretty::warn(&format!("{}: {}", c.path, c.reason));
+    }
+    emit(
+        &m.output,
+        analysis.files.len(),
+        &analysis,
+        None,
+        &Style::default(),
+    )
+}
+
+fn run_diff(d: &DiffArgs) -> Result<(), BoloError> {
+    if let Some(out) = &d.output {
+        check_outputs(std::slice::from_ref(out), d.force, false)?;
+    }
+    let (old, new) = (merge::load(&d.old)?, merge::load(&d.new)?);
+    let diff = Diff::new(&old.files, &new.files);
+    let format = match (d.format, &d.output) {
+        (Some(FormatArg::GhSummary), out) => {
+            return publish(&summary::render_diff(&diff), out.as_deref());
+        }
+        (Some(FormatArg::Json), _) | (None, None) => Format::Json,
+        (Some(FormatArg::Dot), _) => Format::Dot,
+        (Some(FormatArg::Html), _) => Format::Html,
+        (None, Some(out)) => Format::from_path(out),
+    };
+    let layout = Layout::pick(None, d.output.is_some());
+    let content = export::render_diff(format, &diff, layout)?;
+    match &d.output {
         Some(out) => {
-            if out.exists() && !args.force {
-                return Err(BoloError::Exists { path: out.clone() });
-            }
-            fs::write_file(out, &json, true)?;
+            fs::write_file(out, &content, true)?;
+            let changes = diff.changes();
             pretty::success(&format!(
-                "{} files \u{2192} {} ({} bytes)",
-                result.len(),
-                out.display(),
-                json.len()
+                "{} definitions, {} edges changed \u{2192} {}",
+                changes.defs.len(),
+                changes.edges.len(),
+                out.display()
             ));
         }
+        None => println!("{content}"),
+    }
+    Ok(())
+}
+
+fn run_semver(v: &SemverArgs) -> Result<(), BoloError> {
+    if let Some(out) = &v.output {
+        check_outputs(std::slice::from_ref(out), v.force, true)?;
+    }
+    let (old, new) = (merge::load(&v.old)?, merge::load(&v.new)?);
+    let report = semver::compare(&old.files, &new.files);
+    let json = export::json(&report, Layout::pick(None, v.output.is_some()))?;
+    match &v.output {
+        Some(out) => fs::write_file(out, &json, true)?,
+        None => println!("{json}"),
+    }
+    let count = |delta| report.changes.iter().filter(|c| c.delta == delta).count();
+    pretty::neutral(&format!(
+        "suggested bump: {} ({} removed, {} changed, {} added)",
+        report.bump,
+        count(Delta::Removed),
+        count(Delta::Changed),
+        count(Delta::Added)
+    ));
+    Ok(())
+}
+
+fn run_preview_rename(r: &PreviewRenameArgs) -> Result<(), BoloError> {
+    if let Some(out) = &r.output {
+        check_outputs(std::slice::from_ref(out), r.force, true)?;
+    }
+    let analysis = merge::load(&r.input)?;
+    let preview =
+        rename::preview(&analysis.files, &r.old, &r.new).map_err(|reason| BoloError::Rename {
+            old: r.old.clone(),
+            reason,
+        })?;
+    let json = export::json(&preview, Layout::pick(None, r.output.is_some()))?;
+    match &r.output {
+        Some(out) => fs::write_file(out, &json, true)?,
+        None => println!("{json}"),
+    }
+    let files: BTreeSet<&str> = preview.sites.iter().map(|s| s.file.as_str()).collect();
+    pretty::neutral(&format!(
+        "{} call site(s) in {} file(s) to update",
+        preview.sites.len(),
+        files.len()
+    ));
+    if !preview.clashes.is_empty() {
+        pretty::warn(&format!(
+            "`{}` is already defined at {}; calls may bind to either after the rename",
+            preview.to,
+            preview.clashes.join(", ")
+        ));
+    }
+    Ok(())
+}
+
+fn run_query(q: &QueryArgs) -> Result<(), BoloError> {
+    match &q.cmd {
+        QueryCmd::Neighborhood(n) => run_neighborhood(n),
+    }
+}
+
+/// Write the part of the call graph within `--radius` edges of a definition.
+fn run_neighborhood(n: &NeighborhoodArgs) -> Result<(), BoloError> {
+    if let Some(out) = &n.output {
+        check_outputs(std::slice::from_ref(out), n.force, false)?;
+    }
+    let analysis = merge::load(&n.input)?;
+    let graph = Graph::build(&analysis.files);
+    let center = graph.find(&n.symbol).map_err(|reason| BoloError::Query {
+        symbol: n.symbol.clone(),
+        reason,
+    })?;
+    let within = graph.neighborhood(center, n.radius);
+    let keep = |i: usize| within[i];
+    let content = match n.format {
+        QueryFormat::Graph => export::json(
+            &export::graph::node_link(&graph, &keep),
+            Layout::pick(None, n.output.is_some()),
+        )?,
+        QueryFormat::Dot => dot::render_graph(&graph, &keep, &Labels::default()),
+    };
+    match &n.output {
+        Some(out) => fs::write_file(out, &content, true)?,
+        None => println!("{content}"),
+    }
+    pretty::neutral(&format!(
+        "{} definition(s) within {} of {}",
+        within.iter().filter(|&&k| k).count(),
+        n.radius,
+        graph.defs[center].id
+    ));
+    Ok(())
+}
+
+/// Pair each `.pyi` under the path with the `.py` beside it and report
+/// where their signatures drift apart.
+fn run_stubs(s: &StubsArgs) -> Result<(), BoloError> {
+    if let Some(out) = &s.output {
+        check_outputs(std::slice::from_ref(out), s.force, true)?;
+    }
+    fs::validate_path(&s.path)?;
+    let signatures = |file: &fs::File| -> Result<Vec<Signature>, BoloError> {
+        let (source, _) = file.read_lossy()?;
+        let tree = Python
+            .get_parser()
+            .parse(&source, None)
+            .ok_or_else(|| BoloError::Parse {
+                file: fs::portable(&file.rel_path, None),
+                reason: "parser returned no tree".into(),
+            })?;
+        Ok(py::signatures(&tree, &source))
+    };
+    let mut pairs = 0;
+    let mut drift = Vec::new();
+    for stub in fs::walk_dir(&s.path, "pyi", false)? {
+        let implementation = fs::File {
+            path: stub.path.with_extension("py"),
+            rel_path: stub.rel_path.with_extension("py"),
+        };
+        if !implementation.path.is_file() {
+            continue;
+        }
+        pairs += 1;
+        drift.extend(stubs::compare(
+            &fs::portable(&stub.rel_path, None),
+            &signatures(&stub)?,
+            &fs::portable(&implementation.rel_path, None),
+            &signatures(&implementation)?,
+        ));
+    }
+    let json = export::json(&drift, Layout::pick(None, s.output.is_some()))?;
+    match &s.output {
+        Some(out) => fs::write_file(out, &json, true)?,
         None => println!("{json}"),
     }
+    for d in &drift {
+        pretty::warn(&format!(
+            "{}:{}: {}",
+            d.file(),
+            d.span.start_line,
+            d.message()
+        ));
+    }
+    pretty::neutral(&format!(
+        "{pairs} stub(s) checked, {} mismatch(es)",
+        drift.len()
+    ));
+    Ok(())
+}
+
+fn run_badge(b: &BadgeArgs) -> Result<(), BoloError> {
+    if let Some(out) = &b.output {
+        check_outputs(std::slice::from_ref(out), b.force, true)?;
+    }
+    let analysis = merge::load(&b.input)?;
+    let badge = badge::badge(&analysis.files, b.metric);
+    let json = export::json(&badge, Layout::pick(None, b.output.is_some()))?;
+    match &b.output {
+        Some(out) => fs::write_file(out, &json, true)?,
+        None => println!("{json}"),
+    }
+    Ok(())
+}
+
+fn run_record(r: &RecordArgs) -> Result<(), BoloError> {
+    let analysis = merge::load(&r.input)?;
+    let commit = match &r.commit {
+        Some(c) => c.clone(),
+        None => git::head(Path::new("."))?.0,
+    };
+    let point = trend::Point::new(&commit, &analysis);
+    trend::record(&r.db, point)?;
+    pretty::success(&format!("recorded {commit} \u{2192} {}", r.db.display()));
+    Ok(())
+}
+
+fn run_trend(t: &TrendArgs) -> Result<(), BoloError> {
+    let metrics: Vec<&str> = t.metric.iter().map(String::as_str).collect();
+    print!("{}", trend::trend(&trend::load(&t.db)?, &metrics));
+    Ok(())
+}
+
+fn run_changelog(c: &ChangelogArgs) -> Result<(), BoloError> {
+    if let Some(out) = &c.output {
+        check_outputs(std::slice::from_ref(out), c.force, false)?;
+    }
+    fs::validate_path(&c.path)?;
+    let (lang, ext) = parser(c.lang);
+    let analyze = |rev: &str| -> Result<Analysis, BoloError> {
+        let (_checkout, root) = git::checkout(&c.path, rev)?;
+        consolidate::recursive(&root, ext, false, &*lang, &Options::default())
+    };
+    let (old, new) = (analyze(&c.from)?, analyze(&c.to)?);
+    let report = semver::compare(&old.files, &new.files);
+    let markdown = changelog::render(&c.from, &c.to, &report);
+    match &c.output {
+        Some(out) => {
+            fs::write_file(out, &markdown, true)?;
+            pretty::success(&format!(
+                "{} changes \u{2192} {}",
+                report.changes.len(),
+                out.display()
+            ));
+        }
+        None => print!("{markdown}"),
+    }
+    Ok(())
+}
+
+/// Install a hook, or check the changed files' rules as one; violations
+/// fail like `--fail-on rule-violation`.
+fn run_hook(h: &HookArgs) -> Result<Option<FailOn>, BoloError> {
+    let here = Path::new(".");
+    let r = match &h.cmd {
+        HookCmd::Install(i) => {
+            let path = hook::install(here, i.hook, i.force)?;
+            pretty::success(&format!("installed {}", path.display()));
+            return Ok(None);
+        }
+        HookCmd::Run(r) => r,
+    };
+    let changed = match git::changed(here, r.against.as_deref()) {
+        Ok(changed) => changed,
+        // A new branch has no upstream yet; let the push through.
+        Err(e) if r.against.is_some() => {
+            pretty::warn(&format!("{e}; skipping checks"));
+            return Ok(None);
+        }
+        Err(e) => return Err(e),
+    };
+    let config = config::resolve(r.config.as_deref(), here)?;
+    let files = hook::analyze(&changed);
+    let violations = rules::check(&files, &config.rules);
+    for v in &violations {
+        pretty::warn(&format!("{}:{}: {}", v.file, v.span.start_line, v.message));
+    }
+    Ok((!violations.is_empty()).then_some(FailOn::RuleViolation))
+}
+
+/// Answer editor requests on stdin/stdout until the client exits.
+fn run_lsp(l: &LspArgs) -> Result<(), BoloError> {
+    let (input, output) = (std::io::stdin().lock(), std::io::stdout().lock());
+    match l.lang {
+        Language::Py => lsp::serve(&mut lsp::Server::new(Python, "py"), input, output),
+        Language::Rs => lsp::serve(&mut lsp::Server::new(Rust, "rs"), input, output),
+        Language::Js => lsp::serve(&mut lsp::Server::new(JavaScript, "js"), input, output),
+        Language::Ts => lsp::serve(&mut lsp::Server::new(TypeScript, "ts"), input, output),
+        Language::Go => lsp::serve(&mut lsp::Server::new(Go, "go"), input, output),
+    }
+}
+
+/// `$BOLO_SERVE_TOKEN`, unless unset or empty.
+fn serve_token() -> Option<String> {
+    std::env::var(serve::TOKEN_VAR)
+        .ok()
+        .filter(|t| !t.is_empty())
+}
+
+fn run_serve(s: &ServeArgs) -> Result<(), BoloError> {
+    fs::validate_path(&s.path)?;
+    let tls = s.tls_cert.clone().zip(s.tls_key.clone());
+    let tls = tls.map(|(cert, key)| serve::Tls { cert, key });
+    let addr = format!("{}:{}", s.host, s.port);
+    if s.grpc {
+        return run_grpc(s, &addr, tls.as_ref());
+    }
+    let listener = serve::Listener::bind(&addr, serve_token(), tls.as_ref())?;
+    let (lang, ext) = parser(s.lang);
+    let server = serve::Server::new(&*lang, ext, s.path.clone(), s.no_ignore, serve_options(s)?)?;
+    pretty::success(&format!(
+        "serving {} files on {}",
+        server.files(),
+        listener.url()
+    ));
+    listener.serve(|method, target| server.handle(method, target))
+}
+
+/// The analysis options of `bolo serve`, flags over bolo.toml.
+fn serve_options(s: &ServeArgs) -> Result<Options, BoloError> {
+    let config = config::resolve(s.config.as_deref(), &s.path)?;
+    Ok(Options {
+        comments: s.comments.or(config.comments).unwrap_or_default(),
+        passes: passes(s.passes.as_ref().or(config.passes.as_ref()))?,
+        root_label: s.root_label.clone().or(config.root_label.clone()),
+        ..Default::default()
+    })
+}
+
+#[cfg(feature = "grpc")]
+fn run_grpc(s: &ServeArgs, addr: &str, tls: Option<&serve::Tls>) -> Result<(), BoloError> {
+    use bolomoty::grpc;
+    let listener = grpc::Listener::bind(addr, serve_token(), tls)?;
+    let (lang, ext) = parser(s.lang);
+    // Served until the process exits, and gRPC services must be 'static.
+    let lang: &'static (dyn Lang + Sync) = Box::leak(lang);
+    let server = serve::Server::new(lang, ext, s.path.clone(), s.no_ignore, serve_options(s)?)?;
+    pretty::success(&format!(
+        "serving {} files over gRPC on {}",
+        server.files(),
+        listener.url()
+    ));
+    listener.serve(server)
+}
+
+#[cfg(not(feature = "grpc"))]
+fn run_grpc(_: &ServeArgs, _: &str, _: Option<&serve::Tls>) -> Result<(), BoloError> {
+    Err(BoloError::Unsupported {
+        flag: "grpc",
+        context: "in a build without the `grpc` feature",
+    })
+}
+
+fn run_open(o: &OpenArgs) -> Result<(), BoloError> {
+    let bundle = Bundle::open(&o.input)?;
+    let listener = serve::Listener::bind(&format!("{}:{}", o.host, o.port), serve_token(), None)?;
+    let url = listener.url();
+    pretty::success(&format!(
+        "serving {} ({} files, bolo {}) on {url}",
+        o.input.display(),
+        bundle.analysis.files.len(),
+        bundle.version
+    ));
+    if !o.no_browser && serve::browse(&format!("{url}/")).is_err() {
+        pretty::warn("could not open a browser; visit the address above");
+    }
+    listener.serve(|method, target| bundle.handle(method, target))
+}
+
+fn run_bench(b: &BenchArgs) -> Result<(), BoloError> {
+    let (lang, ext) = parser(b.lang);
+    let corpus = bench::Corpus {
+        files: b.files,
+        functions: b.functions,
+        seed: b.seed,
+    };
+    let dir = tempfile::TempDir::new().map_err(|e| BoloError::Write {
+        path: std::env::temp_dir(),
+        reason: e.to_string(),
+    })?;
+    let start = Instant::now();
+    bench::generate(dir.path(), ext, &corpus)?;
+    let generated = start.elapsed();
+
+    // Sized after generating, so `--jobs auto` sees the corpus.
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(b.jobs.parse_threads(&fs::walk_dir(dir.path(), ext, true)?))
+        .build_global()
+        .ok();
+
+    let mut report = bench::run(dir.path(), ext, &*lang)?;
+    report.phases.insert(
+        0,
+        bench::Phase {
+            name: "generate",
+            seconds: generated.as_secs_f64(),
+        },
+    );
+    if b.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!("{}", report.render(ext));
+    }
+    Ok(())
+}
+
+/// Re-parse `files` for their mutable globals, skipping files not in
+/// `result`. Only Python and Rust define globals this way.
+fn global_state(
+    files: &[fs::File],
+    language: Language,
+    lang: &dyn Lang,
+    opts: &Options,
+    result: &[Vec<Syntax>],
+) -> Result<Vec<(String, Globals)>, BoloError> {
+    let extract = match language {
+        Language::Py => py::globals,
+        Language::Rs => rs::globals,
+        _ => {
+            pretty::warn(&format!(
+                "globals: {} is not supported; only py and rs",
+                language_name(language)
+            ));
+            return Ok(vec![]);
+        }
+    };
+    let kept: HashSet<&str> = result
+        .iter()
+        .filter_map(|n| match n.first().map(|s| &s.node) {
+            Some(ASTNode::File(f)) => Some(f.path.as_ref()),
+            _ => None,
+        })
+        .collect();
+    let mut out = Vec::new();
+    for f in files {
+        let path = fs::portable(&f.rel_path, opts.root_label.as_deref());
+        if !kept.contains(path.as_str()) {
+            continue;
+        }
+        let (source, _) = f.read_lossy()?;
+        let tree = lang
+            .get_parser()
+            .parse(&source, None)
+            .ok_or_else(|| BoloError::Parse {
+                file: fs::portable(&f.rel_path, None),
+                reason: "parser returned no tree".into(),
+            })?;
+        out.push((path, extract(&tree, &source)));
+    }
+    Ok(out)
+}
+
+fn parser(language: Language) -> (Box<dyn Lang + Sync>, &'static str) {
+    match language {
+        Language::Py => (Box::new(Python), "py"),
+        Language::Rs => (Box::new(Rust), "rs"),
+        Language::Js => (Box::new(JavaScript), "js"),
+        Language::Ts => (Box::new(TypeScript), "ts"),
+        Language::Go => (Box::new(Go), "go"),
+    }
+}
+
+fn language_name(language: Language) -> &'static str {
+    match language {
+        Language::Py => "python",
+        Language::Rs => "rust",
+        Language::Js => "javascript",
+        Language::Ts => "typescript",
+        Language::Go => "go",
+    }
+}
+
+/// Read a `--files-from` list from a file, or stdin for `-`.
+fn read_list(list: &Path, null: bool) -> Result<Vec<PathBuf>, BoloError> {
+    let mut buf = Vec::new();
+    let read = if list == Path::new("-") {
+        std::io::stdin().read_to_end(&mut buf)
+    } else {
+        std::fs::File::open(list).and_then(|mut f| f.read_to_end(&mut buf))
+    };
+    read.map_err(|e| BoloError::Read {
+        path: list.to_path_buf(),
+        reason: e.to_string(),
+    })?;
+    Ok(fs::read_list(&buf, null))
+}
+
+// ── Output ──────────────────────────────────────────────────────────
+
+/// Refuse to clobber outputs without `-f`; reports only render as JSON.
+/// `$GITHUB_STEP_SUMMARY`: the markdown file a GitHub Actions step appends
+/// its job summary to.
+fn step_summary() -> Option<PathBuf> {
+    std::env::var_os("GITHUB_STEP_SUMMARY")
+        .filter(|p| !p.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Write a markdown summary to `output`, else append it to the job summary
+/// when running in GitHub Actions, else print it.
+fn publish(markdown: &str, output: Option<&Path>) -> Result<(), BoloError> {
+    match (output, step_summary()) {
+        (Some(out), _) => fs::write_file(out, markdown, true)?,
+        (None, Some(path)) => fs::append_file(&path, markdown)?,
+        (None, None) => {
+            print!("{markdown}");
+            return Ok(());
+        }
+    }
+    let to = output.map_or_else(step_summary, |o| Some(o.to_path_buf()));
+    pretty::success(&format!(
+        "summary \u{2192} {}",
+        to.unwrap_or_default().display()
+    ));
+    Ok(())
+}
+
+/// The JSON layout `--compact` or `--pretty` asks for, if either.
+fn layout(args: &Args) -> Option<Layout> {
+    match (args.compact, args.pretty) {
+        (true, _) => Some(Layout::Compact),
+        (_, true) => Some(Layout::Pretty),
+        _ => None,
+    }
+}
+
+/// The built-in passes named by `--passes` or bolo.toml, else the default;
+/// `--passes ""` names none.
+fn passes(names: Option<&Vec<String>>) -> Result<Pipeline, BoloError> {
+    let Some(names) = names else {
+        return Ok(Pipeline::default());
+    };
+    let names: Vec<_> = names.iter().filter(|n| !n.is_empty()).collect();
+    Registry::default().pipeline(&names)
+}
+
+fn check_outputs(outputs: &[PathBuf], force: bool, json_only: bool) -> Result<(), BoloError> {
+    for out in outputs {
+        if out.exists() && !force {
+            return Err(BoloError::Exists { path: out.clone() });
+        }
+        if json_only && Format::from_path(out) != Format::Json {
+            return Err(BoloError::Format {
+                path: out.clone(),
+                reason: "reports are JSON only".into(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// How [`emit`] renders, the same for every output of a run.
+#[derive(Default)]
+struct Style {
+    /// Format for stdout and for outputs whose extension asks for JSON.
+    plain: Format,
+    /// Analysis JSON over this is cut down to fit (see [`budget::fit`]).
+    max_bytes: Option<usize>,
+    /// Forced JSON layout, else per [`Layout::pick`].
+    layout: Option<Layout>,
+    labels: Labels,
+}
+
+/// Write `report` (or `analysis` rendered per output format) to each output,
+/// or to stdout when there are none. `files` is the count shown on success.
+fn emit(
+    outputs: &[PathBuf],
+    files: usize,
+    analysis: &Analysis,
+    report: Option<serde_json::Value>,
+    style: &Style,
+) -> Result<(), BoloError> {
+    let Style {
+        plain,
+        max_bytes,
+        layout,
+        ref labels,
+    } = *style;
+    let render = |format, to_file| {
+        let layout = Layout::pick(layout, to_file);
+        match (&report, format, max_bytes) {
+            (Some(json), _, _) => export::json(json, layout),
+            (None, Format::Json, Some(max)) => {
+                let (json, truncated) = budget::fit(analysis, max, |a| {
+                    export::render(Format::Json, a, layout, labels)
+                })?;
+                if let Some(t) = truncated {
+                    let dropped: Vec<String> = t.dropped.iter().map(|d| d.to_string()).collect();
+                    pretty::warn(&format!(
+                        "output over {max} bytes: dropped {}",
+                        dropped.join(", ")
+                    ));
+                }
+                Ok(json)
+            }
+            (None, format, _) => export::render(format, analysis, layout, labels),
+        }
+    };
+
+    if outputs.is_empty() {
+        println!("{}", render(plain, false)?);
+    }
+    for out in outputs {
+        let content = match Format::from_path(out) {
+            Format::Json => render(plain, true)?,
+            format => render(format, true)?,
+        };
+        fs::write_file(out, &content, true)?;
+        pretty::success(&format!(
+            "{files} files \u{2192} {} ({} bytes)",
+            out.display(),
+            content.len()
+        ));
+    }
 
     Ok(())
 }
@@ -76,7 +1480,16 @@ fn run() -> Result<(), BoloError> {
 
 #[cfg(test)]
 mod tests {
-    use bolomoty::cli::{Args, Bolo, LangCmd};
+    use super::{layout, passes};
+    use bolomoty::clean::CommentPolicy;
+    use bolomoty::cli::{
+        AnalysisFormat, Args, Bolo, FailOn, FormatArg, HookArgs, HookCmd, LangCmd, Language,
+        QueryArgs, QueryCmd, QueryFormat, StringsArg,
+    };
+    use bolomoty::export::Layout;
+    use bolomoty::hook::Hook;
+    use bolomoty::jobs::Jobs;
+    use bolomoty::pipeline;
     use clap::Parser;
     use std::path::Path;
     use std::path::PathBuf;
@@ -87,7 +1500,37 @@ mod tests {
 
     fn args(cli: &Bolo) -> &Args {
         match &cli.lang {
-            LangCmd::Py(a) | LangCmd::Rs(a) => a,
+            LangCmd::Py(a)
+            | LangCmd::Rs(a)
+            | LangCmd::Js(a)
+            | LangCmd::Ts(a)
+            | LangCmd::Go(a)
+            | LangCmd::Unsafe(a)
+            | LangCmd::Exceptions(a)
+            | LangCmd::Errors(a) => a,
+            LangCmd::Panics(p) => &p.args,
+            LangCmd::Watch(w)
+            | LangCmd::Clusters(w)
+            | LangCmd::Coupling(w)
+            | LangCmd::Globals(w)
+            | LangCmd::Stats(w)
+            | LangCmd::Vocab(w) => &w.args,
+            LangCmd::All(_)
+            | LangCmd::Merge(_)
+            | LangCmd::Diff(_)
+            | LangCmd::Semver(_)
+            | LangCmd::PreviewRename(_)
+            | LangCmd::Query(_)
+            | LangCmd::Stubs(_)
+            | LangCmd::Badge(_)
+            | LangCmd::Record(_)
+            | LangCmd::Trend(_)
+            | LangCmd::Changelog(_)
+            | LangCmd::Bench(_)
+            | LangCmd::Lsp(_)
+            | LangCmd::Serve(_)
+            | LangCmd::Open(_)
+            | LangCmd::Hook(_) => panic!("no analysis args"),
         }
     }
 
@@ -105,6 +1548,494 @@ mod tests {
         assert!(matches!(cli.lang, LangCmd::Rs(_)));
     }
 
+    #[test]
+    fn subcommand_js() {
+        let cli = parse(&["bolo", "js"]);
+        assert!(matches!(cli.lang, LangCmd::Js(_)));
+    }
+
+    #[test]
+    fn subcommand_ts() {
+        let cli = parse(&["bolo", "ts"]);
+        assert!(matches!(cli.lang, LangCmd::Ts(_)));
+    }
+
+    #[test]
+    fn subcommand_go() {
+        let cli = parse(&["bolo", "go"]);
+        assert!(matches!(cli.lang, LangCmd::Go(_)));
+    }
+
+    #[test]
+    fn subcommand_bench_defaults() {
+        let cli = parse(&["bolo", "bench", "rs"]);
+        let LangCmd::Bench(b) = &cli.lang else {
+            panic!("expected bench");
+        };
+        assert_eq!(b.lang, Language::Rs);
+        assert_eq!((b.files, b.functions, b.seed), (1000, 20, 0));
+        assert_eq!(b.jobs, Jobs::Fixed(1));
+        assert!(!b.json);
+    }
+
+    #[test]
+    fn subcommand_bench_sized() {
+        let cli = parse(&[
+            "bolo",
+            "bench",
+            "py",
+            "--files",
+            "50",
+            "--functions",
+            "5",
+            "--seed",
+            "9",
+            "--json",
+        ]);
+        let LangCmd::Bench(b) = &cli.lang else {
+            panic!("expected bench");
+        };
+        assert_eq!((b.files, b.functions, b.seed), (50, 5, 9));
+        assert!(b.json);
+    }
+
+    #[test]
+    fn subcommand_lsp() {
+        let cli = parse(&["bolo", "lsp", "py"]);
+        let LangCmd::Lsp(l) = &cli.lang else {
+            panic!("expected lsp");
+        };
+        assert_eq!(l.lang, Language::Py);
+    }
+
+    #[test]
+    fn subcommand_hook() {
+        let cli = parse(&["bolo", "hook", "install", "--hook", "pre-push", "-f"]);
+        let LangCmd::Hook(HookArgs {
+            cmd: HookCmd::Install(i),
+        }) = &cli.lang
+        else {
+            panic!("expected hook install");
+        };
+        assert_eq!(i.hook, Hook::PrePush);
+        assert!(i.force);
+
+        let cli = parse(&["bolo", "hook", "install"]);
+        let LangCmd::Hook(HookArgs {
+            cmd: HookCmd::Install(i),
+        }) = &cli.lang
+        else {
+            panic!("expected hook install");
+        };
+        assert_eq!(i.hook, Hook::PreCommit);
+
+        let cli = parse(&["bolo", "hook", "run", "--against", "origin/main"]);
+        let LangCmd::Hook(HookArgs {
+            cmd: HookCmd::Run(r),
+        }) = &cli.lang
+        else {
+            panic!("expected hook run");
+        };
+        assert_eq!(r.against.as_deref(), Some("origin/main"));
+    }
+
+    #[test]
+    fn subcommand_unsafe() {
+        let cli = parse(&["bolo", "unsafe", "src/"]);
+        assert!(matches!(cli.lang, LangCmd::Unsafe(_)));
+        assert_eq!(args(&cli).path, PathBuf::from("src/"));
+    }
+
+    #[test]
+    fn subcommand_exceptions() {
+        let cli = parse(&["bolo", "exceptions", "app/"]);
+        assert!(matches!(cli.lang, LangCmd::Exceptions(_)));
+        assert_eq!(args(&cli).path, PathBuf::from("app/"));
+    }
+
+    #[test]
+    fn subcommand_errors() {
+        let cli = parse(&["bolo", "errors", "src/"]);
+        assert!(matches!(cli.lang, LangCmd::Errors(_)));
+        assert_eq!(args(&cli).path, PathBuf::from("src/"));
+    }
+
+    #[test]
+    fn subcommand_panics() {
+        let cli = parse(&["bolo", "panics", "py", "src/", "-o", "p.json"]);
+        let LangCmd::Panics(p) = &cli.lang else {
+            panic!("expected panics");
+        };
+        assert_eq!(p.lang, Language::Py);
+        assert!(p.calls.is_empty());
+        assert_eq!(args(&cli).path, PathBuf::from("src/"));
+        assert_eq!(args(&cli).output, vec![PathBuf::from("p.json")]);
+    }
+
+    #[test]
+    fn panics_custom_calls() {
+        let cli = parse(&["bolo", "panics", "rs", "--calls", "unwrap,dbg!"]);
+        let LangCmd::Panics(p) = &cli.lang else {
+            panic!("expected panics");
+        };
+        assert_eq!(p.calls, vec!["unwrap", "dbg!"]);
+    }
+
+    #[test]
+    fn panics_requires_language() {
+        assert!(Bolo::try_parse_from(["bolo", "panics"]).is_err());
+        assert!(Bolo::try_parse_from(["bolo", "panics", "java"]).is_err());
+    }
+
+    #[test]
+    fn subcommand_watch() {
+        let cli = parse(&["bolo", "watch", "rs", "src/", "--config", "ci/bolo.toml"]);
+        let LangCmd::Watch(w) = &cli.lang else {
+            panic!("expected watch");
+        };
+        assert_eq!(w.lang, Language::Rs);
+        assert_eq!(
+            args(&cli).config.as_deref(),
+            Some(Path::new("ci/bolo.toml"))
+        );
+    }
+
+    #[test]
+    fn subcommand_clusters() {
+        let cli = parse(&["bolo", "clusters", "py", "src/"]);
+        let LangCmd::Clusters(c) = &cli.lang else {
+            panic!("expected clusters");
+        };
+        assert_eq!(c.lang, Language::Py);
+        assert_eq!(args(&cli).path, Path::new("src/"));
+    }
+
+    #[test]
+    fn subcommand_coupling() {
+        let cli = parse(&["bolo", "coupling", "rs", "src/"]);
+        assert!(matches!(cli.lang, LangCmd::Coupling(_)));
+        assert_eq!(args(&cli).path, Path::new("src/"));
+    }
+
+    #[test]
+    fn subcommand_globals() {
+        let cli = parse(&["bolo", "globals", "py", "src/"]);
+        let LangCmd::Globals(g) = &cli.lang else {
+            panic!("expected Globals");
+        };
+        assert_eq!(g.lang, Language::Py);
+        assert_eq!(args(&cli).path, Path::new("src/"));
+    }
+
+    #[test]
+    fn subcommand_stats() {
+        let cli = parse(&["bolo", "stats", "rs", "src/"]);
+        let LangCmd::Stats(s) = &cli.lang else {
+            panic!("expected Stats");
+        };
+        assert_eq!(s.lang, Language::Rs);
+        assert_eq!(args(&cli).path, Path::new("src/"));
+    }
+
+    #[test]
+    fn subcommand_vocab() {
+        let cli = parse(&["bolo", "vocab", "ts", "src/"]);
+        let LangCmd::Vocab(v) = &cli.lang else {
+            panic!("expected Vocab");
+        };
+        assert_eq!(v.lang, Language::Ts);
+        assert_eq!(args(&cli).path, Path::new("src/"));
+    }
+
+    #[test]
+    fn subcommand_all() {
+        let cli = parse(&["bolo", "all", "src/", "--lang", "py,rs", "-o", "all.json"]);
+        let LangCmd::All(a) = &cli.lang else {
+            panic!("expected All");
+        };
+        assert_eq!(a.path, Path::new("src/"));
+        assert_eq!(a.lang, [Language::Py, Language::Rs]);
+        assert_eq!(a.output, [PathBuf::from("all.json")]);
+        assert!(!a.shallow);
+
+        let cli = parse(&["bolo", "all", "--shallow"]);
+        let LangCmd::All(a) = &cli.lang else {
+            panic!("expected All");
+        };
+        assert!(a.shallow);
+    }
+
+    #[test]
+    fn subcommand_serve() {
+        let cli = parse(&["bolo", "serve", "py", "src/", "--port", "9000"]);
+        let LangCmd::Serve(s) = &cli.lang else {
+            panic!("expected Serve");
+        };
+        assert_eq!(s.lang, Language::Py);
+        assert_eq!(s.path, Path::new("src/"));
+        assert_eq!((s.host.as_str(), s.port), ("127.0.0.1", 9000));
+        assert!(s.tls_cert.is_none() && !s.no_ignore && !s.grpc);
+
+        let cli = parse(&[
+            "bolo",
+            "serve",
+            "py",
+            "--tls-cert",
+            "c.pem",
+            "--tls-key",
+            "k.pem",
+            "--no-ignore",
+            "--grpc",
+        ]);
+        let LangCmd::Serve(s) = &cli.lang else {
+            panic!("expected Serve");
+        };
+        assert_eq!(s.tls_key.as_deref(), Some(Path::new("k.pem")));
+        assert!(s.no_ignore && s.grpc);
+        assert!(Bolo::try_parse_from(["bolo", "serve", "py", "--tls-cert", "c.pem"]).is_err());
+    }
+
+    #[test]
+    fn subcommand_open() {
+        let cli = parse(&["bolo", "open", "out.bolo", "--port", "9000"]);
+        let LangCmd::Open(o) = &cli.lang else {
+            panic!("expected Open");
+        };
+        assert_eq!(o.input, Path::new("out.bolo"));
+        assert_eq!(o.port, 9000);
+    }
+
+    #[test]
+    fn bundle_flag() {
+        let cli = parse(&["bolo", "rs", "src/", "--bundle", "out.bolo"]);
+        assert_eq!(args(&cli).bundle.as_deref(), Some(Path::new("out.bolo")));
+    }
+
+    #[test]
+    fn subcommand_merge() {
+        let cli = parse(&["bolo", "merge", "py.json", "rs.json", "-o", "all.json"]);
+        let LangCmd::Merge(m) = &cli.lang else {
+            panic!("expected merge");
+        };
+        assert_eq!(
+            m.inputs,
+            vec![PathBuf::from("py.json"), PathBuf::from("rs.json")]
+        );
+        assert_eq!(m.output, vec![PathBuf::from("all.json")]);
+        assert!(!m.force);
+    }
+
+    #[test]
+    fn subcommand_diff() {
+        let cli = parse(&["bolo", "diff", "old.json", "new.json", "--format", "dot"]);
+        let LangCmd::Diff(d) = &cli.lang else {
+            panic!("expected diff");
+        };
+        assert_eq!(d.old, PathBuf::from("old.json"));
+        assert_eq!(d.new, PathBuf::from("new.json"));
+        assert_eq!(d.format, Some(FormatArg::Dot));
+        assert!(d.output.is_none());
+        assert!(Bolo::try_parse_from(["bolo", "diff", "old.json"]).is_err());
+    }
+
+    #[test]
+    fn subcommand_semver() {
+        let cli = parse(&["bolo", "semver", "v1.json", "v2.json", "-o", "bump.json"]);
+        let LangCmd::Semver(v) = &cli.lang else {
+            panic!("expected semver");
+        };
+        assert_eq!(
+            (v.old.as_path(), v.new.as_path()),
+            (Path::new("v1.json"), Path::new("v2.json"))
+        );
+        assert_eq!(v.output, Some(PathBuf::from("bump.json")));
+    }
+
+    #[test]
+    fn subcommand_query_neighborhood() {
+        let cli = parse(&[
+            "bolo",
+            "query",
+            "neighborhood",
+            "net.py::send",
+            "-i",
+            "a.json",
+            "--radius",
+            "2",
+            "--format",
+            "dot",
+        ]);
+        let LangCmd::Query(QueryArgs {
+            cmd: QueryCmd::Neighborhood(n),
+        }) = &cli.lang
+        else {
+            panic!("expected query neighborhood");
+        };
+        assert_eq!(n.symbol, "net.py::send");
+        assert_eq!((n.radius, n.format), (2, QueryFormat::Dot));
+        let n = parse(&["bolo", "query", "neighborhood", "f", "-i", "a.json"]);
+        let LangCmd::Query(QueryArgs {
+            cmd: QueryCmd::Neighborhood(n),
+        }) = &n.lang
+        else {
+            panic!("expected query neighborhood");
+        };
+        assert_eq!((n.radius, n.format), (1, QueryFormat::Graph));
+    }
+
+    #[test]
+    fn subcommand_preview_rename() {
+        let cli = parse(&[
+            "bolo",
+            "preview-rename",
+            "net.py::send",
+            "dispatch",
+            "-i",
+            "a.json",
+        ]);
+        let LangCmd::PreviewRename(r) = &cli.lang else {
+            panic!("expected preview-rename");
+        };
+        assert_eq!(
+            (r.old.as_str(), r.new.as_str()),
+            ("net.py::send", "dispatch")
+        );
+        assert_eq!(r.input, PathBuf::from("a.json"));
+        assert!(Bolo::try_parse_from(["bolo", "preview-rename", "a", "b"]).is_err());
+    }
+
+    #[test]
+    fn subcommand_stubs() {
+        let LangCmd::Stubs(s) = &parse(&["bolo", "stubs"]).lang else {
+            panic!("expected stubs");
+        };
+        assert_eq!(s.path, PathBuf::from("."));
+        let LangCmd::Stubs(s) = &parse(&["bolo", "stubs", "pkg/", "-o", "drift.json"]).lang else {
+            panic!("expected stubs");
+        };
+        assert_eq!(s.output, Some(PathBuf::from("drift.json")));
+    }
+
+    #[test]
+    fn subcommand_badge() {
+        let cli = parse(&["bolo", "badge", "a.json", "--metric", "coverage"]);
+        let LangCmd::Badge(b) = &cli.lang else {
+            panic!("expected badge");
+        };
+        assert_eq!(b.input, PathBuf::from("a.json"));
+        assert_eq!(b.metric, bolomoty::badge::Metric::Coverage);
+        assert!(Bolo::try_parse_from(["bolo", "badge", "a.json"]).is_err());
+    }
+
+    #[test]
+    fn subcommand_record_and_trend() {
+        let cli = parse(&["bolo", "record", "a.json", "--db", "t.jsonl"]);
+        let LangCmd::Record(r) = &cli.lang else {
+            panic!("expected record");
+        };
+        assert_eq!(r.db, PathBuf::from("t.jsonl"));
+        assert!(r.commit.is_none());
+        let cli = parse(&["bolo", "trend", "--db", "t.jsonl", "--metric", "cycles"]);
+        let LangCmd::Trend(t) = &cli.lang else {
+            panic!("expected trend");
+        };
+        assert_eq!(t.metric, vec!["cycles"]);
+        assert!(Bolo::try_parse_from(["bolo", "trend", "--db", "t", "--metric", "loc"]).is_err());
+    }
+
+    #[test]
+    fn max_output_bytes_flag() {
+        let cli = parse(&["bolo", "rs", "--max-output-bytes", "1000"]);
+        assert_eq!(args(&cli).max_output_bytes, Some(1000));
+        assert!(Bolo::try_parse_from(["bolo", "rs", "--max-output-bytes", "1k"]).is_err());
+    }
+
+    #[test]
+    fn json_layout_flags() {
+        assert_eq!(layout(args(&parse(&["bolo", "py"]))), None);
+        let compact = parse(&["bolo", "py", "--compact"]);
+        assert_eq!(layout(args(&compact)), Some(Layout::Compact));
+        let pretty = parse(&["bolo", "py", "--pretty"]);
+        assert_eq!(layout(args(&pretty)), Some(Layout::Pretty));
+        assert!(Bolo::try_parse_from(["bolo", "py", "--compact", "--pretty"]).is_err());
+    }
+
+    #[test]
+    fn color_flag_global() {
+        use bolomoty::pretty::Color;
+        assert_eq!(parse(&["bolo", "py"]).color, Color::Auto);
+        assert_eq!(
+            parse(&["bolo", "--color", "never", "py"]).color,
+            Color::Never
+        );
+        assert_eq!(
+            parse(&["bolo", "diff", "a", "b", "--color", "always"]).color,
+            Color::Always
+        );
+        assert!(Bolo::try_parse_from(["bolo", "py", "--color", "rainbow"]).is_err());
+    }
+
+    #[test]
+    fn no_default_excludes_flag() {
+        assert!(args(&parse(&["bolo", "py", "--no-default-excludes"])).no_default_excludes);
+        assert!(!args(&parse(&["bolo", "py"])).no_default_excludes);
+    }
+
+    #[test]
+    fn include_deps_flag() {
+        assert!(args(&parse(&["bolo", "py", "--include-deps"])).include_deps);
+        assert!(
+            Bolo::try_parse_from(["bolo", "py", "--include-deps", "--files-from", "x"]).is_err()
+        );
+    }
+
+    #[test]
+    fn format_cytoscape() {
+        let cli = parse(&["bolo", "rs", "--format", "cytoscape"]);
+        assert_eq!(args(&cli).format, Some(AnalysisFormat::Cytoscape));
+    }
+
+    #[test]
+    fn format_graph() {
+        let cli = parse(&["bolo", "go", "--format", "graph"]);
+        assert_eq!(args(&cli).format, Some(AnalysisFormat::Graph));
+    }
+
+    #[test]
+    fn format_dot() {
+        let cli = parse(&["bolo", "py", "--format", "dot"]);
+        assert_eq!(args(&cli).format, Some(AnalysisFormat::Dot));
+    }
+
+    #[test]
+    fn format_ndjson() {
+        let cli = parse(&["bolo", "ts", "--format", "ndjson"]);
+        assert_eq!(args(&cli).format, Some(AnalysisFormat::Ndjson));
+    }
+
+    #[test]
+    fn anonymize_flag() {
+        assert!(args(&parse(&["bolo", "py", "--anonymize"])).anonymize);
+        assert!(!args(&parse(&["bolo", "py"])).anonymize);
+    }
+
+    #[test]
+    fn subcommand_changelog() {
+        let cli = parse(&["bolo", "changelog", "rs", "--from", "v1.2"]);
+        let LangCmd::Changelog(c) = &cli.lang else {
+            panic!("expected changelog");
+        };
+        assert_eq!(c.lang, Language::Rs);
+        assert_eq!(c.path, PathBuf::from("."));
+        assert_eq!((c.from.as_str(), c.to.as_str()), ("v1.2", "HEAD"));
+        assert!(Bolo::try_parse_from(["bolo", "changelog", "rs"]).is_err());
+    }
+
+    #[test]
+    fn merge_requires_inputs() {
+        assert!(Bolo::try_parse_from(["bolo", "merge"]).is_err());
+    }
+
     #[test]
     fn missing_subcommand_errors() {
         assert!(Bolo::try_parse_from(["bolo"]).is_err());
@@ -112,7 +2043,7 @@ mod tests {
 
     #[test]
     fn invalid_subcommand_errors() {
-        assert!(Bolo::try_parse_from(["bolo", "go"]).is_err());
+        assert!(Bolo::try_parse_from(["bolo", "java"]).is_err());
     }
 
     // ── Defaults ──
@@ -122,12 +2053,29 @@ mod tests {
         let cli = parse(&["bolo", "py"]);
         let a = args(&cli);
         assert_eq!(a.path, PathBuf::from("."));
-        assert!(a.output.is_none());
+        assert!(a.output.is_empty());
         assert!(!a.force);
         assert!(!a.no_ignore);
         assert!(!a.shallow);
         assert!(!a.dry_run);
-        assert_eq!(a.jobs, 1);
+        assert!(!a.routes);
+        assert!(a.strings.is_none());
+        assert!(a.comments.is_none());
+        assert!(a.passes.is_none());
+        assert!(a.root_label.is_none());
+        assert!(a.shard.is_none());
+        assert!(a.rev.is_none());
+        assert!(a.files_from.is_none());
+        assert!(!a.stdin);
+        assert!(a.filename.is_none());
+        assert!(!a.null);
+        assert!(!a.list_files);
+        assert!(a.config.is_none());
+        assert!(a.profile.is_none());
+        assert!(a.link_template.is_none());
+        assert!(a.format.is_none());
+        assert!(a.fail_on.is_empty());
+        assert_eq!(a.jobs, Jobs::Fixed(1));
     }
 
     // ── Path positional ──
@@ -138,18 +2086,88 @@ mod tests {
         assert_eq!(args(&cli).path, PathBuf::from("src/"));
     }
 
+    #[test]
+    fn remote_path_with_rev() {
+        let cli = parse(&["bolo", "py", "https://github.com/org/repo", "--rev", "v1.2"]);
+        let a = args(&cli);
+        assert_eq!(a.path, PathBuf::from("https://github.com/org/repo"));
+        assert_eq!(a.rev.as_deref(), Some("v1.2"));
+    }
+
+    #[test]
+    fn files_from_stdin_nul() {
+        let cli = parse(&["bolo", "py", "--files-from", "-", "-0"]);
+        let a = args(&cli);
+        assert_eq!(a.files_from.as_deref(), Some(Path::new("-")));
+        assert!(a.null);
+    }
+
+    #[test]
+    fn null_requires_files_from() {
+        assert!(Bolo::try_parse_from(["bolo", "py", "--null"]).is_err());
+    }
+
+    #[test]
+    fn stdin_needs_a_filename() {
+        let cli = parse(&["bolo", "py", "--stdin", "--filename", "pkg/foo.py"]);
+        let a = args(&cli);
+        assert!(a.stdin);
+        assert_eq!(a.filename.as_deref(), Some(Path::new("pkg/foo.py")));
+        assert!(Bolo::try_parse_from(["bolo", "py", "--stdin"]).is_err());
+        assert!(Bolo::try_parse_from(["bolo", "py", "--filename", "foo.py"]).is_err());
+        assert!(
+            Bolo::try_parse_from([
+                "bolo",
+                "py",
+                "--stdin",
+                "--filename",
+                "a.py",
+                "--files-from",
+                "-"
+            ])
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn list_files() {
+        let cli = parse(&["bolo", "rs", "src/", "--list-files"]);
+        assert!(args(&cli).list_files);
+        assert!(Bolo::try_parse_from(["bolo", "rs", "--list-files", "--dry-run"]).is_err());
+    }
+
+    #[test]
+    fn cfg_matrix() {
+        let cli = parse(&["bolo", "rs", "--cfg-matrix"]);
+        assert!(args(&cli).cfg_matrix);
+        assert!(Bolo::try_parse_from(["bolo", "rs", "--cfg-matrix", "--list-files"]).is_err());
+    }
+
     // ── Output flag ──
 
     #[test]
     fn output_short() {
         let cli = parse(&["bolo", "py", "-o", "out.json"]);
-        assert_eq!(args(&cli).output.as_deref(), Some(Path::new("out.json")));
+        assert_eq!(args(&cli).output, vec![PathBuf::from("out.json")]);
     }
 
     #[test]
     fn output_long() {
         let cli = parse(&["bolo", "py", "--output", "dag.json"]);
-        assert_eq!(args(&cli).output.as_deref(), Some(Path::new("dag.json")));
+        assert_eq!(args(&cli).output, vec![PathBuf::from("dag.json")]);
+    }
+
+    #[test]
+    fn output_repeated() {
+        let cli = parse(&[
+            "bolo", "py", "-o", "dag.json", "-o", "dag.dot", "--output", "r.html",
+        ]);
+        let outputs: Vec<_> = args(&cli)
+            .output
+            .iter()
+            .map(|p| p.to_str().unwrap())
+            .collect();
+        assert_eq!(outputs, vec!["dag.json", "dag.dot", "r.html"]);
     }
 
     #[test]
@@ -191,24 +2209,190 @@ mod tests {
         assert!(args(&cli).dry_run);
     }
 
+    #[test]
+    fn routes() {
+        let cli = parse(&["bolo", "py", "--routes"]);
+        assert!(args(&cli).routes);
+    }
+
+    #[test]
+    fn strings_defaults_to_all() {
+        let cli = parse(&["bolo", "py", "--strings"]);
+        assert_eq!(args(&cli).strings, Some(StringsArg::All));
+    }
+
+    #[test]
+    fn strings_interesting() {
+        let cli = parse(&["bolo", "rs", "--strings", "interesting"]);
+        assert_eq!(args(&cli).strings, Some(StringsArg::Interesting));
+    }
+
+    #[test]
+    fn strings_invalid_value_errors() {
+        assert!(Bolo::try_parse_from(["bolo", "rs", "--strings", "bogus"]).is_err());
+    }
+
+    #[test]
+    fn comments_policy() {
+        let cli = parse(&["bolo", "py", "--comments", "keep-in-place"]);
+        assert_eq!(args(&cli).comments, Some(CommentPolicy::KeepInPlace));
+        assert!(Bolo::try_parse_from(["bolo", "py", "--comments", "hoist"]).is_err());
+    }
+
+    #[test]
+    fn passes_in_order() {
+        let cli = parse(&["bolo", "py", "--passes", "strip-comments,metrics"]);
+        let names = args(&cli).passes.as_ref();
+        assert_eq!(
+            passes(names).unwrap().names(),
+            ["strip-comments", "metrics"]
+        );
+        assert_eq!(passes(None).unwrap().names(), pipeline::DEFAULT);
+        let cli = parse(&["bolo", "py", "--passes", ""]);
+        assert!(
+            passes(args(&cli).passes.as_ref())
+                .unwrap()
+                .names()
+                .is_empty()
+        );
+        let err = passes(Some(&vec!["nope".into()])).unwrap_err();
+        assert!(err.to_string().starts_with("unknown pass `nope`"));
+    }
+
+    #[test]
+    fn root_label() {
+        let cli = parse(&["bolo", "rs", "--root-label", "bolo"]);
+        assert_eq!(args(&cli).root_label.as_deref(), Some("bolo"));
+    }
+
+    #[test]
+    fn profile() {
+        let cli = parse(&["bolo", "py", "--profile", "py-spy.json"]);
+        assert_eq!(
+            args(&cli).profile.as_deref(),
+            Some(Path::new("py-spy.json"))
+        );
+    }
+
+    #[test]
+    fn link_template() {
+        let cli = parse(&["bolo", "py", "--link-template", "https://x/{path}#L{start}"]);
+        assert!(args(&cli).link_template.is_some());
+        assert!(
+            Bolo::try_parse_from(["bolo", "py", "--link-template", "https://x/{line}"]).is_err()
+        );
+    }
+
+    #[test]
+    fn gh_summary_format() {
+        let cli = parse(&["bolo", "rs", "--format", "gh-summary"]);
+        assert_eq!(args(&cli).format, Some(AnalysisFormat::GhSummary));
+        let cli = parse(&["bolo", "diff", "a.json", "b.json", "--format", "gh-summary"]);
+        let LangCmd::Diff(d) = &cli.lang else {
+            panic!("expected diff");
+        };
+        assert_eq!(d.format, Some(FormatArg::GhSummary));
+    }
+
+    #[test]
+    fn shard() {
+        let cli = parse(&["bolo", "py", "--shard", "2/4"]);
+        let s = args(&cli).shard.unwrap();
+        assert_eq!((s.index, s.count), (2, 4));
+        assert!(Bolo::try_parse_from(["bolo", "py", "--shard", "5/4"]).is_err());
+    }
+
+    #[test]
+    fn sample_and_max_files() {
+        let cli = parse(&[
+            "bolo",
+            "py",
+            "--sample",
+            "10%",
+            "--max-files",
+            "50",
+            "--seed",
+            "3",
+        ]);
+        let a = args(&cli);
+        assert_eq!(a.sample.unwrap().percent, 10.0);
+        assert_eq!(a.max_files.unwrap().get(), 50);
+        assert_eq!(a.seed, 3);
+        assert!(Bolo::try_parse_from(["bolo", "py", "--max-files", "0"]).is_err());
+        assert!(Bolo::try_parse_from(["bolo", "py", "--sample", "0%"]).is_err());
+    }
+
+    #[test]
+    fn cache_dir_optional() {
+        assert!(args(&parse(&["bolo", "py", "src/"])).cache.is_none());
+        let cli = parse(&["bolo", "py", "--cache", "src/"]);
+        assert_eq!(args(&cli).cache, Some(None));
+        assert_eq!(args(&cli).path, PathBuf::from("src/"));
+        let cli = parse(&["bolo", "py", "--cache=/tmp/bolo", "src/"]);
+        assert_eq!(args(&cli).cache, Some(Some(PathBuf::from("/tmp/bolo"))));
+    }
+
+    // ── Fail-on ──
+
+    #[test]
+    fn fail_on_comma_separated_and_repeated() {
+        let cli = parse(&[
+            "bolo",
+            "rs",
+            "--fail-on",
+            "cycle,parse-error",
+            "--fail-on",
+            "rule-violation",
+        ]);
+        assert_eq!(
+            args(&cli).fail_on,
+            vec![FailOn::Cycle, FailOn::ParseError, FailOn::RuleViolation]
+        );
+    }
+
+    #[test]
+    fn fail_on_invalid_value_errors() {
+        assert!(Bolo::try_parse_from(["bolo", "py", "--fail-on", "lint"]).is_err());
+    }
+
+    #[test]
+    fn exit_codes_distinct_and_reserved() {
+        let codes: Vec<u8> = [
+            FailOn::ParseError,
+            FailOn::Warning,
+            FailOn::Cycle,
+            FailOn::RuleViolation,
+        ]
+        .into_iter()
+        .map(super::exit_code)
+        .collect();
+        assert_eq!(codes, vec![3, 4, 5, 6]);
+    }
+
     // ── Jobs flag ──
 
     #[test]
     fn jobs_short() {
         let cli = parse(&["bolo", "py", "-j", "4"]);
-        assert_eq!(args(&cli).jobs, 4);
+        assert_eq!(args(&cli).jobs, Jobs::Fixed(4));
     }
 
     #[test]
     fn jobs_long() {
         let cli = parse(&["bolo", "py", "--jobs", "8"]);
-        assert_eq!(args(&cli).jobs, 8);
+        assert_eq!(args(&cli).jobs, Jobs::Fixed(8));
     }
 
     #[test]
     fn jobs_zero_means_all_cores() {
         let cli = parse(&["bolo", "py", "-j", "0"]);
-        assert_eq!(args(&cli).jobs, 0);
+        assert_eq!(args(&cli).jobs, Jobs::Fixed(0));
+    }
+
+    #[test]
+    fn jobs_auto() {
+        let cli = parse(&["bolo", "py", "-j", "auto"]);
+        assert_eq!(args(&cli).jobs, Jobs::Auto);
     }
 
     #[test]
@@ -228,8 +2412,8 @@ mod tests {
         let cli = parse(&["bolo", "py", "-f", "-o", "out.json", "-j", "2"]);
         let a = args(&cli);
         assert!(a.force);
-        assert_eq!(a.output.as_deref(), Some(Path::new("out.json")));
-        assert_eq!(a.jobs, 2);
+        assert_eq!(a.output, vec![PathBuf::from("out.json")]);
+        assert_eq!(a.jobs, Jobs::Fixed(2));
     }
 
     #[test]
@@ -266,12 +2450,12 @@ mod tests {
         ]);
         let a = args(&cli);
         assert_eq!(a.path, PathBuf::from("src/"));
-        assert_eq!(a.output.as_deref(), Some(Path::new("dag.json")));
+        assert_eq!(a.output, vec![PathBuf::from("dag.json")]);
         assert!(a.force);
         assert!(a.no_ignore);
         assert!(a.shallow);
         assert!(a.dry_run);
-        assert_eq!(a.jobs, 4);
+        assert_eq!(a.jobs, Jobs::Fixed(4));
     }
 
     #[test]
@@ -290,6 +2474,6 @@ mod tests {
         assert!(a.force);
         assert!(a.no_ignore);
         assert_eq!(a.path, PathBuf::from("src/"));
-        assert_eq!(a.jobs, 2);
+        assert_eq!(a.jobs, Jobs::Fixed(2));
     }
 }
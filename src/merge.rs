@@ -0,0 +1,360 @@
+use crate::analysis::Analysis;
+use crate::api::tree_sitter::{ASTNode, Syntax};
+use crate::error::BoloError;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// Two inputs disagree about the same file.
+#[derive(Debug, Clone, Serialize)]
+pub struct Conflict {
+    pub path: String,
+    pub reason: String,
+}
+
+/// Either output shape, the `{files, warnings}` envelope or a bare file array
+/// written before warnings existed, keeping fields this bolo does not know
+/// (see [`Syntax::from_value`]).
+pub(crate) fn input(value: Value) -> serde_json::Result<Analysis> {
+    let files = |value: Value| match value {
+        Value::Array(files) => files
+            .into_iter()
+            .map(|file| match file {
+                Value::Array(nodes) => nodes.into_iter().map(Syntax::from_value).collect(),
+                other => serde_json::from_value(other),
+            })
+            .collect(),
+        other => serde_json::from_value(other),
+    };
+    match value {
+        Value::Object(mut envelope) => {
+            let nodes = envelope.insert("files".into(), Value::Array(vec![]));
+            let mut analysis: Analysis = serde_json::from_value(Value::Object(envelope))?;
+            analysis.files = match nodes {
+                Some(nodes) => files(nodes)?,
+                None => return Err(serde::de::Error::missing_field("files")),
+            };
+            Ok(analysis)
+        }
+        bare => Ok(Analysis {
+            files: files(bare)?,
+            ..Default::default()
+        }),
+    }
+}
+
+/// Read one analysis JSON file (the output of `bolo py|rs`), or the lines
+/// of `--format ndjson` output joined into one analysis. Fields written by
+/// another bolo version that this one does not know are kept, so writing
+/// the analysis back out loses nothing.
+pub fn load(path: &Path) -> Result<Analysis, BoloError> {
+    let text = fs::read_to_string(path).map_err(|e| BoloError::Read {
+        path: path.to_path_buf(),
+        reason: e.to_string(),
+    })?;
+    let inputs = match serde_json::from_str(&text).and_then(input) {
+        Ok(input) => vec![input],
+        Err(e) => text
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(|l| serde_json::from_str(l).and_then(input))
+            .collect::<Result<Vec<_>, _>>()
+            .ok()
+            .filter(|lines| lines.len() > 1)
+            .ok_or_else(|| BoloError::Parse {
+                file: path.display().to_string(),
+                reason: e.to_string(),
+            })?,
+    };
+    let mut analysis = Analysis::default();
+    for a in inputs {
+        analysis.files.extend(a.files);
+        analysis.warnings.extend(a.warnings);
+        analysis.crates.extend(a.crates);
+        analysis.truncated = analysis.truncated.or(a.truncated);
+        for (key, value) in a.extra {
+            analysis.extra.entry(key).or_insert(value);
+        }
+    }
+    Ok(analysis)
+}
+
+/// Merge analyses into one, ordered by file path.
+///
+/// A file seen more than once is kept once; its nodes are unioned, matching
+/// nodes by kind, name and span so shared calls and children are not
+/// duplicated. If the copies were taken from different file contents (their
+/// File metadata differs), a [`Conflict`] is reported and the union kept.
+/// Warnings and crate edges are concatenated, dropping exact duplicates;
+/// unknown top-level keys are kept from the first input that has them.
+pub fn merge(inputs: Vec<Analysis>) -> (Analysis, Vec<Conflict>) {
+    let mut files: BTreeMap<String, Vec<Syntax>> = BTreeMap::new();
+    let mut warnings = Vec::new();
+    let mut crates = Vec::new();
+    let mut extra = serde_json::Map::new();
+    let mut conflicts = Vec::new();
+
+    for input in inputs {
+        for w in input.warnings {
+            if !warnings.contains(&w) {
+                warnings.push(w);
+            }
+        }
+        for e in input.crates {
+            if !crates.contains(&e) {
+                crates.push(e);
+            }
+        }
+        for (key, value) in input.extra {
+            extra.entry(key).or_insert(value);
+        }
+        merge_files(&mut files, input.files, &mut conflicts);
+    }
+
+    let files = files.into_values().collect();
+    (
+        Analysis {
+            files,
+            warnings,
+            crates,
+            extra,
+            ..Default::default()
+        },
+        conflicts,
+    )
+}
+
+fn merge_files<'s>(
+    files: &mut BTreeMap<String, Vec<Syntax<'s>>>,
+    inputs: Vec<Vec<Syntax<'s>>>,
+    conflicts: &mut Vec<Conflict>,
+) {
+    for nodes in inputs {
+        let Some(ASTNode::File(file)) = nodes.first().map(|s| &s.node) else {
+            continue;
+        };
+        let path = file.path.to_string();
+        match files.get_mut(&path) {
+            None => {
+                files.insert(path, nodes);
+            }
+            Some(existing) => {
+                let (a, b) = (&existing[0].metadata, &nodes[0].metadata);
+                if (a.chars, a.lines, a.words) != (b.chars, b.lines, b.words) {
+                    conflicts.push(Conflict {
+                        path,
+                        reason: format!(
+                            "contents differ ({} vs {} lines, {} vs {} chars)",
+                            a.lines, b.lines, a.chars, b.chars
+                        ),
+                    });
+                }
+                union(existing, nodes.into_iter().skip(1).collect());
+            }
+        }
+    }
+}
+
+fn union<'s>(into: &mut Vec<Syntax<'s>>, from: Vec<Syntax<'s>>) {
+    for s in from {
+        let key = identity(&s);
+        match into.iter_mut().find(|t| identity(t) == key) {
+            Some(t) => union(&mut t.contains, s.contains),
+            None => into.push(s),
+        }
+    }
+}
+
+/// Node payload plus span; two nodes with the same identity are the same node.
+fn identity(s: &Syntax) -> (String, usize, usize) {
+    let node = serde_json::to_string(&s.node).unwrap_or_default();
+    (node, s.metadata.span.start_line, s.metadata.span.end_line)
+}
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::{Warning, WarningKind};
+    use crate::api::tree_sitter::analyze;
+    use crate::api::tree_sitter::py::Python;
+    use tempfile::TempDir;
+
+    fn input(files: Vec<Vec<Syntax<'static>>>) -> Analysis {
+        Analysis {
+            files,
+            ..Default::default()
+        }
+    }
+
+    fn paths(files: &[Vec<Syntax>]) -> Vec<String> {
+        files
+            .iter()
+            .filter_map(|n| match &n[0].node {
+                ASTNode::File(f) => Some(f.path.to_string()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn count_calls(nodes: &[Syntax]) -> usize {
+        nodes
+            .iter()
+            .map(|s| matches!(s.node, ASTNode::Call(_)) as usize + count_calls(&s.contains))
+            .sum()
+    }
+
+    #[test]
+    fn disjoint_inputs_concatenated_sorted() {
+        let (merged, conflicts) = merge(vec![
+            input(vec![analyze(&Python, "b.py", "x = 1\n")]),
+            input(vec![analyze(&Python, "a.py", "y = 2\n")]),
+        ]);
+        assert_eq!(paths(&merged.files), vec!["a.py", "b.py"]);
+        assert!(conflicts.is_empty());
+    }
+
+    #[test]
+    fn identical_files_deduplicated() {
+        let src = "def f():\n    g()\n";
+        let (merged, conflicts) = merge(vec![
+            input(vec![analyze(&Python, "a.py", src)]),
+            input(vec![analyze(&Python, "a.py", src)]),
+        ]);
+        assert_eq!(merged.files.len(), 1);
+        assert_eq!(count_calls(&merged.files[0]), 1);
+        assert!(conflicts.is_empty());
+    }
+
+    #[test]
+    fn differing_files_unioned_with_conflict() {
+        let (merged, conflicts) = merge(vec![
+            input(vec![analyze(&Python, "a.py", "def f():\n    g()\n")]),
+            input(vec![analyze(
+                &Python,
+                "a.py",
+                "def f():\n    g()\n    h()\n",
+            )]),
+        ]);
+        let files = merged.files;
+        assert_eq!(files.len(), 1);
+        // f matches by name but not span, so both versions are kept
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].path, "a.py");
+        assert!(count_calls(&files[0]) >= 2);
+    }
+
+    #[test]
+    fn union_merges_children_of_matching_nodes() {
+        let mut a = analyze(&Python, "a.py", "def f():\n    g()\n");
+        let b = analyze(&Python, "a.py", "def f():\n    h()\n");
+        union(&mut a, b.into_iter().skip(1).collect());
+        let f = a
+            .iter()
+            .find(|s| matches!(s.node, ASTNode::Function(_)))
+            .unwrap();
+        assert_eq!(count_calls(&f.contains), 2);
+    }
+
+    #[test]
+    fn load_roundtrip() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("a.json");
+        let files = vec![analyze(&Python, "a.py", "def f():\n    g()\n")];
+        fs::write(&path, serde_json::to_string(&files).unwrap()).unwrap();
+        let loaded = load(&path).unwrap();
+        assert_eq!(paths(&loaded.files), vec!["a.py"]);
+        assert_eq!(count_calls(&loaded.files[0]), 1);
+    }
+
+    #[test]
+    fn load_envelope_keeps_warnings() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("a.json");
+        let mut analysis = input(vec![analyze(&Python, "a.py", "x = 1\n")]);
+        analysis.warnings.push(Warning::new(
+            WarningKind::SkippedFile,
+            "big.py",
+            "too large",
+        ));
+        fs::write(&path, serde_json::to_string(&analysis).unwrap()).unwrap();
+        let loaded = load(&path).unwrap();
+        assert_eq!(loaded.files.len(), 1);
+        assert_eq!(loaded.warnings, analysis.warnings);
+    }
+
+    #[test]
+    fn warnings_concatenated_without_duplicates() {
+        let a = Warning::new(WarningKind::LossyDecode, "a.py", "invalid UTF-8");
+        let b = Warning::new(WarningKind::LossyDecode, "b.py", "invalid UTF-8");
+        let (merged, _) = merge(vec![
+            Analysis {
+                warnings: vec![a.clone()],
+                ..Default::default()
+            },
+            Analysis {
+                warnings: vec![a.clone(), b.clone()],
+                ..Default::default()
+            },
+        ]);
+        assert_eq!(merged.warnings, vec![a, b]);
+    }
+
+    #[test]
+    fn load_joins_ndjson_lines() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("a.ndjson");
+        let mut a = input(vec![analyze(&Python, "a.py", "x = 1\n")]);
+        a.warnings.push(Warning::new(
+            WarningKind::LossyDecode,
+            "a.py",
+            "invalid UTF-8",
+        ));
+        let b = input(vec![analyze(&Python, "b.py", "x = 1\n")]);
+        let lines = [&a, &b].map(|a| serde_json::to_string(a).unwrap());
+        fs::write(&path, lines.join("\n") + "\n").unwrap();
+        let loaded = load(&path).unwrap();
+        assert_eq!(paths(&loaded.files), vec!["a.py", "b.py"]);
+        assert_eq!(loaded.warnings, a.warnings);
+
+        fs::write(&path, format!("{}\n{{", lines[0])).unwrap();
+        assert!(matches!(load(&path), Err(BoloError::Parse { .. })));
+    }
+
+    #[test]
+    fn load_keeps_fields_from_newer_versions() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("new.json");
+        fs::write(
+            &path,
+            r#"{"files":[[{"node":{"File":{"path":"a.py","owner":"core"}},
+                "metadata":{"chars":0,"lines":1,"words":0,"whitespaces":0,"newlines":0,
+                "span":{"start_line":1,"end_line":1},"entropy":0.5},
+                "contains":[],"hash":"f00"}]],"warnings":[],"schema":9}"#,
+        )
+        .unwrap();
+        let (merged, _) = merge(vec![load(&path).unwrap()]);
+        let out = serde_json::to_value(&merged).unwrap();
+        assert_eq!(out["schema"], 9);
+        let file = &out["files"][0][0];
+        assert_eq!(file["node"]["File"]["owner"], "core");
+        assert_eq!(file["metadata"]["entropy"], 0.5);
+        assert_eq!(file["hash"], "f00");
+
+        let again = dir.path().join("again.json");
+        fs::write(&again, out.to_string()).unwrap();
+        let reloaded = serde_json::to_value(load(&again).unwrap()).unwrap();
+        assert_eq!(reloaded, out);
+    }
+
+    #[test]
+    fn load_invalid_json_errors() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("bad.json");
+        fs::write(&path, "{").unwrap();
+        assert!(matches!(load(&path), Err(BoloError::Parse { .. })));
+    }
+}
@@ -0,0 +1,492 @@
+use crate::api::tree_sitter::{ASTNode, Syntax};
+use std::borrow::Cow;
+use std::collections::HashSet;
+
+/// Set [`File::module`](crate::api::tree_sitter::File::module) on every
+/// Python and Rust file: its dotted module path (`pkg.sub.m`) or its path
+/// in the crate's module tree (`crate::a::b`). Package and crate roots are
+/// found among the analyzed files, so paths are as complete as the tree.
+pub fn assign(files: &mut [Vec<Syntax>]) {
+    let known: HashSet<String> = files
+        .iter()
+        .filter_map(|n| match n.first().map(|s| &s.node) {
+            Some(ASTNode::File(f)) => Some(f.path.to_string()),
+            _ => None,
+        })
+        .collect();
+    for nodes in files {
+        resolve_file(nodes, &known);
+        assign_file(nodes, &known);
+    }
+}
+
+/// [`assign`] for one file's nodes, with `known` the paths of every file
+/// in the tree.
+///
+/// Also make Rust `self::` and `super::` paths absolute, so
+/// `super::util::run()` in `crate::net::http` is `crate::net::util::run`.
+pub fn assign_file(nodes: &mut [Syntax], known: &HashSet<String>) {
+    let Some((first, rest)) = nodes.split_first_mut() else {
+        return;
+    };
+    let ASTNode::File(f) = &mut first.node else {
+        return;
+    };
+    let module = match f.path.rsplit_once('.') {
+        Some((_, "py")) => python(&f.path, known),
+        Some((_, "rs")) => {
+            let module = rust(&f.path, known);
+            qualify_rust(rest, &module);
+            Some(module)
+        }
+        _ => None,
+    };
+    f.module = module.map(Cow::Owned);
+}
+
+/// Resolve one file's imports against `known`, the paths of every file in
+/// the tree: point each Rust `mod foo;` at the file it declares (see
+/// [`declared`]), and make the names Python relative imports bring in
+/// absolute, so calls, imports, exceptions and route handlers through
+/// `from .models import Request` in `pkg/api.py` name `pkg.models.Request`.
+pub fn resolve_file(nodes: &mut [Syntax], known: &HashSet<String>) {
+    let Some((first, rest)) = nodes.split_first_mut() else {
+        return;
+    };
+    let ASTNode::File(f) = &first.node else {
+        return;
+    };
+    match f.path.rsplit_once('.') {
+        Some((_, "rs")) => link(rest, &f.path, known),
+        Some((_, "py")) => {
+            let module = python(&f.path, known);
+            let package = match &module {
+                Some(m) if f.path.rsplit('/').next() == Some("__init__.py") => Some(&**m),
+                Some(m) => m.rsplit_once('.').map(|(p, _)| p),
+                None => None,
+            };
+            if let Some(package) = package {
+                qualify(rest, &|name| relative(name, package));
+            }
+        }
+        _ => {}
+    }
+}
+
+fn link(nodes: &mut [Syntax], path: &str, known: &HashSet<String>) {
+    for s in nodes {
+        match &mut s.node {
+            ASTNode::Module(m) if m.inline => {
+                let inner = nested(path, &m.name);
+                link(&mut s.contains, &inner, known);
+            }
+            ASTNode::Module(m) => {
+                m.file = declared(path, &m.name, m.path.as_deref())
+                    .into_iter()
+                    .find(|c| known.contains(c))
+                    .map(Cow::Owned);
+            }
+            _ => link(&mut s.contains, path, known),
+        }
+    }
+}
+
+/// Rewrite the names under `nodes` that `absolute` makes absolute.
+fn qualify(nodes: &mut [Syntax], absolute: &impl Fn(&str) -> Option<String>) {
+    for s in nodes {
+        let name = match &mut s.node {
+            ASTNode::Call(c) => Some(&mut c.name),
+            ASTNode::Import(i) => Some(&mut i.module),
+            ASTNode::Exception(e) => Some(&mut e.name),
+            ASTNode::Endpoint(e) => Some(&mut e.handler),
+            _ => None,
+        };
+        if let Some(name) = name
+            && let Some(full) = absolute(name)
+        {
+            *name = Cow::Owned(full);
+        }
+        qualify(&mut s.contains, absolute);
+    }
+}
+
+/// [`qualify`] with [`absolute`] for Rust nodes in `module`, descending into
+/// inline `mod foo { .. }` bodies as `module::foo`.
+fn qualify_rust(nodes: &mut [Syntax], module: &str) {
+    for s in nodes {
+        match &s.node {
+            ASTNode::Module(m) if m.inline => {
+                let inner = format!("{module}::{}", m.name);
+                qualify_rust(&mut s.contains, &inner);
+            }
+            _ => qualify(std::slice::from_mut(s), &|name| absolute(name, module)),
+        }
+    }
+}
+
+/// `a/pkg/sub/m.py` → `pkg.sub.m` when `pkg/` and `sub/` hold an
+/// `__init__.py` and `a/` does not; `pkg/__init__.py` → `pkg`. A file in a
+/// directory without `__init__.py` is a top-level module. `None` for an
+/// `__init__.py` at the root, whose package name is not in the path.
+pub fn python(path: &str, known: &HashSet<String>) -> Option<String> {
+    let (dir, file) = path.rsplit_once('/').unwrap_or(("", path));
+    let stem = file.trim_end_matches(".py");
+    let mut parts = Vec::new();
+    if stem != "__init__" {
+        parts.push(stem);
+    }
+    let mut dir = dir;
+    while !dir.is_empty() && known.contains(&format!("{dir}/__init__.py")) {
+        let (parent, name) = dir.rsplit_once('/').unwrap_or(("", dir));
+        parts.push(name);
+        dir = parent;
+    }
+    parts.reverse();
+    (!parts.is_empty()).then(|| parts.join("."))
+}
+
+/// `src/a/b.rs` → `crate::a::b` under `src/lib.rs` or `src/main.rs`;
+/// `src/a/mod.rs` → `crate::a`. Crate roots — `lib.rs`, `main.rs`, files in
+/// `bin/`, `tests/`, `examples/` and `benches/` — are `crate`. Without a
+/// root above it, a file's path counts from the top of the tree.
+pub fn rust(path: &str, known: &HashSet<String>) -> String {
+    let (dir, file) = path.rsplit_once('/').unwrap_or(("", path));
+    let stem = file.trim_end_matches(".rs");
+    let parent = dir.rsplit('/').next().unwrap_or(dir);
+    if matches!(stem, "lib" | "main") || matches!(parent, "bin" | "tests" | "examples" | "benches")
+    {
+        return "crate".to_string();
+    }
+
+    let is_root = |d: &str| {
+        ["lib.rs", "main.rs"].iter().any(|f| {
+            let root = if d.is_empty() {
+                f.to_string()
+            } else {
+                format!("{d}/{f}")
+            };
+            known.contains(&root)
+        })
+    };
+    let mut root = Some(dir);
+    while let Some(d) = root.filter(|d| !is_root(d)) {
+        root = (!d.is_empty()).then(|| d.rsplit_once('/').map_or("", |(p, _)| p));
+    }
+    // No root anywhere above: a lone top-level file is one.
+    if root.is_none() && dir.is_empty() {
+        return "crate".to_string();
+    }
+
+    let rest = match root {
+        Some("") | None => dir,
+        Some(r) => dir[r.len()..].trim_start_matches('/'),
+    };
+    let mut out = String::from("crate");
+    for part in rest.split('/').filter(|p| !p.is_empty()) {
+        out.push_str("::");
+        out.push_str(part);
+    }
+    if stem != "mod" {
+        out.push_str("::");
+        out.push_str(stem);
+    }
+    out
+}
+
+/// The files `mod name;` in the Rust file `path` may live in, in order of
+/// preference. Declared in `dir/lib.rs`, `main.rs` or `mod.rs`, `name` is
+/// `dir/name.rs` or `dir/name/mod.rs`; declared in `dir/bar.rs`, it is
+/// `dir/bar/name.rs` or `dir/bar/name/mod.rs`. A `#[path]` is relative to
+/// `dir`.
+pub fn declared(path: &str, name: &str, attr: Option<&str>) -> Vec<String> {
+    let (dir, stem) = match path.rsplit_once('/') {
+        Some((d, f)) => (format!("{d}/"), f.trim_end_matches(".rs")),
+        None => (String::new(), path.trim_end_matches(".rs")),
+    };
+    if let Some(p) = attr {
+        return vec![format!("{dir}{p}")];
+    }
+    let owner = if matches!(stem, "lib" | "main" | "mod") {
+        dir
+    } else {
+        format!("{dir}{stem}/")
+    };
+    vec![format!("{owner}{name}.rs"), format!("{owner}{name}/mod.rs")]
+}
+
+/// The path declarations inside the inline `mod name { .. }` of the Rust
+/// file `path` resolve from, as if its body were a file of its own:
+/// `mod b;` inside `mod a { .. }` in `src/lib.rs` is `src/a/b.rs`.
+pub fn nested(path: &str, name: &str) -> String {
+    let (dir, stem) = match path.rsplit_once('/') {
+        Some((d, f)) => (format!("{d}/"), f.trim_end_matches(".rs")),
+        None => (String::new(), path.trim_end_matches(".rs")),
+    };
+    match stem {
+        "lib" | "main" | "mod" => format!("{dir}{name}/mod.rs"),
+        _ => format!("{dir}{stem}/{name}/mod.rs"),
+    }
+}
+
+/// `name` from the top of the tree when it is a relative Python name, as
+/// written in a module of `package`: `.models.Request` in `pkg.api` is
+/// `pkg.api.models.Request` and `..util.run` is `pkg.util.run`. `None` for
+/// other names and for ones climbing above the tree.
+pub fn relative(name: &str, package: &str) -> Option<String> {
+    let rest = name.trim_start_matches('.');
+    let dots = name.len() - rest.len();
+    let mut base: Vec<&str> = package.split('.').collect();
+    base.truncate((base.len() + 1).checked_sub(dots)?);
+    (dots > 0 && !base.is_empty()).then(|| match rest {
+        "" => base.join("."),
+        _ => format!("{}.{rest}", base.join(".")),
+    })
+}
+
+/// `path` from the crate root when it starts with `self::` or `super::`,
+/// as written in `module`: `super::a` in `crate::m::n` is `crate::m::a`.
+/// `None` for other paths and for ones climbing above the crate.
+pub fn absolute(path: &str, module: &str) -> Option<String> {
+    let mut base: Vec<&str> = module.split("::").collect();
+    let mut rest = path;
+    loop {
+        if let Some(r) = rest.strip_prefix("self::") {
+            rest = r;
+        } else if let Some(r) = rest.strip_prefix("super::") {
+            base.pop();
+            rest = r;
+        } else {
+            break;
+        }
+    }
+    (rest.len() < path.len() && !base.is_empty()).then(|| format!("{}::{rest}", base.join("::")))
+}
+
+/// A definition's symbol: its qualified name (`Client.send`) under its
+/// file's module, `pkg.net.Client.send` or `crate::net::Client::send`.
+pub fn symbol(module: &str, qualified: &str) -> String {
+    if module == "crate" || module.starts_with("crate::") {
+        format!("{module}::{}", qualified.replace('.', "::"))
+    } else {
+        format!("{module}.{qualified}")
+    }
+}
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::tree_sitter::analyze;
+
+    fn known(paths: &[&str]) -> HashSet<String> {
+        paths.iter().map(|p| p.to_string()).collect()
+    }
+
+    // ── Python ──
+
+    #[test]
+    fn python_packages_from_init_files() {
+        let k = known(&[
+            "src/pkg/__init__.py",
+            "src/pkg/sub/__init__.py",
+            "src/pkg/sub/m.py",
+            "src/pkg/util.py",
+        ]);
+        assert_eq!(python("src/pkg/sub/m.py", &k).unwrap(), "pkg.sub.m");
+        assert_eq!(python("src/pkg/util.py", &k).unwrap(), "pkg.util");
+        assert_eq!(python("src/pkg/sub/__init__.py", &k).unwrap(), "pkg.sub");
+    }
+
+    #[test]
+    fn python_without_package_is_top_level() {
+        let k = known(&["scripts/run.py", "__init__.py", "app.py"]);
+        assert_eq!(python("scripts/run.py", &k).unwrap(), "run");
+        assert_eq!(python("app.py", &k).unwrap(), "app");
+        assert_eq!(python("__init__.py", &k), None);
+    }
+
+    #[test]
+    fn relative_names_from_the_package() {
+        assert_eq!(
+            relative(".models.Request", "pkg.api").unwrap(),
+            "pkg.api.models.Request"
+        );
+        assert_eq!(relative("..util.run", "pkg.api").unwrap(), "pkg.util.run");
+        assert_eq!(relative(".", "pkg").unwrap(), "pkg");
+        assert_eq!(relative("...x", "pkg.api"), None);
+        assert_eq!(relative("..x", "pkg"), None);
+        assert_eq!(relative("os.path.join", "pkg"), None);
+    }
+
+    #[test]
+    fn python_files_get_absolute_relative_names() {
+        use crate::api::tree_sitter::py::Python;
+
+        let k = known(&["pkg/__init__.py", "pkg/api/__init__.py", "pkg/api/views.py"]);
+        let names = |path: &str, source: &str| {
+            let mut nodes = analyze(&Python, path, source);
+            resolve_file(&mut nodes, &k);
+            nodes
+                .iter()
+                .flat_map(|s| std::iter::once(s).chain(&s.contains))
+                .filter_map(|s| match &s.node {
+                    ASTNode::Import(i) => Some(i.module.to_string()),
+                    ASTNode::Call(c) => Some(c.name.to_string()),
+                    ASTNode::Exception(e) => Some(e.name.to_string()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+        let source = "from .models import Request\nfrom ..errors import Bad\n\
+                      def f():\n    Request()\n    raise Bad()\n";
+        assert_eq!(
+            names("pkg/api/views.py", source),
+            [
+                "pkg.api.models",
+                "pkg.errors",
+                "pkg.api.models.Request",
+                "pkg.errors.Bad",
+                "pkg.errors.Bad"
+            ]
+        );
+        assert_eq!(
+            names(
+                "pkg/api/__init__.py",
+                "from . import views\nviews.index()\n"
+            ),
+            ["pkg.api", "pkg.api.views.index"]
+        );
+        // Outside a package there is nothing to be relative to.
+        assert_eq!(names("run.py", "from .m import g\ng()\n"), [".m", ".m.g"]);
+    }
+
+    // ── Rust ──
+
+    #[test]
+    fn rust_module_tree_from_crate_root() {
+        let k = known(&["src/lib.rs", "src/a/mod.rs", "src/a/b.rs", "src/c.rs"]);
+        assert_eq!(rust("src/lib.rs", &k), "crate");
+        assert_eq!(rust("src/a/mod.rs", &k), "crate::a");
+        assert_eq!(rust("src/a/b.rs", &k), "crate::a::b");
+        assert_eq!(rust("src/c.rs", &k), "crate::c");
+    }
+
+    #[test]
+    fn rust_other_crate_roots() {
+        let k = known(&["src/main.rs", "src/bin/tool.rs", "tests/it.rs", "build.rs"]);
+        assert_eq!(rust("src/bin/tool.rs", &k), "crate");
+        assert_eq!(rust("tests/it.rs", &k), "crate");
+        assert_eq!(rust("build.rs", &k), "crate");
+    }
+
+    #[test]
+    fn rust_without_root_counts_from_top() {
+        let k = known(&["net/http.rs", "net/mod.rs"]);
+        assert_eq!(rust("net/http.rs", &k), "crate::net::http");
+        assert_eq!(rust("net/mod.rs", &k), "crate::net");
+    }
+
+    #[test]
+    fn mod_declarations_by_declaring_file() {
+        assert_eq!(
+            declared("src/lib.rs", "net", None),
+            ["src/net.rs", "src/net/mod.rs"]
+        );
+        assert_eq!(
+            declared("src/net.rs", "http", None),
+            ["src/net/http.rs", "src/net/http/mod.rs"]
+        );
+        assert_eq!(declared("src/net/mod.rs", "tls", None)[0], "src/net/tls.rs");
+        assert_eq!(
+            declared("src/lib.rs", "sys", Some("sys/unix.rs")),
+            ["src/sys/unix.rs"]
+        );
+    }
+
+    #[test]
+    fn relative_paths_from_the_crate_root() {
+        assert_eq!(
+            absolute("super::util::run", "crate::net::http").unwrap(),
+            "crate::net::util::run"
+        );
+        assert_eq!(
+            absolute("super::super::main", "crate::a::b").unwrap(),
+            "crate::main"
+        );
+        assert_eq!(absolute("self::f", "crate::a").unwrap(), "crate::a::f");
+        assert_eq!(absolute("super::f", "crate"), None);
+        assert_eq!(absolute("crate::a::f", "crate::b"), None);
+        assert_eq!(absolute("util::run", "crate::b"), None);
+    }
+
+    #[test]
+    fn rust_files_get_module_files_and_absolute_calls() {
+        use crate::api::tree_sitter::rs::Rust;
+
+        let source = "mod tls;\nmod gone;\nfn f() { super::util::run(); self::g(); crate::h(); }\n";
+        let mut nodes = analyze(&Rust, "src/net/mod.rs", source);
+        let k = known(&["src/lib.rs", "src/net/mod.rs", "src/net/tls.rs"]);
+        resolve_file(&mut nodes, &k);
+        assign_file(&mut nodes, &k);
+
+        let files: Vec<_> = nodes
+            .iter()
+            .filter_map(|s| match &s.node {
+                ASTNode::Module(m) => Some(m.file.as_deref()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(files, [Some("src/net/tls.rs"), None]);
+        let calls: Vec<_> = nodes
+            .iter()
+            .flat_map(|s| &s.contains)
+            .filter_map(|s| match &s.node {
+                ASTNode::Call(c) => Some(&*c.name),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(calls, ["crate::util::run", "crate::net::g", "crate::h"]);
+    }
+
+    #[test]
+    fn inline_modules_nest_declarations_and_paths() {
+        use crate::api::tree_sitter::rs::Rust;
+
+        assert_eq!(nested("src/lib.rs", "sys"), "src/sys/mod.rs");
+        assert_eq!(nested("src/net.rs", "sys"), "src/net/sys/mod.rs");
+
+        let source = "mod sys {\n    mod unix;\n    fn f() { super::g(); self::h(); }\n}\n";
+        let mut nodes = analyze(&Rust, "src/lib.rs", source);
+        let k = known(&["src/lib.rs", "src/sys/unix.rs"]);
+        resolve_file(&mut nodes, &k);
+        assign_file(&mut nodes, &k);
+
+        let inner = &nodes[1].contains;
+        let ASTNode::Module(m) = &inner[0].node else {
+            panic!("expected module, got {:?}", inner[0].node);
+        };
+        assert_eq!(m.file.as_deref(), Some("src/sys/unix.rs"));
+        let calls: Vec<_> = inner[1]
+            .contains
+            .iter()
+            .filter_map(|s| match &s.node {
+                ASTNode::Call(c) => Some(&*c.name),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(calls, ["crate::g", "crate::sys::h"]);
+    }
+
+    // ── Symbols ──
+
+    #[test]
+    fn symbols_use_the_language_separator() {
+        assert_eq!(symbol("pkg.net", "Client.send"), "pkg.net.Client.send");
+        assert_eq!(
+            symbol("crate::net", "Client.send"),
+            "crate::net::Client::send"
+        );
+        assert_eq!(symbol("crate", "main"), "crate::main");
+    }
+}
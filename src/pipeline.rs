@@ -0,0 +1,356 @@
+use crate::analysis::{self, Warning};
+use crate::api::tree_sitter::{Options, Syntax};
+use crate::clean;
+use crate::error::BoloError;
+use crate::modules;
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::Arc;
+
+/// The passes run when none are chosen, in order.
+pub const DEFAULT: [&str; 4] = [
+    "metrics",
+    "strip-comments",
+    "resolve-imports",
+    "qualify-names",
+];
+
+/// One transform over a file's nodes after parsing: `nodes[0]` is its File
+/// node (see [`clean::prepare`]). Implement it to add a pass of your own and
+/// [`Registry::register`] it.
+pub trait Pass: Send + Sync {
+    /// The name the pass is chosen by, e.g. `strip-comments`.
+    fn name(&self) -> &str;
+
+    /// Transform `nodes`, returning warnings about them.
+    fn run(&self, nodes: &mut Vec<Syntax<'static>>, cx: &Context) -> Vec<Warning>;
+}
+
+/// What a [`Pass`] sees besides the nodes.
+#[derive(Debug, Clone, Copy)]
+pub struct Context<'a> {
+    /// The file's source, as parsed.
+    pub source: &'a str,
+    /// The reported paths of every walked file, including those a shard,
+    /// sample or `--stdin` run does not analyze; `None` skips the passes
+    /// that resolve across files.
+    pub known: Option<&'a HashSet<String>>,
+    pub options: &'a Options,
+}
+
+/// The passes that can be chosen by name: the built-in ones and any
+/// registered.
+#[derive(Clone)]
+pub struct Registry {
+    passes: Vec<Arc<dyn Pass>>,
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        Registry {
+            passes: vec![
+                Arc::new(Metrics),
+                Arc::new(StripComments),
+                Arc::new(ResolveImports),
+                Arc::new(QualifyNames),
+            ],
+        }
+    }
+}
+
+impl Registry {
+    /// Add `pass`, replacing any registered under the same name.
+    pub fn register(&mut self, pass: impl Pass + 'static) {
+        self.passes.retain(|p| p.name() != pass.name());
+        self.passes.push(Arc::new(pass));
+    }
+
+    /// The registered names, built-in ones first.
+    pub fn names(&self) -> Vec<&str> {
+        self.passes.iter().map(|p| p.name()).collect()
+    }
+
+    /// The pipeline running `names` in the order given.
+    pub fn pipeline(&self, names: &[impl AsRef<str>]) -> Result<Pipeline, BoloError> {
+        let passes = names
+            .iter()
+            .map(|name| {
+                let name = name.as_ref();
+                self.passes
+                    .iter()
+                    .find(|p| p.name() == name)
+                    .cloned()
+                    .ok_or_else(|| BoloError::Pass {
+                        name: name.to_string(),
+                        known: self.names().join(", "),
+                    })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Pipeline { passes })
+    }
+}
+
+/// Passes run in order over each file's nodes; [`DEFAULT`] unless chosen
+/// with `--passes` or `passes` in bolo.toml.
+#[derive(Clone)]
+pub struct Pipeline {
+    passes: Vec<Arc<dyn Pass>>,
+}
+
+impl Default for Pipeline {
+    fn default() -> Self {
+        Registry::default()
+            .pipeline(&DEFAULT)
+            .expect("default passes are built in")
+    }
+}
+
+impl fmt::Debug for Pipeline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.names()).finish()
+    }
+}
+
+impl Pipeline {
+    pub fn names(&self) -> Vec<&str> {
+        self.passes.iter().map(|p| p.name()).collect()
+    }
+
+    /// Run every pass over `nodes`, collecting their warnings.
+    pub fn run(&self, nodes: &mut Vec<Syntax<'static>>, cx: &Context) -> Vec<Warning> {
+        self.passes.iter().flat_map(|p| p.run(nodes, cx)).collect()
+    }
+}
+
+// ── Built-in passes ─────────────────────────────────────────────────
+
+/// Comment, code, definition and documentation counts on the File node;
+/// run it before `strip-comments`, which moves or drops what it counts.
+struct Metrics;
+
+impl Pass for Metrics {
+    fn name(&self) -> &str {
+        "metrics"
+    }
+
+    fn run(&self, nodes: &mut Vec<Syntax<'static>>, cx: &Context) -> Vec<Warning> {
+        clean::measure(cx.source, nodes);
+        vec![]
+    }
+}
+
+/// Apply [`Options::comments`]; without this pass comments stay in place.
+struct StripComments;
+
+impl Pass for StripComments {
+    fn name(&self) -> &str {
+        "strip-comments"
+    }
+
+    fn run(&self, nodes: &mut Vec<Syntax<'static>>, cx: &Context) -> Vec<Warning> {
+        clean::strip(nodes, cx.options.comments);
+        vec![]
+    }
+}
+
+/// Point Rust `mod foo;` declarations at their files, warning about those
+/// not analyzed, and make names from Python relative imports absolute; see
+/// [`modules::resolve_file`].
+struct ResolveImports;
+
+impl Pass for ResolveImports {
+    fn name(&self) -> &str {
+        "resolve-imports"
+    }
+
+    fn run(&self, nodes: &mut Vec<Syntax<'static>>, cx: &Context) -> Vec<Warning> {
+        let Some(known) = cx.known else {
+            return vec![];
+        };
+        modules::resolve_file(nodes, known);
+        analysis::unresolved_in(nodes, known)
+    }
+}
+
+/// Module paths on File nodes and absolute Rust `self::`/`super::` paths;
+/// see [`modules::assign_file`].
+struct QualifyNames;
+
+impl Pass for QualifyNames {
+    fn name(&self) -> &str {
+        "qualify-names"
+    }
+
+    fn run(&self, nodes: &mut Vec<Syntax<'static>>, cx: &Context) -> Vec<Warning> {
+        if let Some(known) = cx.known {
+            modules::assign_file(nodes, known);
+        }
+        vec![]
+    }
+}
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::tree_sitter::py::Python;
+    use crate::api::tree_sitter::rs::Rust;
+    use crate::api::tree_sitter::{ASTNode, Lang, analyze};
+
+    fn run(
+        lang: &dyn Lang,
+        path: &str,
+        source: &str,
+        pipeline: &Pipeline,
+        known: &HashSet<String>,
+    ) -> (Vec<Syntax<'static>>, Vec<Warning>) {
+        let opts = Options::default();
+        let mut parser = lang.get_parser();
+        let ast = lang.parse(&mut parser, source, &opts).unwrap();
+        let mut nodes: Vec<_> = clean::prepare(path, source, ast)
+            .into_iter()
+            .map(Syntax::into_owned)
+            .collect();
+        let cx = Context {
+            source,
+            known: Some(known),
+            options: &opts,
+        };
+        let warnings = pipeline.run(&mut nodes, &cx);
+        (nodes, warnings)
+    }
+
+    fn kinds(nodes: &[Syntax]) -> Vec<&'static str> {
+        nodes
+            .iter()
+            .map(|s| match s.node {
+                ASTNode::File(_) => "file",
+                ASTNode::Comment => "comment",
+                ASTNode::Function(_) => "fn",
+                _ => "other",
+            })
+            .collect()
+    }
+
+    const SOURCE: &str = "# top\ndef f():\n    # inside\n    g()\n";
+
+    #[test]
+    fn default_matches_clean() {
+        let known = HashSet::from(["a.py".to_string()]);
+        let (nodes, warnings) = run(&Python, "a.py", SOURCE, &Pipeline::default(), &known);
+        assert!(warnings.is_empty());
+        let mut cleaned = analyze(&Python, "a.py", SOURCE);
+        modules::resolve_file(&mut cleaned, &known);
+        modules::assign_file(&mut cleaned, &known);
+        assert_eq!(
+            serde_json::to_value(&nodes).unwrap(),
+            serde_json::to_value(&cleaned).unwrap()
+        );
+        assert_eq!(
+            format!("{:?}", Pipeline::default()),
+            r#"["metrics", "strip-comments", "resolve-imports", "qualify-names"]"#
+        );
+    }
+
+    #[test]
+    fn only_chosen_passes_run() {
+        let known = HashSet::from(["a.py".to_string()]);
+        let none = Registry::default().pipeline(&[] as &[&str]).unwrap();
+        let (nodes, _) = run(&Python, "a.py", SOURCE, &none, &known);
+        assert_eq!(kinds(&nodes), ["file", "comment", "fn"]);
+        assert!(nodes[0].metadata.metrics.is_empty());
+        let ASTNode::File(f) = &nodes[0].node else {
+            unreachable!()
+        };
+        assert_eq!(f.module, None);
+
+        let strip = Registry::default().pipeline(&["strip-comments"]).unwrap();
+        let (nodes, _) = run(&Python, "a.py", SOURCE, &strip, &known);
+        assert_eq!(kinds(&nodes), ["file", "comment", "fn"]);
+        assert!(
+            nodes[2]
+                .contains
+                .iter()
+                .all(|s| !matches!(s.node, ASTNode::Comment))
+        );
+        assert!(nodes[0].metadata.metrics.is_empty());
+    }
+
+    #[test]
+    fn resolve_imports_warns_and_links() {
+        let known = HashSet::from(["lib.rs".to_string(), "here.rs".to_string()]);
+        let resolve = Registry::default().pipeline(&["resolve-imports"]).unwrap();
+        let source = "mod here;\nmod gone;\n";
+        let (nodes, warnings) = run(&Rust, "lib.rs", source, &resolve, &known);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].message.contains("`gone`"));
+        let files: Vec<_> = nodes
+            .iter()
+            .filter_map(|s| match &s.node {
+                ASTNode::Module(m) => Some(m.file.as_deref()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(files, [Some("here.rs"), None]);
+    }
+
+    struct Rename;
+
+    impl Pass for Rename {
+        fn name(&self) -> &str {
+            "rename"
+        }
+
+        fn run(&self, nodes: &mut Vec<Syntax<'static>>, _: &Context) -> Vec<Warning> {
+            for s in nodes {
+                if let ASTNode::Function(f) = &mut s.node {
+                    f.name = format!("renamed_{}", f.name).into();
+                }
+            }
+            vec![]
+        }
+    }
+
+    #[test]
+    fn resolve_imports_makes_python_relative_names_absolute() {
+        let known = HashSet::from(["pkg/__init__.py".to_string(), "pkg/a.py".to_string()]);
+        let source = "from .models import Request\ndef f():\n    Request()\n";
+        let calls = |names: &[&str]| {
+            let pipeline = Registry::default().pipeline(names).unwrap();
+            let (nodes, _) = run(&Python, "pkg/a.py", source, &pipeline, &known);
+            let ASTNode::Call(c) = &nodes[2].contains[0].node else {
+                unreachable!()
+            };
+            c.name.to_string()
+        };
+        assert_eq!(calls(&["resolve-imports"]), "pkg.models.Request");
+        assert_eq!(calls(&["qualify-names"]), ".models.Request");
+    }
+
+    #[test]
+    fn registered_passes_run_by_name() {
+        let mut registry = Registry::default();
+        registry.register(Rename);
+        assert_eq!(registry.names().last(), Some(&"rename"));
+        let pipeline = registry.pipeline(&["strip-comments", "rename"]).unwrap();
+        assert_eq!(pipeline.names(), ["strip-comments", "rename"]);
+        let (nodes, _) = run(&Python, "a.py", SOURCE, &pipeline, &HashSet::new());
+        let ASTNode::Function(f) = &nodes[2].node else {
+            unreachable!()
+        };
+        assert_eq!(f.name, "renamed_f");
+    }
+
+    #[test]
+    fn unknown_pass_names_the_known_ones() {
+        let err = Registry::default()
+            .pipeline(&["metrics", "nope"])
+            .unwrap_err()
+            .to_string();
+        assert_eq!(
+            err,
+            "unknown pass `nope` (known: metrics, strip-comments, resolve-imports, qualify-names)"
+        );
+    }
+}
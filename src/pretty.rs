@@ -1,4 +1,41 @@
 use colored::Colorize;
+use std::io::IsTerminal;
+
+/// When messages on stderr are colored.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum Color {
+    /// When stderr is a terminal, unless NO_COLOR or CLICOLOR=0 is set (CLICOLOR_FORCE wins)
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// Apply `choice` to every message printed after this.
+pub fn set_color(choice: Color) {
+    let on = match choice {
+        Color::Always => true,
+        Color::Never => false,
+        Color::Auto => auto(
+            |key| std::env::var(key).ok(),
+            std::io::stderr().is_terminal(),
+        ),
+    };
+    colored::control::set_override(on);
+}
+
+/// The `auto` decision from environment variables (`var`) and whether
+/// stderr is a terminal.
+fn auto(var: impl Fn(&str) -> Option<String>, terminal: bool) -> bool {
+    let set = |key| var(key).is_some_and(|v| !v.is_empty() && v != "0");
+    if set("CLICOLOR_FORCE") {
+        return true;
+    }
+    if var("NO_COLOR").is_some_and(|v| !v.is_empty()) || var("CLICOLOR").as_deref() == Some("0") {
+        return false;
+    }
+    terminal
+}
 
 pub fn error(msg: &str) {
     eprintln!("{} {msg}", "error:".red().bold());
@@ -15,3 +52,36 @@ pub fn success(msg: &str) {
 pub fn neutral(msg: &str) {
     eprintln!("{msg}");
 }
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env<'a>(vars: &'a [(&str, &str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        move |key| {
+            vars.iter()
+                .find(|(k, _)| *k == key)
+                .map(|(_, v)| v.to_string())
+        }
+    }
+
+    #[test]
+    fn auto_follows_terminal() {
+        assert!(auto(env(&[]), true));
+        assert!(!auto(env(&[]), false));
+    }
+
+    #[test]
+    fn auto_honors_environment() {
+        assert!(!auto(env(&[("NO_COLOR", "1")]), true));
+        assert!(auto(env(&[("NO_COLOR", "")]), true));
+        assert!(!auto(env(&[("CLICOLOR", "0")]), true));
+        assert!(auto(
+            env(&[("CLICOLOR_FORCE", "1"), ("NO_COLOR", "1")]),
+            false
+        ));
+        assert!(!auto(env(&[("CLICOLOR_FORCE", "0")]), false));
+    }
+}
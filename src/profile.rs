@@ -0,0 +1,509 @@
+use crate::api::tree_sitter::{ASTNode, Span, Syntax};
+use crate::error::BoloError;
+use serde::Deserialize;
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::Path;
+
+/// Metric keys set on Function nodes by [`attach`].
+pub const SAMPLES: &str = "samples";
+pub const SELF_SAMPLES: &str = "self_samples";
+pub const HOT: &str = "hot";
+
+/// Share of all samples a function's stacks must reach to be marked [`HOT`].
+const HOT_SHARE: f64 = 0.05;
+
+/// One stack frame as the profiler names it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Frame {
+    pub name: String,
+    pub file: Option<String>,
+    pub line: Option<usize>,
+}
+
+/// Sampled call stacks, root first, each with its sample count.
+#[derive(Debug, Clone, Default)]
+pub struct Profile {
+    pub stacks: Vec<(Vec<Frame>, u64)>,
+}
+
+impl Profile {
+    /// Read a speedscope JSON file (`py-spy record -f speedscope`) or folded
+    /// stacks (`stackcollapse-perf.pl`, `py-spy record -f raw`, inferno).
+    pub fn load(path: &Path) -> Result<Self, BoloError> {
+        let err = |reason| BoloError::Profile {
+            path: path.to_path_buf(),
+            reason,
+        };
+        let text = fs::read_to_string(path).map_err(|e| err(e.to_string()))?;
+        Profile::parse(&text).map_err(err)
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        if text.trim_start().starts_with('{') {
+            speedscope(text)
+        } else {
+            folded(text)
+        }
+    }
+
+    pub fn total(&self) -> u64 {
+        self.stacks.iter().map(|(_, n)| n).sum()
+    }
+}
+
+/// `main;parse;walk 42` per line; py-spy frames read `walk (src/fs.py:12)`.
+fn folded(text: &str) -> Result<Profile, String> {
+    let mut stacks = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim_end();
+        if line.is_empty() {
+            continue;
+        }
+        let (stack, count) = line
+            .rsplit_once(' ')
+            .and_then(|(s, n)| Some((s, n.parse::<u64>().ok()?)))
+            .ok_or_else(|| format!("line {}: expected `frame;frame;... COUNT`", i + 1))?;
+        stacks.push((stack.split(';').map(frame).collect(), count));
+    }
+    Ok(Profile { stacks })
+}
+
+fn frame(text: &str) -> Frame {
+    let located = text
+        .strip_suffix(')')
+        .and_then(|t| t.rsplit_once(" ("))
+        .and_then(|(name, loc)| {
+            let (file, line) = loc.rsplit_once(':')?;
+            Some((name, file, line.parse().ok()?))
+        });
+    match located {
+        Some((name, file, line)) => Frame {
+            name: name.to_string(),
+            file: Some(file.to_string()),
+            line: Some(line),
+        },
+        None => Frame {
+            name: text.to_string(),
+            file: None,
+            line: None,
+        },
+    }
+}
+
+#[derive(Deserialize)]
+struct Speedscope {
+    shared: Shared,
+    profiles: Vec<SpeedscopeProfile>,
+}
+
+#[derive(Deserialize)]
+struct Shared {
+    frames: Vec<SpeedscopeFrame>,
+}
+
+#[derive(Deserialize)]
+struct SpeedscopeFrame {
+    name: String,
+    file: Option<String>,
+    line: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct SpeedscopeProfile {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    samples: Vec<Vec<usize>>,
+    #[serde(default)]
+    weights: Vec<f64>,
+}
+
+/// The `sampled` profiles of a speedscope file; weights are rounded to
+/// whole samples. Evented (instrumented) profiles are not supported.
+fn speedscope(text: &str) -> Result<Profile, String> {
+    let doc: Speedscope = serde_json::from_str(text).map_err(|e| e.to_string())?;
+    let frames: Vec<Frame> = doc
+        .shared
+        .frames
+        .into_iter()
+        .map(|f| Frame {
+            name: f.name,
+            file: f.file,
+            line: f.line,
+        })
+        .collect();
+    let sampled: Vec<_> = doc
+        .profiles
+        .iter()
+        .filter(|p| p.kind == "sampled")
+        .collect();
+    if sampled.is_empty() {
+        return Err(
+            "no sampled profiles (only `sampled` speedscope profiles are supported)".into(),
+        );
+    }
+    let mut stacks = Vec::new();
+    for p in sampled {
+        for (i, sample) in p.samples.iter().enumerate() {
+            let stack = sample
+                .iter()
+                .map(|&f| frames.get(f).cloned().ok_or(format!("unknown frame {f}")))
+                .collect::<Result<_, _>>()?;
+            let weight = p.weights.get(i).map_or(1, |w| w.round() as u64);
+            stacks.push((stack, weight));
+        }
+    }
+    Ok(Profile { stacks })
+}
+
+/// Samples matched to functions, out of the profile's total.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Coverage {
+    pub matched: u64,
+    pub total: u64,
+}
+
+/// Attach the profile's sample counts to the Function nodes they resolve
+/// to: [`SAMPLES`] counts stacks the function is on, [`SELF_SAMPLES`] those
+/// it is at the top of, and [`HOT`] marks functions on at least 5% of all
+/// samples. Frames resolve by name, narrowed by file and line when the
+/// profiler gives them and by qualifiers (`Graph::build`, `Graph.build`);
+/// frames that still match several functions, or none, are skipped.
+pub fn attach(files: &mut [Vec<Syntax>], profile: &Profile) -> Coverage {
+    let mut funcs = Vec::new();
+    for (i, nodes) in files.iter().enumerate() {
+        let Some(ASTNode::File(file)) = nodes.first().map(|s| &s.node) else {
+            continue;
+        };
+        functions(nodes, &file.path, i, &mut vec![], &[], &mut funcs);
+    }
+
+    let mut resolved: HashMap<&Frame, Option<usize>> = HashMap::new();
+    let (mut total, mut own) = (vec![0u64; funcs.len()], vec![0u64; funcs.len()]);
+    let mut matched = 0;
+    for (stack, count) in &profile.stacks {
+        let on: Vec<Option<usize>> = stack
+            .iter()
+            .map(|f| *resolved.entry(f).or_insert_with(|| resolve(f, &funcs)))
+            .collect();
+        let distinct: BTreeSet<usize> = on.iter().flatten().copied().collect();
+        if distinct.is_empty() {
+            continue;
+        }
+        matched += count;
+        for &f in &distinct {
+            total[f] += count;
+        }
+        if let Some(Some(top)) = on.last() {
+            own[*top] += count;
+        }
+    }
+
+    let all = profile.total();
+    for (f, func) in funcs.iter().enumerate() {
+        if total[f] == 0 {
+            continue;
+        }
+        let mut node = &mut files[func.file][func.path[0]];
+        for &i in &func.path[1..] {
+            node = &mut node.contains[i];
+        }
+        let meta = &mut node.metadata;
+        meta.set_metric(SAMPLES, total[f]);
+        meta.set_metric(SELF_SAMPLES, own[f]);
+        if total[f] as f64 >= all as f64 * HOT_SHARE {
+            meta.set_metric(HOT, true);
+        }
+    }
+    Coverage {
+        matched,
+        total: all,
+    }
+}
+
+/// A Function node: where it is and what a profiler might call it.
+struct Func {
+    file: usize,
+    /// Child indices from the file's node list down to the function.
+    path: Vec<usize>,
+    file_path: String,
+    /// Enclosing type and function names, then its own.
+    qualified: Vec<String>,
+    span: Span,
+}
+
+fn functions(
+    nodes: &[Syntax],
+    file_path: &str,
+    file: usize,
+    path: &mut Vec<usize>,
+    scope: &[String],
+    out: &mut Vec<Func>,
+) {
+    for (i, s) in nodes.iter().enumerate() {
+        let name = match &s.node {
+            ASTNode::Function(f) => Some(f.name.to_string()),
+            // `impl Trait for Foo` methods belong to `Foo`.
+            ASTNode::Type(t) => t.name.rsplit(" for ").next().map(self_name),
+            _ => None,
+        };
+        let mut inner = scope.to_vec();
+        inner.extend(name);
+        path.push(i);
+        if matches!(s.node, ASTNode::Function(_)) {
+            out.push(Func {
+                file,
+                path: path.clone(),
+                file_path: file_path.to_string(),
+                qualified: inner.clone(),
+                span: s.metadata.span,
+            });
+        }
+        functions(&s.contains, file_path, file, path, &inner, out);
+        path.pop();
+    }
+}
+
+fn resolve(frame: &Frame, funcs: &[Func]) -> Option<usize> {
+    let segments = symbol(&frame.name);
+    let name = segments.last()?;
+    let mut candidates: Vec<usize> = (0..funcs.len())
+        .filter(|&f| funcs[f].qualified.last() == Some(name))
+        .collect();
+    let mut narrow = |keep: &dyn Fn(&Func) -> bool| {
+        let kept: Vec<usize> = candidates
+            .iter()
+            .copied()
+            .filter(|&f| keep(&funcs[f]))
+            .collect();
+        if !kept.is_empty() {
+            candidates = kept;
+        }
+    };
+    if let Some(file) = &frame.file {
+        narrow(&|f| same_file(file, &f.file_path));
+    }
+    if let Some(line) = frame.line {
+        narrow(&|f| f.span.start_line <= line && line <= f.span.end_line);
+    }
+    narrow(&|f| segments.ends_with(&f.qualified));
+    match candidates.as_slice() {
+        [only] => Some(*only),
+        _ => None,
+    }
+}
+
+/// True if one path is the other with leading directories dropped.
+fn same_file(a: &str, b: &str) -> bool {
+    let a = a.replace('\\', "/");
+    let (long, short) = if a.len() >= b.len() {
+        (a.as_str(), b)
+    } else {
+        (b, a.as_str())
+    };
+    long.strip_suffix(short)
+        .is_some_and(|head| head.is_empty() || head.ends_with('/'))
+}
+
+/// Name segments of a profiler symbol: generic arguments, closures and
+/// Rust's trailing `::h0123…` hashes removed, split on `::` and `.`.
+fn symbol(name: &str) -> Vec<String> {
+    let mut plain = String::new();
+    let mut depth = 0usize;
+    for c in name.chars() {
+        match c {
+            '<' => depth += 1,
+            '>' => depth = depth.saturating_sub(1),
+            _ if depth == 0 => plain.push(c),
+            _ => {}
+        }
+    }
+    let mut segments: Vec<String> = plain
+        .split("::")
+        .flat_map(|s| s.split('.'))
+        .filter(|s| !s.is_empty() && !s.starts_with("{{"))
+        .map(str::to_string)
+        .collect();
+    let hash = |s: &String| {
+        s.len() == 17 && s.starts_with('h') && s[1..].chars().all(|c| c.is_ascii_hexdigit())
+    };
+    if segments.len() > 1 && segments.last().is_some_and(hash) {
+        segments.pop();
+    }
+    segments
+}
+
+/// `a::Foo<T>` → `Foo`.
+fn self_name(ty: &str) -> String {
+    let ty = ty.split('<').next().unwrap_or(ty);
+    ty.rsplit("::").next().unwrap_or(ty).trim().to_string()
+}
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::tree_sitter::analyze;
+    use crate::api::tree_sitter::py::Python;
+    use crate::api::tree_sitter::rs::Rust;
+    use serde_json::Value;
+
+    fn metric<'a>(nodes: &'a [Syntax], name: &str, key: &str) -> Option<&'a Value> {
+        nodes.iter().find_map(|s| match &s.node {
+            ASTNode::Function(f) if f.name == name => s.metadata.metric(key),
+            _ => metric(&s.contains, name, key),
+        })
+    }
+
+    // ── Parsing ──
+
+    #[test]
+    fn folded_stacks() {
+        let p = Profile::parse("main;run;walk 30\nmain;run 10\n\n").unwrap();
+        assert_eq!(p.stacks.len(), 2);
+        assert_eq!(p.stacks[0].0.len(), 3);
+        assert_eq!(p.total(), 40);
+    }
+
+    #[test]
+    fn folded_py_spy_locations() {
+        let p = Profile::parse("<module> (app.py:1);walk (pkg/fs.py:12) 5\n").unwrap();
+        assert_eq!(
+            p.stacks[0].0[1],
+            Frame {
+                name: "walk".into(),
+                file: Some("pkg/fs.py".into()),
+                line: Some(12),
+            }
+        );
+    }
+
+    #[test]
+    fn folded_bad_count_errors() {
+        let err = Profile::parse("main;run lots\n").unwrap_err();
+        assert!(err.contains("line 1"));
+    }
+
+    #[test]
+    fn speedscope_sampled() {
+        let json = r#"{"shared":{"frames":[{"name":"main","file":"app.py","line":1},{"name":"walk"}]},
+            "profiles":[{"type":"sampled","samples":[[0,1],[0]],"weights":[3,1]}]}"#;
+        let p = Profile::parse(json).unwrap();
+        assert_eq!(p.stacks.len(), 2);
+        assert_eq!(p.stacks[0].0[1].name, "walk");
+        assert_eq!(p.total(), 4);
+    }
+
+    #[test]
+    fn speedscope_evented_only_errors() {
+        let json = r#"{"shared":{"frames":[]},"profiles":[{"type":"evented"}]}"#;
+        assert!(Profile::parse(json).unwrap_err().contains("sampled"));
+    }
+
+    #[test]
+    fn rust_symbols_normalized() {
+        assert_eq!(
+            symbol("bolomoty::graph::Graph<'a>::build::h0123456789abcdef"),
+            vec!["bolomoty", "graph", "Graph", "build"]
+        );
+        assert_eq!(
+            symbol("core::iter::Map<I,F>::next::{{closure}}"),
+            vec!["core", "iter", "Map", "next"]
+        );
+        assert_eq!(symbol("pkg.mod.func"), vec!["pkg", "mod", "func"]);
+    }
+
+    #[test]
+    fn file_suffixes() {
+        assert!(same_file("/home/u/proj/pkg/fs.py", "pkg/fs.py"));
+        assert!(same_file("fs.py", "pkg/fs.py"));
+        assert!(!same_file("/proj/xfs.py", "fs.py"));
+    }
+
+    // ── Attaching ──
+
+    #[test]
+    fn counts_inclusive_and_self_samples() {
+        let mut files = [analyze(
+            &Python,
+            "app.py",
+            "def main():\n    run()\ndef run():\n    walk()\ndef walk(): pass\ndef idle(): pass\n",
+        )];
+        let p = Profile::parse("main;run;walk 30\nmain;run 10\nmain;main 5\n").unwrap();
+        let c = attach(&mut files, &p);
+        assert_eq!(
+            c,
+            Coverage {
+                matched: 45,
+                total: 45
+            }
+        );
+        assert_eq!(metric(&files[0], "main", SAMPLES), Some(&Value::from(45)));
+        assert_eq!(metric(&files[0], "run", SAMPLES), Some(&Value::from(40)));
+        assert_eq!(
+            metric(&files[0], "run", SELF_SAMPLES),
+            Some(&Value::from(10))
+        );
+        assert_eq!(
+            metric(&files[0], "walk", SELF_SAMPLES),
+            Some(&Value::from(30))
+        );
+        assert_eq!(metric(&files[0], "walk", HOT), Some(&Value::from(true)));
+        assert_eq!(metric(&files[0], "idle", SAMPLES), None);
+    }
+
+    #[test]
+    fn cold_functions_not_hot() {
+        let mut files = [analyze(&Python, "a.py", "def f(): pass\ndef g(): pass\n")];
+        let p = Profile::parse("f 99\ng 1\n").unwrap();
+        attach(&mut files, &p);
+        assert_eq!(metric(&files[0], "f", HOT), Some(&Value::from(true)));
+        assert_eq!(metric(&files[0], "g", HOT), None);
+        assert_eq!(metric(&files[0], "g", SAMPLES), Some(&Value::from(1)));
+    }
+
+    #[test]
+    fn unmatched_frames_not_counted() {
+        let mut files = [analyze(&Python, "a.py", "def f(): pass\n")];
+        let p = Profile::parse("libc;malloc 7\nf 3\n").unwrap();
+        assert_eq!(
+            attach(&mut files, &p),
+            Coverage {
+                matched: 3,
+                total: 10
+            }
+        );
+    }
+
+    #[test]
+    fn rust_methods_resolve_by_qualifier() {
+        let mut files = [analyze(
+            &Rust,
+            "src/graph.rs",
+            "struct Graph;\nimpl Graph { fn build() {} }\nstruct Tree;\nimpl Tree { fn build() {} }\n",
+        )];
+        let p = Profile::parse("bolomoty::graph::Graph::build::h0123456789abcdef 4\n").unwrap();
+        attach(&mut files, &p);
+        let build: Vec<_> = files[0]
+            .iter()
+            .filter(|s| matches!(&s.node, ASTNode::Type(_)) && !s.contains.is_empty())
+            .map(|s| s.contains[0].metadata.metric(SAMPLES).cloned())
+            .collect();
+        assert_eq!(build, vec![Some(Value::from(4)), None]);
+    }
+
+    #[test]
+    fn ambiguous_names_skipped() {
+        let mut files = [
+            analyze(&Python, "a.py", "def run(): pass\n"),
+            analyze(&Python, "b.py", "def run(): pass\n"),
+        ];
+        let p = Profile::parse("run 5\nrun (pkg/b.py:1) 2\n").unwrap();
+        let c = attach(&mut files, &p);
+        assert_eq!(c.matched, 2);
+        assert_eq!(metric(&files[0], "run", SAMPLES), None);
+        assert_eq!(metric(&files[1], "run", SAMPLES), Some(&Value::from(2)));
+    }
+}
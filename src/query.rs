@@ -0,0 +1,346 @@
+use crate::api::tree_sitter::{ASTNode, Syntax};
+use crate::graph::Graph;
+use serde::Serialize;
+
+/// Page size when a query gives no `limit`.
+pub const DEFAULT_LIMIT: usize = 100;
+/// Largest `limit` honored, so one request can't ask for the whole graph.
+pub const MAX_LIMIT: usize = 1000;
+
+/// Filters and a page window over a graph's definitions or an analysis's
+/// files, parsed from a URL query string such as
+/// `kind=function&path_prefix=src/api&min_fan_in=3`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Query {
+    /// Definition kind as [`Graph`] names it: `fn`, `type` or `fixture`.
+    pub kind: Option<&'static str>,
+    pub path_prefix: Option<String>,
+    /// Fewest distinct callers a definition needs.
+    pub min_fan_in: usize,
+    /// Id of the last definition already returned.
+    pub cursor: Option<String>,
+    pub limit: usize,
+}
+
+impl Default for Query {
+    fn default() -> Self {
+        Query {
+            kind: None,
+            path_prefix: None,
+            min_fan_in: 0,
+            cursor: None,
+            limit: DEFAULT_LIMIT,
+        }
+    }
+}
+
+/// One definition in a [`Page`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Item {
+    pub id: String,
+    pub kind: &'static str,
+    pub file: String,
+    pub fan_in: usize,
+    pub fan_out: usize,
+}
+
+/// One file in a [`Page`] of files.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FileItem {
+    pub path: String,
+    pub lines: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Page<T = Item> {
+    pub items: Vec<T>,
+    /// Pass back as `cursor` for the following page; absent on the last one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next: Option<String>,
+}
+
+impl Query {
+    /// Parse `key=value` pairs separated by `&`, percent-decoding values.
+    /// Unknown keys are ignored; malformed values are errors.
+    pub fn parse(query: &str) -> Result<Self, String> {
+        let mut q = Query::default();
+        for pair in query.split('&').filter(|p| !p.is_empty()) {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            let value = decode(&value.replace('+', " "))
+                .ok_or_else(|| format!("bad escape in `{pair}`"))?;
+            let number = |v: &str| {
+                v.parse::<usize>()
+                    .map_err(|_| format!("`{key}` must be a number, got `{v}`"))
+            };
+            match key {
+                "kind" => {
+                    q.kind = Some(match value.as_str() {
+                        "fn" | "function" => "fn",
+                        "type" => "type",
+                        "fixture" => "fixture",
+                        other => {
+                            return Err(format!(
+                                "unknown kind `{other}` (use function, type or fixture)"
+                            ));
+                        }
+                    })
+                }
+                "path_prefix" => q.path_prefix = Some(value),
+                "min_fan_in" => q.min_fan_in = number(&value)?,
+                "cursor" => q.cursor = Some(value).filter(|c| !c.is_empty()),
+                "limit" => q.limit = number(&value)?.clamp(1, MAX_LIMIT),
+                _ => {}
+            }
+        }
+        Ok(q)
+    }
+
+    /// The matching definitions after `cursor`, ordered by id so a cursor
+    /// stays valid when definitions are added or removed elsewhere.
+    pub fn page(&self, graph: &Graph) -> Page {
+        let mut callers = vec![0; graph.defs.len()];
+        let mut callees = vec![0; graph.defs.len()];
+        for &(a, b) in &graph.edges {
+            callees[a] += 1;
+            callers[b] += 1;
+        }
+
+        let mut matching: Vec<Item> = graph
+            .defs
+            .iter()
+            .enumerate()
+            .filter(|(_, d)| self.kind.is_none_or(|k| d.kind.as_str() == k))
+            .filter(|(_, d)| {
+                self.path_prefix
+                    .as_deref()
+                    .is_none_or(|p| d.file.starts_with(p))
+            })
+            .filter(|(i, _)| callers[*i] >= self.min_fan_in)
+            .filter(|(_, d)| self.cursor.as_deref().is_none_or(|c| d.id.as_str() > c))
+            .map(|(i, d)| Item {
+                id: d.id.clone(),
+                kind: d.kind.as_str(),
+                file: d.file.to_string(),
+                fan_in: callers[i],
+                fan_out: callees[i],
+            })
+            .collect();
+        matching.sort_by(|a, b| a.id.cmp(&b.id));
+        self.window(matching, |i| &i.id)
+    }
+
+    /// The files under `path_prefix` after `cursor`, ordered by path; `kind`
+    /// and `min_fan_in` do not apply to files.
+    pub fn files(&self, files: &[Vec<Syntax>]) -> Page<FileItem> {
+        let mut matching: Vec<FileItem> = files
+            .iter()
+            .filter_map(|nodes| {
+                let s = nodes.first()?;
+                let ASTNode::File(f) = &s.node else {
+                    return None;
+                };
+                Some(FileItem {
+                    path: f.path.to_string(),
+                    lines: s.metadata.lines,
+                })
+            })
+            .filter(|f| {
+                self.path_prefix
+                    .as_deref()
+                    .is_none_or(|p| f.path.starts_with(p))
+            })
+            .filter(|f| self.cursor.as_deref().is_none_or(|c| f.path.as_str() > c))
+            .collect();
+        matching.sort_by(|a, b| a.path.cmp(&b.path));
+        self.window(matching, |f| &f.path)
+    }
+
+    /// The first `limit` of `sorted`, with the cursor to the rest if any.
+    fn window<T>(&self, mut sorted: Vec<T>, key: impl Fn(&T) -> &String) -> Page<T> {
+        let next = (sorted.len() > self.limit).then(|| key(&sorted[self.limit - 1]).clone());
+        sorted.truncate(self.limit);
+        Page {
+            items: sorted,
+            next,
+        }
+    }
+}
+
+/// Decode the `%XX` escapes of a URL path or query value; `None` on a bad
+/// escape or when the result is not UTF-8.
+pub fn decode(s: &str) -> Option<String> {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = s.get(i + 1..i + 3)?;
+            out.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(out).ok()
+}
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::tree_sitter::py::Python;
+    use crate::api::tree_sitter::{Syntax, analyze};
+
+    fn ids(page: &Page) -> Vec<&str> {
+        page.items.iter().map(|i| i.id.as_str()).collect()
+    }
+
+    fn files() -> [Vec<Syntax<'static>>; 3] {
+        [
+            analyze(
+                &Python,
+                "src/api/fs.py",
+                "def walk():\n    read()\n\ndef read(): pass\n",
+            ),
+            analyze(
+                &Python,
+                "src/api/git.py",
+                "class Repo: pass\n\ndef clone():\n    read()\n",
+            ),
+            analyze(
+                &Python,
+                "src/main.py",
+                "def main():\n    walk()\n    clone()\n    read()\n",
+            ),
+        ]
+    }
+
+    // ── parse ──
+
+    #[test]
+    fn parse_all_keys() {
+        let q = Query::parse("kind=function&path_prefix=src%2Fapi&min_fan_in=3&cursor=a&limit=5")
+            .unwrap();
+        assert_eq!(q.kind, Some("fn"));
+        assert_eq!(q.path_prefix.as_deref(), Some("src/api"));
+        assert_eq!((q.min_fan_in, q.limit), (3, 5));
+        assert_eq!(q.cursor.as_deref(), Some("a"));
+    }
+
+    #[test]
+    fn parse_defaults_and_unknown_keys() {
+        assert_eq!(Query::parse("").unwrap(), Query::default());
+        assert_eq!(Query::parse("sort=name&").unwrap(), Query::default());
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(Query::parse("kind=module").unwrap_err().contains("module"));
+        assert!(Query::parse("min_fan_in=many").is_err());
+        assert!(Query::parse("path_prefix=%zz").is_err());
+    }
+
+    #[test]
+    fn decode_escapes() {
+        assert_eq!(decode("a%20b%2Fc+d").as_deref(), Some("a b/c+d"));
+        assert_eq!(decode("%zz"), None);
+        assert_eq!(decode("%2"), None);
+        assert_eq!(decode("%ff"), None);
+        let q = Query::parse("path_prefix=a+b%2Bc").unwrap();
+        assert_eq!(q.path_prefix.as_deref(), Some("a b+c"));
+    }
+
+    #[test]
+    fn limit_clamped() {
+        assert_eq!(Query::parse("limit=0").unwrap().limit, 1);
+        assert_eq!(Query::parse("limit=999999").unwrap().limit, MAX_LIMIT);
+    }
+
+    // ── page ──
+
+    #[test]
+    fn filters_combine() {
+        let files = files();
+        let graph = Graph::build(&files);
+        let q = Query::parse("kind=function&path_prefix=src/api").unwrap();
+        assert_eq!(
+            ids(&q.page(&graph)),
+            vec![
+                "src/api/fs.py::read",
+                "src/api/fs.py::walk",
+                "src/api/git.py::clone"
+            ]
+        );
+        let q = Query::parse("min_fan_in=3").unwrap();
+        let page = q.page(&graph);
+        assert_eq!(ids(&page), vec!["src/api/fs.py::read"]);
+        assert_eq!((page.items[0].fan_in, page.items[0].fan_out), (3, 0));
+    }
+
+    #[test]
+    fn same_named_definitions_are_all_listed() {
+        let files = [analyze(
+            &Python,
+            "a.py",
+            "def f():\n    g()\ndef f(): pass\ndef g(): pass\n",
+        )];
+        let page = Query::default().page(&Graph::build(&files));
+        assert_eq!(ids(&page), vec!["a.py::f", "a.py::f#2", "a.py::g"]);
+        assert_eq!(page.items[2].fan_in, 1);
+    }
+
+    #[test]
+    fn cursor_walks_every_item_once() {
+        let files = files();
+        let graph = Graph::build(&files);
+        let all = ids(&Query::default().page(&graph))
+            .into_iter()
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(all.len(), 5);
+
+        let mut seen = Vec::new();
+        let mut q = Query::parse("limit=4").unwrap();
+        loop {
+            let page = q.page(&graph);
+            seen.extend(page.items.iter().map(|i| i.id.clone()));
+            match page.next {
+                Some(next) => q.cursor = Some(next),
+                None => break,
+            }
+        }
+        assert_eq!(seen, all);
+    }
+
+    #[test]
+    fn files_by_prefix_and_cursor() {
+        let files = files();
+        let q = Query::parse("path_prefix=src/api&limit=1").unwrap();
+        let page = q.files(&files);
+        assert_eq!(
+            page.items,
+            vec![FileItem {
+                path: "src/api/fs.py".into(),
+                lines: 5
+            }]
+        );
+        let q = Query {
+            cursor: page.next,
+            ..q
+        };
+        let page = q.files(&files);
+        assert_eq!(page.items[0].path, "src/api/git.py");
+        assert!(page.next.is_none());
+    }
+
+    #[test]
+    fn exact_last_page_has_no_cursor() {
+        let files = files();
+        let graph = Graph::build(&files);
+        let page = Query::parse("limit=5").unwrap().page(&graph);
+        assert_eq!(page.items.len(), 5);
+        assert!(page.next.is_none());
+    }
+}
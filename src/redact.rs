@@ -0,0 +1,488 @@
+use crate::analysis::Analysis;
+use crate::api::tree_sitter::{ASTNode, Syntax};
+use crate::crates::EdgeKind;
+use crate::report::calls;
+use crate::rules::{self, Violation};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashSet;
+
+/// Stands in for anything redacted in a summary.
+pub const REDACTED: &str = "(redacted)";
+
+/// What the `[redact]` table in `bolo.toml` keeps out of written output.
+#[derive(Debug, Default, Deserialize)]
+pub struct Redact {
+    /// Path globs over File paths, as in `[[rules.layers]]`; matching files
+    /// are left out entirely.
+    #[serde(default)]
+    pub paths: Vec<String>,
+    /// Name patterns, matched like watchlist entries; matching definitions
+    /// (with their bodies) and calls, types and fixtures used by that name
+    /// are left out.
+    #[serde(default)]
+    pub symbols: Vec<String>,
+}
+
+/// How much [`Redact::apply`] took out.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Redacted {
+    pub files: usize,
+    pub nodes: usize,
+}
+
+/// The paths and names [`Redact::apply`] would take out of an analysis, for
+/// masking reports computed from all of it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Hidden {
+    pub paths: HashSet<String>,
+    pub names: HashSet<String>,
+}
+
+impl Hidden {
+    /// Replace every string in `value`, object keys included, that is a
+    /// hidden path or name, or holds one as a `::` part or last `.`
+    /// segment (`app/phi.py::lookup`, `db.phi_lookup`), with [`REDACTED`].
+    pub fn mask(&self, value: &mut Value) {
+        if self.paths.is_empty() && self.names.is_empty() {
+            return;
+        }
+        match value {
+            Value::String(s) if self.hides(s) => *s = REDACTED.to_string(),
+            Value::Array(items) => items.iter_mut().for_each(|v| self.mask(v)),
+            Value::Object(fields) => {
+                *fields = std::mem::take(fields)
+                    .into_iter()
+                    .map(|(k, mut v)| {
+                        self.mask(&mut v);
+                        let k = if self.hides(&k) {
+                            REDACTED.to_string()
+                        } else {
+                            k
+                        };
+                        (k, v)
+                    })
+                    .collect();
+            }
+            _ => {}
+        }
+    }
+
+    fn hides(&self, s: &str) -> bool {
+        let hidden = |p: &str| self.paths.contains(p) || self.names.contains(p);
+        s.split("::").any(|part| {
+            hidden(part)
+                || part
+                    .rsplit('.')
+                    .next()
+                    .is_some_and(|n| self.names.contains(n))
+        })
+    }
+}
+
+impl Redact {
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty() && self.symbols.is_empty()
+    }
+
+    pub fn hides_path(&self, path: &str) -> bool {
+        self.paths.iter().any(|p| rules::path_glob(path, p))
+    }
+
+    pub fn hides_symbol(&self, name: &str) -> bool {
+        self.symbols.iter().any(|p| calls::matches(name, p))
+    }
+
+    /// Remove redacted files, their warnings and build-script edges, and
+    /// redacted nodes from `analysis`, along with imported items,
+    /// parameters, return types, annotations and decorators that mention a
+    /// redacted symbol. Metadata of what stays is untouched, so a File's
+    /// counts still include redacted definitions.
+    pub fn apply(&self, analysis: &mut Analysis) -> Redacted {
+        let mut out = Redacted::default();
+        analysis.files.retain(|nodes| {
+            let hidden = matches!(
+                nodes.first().map(|s| &s.node),
+                Some(ASTNode::File(f)) if self.hides_path(&f.path)
+            );
+            out.files += usize::from(hidden);
+            !hidden
+        });
+        analysis.warnings.retain(|w| !self.hides_path(&w.file));
+        analysis
+            .crates
+            .retain(|e| e.kind != EdgeKind::BuildScript || !self.hides_path(&e.to));
+        if !self.symbols.is_empty() {
+            for nodes in &mut analysis.files {
+                out.nodes += self.prune(nodes);
+            }
+        }
+        out
+    }
+
+    /// What [`apply`](Self::apply) would take out of `analysis`: the paths
+    /// of redacted files, and the names of redacted nodes and of what they
+    /// mention in the rest.
+    pub fn hidden(&self, analysis: &Analysis) -> Hidden {
+        let mut out = Hidden::default();
+        if self.is_empty() {
+            return out;
+        }
+        let mut shown = Vec::new();
+        for nodes in &analysis.files {
+            match nodes.first().map(|s| &s.node) {
+                Some(ASTNode::File(f)) if self.hides_path(&f.path) => {
+                    out.paths.insert(f.path.to_string());
+                }
+                Some(ASTNode::File(f)) => {
+                    shown.push(f.path.as_ref());
+                    self.collect(nodes, &mut out.names);
+                }
+                _ => self.collect(nodes, &mut out.names),
+            }
+        }
+        // Directories holding only redacted files, as module reports name them.
+        let dirs: Vec<String> = out
+            .paths
+            .iter()
+            .flat_map(|p| p.match_indices('/').map(|(i, _)| &p[..i]))
+            .filter(|dir| !shown.iter().any(|s| s.starts_with(&format!("{dir}/"))))
+            .map(str::to_string)
+            .collect();
+        out.paths.extend(dirs);
+        out
+    }
+
+    fn collect(&self, nodes: &[Syntax], names: &mut HashSet<String>) {
+        for s in nodes {
+            for text in texts(&s.node) {
+                if self.hides_symbol(text) {
+                    names.insert(text.to_string());
+                }
+                names.extend(
+                    words(text)
+                        .filter(|w| self.hides_symbol(w))
+                        .map(str::to_string),
+                );
+            }
+            self.collect(&s.contains, names);
+        }
+    }
+
+    /// `v` as it may appear in a summary: with every field replaced if it
+    /// involves a redacted file or symbol.
+    pub fn violation(&self, v: Violation) -> Violation {
+        let hidden = self.hides_path(&v.file)
+            || v.function.as_deref().is_some_and(|f| self.hides_symbol(f))
+            || v.target.as_ref().is_some_and(|t| {
+                self.hides_path(&t.file)
+                    || t.function.as_deref().is_some_and(|f| self.hides_symbol(f))
+            })
+            // Names in messages are in backticks.
+            || v.message.split('`').skip(1).step_by(2).any(|n| self.hides_symbol(n));
+        if !hidden {
+            return v;
+        }
+        Violation {
+            file: REDACTED.to_string(),
+            function: None,
+            message: REDACTED.to_string(),
+            target: None,
+            ..v
+        }
+    }
+
+    fn prune(&self, nodes: &mut Vec<Syntax>) -> usize {
+        let before = nodes.len();
+        nodes.retain_mut(|s| !self.hides_node(&mut s.node));
+        let mut removed = before - nodes.len();
+        for s in nodes {
+            removed += self.prune(&mut s.contains);
+        }
+        removed
+    }
+
+    /// True if a symbol or any word of `text` is redacted: `phi_lookup`,
+    /// `Optional[PhiRecord]`, `app.route("/phi")`.
+    fn mentions(&self, text: &str) -> bool {
+        self.hides_symbol(text) || words(text).any(|w| self.hides_symbol(w))
+    }
+
+    /// Whether `node` names or mentions a redacted symbol. Definitions that
+    /// only mention one in their signature keep their name but lose the
+    /// parameters, return type, annotations and decorators that do, and
+    /// imports lose the redacted items (the whole Import if none is left).
+    fn hides_node(&self, node: &mut ASTNode) -> bool {
+        let mentions = |t: &str| self.mentions(t);
+        match node {
+            ASTNode::Function(f) => {
+                if mentions(&f.name) {
+                    return true;
+                }
+                f.params.retain(|p| {
+                    let texts = [Some(&p.name), p.annotation.as_ref(), p.default.as_ref()];
+                    !texts.into_iter().flatten().any(|t| mentions(t))
+                });
+                if f.return_type.as_deref().is_some_and(mentions) {
+                    f.return_type = None;
+                }
+                f.decorators.retain(|d| !mentions(d));
+                false
+            }
+            ASTNode::Type(t) => {
+                t.decorators.retain(|d| !mentions(d));
+                mentions(&t.name)
+            }
+            ASTNode::Field(f) => {
+                if f.annotation.as_deref().is_some_and(mentions) {
+                    f.annotation = None;
+                }
+                mentions(&f.name)
+            }
+            ASTNode::Import(i) => {
+                if mentions(&i.module) || i.alias.as_deref().is_some_and(mentions) {
+                    return true;
+                }
+                let whole = i.items.is_empty();
+                i.items.retain(|item| !mentions(item));
+                !whole && i.items.is_empty()
+            }
+            ASTNode::DynamicImport(d) => d.module.as_deref().is_some_and(mentions),
+            ASTNode::Attribute(a) => mentions(&a.name) || a.args.iter().any(|t| mentions(t)),
+            ASTNode::Endpoint(e) => mentions(&e.handler) || mentions(&e.route),
+            ASTNode::Call(c) => mentions(&c.name) || c.spawned_by.as_deref().is_some_and(mentions),
+            _ => texts(node).any(mentions),
+        }
+    }
+}
+
+/// The names and text a node carries that may mention a symbol.
+fn texts<'a>(node: &'a ASTNode) -> Box<dyn Iterator<Item = &'a str> + 'a> {
+    let one = |s: &'a str| Box::new(std::iter::once(s)) as Box<dyn Iterator<Item = _>>;
+    match node {
+        ASTNode::Function(f) => Box::new(
+            std::iter::once(&f.name)
+                .chain(f.params.iter().flat_map(|p| {
+                    [Some(&p.name), p.annotation.as_ref(), p.default.as_ref()]
+                        .into_iter()
+                        .flatten()
+                }))
+                .chain(&f.return_type)
+                .chain(&f.decorators)
+                .map(|s| s.as_ref()),
+        ),
+        ASTNode::Type(t) => Box::new(
+            std::iter::once(&t.name)
+                .chain(&t.decorators)
+                .map(|s| s.as_ref()),
+        ),
+        ASTNode::Field(f) => Box::new(
+            std::iter::once(&f.name)
+                .chain(&f.annotation)
+                .map(|s| s.as_ref()),
+        ),
+        ASTNode::Variant(v) => one(&v.name),
+        ASTNode::Call(c) => Box::new(
+            std::iter::once(&c.name)
+                .chain(&c.spawned_by)
+                .map(|s| s.as_ref()),
+        ),
+        ASTNode::UsesType(u) => one(&u.name),
+        ASTNode::Fixture(f) => one(&f.name),
+        ASTNode::UsesFixture(u) => one(&u.name),
+        ASTNode::Endpoint(e) => Box::new([e.handler.as_ref(), e.route.as_ref()].into_iter()),
+        ASTNode::Exception(e) => one(&e.name),
+        ASTNode::Module(m) => one(&m.name),
+        ASTNode::Import(i) => Box::new(
+            std::iter::once(&i.module)
+                .chain(&i.items)
+                .chain(&i.alias)
+                .map(|s| s.as_ref()),
+        ),
+        ASTNode::DynamicImport(d) => Box::new(d.module.iter().map(|s| s.as_ref())),
+        ASTNode::Attribute(a) => {
+            Box::new(std::iter::once(&a.name).chain(&a.args).map(|s| s.as_ref()))
+        }
+        ASTNode::Literal(l) => one(&l.value),
+        ASTNode::File(_) | ASTNode::Unsafe(_) | ASTNode::Comment | ASTNode::Unknown(_) => {
+            Box::new(std::iter::empty())
+        }
+    }
+}
+
+/// The identifiers and paths in `text` (`a.b`, `a::b`), split at anything
+/// else.
+fn words(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '.' | ':')))
+        .map(|w| w.trim_matches(|c| c == '.' || c == ':'))
+        .filter(|w| !w.is_empty())
+}
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::{Warning, WarningKind};
+    use crate::api::tree_sitter::py::Python;
+    use crate::api::tree_sitter::{Span, analyze};
+
+    fn sample() -> Analysis {
+        Analysis {
+            files: vec![
+                analyze(
+                    &Python,
+                    "app/views.py",
+                    "def show():\n    phi_lookup()\n    render()\n",
+                ),
+                analyze(&Python, "app/phi/records.py", "def phi_lookup(): pass\n"),
+            ],
+            warnings: vec![Warning::new(
+                WarningKind::LossyDecode,
+                "app/phi/records.py",
+                "invalid UTF-8",
+            )],
+            ..Default::default()
+        }
+    }
+
+    fn redact(paths: &[&str], symbols: &[&str]) -> Redact {
+        Redact {
+            paths: paths.iter().map(|s| s.to_string()).collect(),
+            symbols: symbols.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    // ── apply ──
+
+    #[test]
+    fn paths_drop_files_and_their_warnings() {
+        let mut a = sample();
+        let n = redact(&["app/phi/"], &[]).apply(&mut a);
+        assert_eq!(n, Redacted { files: 1, nodes: 0 });
+        assert_eq!(a.files.len(), 1);
+        assert!(a.warnings.is_empty());
+    }
+
+    #[test]
+    fn symbols_drop_nodes_but_keep_file_counts() {
+        let mut a = sample();
+        let lines = a.files[0][1].metadata.lines;
+        let n = redact(&[], &["phi_*"]).apply(&mut a);
+        assert_eq!(n, Redacted { files: 0, nodes: 2 });
+        let json = serde_json::to_string(&a).unwrap();
+        assert!(!json.contains("phi_lookup"));
+        assert!(json.contains("render"));
+        // The enclosing function still measures the redacted call's line.
+        assert_eq!(a.files[0][1].metadata.lines, lines);
+    }
+
+    #[test]
+    fn imports_lose_redacted_items() {
+        let src = "import phi_tools\nfrom app.phi import phi_lookup\n\
+                   from app.util import phi_scrub, render\nimport json\n";
+        let mut a = Analysis {
+            files: vec![analyze(&Python, "app/views.py", src)],
+            ..Default::default()
+        };
+        let n = redact(&[], &["phi_*"]).apply(&mut a);
+        assert_eq!(n.nodes, 2);
+        let json = serde_json::to_string(&a).unwrap();
+        assert!(!json.contains("phi_"), "{json}");
+        assert!(json.contains("render") && json.contains("json"));
+    }
+
+    #[test]
+    fn signatures_lose_redacted_params_types_and_decorators() {
+        let src = "@phi_audit\ndef show(record: PhiRecord, page=1) -> PhiRecord:\n    pass\n";
+        let mut a = Analysis {
+            files: vec![analyze(&Python, "app/views.py", src)],
+            ..Default::default()
+        };
+        redact(&[], &["phi_*", "Phi*"]).apply(&mut a);
+        let json = serde_json::to_string(&a).unwrap();
+        assert!(
+            !json.contains("hi_") && !json.contains("PhiRecord"),
+            "{json}"
+        );
+        let ASTNode::Function(f) = &a.files[0][1].node else {
+            panic!("{:?}", a.files[0][1].node);
+        };
+        assert_eq!(f.name, "show");
+        let params: Vec<&str> = f.params.iter().map(|p| p.name.as_ref()).collect();
+        assert_eq!(params, vec!["page"]);
+        assert!(f.return_type.is_none() && f.decorators.is_empty());
+    }
+
+    #[test]
+    fn stats_count_redacted_files_under_masked_paths() {
+        use crate::report::stats;
+        let a = sample();
+        let hidden = redact(&["app/phi/"], &[]).hidden(&a);
+        let mut report = serde_json::to_value(stats::collect(&a.files)).unwrap();
+        hidden.mask(&mut report);
+        let json = report.to_string();
+        assert!(!json.contains("phi"), "{json}");
+        let files = report["files"].as_array().unwrap();
+        assert_eq!(files.len(), 2);
+        let definitions: u64 = files
+            .iter()
+            .map(|f| f["definitions"].as_u64().unwrap())
+            .sum();
+        assert_eq!(definitions, 2);
+    }
+
+    #[test]
+    fn hidden_names_masked_in_reports() {
+        let a = sample();
+        let hidden = redact(&[], &["phi_*"]).hidden(&a);
+        assert!(hidden.names.contains("phi_lookup"));
+        let mut report = serde_json::json!({
+            "calls": ["phi_lookup", "db.phi_lookup", "render"],
+            "app/views.py::phi_lookup": 1,
+        });
+        hidden.mask(&mut report);
+        assert_eq!(
+            report,
+            serde_json::json!({
+                "calls": [REDACTED, REDACTED, "render"],
+                REDACTED: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn empty_redacts_nothing() {
+        let mut a = sample();
+        assert!(Redact::default().is_empty());
+        assert_eq!(Redact::default().apply(&mut a), Redacted::default());
+        assert_eq!(a.files.len(), 2);
+    }
+
+    // ── violation ──
+
+    #[test]
+    fn violations_masked() {
+        let r = redact(&["app/phi/"], &["phi_*"]);
+        let v = Violation {
+            rule: "forbid",
+            file: "app/views.py".into(),
+            function: Some("show".into()),
+            span: Span::lines(2, 2),
+            message: "call to `phi_lookup` matches forbidden `phi_lookup`".into(),
+            target: None,
+        };
+        let masked = r.violation(v.clone());
+        assert_eq!(
+            (masked.file.as_str(), masked.message.as_str()),
+            (REDACTED, REDACTED)
+        );
+        assert_eq!(masked.span.start_line, 2);
+
+        let clean = Violation {
+            message: "call to `eval` matches forbidden `eval`".into(),
+            ..v
+        };
+        assert_eq!(r.violation(clean).file, "app/views.py");
+    }
+}
@@ -0,0 +1,180 @@
+use crate::api::tree_sitter::{ASTNode, Span, Syntax};
+use crate::graph::Graph;
+use serde::Serialize;
+
+/// What renaming a definition would touch: its declaration and every call
+/// that resolves to it (see [`Graph::build`]), plus definitions already
+/// carrying the new name, whose calls the rename could capture.
+#[derive(Debug, Clone, Serialize)]
+pub struct Preview {
+    /// Graph id of the renamed definition, `file::Qual.name`.
+    pub definition: String,
+    pub file: String,
+    pub span: Span,
+    pub from: String,
+    pub to: String,
+    pub sites: Vec<Site>,
+    /// Ids of other definitions named `to`. Calls resolve by name, so after
+    /// the rename they may bind to either.
+    pub clashes: Vec<String>,
+}
+
+/// A call to update.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Site {
+    pub file: String,
+    /// Graph id of the calling definition.
+    pub caller: String,
+    /// The call as written, e.g. `client.send`.
+    pub call: String,
+    pub span: Span,
+}
+
+/// Preview renaming `old` to `new` in `files`. `old` is a graph id
+/// (`a.py::Client.send`), a symbol (`pkg.a.Client.send`), a qualified name
+/// (`Client.send`) or a plain name,
+/// and must pick out one definition; `new` may be qualified the same ways,
+/// only its last segment is the new name.
+pub fn preview(files: &[Vec<Syntax>], old: &str, new: &str) -> Result<Preview, String> {
+    let graph = Graph::build(files);
+    let target = graph.find(old)?;
+    let to = new.rsplit(['.', ':']).next().unwrap_or(new);
+    if to.is_empty() {
+        return Err(format!("`{new}` has no name to rename to"));
+    }
+    let def = &graph.defs[target];
+
+    let mut sites = Vec::new();
+    for &(a, b) in &graph.edges {
+        if b != target {
+            continue;
+        }
+        let caller = &graph.defs[a];
+        for s in graph.call_sites(a, b) {
+            let ASTNode::Call(c) = &s.node else { continue };
+            sites.push(Site {
+                file: caller.file.to_string(),
+                caller: caller.id.clone(),
+                call: c.name.to_string(),
+                span: s.metadata.span,
+            });
+        }
+    }
+    for spawn in graph.spawns.iter().filter(|s| s.to == target) {
+        let spawner = &graph.defs[spawn.from];
+        for s in graph.spawn_sites(spawn) {
+            let ASTNode::Call(c) = &s.node else { continue };
+            sites.push(Site {
+                file: spawner.file.to_string(),
+                caller: spawner.id.clone(),
+                call: c.name.to_string(),
+                span: s.metadata.span,
+            });
+        }
+    }
+    sites.sort_by(|a, b| (&a.file, a.span.start_line).cmp(&(&b.file, b.span.start_line)));
+
+    let clashes = graph
+        .defs
+        .iter()
+        .enumerate()
+        .filter(|&(i, d)| i != target && d.name == to)
+        .map(|(_, d)| d.id.clone())
+        .collect();
+    Ok(Preview {
+        definition: def.id.clone(),
+        file: def.file.to_string(),
+        span: def.syntax.metadata.span,
+        from: def.name.to_string(),
+        to: to.to_string(),
+        sites,
+        clashes,
+    })
+}
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::tree_sitter::analyze;
+    use crate::api::tree_sitter::py::Python;
+
+    fn files() -> Vec<Vec<Syntax<'static>>> {
+        vec![
+            analyze(
+                &Python,
+                "app.py",
+                "from net import send\ndef main():\n    send(1)\n    x = 2\n    send(x)\n",
+            ),
+            analyze(
+                &Python,
+                "net.py",
+                "def send(v):\n    pass\nclass Client:\n    def post(self):\n        pass\n    \
+                 def retry(self):\n        self.post()\n",
+            ),
+        ]
+    }
+
+    #[test]
+    fn lists_each_resolved_call_site() {
+        let files = files();
+        let p = preview(&files, "net.py::send", "dispatch").unwrap();
+        assert_eq!(p.definition, "net.py::send");
+        assert_eq!((p.from.as_str(), p.to.as_str()), ("send", "dispatch"));
+        assert_eq!((p.span.start_line, p.span.end_line), (1, 2));
+        let lines: Vec<(&str, usize)> = p
+            .sites
+            .iter()
+            .map(|s| (s.caller.as_str(), s.span.start_line))
+            .collect();
+        assert_eq!(lines, [("app.py::main", 3), ("app.py::main", 5)]);
+        assert_eq!(p.sites[0].call, "net.send");
+        assert!(p.clashes.is_empty());
+    }
+
+    #[test]
+    fn methods_by_qualified_name() {
+        let files = files();
+        let p = preview(&files, "Client.post", "put").unwrap();
+        assert_eq!(p.definition, "net.py::Client.post");
+        assert_eq!(p.sites.len(), 1);
+        assert_eq!(p.sites[0].caller, "net.py::Client.retry");
+        assert_eq!(p.sites[0].call, "self.post");
+    }
+
+    #[test]
+    fn qualified_new_name_and_clashes() {
+        let files = files();
+        let p = preview(&files, "Client.retry", "net::Client.send").unwrap();
+        assert_eq!(p.to, "send");
+        assert_eq!(p.clashes, ["net.py::send"]);
+    }
+
+    #[test]
+    fn ambiguous_or_unknown_old_name() {
+        let files = [
+            analyze(&Python, "a.py", "def h(): pass\n"),
+            analyze(&Python, "b.py", "def h(): pass\n"),
+        ];
+        let err = preview(&files, "h", "x").unwrap_err();
+        assert_eq!(err, "2 definitions match, use one of: a.py::h, b.py::h");
+        assert_eq!(
+            preview(&files, "a.py::h", "x").unwrap().definition,
+            "a.py::h"
+        );
+        assert_eq!(
+            preview(&files, "nope", "x").unwrap_err(),
+            "no such definition"
+        );
+        assert!(preview(&files, "a.py::h", "a.").is_err());
+    }
+
+    #[test]
+    fn old_name_by_symbol() {
+        let mut files = files();
+        crate::modules::assign(&mut files);
+        let p = preview(&files, "net.Client.post", "put").unwrap();
+        assert_eq!(p.definition, "net.py::Client.post");
+    }
+}
@@ -0,0 +1,275 @@
+use crate::api::tree_sitter::{ASTNode, Span, Syntax};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Calls that abort the process or unwind; the default `bolo panics` list.
+pub const PANIC_CALLS: &[&str] = &[
+    "panic!",
+    "unreachable!",
+    "todo!",
+    "unimplemented!",
+    "unwrap",
+    "expect",
+    "exit",
+    "_exit",
+    "abort",
+];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CallSite {
+    /// Resolved call name as it appears in the Call node.
+    pub name: String,
+    pub function: Option<String>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileCalls {
+    pub file: String,
+    pub calls: Vec<CallSite>,
+}
+
+/// True if the call `name` matches `pattern`.
+///
+/// Patterns with `*` are globs over the whole name (`requests.*`). Plain patterns
+/// match the name or a trailing `.`/`::` segment run: `exit` matches `sys.exit`
+/// and `std::process::exit`, but not `on_exit`.
+pub fn matches(name: &str, pattern: &str) -> bool {
+    if pattern.contains('*') {
+        return glob(name, pattern);
+    }
+    match name.strip_suffix(pattern) {
+        Some("") => true,
+        Some(head) => head.ends_with('.') || head.ends_with("::"),
+        None => false,
+    }
+}
+
+/// `*` matches any run of characters, including none.
+pub(crate) fn glob(name: &str, pattern: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let Some(mut rest) = name.strip_prefix(parts.next().unwrap_or("")) else {
+        return false;
+    };
+    let parts: Vec<_> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+/// Calls matching any of `patterns`, grouped by file. Files without hits are omitted.
+pub fn collect(files: &[Vec<Syntax>], patterns: &[String]) -> Vec<FileCalls> {
+    let mut out: Vec<FileCalls> = Vec::new();
+    super::each_node(files, |file, function, s| {
+        let ASTNode::Call(call) = &s.node else {
+            return;
+        };
+        if !patterns.iter().any(|p| matches(&call.name, p)) {
+            return;
+        }
+        let site = CallSite {
+            name: call.name.to_string(),
+            function: function.map(str::to_string),
+            span: s.metadata.span,
+        };
+        match out.last_mut() {
+            Some(group) if group.file == file => group.calls.push(site),
+            _ => out.push(FileCalls {
+                file: file.to_string(),
+                calls: vec![site],
+            }),
+        }
+    });
+    out
+}
+
+/// Add the name of every watchlist a Call matches to its `tags`.
+pub fn tag(files: &mut [Vec<Syntax>], lists: &BTreeMap<String, Vec<String>>) {
+    for nodes in files {
+        tag_nodes(nodes, lists);
+    }
+}
+
+fn tag_nodes(nodes: &mut [Syntax], lists: &BTreeMap<String, Vec<String>>) {
+    for s in nodes {
+        if let ASTNode::Call(call) = &mut s.node {
+            for (list, patterns) in lists {
+                if patterns.iter().any(|p| matches(&call.name, p)) {
+                    call.tags.push(list.clone());
+                }
+            }
+        }
+        tag_nodes(&mut s.contains, lists);
+    }
+}
+
+/// Per-watchlist report: every list, including ones without hits.
+pub fn watchlists(
+    files: &[Vec<Syntax>],
+    lists: &BTreeMap<String, Vec<String>>,
+) -> BTreeMap<String, Vec<FileCalls>> {
+    lists
+        .iter()
+        .map(|(name, patterns)| (name.clone(), collect(files, patterns)))
+        .collect()
+}
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::tree_sitter::analyze;
+    use crate::api::tree_sitter::py::Python;
+    use crate::api::tree_sitter::rs::Rust;
+
+    fn defaults() -> Vec<String> {
+        PANIC_CALLS.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn lists(entries: &[(&str, &[&str])]) -> BTreeMap<String, Vec<String>> {
+        entries
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.iter().map(|p| p.to_string()).collect()))
+            .collect()
+    }
+
+    fn tags_of(nodes: &[Syntax]) -> Vec<(String, Vec<String>)> {
+        let mut out = Vec::new();
+        for s in nodes {
+            if let ASTNode::Call(c) = &s.node {
+                out.push((c.name.to_string(), c.tags.clone()));
+            }
+            out.extend(tags_of(&s.contains));
+        }
+        out
+    }
+
+    // ── matches ──
+
+    #[test]
+    fn matches_exact() {
+        assert!(matches("panic!", "panic!"));
+    }
+
+    #[test]
+    fn matches_method_segment() {
+        assert!(matches("cfg.unwrap", "unwrap"));
+        assert!(matches("sys.exit", "exit"));
+        assert!(matches("std::process::exit", "exit"));
+        assert!(matches("std::process::exit", "process::exit"));
+    }
+
+    #[test]
+    fn partial_segment_does_not_match() {
+        assert!(!matches("on_exit", "exit"));
+        assert!(!matches("x.unwrap_or", "unwrap"));
+        assert!(!matches("exit", "sys.exit"));
+    }
+
+    #[test]
+    fn glob_patterns() {
+        assert!(matches("requests.get", "requests.*"));
+        assert!(matches("reqwest::Client::new", "reqwest::*"));
+        assert!(matches("subprocess.run", "*.run"));
+        assert!(matches("os.path.join", "os.*.join"));
+        assert!(matches("anything", "*"));
+        assert!(!matches("requests", "requests.*"));
+        assert!(!matches("myrequests.get", "requests.*"));
+    }
+
+    // ── collect ──
+
+    #[test]
+    fn rust_panics_grouped_by_file() {
+        let files = vec![
+            analyze(&Rust, "a.rs", "fn f() { x.unwrap(); panic!(\"no\"); ok() }"),
+            analyze(&Rust, "b.rs", "fn g() { y.unwrap_or(1) }"),
+            analyze(&Rust, "c.rs", "fn h() { std::process::exit(1) }"),
+        ];
+        let report = collect(&files, &defaults());
+        assert_eq!(report.len(), 2);
+        assert_eq!(report[0].file, "a.rs");
+        let names: Vec<_> = report[0].calls.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["x.unwrap", "panic!"]);
+        assert_eq!(report[0].calls[0].function.as_deref(), Some("f"));
+        assert_eq!(report[1].calls[0].name, "std::process::exit");
+    }
+
+    #[test]
+    fn rust_panics_inside_inline_modules() {
+        let src = "fn f() {}\n#[cfg(test)]\nmod tests {\n    fn t() { x.expect(\"set\"); }\n    mod deep { fn u() { y.unwrap() } }\n}";
+        let report = collect(&[analyze(&Rust, "a.rs", src)], &defaults());
+        let calls: Vec<_> = report[0]
+            .calls
+            .iter()
+            .map(|c| (c.name.as_str(), c.function.as_deref()))
+            .collect();
+        assert_eq!(calls, [("x.expect", Some("t")), ("y.unwrap", Some("u"))]);
+        assert_eq!(report[0].calls[0].span.start_line, 4);
+    }
+
+    #[test]
+    fn python_exits() {
+        let src = "import sys, os\ndef main():\n    sys.exit(1)\nos._exit(2)\n";
+        let report = collect(&[analyze(&Python, "m.py", src)], &defaults());
+        let calls = &report[0].calls;
+        assert_eq!(calls[0].name, "sys.exit");
+        assert_eq!(calls[0].function.as_deref(), Some("main"));
+        assert_eq!(calls[0].span.start_line, 3);
+        assert_eq!(calls[1].name, "os._exit");
+        assert!(calls[1].function.is_none());
+    }
+
+    // ── tag / watchlists ──
+
+    #[test]
+    fn tag_marks_matching_calls() {
+        let src = "import requests, subprocess\ndef f():\n    requests.get(u)\n    subprocess.run(c)\n    ok()\n";
+        let mut files = vec![analyze(&Python, "m.py", src)];
+        let cfg = lists(&[
+            ("network", &["requests.*"]),
+            ("process", &["subprocess.*", "*.get"]),
+        ]);
+        tag(&mut files, &cfg);
+        assert_eq!(
+            tags_of(&files[0]),
+            vec![
+                (
+                    "requests.get".into(),
+                    vec!["network".into(), "process".into()]
+                ),
+                ("subprocess.run".into(), vec!["process".into()]),
+                ("ok".into(), vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn watchlists_report_per_list() {
+        let files = vec![analyze(
+            &Rust,
+            "a.rs",
+            "fn f() { reqwest::get(u); std::fs::read(p) }",
+        )];
+        let cfg = lists(&[("network", &["reqwest::*"]), ("db", &["sqlx::*"])]);
+        let report = watchlists(&files, &cfg);
+        assert_eq!(report["network"][0].calls[0].name, "reqwest::get");
+        assert!(report["db"].is_empty());
+    }
+
+    #[test]
+    fn custom_list_replaces_defaults() {
+        let files = vec![analyze(&Rust, "a.rs", "fn f() { x.unwrap(); dbg!(x) }")];
+        let report = collect(&files, &["dbg!".to_string()]);
+        assert_eq!(report[0].calls.len(), 1);
+        assert_eq!(report[0].calls[0].name, "dbg!");
+    }
+}
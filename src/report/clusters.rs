@@ -0,0 +1,223 @@
+use crate::api::tree_sitter::{ASTNode, Syntax};
+use crate::graph::Graph;
+use crate::report::directory;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Label propagation rounds before giving up on convergence.
+const MAX_ROUNDS: usize = 100;
+
+/// Files grouped by how they call each other, next to where they live.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Clusters {
+    /// Groups of two or more connected files, largest first.
+    pub clusters: Vec<Cluster>,
+    /// Files whose calls tie them to another directory's cluster.
+    pub misplaced: Vec<Misplaced>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Cluster {
+    /// The directory holding most of the files.
+    pub directory: String,
+    /// Share of the files in `directory`, from 0 to 1.
+    pub purity: f64,
+    pub files: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Misplaced {
+    pub file: String,
+    pub directory: String,
+    /// The directory of the cluster the file's calls put it in.
+    pub cluster: String,
+    /// Calls to and from files in `cluster`, and in its own directory.
+    pub calls_to_cluster: usize,
+    pub calls_to_directory: usize,
+}
+
+/// Cluster the file-level call graph by weighted label propagation, treating
+/// calls in either direction alike, and compare the clusters to directories.
+///
+/// A file is misplaced when its cluster mostly lives in another directory and
+/// more of its calls go there than to its own directory.
+pub fn collect<'a>(files: &'a [Vec<Syntax<'a>>]) -> Clusters {
+    let paths: Vec<&str> = files
+        .iter()
+        .filter_map(|nodes| match nodes.first().map(|s| &s.node) {
+            Some(ASTNode::File(f)) => Some(f.path.as_ref()),
+            _ => None,
+        })
+        .collect();
+    let index: BTreeMap<&str, usize> = paths.iter().enumerate().map(|(i, p)| (*p, i)).collect();
+
+    let mut weights: Vec<BTreeMap<usize, usize>> = vec![BTreeMap::new(); paths.len()];
+    for ((a, b), w) in Graph::build(files).file_weights() {
+        let (Some(&a), Some(&b)) = (index.get(a), index.get(b)) else {
+            continue;
+        };
+        *weights[a].entry(b).or_default() += w;
+        *weights[b].entry(a).or_default() += w;
+    }
+
+    let labels = propagate(&weights);
+    let mut groups: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for (i, &label) in labels.iter().enumerate() {
+        groups.entry(label).or_default().push(i);
+    }
+
+    let mut out = Clusters::default();
+    for members in groups.into_values().filter(|m| m.len() > 1) {
+        let mut by_dir: BTreeMap<&str, usize> = BTreeMap::new();
+        for &i in &members {
+            *by_dir.entry(directory(paths[i])).or_default() += 1;
+        }
+        let (home, count) = by_dir
+            .iter()
+            .max_by_key(|(dir, n)| (**n, std::cmp::Reverse(**dir)))
+            .map(|(d, n)| (*d, *n))
+            .unwrap_or_default();
+
+        for &i in &members {
+            let own = directory(paths[i]);
+            if own == home {
+                continue;
+            }
+            let calls_to = |dir: &str| {
+                weights[i]
+                    .iter()
+                    .filter(|(j, _)| directory(paths[**j]) == dir)
+                    .map(|(_, w)| w)
+                    .sum::<usize>()
+            };
+            let (to_cluster, to_own) = (calls_to(home), calls_to(own));
+            if to_cluster > to_own {
+                out.misplaced.push(Misplaced {
+                    file: paths[i].to_string(),
+                    directory: own.to_string(),
+                    cluster: home.to_string(),
+                    calls_to_cluster: to_cluster,
+                    calls_to_directory: to_own,
+                });
+            }
+        }
+        out.clusters.push(Cluster {
+            directory: home.to_string(),
+            purity: count as f64 / members.len() as f64,
+            files: members.iter().map(|&i| paths[i].to_string()).collect(),
+        });
+    }
+    out.clusters.sort_by(|a, b| {
+        b.files
+            .len()
+            .cmp(&a.files.len())
+            .then(a.files.cmp(&b.files))
+    });
+    out.misplaced.sort_by(|a, b| a.file.cmp(&b.file));
+    out
+}
+
+/// Each node repeatedly takes the label with the most edge weight among its
+/// neighbors, visiting nodes in order so runs are reproducible. Ties keep the
+/// current label if it is among the best, else take the smallest.
+fn propagate(weights: &[BTreeMap<usize, usize>]) -> Vec<usize> {
+    let mut labels: Vec<usize> = (0..weights.len()).collect();
+    for _ in 0..MAX_ROUNDS {
+        let mut changed = false;
+        for (i, neighbors) in weights.iter().enumerate() {
+            let mut score: BTreeMap<usize, usize> = BTreeMap::new();
+            for (&j, &w) in neighbors {
+                *score.entry(labels[j]).or_default() += w;
+            }
+            let Some(&best) = score.values().max() else {
+                continue;
+            };
+            if score.get(&labels[i]) == Some(&best) {
+                continue;
+            }
+            if let Some((&label, _)) = score.iter().find(|(_, w)| **w == best) {
+                labels[i] = label;
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+    labels
+}
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::tree_sitter::analyze;
+    use crate::api::tree_sitter::py::Python;
+
+    #[test]
+    fn unconnected_files_form_no_clusters() {
+        let files = [
+            analyze(&Python, "a.py", "def f(): pass\n"),
+            analyze(&Python, "b.py", "def g(): pass\n"),
+        ];
+        let c = collect(&files);
+        assert!(c.clusters.is_empty());
+        assert!(c.misplaced.is_empty());
+    }
+
+    #[test]
+    fn two_groups_split() {
+        let files = [
+            analyze(&Python, "db/conn.py", "def connect():\n    query()\n"),
+            analyze(&Python, "db/query.py", "def query():\n    connect()\n"),
+            analyze(&Python, "ui/view.py", "def show():\n    render()\n"),
+            analyze(&Python, "ui/render.py", "def render():\n    show()\n"),
+        ];
+        let c = collect(&files);
+        assert_eq!(c.clusters.len(), 2);
+        assert_eq!(c.clusters[0].directory, "db");
+        assert_eq!(c.clusters[0].files, vec!["db/conn.py", "db/query.py"]);
+        assert_eq!(c.clusters[0].purity, 1.0);
+        assert_eq!(c.clusters[1].directory, "ui");
+        assert!(c.misplaced.is_empty());
+    }
+
+    #[test]
+    fn file_in_wrong_directory_flagged() {
+        let files = [
+            analyze(
+                &Python,
+                "db/conn.py",
+                "def connect():\n    query()\n    pool()\n",
+            ),
+            analyze(
+                &Python,
+                "db/query.py",
+                "def query():\n    connect()\n    pool()\n",
+            ),
+            analyze(&Python, "util/pool.py", "def pool():\n    connect()\n"),
+            analyze(&Python, "util/text.py", "def pad(): pass\n"),
+        ];
+        let c = collect(&files);
+        assert_eq!(c.clusters.len(), 1);
+        assert_eq!(c.clusters[0].directory, "db");
+        assert!((c.clusters[0].purity - 2.0 / 3.0).abs() < 1e-9);
+        assert_eq!(c.misplaced.len(), 1);
+        let m = &c.misplaced[0];
+        assert_eq!(m.file, "util/pool.py");
+        assert_eq!(m.directory, "util");
+        assert_eq!(m.cluster, "db");
+        assert_eq!((m.calls_to_cluster, m.calls_to_directory), (3, 0));
+    }
+
+    #[test]
+    fn propagation_is_deterministic() {
+        let mut w = vec![BTreeMap::new(); 4];
+        for (a, b) in [(0, 1), (1, 2), (2, 3), (3, 0)] {
+            w[a].insert(b, 1);
+            w[b].insert(a, 1);
+        }
+        assert_eq!(propagate(&w), propagate(&w));
+    }
+}
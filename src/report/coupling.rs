@@ -0,0 +1,172 @@
+use crate::api::tree_sitter::{ASTNode, Syntax};
+use crate::graph::Graph;
+use crate::report::directory;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Robert Martin's package metrics for one directory.
+#[derive(Debug, Clone, Serialize)]
+pub struct Package {
+    /// Directory of the files, `.` for top-level ones.
+    pub package: String,
+    pub files: usize,
+    /// Afferent coupling: files outside the package that call into it.
+    pub ca: usize,
+    /// Efferent coupling: files outside the package that it calls into.
+    pub ce: usize,
+    /// `ce / (ca + ce)`: 0 is depended on and stable, 1 depends and is free to change.
+    pub instability: f64,
+    pub types: usize,
+    pub abstract_types: usize,
+    /// `abstract_types / types`.
+    pub abstractness: f64,
+    /// `|abstractness + instability − 1|`: how far the package is from the
+    /// main sequence, between the zone of pain (0, 0) and uselessness (1, 1).
+    pub distance: f64,
+}
+
+/// Per-directory coupling, instability, abstractness and distance from the
+/// main sequence over the resolved file-level call graph, farthest first.
+///
+/// Types are counted once per name per package, so an `impl` block adds
+/// nothing beside its type, and trait impls (`Trait for Foo`) are skipped.
+/// Packages without calls in or out have instability 0; without types,
+/// abstractness 0.
+pub fn collect<'a>(files: &'a [Vec<Syntax<'a>>]) -> Vec<Package> {
+    #[derive(Default)]
+    struct Tally<'a> {
+        files: usize,
+        afferent: BTreeSet<&'a str>,
+        efferent: BTreeSet<&'a str>,
+        types: BTreeMap<&'a str, bool>,
+    }
+
+    let mut tally: BTreeMap<&str, Tally> = BTreeMap::new();
+    for nodes in files {
+        let Some(ASTNode::File(file)) = nodes.first().map(|s| &s.node) else {
+            continue;
+        };
+        let t = tally.entry(directory(&file.path)).or_default();
+        t.files += 1;
+        types(nodes, &mut t.types);
+    }
+    for (a, b) in Graph::build(files).file_edges() {
+        let (from, to) = (directory(a), directory(b));
+        if from == to {
+            continue;
+        }
+        tally.entry(from).or_default().efferent.insert(b);
+        tally.entry(to).or_default().afferent.insert(a);
+    }
+
+    let ratio = |n: usize, d: usize| if d == 0 { 0.0 } else { n as f64 / d as f64 };
+    let mut out: Vec<Package> = tally
+        .into_iter()
+        .map(|(package, t)| {
+            let (ca, ce) = (t.afferent.len(), t.efferent.len());
+            let abstract_types = t.types.values().filter(|a| **a).count();
+            let instability = ratio(ce, ca + ce);
+            let abstractness = ratio(abstract_types, t.types.len());
+            Package {
+                package: package.to_string(),
+                files: t.files,
+                ca,
+                ce,
+                instability,
+                types: t.types.len(),
+                abstract_types,
+                abstractness,
+                distance: (abstractness + instability - 1.0).abs(),
+            }
+        })
+        .collect();
+    out.sort_by(|a, b| {
+        b.distance
+            .total_cmp(&a.distance)
+            .then_with(|| a.package.cmp(&b.package))
+    });
+    out
+}
+
+/// Every Type in `nodes` by name, abstract if any definition of it is.
+fn types<'a>(nodes: &'a [Syntax], out: &mut BTreeMap<&'a str, bool>) {
+    for s in nodes {
+        if let ASTNode::Type(t) = &s.node
+            && !t.name.contains(" for ")
+        {
+            *out.entry(&t.name).or_default() |= t.is_abstract;
+        }
+        types(&s.contains, out);
+    }
+}
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::tree_sitter::analyze;
+    use crate::api::tree_sitter::rs::Rust;
+
+    fn package<'p>(packages: &'p [Package], name: &str) -> &'p Package {
+        packages.iter().find(|p| p.package == name).unwrap()
+    }
+
+    #[test]
+    fn stable_abstract_core_and_unstable_app() {
+        let files = [
+            analyze(
+                &Rust,
+                "core/repo.rs",
+                "pub trait Repo {}\npub fn open() {}\n",
+            ),
+            analyze(&Rust, "app/main.rs", "struct App;\nfn main() { open(); }\n"),
+            analyze(&Rust, "app/cli.rs", "fn cli() { open(); }\n"),
+        ];
+        let p = collect(&files);
+        let core = package(&p, "core");
+        assert_eq!((core.ca, core.ce), (2, 0));
+        assert_eq!(core.instability, 0.0);
+        assert_eq!(core.abstractness, 1.0);
+        assert_eq!(core.distance, 0.0);
+
+        let app = package(&p, "app");
+        assert_eq!((app.files, app.ca, app.ce), (2, 0, 1));
+        assert_eq!(app.instability, 1.0);
+        assert_eq!((app.types, app.abstract_types), (1, 0));
+        assert_eq!(app.distance, 0.0);
+    }
+
+    #[test]
+    fn concrete_stable_package_in_zone_of_pain() {
+        let files = [
+            analyze(&Rust, "util/text.rs", "pub struct Pad;\npub fn pad() {}\n"),
+            analyze(&Rust, "a/x.rs", "fn x() { pad(); }\n"),
+        ];
+        let p = collect(&files);
+        assert_eq!(p[0].package, "util");
+        assert_eq!(p[0].distance, 1.0);
+    }
+
+    #[test]
+    fn impl_blocks_not_counted_as_types() {
+        let files = [analyze(
+            &Rust,
+            "lib.rs",
+            "struct S;\nimpl S { fn new() {} }\nimpl Clone for S { fn clone() {} }\n",
+        )];
+        let p = collect(&files);
+        assert_eq!(p[0].package, ".");
+        assert_eq!(p[0].types, 1);
+    }
+
+    #[test]
+    fn calls_within_package_not_coupling() {
+        let files = [
+            analyze(&Rust, "core/a.rs", "fn a() { b(); }\n"),
+            analyze(&Rust, "core/b.rs", "fn b() {}\n"),
+        ];
+        let p = collect(&files);
+        assert_eq!((p[0].ca, p[0].ce), (0, 0));
+    }
+}
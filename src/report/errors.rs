@@ -0,0 +1,122 @@
+use crate::api::tree_sitter::{ASTNode, ExceptionKind, Syntax};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// An error type and the modules whose functions return or propagate it.
+#[derive(Debug, Clone, Serialize)]
+pub struct ErrorFlow {
+    pub error: String,
+    pub modules: Vec<ModuleFlow>,
+}
+
+/// Where one error type passes through a module.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ModuleFlow {
+    /// `crate::a::b` when the file's module is known, else its path.
+    pub module: String,
+    pub file: String,
+    /// Functions returning `Result<_, error>`, in source order.
+    pub functions: Vec<String>,
+    /// `?` operators in those functions.
+    pub propagations: usize,
+}
+
+/// Group the error types functions return by module, errors passing
+/// through the most modules first, then by name.
+pub fn collect(files: &[Vec<Syntax>]) -> Vec<ErrorFlow> {
+    let mut by_error: BTreeMap<String, Vec<ModuleFlow>> = BTreeMap::new();
+    for nodes in files {
+        let Some(ASTNode::File(file)) = nodes.first().map(|s| &s.node) else {
+            continue;
+        };
+        let module = file.module.as_deref().unwrap_or(&file.path);
+        super::each_node(std::slice::from_ref(nodes), |path, function, s| {
+            let ASTNode::Exception(e) = &s.node else {
+                return;
+            };
+            if !matches!(e.kind, ExceptionKind::Returns | ExceptionKind::Propagates) {
+                return;
+            }
+            let flows = by_error.entry(e.name.to_string()).or_default();
+            let flow = match flows.iter().position(|f| f.file == path) {
+                Some(i) => &mut flows[i],
+                None => {
+                    flows.push(ModuleFlow {
+                        module: module.to_string(),
+                        file: path.to_string(),
+                        functions: vec![],
+                        propagations: 0,
+                    });
+                    flows.last_mut().unwrap()
+                }
+            };
+            match e.kind {
+                ExceptionKind::Returns => flow.functions.extend(function.map(str::to_string)),
+                _ => flow.propagations += 1,
+            }
+        });
+    }
+    let mut out: Vec<ErrorFlow> = by_error
+        .into_iter()
+        .map(|(error, modules)| ErrorFlow { error, modules })
+        .collect();
+    out.sort_by_key(|f| std::cmp::Reverse(f.modules.len()));
+    out
+}
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::tree_sitter::analyze;
+    use crate::api::tree_sitter::rs::Rust;
+
+    #[test]
+    fn no_results_no_flows() {
+        let files = vec![analyze(&Rust, "a.rs", "fn f() -> u8 { g() }")];
+        assert!(collect(&files).is_empty());
+    }
+
+    #[test]
+    fn functions_and_propagations_per_module() {
+        let src = "fn load() -> Result<(), Error> { read()?; parse()?; Ok(()) }\n\
+                   fn save() -> Result<(), Error> { write()?; Ok(()) }\n";
+        let flows = collect(&[analyze(&Rust, "src/config.rs", src)]);
+        assert_eq!(flows.len(), 1);
+        assert_eq!(flows[0].error, "Error");
+        assert_eq!(
+            flows[0].modules,
+            [ModuleFlow {
+                module: "src/config.rs".into(),
+                file: "src/config.rs".into(),
+                functions: vec!["load".into(), "save".into()],
+                propagations: 3,
+            }]
+        );
+    }
+
+    #[test]
+    fn widest_errors_first() {
+        let files = vec![
+            analyze(
+                &Rust,
+                "a.rs",
+                "fn a() -> io::Result<()> {}\nfn b() -> Result<u8, Bad> {}",
+            ),
+            analyze(&Rust, "b.rs", "fn c() -> Result<u8, Bad> { a()? }"),
+        ];
+        let flows = collect(&files);
+        let summary: Vec<(&str, Vec<&str>)> = flows
+            .iter()
+            .map(|f| {
+                let files = f.modules.iter().map(|m| m.file.as_str()).collect();
+                (f.error.as_str(), files)
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [("Bad", vec!["a.rs", "b.rs"]), ("io::Error", vec!["a.rs"])]
+        );
+    }
+}
@@ -0,0 +1,127 @@
+use crate::api::tree_sitter::{ASTNode, ExceptionKind, Span, Syntax};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+/// Handlers that catch every exception a module raises.
+const CATCH_ALL: &[&str] = &["Exception", "BaseException"];
+
+/// One raise or handle edge between a function and an exception type.
+#[derive(Debug, Clone, Serialize)]
+pub struct ExceptionSite {
+    pub file: String,
+    pub kind: ExceptionKind,
+    pub exception: String,
+    /// Nearest enclosing function; `None` at module level.
+    pub function: Option<String>,
+    /// For raises: true when no `except` in the same file catches the
+    /// type, by its last segment, or a catch-all.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub escapes: Option<bool>,
+    pub span: Span,
+}
+
+/// Collect every raised and handled exception across cleaned per-file
+/// results, in file order.
+pub fn collect(files: &[Vec<Syntax>]) -> Vec<ExceptionSite> {
+    let mut out = Vec::new();
+    super::each_node(files, |file, function, s| {
+        if let ASTNode::Exception(e) = &s.node {
+            out.push(ExceptionSite {
+                file: file.to_string(),
+                kind: e.kind,
+                exception: e.name.to_string(),
+                function: function.map(str::to_string),
+                escapes: None,
+                span: s.metadata.span,
+            });
+        }
+    });
+
+    let mut handled: HashMap<String, HashSet<String>> = HashMap::new();
+    for s in out.iter().filter(|s| s.kind == ExceptionKind::Handle) {
+        handled
+            .entry(s.file.clone())
+            .or_default()
+            .insert(last_segment(&s.exception).to_string());
+    }
+    for s in out.iter_mut().filter(|s| s.kind == ExceptionKind::Raise) {
+        let caught = handled.get(&s.file).is_some_and(|h| {
+            h.contains(last_segment(&s.exception)) || CATCH_ALL.iter().any(|&c| h.contains(c))
+        });
+        s.escapes = Some(!caught);
+    }
+    out
+}
+
+fn last_segment(name: &str) -> &str {
+    name.rsplit('.').next().unwrap_or(name)
+}
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::tree_sitter::analyze;
+    use crate::api::tree_sitter::py::Python;
+
+    #[test]
+    fn no_exceptions_no_sites() {
+        let files = vec![analyze(&Python, "a.py", "def f():\n    g()\n")];
+        assert!(collect(&files).is_empty());
+    }
+
+    #[test]
+    fn raise_reports_enclosing_function() {
+        let src = "def load():\n    raise KeyError(\"x\")\n";
+        let sites = collect(&[analyze(&Python, "a.py", src)]);
+        assert_eq!(sites.len(), 1);
+        assert_eq!(sites[0].kind, ExceptionKind::Raise);
+        assert_eq!(sites[0].exception, "KeyError");
+        assert_eq!(sites[0].function.as_deref(), Some("load"));
+        assert_eq!(sites[0].escapes, Some(true));
+        assert_eq!(sites[0].span.start_line, 2);
+    }
+
+    #[test]
+    fn handled_in_same_file_does_not_escape() {
+        let src = "from app import errors\n\
+                   def load():\n    raise errors.Missing()\n\
+                   def run():\n    try:\n        load()\n    except Missing:\n        pass\n";
+        let sites = collect(&[analyze(&Python, "a.py", src)]);
+        let summary: Vec<_> = sites
+            .iter()
+            .map(|s| (s.kind, s.exception.as_str(), s.escapes))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (ExceptionKind::Raise, "app.errors.Missing", Some(false)),
+                (ExceptionKind::Handle, "Missing", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn catch_all_stops_every_raise() {
+        let src =
+            "def f():\n    try:\n        raise ValueError\n    except Exception:\n        pass\n";
+        let sites = collect(&[analyze(&Python, "a.py", src)]);
+        assert_eq!(sites[0].escapes, Some(false));
+    }
+
+    #[test]
+    fn handlers_in_other_files_do_not_count() {
+        let files = vec![
+            analyze(&Python, "a.py", "def f():\n    raise Timeout()\n"),
+            analyze(
+                &Python,
+                "b.py",
+                "try:\n    f()\nexcept Timeout:\n    pass\n",
+            ),
+        ];
+        let sites = collect(&files);
+        assert_eq!(sites[0].file, "a.py");
+        assert_eq!(sites[0].escapes, Some(true));
+    }
+}
@@ -0,0 +1,264 @@
+use crate::api::tree_sitter::Span;
+use crate::api::tree_sitter::rs::{Cfg, Gated, GatedKind};
+use crate::crates::{self, Crate};
+use serde::Serialize;
+use std::collections::BTreeSet;
+
+/// The feature combinations of each crate, and the definitions and calls
+/// compiled under some of its combinations but not all.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Matrix {
+    pub combinations: Vec<Combination>,
+    pub gated: Vec<Entry>,
+}
+
+/// One set of enabled features for a crate.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Combination {
+    #[serde(rename = "crate")]
+    pub krate: String,
+    /// `no-default-features`, `default`, a feature's name (that feature
+    /// alone, without the defaults) or `all-features`.
+    pub name: String,
+    /// Every feature enabled, following what features enable.
+    pub features: Vec<String>,
+}
+
+/// A definition or call that only some combinations compile.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Entry {
+    pub file: String,
+    #[serde(rename = "crate")]
+    pub krate: String,
+    pub kind: GatedKind,
+    /// `f` or `Type::f` for definitions, the callee for calls.
+    pub name: String,
+    /// The function a call is made from.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub caller: Option<String>,
+    /// Its conditions, joined with `all(...)` when there are several.
+    pub cfg: String,
+    /// Names of the combinations it is compiled under.
+    pub present: Vec<String>,
+    pub span: Span,
+}
+
+/// The feature sets worth building `c` with: none, the defaults, each
+/// feature alone, and all of them. Sets equal to an earlier one are left
+/// out, so a crate without features has one combination.
+pub fn combinations(c: &Crate) -> Vec<Combination> {
+    let declared: Vec<&str> = c.features.keys().map(String::as_str).collect();
+    let mut seeds: Vec<(&str, Vec<&str>)> = vec![
+        ("no-default-features", vec![]),
+        ("default", vec!["default"]),
+    ];
+    seeds.extend(
+        declared
+            .iter()
+            .filter(|&&f| f != "default")
+            .map(|&f| (f, vec![f])),
+    );
+    seeds.push(("all-features", declared));
+
+    let mut out: Vec<Combination> = Vec::new();
+    for (name, seed) in seeds {
+        let features: Vec<String> = enabled(c, &seed).into_iter().collect();
+        if out.iter().all(|o| o.features != features) {
+            out.push(Combination {
+                krate: c.name.clone(),
+                name: name.to_string(),
+                features,
+            });
+        }
+    }
+    out
+}
+
+/// `seed` and every feature they enable in turn. `dep:x` and `x/y`
+/// entries name dependencies, not features of `c`.
+fn enabled(c: &Crate, seed: &[&str]) -> BTreeSet<String> {
+    let mut on = BTreeSet::new();
+    let mut todo: Vec<&str> = seed.to_vec();
+    while let Some(f) = todo.pop() {
+        if f == "default" && !c.features.contains_key(f) {
+            continue;
+        }
+        if !on.insert(f.to_string()) {
+            continue;
+        }
+        if let Some(implied) = c.features.get(f) {
+            todo.extend(
+                implied
+                    .iter()
+                    .map(String::as_str)
+                    .filter(|i| !i.contains(':') && !i.contains('/')),
+            );
+        }
+    }
+    on
+}
+
+/// Evaluate each file's gated nodes under its crate's combinations and
+/// keep those compiled under some but not all. Files outside every crate
+/// are skipped; conditions on more than features (`unix`, `test`) count
+/// as met.
+pub fn collect(files: &[(String, Vec<Gated>)], crates: &[Crate]) -> Matrix {
+    let mut matrix = Matrix::default();
+    let mut sets: Vec<Vec<(String, BTreeSet<String>)>> = Vec::new();
+    for c in crates {
+        let combos = combinations(c);
+        sets.push(
+            combos
+                .iter()
+                .map(|k| (k.name.clone(), k.features.iter().cloned().collect()))
+                .collect(),
+        );
+        matrix.combinations.extend(combos);
+    }
+
+    for (file, gated) in files {
+        let Some(owner) = crates::owner(crates, file) else {
+            continue;
+        };
+        let index = crates
+            .iter()
+            .position(|c| std::ptr::eq(c, owner))
+            .unwrap_or_default();
+        let combos = &sets[index];
+        for g in gated {
+            let present: Vec<String> = combos
+                .iter()
+                .filter(|(_, on)| g.cfg.iter().all(|c| c.eval(on) != Some(false)))
+                .map(|(name, _)| name.clone())
+                .collect();
+            if present.len() == combos.len() {
+                continue;
+            }
+            let cfg = match g.cfg.as_slice() {
+                [one] => one.to_string(),
+                all => Cfg::All(all.to_vec()).to_string(),
+            };
+            matrix.gated.push(Entry {
+                file: file.clone(),
+                krate: owner.name.clone(),
+                kind: g.kind,
+                name: g.name.clone(),
+                caller: g.caller.clone(),
+                cfg,
+                present,
+                span: g.span,
+            });
+        }
+    }
+    matrix
+}
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::tree_sitter::Lang;
+    use crate::api::tree_sitter::rs::{self, Rust};
+    use std::collections::BTreeMap;
+
+    fn krate(features: &[(&str, &[&str])]) -> Crate {
+        Crate {
+            name: "app".into(),
+            dir: String::new(),
+            build: None,
+            proc_macro: false,
+            dependencies: vec![],
+            features: features
+                .iter()
+                .map(|(f, on)| (f.to_string(), on.iter().map(|s| s.to_string()).collect()))
+                .collect::<BTreeMap<_, _>>(),
+        }
+    }
+
+    fn gated(source: &str) -> Vec<Gated> {
+        let tree = Rust.get_parser().parse(source, None).unwrap();
+        rs::gated(&tree, source)
+    }
+
+    #[test]
+    fn combinations_follow_implied_features() {
+        let c = krate(&[
+            ("default", &["std"]),
+            ("std", &[]),
+            ("full", &["std", "net", "dep:tokio", "serde/derive"]),
+            ("net", &[]),
+        ]);
+        let combos: Vec<(String, Vec<String>)> = combinations(&c)
+            .into_iter()
+            .map(|k| (k.name, k.features))
+            .collect();
+        let set = |f: &[&str]| f.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            combos,
+            [
+                ("no-default-features".into(), set(&[])),
+                ("default".into(), set(&["default", "std"])),
+                ("full".into(), set(&["full", "net", "std"])),
+                ("net".into(), set(&["net"])),
+                ("std".into(), set(&["std"])),
+                (
+                    "all-features".into(),
+                    set(&["default", "full", "net", "std"])
+                ),
+            ]
+        );
+        assert_eq!(combinations(&krate(&[])).len(), 1);
+    }
+
+    #[test]
+    fn collect_keeps_what_some_combinations_drop() {
+        let src = "#[cfg(feature = \"net\")]\nfn fetch() {}\n\
+                   #[cfg(unix)]\nfn everywhere() {}\n\
+                   fn run() {\n    #[cfg(not(feature = \"std\"))]\n    alloc();\n}\n";
+        let c = krate(&[("default", &["std"]), ("std", &[]), ("net", &[])]);
+        let m = collect(&[("src/lib.rs".into(), gated(src))], &[c]);
+        assert_eq!(m.combinations.len(), 5);
+        let found: Vec<(&str, Option<&str>, &str, Vec<&str>)> = m
+            .gated
+            .iter()
+            .map(|e| {
+                let present = e.present.iter().map(String::as_str).collect();
+                (
+                    e.name.as_str(),
+                    e.caller.as_deref(),
+                    e.cfg.as_str(),
+                    present,
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (
+                    "fetch",
+                    None,
+                    "feature = \"net\"",
+                    vec!["net", "all-features"]
+                ),
+                (
+                    "alloc",
+                    Some("run"),
+                    "not(feature = \"std\")",
+                    vec!["no-default-features", "net"]
+                ),
+            ]
+        );
+        assert_eq!(m.gated[1].kind, GatedKind::Call);
+        assert_eq!(m.gated[1].krate, "app");
+    }
+
+    #[test]
+    fn files_outside_crates_skipped() {
+        let mut c = krate(&[("net", &[])]);
+        c.dir = "app".into();
+        let src = "#[cfg(feature = \"net\")]\nfn fetch() {}\n";
+        let m = collect(&[("tools/x.rs".into(), gated(src))], &[c]);
+        assert!(m.gated.is_empty());
+    }
+}
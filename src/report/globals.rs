@@ -0,0 +1,111 @@
+use crate::api::tree_sitter::{GlobalKind, Globals, Span};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// A mutable global and the functions that reference it.
+#[derive(Debug, Clone, Serialize)]
+pub struct GlobalState {
+    pub file: String,
+    pub name: String,
+    pub kind: GlobalKind,
+    /// `file::function` graph ids, in file then source order.
+    pub functions: Vec<String>,
+    pub span: Span,
+}
+
+/// Match every function's names against the globals of its own file, then
+/// against names defined in exactly one other file. Globals no function
+/// mentions are still listed.
+pub fn collect(files: &[(String, Globals)]) -> Vec<GlobalState> {
+    let mut out = Vec::new();
+    let mut index: HashMap<(&str, &str), usize> = HashMap::new();
+    let mut owners: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (file, globals) in files {
+        for g in &globals.defined {
+            index.insert((file, &g.name), out.len());
+            owners.entry(&g.name).or_default().push(out.len());
+            out.push(GlobalState {
+                file: file.clone(),
+                name: g.name.clone(),
+                kind: g.kind,
+                functions: vec![],
+                span: g.span,
+            });
+        }
+    }
+    for (file, globals) in files {
+        for (function, names) in &globals.uses {
+            for name in names {
+                let target = index
+                    .get(&(file.as_str(), name.as_str()))
+                    .copied()
+                    .or_else(|| match owners.get(name.as_str()).map(Vec::as_slice) {
+                        Some(&[only]) => Some(only),
+                        _ => None,
+                    });
+                if let Some(i) = target {
+                    out[i].functions.push(format!("{file}::{function}"));
+                }
+            }
+        }
+    }
+    out
+}
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::tree_sitter::Lang;
+    use crate::api::tree_sitter::py::{self, Python};
+
+    fn globals(source: &str) -> Globals {
+        let tree = Python.get_parser().parse(source, None).unwrap();
+        py::globals(&tree, source)
+    }
+
+    fn summary(states: &[GlobalState]) -> Vec<(&str, &str, Vec<&str>)> {
+        states
+            .iter()
+            .map(|s| {
+                let functions = s.functions.iter().map(String::as_str).collect();
+                (s.file.as_str(), s.name.as_str(), functions)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn no_globals_no_states() {
+        let files = vec![("a.py".into(), globals("X = 1\ndef f():\n    return X\n"))];
+        assert!(collect(&files).is_empty());
+    }
+
+    #[test]
+    fn references_in_same_file() {
+        let src = "CACHE = {}\ndef get(k):\n    return CACHE[k]\ndef other():\n    pass\n";
+        let states = collect(&[("a.py".into(), globals(src))]);
+        assert_eq!(summary(&states), [("a.py", "CACHE", vec!["a.py::get"])]);
+        assert_eq!(states[0].kind, GlobalKind::Container);
+    }
+
+    #[test]
+    fn references_across_files_resolve_unique_names() {
+        let files = vec![
+            ("a.py".into(), globals("REGISTRY = []\nSEEN = set()\n")),
+            ("b.py".into(), globals("SEEN = set()\n")),
+            (
+                "c.py".into(),
+                globals("def add(x):\n    REGISTRY.append(x)\n    SEEN.add(x)\n"),
+            ),
+        ];
+        assert_eq!(
+            summary(&collect(&files)),
+            [
+                ("a.py", "REGISTRY", vec!["c.py::add"]),
+                ("a.py", "SEEN", vec![]),
+                ("b.py", "SEEN", vec![]),
+            ]
+        );
+    }
+}
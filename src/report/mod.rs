@@ -0,0 +1,50 @@
+pub mod calls;
+pub mod clusters;
+pub mod coupling;
+pub mod errors;
+pub mod exceptions;
+pub mod features;
+pub mod globals;
+pub mod stats;
+pub mod stubs;
+pub mod unsafety;
+pub mod vocab;
+
+use crate::api::tree_sitter::{ASTNode, Syntax};
+
+/// Visit every node of every file with its file path and nearest enclosing function.
+///
+/// A function node is visited with its parent's context; its children see it as
+/// their enclosing function.
+pub(crate) fn each_node<'a>(
+    files: &'a [Vec<Syntax>],
+    mut f: impl FnMut(&'a str, Option<&'a str>, &'a Syntax),
+) {
+    for nodes in files {
+        let Some(ASTNode::File(file)) = nodes.first().map(|s| &s.node) else {
+            continue;
+        };
+        visit(nodes, &file.path, None, &mut f);
+    }
+}
+
+fn visit<'a>(
+    nodes: &'a [Syntax],
+    file: &'a str,
+    function: Option<&'a str>,
+    f: &mut impl FnMut(&'a str, Option<&'a str>, &'a Syntax),
+) {
+    for s in nodes {
+        f(file, function, s);
+        let inner = match &s.node {
+            ASTNode::Function(func) => Some(func.name.as_ref()),
+            _ => function,
+        };
+        visit(&s.contains, file, inner, f);
+    }
+}
+
+/// The directory part of a `/`-separated path, `.` for top-level files.
+pub(crate) fn directory(path: &str) -> &str {
+    path.rsplit_once('/').map_or(".", |(dir, _)| dir)
+}
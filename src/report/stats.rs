@@ -0,0 +1,144 @@
+use crate::api::tree_sitter::{ASTNode, Metadata, Syntax};
+use crate::clean::{CODE_LINES, COMMENT_LINES, DEFINITIONS, DOCUMENTED};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Comment and documentation coverage per file and per directory.
+#[derive(Debug, Clone, Serialize)]
+pub struct Stats {
+    pub files: Vec<Coverage>,
+    /// Files grouped by their directory, `.` for the top level.
+    pub modules: Vec<Coverage>,
+}
+
+/// Comment lines against code lines, and documented public definitions
+/// against all of them.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Coverage {
+    pub path: String,
+    pub comment_lines: usize,
+    pub code_lines: usize,
+    /// `comment_lines / code_lines`, 0 without code.
+    pub comment_ratio: f64,
+    pub definitions: usize,
+    pub documented: usize,
+    /// `documented / definitions`, 1 without definitions.
+    pub doc_coverage: f64,
+}
+
+impl Coverage {
+    /// The counts [`clean`](crate::clean::clean) left on a File node.
+    pub fn of(path: &str, meta: &Metadata) -> Self {
+        let count = |key| {
+            meta.metric(key)
+                .and_then(|v| v.as_u64())
+                .unwrap_or_default() as usize
+        };
+        Coverage {
+            path: path.to_string(),
+            comment_lines: count(COMMENT_LINES),
+            code_lines: count(CODE_LINES),
+            definitions: count(DEFINITIONS),
+            documented: count(DOCUMENTED),
+            ..Default::default()
+        }
+        .ratios()
+    }
+
+    fn add(&mut self, other: &Coverage) {
+        self.comment_lines += other.comment_lines;
+        self.code_lines += other.code_lines;
+        self.definitions += other.definitions;
+        self.documented += other.documented;
+    }
+
+    /// Fill in both ratios, rounded to three decimals.
+    fn ratios(mut self) -> Self {
+        let round = |r: f64| (r * 1000.0).round() / 1000.0;
+        self.comment_ratio = match self.code_lines {
+            0 => 0.0,
+            n => round(self.comment_lines as f64 / n as f64),
+        };
+        self.doc_coverage = match self.definitions {
+            0 => 1.0,
+            n => round(self.documented as f64 / n as f64),
+        };
+        self
+    }
+}
+
+/// Coverage of every file, in file order, and of every directory, by path.
+pub fn collect(files: &[Vec<Syntax>]) -> Stats {
+    let mut out = Vec::new();
+    let mut modules: BTreeMap<String, Coverage> = BTreeMap::new();
+    for nodes in files {
+        let Some(s) = nodes.first() else { continue };
+        let ASTNode::File(file) = &s.node else {
+            continue;
+        };
+        let coverage = Coverage::of(&file.path, &s.metadata);
+        let dir = super::directory(&file.path);
+        modules
+            .entry(dir.to_string())
+            .or_insert_with(|| Coverage {
+                path: dir.to_string(),
+                ..Default::default()
+            })
+            .add(&coverage);
+        out.push(coverage);
+    }
+    Stats {
+        files: out,
+        modules: modules.into_values().map(Coverage::ratios).collect(),
+    }
+}
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::tree_sitter::analyze;
+    use crate::api::tree_sitter::py::Python;
+
+    #[test]
+    fn empty_file_fully_covered() {
+        let stats = collect(&[analyze(&Python, "a.py", "")]);
+        assert_eq!(stats.files[0].comment_ratio, 0.0);
+        assert_eq!(stats.files[0].doc_coverage, 1.0);
+    }
+
+    #[test]
+    fn ratios_per_file() {
+        let src = "# helper\ndef f():\n    return 1\n\ndef g():\n    \"\"\"Doc.\"\"\"\n    return 2\n\ndef _h():\n    pass\n";
+        let stats = collect(&[analyze(&Python, "pkg/a.py", src)]);
+        assert_eq!(
+            stats.files[0],
+            Coverage {
+                path: "pkg/a.py".into(),
+                comment_lines: 2,
+                code_lines: 6,
+                comment_ratio: 0.333,
+                definitions: 2,
+                documented: 2,
+                doc_coverage: 1.0,
+            }
+        );
+    }
+
+    #[test]
+    fn modules_sum_their_files() {
+        let files = vec![
+            analyze(&Python, "pkg/a.py", "def f():\n    pass\n"),
+            analyze(&Python, "pkg/b.py", "# doc\ndef g():\n    pass\n"),
+            analyze(&Python, "main.py", "x = 1\n"),
+        ];
+        let stats = collect(&files);
+        let summary: Vec<_> = stats
+            .modules
+            .iter()
+            .map(|m| (m.path.as_str(), m.definitions, m.documented, m.doc_coverage))
+            .collect();
+        assert_eq!(summary, [(".", 0, 0, 1.0), ("pkg", 2, 1, 0.5)]);
+    }
+}
@@ -0,0 +1,224 @@
+use crate::api::tree_sitter::Span;
+use crate::api::tree_sitter::py::{Arity, Signature};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Where a `.pyi` stub and its `.py` implementation disagree.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Drift {
+    /// The `.pyi` file.
+    pub stub: String,
+    /// The `.py` file next to it.
+    pub implementation: String,
+    /// `f` or `Class.method`.
+    pub function: String,
+    pub kind: DriftKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stub_arity: Option<Arity>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub implementation_arity: Option<Arity>,
+    /// In the stub, or in the implementation for `missing-in-stub`.
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DriftKind {
+    /// Declared in the stub, not defined in the implementation.
+    MissingInImplementation,
+    /// A public function of the implementation the stub leaves out.
+    MissingInStub,
+    /// Both have it, taking different numbers of arguments.
+    Arity,
+}
+
+impl Drift {
+    /// The file `span` is in.
+    pub fn file(&self) -> &str {
+        match self.kind {
+            DriftKind::MissingInStub => &self.implementation,
+            _ => &self.stub,
+        }
+    }
+
+    /// One line for the terminal.
+    pub fn message(&self) -> String {
+        let f = &self.function;
+        match (self.kind, self.stub_arity, self.implementation_arity) {
+            (DriftKind::MissingInImplementation, _, _) => {
+                format!("`{f}` is declared in the stub but not defined")
+            }
+            (DriftKind::MissingInStub, _, _) => format!("`{f}` is public but not in the stub"),
+            (DriftKind::Arity, Some(s), Some(i)) => {
+                format!("`{f}` takes {s} argument(s) in the stub, {i} in the implementation")
+            }
+            (DriftKind::Arity, _, _) => format!("`{f}` takes different arguments"),
+        }
+    }
+}
+
+/// Compare a stub's signatures with its implementation's, in stub order and
+/// then implementation order. A name's `@overload` variants together give
+/// its arity; of several plain definitions (say under `if sys.version_info`)
+/// the first counts.
+pub fn compare(
+    stub_file: &str,
+    stub: &[Signature],
+    impl_file: &str,
+    implementation: &[Signature],
+) -> Vec<Drift> {
+    let declared = arities(stub);
+    let defined = arities(implementation);
+    let drift = |function: &str, kind, stub_arity, implementation_arity, span| Drift {
+        stub: stub_file.to_string(),
+        implementation: impl_file.to_string(),
+        function: function.to_string(),
+        kind,
+        stub_arity,
+        implementation_arity,
+        span,
+    };
+
+    let mut out = Vec::new();
+    for (name, (s, span)) in ordered(stub, &declared) {
+        match defined.get(name) {
+            None => out.push(drift(
+                name,
+                DriftKind::MissingInImplementation,
+                Some(s),
+                None,
+                span,
+            )),
+            Some(&(i, _)) if i != s => {
+                out.push(drift(name, DriftKind::Arity, Some(s), Some(i), span))
+            }
+            Some(_) => {}
+        }
+    }
+    for (name, (i, span)) in ordered(implementation, &defined) {
+        let public = !name.split('.').any(private_segment);
+        if public && !declared.contains_key(name) {
+            out.push(drift(name, DriftKind::MissingInStub, None, Some(i), span));
+        }
+    }
+    out
+}
+
+/// Each name's arity and the span of its first signature.
+fn arities(sigs: &[Signature]) -> BTreeMap<&str, (Arity, Span)> {
+    let mut out: BTreeMap<&str, (Arity, Span)> = BTreeMap::new();
+    let overloaded: Vec<&str> = sigs
+        .iter()
+        .filter(|s| s.overload)
+        .map(|s| s.name.as_str())
+        .collect();
+    for s in sigs {
+        // The implementation behind `@overload`s says less than they do.
+        if overloaded.contains(&s.name.as_str()) && !s.overload {
+            continue;
+        }
+        out.entry(&s.name)
+            .and_modify(|(a, _)| {
+                if s.overload {
+                    *a = a.union(s.arity);
+                }
+            })
+            .or_insert((s.arity, s.span));
+    }
+    out
+}
+
+/// `arities` in the order the names first appear in `sigs`.
+fn ordered<'a>(
+    sigs: &'a [Signature],
+    arities: &BTreeMap<&'a str, (Arity, Span)>,
+) -> Vec<(&'a str, (Arity, Span))> {
+    let mut seen = Vec::new();
+    for s in sigs {
+        if !seen.contains(&s.name.as_str()) {
+            seen.push(&s.name);
+        }
+    }
+    seen.into_iter()
+        .filter_map(|n| arities.get(n).map(|&a| (n, a)))
+        .collect()
+}
+
+/// A segment that makes a qualified name private: `_helper`, or `_Private`
+/// for its methods. Dunders like `__init__` are public.
+fn private_segment(segment: &str) -> bool {
+    segment.starts_with('_') && !(segment.starts_with("__") && segment.ends_with("__"))
+}
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::tree_sitter::Lang;
+    use crate::api::tree_sitter::py::{Python, signatures};
+
+    fn sigs(source: &str) -> Vec<Signature> {
+        let tree = Python.get_parser().parse(source, None).unwrap();
+        signatures(&tree, source)
+    }
+
+    fn drift(stub: &str, implementation: &str) -> Vec<(String, DriftKind)> {
+        compare("m.pyi", &sigs(stub), "m.py", &sigs(implementation))
+            .into_iter()
+            .map(|d| (d.function, d.kind))
+            .collect()
+    }
+
+    #[test]
+    fn matching_signatures_have_no_drift() {
+        let stub =
+            "def f(a: int, b: int = ...) -> int: ...\nclass C:\n    def m(self) -> None: ...\n";
+        let src = "def f(a, b=2):\n    return a\nclass C:\n    def m(self):\n        pass\n";
+        assert!(drift(stub, src).is_empty());
+    }
+
+    #[test]
+    fn missing_on_either_side() {
+        let stub = "def f() -> None: ...\ndef gone() -> None: ...\n";
+        let src = "def f(): pass\ndef new(): pass\ndef _helper(): pass\n\
+                   class _Private:\n    def run(self): pass\n";
+        assert_eq!(
+            drift(stub, src),
+            [
+                ("gone".into(), DriftKind::MissingInImplementation),
+                ("new".into(), DriftKind::MissingInStub),
+            ]
+        );
+        let d = compare("m.pyi", &sigs(stub), "m.py", &sigs(src));
+        assert_eq!((d[0].file(), d[1].file()), ("m.pyi", "m.py"));
+    }
+
+    #[test]
+    fn differing_arity() {
+        let d = compare(
+            "m.pyi",
+            &sigs("class C:\n    def m(self, x: int) -> None: ...\n"),
+            "m.py",
+            &sigs("class C:\n    def m(self, x, y=0, *rest): pass\n"),
+        );
+        assert_eq!(d.len(), 1);
+        assert_eq!(d[0].kind, DriftKind::Arity);
+        assert_eq!(
+            d[0].message(),
+            "`C.m` takes 2 argument(s) in the stub, 2+ in the implementation"
+        );
+        assert_eq!(d[0].span.start_line, 2);
+    }
+
+    #[test]
+    fn overloads_union_their_arities() {
+        let stub =
+            "@overload\ndef f(a: int) -> int: ...\n@overload\ndef f(a: str, b: str) -> str: ...\n";
+        assert!(drift(stub, "def f(a, b=None): pass\n").is_empty());
+        assert_eq!(
+            drift(stub, "def f(a): pass\n"),
+            [("f".into(), DriftKind::Arity)]
+        );
+    }
+}
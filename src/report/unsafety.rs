@@ -0,0 +1,99 @@
+use crate::api::tree_sitter::{ASTNode, Span, Syntax, UnsafeKind};
+use serde::Serialize;
+
+/// One `unsafe` site with its location and enclosing function.
+#[derive(Debug, Clone, Serialize)]
+pub struct UnsafeSite {
+    pub file: String,
+    pub kind: UnsafeKind,
+    /// Nearest enclosing function; for `unsafe fn` this is the function itself.
+    pub function: Option<String>,
+    pub span: Span,
+}
+
+/// Collect every `unsafe` site across cleaned per-file results, in file order.
+pub fn collect(files: &[Vec<Syntax>]) -> Vec<UnsafeSite> {
+    let mut out = Vec::new();
+    super::each_node(files, |file, function, s| {
+        if let ASTNode::Unsafe(u) = &s.node {
+            out.push(UnsafeSite {
+                file: file.to_string(),
+                kind: u.kind,
+                function: function.map(str::to_string),
+                span: s.metadata.span,
+            });
+        }
+    });
+    out
+}
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::tree_sitter::analyze;
+    use crate::api::tree_sitter::rs::Rust;
+
+    #[test]
+    fn no_unsafe_no_sites() {
+        let files = vec![analyze(&Rust, "a.rs", "fn f() { g() }")];
+        assert!(collect(&files).is_empty());
+    }
+
+    #[test]
+    fn block_reports_enclosing_function() {
+        let src = "fn outer() {\n    unsafe { ptr.read() }\n}";
+        let sites = collect(&[analyze(&Rust, "a.rs", src)]);
+        assert_eq!(sites.len(), 1);
+        assert_eq!(sites[0].file, "a.rs");
+        assert_eq!(sites[0].kind, UnsafeKind::Block);
+        assert_eq!(sites[0].function.as_deref(), Some("outer"));
+        assert_eq!(sites[0].span.start_line, 2);
+    }
+
+    #[test]
+    fn unsafe_fn_is_its_own_function() {
+        let sites = collect(&[analyze(&Rust, "a.rs", "unsafe fn raw() {}")]);
+        assert_eq!(sites[0].kind, UnsafeKind::Fn);
+        assert_eq!(sites[0].function.as_deref(), Some("raw"));
+    }
+
+    #[test]
+    fn impl_has_no_function() {
+        let sites = collect(&[analyze(&Rust, "a.rs", "unsafe impl Send for X {}")]);
+        assert_eq!(sites[0].kind, UnsafeKind::Impl);
+        assert!(sites[0].function.is_none());
+    }
+
+    #[test]
+    fn method_inside_impl() {
+        let src = "impl X { fn get(&self) { unsafe { f() } } }";
+        let sites = collect(&[analyze(&Rust, "a.rs", src)]);
+        assert_eq!(sites[0].function.as_deref(), Some("get"));
+    }
+
+    #[test]
+    fn inside_inline_module() {
+        let src = "mod inner {\n    pub unsafe fn f() {}\n    fn g() { unsafe {} }\n}";
+        let sites = collect(&[analyze(&Rust, "a.rs", src)]);
+        let kinds: Vec<_> = sites
+            .iter()
+            .map(|s| (s.kind, s.function.as_deref()))
+            .collect();
+        assert_eq!(
+            kinds,
+            [(UnsafeKind::Fn, Some("f")), (UnsafeKind::Block, Some("g"))]
+        );
+    }
+
+    #[test]
+    fn sites_across_files_in_order() {
+        let files = vec![
+            analyze(&Rust, "a.rs", "unsafe fn a() {}"),
+            analyze(&Rust, "b.rs", "fn b() { unsafe {} }"),
+        ];
+        let files: Vec<_> = collect(&files).into_iter().map(|s| s.file).collect();
+        assert_eq!(files, vec!["a.rs", "b.rs"]);
+    }
+}
@@ -0,0 +1,149 @@
+use crate::api::tree_sitter::{ASTNode, Syntax};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Verbs that name the same action; a codebase using several of one group
+/// is worth a look.
+const SYNONYMS: &[&[&str]] = &[
+    &["fetch", "get", "load", "read", "retrieve"],
+    &["build", "create", "make", "new"],
+    &["delete", "destroy", "drop", "remove"],
+    &["modify", "set", "update"],
+    &["save", "store", "write", "persist"],
+    &["find", "lookup", "search"],
+    &["check", "validate", "verify"],
+    &["init", "initialize", "setup"],
+    &["parse", "decode"],
+    &["emit", "publish", "send"],
+];
+
+/// Identifier words across definitions and calls.
+#[derive(Debug, Clone, Serialize)]
+pub struct Vocabulary {
+    /// Most used first, then alphabetically.
+    pub words: Vec<Word>,
+    /// Synonym groups with more than one member in use.
+    pub synonyms: Vec<Vec<Word>>,
+}
+
+/// How often one word appears in the names of each kind of node.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Word {
+    pub word: String,
+    pub total: usize,
+    pub functions: usize,
+    pub types: usize,
+    pub calls: usize,
+}
+
+/// Count the words in every Function, Type and Call name. Calls count their
+/// last path segment, so `os.path.join` adds `join`.
+pub fn collect(files: &[Vec<Syntax>]) -> Vocabulary {
+    let mut words: BTreeMap<String, Word> = BTreeMap::new();
+    super::each_node(files, |_, _, s| {
+        let (name, kind): (&str, fn(&mut Word) -> &mut usize) = match &s.node {
+            ASTNode::Function(f) => (&f.name, |w| &mut w.functions),
+            ASTNode::Type(t) => (&t.name, |w| &mut w.types),
+            ASTNode::Call(c) => (last_segment(&c.name), |w| &mut w.calls),
+            _ => return,
+        };
+        for word in split(name) {
+            let w = words.entry(word.clone()).or_insert_with(|| Word {
+                word,
+                ..Default::default()
+            });
+            w.total += 1;
+            *kind(w) += 1;
+        }
+    });
+    let synonyms = SYNONYMS
+        .iter()
+        .map(|group| {
+            let mut used: Vec<Word> = group
+                .iter()
+                .filter_map(|g| words.get(*g).cloned())
+                .collect();
+            used.sort_by_key(|w| std::cmp::Reverse(w.total));
+            used
+        })
+        .filter(|used| used.len() > 1)
+        .collect();
+    let mut words: Vec<Word> = words.into_values().collect();
+    words.sort_by_key(|w| std::cmp::Reverse(w.total));
+    Vocabulary { words, synonyms }
+}
+
+fn last_segment(name: &str) -> &str {
+    name.rsplit(['.', ':', '/']).next().unwrap_or(name)
+}
+
+/// Lowercased words of a snake_case, kebab-case, camelCase or PascalCase
+/// name; an acronym stays whole (`HTTPServer` is `http`, `server`).
+/// Single letters and numbers are dropped.
+fn split(name: &str) -> Vec<String> {
+    let chars: Vec<char> = name.chars().collect();
+    let mut out = Vec::new();
+    let mut word = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            out.push(std::mem::take(&mut word));
+            continue;
+        }
+        let prev = i.checked_sub(1).map(|j| chars[j]);
+        let next = chars.get(i + 1);
+        let boundary = c.is_uppercase()
+            && prev.is_some_and(|p| {
+                p.is_lowercase()
+                    || p.is_ascii_digit()
+                    || (p.is_uppercase() && next.is_some_and(|n| n.is_lowercase()))
+            });
+        if boundary {
+            out.push(std::mem::take(&mut word));
+        }
+        word.extend(c.to_lowercase());
+    }
+    out.push(word);
+    out.retain(|w| w.chars().count() > 1 && !w.chars().all(|c| c.is_ascii_digit()));
+    out
+}
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::tree_sitter::analyze;
+    use crate::api::tree_sitter::py::Python;
+
+    #[test]
+    fn split_names() {
+        assert_eq!(split("get_user_id"), ["get", "user", "id"]);
+        assert_eq!(split("fetchUser"), ["fetch", "user"]);
+        assert_eq!(split("HTTPServer"), ["http", "server"]);
+        assert_eq!(split("__init__"), ["init"]);
+        assert_eq!(split("parse2"), ["parse2"]);
+        assert_eq!(split("x_1"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn words_counted_by_kind() {
+        let src = "class UserStore:\n    def load_user(self):\n        db.load(1)\n";
+        let vocab = collect(&[analyze(&Python, "a.py", src)]);
+        let user = vocab.words.iter().find(|w| w.word == "user").unwrap();
+        assert_eq!((user.total, user.functions, user.types), (2, 1, 1));
+        assert_eq!(vocab.words[0].word, "load");
+        assert_eq!(vocab.words[0].calls, 1);
+    }
+
+    #[test]
+    fn synonyms_in_use_grouped() {
+        let src = "def fetch_a():\n    get_b()\n    get_c()\n\ndef remove_d():\n    pass\n";
+        let vocab = collect(&[analyze(&Python, "a.py", src)]);
+        let groups: Vec<Vec<(&str, usize)>> = vocab
+            .synonyms
+            .iter()
+            .map(|g| g.iter().map(|w| (w.word.as_str(), w.total)).collect())
+            .collect();
+        assert_eq!(groups, [vec![("get", 2), ("fetch", 1)]]);
+    }
+}
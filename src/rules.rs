@@ -0,0 +1,465 @@
+use crate::api::tree_sitter::{ASTNode, Span, Syntax};
+use crate::graph::{Def, Graph};
+use crate::report::stats::Coverage;
+use crate::report::{calls, each_node};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// Checks from the `[rules]` table in `bolo.toml`.
+#[derive(Debug, Default, Deserialize)]
+pub struct Rules {
+    /// Call patterns that must not appear, matched like watchlist entries.
+    #[serde(default)]
+    pub forbid: Vec<String>,
+    /// Architecture layers, from `[[rules.layers]]`. A file belongs to the
+    /// first layer with a matching path glob; files in no layer are free.
+    #[serde(default)]
+    pub layers: Vec<Layer>,
+    /// The least share of public functions and types each file documents,
+    /// 0 to 1, as `bolo stats` reports it.
+    #[serde(default)]
+    pub min_doc_coverage: Option<f64>,
+    /// The least comment lines per code line each file has, 0 to 1.
+    #[serde(default)]
+    pub min_comment_ratio: Option<f64>,
+}
+
+/// A set of files that may only depend on itself and the layers it names.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Layer {
+    pub name: String,
+    /// Path globs over File paths: `*` stays within a directory, `**` spans
+    /// any number, and `dir/` is everything under `dir`.
+    pub paths: Vec<String>,
+    /// Layers this one may call into.
+    #[serde(default)]
+    pub may_use: Vec<String>,
+}
+
+impl Rules {
+    pub fn is_empty(&self) -> bool {
+        self.forbid.is_empty()
+            && self.layers.is_empty()
+            && self.min_doc_coverage.is_none()
+            && self.min_comment_ratio.is_none()
+    }
+
+    /// Reject layer names that are repeated or that `may_use` doesn't know,
+    /// and thresholds outside 0 to 1.
+    pub fn validate(&self) -> Result<(), String> {
+        let thresholds = [
+            ("min_doc_coverage", self.min_doc_coverage),
+            ("min_comment_ratio", self.min_comment_ratio),
+        ];
+        for (key, min) in thresholds {
+            if let Some(min) = min.filter(|m| !(0.0..=1.0).contains(m)) {
+                return Err(format!("`{key}` must be between 0 and 1, not {min}"));
+            }
+        }
+        let mut names = BTreeSet::new();
+        for layer in &self.layers {
+            if !names.insert(layer.name.as_str()) {
+                return Err(format!("layer `{}` is declared twice", layer.name));
+            }
+        }
+        for layer in &self.layers {
+            if let Some(unknown) = layer.may_use.iter().find(|u| !names.contains(u.as_str())) {
+                return Err(format!(
+                    "layer `{}` may use unknown layer `{unknown}`",
+                    layer.name
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// The layer `file` belongs to, if any.
+    fn layer_of(&self, file: &str) -> Option<&Layer> {
+        self.layers
+            .iter()
+            .find(|l| l.paths.iter().any(|p| path_glob(file, p)))
+    }
+}
+
+/// One place where the analyzed code breaks a rule.
+#[derive(Debug, Clone, Serialize)]
+pub struct Violation {
+    /// The rule's key in `[rules]`, e.g. `forbid`.
+    pub rule: &'static str,
+    pub file: String,
+    pub function: Option<String>,
+    pub span: Span,
+    pub message: String,
+    /// The other end of a dependency that breaks a rule, e.g. the callee
+    /// of a `layers` violation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<Location>,
+}
+
+/// A definition's place in the analyzed code.
+#[derive(Debug, Clone, Serialize)]
+pub struct Location {
+    pub file: String,
+    pub function: Option<String>,
+    pub span: Span,
+}
+
+impl Location {
+    fn of(def: &Def) -> Self {
+        Location {
+            file: def.file.to_string(),
+            function: def.id.split_once("::").map(|(_, name)| name.to_string()),
+            span: def.syntax.metadata.span,
+        }
+    }
+}
+
+/// Every violation across cleaned per-file results: calls in file order,
+/// then layer violations by file pair, then files under a coverage
+/// threshold.
+pub fn check<'a>(files: &'a [Vec<Syntax<'a>>], rules: &Rules) -> Vec<Violation> {
+    let mut out = forbidden(files, rules);
+    if !rules.layers.is_empty() {
+        out.extend(layers(&Graph::build(files), rules));
+    }
+    out.extend(coverage(files, rules));
+    out
+}
+
+/// One violation per file documenting too few definitions or commenting
+/// too few lines, located at the whole file.
+fn coverage(files: &[Vec<Syntax>], rules: &Rules) -> Vec<Violation> {
+    if rules.min_doc_coverage.is_none() && rules.min_comment_ratio.is_none() {
+        return vec![];
+    }
+    let mut out = Vec::new();
+    for s in files.iter().filter_map(|nodes| nodes.first()) {
+        let ASTNode::File(file) = &s.node else {
+            continue;
+        };
+        let c = Coverage::of(&file.path, &s.metadata);
+        let mut push = |rule, message| {
+            out.push(Violation {
+                rule,
+                file: c.path.clone(),
+                function: None,
+                span: s.metadata.span,
+                message,
+                target: None,
+            })
+        };
+        if let Some(min) = rules.min_doc_coverage.filter(|&m| c.doc_coverage < m) {
+            push(
+                "min_doc_coverage",
+                format!(
+                    "{} of {} public definitions documented ({}), below {min}",
+                    c.documented, c.definitions, c.doc_coverage
+                ),
+            );
+        }
+        if let Some(min) = rules.min_comment_ratio.filter(|&m| c.comment_ratio < m) {
+            push(
+                "min_comment_ratio",
+                format!(
+                    "{} comment lines for {} code lines ({}), below {min}",
+                    c.comment_lines, c.code_lines, c.comment_ratio
+                ),
+            );
+        }
+    }
+    out
+}
+
+fn forbidden(files: &[Vec<Syntax>], rules: &Rules) -> Vec<Violation> {
+    let mut out = Vec::new();
+    each_node(files, |file, function, s| {
+        let ASTNode::Call(call) = &s.node else {
+            return;
+        };
+        if let Some(pattern) = rules.forbid.iter().find(|p| calls::matches(&call.name, p)) {
+            out.push(Violation {
+                rule: "forbid",
+                file: file.to_string(),
+                function: function.map(str::to_string),
+                span: s.metadata.span,
+                message: format!("call to `{}` matches forbidden `{pattern}`", call.name),
+                target: None,
+            });
+        }
+    });
+    out
+}
+
+/// One violation per file-level dependency between layers that `may_use`
+/// doesn't allow, located at the first call edge behind it.
+fn layers(graph: &Graph, rules: &Rules) -> Vec<Violation> {
+    let mut seen = BTreeSet::new();
+    let mut out = Vec::new();
+    for &(a, b) in &graph.edges {
+        let (caller, callee) = (&graph.defs[a], &graph.defs[b]);
+        let (Some(from), Some(to)) = (rules.layer_of(caller.file), rules.layer_of(callee.file))
+        else {
+            continue;
+        };
+        if from.name == to.name || from.may_use.contains(&to.name) {
+            continue;
+        }
+        if !seen.insert((caller.file, callee.file)) {
+            continue;
+        }
+        let here = Location::of(caller);
+        let there = Location::of(callee);
+        out.push(Violation {
+            rule: "layers",
+            message: format!(
+                "layer `{}` must not depend on `{}`: {} calls {} ({}:{})",
+                from.name,
+                to.name,
+                here.function.as_deref().unwrap_or(caller.name),
+                there.function.as_deref().unwrap_or(callee.name),
+                there.file,
+                there.span.start_line
+            ),
+            file: here.file,
+            function: here.function,
+            span: here.span,
+            target: Some(there),
+        });
+    }
+    out.sort_by(|x, y| {
+        let key = |v: &Violation| (v.file.clone(), v.target.as_ref().map(|t| t.file.clone()));
+        key(x).cmp(&key(y))
+    });
+    out
+}
+
+/// Match a `/`-separated `path` against `pattern`, segment by segment:
+/// `**` matches any number of segments and `*` any run within one. A
+/// trailing `/` means everything under the directory, like `dir/**`.
+pub(crate) fn path_glob(path: &str, pattern: &str) -> bool {
+    fn go(path: &[&str], pattern: &[&str]) -> bool {
+        match pattern.split_first() {
+            None => path.is_empty(),
+            Some((&"**", rest)) => (0..=path.len()).any(|i| go(&path[i..], rest)),
+            Some((seg, rest)) => match path.split_first() {
+                Some((name, tail)) => calls::glob(name, seg) && go(tail, rest),
+                None => false,
+            },
+        }
+    }
+    let path: Vec<&str> = path.split('/').collect();
+    let mut pattern: Vec<&str> = pattern.split('/').collect();
+    if pattern.last() == Some(&"") {
+        *pattern.last_mut().unwrap() = "**";
+    }
+    go(&path, &pattern)
+}
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::tree_sitter::analyze;
+    use crate::api::tree_sitter::py::Python;
+
+    #[test]
+    fn no_rules_no_violations() {
+        let files = [analyze(&Python, "a.py", "def f():\n    eval(x)\n")];
+        assert!(check(&files, &Rules::default()).is_empty());
+    }
+
+    #[test]
+    fn forbidden_call_reported() {
+        let rules = Rules {
+            forbid: vec!["eval".into(), "os.system".into()],
+            ..Default::default()
+        };
+        let files = [analyze(
+            &Python,
+            "a.py",
+            "def f():\n    eval(x)\n    print(x)\n",
+        )];
+        let v = check(&files, &rules);
+        assert_eq!(v.len(), 1);
+        assert_eq!(v[0].rule, "forbid");
+        assert_eq!(v[0].file, "a.py");
+        assert_eq!(v[0].function.as_deref(), Some("f"));
+        assert_eq!(v[0].span.start_line, 2);
+        assert!(v[0].message.contains("`eval`"));
+    }
+
+    #[test]
+    fn forbid_uses_watchlist_matching() {
+        let rules = Rules {
+            forbid: vec!["subprocess.*".into()],
+            ..Default::default()
+        };
+        let files = [analyze(&Python, "a.py", "subprocess.run(x)\nrun(x)\n")];
+        assert_eq!(check(&files, &rules).len(), 1);
+    }
+
+    // ── Layers ──
+
+    fn layer(name: &str, paths: &[&str], may_use: &[&str]) -> Layer {
+        Layer {
+            name: name.into(),
+            paths: paths.iter().map(|p| p.to_string()).collect(),
+            may_use: may_use.iter().map(|u| u.to_string()).collect(),
+        }
+    }
+
+    fn layered() -> Rules {
+        Rules {
+            layers: vec![
+                layer("domain", &["domain/**"], &[]),
+                layer("adapters", &["adapters/**"], &["domain"]),
+                layer("ui", &["ui/**"], &["adapters", "domain"]),
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn path_globs() {
+        assert!(path_glob("domain/user.py", "domain/**"));
+        assert!(path_glob("domain/a/b/user.py", "domain/**"));
+        assert!(path_glob("src/domain/user.py", "**/domain/**"));
+        assert!(path_glob("ui/view.py", "ui/*.py"));
+        assert!(!path_glob("ui/a/view.py", "ui/*.py"));
+        assert!(!path_glob("domainx/user.py", "domain/**"));
+        assert!(path_glob("adapters/db.py", "adapters/"));
+        assert!(!path_glob("adapters/db.py", "adapters"));
+    }
+
+    #[test]
+    fn allowed_direction_passes() {
+        let files = [
+            analyze(
+                &Python,
+                "ui/view.py",
+                "def show():\n    load()\n    rule()\n",
+            ),
+            analyze(&Python, "adapters/db.py", "def load():\n    rule()\n"),
+            analyze(&Python, "domain/user.py", "def rule(): pass\n"),
+        ];
+        assert!(check(&files, &layered()).is_empty());
+    }
+
+    #[test]
+    fn reverse_dependency_reported_with_both_ends() {
+        let files = [
+            analyze(
+                &Python,
+                "domain/user.py",
+                "def rule():\n    load()\n    load()\n",
+            ),
+            analyze(&Python, "adapters/db.py", "\n\ndef load(): pass\n"),
+        ];
+        let v = check(&files, &layered());
+        assert_eq!(v.len(), 1);
+        assert_eq!(v[0].rule, "layers");
+        assert_eq!(v[0].file, "domain/user.py");
+        assert_eq!(v[0].function.as_deref(), Some("rule"));
+        assert_eq!(v[0].span.start_line, 1);
+        let target = v[0].target.as_ref().unwrap();
+        assert_eq!(target.file, "adapters/db.py");
+        assert_eq!(target.function.as_deref(), Some("load"));
+        assert_eq!(target.span.start_line, 3);
+        assert!(
+            v[0].message
+                .contains("`domain` must not depend on `adapters`")
+        );
+    }
+
+    #[test]
+    fn one_violation_per_file_pair() {
+        let files = [
+            analyze(
+                &Python,
+                "domain/user.py",
+                "def a():\n    load()\ndef b():\n    save()\n",
+            ),
+            analyze(
+                &Python,
+                "ui/view.py",
+                "def load(): pass\ndef save(): pass\n",
+            ),
+        ];
+        assert_eq!(check(&files, &layered()).len(), 1);
+    }
+
+    #[test]
+    fn unlayered_files_unconstrained() {
+        let files = [
+            analyze(&Python, "domain/user.py", "def rule():\n    log()\n"),
+            analyze(&Python, "util.py", "def log():\n    rule()\n"),
+        ];
+        assert!(check(&files, &layered()).is_empty());
+    }
+
+    #[test]
+    fn first_matching_layer_wins() {
+        let rules = Rules {
+            layers: vec![
+                layer("core", &["domain/core/**"], &[]),
+                layer("domain", &["domain/**"], &["core"]),
+            ],
+            ..Default::default()
+        };
+        let files = [
+            analyze(&Python, "domain/core/id.py", "def make():\n    rule()\n"),
+            analyze(&Python, "domain/user.py", "def rule(): pass\n"),
+        ];
+        let v = check(&files, &rules);
+        assert_eq!(v.len(), 1);
+        assert_eq!(v[0].file, "domain/core/id.py");
+    }
+
+    #[test]
+    fn validate_layer_names() {
+        assert!(layered().validate().is_ok());
+        let mut rules = layered();
+        rules.layers.push(layer("ui", &["web/**"], &[]));
+        assert!(rules.validate().unwrap_err().contains("declared twice"));
+    }
+
+    // ── Coverage ──
+
+    #[test]
+    fn coverage_thresholds_per_file() {
+        let rules = Rules {
+            min_doc_coverage: Some(0.5),
+            min_comment_ratio: Some(0.25),
+            ..Default::default()
+        };
+        let files = [
+            analyze(&Python, "a.py", "# Loads.\ndef load():\n    pass\n"),
+            analyze(
+                &Python,
+                "b.py",
+                "def f():\n    pass\n\ndef g():\n    pass\n",
+            ),
+        ];
+        let v = check(&files, &rules);
+        let summary: Vec<_> = v.iter().map(|v| (v.rule, v.file.as_str())).collect();
+        assert_eq!(
+            summary,
+            [("min_doc_coverage", "b.py"), ("min_comment_ratio", "b.py")]
+        );
+        assert_eq!(v[0].span.start_line, 1);
+        assert!(v[0].message.starts_with("0 of 2 public definitions"));
+    }
+
+    #[test]
+    fn validate_thresholds() {
+        let mut rules = Rules {
+            min_doc_coverage: Some(0.8),
+            ..Default::default()
+        };
+        assert!(rules.validate().is_ok());
+        assert!(!rules.is_empty());
+        rules.min_comment_ratio = Some(20.0);
+        assert!(rules.validate().unwrap_err().contains("min_comment_ratio"));
+    }
+}
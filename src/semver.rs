@@ -0,0 +1,473 @@
+use crate::api::tree_sitter::{ASTNode, Function, Syntax};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+/// Path components whose files are never part of a library's API.
+const NON_API_DIRS: &[&str] = &["tests", "test", "benches", "examples"];
+
+/// The version component a release has to bump.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Bump {
+    Patch,
+    Minor,
+    Major,
+}
+
+impl fmt::Display for Bump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Bump::Patch => "patch",
+            Bump::Minor => "minor",
+            Bump::Major => "major",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Delta {
+    Added,
+    Removed,
+    /// Same item, different parameters, return type, signature types or
+    /// variants.
+    Changed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ApiChange {
+    pub id: String,
+    pub kind: &'static str,
+    pub delta: Delta,
+    pub bump: Bump,
+}
+
+/// Suggested bump and the public API changes behind it.
+#[derive(Debug, Clone, Serialize)]
+pub struct Report {
+    pub bump: Bump,
+    pub changes: Vec<ApiChange>,
+}
+
+/// A public item: its kind and what a caller depends on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Item {
+    kind: &'static str,
+    /// Signature types for functions, variant names for types.
+    shape: BTreeSet<String>,
+    /// Parameters in order, then the return type, for functions.
+    signature: Vec<String>,
+}
+
+/// Compare the public API of two analyses.
+///
+/// Removing or changing a public item is major, adding one is minor, and
+/// anything else is a patch. Public means `pub` in Rust and no leading
+/// underscore in Python (on the item, its enclosing class, and the module).
+pub fn compare(old: &[Vec<Syntax>], new: &[Vec<Syntax>]) -> Report {
+    let (old, new) = (api(old), api(new));
+    let mut changes = Vec::new();
+    for (id, item) in &old {
+        match new.get(id) {
+            None => changes.push(change(id, item, Delta::Removed)),
+            Some(now) if now != item => changes.push(change(id, now, Delta::Changed)),
+            Some(_) => {}
+        }
+    }
+    for (id, item) in &new {
+        if !old.contains_key(id) {
+            changes.push(change(id, item, Delta::Added));
+        }
+    }
+    changes.sort_by(|a, b| b.bump.cmp(&a.bump).then_with(|| a.id.cmp(&b.id)));
+    Report {
+        bump: changes.iter().map(|c| c.bump).max().unwrap_or(Bump::Patch),
+        changes,
+    }
+}
+
+fn change(id: &str, item: &Item, delta: Delta) -> ApiChange {
+    ApiChange {
+        id: id.to_string(),
+        kind: item.kind,
+        delta,
+        bump: match delta {
+            Delta::Added => Bump::Minor,
+            Delta::Removed | Delta::Changed => Bump::Major,
+        },
+    }
+}
+
+/// Public items by id (`file::Qualified.name`, as in the call graph).
+fn api(files: &[Vec<Syntax>]) -> BTreeMap<String, Item> {
+    // Rust impl blocks carry no visibility; they count when their type does.
+    let public_types: BTreeSet<&str> = files
+        .iter()
+        .flat_map(|nodes| nodes.iter().flat_map(|s| &s.contains).chain(nodes))
+        .filter_map(|s| match &s.node {
+            ASTNode::Type(t) if t.public => Some(&*t.name),
+            _ => None,
+        })
+        .collect();
+
+    let mut out = BTreeMap::new();
+    for nodes in files {
+        let Some(ASTNode::File(file)) = nodes.first().map(|s| &s.node) else {
+            continue;
+        };
+        if is_api_file(&file.path) {
+            let sig = Signature {
+                rust: file.path.ends_with(".rs"),
+            };
+            collect(
+                &nodes[1..],
+                &format!("{}::", file.path),
+                &public_types,
+                sig,
+                &mut out,
+            );
+        }
+    }
+    out
+}
+
+/// How a file's function signatures are compared.
+#[derive(Clone, Copy)]
+struct Signature {
+    /// Rust callers pass arguments by position only, so parameter names and
+    /// defaults are not part of the API; Python callers may use keywords.
+    rust: bool,
+}
+
+impl Signature {
+    fn of(self, f: &Function) -> Vec<String> {
+        let mut out: Vec<String> = f
+            .params
+            .iter()
+            .map(|p| match (&p.annotation, self.rust) {
+                (Some(ty), true) => ty.to_string(),
+                (None, true) => p.name.to_string(),
+                (ty, false) => {
+                    let mut s = p.name.to_string();
+                    if let Some(ty) = ty {
+                        s += &format!(": {ty}");
+                    }
+                    if let Some(d) = &p.default {
+                        s += &format!(" = {d}");
+                    }
+                    s
+                }
+            })
+            .collect();
+        if let Some(ret) = &f.return_type {
+            out.push(format!("-> {ret}"));
+        }
+        out
+    }
+}
+
+fn collect(
+    nodes: &[Syntax],
+    prefix: &str,
+    public_types: &BTreeSet<&str>,
+    sig: Signature,
+    out: &mut BTreeMap<String, Item>,
+) {
+    for s in nodes {
+        match &s.node {
+            ASTNode::Function(f) if f.public => {
+                let shape = s
+                    .contains
+                    .iter()
+                    .filter_map(|c| match &c.node {
+                        ASTNode::UsesType(u) => Some(u.name.to_string()),
+                        _ => None,
+                    })
+                    .collect();
+                out.insert(
+                    format!("{prefix}{}", f.name),
+                    Item {
+                        kind: "fn",
+                        shape,
+                        signature: sig.of(f),
+                    },
+                );
+            }
+            ASTNode::Type(t) if t.public => {
+                let shape = s
+                    .contains
+                    .iter()
+                    .filter_map(|c| match &c.node {
+                        ASTNode::Variant(v) => Some(v.name.to_string()),
+                        _ => None,
+                    })
+                    .collect();
+                out.insert(
+                    format!("{prefix}{}", t.name),
+                    Item {
+                        kind: "type",
+                        shape,
+                        signature: Vec::new(),
+                    },
+                );
+                collect(
+                    &s.contains,
+                    &format!("{prefix}{}.", t.name),
+                    public_types,
+                    sig,
+                    out,
+                );
+            }
+            // `impl Trait for Foo` is an API item of its own; its methods
+            // follow from the trait. `impl Foo` adds Foo's methods.
+            ASTNode::Type(t) if public_types.contains(self_type(&t.name)) => {
+                if t.name.contains(" for ") {
+                    out.insert(
+                        format!("{prefix}{}", t.name),
+                        Item {
+                            kind: "impl",
+                            shape: BTreeSet::new(),
+                            signature: Vec::new(),
+                        },
+                    );
+                } else {
+                    let owner = format!("{prefix}{}.", self_type(&t.name));
+                    collect(&s.contains, &owner, public_types, sig, out);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// `Foo` from an impl label like `Foo<T>`, `a::Foo` or `Display for Foo`.
+fn self_type(label: &str) -> &str {
+    let ty = label.rsplit(" for ").next().unwrap_or(label);
+    let ty = ty.split('<').next().unwrap_or(ty);
+    ty.rsplit("::").next().unwrap_or(ty).trim()
+}
+
+/// Tests, benches and examples are not API, nor are private Python modules
+/// (`_internal.py`, `_impl/`).
+fn is_api_file(path: &str) -> bool {
+    let parts: Vec<&str> = path.split('/').collect();
+    let (name, dirs) = parts.split_last().unwrap_or((&"", &[]));
+    if dirs.iter().any(|d| NON_API_DIRS.contains(d)) {
+        return false;
+    }
+    let Some(stem) = name.strip_suffix(".py") else {
+        return true;
+    };
+    let private = |p: &str| p.starts_with('_') && !(p.starts_with("__") && p.ends_with("__"));
+    !(stem.starts_with("test_")
+        || stem.ends_with("_test")
+        || stem == "conftest"
+        || private(stem)
+        || dirs.iter().any(|d| private(d)))
+}
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::tree_sitter::analyze;
+    use crate::api::tree_sitter::py::Python;
+    use crate::api::tree_sitter::rs::Rust;
+
+    fn ids(files: &[Vec<Syntax>]) -> Vec<String> {
+        api(files).into_keys().collect()
+    }
+
+    fn deltas(report: &Report) -> Vec<(&str, Delta)> {
+        report
+            .changes
+            .iter()
+            .map(|c| (c.id.as_str(), c.delta))
+            .collect()
+    }
+
+    // ── api ──
+
+    #[test]
+    fn rust_pub_items_only() {
+        let src = "pub fn a() {}\nfn b() {}\npub(crate) fn c() {}\npub struct S;\nstruct P;\n";
+        assert_eq!(
+            ids(&[analyze(&Rust, "lib.rs", src)]),
+            vec!["lib.rs::S", "lib.rs::a"]
+        );
+    }
+
+    #[test]
+    fn rust_inherent_impl_of_pub_type() {
+        let src = "pub struct S;\nimpl S {\n    pub fn new() {}\n    fn helper() {}\n}\n\
+                   struct P;\nimpl P {\n    pub fn hidden() {}\n}\n";
+        assert_eq!(
+            ids(&[analyze(&Rust, "lib.rs", src)]),
+            vec!["lib.rs::S", "lib.rs::S.new"]
+        );
+    }
+
+    #[test]
+    fn rust_trait_impl_is_an_item() {
+        let src = "pub struct S<T>(T);\nimpl<T> Clone for S<T> {\n    fn clone(&self) -> Self { todo!() }\n}\n";
+        assert_eq!(
+            ids(&[analyze(&Rust, "lib.rs", src)]),
+            vec!["lib.rs::Clone for S<T>", "lib.rs::S"]
+        );
+    }
+
+    #[test]
+    fn rust_trait_default_methods_public() {
+        let src = "pub trait T {\n    fn run(&self) {}\n}\n";
+        assert_eq!(
+            ids(&[analyze(&Rust, "lib.rs", src)]),
+            vec!["lib.rs::T", "lib.rs::T.run"]
+        );
+    }
+
+    #[test]
+    fn python_underscore_is_private() {
+        let src = "def a(): pass\ndef _b(): pass\nclass C:\n    def m(self): pass\n    def _p(self): pass\n    def __init__(self): pass\nclass _D:\n    def m(self): pass\n";
+        assert_eq!(
+            ids(&[analyze(&Python, "pkg/mod.py", src)]),
+            vec![
+                "pkg/mod.py::C",
+                "pkg/mod.py::C.__init__",
+                "pkg/mod.py::C.m",
+                "pkg/mod.py::a"
+            ]
+        );
+    }
+
+    #[test]
+    fn non_api_files_skipped() {
+        assert!(is_api_file("pkg/__init__.py"));
+        assert!(is_api_file("src/lib.rs"));
+        assert!(!is_api_file("pkg/_internal.py"));
+        assert!(!is_api_file("pkg/_impl/x.py"));
+        assert!(!is_api_file("pkg/test_x.py"));
+        assert!(!is_api_file("pkg/conftest.py"));
+        assert!(!is_api_file("tests/it.rs"));
+        assert!(!is_api_file("examples/demo.py"));
+    }
+
+    #[test]
+    fn self_type_strips_trait_generics_and_path() {
+        assert_eq!(self_type("Foo"), "Foo");
+        assert_eq!(self_type("Foo<T>"), "Foo");
+        assert_eq!(self_type("fmt::Display for a::Foo<T>"), "Foo");
+    }
+
+    // ── compare ──
+
+    #[test]
+    fn unchanged_is_patch() {
+        let files = [analyze(&Rust, "lib.rs", "pub fn a() {}\nfn b() {}\n")];
+        let after = [analyze(&Rust, "lib.rs", "pub fn a() {}\nfn b() { a(); }\n")];
+        let report = compare(&files, &after);
+        assert_eq!(report.bump, Bump::Patch);
+        assert!(report.changes.is_empty());
+    }
+
+    #[test]
+    fn addition_is_minor() {
+        let report = compare(
+            &[analyze(&Rust, "lib.rs", "pub fn a() {}\n")],
+            &[analyze(&Rust, "lib.rs", "pub fn a() {}\npub fn b() {}\n")],
+        );
+        assert_eq!(report.bump, Bump::Minor);
+        assert_eq!(deltas(&report), vec![("lib.rs::b", Delta::Added)]);
+    }
+
+    #[test]
+    fn removal_is_major() {
+        let report = compare(
+            &[analyze(&Rust, "lib.rs", "pub fn a() {}\npub fn b() {}\n")],
+            &[analyze(&Rust, "lib.rs", "pub fn a() {}\nfn b() {}\n")],
+        );
+        assert_eq!(report.bump, Bump::Major);
+        assert_eq!(deltas(&report), vec![("lib.rs::b", Delta::Removed)]);
+    }
+
+    #[test]
+    fn signature_change_is_major() {
+        let report = compare(
+            &[analyze(
+                &Rust,
+                "lib.rs",
+                "pub struct A;\npub struct B;\npub fn f(x: A) {}\n",
+            )],
+            &[analyze(
+                &Rust,
+                "lib.rs",
+                "pub struct A;\npub struct B;\npub fn f(x: B) {}\n",
+            )],
+        );
+        assert_eq!(report.bump, Bump::Major);
+        assert_eq!(deltas(&report), vec![("lib.rs::f", Delta::Changed)]);
+    }
+
+    #[test]
+    fn added_param_is_major() {
+        let report = compare(
+            &[analyze(&Rust, "lib.rs", "pub fn keep(a: u8) -> u8 { a }\n")],
+            &[analyze(
+                &Rust,
+                "lib.rs",
+                "pub fn keep(a: u8, b: u8) -> u8 { a + b }\n",
+            )],
+        );
+        assert_eq!(report.bump, Bump::Major);
+        assert_eq!(deltas(&report), vec![("lib.rs::keep", Delta::Changed)]);
+    }
+
+    #[test]
+    fn return_type_change_is_major() {
+        let report = compare(
+            &[analyze(&Rust, "lib.rs", "pub fn f() -> u8 { 0 }\n")],
+            &[analyze(&Rust, "lib.rs", "pub fn f() -> u16 { 0 }\n")],
+        );
+        assert_eq!(deltas(&report), vec![("lib.rs::f", Delta::Changed)]);
+    }
+
+    #[test]
+    fn rust_param_rename_is_patch() {
+        let report = compare(
+            &[analyze(&Rust, "lib.rs", "pub fn f(a: u8) {}\n")],
+            &[analyze(&Rust, "lib.rs", "pub fn f(b: u8) {}\n")],
+        );
+        assert!(report.changes.is_empty());
+    }
+
+    #[test]
+    fn python_keyword_rename_is_major() {
+        let report = compare(
+            &[analyze(&Python, "m.py", "def f(a, n=1): pass\n")],
+            &[analyze(&Python, "m.py", "def f(a, count=1): pass\n")],
+        );
+        assert_eq!(deltas(&report), vec![("m.py::f", Delta::Changed)]);
+    }
+
+    #[test]
+    fn new_variant_is_major() {
+        let report = compare(
+            &[analyze(&Rust, "lib.rs", "pub enum E { A }\n")],
+            &[analyze(&Rust, "lib.rs", "pub enum E { A, B }\n")],
+        );
+        assert_eq!(deltas(&report), vec![("lib.rs::E", Delta::Changed)]);
+    }
+
+    #[test]
+    fn major_changes_listed_first() {
+        let report = compare(
+            &[analyze(&Python, "m.py", "def a(): pass\n")],
+            &[analyze(&Python, "m.py", "def b(): pass\n")],
+        );
+        assert_eq!(
+            deltas(&report),
+            vec![("m.py::a", Delta::Removed), ("m.py::b", Delta::Added)]
+        );
+    }
+}
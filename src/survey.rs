@@ -0,0 +1,207 @@
+use crate::api::fs::{self, File};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::time::Duration;
+
+/// Rough single-thread parse + clean throughput, measured on this repo and
+/// CPython's stdlib; only used for `--dry-run` estimates.
+pub const BYTES_PER_SEC: u64 = 8 * 1024 * 1024;
+
+/// How many of the largest files `--dry-run` lists.
+const LARGEST: usize = 5;
+
+/// What a run would cover: counts and sizes, for `--dry-run`.
+#[derive(Debug, Default)]
+pub struct Survey {
+    pub files: usize,
+    pub bytes: u64,
+    /// Top-level directory (or `.` for files at the root) → (files, bytes).
+    pub by_dir: BTreeMap<String, (usize, u64)>,
+    /// Largest files first, as (portable path, bytes).
+    pub largest: Vec<(String, u64)>,
+}
+
+impl Survey {
+    pub fn new(files: &[File]) -> Self {
+        let mut s = Survey::default();
+        let mut sized = Vec::with_capacity(files.len());
+        for f in files {
+            let size = f.size();
+            let path = fs::portable(&f.rel_path, None);
+            let dir = match path.split_once('/') {
+                Some((top, _)) => top.to_string(),
+                None => ".".to_string(),
+            };
+            let entry = s.by_dir.entry(dir).or_default();
+            entry.0 += 1;
+            entry.1 += size;
+            s.files += 1;
+            s.bytes += size;
+            sized.push((path, size));
+        }
+        sized.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        sized.truncate(LARGEST);
+        s.largest = sized;
+        s
+    }
+
+    /// Estimated wall time on `threads` threads, from [`BYTES_PER_SEC`].
+    pub fn estimate(&self, threads: usize) -> Duration {
+        let rate = BYTES_PER_SEC * threads.max(1) as u64;
+        Duration::from_millis(self.bytes * 1000 / rate)
+    }
+
+    /// Human-readable summary for `ext` files on `threads` threads.
+    pub fn render(&self, ext: &str, threads: usize) -> String {
+        let mut out = format!(
+            "{} .{ext} files found ({})\n",
+            self.files,
+            human(self.bytes)
+        );
+        if !self.by_dir.is_empty() {
+            out.push_str("\nby directory:\n");
+            for (dir, (n, bytes)) in &self.by_dir {
+                let _ = writeln!(out, "  {dir:<24} {n:>6} files  {:>9}", human(*bytes));
+            }
+        }
+        if !self.largest.is_empty() {
+            out.push_str("\nlargest:\n");
+            for (path, bytes) in &self.largest {
+                let _ = writeln!(out, "  {:>9}  {path}", human(*bytes));
+            }
+        }
+        let est = self.estimate(threads).as_secs_f64();
+        let est = if est < 0.1 {
+            "<0.1s".to_string()
+        } else {
+            format!("~{est:.1}s")
+        };
+        let _ = write!(
+            out,
+            "\nestimated time: {est} on {threads} thread{}",
+            if threads == 1 { "" } else { "s" }
+        );
+        out
+    }
+}
+
+/// One file a run would analyze, for `--list-files`.
+#[derive(Debug, Clone, Serialize)]
+pub struct Entry {
+    /// Same form as the File node path.
+    pub path: String,
+    pub size: u64,
+    pub language: &'static str,
+}
+
+pub fn entries(files: &[File], language: &'static str, root_label: Option<&str>) -> Vec<Entry> {
+    files
+        .iter()
+        .map(|f| Entry {
+            path: fs::portable(&f.rel_path, root_label),
+            size: f.size(),
+            language,
+        })
+        .collect()
+}
+
+pub(crate) fn human(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{size:.1} {}", UNITS[unit])
+    }
+}
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn fixture() -> (TempDir, Vec<File>) {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("src/api")).unwrap();
+        std::fs::create_dir_all(dir.path().join("tests")).unwrap();
+        std::fs::write(dir.path().join("setup.py"), "x".repeat(10)).unwrap();
+        std::fs::write(dir.path().join("src/a.py"), "x".repeat(300)).unwrap();
+        std::fs::write(dir.path().join("src/api/b.py"), "x".repeat(200)).unwrap();
+        std::fs::write(dir.path().join("tests/t.py"), "x".repeat(50)).unwrap();
+        let files = fs::walk_dir(dir.path(), "py", false).unwrap();
+        (dir, files)
+    }
+
+    #[test]
+    fn totals_and_directories() {
+        let (_dir, files) = fixture();
+        let s = Survey::new(&files);
+        assert_eq!(s.files, 4);
+        assert_eq!(s.bytes, 560);
+        assert_eq!(s.by_dir["src"], (2, 500));
+        assert_eq!(s.by_dir["tests"], (1, 50));
+        assert_eq!(s.by_dir["."], (1, 10));
+    }
+
+    #[test]
+    fn largest_first() {
+        let (_dir, files) = fixture();
+        let s = Survey::new(&files);
+        assert_eq!(s.largest[0], ("src/a.py".to_string(), 300));
+        assert_eq!(s.largest[1], ("src/api/b.py".to_string(), 200));
+    }
+
+    #[test]
+    fn estimate_scales_with_threads() {
+        let s = Survey {
+            bytes: BYTES_PER_SEC * 4,
+            ..Survey::default()
+        };
+        assert_eq!(s.estimate(1), Duration::from_secs(4));
+        assert_eq!(s.estimate(4), Duration::from_secs(1));
+        assert_eq!(s.estimate(0), Duration::from_secs(4));
+    }
+
+    #[test]
+    fn render_summary() {
+        let (_dir, files) = fixture();
+        let text = Survey::new(&files).render("py", 2);
+        assert!(text.starts_with("4 .py files found (560 B)"));
+        assert!(text.contains("by directory:"));
+        assert!(text.contains("largest:"));
+        assert!(text.ends_with("on 2 threads"));
+    }
+
+    #[test]
+    fn entries_match_file_node_paths() {
+        let (_dir, files) = fixture();
+        let list = entries(&files, "python", Some("proj"));
+        let paths: Vec<_> = list.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "proj/setup.py",
+                "proj/src/a.py",
+                "proj/src/api/b.py",
+                "proj/tests/t.py"
+            ]
+        );
+        assert_eq!(list[1].size, 300);
+        assert_eq!(list[1].language, "python");
+    }
+
+    #[test]
+    fn human_sizes() {
+        assert_eq!(human(512), "512 B");
+        assert_eq!(human(2048), "2.0 KB");
+        assert_eq!(human(5 * 1024 * 1024), "5.0 MB");
+    }
+}
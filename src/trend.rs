@@ -0,0 +1,452 @@
+use crate::analysis::Analysis;
+use crate::badge;
+use crate::deps;
+use crate::error::BoloError;
+use crate::export::summary;
+use crate::graph::Graph;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use std::path::Path;
+
+/// Aggregate metrics of one analysis, keyed by the commit it was taken at.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Point {
+    pub commit: String,
+    pub files: usize,
+    pub functions: usize,
+    pub types: usize,
+    pub calls: usize,
+    pub warnings: usize,
+    pub cycles: usize,
+    /// Mean calls per function, as `bolo badge --metric complexity`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub complexity: Option<f64>,
+    /// Percent of functions reached from tests, as `bolo badge --metric coverage`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coverage: Option<usize>,
+}
+
+/// Names [`trend`] accepts, in the order it prints them.
+pub const METRICS: &[&str] = &[
+    "files",
+    "functions",
+    "types",
+    "calls",
+    "warnings",
+    "cycles",
+    "complexity",
+    "coverage",
+];
+
+impl Point {
+    pub fn new(commit: &str, analysis: &Analysis) -> Self {
+        let (mut functions, mut types, mut calls) = (0, 0, 0);
+        for nodes in &analysis.files {
+            summary::count(nodes, &mut functions, &mut types, &mut calls);
+        }
+        let own = deps::first_party(&analysis.files);
+        let graph = Graph::build(&own);
+        Point {
+            commit: commit.to_string(),
+            files: analysis.files.len(),
+            functions,
+            types,
+            calls,
+            warnings: analysis.warnings.len(),
+            cycles: graph.file_cycles().len(),
+            complexity: badge::complexity(&graph).map(|c| (c * 10.0).round() / 10.0),
+            coverage: badge::coverage(&graph),
+        }
+    }
+
+    /// The metric named `name` (one of [`METRICS`]), if recorded.
+    pub fn get(&self, name: &str) -> Option<f64> {
+        Some(match name {
+            "files" => self.files as f64,
+            "functions" => self.functions as f64,
+            "types" => self.types as f64,
+            "calls" => self.calls as f64,
+            "warnings" => self.warnings as f64,
+            "cycles" => self.cycles as f64,
+            "complexity" => self.complexity?,
+            "coverage" => self.coverage? as f64,
+            _ => return None,
+        })
+    }
+}
+
+/// Extensions of trend databases kept in SQLite; any other file is JSON
+/// Lines, one [`Point`] per line.
+const SQLITE: &[&str] = &["sqlite", "sqlite3", "db"];
+
+fn is_sqlite(db: &Path) -> bool {
+    db.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| SQLITE.contains(&e))
+}
+
+/// Read the points recorded in `db`, oldest first; a missing file has none.
+pub fn load(db: &Path) -> Result<Vec<Point>, BoloError> {
+    if is_sqlite(db) {
+        return sqlite::load(db);
+    }
+    let text = match std::fs::read_to_string(db) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => {
+            return Err(BoloError::Read {
+                path: db.to_path_buf(),
+                reason: e.to_string(),
+            });
+        }
+    };
+    text.lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty())
+        .map(|(i, line)| {
+            serde_json::from_str(line).map_err(|e| BoloError::Parse {
+                file: format!("{}:{}", db.display(), i + 1),
+                reason: e.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Add `point` to `db`. A commit recorded before is replaced in place, so
+/// re-running on the same commit doesn't duplicate it.
+pub fn record(db: &Path, point: Point) -> Result<(), BoloError> {
+    if is_sqlite(db) {
+        return sqlite::record(db, &point);
+    }
+    let mut points = load(db)?;
+    match points.iter_mut().find(|p| p.commit == point.commit) {
+        Some(old) => *old = point,
+        None => points.push(point),
+    }
+    let mut out = String::new();
+    for p in &points {
+        out.push_str(&serde_json::to_string(p)?);
+        out.push('\n');
+    }
+    crate::api::fs::write_file(db, &out, true)
+}
+
+/// A `points` table, one row per commit in the order first recorded.
+#[cfg(feature = "sqlite")]
+mod sqlite {
+    use super::Point;
+    use crate::error::BoloError;
+    use rusqlite::{Connection, OpenFlags, Row, params};
+    use std::path::Path;
+
+    const SCHEMA: &str = "CREATE TABLE IF NOT EXISTS points (
+        sha TEXT PRIMARY KEY,
+        files INTEGER NOT NULL,
+        functions INTEGER NOT NULL,
+        types INTEGER NOT NULL,
+        calls INTEGER NOT NULL,
+        warnings INTEGER NOT NULL,
+        cycles INTEGER NOT NULL,
+        complexity REAL,
+        coverage INTEGER
+    )";
+
+    pub fn load(db: &Path) -> Result<Vec<Point>, BoloError> {
+        if !db.exists() {
+            return Ok(Vec::new());
+        }
+        let read = |e: rusqlite::Error| BoloError::Read {
+            path: db.to_path_buf(),
+            reason: e.to_string(),
+        };
+        let conn =
+            Connection::open_with_flags(db, OpenFlags::SQLITE_OPEN_READ_ONLY).map_err(read)?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT sha, files, functions, types, calls, warnings, cycles, complexity, \
+                 coverage FROM points ORDER BY rowid",
+            )
+            .map_err(read)?;
+        let rows = stmt.query_map([], point).map_err(read)?;
+        rows.collect::<Result<_, _>>().map_err(read)
+    }
+
+    fn point(row: &Row) -> rusqlite::Result<Point> {
+        // SQLite integers are i64; counts are never negative.
+        let count = |i| row.get::<_, i64>(i).map(|n| n as usize);
+        Ok(Point {
+            commit: row.get(0)?,
+            files: count(1)?,
+            functions: count(2)?,
+            types: count(3)?,
+            calls: count(4)?,
+            warnings: count(5)?,
+            cycles: count(6)?,
+            complexity: row.get(7)?,
+            coverage: row.get::<_, Option<i64>>(8)?.map(|n| n as usize),
+        })
+    }
+
+    /// Upsert by commit, so a commit recorded again keeps its place.
+    pub fn record(db: &Path, p: &Point) -> Result<(), BoloError> {
+        let write = |e: rusqlite::Error| BoloError::Write {
+            path: db.to_path_buf(),
+            reason: e.to_string(),
+        };
+        let conn = Connection::open(db).map_err(write)?;
+        conn.execute(SCHEMA, []).map_err(write)?;
+        conn.execute(
+            "INSERT INTO points \
+             (sha, files, functions, types, calls, warnings, cycles, complexity, coverage) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9) \
+             ON CONFLICT(sha) DO UPDATE SET files = ?2, functions = ?3, types = ?4, \
+             calls = ?5, warnings = ?6, cycles = ?7, complexity = ?8, coverage = ?9",
+            params![
+                p.commit,
+                p.files as i64,
+                p.functions as i64,
+                p.types as i64,
+                p.calls as i64,
+                p.warnings as i64,
+                p.cycles as i64,
+                p.complexity,
+                p.coverage.map(|n| n as i64)
+            ],
+        )
+        .map_err(write)?;
+        Ok(())
+    }
+}
+
+/// Builds without the `sqlite` feature keep JSON Lines only.
+#[cfg(not(feature = "sqlite"))]
+mod sqlite {
+    use super::Point;
+    use crate::error::BoloError;
+    use std::path::Path;
+
+    const UNSUPPORTED: BoloError = BoloError::Unsupported {
+        flag: "db",
+        context: "with a SQLite file in a build without the `sqlite` feature",
+    };
+
+    pub fn load(_: &Path) -> Result<Vec<Point>, BoloError> {
+        Err(UNSUPPORTED)
+    }
+
+    pub fn record(_: &Path, _: &Point) -> Result<(), BoloError> {
+        Err(UNSUPPORTED)
+    }
+}
+
+/// One line per metric in `metrics` (all of [`METRICS`] if empty): first
+/// and last recorded value, and a sparkline over every point, oldest left.
+pub fn trend(points: &[Point], metrics: &[&str]) -> String {
+    let metrics = if metrics.is_empty() { METRICS } else { metrics };
+    let width = metrics.iter().map(|m| m.len()).max().unwrap_or(0);
+    let mut out = String::new();
+    let (Some(first), Some(last)) = (points.first(), points.last()) else {
+        out.push_str("no points recorded\n");
+        return out;
+    };
+    let _ = writeln!(
+        out,
+        "{} points, {} \u{2192} {}",
+        points.len(),
+        short(&first.commit),
+        short(&last.commit)
+    );
+    for m in metrics {
+        let values: Vec<Option<f64>> = points.iter().map(|p| p.get(m)).collect();
+        let known = || values.iter().flatten();
+        let (Some(from), Some(to)) = (known().next(), known().last()) else {
+            let _ = writeln!(out, "{m:<width$}  n/a");
+            continue;
+        };
+        let _ = writeln!(
+            out,
+            "{m:<width$}  {} \u{2192} {}  {}",
+            number(*from),
+            number(*to),
+            sparkline(&values)
+        );
+    }
+    out
+}
+
+/// Eight-level bars scaled between the smallest and largest value; a gap
+/// where a point has no value.
+fn sparkline(values: &[Option<f64>]) -> String {
+    const BARS: [char; 8] = [
+        '\u{2581}', '\u{2582}', '\u{2583}', '\u{2584}', '\u{2585}', '\u{2586}', '\u{2587}',
+        '\u{2588}',
+    ];
+    let known = values.iter().flatten();
+    let lo = known.clone().copied().fold(f64::INFINITY, f64::min);
+    let hi = known.copied().fold(f64::NEG_INFINITY, f64::max);
+    values
+        .iter()
+        .map(|v| match v {
+            None => ' ',
+            Some(_) if hi <= lo => BARS[0],
+            Some(v) => BARS[((v - lo) / (hi - lo) * 7.0).round() as usize],
+        })
+        .collect()
+}
+
+/// Whole numbers without a fraction, others to one decimal.
+fn number(v: f64) -> String {
+    if v.fract() == 0.0 {
+        format!("{v:.0}")
+    } else {
+        format!("{v:.1}")
+    }
+}
+
+fn short(commit: &str) -> &str {
+    commit.get(..7).unwrap_or(commit)
+}
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::tree_sitter::analyze;
+    use crate::api::tree_sitter::py::Python;
+    use tempfile::TempDir;
+
+    fn analysis(source: &str) -> Analysis {
+        let nodes = analyze(&Python, "a.py", source);
+        Analysis {
+            files: vec![nodes],
+            ..Default::default()
+        }
+    }
+
+    fn point(commit: &str, functions: usize, coverage: Option<usize>) -> Point {
+        Point {
+            commit: commit.to_string(),
+            files: 1,
+            functions,
+            types: 0,
+            calls: 0,
+            warnings: 0,
+            cycles: 0,
+            complexity: None,
+            coverage,
+        }
+    }
+
+    // ── Point ──
+
+    #[test]
+    fn aggregates_analysis() {
+        let p = Point::new(
+            "abc",
+            &analysis("class C: pass\n\ndef f():\n    g()\n\ndef g(): pass\n"),
+        );
+        assert_eq!(
+            (p.files, p.functions, p.types, p.calls, p.cycles),
+            (1, 2, 1, 1, 0)
+        );
+        assert_eq!(p.complexity, Some(0.5));
+        assert_eq!(p.coverage, Some(0));
+    }
+
+    // ── record ──
+
+    #[test]
+    fn record_appends_and_replaces_by_commit() {
+        let dir = TempDir::new().unwrap();
+        let db = dir.path().join("trends.jsonl");
+        assert!(load(&db).unwrap().is_empty());
+        record(&db, point("a", 1, None)).unwrap();
+        record(&db, point("b", 2, None)).unwrap();
+        record(&db, point("a", 3, None)).unwrap();
+        let points = load(&db).unwrap();
+        let got: Vec<_> = points
+            .iter()
+            .map(|p| (p.commit.as_str(), p.functions))
+            .collect();
+        assert_eq!(got, vec![("a", 3), ("b", 2)]);
+        assert_eq!(std::fs::read_to_string(&db).unwrap().lines().count(), 2);
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn sqlite_records_in_place() {
+        let dir = TempDir::new().unwrap();
+        let db = dir.path().join("trends.sqlite");
+        assert!(load(&db).unwrap().is_empty());
+        record(&db, point("a", 1, None)).unwrap();
+        record(&db, point("b", 2, Some(40))).unwrap();
+        record(&db, point("a", 3, None)).unwrap();
+        let mut c = point("c", 4, Some(50));
+        c.complexity = Some(2.5);
+        record(&db, c.clone()).unwrap();
+        let points = load(&db).unwrap();
+        assert_eq!(
+            points,
+            vec![point("a", 3, None), point("b", 2, Some(40)), c]
+        );
+        assert!(
+            std::fs::read(&db)
+                .unwrap()
+                .starts_with(b"SQLite format 3\0")
+        );
+    }
+
+    #[test]
+    fn sqlite_by_extension() {
+        assert!(is_sqlite(Path::new("trends.sqlite")));
+        assert!(is_sqlite(Path::new("ci/trends.db")));
+        assert!(!is_sqlite(Path::new("trends.jsonl")));
+        assert!(!is_sqlite(Path::new("sqlite")));
+    }
+
+    #[test]
+    fn bad_line_reported_with_number() {
+        let dir = TempDir::new().unwrap();
+        let db = dir.path().join("trends.jsonl");
+        std::fs::write(&db, "\n{oops}\n").unwrap();
+        match load(&db).unwrap_err() {
+            BoloError::Parse { file, .. } => assert!(file.ends_with("trends.jsonl:2")),
+            e => panic!("unexpected {e}"),
+        }
+    }
+
+    // ── trend ──
+
+    #[test]
+    fn trend_lines() {
+        let points = [
+            point("1111111aaaa", 1, None),
+            point("2222222bbbb", 5, Some(40)),
+            point("3333333cccc", 3, Some(60)),
+        ];
+        let out = trend(&points, &["functions", "coverage", "types"]);
+        let lines: Vec<_> = out.lines().collect();
+        assert_eq!(lines[0], "3 points, 1111111 \u{2192} 3333333");
+        assert_eq!(
+            lines[1],
+            "functions  1 \u{2192} 3  \u{2581}\u{2588}\u{2585}"
+        );
+        assert_eq!(lines[2], "coverage   40 \u{2192} 60   \u{2581}\u{2588}");
+        assert_eq!(
+            lines[3],
+            "types      0 \u{2192} 0  \u{2581}\u{2581}\u{2581}"
+        );
+    }
+
+    #[test]
+    fn empty_db() {
+        assert_eq!(trend(&[], &[]), "no points recorded\n");
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(number(3.0), "3");
+        assert_eq!(number(2.46), "2.5");
+    }
+}